@@ -0,0 +1,153 @@
+//! User-defined external actions shown in context menus
+//!
+//! Actions are `[[actions]]` tables in `config.toml`:
+//!
+//! ```toml
+//! [[actions]]
+//! context = "commit"
+//! label = "Open on GitHub"
+//! template = "https://github.com/org/repo/commit/{sha}"
+//!
+//! [[actions]]
+//! context = "file"
+//! label = "Open in VS Code"
+//! template = "code {abs_path}"
+//! ```
+//!
+//! Templates starting with `http://` or `https://` are opened in the browser; anything
+//! else runs through `sh -c` in the repository root. Placeholders are `{repo}` plus
+//! `{sha}`, `{short}`, `{subject}` for commits, `{path}`, `{abs_path}` for files and
+//! `{branch}` for branches. Values are shell-quoted in commands.
+
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionContext {
+    Commit,
+    File,
+    Branch,
+}
+
+#[derive(Clone, Debug)]
+pub struct ExternalAction {
+    pub context: ActionContext,
+    pub label: String,
+    pub template: String,
+}
+
+impl ExternalAction {
+    pub fn is_url(&self) -> bool {
+        let t = self.template.trim_start();
+        t.starts_with("http://") || t.starts_with("https://")
+    }
+
+    /// The template with placeholders replaced, quoting values for the shell
+    /// unless this is a URL action. One pass from left to right, so a value that
+    /// contains `{name}` itself is never expanded again.
+    pub fn expand(&self, vars: &[(&str, &str)]) -> String {
+        let quote = !self.is_url();
+        let mut rest = self.template.trim();
+        let mut out = String::with_capacity(rest.len());
+        while let Some(open) = rest.find('{') {
+            out.push_str(&rest[..open]);
+            rest = &rest[open..];
+            let value = rest.find('}').and_then(|close| {
+                let name = &rest[1..close];
+                let (_, value) = vars.iter().find(|(n, _)| *n == name)?;
+                Some((close, value))
+            });
+            match value {
+                Some((close, value)) => {
+                    if quote {
+                        out.push_str(&shell_quote(value));
+                    } else {
+                        out.push_str(value);
+                    }
+                    rest = &rest[close + 1..];
+                }
+                None => {
+                    out.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+/// Run an expanded action: open URLs in the browser, run anything else with `sh -c`.
+pub fn run(action: &ExternalAction, expanded: &str, cwd: &Path) -> Result<(), String> {
+    let out = if action.is_url() {
+        let opener = if cfg!(target_os = "macos") {
+            "open"
+        } else {
+            "xdg-open"
+        };
+        Command::new(opener).arg(expanded).current_dir(cwd).output()
+    } else {
+        Command::new("sh")
+            .arg("-c")
+            .arg(expanded)
+            .current_dir(cwd)
+            .output()
+    }
+    .map_err(|e| e.to_string())?;
+
+    if !out.status.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+        return Err(if stderr.is_empty() {
+            format!("{} exited with {}", action.label, out.status)
+        } else {
+            stderr
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(template: &str) -> ExternalAction {
+        ExternalAction {
+            context: ActionContext::Commit,
+            label: "test".to_string(),
+            template: template.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_expand_quotes_shell_values() {
+        let a = action("git show {sha} -- {path}");
+        assert_eq!(
+            a.expand(&[("sha", "abc"), ("path", "it's here")]),
+            "git show 'abc' -- 'it'\\''s here'"
+        );
+    }
+
+    #[test]
+    fn test_expand_never_rescans_values() {
+        let a = action("cat {path} {abs_path} {unknown}");
+        let path = "{abs_path}';touch pwned;'";
+        assert_eq!(
+            a.expand(&[("path", path), ("abs_path", "/repo/x")]),
+            r"cat '{abs_path}'\'';touch pwned;'\''' '/repo/x' {unknown}"
+        );
+    }
+
+    #[test]
+    fn test_expand_leaves_urls_unquoted() {
+        let a = action("https://example.com/commit/{sha}");
+        assert!(a.is_url());
+        assert_eq!(
+            a.expand(&[("sha", "abc")]),
+            "https://example.com/commit/abc"
+        );
+    }
+}
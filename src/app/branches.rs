@@ -0,0 +1,323 @@
+//! Branches: the branch picker and checking out, creating, renaming and deleting them
+
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BranchPickerMode {
+    Checkout,
+    LogView,
+}
+
+/// New name or upstream being typed for a branch in the branch manager
+pub(crate) struct BranchInput {
+    pub(crate) kind: BranchInputKind,
+    pub(crate) text: String,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) enum BranchInputKind {
+    /// Branch being renamed
+    Rename(String),
+    /// Branch whose upstream is being set; empty text removes it
+    Upstream(String),
+    /// Branch whose description is being edited; empty text removes it
+    Description(String),
+}
+
+impl App {
+    pub(crate) fn open_branch_picker(&mut self) {
+        self.branch_picker_mode = BranchPickerMode::Checkout;
+        self.open_branch_picker_internal();
+    }
+
+    pub(crate) fn open_log_branch_picker(&mut self) {
+        self.branch_picker_mode = BranchPickerMode::LogView;
+        self.open_branch_picker_internal();
+    }
+
+    pub(crate) fn open_branch_picker_internal(&mut self) {
+        self.context_menu = None;
+        self.commit.open = false;
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        match git_ops::list_branches(&repo_root) {
+            Ok(branches) => {
+                self.branch_ui.open = true;
+                self.author_ui.open = false;
+                self.branch_ui.query.clear();
+                self.branch_ui.status = None;
+                self.branch_ui.set_branches(branches);
+            }
+            Err(e) => {
+                self.set_status(e);
+            }
+        }
+    }
+
+    pub(crate) fn close_branch_picker(&mut self) {
+        self.branch_ui.open = false;
+        self.branch_ui.query.clear();
+        self.branch_ui.items.clear();
+        self.branch_ui.branches.clear();
+
+        self.branch_ui.status = None;
+        self.branch_ui.list_state.select(None);
+    }
+
+    pub(crate) fn confirm_log_branch_picker(&mut self) {
+        let Some(branch) = self.branch_ui.selected_branch() else {
+            self.set_status("No branch selected");
+            return;
+        };
+
+        if !branch.is_remote && branch.is_current {
+            self.log_ui.history_ref = None;
+        } else {
+            self.log_ui.history_ref = Some(branch.name);
+        }
+
+        self.refresh_log_data();
+        self.close_branch_picker();
+    }
+
+    pub(crate) fn branch_checkout_selected(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.branch_ui.status = Some("Not a git repository".to_string());
+            return;
+        };
+
+        let Some(branch) = self.branch_ui.selected_branch() else {
+            self.branch_ui.status = Some("No branch selected".to_string());
+            return;
+        };
+        let dirty = match git_ops::is_dirty(&repo_root) {
+            Ok(dirty) => dirty,
+            Err(e) => {
+                self.branch_ui.status = Some(e);
+                return;
+            }
+        };
+        let lines = if dirty {
+            vec![
+                "Working tree has changes.".to_string(),
+                String::new(),
+                format!("Checkout `{}` anyway?", branch.name),
+            ]
+        } else {
+            vec![format!("Checkout `{}`?", branch.name)]
+        };
+        self.request_confirm(
+            confirm::ConfirmKind::Checkout,
+            dirty,
+            confirm::ConfirmDialog {
+                title: if dirty {
+                    " Uncommitted Changes ".to_string()
+                } else {
+                    " Checkout ".to_string()
+                },
+                lines,
+                confirm_label: "Checkout".to_string(),
+                action: ConfirmAction::Checkout(branch),
+            },
+        );
+    }
+
+    pub(crate) fn checkout_branch(&mut self, branch: branch::BranchEntry) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.branch_ui.status = Some("Not a git repository".to_string());
+            return;
+        };
+        if let Some(path) = &branch.worktree {
+            self.branch_ui.status = Some(format!(
+                "{} is checked out in another worktree: {}",
+                branch.name, path
+            ));
+            return;
+        }
+        let name = branch.name.clone();
+        let cmd = if branch.is_remote {
+            format!("git checkout --track {}", name)
+        } else {
+            format!("git checkout {}", name)
+        };
+        let hook_config = self.active_hooks();
+        let env = self.hook_env(&[("LZGIT_TARGET", branch.name.clone())]);
+        self.start_git_job(cmd, true, false, move || {
+            hook_config.run(hooks::HookOp::Checkout, &repo_root, &env, || {
+                git_ops::checkout_branch_entry(&repo_root, &branch)
+            })
+        });
+        self.close_branch_picker();
+    }
+
+    /// The branch manager's selected branch when it is a local one, which
+    /// rename, delete and upstream need.
+    pub(crate) fn selected_local_branch(&mut self) -> Option<branch::BranchEntry> {
+        let Some(branch) = self.branch_ui.selected_branch() else {
+            self.branch_ui.status = Some("No branch selected".to_string());
+            return None;
+        };
+        if branch.is_remote {
+            self.branch_ui.status = Some("Not a local branch".to_string());
+            return None;
+        }
+        Some(branch)
+    }
+
+    pub(crate) fn branch_new(&mut self) {
+        self.close_branch_picker();
+        self.new_branch_input = Some(String::new());
+    }
+
+    pub(crate) fn branch_rename_selected(&mut self) {
+        if let Some(branch) = self.selected_local_branch() {
+            self.branch_input = Some(BranchInput {
+                text: branch.name.clone(),
+                kind: BranchInputKind::Rename(branch.name),
+            });
+        }
+    }
+
+    pub(crate) fn branch_upstream_selected(&mut self) {
+        if let Some(branch) = self.selected_local_branch() {
+            let text = branch
+                .upstream
+                .unwrap_or_else(|| format!("origin/{}", branch.name));
+            self.branch_input = Some(BranchInput {
+                kind: BranchInputKind::Upstream(branch.name),
+                text,
+            });
+        }
+    }
+
+    pub(crate) fn branch_describe_selected(&mut self) {
+        if let Some(branch) = self.selected_local_branch() {
+            self.branch_input = Some(BranchInput {
+                text: branch.description.unwrap_or_default(),
+                kind: BranchInputKind::Description(branch.name),
+            });
+        }
+    }
+
+    /// `git branch -d` refuses unmerged branches, which then get a force-delete
+    /// confirmation.
+    pub(crate) fn branch_delete_selected(&mut self) {
+        let Some(branch) = self.selected_local_branch() else {
+            return;
+        };
+        if branch.is_current {
+            self.branch_ui.status = Some("Cannot delete the checked-out branch".to_string());
+            return;
+        }
+        if let Some(path) = branch.worktree {
+            self.branch_ui.status = Some(format!(
+                "Cannot delete {}: it is checked out in {} (remove that worktree or switch it \
+                 to another branch first)",
+                branch.name, path
+            ));
+            return;
+        }
+        self.delete_branch(branch.name, false);
+    }
+
+    pub(crate) fn delete_branch(&mut self, name: String, force: bool) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let cmd = format!("git branch {} {}", if force { "-D" } else { "-d" }, name);
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::delete_branch(&repo_root, &name, force)
+        });
+    }
+
+    pub(crate) fn submit_branch_input(&mut self) {
+        let Some(input) = self.branch_input.take() else {
+            return;
+        };
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let text = input.text.trim().to_string();
+        match input.kind {
+            BranchInputKind::Rename(old) => {
+                if text.is_empty() || text == old {
+                    return;
+                }
+                let cmd = format!("git branch -m {} {}", old, text);
+                self.start_git_job(cmd, true, false, move || {
+                    git_ops::rename_branch(&repo_root, &old, &text)
+                });
+            }
+            BranchInputKind::Upstream(branch) => {
+                let cmd = if text.is_empty() {
+                    format!("git branch --unset-upstream {}", branch)
+                } else {
+                    format!("git branch --set-upstream-to={} {}", text, branch)
+                };
+                self.start_git_job(cmd, true, false, move || {
+                    git_ops::set_upstream(&repo_root, &branch, &text)
+                });
+            }
+            BranchInputKind::Description(branch) => {
+                let cmd = format!("git branch --edit-description {}", branch);
+                self.start_git_job(cmd, true, false, move || {
+                    git_ops::set_branch_description(&repo_root, &branch, &text)
+                });
+            }
+        }
+    }
+
+    /// Re-list branches after the manager changed one, keeping the filter.
+    pub(crate) fn reload_branch_picker(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        match git_ops::list_branches(&repo_root) {
+            Ok(branches) => self.branch_ui.set_branches(branches),
+            Err(e) => self.branch_ui.status = Some(e),
+        }
+    }
+
+    pub(crate) fn new_branch_at_selected_commit(&mut self) {
+        if self.git.repo_root.is_none() {
+            self.set_status("Not a git repository");
+            return;
+        }
+        let Some(entry) = self.selected_history_entry() else {
+            self.set_status("No commit selected");
+            return;
+        };
+        self.new_branch_from = Some(entry.hash.clone());
+        self.new_branch_input = Some(String::new());
+    }
+
+    pub(crate) fn create_branch_at(&mut self, name: String, start: String, checkout: bool) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let short = &start[..start.len().min(7)];
+        if !checkout {
+            let cmd = format!("git branch {} {}", name, short);
+            self.start_git_job(cmd, true, false, move || {
+                git_ops::create_branch_at(&repo_root, &name, &start)
+            });
+            return;
+        }
+        let cmd = format!("git checkout -b {} {}", name, short);
+        let hook_config = self.active_hooks();
+        let env = self.hook_env(&[("LZGIT_TARGET", name.clone())]);
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::create_branch_at(&repo_root, &name, &start)?;
+            hook_config.run(hooks::HookOp::Checkout, &repo_root, &env, || {
+                git_ops::checkout_branch(&repo_root, &name)
+            })
+        });
+    }
+}
@@ -0,0 +1,124 @@
+//! Merge conflicts: loading them and resolving them block by block
+
+use super::*;
+
+pub(crate) struct ConflictUi {
+    pub(crate) path: Option<String>,
+    pub(crate) file: Option<ConflictFile>,
+    pub(crate) selected_block: usize,
+    pub(crate) scroll_y: u16,
+}
+
+impl ConflictUi {
+    pub(crate) fn new() -> Self {
+        Self {
+            path: None,
+            file: None,
+            selected_block: 0,
+            scroll_y: 0,
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.path = None;
+        self.file = None;
+        self.selected_block = 0;
+        self.scroll_y = 0;
+    }
+}
+
+impl App {
+    pub(crate) fn ensure_conflicts_loaded(&mut self) {
+        let Some(entry) = self.git.selected_tree_entry() else {
+            self.conflict_ui.reset();
+            return;
+        };
+
+        if !entry.is_conflict {
+            self.conflict_ui.reset();
+            return;
+        }
+
+        if self.conflict_ui.path.as_deref() == Some(entry.path.as_str())
+            && self.conflict_ui.file.is_some()
+        {
+            return;
+        }
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.conflict_ui.reset();
+            return;
+        };
+
+        let abs = repo_root.join(&entry.path);
+        match conflict::load_conflicts(&abs) {
+            Ok(file) => {
+                self.conflict_ui.path = Some(entry.path.clone());
+                self.conflict_ui.file = Some(file);
+                self.conflict_ui.selected_block = 0;
+                self.conflict_ui.scroll_y = 0;
+            }
+            Err(e) => {
+                self.conflict_ui.path = Some(entry.path.clone());
+                self.conflict_ui.file = None;
+                self.conflict_ui.selected_block = 0;
+                self.conflict_ui.scroll_y = 0;
+                self.set_status(e);
+            }
+        }
+    }
+
+    pub(crate) fn change_conflict_block(&mut self, delta: i32) {
+        self.ensure_conflicts_loaded();
+        let Some(file) = self.conflict_ui.file.as_ref() else {
+            self.set_status("No conflicts loaded");
+            return;
+        };
+        if file.blocks.is_empty() {
+            self.set_status("No conflict markers found");
+            return;
+        }
+
+        let cur = self.conflict_ui.selected_block as i32;
+        let next = (cur + delta).clamp(0, file.blocks.len().saturating_sub(1) as i32);
+        self.conflict_ui.selected_block = next as usize;
+        self.conflict_ui.scroll_y = 0;
+    }
+
+    pub(crate) fn apply_conflict_resolution(&mut self, resolution: ConflictResolution) {
+        self.ensure_conflicts_loaded();
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let Some(rel) = self.conflict_ui.path.clone() else {
+            self.set_status("No conflict file selected");
+            return;
+        };
+
+        let abs = repo_root.join(&rel);
+        let idx = self.conflict_ui.selected_block;
+        // A job, so it waits for any git command that may be writing the file
+        let cmd = format!("resolve conflict {}", rel);
+        self.start_git_job(cmd, true, false, move || {
+            conflict::apply_conflict_resolution(&abs, idx, resolution)
+        });
+    }
+
+    pub(crate) fn mark_conflict_resolved(&mut self) {
+        let Some(entry) = self.git.selected_tree_entry() else {
+            self.set_status("No selection");
+            return;
+        };
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        let path = entry.path.clone();
+        let cmd = format!("git add -- {}", path);
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::stage_path(&repo_root, &path)
+        });
+    }
+}
@@ -0,0 +1,1358 @@
+//! Keys and mouse input for whatever is on screen
+
+use super::*;
+
+/// Apply one terminal event: keys and mouse input for whatever is on screen.
+pub(crate) fn handle_event(app: &mut App, event: Event) {
+    match event {
+        // The key after a `[` or `]`: `f` makes it a file jump, anything else lets the
+        // bracket do its own thing first
+        Event::Key(key)
+            if key.kind == KeyEventKind::Press && app.nav_keys.pending_bracket().is_some() =>
+        {
+            let bracket = app.nav_keys.take_bracket();
+            if key.code == KeyCode::Char('f') && key.modifiers == KeyModifiers::NONE {
+                app.jump_diff_anchor(true, bracket == Some(']'));
+            } else if key.code != KeyCode::Esc {
+                if let Some(bracket) = bracket {
+                    app.bracket_key(bracket);
+                }
+                handle_event(app, Event::Key(key));
+            }
+        }
+        Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+            _ if app.keymap.pressed(&AppAction::Quit, &key) => app.update(AppAction::Quit.into()),
+            _ if app.confirm.is_some() => match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                    app.update(AppAction::AcceptConfirm.into())
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    app.update(AppAction::CancelConfirm.into())
+                }
+                _ => {}
+            },
+            KeyCode::Char(c @ '1'..='9')
+                if key.modifiers.contains(KeyModifiers::ALT)
+                    && app.operation_popup.is_none()
+                    && !app.theme_picker.open
+                    && !app.command_palette.open
+                    && !app.bookmarks_ui.open
+                    && app.current_tab != Tab::Terminal =>
+            {
+                app.update(AppAction::JumpToBookmark(c).into());
+            }
+            KeyCode::Char(c @ '0'..='9') if app.accepts_count() => {
+                app.nav_keys
+                    .push_digit(c.to_digit(10).unwrap_or(0), Instant::now());
+            }
+            KeyCode::Char(c @ '1'..='3')
+                if app.operation_popup.is_none()
+                    && !app.theme_picker.open
+                    && !app.command_palette.open
+                    && !app.stash_ui.open
+                    && !app.bookmarks_ui.open
+                    && !app.branch_ui.open
+                    && app.current_tab != Tab::Terminal =>
+            {
+                app.switch_tab_by_number(c);
+            }
+            _ if app.keymap.pressed(&AppAction::OpenCommandPalette, &key)
+                && app.operation_popup.is_none()
+                && app.confirm.is_none()
+                && !app.branch_ui.open
+                && !app.author_ui.open
+                && app.context_menu.is_none()
+                && !app.log_ui.inspect.open =>
+            {
+                app.update(AppAction::OpenCommandPalette.into());
+            }
+            _ if app.keymap.pressed(&AppAction::OpenThemePicker, &key)
+                && app.operation_popup.is_none()
+                && app.confirm.is_none()
+                && !app.branch_ui.open
+                && !app.author_ui.open
+                && app.context_menu.is_none()
+                && !app.log_ui.inspect.open =>
+            {
+                app.update(AppAction::OpenThemePicker.into());
+            }
+            KeyCode::Esc => {
+                app.nav_keys.clear();
+                app.context_menu = None;
+                app.update_confirm = None;
+                app.stash_input = None;
+                app.push_input = None;
+                app.new_branch_input = None;
+                app.new_branch_orphan = false;
+                app.new_branch_from = None;
+                app.new_branch_checkout = false;
+                app.init_wizard = None;
+                app.gitignore_picker = None;
+                app.clone_input = None;
+                app.tag_input = None;
+                app.release_wizard = None;
+                app.reset_picker = None;
+                app.pull_picker = None;
+                app.key_help = None;
+                app.syntax_picker = None;
+                app.file_finder = None;
+                app.commit_find = None;
+                app.changelog_input = None;
+                if app
+                    .changelog_view
+                    .as_mut()
+                    .and_then(|v| v.export_path.take())
+                    .is_none()
+                {
+                    app.changelog_view = None;
+                }
+                app.snapshot_name_input = None;
+                app.export_path_input = None;
+                app.am_path_input = None;
+                app.bundle_input = None;
+                app.explorer_input = None;
+                app.goto_commit_input = None;
+                app.diff_selection = None;
+                if app.snapshot_ui.open {
+                    app.close_snapshot_picker();
+                }
+                if app.tag_ui.open {
+                    app.close_tag_picker();
+                }
+                if app.remote_input.take().is_none() && app.remote_ui.open {
+                    app.close_remote_manager();
+                }
+                if app.bookmarks_ui.rename.take().is_none() && app.bookmarks_ui.open {
+                    app.close_bookmarks_manager();
+                }
+                app.operation_popup = None;
+                app.theme_picker.open = false;
+                app.command_palette.open = false;
+                if app.current_tab == Tab::Log && app.log_ui.filter_edit {
+                    if app.log_ui.filter_query.trim().is_empty() {
+                        app.log_ui.filter_edit = false;
+                    } else {
+                        app.log_ui.filter_query.clear();
+                        app.log_ui.update_filtered();
+                        app.refresh_log_diff();
+                    }
+                } else {
+                    app.log_ui.filter_edit = false;
+                    if app.current_tab == Tab::Log {
+                        app.log_ui.marked.clear();
+                    }
+                }
+                app.log_ui.inspect.close();
+                app.blame.close();
+                if app.branch_input.take().is_none() && app.branch_ui.open {
+                    app.close_branch_picker();
+                }
+                let picking_commit_author = app.author_ui.open
+                    && matches!(
+                        app.author_picker_mode,
+                        AuthorPickerMode::CommitAuthor | AuthorPickerMode::CoAuthors
+                    );
+                let picking_commit_message = app.message_picker.take().is_some();
+                if app.author_ui.open {
+                    app.close_author_picker();
+                }
+                if app.stash_ui.open {
+                    app.close_stash_picker();
+                }
+                if app.current_tab == Tab::Git && !picking_commit_author && !picking_commit_message
+                {
+                    if app.review.open {
+                        app.review.open = false;
+                    } else {
+                        app.commit.open = false;
+                    }
+                }
+            }
+            _ => {
+                if app.theme_picker.open {
+                    match key.code {
+                        KeyCode::Char('j') | KeyCode::Down => app.move_theme_picker(1),
+                        KeyCode::Char('k') | KeyCode::Up => app.move_theme_picker(-1),
+                        KeyCode::Enter => app.apply_theme_picker_selection(),
+                        KeyCode::Char(ch) if ('1'..='5').contains(&ch) => {
+                            let idx = ch.to_digit(10).unwrap_or(1).saturating_sub(1) as usize;
+                            if idx < THEME_ORDER.len() {
+                                app.theme_picker.list_state.select(Some(idx));
+                                app.apply_theme_picker_selection();
+                            }
+                        }
+                        _ => {}
+                    }
+                } else if app.command_palette.open {
+                    match key.code {
+                        KeyCode::Char('j') | KeyCode::Down => app.move_command_palette(1),
+                        KeyCode::Char('k') | KeyCode::Up => app.move_command_palette(-1),
+                        KeyCode::Enter => app.run_command_palette_selection(),
+                        _ => {}
+                    }
+                } else if let Some(popup) = &mut app.operation_popup {
+                    match key.code {
+                        KeyCode::Esc | KeyCode::Enter => {
+                            app.update(AppAction::CloseOperationPopup.into())
+                        }
+                        KeyCode::Char('r') if popup.retry.is_some() => {
+                            app.update(AppAction::RetryOperation.into())
+                        }
+                        KeyCode::Char('j') | KeyCode::Down => {
+                            popup.scroll_y = popup.scroll_y.saturating_add(3)
+                        }
+                        KeyCode::Char('k') | KeyCode::Up => {
+                            popup.scroll_y = popup.scroll_y.saturating_sub(3)
+                        }
+                        _ => {}
+                    }
+                } else if app.update_confirm.is_some() {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                            app.update(AppAction::AcceptUpdate.into())
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                            app.update(AppAction::CancelUpdate.into())
+                        }
+                        _ => {}
+                    }
+                } else if app.stash_input.is_some() {
+                    app.stash_input_key(&key);
+                } else if app.push_input.is_some() {
+                    app.push_input_key(&key);
+                } else if app.new_branch_input.is_some() {
+                    match key.code {
+                        KeyCode::Esc => {
+                            app.new_branch_input = None;
+                            app.new_branch_orphan = false;
+                            app.new_branch_from = None;
+                            app.new_branch_checkout = false;
+                        }
+                        KeyCode::Enter => {
+                            if let Some(name) = app.new_branch_input.take() {
+                                let name = name.trim();
+                                let orphan = std::mem::take(&mut app.new_branch_orphan);
+                                let from = app.new_branch_from.take();
+                                let checkout = std::mem::take(&mut app.new_branch_checkout);
+                                if let Some(start) = from.filter(|_| !name.is_empty()) {
+                                    app.create_branch_at(name.to_string(), start, checkout);
+                                } else if !name.is_empty() {
+                                    let cmd = if orphan {
+                                        format!("git switch --orphan {}", name)
+                                    } else {
+                                        format!("git checkout -b {}", name)
+                                    };
+                                    app.start_operation_job(&cmd, true);
+                                }
+                            }
+                        }
+                        KeyCode::Tab if app.new_branch_from.is_some() => {
+                            app.new_branch_checkout = !app.new_branch_checkout;
+                        }
+                        KeyCode::Backspace => {
+                            if let Some(ref mut input) = app.new_branch_input {
+                                input.pop();
+                            }
+                        }
+                        KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            if let Some(ref mut input) = app.new_branch_input {
+                                input.push(ch);
+                            }
+                        }
+                        _ => {}
+                    }
+                } else if app.snapshot_name_input.is_some() {
+                    match key.code {
+                        KeyCode::Enter => {
+                            if let Some(name) = app.snapshot_name_input.take() {
+                                app.create_snapshot(&name);
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            if let Some(ref mut input) = app.snapshot_name_input {
+                                input.pop();
+                            }
+                        }
+                        KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            if let Some(ref mut input) = app.snapshot_name_input {
+                                input.push(ch);
+                            }
+                        }
+                        _ => {}
+                    }
+                } else if app.export_path_input.is_some() {
+                    match key.code {
+                        KeyCode::Enter => {
+                            if let Some(path) = app.export_path_input.take() {
+                                app.export_view_to_file(&path);
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            if let Some(ref mut input) = app.export_path_input {
+                                input.pop();
+                            }
+                        }
+                        KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            if let Some(ref mut input) = app.export_path_input {
+                                input.push(ch);
+                            }
+                        }
+                        _ => {}
+                    }
+                } else if app.am_path_input.is_some() {
+                    match key.code {
+                        KeyCode::Enter => {
+                            if let Some(input) = app.am_path_input.take() {
+                                app.confirm_apply_patches(&input);
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            if let Some(ref mut input) = app.am_path_input {
+                                input.pop();
+                            }
+                        }
+                        KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            if let Some(ref mut input) = app.am_path_input {
+                                input.push(ch);
+                            }
+                        }
+                        _ => {}
+                    }
+                } else if app.init_wizard.is_some() {
+                    app.init_wizard_key(&key);
+                } else if app.gitignore_picker.is_some() {
+                    app.gitignore_picker_key(&key);
+                } else if app.clone_input.is_some() {
+                    app.clone_input_key(&key);
+                } else if app.tag_input.is_some() {
+                    app.tag_input_key(&key);
+                } else if app.release_wizard.is_some() {
+                    app.release_wizard_key(&key);
+                } else if app.reset_picker.is_some() {
+                    app.reset_picker_key(&key);
+                } else if app.pull_picker.is_some() {
+                    app.pull_picker_key(&key);
+                } else if app.commit_find.is_some() {
+                    app.commit_find_key(&key);
+                } else if app.key_help.is_some() {
+                    app.key_help_key(&key);
+                } else if app.syntax_picker.is_some() {
+                    app.syntax_picker_key(&key);
+                } else if app.file_finder.is_some() {
+                    app.file_finder_key(&key);
+                } else if app.message_picker.is_some() {
+                    app.message_picker_key(&key);
+                } else if app.changelog_input.is_some() {
+                    app.changelog_input_key(&key);
+                } else if app.changelog_view.is_some() {
+                    app.changelog_view_key(&key);
+                } else if app.remote_input.is_some() {
+                    app.remote_input_key(&key);
+                } else if let Some(input) = app.branch_input.as_mut() {
+                    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+                    // Descriptions span lines: Enter breaks one and Ctrl+S saves
+                    let multiline = matches!(input.kind, BranchInputKind::Description(_));
+                    match key.code {
+                        KeyCode::Enter if multiline && !ctrl => input.text.push('\n'),
+                        KeyCode::Char('s') if multiline && ctrl => app.submit_branch_input(),
+                        KeyCode::Enter => app.submit_branch_input(),
+                        KeyCode::Backspace => {
+                            input.text.pop();
+                        }
+                        KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            input.text.push(ch);
+                        }
+                        _ => {}
+                    }
+                } else if let Some(input) = app.bundle_input.as_mut() {
+                    match key.code {
+                        KeyCode::Enter => app.submit_bundle_input(),
+                        KeyCode::Backspace => {
+                            input.text.pop();
+                        }
+                        KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            input.text.push(ch);
+                        }
+                        _ => {}
+                    }
+                } else if let Some(input) = app.explorer_input.as_mut() {
+                    match key.code {
+                        KeyCode::Enter => app.submit_explorer_input(),
+                        KeyCode::Backspace => {
+                            input.text.pop();
+                        }
+                        KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            input.text.push(ch);
+                        }
+                        _ => {}
+                    }
+                } else if app.goto_commit_input.is_some() {
+                    match key.code {
+                        KeyCode::Enter => {
+                            if let Some(rev) = app.goto_commit_input.take() {
+                                let rev = rev.trim().to_string();
+                                if !rev.is_empty() {
+                                    app.show_log_commit(&rev);
+                                }
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            if let Some(ref mut input) = app.goto_commit_input {
+                                input.pop();
+                            }
+                        }
+                        KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            if let Some(ref mut input) = app.goto_commit_input {
+                                input.push(ch);
+                            }
+                        }
+                        _ => {}
+                    }
+                } else if app.bookmarks_ui.open && app.bookmarks_ui.rename.is_some() {
+                    match key.code {
+                        KeyCode::Enter => app.commit_bookmark_rename(),
+                        KeyCode::Backspace => {
+                            if let Some(ref mut name) = app.bookmarks_ui.rename {
+                                name.pop();
+                            }
+                        }
+                        KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            if let Some(ref mut name) = app.bookmarks_ui.rename {
+                                name.push(ch);
+                            }
+                        }
+                        _ => {}
+                    }
+                } else if app.bookmarks_ui.open
+                    && let Some(nav) = app.nav_keys.key(&key, true)
+                {
+                    app.move_bookmark_selection(nav.delta());
+                } else if app.bookmarks_ui.open {
+                    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+                    let action = match key.code {
+                        KeyCode::Char('J') => AppAction::BookmarkMoveDown,
+                        KeyCode::Char('K') => AppAction::BookmarkMoveUp,
+                        KeyCode::Down if shift => AppAction::BookmarkMoveDown,
+                        KeyCode::Up if shift => AppAction::BookmarkMoveUp,
+                        KeyCode::Enter => AppAction::BookmarkOpen,
+                        KeyCode::Char('r') => AppAction::BookmarkRename,
+                        KeyCode::Char('x') | KeyCode::Delete => AppAction::BookmarkDelete,
+                        KeyCode::Char(c @ '1'..='9') => AppAction::BookmarkAssignKey(c),
+                        _ => AppAction::None,
+                    };
+                    app.update(Msg::Action(action));
+                } else if app.snapshot_ui.open
+                    && let Some(nav) = app.nav_keys.key(&key, true)
+                {
+                    app.snapshot_ui.move_selection(nav.delta());
+                } else if app.snapshot_ui.open {
+                    let action = match key.code {
+                        KeyCode::Enter | KeyCode::Char('d') => AppAction::SnapshotDiff,
+                        KeyCode::Char('r') => AppAction::SnapshotRestore,
+                        KeyCode::Char('x') => AppAction::SnapshotDelete,
+                        _ => AppAction::None,
+                    };
+                    app.update(Msg::Action(action));
+                } else if app.tag_ui.open
+                    && let Some(nav) = app.nav_keys.key(&key, true)
+                {
+                    app.tag_ui.move_selection(nav.delta());
+                } else if app.tag_ui.open {
+                    let action = match key.code {
+                        KeyCode::Enter => AppAction::TagShow,
+                        KeyCode::Char('p') => AppAction::TagPush,
+                        KeyCode::Char('P') => AppAction::TagPushAll,
+                        KeyCode::Char('x') | KeyCode::Delete => AppAction::TagDelete,
+                        _ => AppAction::None,
+                    };
+                    app.update(Msg::Action(action));
+                } else if app.remote_ui.open
+                    && let Some(nav) = app.nav_keys.key(&key, true)
+                {
+                    app.remote_ui.move_selection(nav.delta());
+                } else if app.remote_ui.open {
+                    let action = match key.code {
+                        KeyCode::Enter => AppAction::RemoteUse,
+                        KeyCode::Char('a') => AppAction::RemoteAdd,
+                        KeyCode::Char('r') => AppAction::RemoteRename,
+                        KeyCode::Char('u') => AppAction::RemoteUrl(false),
+                        KeyCode::Char('U') => AppAction::RemoteUrl(true),
+                        KeyCode::Char('x') | KeyCode::Delete => AppAction::RemoteRemove,
+                        _ => AppAction::None,
+                    };
+                    app.update(Msg::Action(action));
+                } else if app.branch_ui.open
+                    && let Some(nav) = app.nav_keys.key(&key, false)
+                {
+                    app.branch_ui.move_selection(nav.delta());
+                } else if app.branch_ui.open {
+                    if let Some(msg) = branch_picker_key(app, &key) {
+                        app.update(msg);
+                    } else {
+                        match key.code {
+                            KeyCode::Char('j') => app.branch_ui.move_selection(1),
+                            KeyCode::Char('k') => app.branch_ui.move_selection(-1),
+                            KeyCode::Backspace => {
+                                app.branch_ui.query.pop();
+                                app.branch_ui.update_filtered();
+                            }
+                            KeyCode::Char(ch)
+                                if !key.modifiers.contains(KeyModifiers::CONTROL)
+                                    && !key.modifiers.contains(KeyModifiers::ALT) =>
+                            {
+                                app.branch_ui.query.push(ch);
+                                app.branch_ui.update_filtered();
+                            }
+                            _ => {}
+                        }
+                    }
+                } else if app.author_ui.open
+                    && let Some(nav) = app.nav_keys.key(&key, false)
+                {
+                    app.author_ui.move_selection(nav.delta());
+                } else if app.author_ui.open {
+                    let listed = !app.author_ui.filtered.is_empty();
+                    if let Some(msg) = author_picker_key(app, &key) {
+                        app.update(msg);
+                    } else {
+                        match key.code {
+                            KeyCode::Tab
+                                if listed
+                                    && app.author_picker_mode == AuthorPickerMode::CoAuthors =>
+                            {
+                                app.author_ui.toggle_marked();
+                                app.author_ui.move_selection(1);
+                            }
+                            KeyCode::Char('j') if listed => app.author_ui.move_selection(1),
+                            KeyCode::Char('k') if listed => app.author_ui.move_selection(-1),
+                            KeyCode::Backspace => {
+                                app.author_ui.query.pop();
+                                app.author_ui.update_filtered();
+                            }
+                            KeyCode::Char(ch)
+                                if !key.modifiers.contains(KeyModifiers::CONTROL)
+                                    && !key.modifiers.contains(KeyModifiers::ALT) =>
+                            {
+                                app.author_ui.query.push(ch);
+                                app.author_ui.update_filtered();
+                            }
+                            _ => {}
+                        }
+                    }
+                } else if app.blame.open {
+                    app.blame_key(&key);
+                } else if let Some(action) = app.keymap_action(&key) {
+                    app.update(Msg::Action(action));
+                } else {
+                    match app.current_tab {
+                        Tab::Explorer => {
+                            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+                            if let Some(nav) = app.nav_keys.key(&key, true) {
+                                app.navigate_focused(nav)
+                            } else if let Some(msg) = explorer_key(&key) {
+                                app.update(msg);
+                            } else {
+                                match key.code {
+                                    KeyCode::Up if ctrl => {
+                                        app.preview_scroll_offset =
+                                            app.preview_scroll_offset.saturating_sub(1);
+                                    }
+                                    KeyCode::Down if ctrl => {
+                                        app.preview_scroll_offset =
+                                            app.preview_scroll_offset.saturating_add(1);
+                                    }
+                                    KeyCode::PageUp if ctrl => {
+                                        app.preview_scroll_offset =
+                                            app.preview_scroll_offset.saturating_sub(10);
+                                    }
+                                    KeyCode::PageDown if ctrl => {
+                                        app.preview_scroll_offset =
+                                            app.preview_scroll_offset.saturating_add(10);
+                                    }
+                                    KeyCode::Tab => {
+                                        app.explorer_focus = match app.explorer_focus {
+                                            PaneFocus::List => PaneFocus::Detail,
+                                            PaneFocus::Detail => PaneFocus::List,
+                                        };
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                        Tab::Git => {
+                            if app.stash_ui.open
+                                && let Some(nav) = app.nav_keys.key(&key, false)
+                            {
+                                app.stash_ui.move_selection(nav.delta());
+                            } else if app.stash_ui.open {
+                                if let Some(msg) = stash_picker_key(&key) {
+                                    app.update(msg);
+                                } else {
+                                    match key.code {
+                                        KeyCode::Char('j') => app.stash_ui.move_selection(1),
+                                        KeyCode::Char('k') => app.stash_ui.move_selection(-1),
+                                        KeyCode::Backspace => {
+                                            app.stash_ui.query.pop();
+                                            app.stash_ui.update_filtered();
+                                        }
+                                        KeyCode::Char(ch)
+                                            if !key.modifiers.contains(KeyModifiers::CONTROL)
+                                                && !key.modifiers.contains(KeyModifiers::ALT) =>
+                                        {
+                                            app.stash_ui.query.push(ch);
+                                            app.stash_ui.update_filtered();
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                            } else if app.review.open
+                                && let Some(nav) = app.nav_keys.key(&key, true)
+                            {
+                                app.review.scroll_y = nav.scroll_u16(app.review.scroll_y);
+                            } else if app.review.open {
+                                if let Some(msg) = review_key(app, &key) {
+                                    app.update(msg);
+                                }
+                            } else if app.commit.open {
+                                if let Some(msg) = commit_drawer_key(app, &key) {
+                                    app.update(msg);
+                                } else {
+                                    edit_commit_drawer(app, &key);
+                                }
+                            } else if app.diff_selection_range(Tab::Git).is_some()
+                                && let Some(nav) = app.nav_keys.key(&key, true)
+                            {
+                                app.move_diff_selection(nav.delta() as isize);
+                            } else if app.diff_selection_range(Tab::Git).is_some() {
+                                if let Some(msg) = diff_selection_key(&key) {
+                                    app.update(msg);
+                                }
+                            } else if let Some(nav) = app.nav_keys.key(&key, true) {
+                                app.navigate_focused(nav);
+                            } else if let Some(msg) = git_key(app, &key) {
+                                app.update(msg);
+                            } else {
+                                match key.code {
+                                    KeyCode::Char('[') => {
+                                        app.nav_keys.push_bracket('[', Instant::now())
+                                    }
+                                    KeyCode::Char(']') => {
+                                        app.nav_keys.push_bracket(']', Instant::now())
+                                    }
+                                    KeyCode::Left => {
+                                        // Collapse or scroll diff
+                                        if let Some(item) = app.git.selected_tree_item() {
+                                            use git::FlatNodeType;
+                                            if item.node_type == FlatNodeType::Section
+                                                || item.node_type == FlatNodeType::Directory
+                                            {
+                                                app.git.collapse_tree_item();
+                                            } else {
+                                                app.git.diff_scroll_x =
+                                                    app.git.diff_scroll_x.saturating_sub(4);
+                                            }
+                                        } else {
+                                            app.git.diff_scroll_x =
+                                                app.git.diff_scroll_x.saturating_sub(4);
+                                        }
+                                    }
+                                    KeyCode::Right => {
+                                        // Expand or scroll diff
+                                        if let Some(item) = app.git.selected_tree_item() {
+                                            use git::FlatNodeType;
+                                            if item.node_type == FlatNodeType::Section
+                                                || item.node_type == FlatNodeType::Directory
+                                            {
+                                                app.git.expand_tree_item();
+                                            } else {
+                                                app.git.diff_scroll_x =
+                                                    app.git.diff_scroll_x.saturating_add(4);
+                                            }
+                                        } else {
+                                            app.git.diff_scroll_x =
+                                                app.git.diff_scroll_x.saturating_add(4);
+                                        }
+                                    }
+                                    KeyCode::Tab => {
+                                        app.git_focus = match app.git_focus {
+                                            PaneFocus::List => PaneFocus::Detail,
+                                            PaneFocus::Detail => PaneFocus::List,
+                                        };
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                        Tab::Log => {
+                            if app.log_ui.inspect.open {
+                                if let Some(msg) = log_inspect_key(&key) {
+                                    app.update(msg);
+                                } else {
+                                    let inspect = &mut app.log_ui.inspect;
+                                    match key.code {
+                                        KeyCode::Down => {
+                                            app.move_log_selection(1);
+                                            app.open_log_inspect();
+                                        }
+                                        KeyCode::Up => {
+                                            app.move_log_selection(-1);
+                                            app.open_log_inspect();
+                                        }
+                                        KeyCode::PageDown => {
+                                            inspect.scroll_y = inspect.scroll_y.saturating_add(10)
+                                        }
+                                        KeyCode::PageUp => {
+                                            inspect.scroll_y = inspect.scroll_y.saturating_sub(10)
+                                        }
+                                        KeyCode::Char('j') => {
+                                            inspect.scroll_y = inspect.scroll_y.saturating_add(3)
+                                        }
+                                        KeyCode::Char('k') => {
+                                            inspect.scroll_y = inspect.scroll_y.saturating_sub(3)
+                                        }
+                                        _ => {}
+                                    }
+                                }
+                            } else if app.diff_selection_range(Tab::Log).is_some()
+                                && let Some(nav) = app.nav_keys.key(&key, true)
+                            {
+                                app.move_diff_selection(nav.delta() as isize);
+                            } else if app.diff_selection_range(Tab::Log).is_some() {
+                                if let Some(msg) = diff_selection_key(&key) {
+                                    app.update(msg);
+                                }
+                            } else if !(app.log_ui.filter_edit
+                                && key.modifiers.contains(KeyModifiers::CONTROL))
+                                && let Some(nav) = app.nav_keys.key(&key, !app.log_ui.filter_edit)
+                            {
+                                // Ctrl keys edit the filter while it is being typed
+                                app.navigate_focused(nav);
+                            } else if let Some(msg) = log_key(app, &key) {
+                                app.update(msg);
+                            } else {
+                                match key.code {
+                                    KeyCode::Char('/')
+                                        if app.log_ui.subtab != LogSubTab::Commands =>
+                                    {
+                                        app.log_ui.filter_edit = !app.log_ui.filter_edit;
+                                        app.log_ui.focus = LogPaneFocus::Commits;
+                                    }
+                                    KeyCode::Enter if app.log_ui.filter_edit => {
+                                        app.log_ui.filter_edit = false;
+                                    }
+                                    KeyCode::Backspace if app.log_ui.filter_edit => {
+                                        app.log_ui.filter_query.pop();
+                                        app.log_ui.update_filtered();
+                                        app.refresh_log_diff();
+                                    }
+                                    KeyCode::Char('u') | KeyCode::Char('l')
+                                        if app.log_ui.subtab != LogSubTab::Commands
+                                            && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                                    {
+                                        app.log_ui.filter_query.clear();
+                                        app.log_ui.update_filtered();
+                                        app.refresh_log_diff();
+                                    }
+                                    KeyCode::Char(ch) if app.log_ui.filter_edit => {
+                                        if !key.modifiers.contains(KeyModifiers::CONTROL)
+                                            && !key.modifiers.contains(KeyModifiers::ALT)
+                                        {
+                                            app.log_ui.filter_query.push(ch);
+                                            app.log_ui.update_filtered();
+                                            app.refresh_log_diff();
+                                        }
+                                    }
+                                    KeyCode::Tab => app.cycle_log_focus(),
+                                    KeyCode::Char('[') => {
+                                        app.nav_keys.push_bracket('[', Instant::now())
+                                    }
+                                    KeyCode::Char(']') => {
+                                        app.nav_keys.push_bracket(']', Instant::now())
+                                    }
+                                    KeyCode::Left => {
+                                        app.log_ui.diff_scroll_x =
+                                            app.log_ui.diff_scroll_x.saturating_sub(4)
+                                    }
+                                    KeyCode::Right => {
+                                        app.log_ui.diff_scroll_x =
+                                            app.log_ui.diff_scroll_x.saturating_add(4)
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                        Tab::Terminal => {
+                            // Forward key input to the terminal
+                            let bytes: Vec<u8> = match key.code {
+                                KeyCode::Char(c) => {
+                                    if key.modifiers.contains(KeyModifiers::CONTROL) {
+                                        // Ctrl+letter -> 1-26
+                                        let code = c.to_ascii_lowercase() as u8;
+                                        if code >= b'a' && code <= b'z' {
+                                            vec![code - b'a' + 1]
+                                        } else {
+                                            vec![]
+                                        }
+                                    } else {
+                                        let mut buf = [0u8; 4];
+                                        c.encode_utf8(&mut buf).as_bytes().to_vec()
+                                    }
+                                }
+                                KeyCode::Enter => vec![b'\r'],
+                                KeyCode::Backspace => vec![127],
+                                KeyCode::Tab => vec![b'\t'],
+                                KeyCode::Esc => vec![27],
+                                KeyCode::Up => b"\x1b[A".to_vec(),
+                                KeyCode::Down => b"\x1b[B".to_vec(),
+                                KeyCode::Right => b"\x1b[C".to_vec(),
+                                KeyCode::Left => b"\x1b[D".to_vec(),
+                                KeyCode::Home => b"\x1b[H".to_vec(),
+                                KeyCode::End => b"\x1b[F".to_vec(),
+                                KeyCode::PageUp => b"\x1b[5~".to_vec(),
+                                KeyCode::PageDown => b"\x1b[6~".to_vec(),
+                                KeyCode::Delete => b"\x1b[3~".to_vec(),
+                                KeyCode::Insert => b"\x1b[2~".to_vec(),
+                                _ => vec![],
+                            };
+                            if !bytes.is_empty() {
+                                app.terminal.write_input(&bytes);
+                            }
+                        }
+                    }
+                }
+            }
+        },
+        Event::Mouse(mouse) => match mouse.kind {
+            MouseEventKind::Moved => {
+                app.hover_pos = Some((mouse.column, mouse.row));
+                app.update_context_menu_hover(mouse.row, mouse.column);
+            }
+            MouseEventKind::ScrollDown => {
+                if app.theme_picker.open {
+                    app.move_theme_picker(3);
+                } else if app.command_palette.open {
+                    app.move_command_palette(3);
+                } else if app.stash_ui.open {
+                    app.stash_ui.move_selection(3);
+                } else if app.bookmarks_ui.open {
+                    app.move_bookmark_selection(3);
+                } else if app.snapshot_ui.open {
+                    app.snapshot_ui.move_selection(3);
+                } else if app.tag_ui.open {
+                    app.tag_ui.move_selection(3);
+                } else if app.remote_ui.open {
+                    app.remote_ui.move_selection(3);
+                } else if app.branch_ui.open {
+                    app.branch_ui.move_selection(3);
+                } else if app.author_ui.open {
+                    app.author_ui.move_selection(3);
+                } else {
+                    match app.current_tab {
+                        Tab::Explorer => {
+                            if mouse.column >= app.explorer_preview_x {
+                                // Preview pane - scroll preview
+                                app.preview_scroll_offset =
+                                    app.preview_scroll_offset.saturating_add(3);
+                            } else if mouse.column >= app.explorer_current_x {
+                                // Current directory pane - scroll file list
+                                let i = app.selected_index().unwrap_or(0);
+                                if i + 3 < app.files.len() {
+                                    app.list_state.select(Some(i + 3));
+                                    app.update_preview();
+                                } else {
+                                    app.list_state
+                                        .select(Some(app.files.len().saturating_sub(1)));
+                                    app.update_preview();
+                                }
+                            }
+                            // Parent pane (left) - no scroll action for now
+                        }
+                        Tab::Git => {
+                            if app.branch_ui.open {
+                                app.branch_ui.move_selection(3);
+                            } else if mouse.column >= app.git_diff_x {
+                                if mouse.modifiers.contains(KeyModifiers::SHIFT) {
+                                    app.git.diff_scroll_x = app.git.diff_scroll_x.saturating_add(4);
+                                } else if app
+                                    .git
+                                    .selected_tree_entry()
+                                    .is_some_and(|e| e.is_conflict)
+                                {
+                                    app.conflict_ui.scroll_y =
+                                        app.conflict_ui.scroll_y.saturating_add(3);
+                                } else if app.git.show_full_file {
+                                    app.git.full_file_scroll_y =
+                                        app.git.full_file_scroll_y.saturating_add(3);
+                                } else {
+                                    app.git.diff_scroll_y = app.git.diff_scroll_y.saturating_add(3);
+                                }
+                            } else {
+                                let i = app.git.list_state.selected().unwrap_or(0);
+                                let next = (i + 3).min(app.git.filtered.len().saturating_sub(1));
+                                if app.git.filtered.is_empty() {
+                                    app.git.list_state.select(None);
+                                } else {
+                                    app.git.select_filtered(next);
+                                    app.request_git_diff_update();
+                                }
+                            }
+                        }
+                        Tab::Log => {
+                            let files_mode = app.log_ui.detail_mode == LogDetailMode::Files
+                                && app.log_ui.subtab == LogSubTab::History
+                                && app.log_ui.zoom != LogZoom::List;
+
+                            if app.log_ui.inspect.open {
+                                app.log_ui.inspect.scroll_y =
+                                    app.log_ui.inspect.scroll_y.saturating_add(3);
+                            } else if mouse.column >= app.log_diff_x {
+                                app.log_ui.focus = LogPaneFocus::Diff;
+                                if mouse.modifiers.contains(KeyModifiers::SHIFT) {
+                                    app.log_ui.diff_scroll_x =
+                                        app.log_ui.diff_scroll_x.saturating_add(4);
+                                } else {
+                                    app.log_ui.diff_scroll_y =
+                                        app.log_ui.diff_scroll_y.saturating_add(3);
+                                }
+                            } else if files_mode && mouse.column >= app.log_files_x {
+                                app.log_ui.focus = LogPaneFocus::Files;
+                                app.move_log_file_selection(3);
+                            } else {
+                                app.log_ui.focus = LogPaneFocus::Commits;
+                                app.move_log_selection(3);
+                            }
+                        }
+                        Tab::Terminal => {
+                            // Terminal handles scrollback internally
+                        }
+                    }
+                }
+            }
+            MouseEventKind::ScrollUp => {
+                if app.theme_picker.open {
+                    app.move_theme_picker(-3);
+                } else if app.command_palette.open {
+                    app.move_command_palette(-3);
+                } else if app.stash_ui.open {
+                    app.stash_ui.move_selection(-3);
+                } else if app.bookmarks_ui.open {
+                    app.move_bookmark_selection(-3);
+                } else if app.snapshot_ui.open {
+                    app.snapshot_ui.move_selection(-3);
+                } else if app.tag_ui.open {
+                    app.tag_ui.move_selection(-3);
+                } else if app.remote_ui.open {
+                    app.remote_ui.move_selection(-3);
+                } else if app.branch_ui.open {
+                    app.branch_ui.move_selection(-3);
+                } else if app.author_ui.open {
+                    app.author_ui.move_selection(-3);
+                } else {
+                    match app.current_tab {
+                        Tab::Explorer => {
+                            if mouse.column >= app.explorer_preview_x {
+                                // Preview pane - scroll preview
+                                app.preview_scroll_offset =
+                                    app.preview_scroll_offset.saturating_sub(3);
+                            } else if mouse.column >= app.explorer_current_x {
+                                // Current directory pane - scroll file list
+                                let i = app.selected_index().unwrap_or(0);
+                                if i >= 3 {
+                                    app.list_state.select(Some(i - 3));
+                                    app.update_preview();
+                                } else {
+                                    app.list_state.select(Some(0));
+                                    app.update_preview();
+                                }
+                            }
+                            // Parent pane (left) - no scroll action for now
+                        }
+                        Tab::Git => {
+                            if app.branch_ui.open {
+                                app.branch_ui.move_selection(-3);
+                            } else if mouse.column >= app.git_diff_x {
+                                if mouse.modifiers.contains(KeyModifiers::SHIFT) {
+                                    app.git.diff_scroll_x = app.git.diff_scroll_x.saturating_sub(4);
+                                } else if app
+                                    .git
+                                    .selected_tree_entry()
+                                    .is_some_and(|e| e.is_conflict)
+                                {
+                                    app.conflict_ui.scroll_y =
+                                        app.conflict_ui.scroll_y.saturating_sub(3);
+                                } else if app.git.show_full_file {
+                                    app.git.full_file_scroll_y =
+                                        app.git.full_file_scroll_y.saturating_sub(3);
+                                } else {
+                                    app.git.diff_scroll_y = app.git.diff_scroll_y.saturating_sub(3);
+                                }
+                            } else {
+                                let i = app.git.list_state.selected().unwrap_or(0);
+                                if i >= 3 {
+                                    app.git.select_filtered(i - 3);
+                                    app.request_git_diff_update();
+                                } else if !app.git.filtered.is_empty() {
+                                    app.git.select_filtered(0);
+                                    app.request_git_diff_update();
+                                }
+                            }
+                        }
+                        Tab::Log => {
+                            let files_mode = app.log_ui.detail_mode == LogDetailMode::Files
+                                && app.log_ui.subtab == LogSubTab::History
+                                && app.log_ui.zoom != LogZoom::List;
+
+                            if app.log_ui.inspect.open {
+                                app.log_ui.inspect.scroll_y =
+                                    app.log_ui.inspect.scroll_y.saturating_sub(3);
+                            } else if mouse.column >= app.log_diff_x {
+                                app.log_ui.focus = LogPaneFocus::Diff;
+                                if mouse.modifiers.contains(KeyModifiers::SHIFT) {
+                                    app.log_ui.diff_scroll_x =
+                                        app.log_ui.diff_scroll_x.saturating_sub(4);
+                                } else {
+                                    app.log_ui.diff_scroll_y =
+                                        app.log_ui.diff_scroll_y.saturating_sub(3);
+                                }
+                            } else if files_mode && mouse.column >= app.log_files_x {
+                                app.log_ui.focus = LogPaneFocus::Files;
+                                app.move_log_file_selection(-3);
+                            } else {
+                                app.log_ui.focus = LogPaneFocus::Commits;
+                                app.move_log_selection(-3);
+                            }
+                        }
+                        Tab::Terminal => {
+                            // Terminal handles scrollback internally
+                        }
+                    }
+                }
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                app.handle_click(mouse.row, mouse.column, mouse.modifiers);
+            }
+            MouseEventKind::Down(MouseButton::Right) => {
+                if app.theme_picker.open {
+                    app.theme_picker.open = false;
+                    return;
+                }
+                if app.command_palette.open {
+                    app.command_palette.open = false;
+                    return;
+                }
+                if app.confirm.is_some() {
+                    app.confirm = None;
+                    return;
+                }
+                if app.stash_ui.open {
+                    app.close_stash_picker();
+                    return;
+                }
+
+                app.context_menu = None;
+                app.pending_menu_action = None;
+                app.handle_context_click(mouse.row, mouse.column, mouse.modifiers);
+                app.open_context_menu(mouse.row, mouse.column);
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+// What a key does, as the message a click, palette entry or bound key sends for the
+// same thing. `None` leaves the key to the caller, which types it into a field, moves a
+// selection or scrolls.
+
+fn branch_picker_key(app: &App, key: &KeyEvent) -> Option<Msg> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let checkout = app.branch_picker_mode == BranchPickerMode::Checkout;
+    Some(match key.code {
+        KeyCode::Esc => AppAction::CloseBranchPicker.into(),
+        KeyCode::Enter if checkout => AppAction::BranchCheckout.into(),
+        KeyCode::Enter => AppAction::ConfirmLogBranchPicker.into(),
+        KeyCode::Char('n') if ctrl && checkout => AppAction::BranchNew.into(),
+        KeyCode::Char('r') if ctrl && checkout => AppAction::BranchRename.into(),
+        KeyCode::Char('t') if ctrl && checkout => AppAction::BranchUpstream.into(),
+        KeyCode::Char('e') if ctrl && checkout => AppAction::BranchDescribe.into(),
+        KeyCode::Delete if checkout => AppAction::BranchDelete.into(),
+        _ => return None,
+    })
+}
+
+fn author_picker_key(app: &App, key: &KeyEvent) -> Option<Msg> {
+    Some(match key.code {
+        KeyCode::Esc => AppAction::CloseAuthorPicker.into(),
+        KeyCode::Enter if !app.author_ui.filtered.is_empty() => {
+            AppAction::ConfirmAuthorPicker.into()
+        }
+        _ => return None,
+    })
+}
+
+fn explorer_key(key: &KeyEvent) -> Option<Msg> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    Some(match key.code {
+        KeyCode::Char('h') | KeyCode::Backspace | KeyCode::Left => AppAction::GoParent.into(),
+        KeyCode::Char('l') | KeyCode::Enter | KeyCode::Right => AppAction::EnterDir.into(),
+        KeyCode::Char('.') => AppAction::ToggleHidden.into(),
+        KeyCode::Char('i') => AppAction::AddToGitignore.into(),
+        KeyCode::Char('u') => AppAction::UndoFileOp.into(),
+        KeyCode::Char('r') if ctrl => AppAction::RedoFileOp.into(),
+        KeyCode::Char('a') => AppAction::NewEntry.into(),
+        KeyCode::F(2) => AppAction::RenameEntry.into(),
+        KeyCode::Char('r') => AppAction::RefreshFiles.into(),
+        KeyCode::Char('z') => AppAction::ToggleExplorerZoom.into(),
+        KeyCode::Char('d') | KeyCode::Delete => AppAction::DeleteEntry.into(),
+        KeyCode::Char('e') => AppAction::OpenInEditor.into(),
+        KeyCode::Char('b') => CommandId::OpenBookmarks.into(),
+        KeyCode::Char('B') => CommandId::BlameFile.into(),
+        KeyCode::Char('H') => CommandId::ToggleSyntaxHighlight.into(),
+        KeyCode::Char('R') => AppAction::ToggleAutoRefresh.into(),
+        _ => return None,
+    })
+}
+
+fn stash_picker_key(key: &KeyEvent) -> Option<Msg> {
+    Some(match key.code {
+        KeyCode::Esc => AppAction::CloseStashPicker.into(),
+        KeyCode::Enter | KeyCode::Char('a') => AppAction::StashApply.into(),
+        KeyCode::Char('p') => AppAction::StashPop.into(),
+        KeyCode::Char('d') => AppAction::StashDrop.into(),
+        _ => return None,
+    })
+}
+
+fn review_key(app: &App, key: &KeyEvent) -> Option<Msg> {
+    Some(match key.code {
+        KeyCode::Char(' ') => AppAction::ReviewApprove.into(),
+        KeyCode::Char('u') => AppAction::ReviewUnstage.into(),
+        KeyCode::Backspace => AppAction::ReviewBack.into(),
+        KeyCode::Enter if app.review.at_end() => AppAction::FinishReview.into(),
+        _ => return None,
+    })
+}
+
+fn commit_drawer_key(app: &App, key: &KeyEvent) -> Option<Msg> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let commit = &app.commit;
+    let action = match key.code {
+        KeyCode::Char('r') if ctrl => AppAction::OpenReview,
+        KeyCode::Char('a') if ctrl => AppAction::ToggleCommitAdvanced,
+        KeyCode::Char('e') if ctrl => AppAction::ToggleCommitAmend,
+        KeyCode::Char('o') if ctrl => AppAction::PickCommitAuthor,
+        KeyCode::Char('k') if ctrl => AppAction::PickCoAuthors,
+        KeyCode::Char('l') if ctrl => AppAction::OpenMessagePicker,
+        // An empty message recalls an earlier one, like a shell's history
+        KeyCode::Up | KeyCode::Down
+            if commit.focus == CommitFocus::Message && commit.message.trim().is_empty() =>
+        {
+            AppAction::OpenMessagePicker
+        }
+        KeyCode::Char('t') if ctrl => AppAction::ToggleConventionalCommit,
+        KeyCode::Char('g') | KeyCode::Char('G') if ctrl => AppAction::GenerateCommitMessage,
+        KeyCode::Enter if ctrl => AppAction::GitFooter(GitFooterAction::Commit),
+        _ if ctrl || key.code == KeyCode::Tab => return None,
+        _ if commit.focus == CommitFocus::Type => match key.code {
+            KeyCode::Left | KeyCode::Up => AppAction::CycleCommitType(-1),
+            KeyCode::Right | KeyCode::Down => AppAction::CycleCommitType(1),
+            KeyCode::Char(' ') | KeyCode::Char('!') => AppAction::ToggleBreakingChange,
+            KeyCode::Enter => AppAction::FocusCommitMessage,
+            _ => return None,
+        },
+        KeyCode::Enter if matches!(commit.focus, CommitFocus::Scope | CommitFocus::Date) => {
+            AppAction::FocusCommitMessage
+        }
+        _ => return None,
+    };
+    Some(action.into())
+}
+
+/// Type a key into the focused field of the commit drawer.
+fn edit_commit_drawer(app: &mut App, key: &KeyEvent) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+    let commit = &mut app.commit;
+    if key.code == KeyCode::Tab && (commit.advanced || commit.conventional.is_some()) {
+        commit.next_focus();
+    } else if commit.focus == CommitFocus::Type && !ctrl {
+        if let KeyCode::Char(ch) = key.code {
+            commit.pick_conventional_type(ch);
+        }
+    } else if commit.focus == CommitFocus::Scope && !ctrl {
+        match key.code {
+            KeyCode::Backspace => commit.edit_scope(None),
+            KeyCode::Char(ch) if !alt => commit.edit_scope(Some(ch)),
+            _ => {}
+        }
+    } else if commit.focus == CommitFocus::Date && !ctrl {
+        match key.code {
+            KeyCode::Backspace => {
+                commit.date.pop();
+            }
+            KeyCode::Char(ch) if !alt => commit.date.push(ch),
+            _ => {}
+        }
+    } else if !commit.busy {
+        match key.code {
+            KeyCode::Left => commit.move_left(),
+            KeyCode::Right => commit.move_right(),
+            KeyCode::Home => commit.move_home(),
+            KeyCode::End => commit.move_end(),
+            KeyCode::Backspace => commit.backspace(),
+            KeyCode::Delete => commit.delete(),
+            KeyCode::Enter => commit.insert_char('\n'),
+            KeyCode::Char(ch) if !ctrl && !alt => commit.insert_char(ch),
+            _ => {}
+        }
+    }
+}
+
+fn diff_selection_key(key: &KeyEvent) -> Option<Msg> {
+    Some(match key.code {
+        KeyCode::Char('y') => AppAction::YankDiffSelection(DiffYank::NewLines).into(),
+        KeyCode::Char('p') => AppAction::YankDiffSelection(DiffYank::Patch).into(),
+        KeyCode::Char('Y') => AppAction::YankDiffSelection(DiffYank::Markers).into(),
+        KeyCode::Char('v') => AppAction::ClearDiffSelection.into(),
+        _ => return None,
+    })
+}
+
+fn git_key(app: &App, key: &KeyEvent) -> Option<Msg> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+    let conflict = app.git.selected_tree_entry().is_some_and(|e| e.is_conflict);
+    Some(match key.code {
+        KeyCode::Char('v') => AppAction::StartDiffSelection.into(),
+        KeyCode::Char(' ') => AppAction::ToggleGitStage.into(),
+        KeyCode::Char('.') => AppAction::RepeatLastAction.into(),
+        KeyCode::Char('A') => AppAction::GitStageAllVisible.into(),
+        KeyCode::Char('U') => AppAction::GitUnstageAllVisible.into(),
+        KeyCode::Char('a') if ctrl => AppAction::SelectAllGitFiles.into(),
+        KeyCode::Char('z') if ctrl && !shift => AppAction::UndoRevert.into(),
+        KeyCode::Char('z') | KeyCode::Char('Z') if ctrl && shift => AppAction::RedoRevert.into(),
+        KeyCode::Char('y') if ctrl => AppAction::RedoRevert.into(),
+        KeyCode::Char('u') if app.git.selected_submodule().is_some() => {
+            AppAction::Submodule(git_ops::SubmoduleOp::Update).into()
+        }
+        KeyCode::Backspace if !app.superprojects.is_empty() => AppAction::LeaveSubmodule.into(),
+        KeyCode::Char('r') => AppAction::RefreshGit.into(),
+        KeyCode::Char('i') => AppAction::AddToGitignore.into(),
+        KeyCode::Char('I') => CommandId::InitRepo.into(),
+        KeyCode::Char('w') => CommandId::ToggleWrapDiff.into(),
+        KeyCode::Char('H') => CommandId::ToggleSyntaxHighlight.into(),
+        KeyCode::Char('#') => CommandId::ToggleDiffLineNumbers.into(),
+        KeyCode::Char('F') => AppAction::ToggleFullFile.into(),
+        KeyCode::Char('+') => AppAction::DiffContext(true).into(),
+        KeyCode::Char('-') => AppAction::DiffContext(false).into(),
+        KeyCode::Char('S') => AppAction::StashHunkInView.into(),
+        KeyCode::Char('B') => AppAction::OpenBranchPicker.into(),
+        KeyCode::Char('z') => CommandId::QuickStash.into(),
+        KeyCode::Char('N') => CommandId::NewBranch.into(),
+        KeyCode::Char('c') => AppAction::ToggleCommitDrawer.into(),
+        KeyCode::Char('n') if conflict => AppAction::ConflictNext.into(),
+        KeyCode::Char('p') if conflict => AppAction::ConflictPrev.into(),
+        KeyCode::Char('o') if conflict => AppAction::ConflictUseOurs.into(),
+        KeyCode::Char('t') if conflict => AppAction::ConflictUseTheirs.into(),
+        KeyCode::Char('b') if conflict => AppAction::ConflictUseBoth.into(),
+        KeyCode::Char('a') if conflict => AppAction::MarkResolved.into(),
+        KeyCode::Char('b') => CommandId::BlameFile.into(),
+        KeyCode::Char('s') if app.git.show_full_file => AppAction::CycleFullFileSource.into(),
+        KeyCode::Char('s') => AppAction::ToggleDiffMode.into(),
+        KeyCode::Char('{') | KeyCode::Char('}') => AppAction::JumpDiffAnchor {
+            files: false,
+            forward: key.code == KeyCode::Char('}'),
+        }
+        .into(),
+        KeyCode::Enter => {
+            if let Some(idx) = app.git.recent_selected {
+                AppAction::OpenRecentCommit(idx).into()
+            } else if app.git_focus == PaneFocus::Detail && app.git.dir_summary().is_some() {
+                AppAction::OpenDirSummaryFile(app.git.dir_summary_selected).into()
+            } else if app.git.selected_submodule().is_some() {
+                AppAction::EnterSubmodule.into()
+            } else {
+                // Expand/collapse sections and directories
+                AppAction::ToggleGitTreeExpand.into()
+            }
+        }
+        _ => return None,
+    })
+}
+
+fn log_inspect_key(key: &KeyEvent) -> Option<Msg> {
+    Some(match key.code {
+        KeyCode::Esc | KeyCode::Enter => AppAction::LogCloseInspect.into(),
+        KeyCode::Char('y') => AppAction::LogInspectCopyPrimary.into(),
+        KeyCode::Char('Y') => AppAction::LogInspectCopySecondary.into(),
+        _ => return None,
+    })
+}
+
+fn log_key(app: &App, key: &KeyEvent) -> Option<Msg> {
+    // Typing into the filter
+    if app.log_ui.filter_edit
+        && matches!(
+            key.code,
+            KeyCode::Char(_) | KeyCode::Enter | KeyCode::Backspace
+        )
+    {
+        return None;
+    }
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let subtab = app.log_ui.subtab;
+    let history = subtab == LogSubTab::History;
+    let stash = subtab == LogSubTab::Stash;
+    let commands = subtab == LogSubTab::Commands;
+    let other_detail = match app.log_ui.detail_mode {
+        LogDetailMode::Diff => LogDetailMode::Files,
+        LogDetailMode::Files => LogDetailMode::Diff,
+    };
+    Some(match key.code {
+        KeyCode::Enter | KeyCode::Char('a') if stash => AppAction::LogStashApply.into(),
+        KeyCode::Char('v') => AppAction::StartDiffSelection.into(),
+        KeyCode::Char('r') => AppAction::LogSwitch(LogSubTab::Reflog).into(),
+        KeyCode::Char('R') if history => AppAction::LogRevert(false).into(),
+        KeyCode::Char('R') => CommandId::RefreshGit.into(),
+        KeyCode::Char('X') if history => AppAction::LogReset.into(),
+        KeyCode::Char('O') if history || subtab == LogSubTab::Reflog => {
+            AppAction::LogCheckout.into()
+        }
+        KeyCode::Char('o') if subtab == LogSubTab::Reflog => AppAction::CycleReflogOp.into(),
+        KeyCode::Char('h') => AppAction::LogSwitch(LogSubTab::History).into(),
+        KeyCode::Char('t') => AppAction::LogSwitch(LogSubTab::Stash).into(),
+        KeyCode::Char('c') => AppAction::LogSwitch(LogSubTab::Commands).into(),
+        KeyCode::Char('x') if commands => AppAction::ClearGitLog.into(),
+        KeyCode::Char('p') if stash => AppAction::LogStashPop.into(),
+        KeyCode::Char('d') if stash => AppAction::LogStashDrop.into(),
+        KeyCode::Char('d') | KeyCode::Char('F') if history => {
+            AppAction::LogDetail(other_detail).into()
+        }
+        KeyCode::Char('m') if history => AppAction::CycleLogDiffBase.into(),
+        KeyCode::Char('f') if ctrl => CommandId::FindInCommit.into(),
+        KeyCode::Char('f') if history => AppAction::LogDetail(LogDetailMode::Files).into(),
+        KeyCode::Char('i') => AppAction::LogInspect.into(),
+        KeyCode::Char('L') if !commands => AppAction::LoadMoreLog.into(),
+        KeyCode::Char('z') => AppAction::LogToggleZoom.into(),
+        KeyCode::Char('{') | KeyCode::Char('}') if !commands => AppAction::JumpDiffAnchor {
+            files: false,
+            forward: key.code == KeyCode::Char('}'),
+        }
+        .into(),
+        KeyCode::Char('s') => AppAction::ToggleDiffMode.into(),
+        KeyCode::Char('w') => CommandId::ToggleWrapDiff.into(),
+        KeyCode::Char('#') => CommandId::ToggleDiffLineNumbers.into(),
+        KeyCode::Char('H') => CommandId::ToggleSyntaxHighlight.into(),
+        KeyCode::Char('b') => CommandId::ToggleRefsSidebar.into(),
+        KeyCode::Char(':') => CommandId::GoToCommit.into(),
+        KeyCode::Char('S') if history => CommandId::SearchFullHistory.into(),
+        KeyCode::Char(' ') if history => {
+            AppAction::ToggleLogMark(app.log_ui.history_state.selected()?).into()
+        }
+        KeyCode::Char('C') if history => AppAction::LogCherryPick.into(),
+        KeyCode::Char('B') => AppAction::OpenBranchPicker.into(),
+        KeyCode::Char('T') => CommandId::OpenTagPicker.into(),
+        KeyCode::Char('A') if !commands => AppAction::OpenAuthorPicker.into(),
+        _ => return None,
+    })
+}
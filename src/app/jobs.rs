@@ -0,0 +1,913 @@
+//! Background jobs and what the app does with their results
+//!
+//! Git commands, refreshes, History loads and AI requests run on their own thread and
+//! report back over a channel; [`App::tick`] polls the channels once per frame.
+
+use super::*;
+
+pub(crate) struct LogDiffJobOutput {
+    pub(crate) diff_lines: Vec<String>,
+    pub(crate) files_hash: Option<String>,
+    pub(crate) files: Option<Vec<git_ops::CommitFileChange>>,
+    pub(crate) files_selected: Option<usize>,
+    pub(crate) parent_count: usize,
+    /// Commit the diff is for, with its verified signature
+    pub(crate) hash: String,
+    pub(crate) signature: git_ops::Signature,
+}
+
+pub(crate) struct GitRefreshJobOutput {
+    pub(crate) repo_root: Option<PathBuf>,
+    pub(crate) branch: String,
+    pub(crate) detached: Option<String>,
+    pub(crate) upstream: Option<String>,
+    pub(crate) ahead: u32,
+    pub(crate) behind: u32,
+    pub(crate) entries: Vec<git::GitFileEntry>,
+    pub(crate) staged_stats: HashMap<String, (u32, u32)>,
+    pub(crate) worktree_stats: HashMap<String, (u32, u32)>,
+    /// Only loaded when commits need a review first
+    pub(crate) staged_diff: Option<String>,
+    pub(crate) recent_commits: Vec<git_ops::CommitEntry>,
+    pub(crate) signing: Option<&'static str>,
+    pub(crate) repo_state: git_ops::RepoState,
+}
+
+pub(crate) enum JobResult {
+    Git {
+        cmd: String,
+        result: Result<(), String>,
+        refresh: bool,
+        close_commit: bool,
+        elapsed: Duration,
+    },
+    GitRefresh {
+        request_id: u64,
+        current_path: PathBuf,
+        result: Result<Box<GitRefreshJobOutput>, String>,
+    },
+    Ai {
+        result: Result<String, String>,
+    },
+    LogReload {
+        history_limit: usize,
+        reflog_limit: usize,
+        stash_limit: usize,
+        history: Result<Vec<git_ops::CommitEntry>, String>,
+        reflog: Result<Vec<git_ops::ReflogEntry>, String>,
+        stash: Result<Vec<git_ops::StashEntry>, String>,
+    },
+    LogDiff {
+        request_id: u64,
+        result: Result<LogDiffJobOutput, String>,
+    },
+    LogSearch {
+        query: String,
+        result: Result<Vec<git_ops::CommitEntry>, String>,
+    },
+    LogHistory {
+        limit: usize,
+        result: Result<Vec<git_ops::CommitEntry>, String>,
+    },
+    LogCount {
+        history_ref: Option<String>,
+        result: Result<usize, String>,
+    },
+    LogReflog {
+        limit: usize,
+        result: Result<Vec<git_ops::ReflogEntry>, String>,
+    },
+    LogStash {
+        limit: usize,
+        result: Result<Vec<git_ops::StashEntry>, String>,
+    },
+}
+
+pub(crate) struct PendingJob {
+    pub(crate) rx: mpsc::Receiver<JobResult>,
+    /// Reported instead if the worker goes away without sending a result
+    pub(crate) lost: JobResult,
+}
+
+/// Git job waiting for the running one to finish
+pub(crate) struct QueuedGitJob {
+    pub(crate) cmd: String,
+    pub(crate) refresh: bool,
+    pub(crate) close_commit: bool,
+    pub(crate) run: Box<dyn FnOnce() -> Result<(), String> + Send>,
+}
+
+/// Jobs beyond this are rejected, so a stuck job can't pile up stale work
+pub(crate) const MAX_QUEUED_JOBS: usize = 16;
+
+/// Newest History rows that get a CI badge
+pub(crate) const CI_STATUS_COMMITS: usize = 20;
+/// How long a pending CI result is shown before it is asked for again
+pub(crate) const CI_PENDING_REFRESH: Duration = Duration::from_secs(30);
+
+/// A commit's hash with its CI status, or why it could not be fetched
+pub(crate) type CiResult = (String, Result<Option<forge::CiStatus>, String>);
+
+/// CI results for History, fetched in the background for the current branch
+#[derive(Default)]
+pub(crate) struct CiStatuses {
+    /// `None` for commits without CI, with when the answer came
+    pub(crate) known: HashMap<String, (Option<forge::CiStatus>, Instant)>,
+    pub(crate) rx: Option<mpsc::Receiver<CiResult>>,
+    /// Set by an error so a bad token isn't retried every frame; a reload clears it
+    pub(crate) failed: bool,
+}
+
+impl CiStatuses {
+    pub(crate) fn get(&self, hash: &str) -> Option<forge::CiStatus> {
+        self.known.get(hash).and_then(|(status, _)| *status)
+    }
+
+    pub(crate) fn is_stale(&self, hash: &str) -> bool {
+        match self.known.get(hash) {
+            Some((Some(forge::CiStatus::Pending), at)) => at.elapsed() >= CI_PENDING_REFRESH,
+            Some(_) => false,
+            None => true,
+        }
+    }
+}
+
+impl App {
+    /// Run a git job in the background, queueing it behind the running job if any.
+    pub(crate) fn start_git_job<F>(&mut self, cmd: String, refresh: bool, close_commit: bool, f: F)
+    where
+        F: FnOnce() -> Result<(), String> + Send + 'static,
+    {
+        if self.pending_job.is_some() {
+            if self.job_queue.len() >= MAX_QUEUED_JOBS {
+                self.set_status("Busy");
+                return;
+            }
+            self.set_status(format!(
+                "Queued: {} ({} pending)",
+                cmd,
+                self.job_queue.len() + 1
+            ));
+            self.job_queue.push_back(QueuedGitJob {
+                cmd,
+                refresh,
+                close_commit,
+                run: Box::new(f),
+            });
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        self.pending_job = Some(PendingJob {
+            rx,
+            lost: JobResult::Git {
+                cmd: cmd.clone(),
+                result: Err("Background job disconnected".to_string()),
+                refresh,
+                close_commit,
+                elapsed: Duration::ZERO,
+            },
+        });
+
+        thread::spawn(move || {
+            let start = Instant::now();
+            let result = f();
+            let _ = tx.send(JobResult::Git {
+                cmd,
+                result,
+                refresh,
+                close_commit,
+                elapsed: start.elapsed(),
+            });
+        });
+    }
+
+    pub(crate) fn start_ai_job<F>(&mut self, f: F)
+    where
+        F: FnOnce() -> Result<String, String> + Send + 'static,
+    {
+        if self.ai_job.is_some() {
+            self.commit.set_status("Busy");
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        self.ai_job = Some(PendingJob {
+            rx,
+            lost: JobResult::Ai {
+                result: Err("AI job disconnected".to_string()),
+            },
+        });
+
+        thread::spawn(move || {
+            let result = f();
+            let _ = tx.send(JobResult::Ai { result });
+        });
+    }
+
+    /// Per-frame housekeeping: finished background jobs, timers and file watches.
+    pub(crate) fn tick(&mut self) {
+        self.tick_pending_menu_action();
+        self.tick_clock();
+        self.tick_history_search();
+        self.poll_pending_job();
+        self.poll_slot(|app| &mut app.log_job);
+        self.poll_slot(|app| &mut app.ai_job);
+        self.poll_slot(|app| &mut app.git_refresh_job);
+        self.poll_clone_job();
+        self.poll_slot(|app| &mut app.log_diff_job);
+        self.poll_slot(|app| &mut app.log_count_job);
+        self.poll_ci_statuses();
+        self.poll_file_finder();
+        self.maybe_expire_status();
+        self.expire_pending_keys();
+        self.check_config_changed();
+        self.lock_screen_when_idle();
+        // Auto-refresh explorer when directory changes
+        if self.current_tab == Tab::Explorer {
+            self.check_auto_refresh();
+        }
+    }
+
+    /// Hand the result of the job in `slot` to [`App::handle_job_result`] once it is
+    /// done, freeing the slot. A worker that died without answering reports the
+    /// job's `lost` result, so the failure reaches the kind of job that was running.
+    pub(crate) fn poll_slot(&mut self, slot: fn(&mut App) -> &mut Option<PendingJob>) {
+        let Some(job) = slot(self) else {
+            return;
+        };
+        let msg = match job.rx.try_recv() {
+            Ok(msg) => Some(msg),
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => None,
+        };
+        if let Some(job) = slot(self).take() {
+            self.handle_job_result(msg.unwrap_or(job.lost));
+        }
+    }
+
+    pub(crate) fn poll_pending_job(&mut self) {
+        self.poll_slot(|app| &mut app.pending_job);
+
+        if self.pending_job.is_none()
+            && let Some(job) = self.job_queue.pop_front()
+        {
+            self.start_git_job(job.cmd, job.refresh, job.close_commit, job.run);
+        }
+    }
+
+    /// Collect CI results, then ask for the History rows on screen that have none
+    /// yet (or only a stale pending one).
+    pub(crate) fn poll_ci_statuses(&mut self) {
+        if let Some(rx) = &self.ci.rx {
+            let mut done = false;
+            let mut error = None;
+            loop {
+                match rx.try_recv() {
+                    Ok((hash, Ok(status))) => {
+                        self.ci.known.insert(hash, (status, Instant::now()));
+                    }
+                    Ok((_, Err(e))) => error = Some(e),
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        done = true;
+                        break;
+                    }
+                }
+            }
+            if let Some(e) = error {
+                self.ci.failed = true;
+                self.set_status(format!("CI status: {}", e));
+            }
+            if !done {
+                return;
+            }
+            self.ci.rx = None;
+        }
+
+        if self.forge_config.provider.is_none()
+            || !self.features.network
+            || self.ci.failed
+            || self.current_tab != Tab::Log
+            || self.log_ui.subtab != LogSubTab::History
+            || self.log_ui.history_ref.is_some()
+        {
+            return;
+        }
+        let hashes: Vec<String> = self
+            .log_ui
+            .history
+            .iter()
+            .take(CI_STATUS_COMMITS)
+            .filter(|e| self.ci.is_stale(&e.hash))
+            .map(|e| e.hash.clone())
+            .collect();
+        if hashes.is_empty() {
+            return;
+        }
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        let forge = self
+            .remote_web_url(&repo_root)
+            .and_then(|web| forge::Forge::new(&web, &self.forge_config, self.getenv));
+        let Some(forge) = forge else {
+            self.ci.failed = true;
+            self.set_status("CI status: no remote on a forge");
+            return;
+        };
+
+        let (tx, rx) = mpsc::channel();
+        self.ci.rx = Some(rx);
+        thread::spawn(move || {
+            for hash in hashes {
+                let result = forge.commit_status(&hash);
+                let failed = result.is_err();
+                if tx.send((hash, result)).is_err() || failed {
+                    break;
+                }
+            }
+        });
+    }
+
+    pub(crate) fn handle_git_diff_result(&mut self, result: git_diff_loader::GitDiffResult) {
+        use git_diff_loader::GitDiffResult;
+
+        match result {
+            GitDiffResult::Ready { request_id, lines } => {
+                // Ignore stale results
+                if request_id != self.git.diff_request_id {
+                    return;
+                }
+                self.git.set_diff_lines(lines);
+                self.git.diff_generation = self.git.diff_generation.wrapping_add(1);
+                self.git_diff_cache.invalidate();
+                if let Some(path) = self.git.selected_tree_entry().map(|e| e.path.clone()) {
+                    self.git.restore_diff_scroll(&path);
+                }
+                if let Some(line) = self.pending_diff_line.take() {
+                    self.scroll_git_diff_to_line(line);
+                }
+            }
+            GitDiffResult::Error { request_id, error } => {
+                // Ignore stale results
+                if request_id != self.git.diff_request_id {
+                    return;
+                }
+                self.git.set_diff_lines(vec![error]);
+                self.git.diff_generation = self.git.diff_generation.wrapping_add(1);
+                self.git_diff_cache.invalidate();
+            }
+            GitDiffResult::Cancelled => {
+                // Cancelled requests are ignored
+            }
+        }
+    }
+
+    pub(crate) fn handle_job_result(&mut self, msg: JobResult) {
+        match msg {
+            JobResult::Git {
+                cmd,
+                result,
+                refresh,
+                close_commit,
+                elapsed,
+            } => {
+                self.push_git_log(cmd.clone(), &result, elapsed);
+
+                if cmd.starts_with("update lzgit ") {
+                    self.update_in_progress = false;
+                    match &result {
+                        Ok(()) => {
+                            self.set_status("Update complete! Please restart lzgit.");
+                        }
+                        Err(e) => {
+                            self.set_status(format!("Update failed: {}", e));
+                        }
+                    }
+                }
+
+                if refresh {
+                    self.refresh_git_state();
+                    if self.current_tab == Tab::Log {
+                        self.refresh_log_data();
+                    }
+                }
+
+                if close_commit {
+                    self.commit.busy = false;
+                }
+
+                if cmd.starts_with("resolve conflict ") {
+                    // Re-read the markers that are left
+                    self.conflict_ui.path = None;
+                    self.ensure_conflicts_loaded();
+                }
+
+                let hook_failed = matches!(&result, Err(e) if e.starts_with(hooks::HOOK_FAILED));
+                let timed_out = matches!(&result, Err(e) if git_ops::is_timeout(e));
+                let remote_op = ["git fetch --prune", "git push"]
+                    .iter()
+                    .any(|base| cmd == *base || cmd == self.remote_command(base))
+                    || cmd.starts_with("git pull ")
+                    || self.custom_push.as_deref() == Some(cmd.as_str());
+                let blocked = matches!(&result, Err(e) if git_ops::blocked_by_local_changes(e))
+                    && (cmd.starts_with("git checkout ") || cmd.starts_with("git pull "));
+                let autostash_failed = cmd.starts_with("autostash ") && result.is_err();
+                let signing_failed =
+                    close_commit && matches!(&result, Err(e) if git_ops::signing_failed(e));
+                let wants_popup = signing_failed
+                    || hook_failed
+                    || timed_out
+                    || autostash_failed
+                    || (!close_commit && remote_op && !blocked);
+
+                let popup = if wants_popup {
+                    let (ok, body) = match &result {
+                        Ok(()) => (true, "Success".to_string()),
+                        Err(e) if signing_failed => {
+                            (false, format!("{}\n\n{}", e, git_ops::SIGNING_ADVICE))
+                        }
+                        Err(e) => (false, e.clone()),
+                    };
+                    let title = if signing_failed {
+                        "Commit signing failed".to_string()
+                    } else {
+                        cmd.clone()
+                    };
+                    let mut popup = OperationPopup::new(title, body, ok);
+                    if timed_out && remote_op {
+                        popup.retry = Some(cmd.clone());
+                    }
+                    Some(popup)
+                } else {
+                    None
+                };
+
+                match result {
+                    Ok(()) => {
+                        if close_commit {
+                            self.commit.open = false;
+                            self.commit.message.clear();
+                            self.commit.cursor = 0;
+                            self.commit.scroll_y = 0;
+                            self.commit.clear_overrides();
+                            // Guided mode keeps the type and scope for the next commit
+                            if let Some(parts) = self.commit.conventional.as_mut() {
+                                parts.breaking = false;
+                                self.commit.apply_conventional();
+                            }
+                            if self.commit.amend {
+                                self.commit.amend = false;
+                                self.commit.draft = None;
+                                self.commit.set_status("Amended");
+                                self.set_status("Amend succeeded");
+                            } else {
+                                self.commit.set_status("Committed");
+                                self.set_status("Commit succeeded");
+                            }
+                        } else {
+                            let msg = if cmd.starts_with("git add") {
+                                "Staged"
+                            } else if cmd.starts_with("git restore --staged -- ") {
+                                "Unstaged"
+                            } else if cmd.starts_with("git restore --staged --worktree") {
+                                "Discarded"
+                            } else if cmd.starts_with("git restore -- ") {
+                                "Discarded"
+                            } else if cmd.starts_with("git clean") {
+                                "Deleted"
+                            } else if cmd == "wip commit" {
+                                "Changes parked; \"Git: undo WIP commit\" brings them back"
+                            } else if cmd == "undo wip" {
+                                "WIP commit undone; its changes are staged"
+                            } else if cmd.starts_with("autostash ") {
+                                "Done; local changes restored"
+                            } else if cmd.starts_with("resolve conflict ") {
+                                "Conflict applied"
+                            } else if cmd == "stash hunk" {
+                                "Hunk stashed"
+                            } else if cmd.starts_with("git stash push") {
+                                "Stashed"
+                            } else if cmd.starts_with("snapshot ") {
+                                "Snapshot saved"
+                            } else if cmd.starts_with("restore snapshot ") {
+                                "Snapshot restored"
+                            } else if cmd.starts_with("action ") {
+                                "Action finished"
+                            } else if cmd.starts_with("git am ") {
+                                "Patches applied"
+                            } else if cmd.starts_with("git cherry-pick ") {
+                                "Cherry-picked"
+                            } else if cmd.starts_with("git revert --no-commit ") {
+                                "Revert staged; commit it when ready"
+                            } else if cmd.starts_with("git revert ") {
+                                "Reverted"
+                            } else if cmd.starts_with("git reset --") {
+                                "Branch reset"
+                            } else if cmd.starts_with("git checkout -b ") {
+                                "Branch created and checked out"
+                            } else if cmd.starts_with("git checkout --detach ") {
+                                "HEAD detached; N in the Git tab creates a branch here"
+                            } else if cmd.starts_with("git branch -m ") {
+                                "Branch renamed"
+                            } else if cmd.starts_with("git branch -d ")
+                                || cmd.starts_with("git branch -D ")
+                            {
+                                "Branch deleted"
+                            } else if cmd.starts_with("git branch --set-upstream-to=") {
+                                "Upstream set"
+                            } else if cmd.starts_with("git branch --unset-upstream ") {
+                                "Upstream removed"
+                            } else if cmd.starts_with("git branch --edit-description ") {
+                                "Description saved"
+                            } else if cmd.starts_with("git branch ") {
+                                "Branch created"
+                            } else if cmd.starts_with("git tag -d ") {
+                                "Tag deleted"
+                            } else if cmd.starts_with("git tag ") {
+                                "Tag created"
+                            } else if cmd.starts_with("git push ") {
+                                "Pushed"
+                            } else if cmd.starts_with("release ") && cmd.contains(" to ") {
+                                "Release tagged and pushed"
+                            } else if cmd.starts_with("release ") {
+                                "Release tagged"
+                            } else if cmd.starts_with("git remote add ") {
+                                "Remote added"
+                            } else if cmd.starts_with("git remote rename ") {
+                                "Remote renamed"
+                            } else if cmd.starts_with("git remote remove ") {
+                                "Remote removed"
+                            } else if cmd.starts_with("git remote set-url ") {
+                                "Remote URL changed"
+                            } else if cmd.starts_with("git submodule init") {
+                                "Submodules initialized"
+                            } else if cmd.starts_with("git submodule update") {
+                                "Submodules updated"
+                            } else if cmd.starts_with("git submodule sync") {
+                                "Submodule URLs synced"
+                            } else if cmd.starts_with("git switch --orphan ") {
+                                "Orphan branch created; commit to start its history"
+                            } else if cmd.starts_with("git init ") {
+                                "Repository initialized"
+                            } else if cmd.starts_with("git bundle create ") {
+                                "Bundle created"
+                            } else if cmd.starts_with("fetch bundle ") {
+                                "Fetched from bundle"
+                            } else if cmd.starts_with("pull bundle ") {
+                                "Pulled from bundle"
+                            } else {
+                                "Done"
+                            };
+                            if (cmd.starts_with("git branch ")
+                                || cmd.starts_with("git checkout -b "))
+                                && self.branch_ui.open
+                            {
+                                self.reload_branch_picker();
+                                self.branch_ui.status = Some(msg.to_string());
+                            }
+                            if (cmd.starts_with("git tag ") || cmd.starts_with("git push "))
+                                && self.tag_ui.open
+                            {
+                                self.reload_tag_picker();
+                                self.tag_ui.status = Some(msg.to_string());
+                            }
+                            if let Some(names) = cmd.strip_prefix("git remote rename ")
+                                && let Some((old, new)) = names.split_once(' ')
+                                && self.target_remote() == Some(old)
+                                && let Some((_, target)) = self.target_remote.as_mut()
+                            {
+                                *target = new.to_string();
+                            }
+                            if let Some(name) = cmd.strip_prefix("git remote remove ")
+                                && self.target_remote() == Some(name)
+                            {
+                                self.target_remote = None;
+                            }
+                            if cmd.starts_with("git remote ") && self.remote_ui.open {
+                                self.reload_remote_manager();
+                                self.remote_ui.status = Some(msg.to_string());
+                            }
+                            self.set_status(msg);
+                        }
+                    }
+                    Err(e) => {
+                        if close_commit {
+                            self.commit.set_status(e.clone());
+                            self.set_status("Commit failed");
+                        } else if let Some(op) = [
+                            ("git cherry-pick", "could not apply", "Cherry-pick"),
+                            ("git revert", "could not revert", "Revert"),
+                        ]
+                        .iter()
+                        .find(|(prefix, stopped, _)| cmd.starts_with(prefix) && e.contains(stopped))
+                        .map(|(_, _, op)| op)
+                        {
+                            // The conflicted files show in the Git tab's conflict view
+                            self.current_tab = Tab::Git;
+                            self.git_focus = PaneFocus::List;
+                            self.set_status(format!(
+                                "{} stopped on a conflict: resolve it, then Continue",
+                                op
+                            ));
+                        } else if blocked {
+                            let mut lines: Vec<String> =
+                                e.lines().take(8).map(String::from).collect();
+                            lines.push(String::new());
+                            lines.push(
+                                "Stash them, run it again and restore them afterwards?".to_string(),
+                            );
+                            self.confirm = Some(confirm::ConfirmDialog {
+                                title: " Local Changes In The Way ".to_string(),
+                                lines,
+                                confirm_label: "Stash and Retry".to_string(),
+                                action: ConfirmAction::AutostashRetry(cmd.clone()),
+                            });
+                        } else if let Some(name) = cmd.strip_prefix("git branch -d ")
+                            && e.contains("not fully merged")
+                        {
+                            self.confirm = Some(confirm::ConfirmDialog {
+                                title: " Delete Unmerged Branch ".to_string(),
+                                lines: vec![
+                                    format!("`{}` is not fully merged.", name),
+                                    String::new(),
+                                    "Its commits stay reachable only from the reflog.".to_string(),
+                                ],
+                                confirm_label: "Force Delete".to_string(),
+                                action: ConfirmAction::ForceDeleteBranch(name.to_string()),
+                            });
+                        } else {
+                            if cmd.starts_with("git branch ") && self.branch_ui.open {
+                                self.branch_ui.status = Some(e.clone());
+                            }
+                            if (cmd.starts_with("git tag ") || cmd.starts_with("git push "))
+                                && self.tag_ui.open
+                            {
+                                self.tag_ui.status = Some(e.clone());
+                            }
+                            if cmd.starts_with("git remote ") && self.remote_ui.open {
+                                self.remote_ui.status = Some(e.clone());
+                            }
+                            self.set_status(e);
+                        }
+                    }
+                }
+
+                if let Some(popup) = popup {
+                    self.operation_popup = Some(popup);
+                }
+            }
+            JobResult::GitRefresh {
+                request_id,
+                current_path,
+                result,
+            } => {
+                if request_id != self.git_refresh_request_id {
+                    return;
+                }
+
+                // Remember current selection before refresh
+                let prev_selected_path = self.git.selected_path();
+
+                match result {
+                    Ok(out) => {
+                        self.git.repo_root = out.repo_root;
+                        self.git.branch = out.branch;
+                        self.git.detached = out.detached;
+                        self.git.upstream = out.upstream;
+                        self.git.ahead = out.ahead;
+                        self.git.behind = out.behind;
+                        self.git.entries = out.entries;
+                        self.git.staged_stats = out.staged_stats;
+                        self.git.worktree_stats = out.worktree_stats;
+                        self.git.recent_commits = out.recent_commits;
+                        self.commit.signing = out.signing;
+                        if self
+                            .git
+                            .recent_selected
+                            .is_some_and(|i| i >= self.git.recent_commits.len())
+                        {
+                            self.git.recent_selected = None;
+                        }
+                        if let Some(diff) = out.staged_diff {
+                            self.review.check(&diff);
+                        }
+                        self.git.filtered.clear();
+                        self.git.list_state.select(None);
+                        self.git.selected_paths.clear();
+                        self.git.selection_anchor = None;
+                        let current_section = self.git.section;
+                        self.git.set_section(current_section);
+                        self.set_repo_state(out.repo_state);
+
+                        // Clear tree selection before rebuild
+                        self.git.tree_state.select(None);
+
+                        // Rebuild tree view
+                        self.git.build_tree();
+
+                        // Try to restore selection by path (file may have moved sections)
+                        let found = if let Some(ref path) = prev_selected_path {
+                            self.git.select_by_path(path)
+                        } else {
+                            false
+                        };
+
+                        // If not found, select first file
+                        if !found && !self.git.flat_tree.is_empty() {
+                            for (i, item) in self.git.flat_tree.iter().enumerate() {
+                                if item.node_type == git::FlatNodeType::File {
+                                    self.git.tree_state.select(Some(i));
+                                    break;
+                                }
+                            }
+                        }
+
+                        // Update diff for new selection
+                        if self.git.selected_tree_entry().is_some() {
+                            self.request_git_diff_update();
+                        } else {
+                            self.git.diff_lines.clear();
+                            self.git.diff_generation = self.git.diff_generation.wrapping_add(1);
+                            self.git_diff_cache.invalidate();
+                        }
+                    }
+                    Err(e) => {
+                        self.set_status(e);
+                        self.git.diff_lines.clear();
+                        self.git.diff_generation = self.git.diff_generation.wrapping_add(1);
+                        self.git_diff_cache.invalidate();
+                    }
+                }
+
+                if self.current_path == current_path {
+                    self.set_status("Git refreshed");
+                }
+            }
+            JobResult::Ai { result } => {
+                self.commit.busy = false;
+                match result {
+                    Ok(msg) => {
+                        self.commit.message = msg;
+                        self.commit.cursor = self.commit.message.chars().count();
+                        self.commit.scroll_y = 0;
+                        self.commit.set_status("AI message generated");
+                    }
+                    Err(e) => {
+                        self.commit.set_status(e);
+                    }
+                }
+            }
+            JobResult::LogReload {
+                history_limit,
+                reflog_limit,
+                stash_limit,
+                history,
+                reflog,
+                stash,
+            } => {
+                self.log_ui.status = None;
+                self.log_ui.history_limit = history_limit;
+                self.log_ui.reflog_limit = reflog_limit;
+                self.log_ui.stash_limit = stash_limit;
+
+                let mut first_err: Option<String> = None;
+
+                self.log_ui.searched_query = None;
+                match history {
+                    Ok(items) => self.log_ui.history = items,
+                    Err(e) => {
+                        if first_err.is_none() {
+                            first_err = Some(e.clone());
+                        }
+                        self.log_ui.history.clear();
+                    }
+                }
+
+                match reflog {
+                    Ok(items) => self.log_ui.reflog = items,
+                    Err(e) => {
+                        if first_err.is_none() {
+                            first_err = Some(e.clone());
+                        }
+                        self.log_ui.reflog.clear();
+                    }
+                }
+
+                match stash {
+                    Ok(items) => self.log_ui.stash = items,
+                    Err(e) => {
+                        if first_err.is_none() {
+                            first_err = Some(e.clone());
+                        }
+                        self.log_ui.stash.clear();
+                    }
+                }
+
+                self.log_ui.status = first_err;
+                self.log_ui.update_filtered();
+                self.refresh_log_diff();
+                self.apply_pending_log_commit();
+            }
+            JobResult::LogDiff { request_id, result } => {
+                if request_id != self.log_ui.diff_request_id {
+                    return;
+                }
+
+                match result {
+                    Ok(out) => {
+                        for entry in self
+                            .log_ui
+                            .history
+                            .iter_mut()
+                            .filter(|e| e.hash == out.hash)
+                        {
+                            entry.signature = out.signature;
+                        }
+                        self.log_ui.diff_lines = out.diff_lines;
+                        self.log_ui.commit_parent_count = out.parent_count;
+                        if let Some(files) = out.files {
+                            self.log_ui.files = files;
+                            self.log_ui.files_hash = out.files_hash;
+                            self.log_ui
+                                .files_state
+                                .select(out.files_selected.or(Some(0)));
+                        }
+                    }
+                    Err(e) => {
+                        self.log_ui.diff_lines = vec![e];
+                    }
+                }
+
+                self.log_ui.diff_generation = self.log_ui.diff_generation.wrapping_add(1);
+                self.log_diff_cache.invalidate();
+            }
+            JobResult::LogSearch { query, result } => {
+                self.log_ui.status = None;
+                let found = match result {
+                    Ok(found) => found,
+                    Err(e) => {
+                        self.log_ui.status = Some(e);
+                        return;
+                    }
+                };
+                let known: BTreeSet<String> =
+                    self.log_ui.history.iter().map(|e| e.hash.clone()).collect();
+                let older: Vec<_> = found
+                    .into_iter()
+                    .filter(|e| !known.contains(&e.hash))
+                    .collect();
+                self.log_ui.status = Some(if older.is_empty() {
+                    format!("No commits match \"{}\" in the full history", query)
+                } else {
+                    format!("Found {} older commits matching \"{}\"", older.len(), query)
+                });
+                self.log_ui.history.extend(older);
+                self.log_ui.update_filtered();
+                self.refresh_log_diff();
+            }
+            JobResult::LogCount {
+                history_ref,
+                result,
+            } => {
+                // A count for a ref no longer shown is stale
+                if history_ref == self.log_ui.history_ref {
+                    self.log_ui.history_total = result.ok();
+                }
+            }
+            JobResult::LogHistory { limit, result } => {
+                self.log_ui.status = None;
+                self.log_ui.history_limit = limit;
+                self.log_ui.searched_query = None;
+                match result {
+                    Ok(items) => self.log_ui.history = items,
+                    Err(e) => self.log_ui.status = Some(e),
+                }
+                self.log_ui.update_filtered();
+                self.refresh_log_diff();
+                self.apply_pending_log_commit();
+            }
+            JobResult::LogReflog { limit, result } => {
+                self.log_ui.status = None;
+                self.log_ui.reflog_limit = limit;
+                match result {
+                    Ok(items) => self.log_ui.reflog = items,
+                    Err(e) => self.log_ui.status = Some(e),
+                }
+                self.log_ui.update_filtered();
+                self.refresh_log_diff();
+            }
+            JobResult::LogStash { limit, result } => {
+                self.log_ui.status = None;
+                self.log_ui.stash_limit = limit;
+                match result {
+                    Ok(items) => self.log_ui.stash = items,
+                    Err(e) => self.log_ui.status = Some(e),
+                }
+                self.log_ui.update_filtered();
+                self.refresh_log_diff();
+            }
+        }
+    }
+}
@@ -0,0 +1,2140 @@
+//! The Log tab: History, Reflog and Stash lists, the commit diff and what can be done
+//! with a commit
+
+use super::*;
+
+/// Search in the diff shown in the Log tab
+pub(crate) struct CommitFind {
+    pub(crate) query: String,
+    pub(crate) matches: Vec<git::DiffMatch>,
+    pub(crate) selected: usize,
+}
+
+/// Mode chooser for resetting the current branch to a History commit
+pub(crate) struct ResetPicker {
+    pub(crate) hash: String,
+    pub(crate) short: String,
+    pub(crate) subject: String,
+    pub(crate) mode: git_ops::ResetMode,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LogSubTab {
+    History,
+    Reflog,
+    Stash,
+    Commands,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum LogDetailMode {
+    Diff,
+    Files,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LogPaneFocus {
+    Commits,
+    Files,
+    Diff,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum LogZoom {
+    None,
+    List,
+    Diff,
+}
+
+pub(crate) struct InspectUi {
+    pub(crate) open: bool,
+    pub(crate) title: String,
+    pub(crate) body: String,
+    pub(crate) scroll_y: u16,
+}
+
+impl InspectUi {
+    pub(crate) fn new() -> Self {
+        Self {
+            open: false,
+            title: String::new(),
+            body: String::new(),
+            scroll_y: 0,
+        }
+    }
+
+    pub(crate) fn close(&mut self) {
+        self.open = false;
+        self.title.clear();
+        self.body.clear();
+        self.scroll_y = 0;
+    }
+}
+
+pub(crate) struct LogUi {
+    pub(crate) status: Option<String>,
+
+    pub(crate) history_ref: Option<String>,
+
+    pub(crate) subtab: LogSubTab,
+    pub(crate) filter_query: String,
+    pub(crate) filter_edit: bool,
+    pub(crate) focus: LogPaneFocus,
+
+    pub(crate) history: Vec<git_ops::CommitEntry>,
+    pub(crate) reflog: Vec<git_ops::ReflogEntry>,
+    pub(crate) stash: Vec<git_ops::StashEntry>,
+    pub(crate) history_filtered: Vec<usize>,
+    pub(crate) reflog_filtered: Vec<usize>,
+    pub(crate) stash_filtered: Vec<usize>,
+    pub(crate) reflog_ops: Vec<git_ops::ReflogOp>,
+
+    pub(crate) detail_mode: LogDetailMode,
+    pub(crate) diff_mode: GitDiffMode,
+    pub(crate) zoom: LogZoom,
+
+    pub(crate) diff_lines: Vec<String>,
+    pub(crate) diff_scroll_y: u16,
+    pub(crate) diff_scroll_x: u16,
+    pub(crate) diff_generation: u64,
+    pub(crate) diff_request_id: u64,
+
+    pub(crate) files: Vec<git_ops::CommitFileChange>,
+    pub(crate) files_hash: Option<String>,
+    pub(crate) follow_file: Option<String>,
+    pub(crate) diff_base: git_ops::DiffBase,
+    pub(crate) commit_parent_count: usize,
+
+    pub(crate) history_limit: usize,
+    /// Commits reachable from the viewed ref, counted in the background
+    pub(crate) history_total: Option<usize>,
+    pub(crate) reflog_limit: usize,
+    pub(crate) stash_limit: usize,
+
+    pub(crate) history_state: ListState,
+    pub(crate) reflog_state: ListState,
+    pub(crate) stash_state: ListState,
+    pub(crate) command_state: ListState,
+
+    pub(crate) left_width: u16,
+    pub(crate) show_date_column: bool,
+    pub(crate) show_author_column: bool,
+    pub(crate) refs: refs::RefsSidebar,
+    /// History commits marked for cherry-pick, by hash
+    pub(crate) marked: BTreeSet<String>,
+    /// History index that Shift+click extends the marks from
+    pub(crate) mark_anchor: Option<usize>,
+    /// When the filter stopped matching loaded commits, to search the full history
+    pub(crate) search_due: Option<Instant>,
+    /// Filter already searched with `git log --grep`, so it runs once per query
+    pub(crate) searched_query: Option<String>,
+    pub(crate) inspect: InspectUi,
+
+    pub(crate) files_state: ListState,
+}
+
+impl LogUi {
+    pub(crate) fn new() -> Self {
+        Self {
+            status: None,
+
+            history_ref: None,
+
+            subtab: LogSubTab::History,
+            filter_query: String::new(),
+            filter_edit: false,
+            focus: LogPaneFocus::Commits,
+
+            history: Vec::new(),
+            reflog: Vec::new(),
+            stash: Vec::new(),
+            history_filtered: Vec::new(),
+            reflog_filtered: Vec::new(),
+            stash_filtered: Vec::new(),
+            reflog_ops: Vec::new(),
+
+            detail_mode: LogDetailMode::Diff,
+            diff_mode: GitDiffMode::Unified,
+            zoom: LogZoom::None,
+
+            diff_lines: Vec::new(),
+            diff_scroll_y: 0,
+            diff_scroll_x: 0,
+            diff_generation: 0,
+            diff_request_id: 0,
+
+            files: Vec::new(),
+            files_hash: None,
+            follow_file: None,
+            diff_base: git_ops::DiffBase::Parent(0),
+            commit_parent_count: 0,
+
+            history_limit: 200,
+            history_total: None,
+            reflog_limit: 200,
+            stash_limit: 200,
+
+            history_state: ListState::default(),
+            reflog_state: ListState::default(),
+            stash_state: ListState::default(),
+            command_state: ListState::default(),
+
+            left_width: 44,
+            show_date_column: true,
+            show_author_column: true,
+            refs: refs::RefsSidebar::new(),
+            marked: BTreeSet::new(),
+            mark_anchor: None,
+            search_due: None,
+            searched_query: None,
+            inspect: InspectUi::new(),
+
+            files_state: ListState::default(),
+        }
+    }
+
+    pub(crate) fn set_subtab(&mut self, subtab: LogSubTab) {
+        if self.subtab == subtab {
+            return;
+        }
+
+        self.subtab = subtab;
+        self.focus = LogPaneFocus::Commits;
+        self.diff_scroll_y = 0;
+        self.diff_scroll_x = 0;
+
+        match self.subtab {
+            LogSubTab::History => {}
+            LogSubTab::Reflog => {}
+            LogSubTab::Stash => {}
+            LogSubTab::Commands => {
+                self.command_state.select(Some(0));
+            }
+        }
+    }
+
+    pub(crate) fn set_detail_mode(&mut self, mode: LogDetailMode) {
+        if self.detail_mode == mode {
+            return;
+        }
+        self.detail_mode = mode;
+        self.diff_scroll_y = 0;
+        self.diff_scroll_x = 0;
+
+        match mode {
+            LogDetailMode::Files if self.subtab == LogSubTab::History => {
+                self.focus = LogPaneFocus::Files;
+            }
+            LogDetailMode::Diff => {
+                self.focus = LogPaneFocus::Diff;
+            }
+            _ => {}
+        }
+
+        if mode != LogDetailMode::Files {
+            self.files.clear();
+            self.files_hash = None;
+            self.files_state.select(None);
+        }
+    }
+
+    pub(crate) fn toggle_reflog_op(&mut self, op: git_ops::ReflogOp) {
+        if let Some(pos) = self.reflog_ops.iter().position(|o| *o == op) {
+            self.reflog_ops.remove(pos);
+        } else {
+            self.reflog_ops.push(op);
+        }
+        self.update_filtered();
+    }
+
+    /// Step a single-chip filter through the operation kinds, ending back at "all".
+    pub(crate) fn cycle_reflog_op(&mut self) {
+        let next = match self.reflog_ops.as_slice() {
+            [] => git_ops::REFLOG_OPS.first().copied(),
+            [cur] => git_ops::REFLOG_OPS
+                .iter()
+                .position(|o| o == cur)
+                .and_then(|i| git_ops::REFLOG_OPS.get(i + 1).copied()),
+            _ => None,
+        };
+        self.reflog_ops.clear();
+        self.reflog_ops.extend(next);
+        self.update_filtered();
+    }
+
+    pub(crate) fn active_state(&self) -> &ListState {
+        match self.subtab {
+            LogSubTab::History => &self.history_state,
+            LogSubTab::Reflog => &self.reflog_state,
+            LogSubTab::Stash => &self.stash_state,
+            LogSubTab::Commands => &self.command_state,
+        }
+    }
+
+    pub(crate) fn active_state_mut(&mut self) -> &mut ListState {
+        match self.subtab {
+            LogSubTab::History => &mut self.history_state,
+            LogSubTab::Reflog => &mut self.reflog_state,
+            LogSubTab::Stash => &mut self.stash_state,
+            LogSubTab::Commands => &mut self.command_state,
+        }
+    }
+
+    pub(crate) fn update_filtered(&mut self) {
+        let prev_hist = self
+            .history_state
+            .selected()
+            .and_then(|sel| self.history_filtered.get(sel).copied());
+        let prev_reflog = self
+            .reflog_state
+            .selected()
+            .and_then(|sel| self.reflog_filtered.get(sel).copied());
+        let prev_stash = self
+            .stash_state
+            .selected()
+            .and_then(|sel| self.stash_filtered.get(sel).copied());
+
+        let parsed = parse_log_filter_query(self.filter_query.as_str());
+        let author_tokens: Vec<String> = parsed.author.iter().map(|s| s.to_lowercase()).collect();
+        let ref_tokens: Vec<String> = parsed.refs.iter().map(|s| s.to_lowercase()).collect();
+        let tokens: Vec<String> = parsed.tokens.iter().map(|s| s.to_lowercase()).collect();
+        let is_empty = author_tokens.is_empty() && ref_tokens.is_empty() && tokens.is_empty();
+
+        let mut history_matches: Vec<(i32, usize)> = Vec::new();
+        let mut reflog_matches: Vec<(i32, usize)> = Vec::new();
+        let mut stash_matches: Vec<(i32, usize)> = Vec::new();
+
+        for (i, e) in self.history.iter().enumerate() {
+            if is_empty {
+                history_matches.push((0, i));
+                continue;
+            }
+
+            let author = e.author.to_lowercase();
+            let refs = e.decoration.to_lowercase();
+            let hay = format!("{} {} {}", e.short, e.subject, e.decoration).to_lowercase();
+
+            let mut score = 0i32;
+            let mut ok = true;
+
+            for t in &author_tokens {
+                if let Some(s) = token_score(author.as_str(), t.as_str()) {
+                    score += s;
+                } else {
+                    ok = false;
+                    break;
+                }
+            }
+            if ok {
+                for t in &ref_tokens {
+                    if let Some(s) = token_score(refs.as_str(), t.as_str()) {
+                        score += s;
+                    } else {
+                        ok = false;
+                        break;
+                    }
+                }
+            }
+            if ok {
+                for t in &tokens {
+                    if let Some(s) = token_score(hay.as_str(), t.as_str()) {
+                        score += s;
+                    } else {
+                        ok = false;
+                        break;
+                    }
+                }
+            }
+
+            if ok {
+                history_matches.push((score, i));
+            }
+        }
+
+        for (i, e) in self.reflog.iter().enumerate() {
+            if !self.reflog_ops.is_empty()
+                && !e.op().is_some_and(|op| self.reflog_ops.contains(&op))
+            {
+                continue;
+            }
+
+            if is_empty {
+                reflog_matches.push((0, i));
+                continue;
+            }
+
+            let refs = e.decoration.to_lowercase();
+            let hay = format!("{} {} {}", e.selector, e.subject, e.decoration).to_lowercase();
+
+            let mut score = 0i32;
+            let mut ok = true;
+
+            for t in &ref_tokens {
+                if let Some(s) = token_score(refs.as_str(), t.as_str()) {
+                    score += s;
+                } else {
+                    ok = false;
+                    break;
+                }
+            }
+            if ok {
+                for t in &tokens {
+                    if let Some(s) = token_score(hay.as_str(), t.as_str()) {
+                        score += s;
+                    } else {
+                        ok = false;
+                        break;
+                    }
+                }
+            }
+
+            if ok {
+                reflog_matches.push((score, i));
+            }
+        }
+
+        for (i, e) in self.stash.iter().enumerate() {
+            if is_empty {
+                stash_matches.push((0, i));
+                continue;
+            }
+
+            if !author_tokens.is_empty() {
+                continue;
+            }
+
+            let hay = format!("{} {}", e.selector, e.subject).to_lowercase();
+            let mut score = 0i32;
+            let mut ok = true;
+
+            for t in &tokens {
+                if let Some(s) = token_score(hay.as_str(), t.as_str()) {
+                    score += s;
+                } else {
+                    ok = false;
+                    break;
+                }
+            }
+
+            if ok {
+                stash_matches.push((score, i));
+            }
+        }
+
+        history_matches.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        reflog_matches.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        stash_matches.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+        self.history_filtered.clear();
+        self.history_filtered
+            .extend(history_matches.into_iter().map(|(_, i)| i));
+
+        self.reflog_filtered.clear();
+        self.reflog_filtered
+            .extend(reflog_matches.into_iter().map(|(_, i)| i));
+
+        self.stash_filtered.clear();
+        self.stash_filtered
+            .extend(stash_matches.into_iter().map(|(_, i)| i));
+
+        if self.history_filtered.is_empty() {
+            self.history_state.select(None);
+        } else if let Some(prev) =
+            prev_hist.and_then(|idx| self.history_filtered.iter().position(|i| *i == idx))
+        {
+            self.history_state.select(Some(prev));
+        } else {
+            self.history_state.select(Some(0));
+        }
+
+        if self.reflog_filtered.is_empty() {
+            self.reflog_state.select(None);
+        } else if let Some(prev) =
+            prev_reflog.and_then(|idx| self.reflog_filtered.iter().position(|i| *i == idx))
+        {
+            self.reflog_state.select(Some(prev));
+        } else {
+            self.reflog_state.select(Some(0));
+        }
+
+        if self.stash_filtered.is_empty() {
+            self.stash_state.select(None);
+        } else if let Some(prev) =
+            prev_stash.and_then(|idx| self.stash_filtered.iter().position(|i| *i == idx))
+        {
+            self.stash_state.select(Some(prev));
+        } else {
+            self.stash_state.select(Some(0));
+        }
+
+        // Nothing loaded matches but older commits exist: search them once typing pauses
+        let query = self.filter_query.trim();
+        let more_history = self.history.len() >= self.history_limit;
+        self.search_due = (!query.is_empty()
+            && self.history_filtered.is_empty()
+            && more_history
+            && self.searched_query.as_deref() != Some(query))
+        .then(Instant::now);
+    }
+}
+
+#[derive(Default, Debug)]
+struct LogFilterQuery {
+    author: Vec<String>,
+    refs: Vec<String>,
+    tokens: Vec<String>,
+}
+
+pub(crate) fn split_query_tokens(input: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut cur = String::new();
+    let mut quote: Option<char> = None;
+
+    for ch in input.chars() {
+        match quote {
+            Some(q) => {
+                cur.push(ch);
+                if ch == q {
+                    quote = None;
+                }
+            }
+            None => {
+                if ch == '"' || ch == '\'' {
+                    quote = Some(ch);
+                    cur.push(ch);
+                } else if ch.is_whitespace() {
+                    let t = cur.trim();
+                    if !t.is_empty() {
+                        out.push(t.to_string());
+                    }
+                    cur.clear();
+                } else {
+                    cur.push(ch);
+                }
+            }
+        }
+    }
+
+    let t = cur.trim();
+    if !t.is_empty() {
+        out.push(t.to_string());
+    }
+
+    out
+}
+
+fn parse_log_filter_query(input: &str) -> LogFilterQuery {
+    let mut q = LogFilterQuery::default();
+
+    for raw in split_query_tokens(input) {
+        let t = raw.trim();
+        if t.is_empty() {
+            continue;
+        }
+
+        fn strip_quotes(s: &str) -> &str {
+            let s = s.trim();
+            if s.len() >= 2 {
+                if let Some(rest) = s.strip_prefix('"').and_then(|x| x.strip_suffix('"')) {
+                    return rest;
+                }
+                if let Some(rest) = s.strip_prefix('\'').and_then(|x| x.strip_suffix('\'')) {
+                    return rest;
+                }
+            }
+            s
+        }
+
+        if let Some(rest) = t.strip_prefix('@') {
+            let rest = strip_quotes(rest);
+            if !rest.is_empty() {
+                q.author.push(rest.to_string());
+            }
+            continue;
+        }
+
+        if let Some(rest) = t.strip_prefix("author:").or_else(|| t.strip_prefix("a:")) {
+            let rest = strip_quotes(rest);
+            if !rest.is_empty() {
+                q.author.push(rest.to_string());
+            }
+            continue;
+        }
+
+        if let Some(rest) = t.strip_prefix("ref:").or_else(|| t.strip_prefix("tag:")) {
+            let rest = strip_quotes(rest);
+            if !rest.is_empty() {
+                q.refs.push(rest.to_string());
+            }
+            continue;
+        }
+
+        q.tokens.push(t.to_string());
+    }
+
+    q
+}
+
+impl App {
+    /// Mark or unmark a History commit for cherry-pick. With `range`, mark every
+    /// commit between the last toggled one and `idx` instead.
+    pub(crate) fn toggle_log_mark(&mut self, idx: usize, range: bool) {
+        let hash_at = |ui: &LogUi, i: usize| {
+            ui.history_filtered
+                .get(i)
+                .and_then(|abs| ui.history.get(*abs))
+                .map(|e| e.hash.clone())
+        };
+        let Some(hash) = hash_at(&self.log_ui, idx) else {
+            return;
+        };
+        if range && let Some(anchor) = self.log_ui.mark_anchor {
+            for i in anchor.min(idx)..=anchor.max(idx) {
+                if let Some(hash) = hash_at(&self.log_ui, i) {
+                    self.log_ui.marked.insert(hash);
+                }
+            }
+        } else if !self.log_ui.marked.remove(&hash) {
+            self.log_ui.marked.insert(hash);
+        }
+        self.log_ui.mark_anchor = Some(idx);
+        self.set_status(format!(
+            "{} commit(s) marked (C to cherry-pick, Esc to clear)",
+            self.log_ui.marked.len()
+        ));
+    }
+
+    /// Cherry-pick the marked History commits, or the selected one, after listing
+    /// them oldest first.
+    pub(crate) fn confirm_cherry_pick(&mut self) {
+        if self.git.repo_root.is_none() {
+            self.set_status("Not a git repository");
+            return;
+        }
+        let commits: Vec<&git_ops::CommitEntry> = if self.log_ui.marked.is_empty() {
+            self.selected_history_entry().into_iter().collect()
+        } else {
+            self.log_ui
+                .history
+                .iter()
+                .rev()
+                .filter(|e| self.log_ui.marked.contains(&e.hash))
+                .collect()
+        };
+        if commits.is_empty() {
+            self.set_status("No commit selected");
+            return;
+        }
+
+        const SHOWN: usize = 12;
+        let branch = if self.git.branch.is_empty() {
+            "HEAD".to_string()
+        } else {
+            self.git.branch.clone()
+        };
+        let mut lines = vec![
+            format!("Apply {} commit(s) onto {}:", commits.len(), branch),
+            String::new(),
+        ];
+        lines.extend(
+            commits
+                .iter()
+                .take(SHOWN)
+                .map(|e| format!("  {}  {}", e.short, e.subject)),
+        );
+        if commits.len() > SHOWN {
+            lines.push(format!("  … and {} more", commits.len() - SHOWN));
+        }
+        let hashes = commits.iter().map(|e| e.hash.clone()).collect();
+        self.confirm = Some(confirm::ConfirmDialog {
+            title: " Cherry-pick ".to_string(),
+            lines,
+            confirm_label: "Cherry-pick".to_string(),
+            action: ConfirmAction::CherryPick(hashes),
+        });
+    }
+
+    pub(crate) fn cherry_pick(&mut self, hashes: Vec<String>) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        self.log_ui.marked.clear();
+        self.log_ui.mark_anchor = None;
+        let cmd = format!("git cherry-pick ({} commits)", hashes.len());
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::cherry_pick(&repo_root, &hashes)
+        });
+    }
+
+    pub(crate) fn confirm_revert_commit(&mut self, no_commit: bool) {
+        if self.git.repo_root.is_none() {
+            self.set_status("Not a git repository");
+            return;
+        }
+        let Some(entry) = self.selected_history_entry() else {
+            self.set_status("No commit selected");
+            return;
+        };
+        let mut lines = vec![
+            format!("  {}  {}", entry.short, entry.subject),
+            String::new(),
+        ];
+        lines.push(if no_commit {
+            "The inverse changes are staged for you to commit.".to_string()
+        } else {
+            "A new commit undoes its changes.".to_string()
+        });
+        self.confirm = Some(confirm::ConfirmDialog {
+            title: " Revert Commit ".to_string(),
+            lines,
+            confirm_label: "Revert".to_string(),
+            action: ConfirmAction::RevertCommit(entry.hash.clone(), no_commit),
+        });
+    }
+
+    pub(crate) fn revert_commit(&mut self, hash: String, no_commit: bool) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let short = &hash[..hash.len().min(7)];
+        let cmd = if no_commit {
+            format!("git revert --no-commit {}", short)
+        } else {
+            format!("git revert {}", short)
+        };
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::revert_commit(&repo_root, &hash, no_commit)
+        });
+    }
+
+    /// Check out the selected History or Reflog commit on a detached HEAD, asking
+    /// first like a branch checkout does.
+    pub(crate) fn confirm_checkout_commit(&mut self) {
+        let Some(repo_root) = self.git.repo_root.as_ref() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let target = match self.log_ui.subtab {
+            LogSubTab::History => self
+                .selected_history_entry()
+                .map(|e| (e.hash.clone(), e.short.clone(), e.subject.clone())),
+            LogSubTab::Reflog => self.selected_reflog_entry().map(|e| {
+                let short = e.hash[..e.hash.len().min(7)].to_string();
+                (e.hash.clone(), short, e.subject.clone())
+            }),
+            _ => None,
+        };
+        let Some((hash, short, subject)) = target else {
+            self.set_status("No commit selected");
+            return;
+        };
+        let dirty = git_ops::is_dirty(repo_root).unwrap_or(true);
+        let dialog = confirm::ConfirmDialog {
+            title: " Checkout Commit ".to_string(),
+            lines: vec![
+                format!("  {}  {}", short, subject),
+                String::new(),
+                "HEAD is detached: new commits belong to no branch until you".to_string(),
+                "create one (N in the Git tab).".to_string(),
+            ],
+            confirm_label: "Checkout".to_string(),
+            action: ConfirmAction::CheckoutCommit(hash),
+        };
+        self.request_confirm(confirm::ConfirmKind::Checkout, dirty, dialog);
+    }
+
+    pub(crate) fn checkout_commit(&mut self, hash: String) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let cmd = format!("git checkout --detach {}", &hash[..hash.len().min(7)]);
+        let hook_config = self.active_hooks();
+        let env = self.hook_env(&[("LZGIT_TARGET", hash.clone())]);
+        self.start_git_job(cmd, true, false, move || {
+            hook_config.run(hooks::HookOp::Checkout, &repo_root, &env, || {
+                git_ops::checkout_detached(&repo_root, &hash)
+            })
+        });
+    }
+
+    pub(crate) fn open_reset_picker(&mut self) {
+        if self.git.repo_root.is_none() {
+            self.set_status("Not a git repository");
+            return;
+        }
+        let Some(entry) = self.selected_history_entry() else {
+            self.set_status("No commit selected");
+            return;
+        };
+        self.reset_picker = Some(ResetPicker {
+            hash: entry.hash.clone(),
+            short: entry.short.clone(),
+            subject: entry.subject.clone(),
+            mode: git_ops::ResetMode::Mixed,
+        });
+    }
+
+    pub(crate) fn reset_picker_key(&mut self, key: &KeyEvent) {
+        let Some(picker) = self.reset_picker.as_mut() else {
+            return;
+        };
+        if let Some(nav) = self.nav_keys.key(key, false) {
+            let modes = &git_ops::ResetMode::ALL;
+            let at = modes.iter().position(|m| *m == picker.mode).unwrap_or(0) as i32;
+            picker.mode = modes[(at + nav.delta()).clamp(0, modes.len() as i32 - 1) as usize];
+            return;
+        }
+        match key.code {
+            KeyCode::Char('s') => self.pick_reset_mode(git_ops::ResetMode::Soft),
+            KeyCode::Char('m') => self.pick_reset_mode(git_ops::ResetMode::Mixed),
+            KeyCode::Char('h') => self.pick_reset_mode(git_ops::ResetMode::Hard),
+            KeyCode::Enter => self.confirm_reset(),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn pick_reset_mode(&mut self, mode: git_ops::ResetMode) {
+        if let Some(picker) = self.reset_picker.as_mut() {
+            picker.mode = mode;
+            self.confirm_reset();
+        }
+    }
+
+    /// Soft and mixed resets keep every change and run straight away; a hard reset
+    /// goes through the `hard_reset` confirmation.
+    pub(crate) fn confirm_reset(&mut self) {
+        let Some(picker) = self.reset_picker.as_ref() else {
+            return;
+        };
+        if picker.mode != git_ops::ResetMode::Hard {
+            let (hash, mode) = (picker.hash.clone(), picker.mode);
+            self.reset_to_commit(hash, mode);
+            return;
+        }
+        let dirty = self
+            .git
+            .repo_root
+            .as_ref()
+            .is_some_and(|root| git_ops::is_dirty(root).unwrap_or(true));
+        let dialog = confirm::ConfirmDialog {
+            title: " Hard Reset ".to_string(),
+            lines: vec![
+                format!("  {}  {}", picker.short, picker.subject),
+                String::new(),
+                "Uncommitted changes are lost and later commits leave the branch.".to_string(),
+            ],
+            confirm_label: "Reset".to_string(),
+            action: ConfirmAction::ResetTo(picker.hash.clone(), picker.mode),
+        };
+        self.request_confirm(confirm::ConfirmKind::HardReset, dirty, dialog);
+        // With double_press the picker stays open for the second press
+        if self.confirm.is_some() {
+            self.reset_picker = None;
+        }
+    }
+
+    pub(crate) fn reset_to_commit(&mut self, hash: String, mode: git_ops::ResetMode) {
+        self.reset_picker = None;
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let cmd = format!(
+            "git reset --{} {}",
+            mode.label(),
+            &hash[..hash.len().min(7)]
+        );
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::reset_to(&repo_root, &hash, mode)
+        });
+    }
+
+    pub(crate) fn push_git_log(
+        &mut self,
+        cmd: String,
+        result: &Result<(), String>,
+        elapsed: Duration,
+    ) {
+        let ok = result.is_ok();
+        let detail = result.as_ref().err().cloned();
+        self.git_log.push_front(GitLogEntry {
+            when: Instant::now(),
+            cmd,
+            ok,
+            detail,
+            elapsed,
+        });
+        while self.git_log.len() > 200 {
+            self.git_log.pop_back();
+        }
+
+        if self.log_ui.subtab == LogSubTab::Commands
+            && self.log_ui.command_state.selected().is_none()
+        {
+            self.log_ui.command_state.select(Some(0));
+            self.refresh_log_diff();
+        }
+    }
+
+    pub(crate) fn refresh_log_data(&mut self) {
+        self.log_ui.status = None;
+        self.ci.failed = false;
+        self.log_diff_cache.invalidate();
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.log_ui.history.clear();
+            self.log_ui.reflog.clear();
+            self.log_ui.stash.clear();
+            self.log_ui.history_filtered.clear();
+            self.log_ui.reflog_filtered.clear();
+            self.log_ui.stash_filtered.clear();
+            self.log_ui.history_state.select(None);
+            self.log_ui.reflog_state.select(None);
+            self.log_ui.stash_state.select(None);
+            self.refresh_log_diff();
+            return;
+        };
+
+        // A reload supersedes whatever log job is still running; dropping its
+        // receiver discards the stale result.
+        if self.log_ui.refs.open {
+            self.load_refs();
+        }
+
+        self.start_history_count();
+
+        let history_limit = self.log_ui.history_limit;
+        let reflog_limit = self.log_ui.reflog_limit;
+        let stash_limit = self.log_ui.stash_limit;
+        let history_ref = self.log_ui.history_ref.clone();
+
+        let (tx, rx) = mpsc::channel();
+        self.log_job = Some(PendingJob {
+            rx,
+            lost: JobResult::LogReload {
+                history_limit,
+                reflog_limit,
+                stash_limit,
+                history: Err("Log job disconnected".to_string()),
+                reflog: Err("Log job disconnected".to_string()),
+                stash: Err("Log job disconnected".to_string()),
+            },
+        });
+
+        thread::spawn(move || {
+            let history = git_ops::list_history(&repo_root, history_limit, history_ref.as_deref());
+            let reflog = git_ops::list_reflog(&repo_root, reflog_limit);
+            let stash = git_ops::list_stashes(&repo_root, stash_limit);
+            let _ = tx.send(JobResult::LogReload {
+                history_limit,
+                reflog_limit,
+                stash_limit,
+                history,
+                reflog,
+                stash,
+            });
+        });
+    }
+
+    /// Count the viewed history in the background for the "N of ~M" title.
+    pub(crate) fn start_history_count(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        let history_ref = self.log_ui.history_ref.clone();
+        let (tx, rx) = mpsc::channel();
+        self.log_count_job = Some(PendingJob {
+            rx,
+            lost: JobResult::LogCount {
+                history_ref: history_ref.clone(),
+                result: Err("History count job disconnected".to_string()),
+            },
+        });
+
+        thread::spawn(move || {
+            let result = git_ops::count_history(&repo_root, history_ref.as_deref());
+            let _ = tx.send(JobResult::LogCount {
+                history_ref,
+                result,
+            });
+        });
+    }
+
+    /// Load the whole history of the viewed ref, for repos small enough to hold it.
+    pub(crate) fn load_all_history(&mut self) {
+        const LOAD_ALL_MAX: usize = 50_000;
+
+        if self.log_job.is_some() {
+            self.set_status("Busy");
+            return;
+        }
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let Some(total) = self.log_ui.history_total else {
+            self.set_status("Still counting commits, try again in a moment");
+            return;
+        };
+        if self.log_ui.history.len() >= total {
+            self.set_status(format!("All {} commits loaded", total));
+            return;
+        }
+        if total > LOAD_ALL_MAX {
+            self.set_status(format!(
+                "History has {} commits; load all is limited to {}",
+                total, LOAD_ALL_MAX
+            ));
+            return;
+        }
+
+        self.current_tab = Tab::Log;
+        self.log_ui.set_subtab(LogSubTab::History);
+        let history_ref = self.log_ui.history_ref.clone();
+        let (tx, rx) = mpsc::channel();
+        self.log_job = Some(PendingJob {
+            rx,
+            lost: JobResult::LogHistory {
+                limit: total,
+                result: Err("Log job disconnected".to_string()),
+            },
+        });
+
+        thread::spawn(move || {
+            let result = git_ops::list_history(&repo_root, total, history_ref.as_deref());
+            let _ = tx.send(JobResult::LogHistory {
+                limit: total,
+                result,
+            });
+        });
+    }
+
+    pub(crate) fn load_more_log_data(&mut self) {
+        if self.log_job.is_some() {
+            self.set_status("Busy");
+            return;
+        }
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        if self.log_ui.subtab == LogSubTab::History
+            && let Some(total) = self.log_ui.history_total
+            && self.log_ui.history.len() >= total
+        {
+            self.set_status(format!("All {} commits loaded", total));
+            return;
+        }
+
+        let (variant, limit) = match self.log_ui.subtab {
+            LogSubTab::History => ("history", self.log_ui.history_limit.saturating_add(200)),
+            LogSubTab::Reflog => ("reflog", self.log_ui.reflog_limit.saturating_add(200)),
+            LogSubTab::Stash => ("stash", self.log_ui.stash_limit.saturating_add(200)),
+            LogSubTab::Commands => {
+                self.set_status("No more to load");
+                return;
+            }
+        };
+
+        let history_ref = self.log_ui.history_ref.clone();
+
+        let lost = match variant {
+            "history" => JobResult::LogHistory {
+                limit,
+                result: Err("Log job disconnected".to_string()),
+            },
+            "reflog" => JobResult::LogReflog {
+                limit,
+                result: Err("Log job disconnected".to_string()),
+            },
+            _ => JobResult::LogStash {
+                limit,
+                result: Err("Log job disconnected".to_string()),
+            },
+        };
+        let (tx, rx) = mpsc::channel();
+        self.log_job = Some(PendingJob { rx, lost });
+
+        match variant {
+            "history" => {
+                thread::spawn(move || {
+                    let result = git_ops::list_history(&repo_root, limit, history_ref.as_deref());
+                    let _ = tx.send(JobResult::LogHistory { limit, result });
+                });
+            }
+            "reflog" => {
+                thread::spawn(move || {
+                    let result = git_ops::list_reflog(&repo_root, limit);
+                    let _ = tx.send(JobResult::LogReflog { limit, result });
+                });
+            }
+            "stash" => {
+                thread::spawn(move || {
+                    let result = git_ops::list_stashes(&repo_root, limit);
+                    let _ = tx.send(JobResult::LogStash { limit, result });
+                });
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Run the pending full-history search once the filter has been idle briefly.
+    pub(crate) fn tick_history_search(&mut self) {
+        let Some(since) = self.log_ui.search_due else {
+            return;
+        };
+        if since.elapsed() < Duration::from_millis(400) || self.log_job.is_some() {
+            return;
+        }
+        self.search_full_history();
+    }
+
+    /// Search all commits with `git log --grep/--author` and merge the matches into
+    /// the loaded history.
+    pub(crate) fn search_full_history(&mut self) {
+        self.log_ui.search_due = None;
+        let query = self.log_ui.filter_query.trim().to_string();
+        if query.is_empty() {
+            self.set_status("Type a filter first");
+            return;
+        }
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        if self.log_job.is_some() {
+            self.set_status("Busy");
+            return;
+        }
+
+        let parsed = parse_log_filter_query(&query);
+        if parsed.author.is_empty() && parsed.tokens.is_empty() {
+            // Ref filters only match decorations, which the loaded list already has
+            return;
+        }
+        self.log_ui.searched_query = Some(query.clone());
+        self.log_ui.status = Some("Searching full history…".to_string());
+        let history_ref = self.log_ui.history_ref.clone();
+
+        let (tx, rx) = mpsc::channel();
+        self.log_job = Some(PendingJob {
+            rx,
+            lost: JobResult::LogSearch {
+                query: query.clone(),
+                result: Err("Log job disconnected".to_string()),
+            },
+        });
+        thread::spawn(move || {
+            let result = git_ops::search_history(
+                &repo_root,
+                history_ref.as_deref(),
+                &parsed.author,
+                &parsed.tokens,
+                500,
+            );
+            let _ = tx.send(JobResult::LogSearch { query, result });
+        });
+    }
+
+    pub(crate) fn maybe_load_more_log_data(&mut self) {
+        if self.pending_job.is_some() {
+            return;
+        }
+
+        let sel = self.log_ui.active_state().selected().unwrap_or(0);
+        let active_len = self.active_log_len();
+        if active_len == 0 {
+            return;
+        }
+
+        let prefetch_start_idx = active_len.saturating_sub(10);
+        if sel < prefetch_start_idx {
+            return;
+        }
+
+        match self.log_ui.subtab {
+            LogSubTab::History => {
+                if !self.log_ui.history.is_empty()
+                    && self.log_ui.history.len() == self.log_ui.history_limit
+                {
+                    self.load_more_log_data();
+                }
+            }
+            LogSubTab::Reflog => {
+                if !self.log_ui.reflog.is_empty()
+                    && self.log_ui.reflog.len() == self.log_ui.reflog_limit
+                {
+                    self.load_more_log_data();
+                }
+            }
+            LogSubTab::Stash => {
+                if !self.log_ui.stash.is_empty()
+                    && self.log_ui.stash.len() == self.log_ui.stash_limit
+                {
+                    self.load_more_log_data();
+                }
+            }
+            LogSubTab::Commands => {}
+        }
+    }
+
+    pub(crate) fn refresh_log_diff(&mut self) {
+        self.log_ui.diff_request_id = self.log_ui.diff_request_id.wrapping_add(1);
+        let request_id = self.log_ui.diff_request_id;
+
+        self.log_ui.diff_scroll_y = 0;
+        self.log_ui.diff_scroll_x = 0;
+
+        self.log_ui.diff_lines = vec!["Loading diff…".to_string()];
+        self.log_ui.diff_generation = self.log_ui.diff_generation.wrapping_add(1);
+        self.log_diff_cache.invalidate();
+
+        match self.log_ui.subtab {
+            LogSubTab::History => {
+                let Some(repo_root) = self.git.repo_root.clone() else {
+                    self.log_ui.diff_lines = vec!["Not a git repository".to_string()];
+                    self.log_ui.diff_generation = self.log_ui.diff_generation.wrapping_add(1);
+                    self.log_diff_cache.invalidate();
+                    return;
+                };
+                let Some(entry) = self.selected_history_entry() else {
+                    self.log_ui.diff_lines = vec!["No commits".to_string()];
+                    self.log_ui.diff_generation = self.log_ui.diff_generation.wrapping_add(1);
+                    self.log_diff_cache.invalidate();
+                    return;
+                };
+
+                let hash = entry.hash.clone();
+                let detail_mode = self.log_ui.detail_mode;
+                let base = self.log_ui.diff_base;
+                let hexdump = self.binary_hexdump;
+
+                let follow_file = self.log_ui.follow_file.take();
+                let wanted_file: Option<String> = if follow_file.is_some() {
+                    follow_file
+                } else if detail_mode == LogDetailMode::Files
+                    && self.log_ui.files_hash.as_deref() == Some(hash.as_str())
+                {
+                    self.log_ui
+                        .files_state
+                        .selected()
+                        .and_then(|sel| self.log_ui.files.get(sel))
+                        .map(|f| f.path.clone())
+                } else {
+                    None
+                };
+
+                let (tx, rx) = mpsc::channel();
+                self.log_diff_job = Some(PendingJob {
+                    rx,
+                    lost: JobResult::LogDiff {
+                        request_id: self.log_ui.diff_request_id,
+                        result: Err("Diff job disconnected".to_string()),
+                    },
+                });
+                thread::spawn(move || {
+                    let parent_count = git_ops::commit_parents(&repo_root, hash.as_str())
+                        .map(|p| p.len())
+                        .unwrap_or(0);
+                    let signature = git_ops::commit_signature(&repo_root, hash.as_str());
+                    let result: Result<LogDiffJobOutput, String> = match detail_mode {
+                        LogDetailMode::Diff => {
+                            match git_ops::show_commit(&repo_root, hash.as_str(), base) {
+                                Ok(text) => Ok(LogDiffJobOutput {
+                                    diff_lines: if text.trim().is_empty() {
+                                        vec!["(no diff)".to_string()]
+                                    } else {
+                                        git_ops::describe_binary_diffs(&repo_root, &text, hexdump)
+                                            .lines()
+                                            .map(|l| l.to_string())
+                                            .collect()
+                                    },
+                                    files_hash: None,
+                                    files: None,
+                                    files_selected: None,
+                                    parent_count,
+                                    hash: hash.clone(),
+                                    signature,
+                                }),
+                                Err(e) => Err(format!("git show failed: {}", e)),
+                            }
+                        }
+                        LogDetailMode::Files => {
+                            match git_ops::list_commit_files(&repo_root, hash.as_str(), base) {
+                                Ok(files) => {
+                                    if files.is_empty() {
+                                        Ok(LogDiffJobOutput {
+                                            diff_lines: vec!["No files".to_string()],
+                                            files_hash: Some(hash.clone()),
+                                            files: Some(files),
+                                            files_selected: None,
+                                            parent_count,
+                                            hash: hash.clone(),
+                                            signature,
+                                        })
+                                    } else {
+                                        let selected_idx =
+                                            wanted_file.as_deref().and_then(|wanted| {
+                                                files.iter().position(|f| f.path.as_str() == wanted)
+                                            });
+                                        let idx = selected_idx.unwrap_or(0);
+                                        let file = files
+                                            .get(idx)
+                                            .map(|f| f.path.clone())
+                                            .unwrap_or_default();
+                                        let old_file =
+                                            files.get(idx).and_then(|f| f.old_path.clone());
+
+                                        match git_ops::show_commit_file_diff(
+                                            &repo_root,
+                                            hash.as_str(),
+                                            &file,
+                                            old_file.as_deref(),
+                                            base,
+                                        ) {
+                                            Ok(diff_text) => Ok(LogDiffJobOutput {
+                                                diff_lines: if diff_text.trim().is_empty() {
+                                                    vec!["(no diff)".to_string()]
+                                                } else {
+                                                    git_ops::describe_binary_diffs(
+                                                        &repo_root, &diff_text, hexdump,
+                                                    )
+                                                    .lines()
+                                                    .map(|l| l.to_string())
+                                                    .collect()
+                                                },
+                                                files_hash: Some(hash.clone()),
+                                                files: Some(files),
+                                                files_selected: Some(idx),
+                                                parent_count,
+                                                hash: hash.clone(),
+                                                signature,
+                                            }),
+                                            Err(e) => Err(format!("git show failed: {}", e)),
+                                        }
+                                    }
+                                }
+                                Err(e) => Err(format!("git show failed: {}", e)),
+                            }
+                        }
+                    };
+
+                    let _ = tx.send(JobResult::LogDiff { request_id, result });
+                });
+            }
+            LogSubTab::Reflog => {
+                self.log_ui.diff_lines = vec!["Reflog is list-only; use Inspect (i)".to_string()];
+                self.log_ui.diff_generation = self.log_ui.diff_generation.wrapping_add(1);
+                self.log_diff_cache.invalidate();
+            }
+            LogSubTab::Stash => {
+                let Some(entry) = self.selected_stash_entry() else {
+                    self.log_ui.diff_lines = vec!["No stashes".to_string()];
+                    self.log_ui.diff_generation = self.log_ui.diff_generation.wrapping_add(1);
+                    self.log_diff_cache.invalidate();
+                    return;
+                };
+
+                let selector = entry.selector.clone();
+                let subject = entry.subject.clone();
+
+                self.log_ui.diff_lines = vec![
+                    selector,
+                    String::new(),
+                    subject,
+                    String::new(),
+                    "Keys: a/apply  p/pop  d/drop  Enter=apply".to_string(),
+                ];
+                self.log_ui.diff_generation = self.log_ui.diff_generation.wrapping_add(1);
+                self.log_diff_cache.invalidate();
+            }
+            LogSubTab::Commands => {
+                let Some(sel) = self.log_ui.command_state.selected() else {
+                    self.log_ui.diff_lines = vec!["No commands".to_string()];
+                    self.log_ui.diff_generation = self.log_ui.diff_generation.wrapping_add(1);
+                    self.log_diff_cache.invalidate();
+                    return;
+                };
+                let Some(entry) = self.git_log.get(sel) else {
+                    return;
+                };
+
+                let mut lines = Vec::new();
+                lines.push(format!("Command: {}", entry.cmd));
+                lines.push(format!("Result: {}", if entry.ok { "OK" } else { "Error" }));
+                lines.push(format!("Duration: {:.2}s", entry.elapsed.as_secs_f64()));
+                lines.push(String::new());
+
+                if let Some(detail) = entry.detail.as_deref() {
+                    if detail.trim().is_empty() {
+                        lines.push("(no output)".to_string());
+                    } else {
+                        lines.extend(detail.lines().map(|l| l.to_string()));
+                    }
+                } else {
+                    lines.push("(no output)".to_string());
+                }
+
+                self.log_ui.diff_lines = lines;
+                self.log_ui.diff_generation = self.log_ui.diff_generation.wrapping_add(1);
+                self.log_diff_cache.invalidate();
+            }
+        }
+    }
+
+    pub(crate) fn step_log_file_revision(&mut self, delta: i32) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let Some(hash) = self.selected_history_entry().map(|e| e.hash.clone()) else {
+            return;
+        };
+        let Some(path) = self
+            .log_ui
+            .files_state
+            .selected()
+            .and_then(|sel| self.log_ui.files.get(sel))
+            .map(|f| f.path.clone())
+        else {
+            self.set_status("No file selected");
+            return;
+        };
+
+        let hashes = match git_ops::file_history_hashes(
+            &repo_root,
+            &path,
+            self.log_ui.history_ref.as_deref(),
+        ) {
+            Ok(h) => h,
+            Err(e) => {
+                self.set_status(e);
+                return;
+            }
+        };
+
+        let Some(cur) = hashes.iter().position(|h| *h == hash) else {
+            self.set_status("Commit not in file history");
+            return;
+        };
+        let target = cur as i64 + delta as i64;
+        let Some(target_hash) = usize::try_from(target).ok().and_then(|i| hashes.get(i)) else {
+            self.set_status(if delta > 0 {
+                "No older revision of this file"
+            } else {
+                "No newer revision of this file"
+            });
+            return;
+        };
+
+        let pos = self.log_ui.history_filtered.iter().position(|idx| {
+            self.log_ui
+                .history
+                .get(*idx)
+                .is_some_and(|e| e.hash == *target_hash)
+        });
+        let Some(pos) = pos else {
+            self.set_status("Revision not in loaded history (L to load more)");
+            return;
+        };
+
+        self.log_ui.follow_file = Some(path);
+        self.log_ui.history_state.select(Some(pos));
+        self.log_ui.focus = LogPaneFocus::Files;
+        self.refresh_log_diff();
+        self.maybe_load_more_log_data();
+    }
+
+    pub(crate) fn set_log_diff_base(&mut self, base: git_ops::DiffBase) {
+        if self.log_ui.diff_base == base {
+            return;
+        }
+        self.log_ui.diff_base = base;
+        self.refresh_log_diff();
+    }
+
+    /// Cycle parent 1 → parent 2 → … → combined for the selected merge commit.
+    pub(crate) fn cycle_log_diff_base(&mut self) {
+        let n = self.log_ui.commit_parent_count;
+        if n < 2 {
+            self.set_status("Not a merge commit");
+            return;
+        }
+        let next = match self.log_ui.diff_base {
+            git_ops::DiffBase::Parent(i) if i + 1 < n => git_ops::DiffBase::Parent(i + 1),
+            git_ops::DiffBase::Parent(_) => git_ops::DiffBase::Combined,
+            git_ops::DiffBase::Combined => git_ops::DiffBase::Parent(0),
+        };
+        self.set_log_diff_base(next);
+    }
+
+    pub(crate) fn active_log_len(&self) -> usize {
+        match self.log_ui.subtab {
+            LogSubTab::History => self.log_ui.history_filtered.len(),
+            LogSubTab::Reflog => self.log_ui.reflog_filtered.len(),
+            LogSubTab::Stash => self.log_ui.stash_filtered.len(),
+            LogSubTab::Commands => self.git_log.len(),
+        }
+    }
+
+    pub(crate) fn set_log_subtab(&mut self, subtab: LogSubTab) {
+        self.log_ui.inspect.close();
+        self.log_ui.set_subtab(subtab);
+
+        if self.log_ui.subtab == LogSubTab::Reflog {
+            self.log_ui.zoom = LogZoom::List;
+            self.log_ui.focus = LogPaneFocus::Commits;
+        }
+
+        if self.log_ui.subtab == LogSubTab::Commands {
+            if self.git_log.is_empty() {
+                self.log_ui.command_state.select(None);
+            } else if self
+                .log_ui
+                .command_state
+                .selected()
+                .map(|i| i >= self.git_log.len())
+                .unwrap_or(true)
+            {
+                self.log_ui.command_state.select(Some(0));
+            }
+        } else {
+            self.log_ui.update_filtered();
+
+            if self.log_ui.subtab == LogSubTab::History && !self.log_ui.history_filtered.is_empty()
+            {
+                if self
+                    .log_ui
+                    .history_state
+                    .selected()
+                    .map(|i| i >= self.log_ui.history_filtered.len())
+                    .unwrap_or(true)
+                {
+                    self.log_ui.history_state.select(Some(0));
+                }
+            }
+            if self.log_ui.subtab == LogSubTab::Reflog && !self.log_ui.reflog_filtered.is_empty() {
+                if self
+                    .log_ui
+                    .reflog_state
+                    .selected()
+                    .map(|i| i >= self.log_ui.reflog_filtered.len())
+                    .unwrap_or(true)
+                {
+                    self.log_ui.reflog_state.select(Some(0));
+                }
+            }
+        }
+
+        self.refresh_log_diff();
+    }
+
+    pub(crate) fn select_log_item(&mut self, idx: usize) {
+        if idx >= self.active_log_len() {
+            return;
+        }
+
+        let prev = self.log_ui.active_state().selected();
+        if prev == Some(idx) {
+            self.maybe_load_more_log_data();
+            return;
+        }
+
+        self.log_ui.active_state_mut().select(Some(idx));
+        self.log_ui.focus = LogPaneFocus::Commits;
+        self.log_ui.diff_scroll_y = 0;
+        self.log_ui.diff_scroll_x = 0;
+        self.refresh_log_diff();
+        self.maybe_load_more_log_data();
+    }
+
+    pub(crate) fn select_log_file(&mut self, idx: usize) {
+        if idx >= self.log_ui.files.len() {
+            return;
+        }
+        self.log_ui.files_state.select(Some(idx));
+        self.log_ui.focus = LogPaneFocus::Files;
+        self.log_ui.diff_scroll_y = 0;
+        self.log_ui.diff_scroll_x = 0;
+        self.refresh_log_diff();
+    }
+
+    pub(crate) fn move_log_file_selection(&mut self, delta: i32) {
+        let len = self.log_ui.files.len();
+        if len == 0 {
+            self.log_ui.files_state.select(None);
+            return;
+        }
+
+        let cur = self.log_ui.files_state.selected().unwrap_or(0) as i32;
+        let next = (cur + delta).clamp(0, len.saturating_sub(1) as i32);
+        self.select_log_file(next as usize);
+    }
+
+    pub(crate) fn move_log_selection(&mut self, delta: i32) {
+        let len = self.active_log_len();
+        if len == 0 {
+            self.log_ui.active_state_mut().select(None);
+            return;
+        }
+
+        let cur = self.log_ui.active_state().selected().unwrap_or(0) as i32;
+        let next = (cur + delta).clamp(0, len.saturating_sub(1) as i32);
+        if next == cur {
+            self.maybe_load_more_log_data();
+            return;
+        }
+        self.select_log_item(next as usize);
+    }
+
+    pub(crate) fn toggle_refs_sidebar(&mut self) {
+        self.log_ui.refs.open = !self.log_ui.refs.open;
+        if self.log_ui.refs.open {
+            self.load_refs();
+        }
+        self.save_persisted_ui_settings();
+    }
+
+    pub(crate) fn load_refs(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.log_ui.refs.set_refs(Vec::new());
+            return;
+        };
+        match git_ops::list_refs(&repo_root) {
+            Ok(list) => self.log_ui.refs.set_refs(list),
+            Err(e) => self.log_ui.status = Some(e),
+        }
+    }
+
+    /// Click on a sidebar row: folders toggle, refs become the history being viewed.
+    pub(crate) fn select_ref_item(&mut self, idx: usize) {
+        self.log_ui.refs.list_state.select(Some(idx));
+        let Some(entry) = self.log_ui.refs.entry_at(idx).cloned() else {
+            self.log_ui.refs.toggle(idx);
+            return;
+        };
+        self.log_ui.history_ref = if entry.is_current {
+            None
+        } else {
+            Some(entry.name)
+        };
+        self.log_ui.set_subtab(LogSubTab::History);
+        self.refresh_log_data();
+    }
+
+    pub(crate) fn checkout_ref(&mut self, entry: refs::RefEntry) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        match git_ops::is_dirty(&repo_root) {
+            Ok(false) => {}
+            Ok(true) => {
+                self.set_status("Uncommitted changes: use the branch picker (B) to force");
+                return;
+            }
+            Err(e) => {
+                self.set_status(e);
+                return;
+            }
+        }
+
+        let branch = branch::BranchEntry {
+            name: entry.name.clone(),
+            is_current: false,
+            is_remote: entry.kind == refs::RefKind::Remote,
+            upstream: None,
+            track: None,
+            description: None,
+            worktree: None,
+        };
+        let cmd = if branch.is_remote {
+            format!("git checkout --track {}", entry.name)
+        } else {
+            format!("git checkout {}", entry.name)
+        };
+        let hook_config = self.active_hooks();
+        let env = self.hook_env(&[("LZGIT_TARGET", entry.name.clone())]);
+        self.log_ui.history_ref = None;
+        self.start_git_job(cmd, true, false, move || {
+            hook_config.run(hooks::HookOp::Checkout, &repo_root, &env, || {
+                git_ops::checkout_branch_entry(&repo_root, &branch)
+            })
+        });
+    }
+
+    pub(crate) fn delete_ref(&mut self, entry: refs::RefEntry) {
+        if self.git.repo_root.is_none() {
+            return;
+        }
+        if self.log_ui.history_ref.as_deref() == Some(entry.name.as_str()) {
+            self.log_ui.history_ref = None;
+        }
+        let name = entry.name;
+        if entry.kind == refs::RefKind::Tag {
+            self.delete_tag(name);
+        } else {
+            self.delete_branch(name, false);
+        }
+    }
+
+    /// Show a commit from the strip under the Git tree in History.
+    pub(crate) fn open_recent_commit(&mut self, idx: usize) {
+        if let Some(hash) = self.git.recent_commits.get(idx).map(|c| c.hash.clone()) {
+            self.git.recent_selected = None;
+            self.show_log_commit(&hash);
+        }
+    }
+
+    /// Switch to Log history and select the commit `rev` resolves to.
+    pub(crate) fn show_log_commit(&mut self, rev: &str) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let hash = match git_ops::resolve_commit(&repo_root, rev) {
+            Ok(h) => h,
+            Err(e) => {
+                self.set_status(e);
+                return;
+            }
+        };
+
+        self.current_tab = Tab::Log;
+        self.set_log_subtab(LogSubTab::History);
+        let loaded = self.log_ui.history.iter().any(|e| e.hash == hash);
+        self.pending_log_commit = Some(hash.clone());
+        if loaded {
+            self.apply_pending_log_commit();
+            return;
+        }
+
+        // Load history deep enough to reach the commit, or view it on its own
+        // when it isn't part of the branch being shown.
+        match git_ops::commits_since(&repo_root, &hash, self.log_ui.history_ref.as_deref()) {
+            Ok(Some(depth)) => {
+                self.log_ui.history_limit = self.log_ui.history_limit.max(depth + 100);
+            }
+            Ok(None) => {
+                self.log_ui.history_ref = Some(hash);
+                self.set_status(format!(
+                    "{} is not on the current branch, showing its history",
+                    rev
+                ));
+            }
+            Err(e) => {
+                self.set_status(e);
+                return;
+            }
+        }
+        self.refresh_log_data();
+    }
+
+    pub(crate) fn open_goto_commit(&mut self) {
+        self.goto_commit_input = Some(String::new());
+    }
+
+    /// Search the diff of the commit shown in the Log tab, starting from the last term.
+    pub(crate) fn open_commit_find(&mut self) {
+        if self.current_tab != Tab::Log
+            || self.log_ui.subtab == LogSubTab::Commands
+            || !self
+                .log_ui
+                .diff_lines
+                .iter()
+                .any(|l| l.starts_with("diff --git "))
+        {
+            self.set_status("No commit diff to search");
+            return;
+        }
+        let query = self.commit_find_query.clone();
+        let matches = git::find_in_diff(&self.log_ui.diff_lines, &query);
+        self.commit_find = Some(CommitFind {
+            query,
+            matches,
+            selected: 0,
+        });
+    }
+
+    pub(crate) fn commit_find_key(&mut self, key: &KeyEvent) {
+        let Some(find) = self.commit_find.as_mut() else {
+            return;
+        };
+        let last = find.matches.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Down => find.selected = (find.selected + 1).min(last),
+            KeyCode::Up => find.selected = find.selected.saturating_sub(1),
+            KeyCode::PageDown => find.selected = (find.selected + 10).min(last),
+            KeyCode::PageUp => find.selected = find.selected.saturating_sub(10),
+            KeyCode::Enter => {
+                let idx = find.selected;
+                self.update(Msg::Action(AppAction::PickCommitFind(idx)));
+                return;
+            }
+            KeyCode::Backspace => {
+                find.query.pop();
+                self.update_commit_find();
+            }
+            KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                find.query.push(ch);
+                self.update_commit_find();
+            }
+            _ => return,
+        }
+        self.jump_to_commit_find();
+    }
+
+    pub(crate) fn update_commit_find(&mut self) {
+        let Some(find) = self.commit_find.as_mut() else {
+            return;
+        };
+        find.matches = git::find_in_diff(&self.log_ui.diff_lines, &find.query);
+        find.selected = 0;
+        self.commit_find_query = find.query.clone();
+    }
+
+    /// Scroll the Log diff so the selected match sits near the top, in the unified
+    /// view whose rows the match indexes.
+    pub(crate) fn jump_to_commit_find(&mut self) {
+        let Some(line) = self
+            .commit_find
+            .as_ref()
+            .and_then(|f| f.matches.get(f.selected))
+            .map(|m| m.line)
+        else {
+            return;
+        };
+        self.log_ui.diff_mode = GitDiffMode::Unified;
+        self.log_ui.focus = LogPaneFocus::Diff;
+        if let Some(row) = self.diff_line_rows(Tab::Log).get(line) {
+            self.log_ui.diff_scroll_y = row.saturating_sub(3).min(u16::MAX as usize) as u16;
+        }
+    }
+
+    pub(crate) fn apply_pending_log_commit(&mut self) {
+        let Some(hash) = self.pending_log_commit.take() else {
+            return;
+        };
+        let pos = self.log_ui.history_filtered.iter().position(|idx| {
+            self.log_ui
+                .history
+                .get(*idx)
+                .is_some_and(|e| e.hash == hash)
+        });
+        let loaded = self.log_ui.history.iter().any(|e| e.hash == hash);
+        match pos {
+            Some(pos) => self.select_log_item(pos),
+            None if loaded && !self.log_ui.filter_query.is_empty() => {
+                // Hidden by the filter: clear it rather than leave the jump dangling
+                self.log_ui.filter_query.clear();
+                self.log_ui.update_filtered();
+                self.pending_log_commit = Some(hash);
+                self.apply_pending_log_commit();
+            }
+            None => self.set_status("Commit not in loaded history (L to load more)"),
+        }
+    }
+
+    pub(crate) fn selected_history_entry(&self) -> Option<&git_ops::CommitEntry> {
+        let sel = self.log_ui.history_state.selected()?;
+        let idx = *self.log_ui.history_filtered.get(sel)?;
+        self.log_ui.history.get(idx)
+    }
+
+    pub(crate) fn selected_reflog_entry(&self) -> Option<&git_ops::ReflogEntry> {
+        let sel = self.log_ui.reflog_state.selected()?;
+        let idx = *self.log_ui.reflog_filtered.get(sel)?;
+        self.log_ui.reflog.get(idx)
+    }
+
+    pub(crate) fn selected_stash_entry(&self) -> Option<&git_ops::StashEntry> {
+        let sel = self.log_ui.stash_state.selected()?;
+        let idx = *self.log_ui.stash_filtered.get(sel)?;
+        self.log_ui.stash.get(idx)
+    }
+
+    pub(crate) fn selected_log_hash(&self) -> Option<String> {
+        match self.log_ui.subtab {
+            LogSubTab::History => self.selected_history_entry().map(|e| e.hash.clone()),
+            LogSubTab::Reflog => self.selected_reflog_entry().map(|e| e.hash.clone()),
+            LogSubTab::Stash => self.selected_stash_entry().map(|e| e.selector.clone()),
+            LogSubTab::Commands => self
+                .log_ui
+                .command_state
+                .selected()
+                .and_then(|i| self.git_log.get(i))
+                .map(|e| e.cmd.clone()),
+        }
+    }
+
+    pub(crate) fn selected_log_subject(&self) -> Option<String> {
+        match self.log_ui.subtab {
+            LogSubTab::History => self.selected_history_entry().map(|e| e.subject.clone()),
+            LogSubTab::Reflog => self.selected_reflog_entry().map(|e| e.subject.clone()),
+            LogSubTab::Stash => self.selected_stash_entry().map(|e| e.subject.clone()),
+            LogSubTab::Commands => None,
+        }
+    }
+
+    pub(crate) fn selected_log_command(&self) -> Option<String> {
+        if self.log_ui.subtab != LogSubTab::Commands {
+            return None;
+        }
+        let sel = self.log_ui.command_state.selected()?;
+        let entry = self.git_log.get(sel)?;
+        Some(entry.cmd.clone())
+    }
+
+    pub(crate) fn open_log_inspect(&mut self) {
+        let (title, body) = match self.log_ui.subtab {
+            LogSubTab::History => {
+                let Some(e) = self.selected_history_entry() else {
+                    self.set_status("No selection");
+                    return;
+                };
+
+                let title = format!("Inspect {}", e.short);
+
+                let body = if let Some(repo_root) = self.git.repo_root.clone() {
+                    match git_ops::show_commit_header(&repo_root, &e.hash) {
+                        Ok(text) => text,
+                        Err(err) => {
+                            let mut out = String::new();
+                            out.push_str("git show failed: ");
+                            out.push_str(&err);
+                            out.push('\n');
+                            out.push('\n');
+                            out.push_str("SHA: ");
+                            out.push_str(&e.hash);
+                            out.push('\n');
+                            let badges = git_decoration_tokens(&e.decoration)
+                                .into_iter()
+                                .take(8)
+                                .map(|t| format!("[{}]", t))
+                                .collect::<Vec<_>>()
+                                .join(" ");
+                            if !badges.is_empty() {
+                                out.push_str("Refs: ");
+                                out.push_str(&badges);
+                                out.push('\n');
+                            }
+                            out.push_str("Date: ");
+                            out.push_str(&e.date);
+                            out.push('\n');
+                            out.push_str("Author: ");
+                            out.push_str(&e.author);
+                            out.push('\n');
+                            out.push('\n');
+                            out.push_str("Subject:\n");
+                            out.push_str(&e.subject);
+                            out.push('\n');
+                            out
+                        }
+                    }
+                } else {
+                    let mut out = String::new();
+                    out.push_str("SHA: ");
+                    out.push_str(&e.hash);
+                    out.push('\n');
+                    let badges = git_decoration_tokens(&e.decoration)
+                        .into_iter()
+                        .take(8)
+                        .map(|t| format!("[{}]", t))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    if !badges.is_empty() {
+                        out.push_str("Refs: ");
+                        out.push_str(&badges);
+                        out.push('\n');
+                    }
+                    out.push_str("Date: ");
+                    out.push_str(&e.date);
+                    out.push('\n');
+                    out.push_str("Author: ");
+                    out.push_str(&e.author);
+                    out.push('\n');
+                    out.push('\n');
+                    out.push_str("Subject:\n");
+                    out.push_str(&e.subject);
+                    out.push('\n');
+                    out
+                };
+
+                (title, body)
+            }
+            LogSubTab::Reflog => {
+                let Some(e) = self.selected_reflog_entry() else {
+                    self.set_status("No selection");
+                    return;
+                };
+
+                let title = format!("Inspect {}", e.selector);
+
+                let body = if let Some(repo_root) = self.git.repo_root.clone() {
+                    match git_ops::show_commit_header(&repo_root, &e.hash) {
+                        Ok(text) => text,
+                        Err(err) => {
+                            let mut out = String::new();
+                            out.push_str("git show failed: ");
+                            out.push_str(&err);
+                            out.push('\n');
+                            out.push('\n');
+                            out.push_str("SHA: ");
+                            out.push_str(&e.hash);
+                            out.push('\n');
+                            out.push_str("Selector: ");
+                            out.push_str(&e.selector);
+                            out.push('\n');
+                            out.push('\n');
+                            out.push_str("Subject:\n");
+                            out.push_str(&e.subject);
+                            out.push('\n');
+                            out
+                        }
+                    }
+                } else {
+                    let mut out = String::new();
+                    out.push_str("SHA: ");
+                    out.push_str(&e.hash);
+                    out.push('\n');
+                    out.push_str("Selector: ");
+                    out.push_str(&e.selector);
+                    out.push('\n');
+                    let badges = git_decoration_tokens(&e.decoration)
+                        .into_iter()
+                        .take(8)
+                        .map(|t| format!("[{}]", t))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    if !badges.is_empty() {
+                        out.push_str("Refs: ");
+                        out.push_str(&badges);
+                        out.push('\n');
+                    }
+                    out.push('\n');
+                    out.push_str("Subject:\n");
+                    out.push_str(&e.subject);
+                    out.push('\n');
+                    out
+                };
+
+                (title, body)
+            }
+            LogSubTab::Stash => {
+                let Some(e) = self.selected_stash_entry() else {
+                    self.set_status("No selection");
+                    return;
+                };
+
+                let mut body = String::new();
+                body.push_str("Selector: ");
+                body.push_str(&e.selector);
+                body.push('\n');
+                body.push('\n');
+                body.push_str("Message:\n");
+                body.push_str(&e.subject);
+                body.push('\n');
+                body.push('\n');
+                body.push_str("Keys: a/apply  p/pop  d/drop");
+                body.push('\n');
+
+                (format!("Inspect {}", e.selector), body)
+            }
+            LogSubTab::Commands => {
+                let Some(sel) = self.log_ui.command_state.selected() else {
+                    self.set_status("No selection");
+                    return;
+                };
+                let Some(e) = self.git_log.get(sel) else {
+                    self.set_status("No selection");
+                    return;
+                };
+
+                let mut body = String::new();
+                body.push_str("Command:\n");
+                body.push_str(&e.cmd);
+                body.push('\n');
+                body.push('\n');
+                body.push_str("Output:\n");
+                if let Some(d) = e.detail.as_deref() {
+                    body.push_str(d);
+                    if !d.ends_with('\n') {
+                        body.push('\n');
+                    }
+                } else {
+                    body.push_str("(no output)\n");
+                }
+
+                ("Inspect Command".to_string(), body)
+            }
+        };
+
+        self.log_ui.inspect.open = true;
+        self.log_ui.inspect.scroll_y = 0;
+        self.log_ui.inspect.title = title;
+        self.log_ui.inspect.body = body;
+        self.context_menu = None;
+    }
+
+    pub(crate) fn toggle_log_zoom(&mut self) {
+        let next = match self.log_ui.zoom {
+            LogZoom::None => LogZoom::Diff,
+            LogZoom::Diff => LogZoom::List,
+            LogZoom::List => LogZoom::None,
+        };
+        self.log_ui.zoom = next;
+
+        match next {
+            LogZoom::Diff => self.log_ui.focus = LogPaneFocus::Diff,
+            LogZoom::List => {
+                self.log_ui.focus = LogPaneFocus::Commits;
+                self.log_ui.inspect.close();
+            }
+            LogZoom::None => {}
+        }
+    }
+
+    pub(crate) fn cycle_log_focus(&mut self) {
+        let files_mode = self.log_ui.detail_mode == LogDetailMode::Files
+            && self.log_ui.subtab == LogSubTab::History;
+
+        match self.log_ui.zoom {
+            LogZoom::List => {
+                self.log_ui.focus = LogPaneFocus::Commits;
+            }
+            LogZoom::Diff => {
+                if files_mode {
+                    self.log_ui.focus = match self.log_ui.focus {
+                        LogPaneFocus::Files => LogPaneFocus::Diff,
+                        _ => LogPaneFocus::Files,
+                    };
+                } else {
+                    self.log_ui.focus = LogPaneFocus::Diff;
+                }
+            }
+            LogZoom::None => {
+                if files_mode {
+                    self.log_ui.focus = match self.log_ui.focus {
+                        LogPaneFocus::Commits => LogPaneFocus::Files,
+                        LogPaneFocus::Files => LogPaneFocus::Diff,
+                        LogPaneFocus::Diff => LogPaneFocus::Commits,
+                    };
+                } else {
+                    self.log_ui.focus = match self.log_ui.focus {
+                        LogPaneFocus::Diff => LogPaneFocus::Commits,
+                        _ => LogPaneFocus::Diff,
+                    };
+                }
+            }
+        }
+    }
+
+    pub(crate) fn adjust_log_left_width(&mut self, delta: i16) {
+        let cur = self.log_ui.left_width as i16;
+        let next = (cur + delta).clamp(32, 90);
+        self.log_ui.left_width = next as u16;
+    }
+}
@@ -0,0 +1,9592 @@
+//! The app: its state, the main loop and everything it shows
+//!
+//! [`run`] owns the loop: it draws a frame, waits for input or a background result
+//! and feeds it to `App::update`. Background jobs live in `jobs`, key and mouse
+//! handling in `input` and drawing in `ui`. Branches, the Log tab, stashes, snapshots,
+//! review and conflicts each have their own module with their state and `App` methods.
+
+use crossterm::{
+    event::{
+        DisableMouseCapture, EnableMouseCapture, Event, EventStream, KeyCode, KeyEvent,
+        KeyEventKind, KeyModifiers, MouseButton, MouseEventKind,
+    },
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use futures::StreamExt;
+use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+};
+use ratatui_image::{picker::Picker, protocol::StatefulProtocol};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    env,
+    fs::{self},
+    io::{self, Read as _, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock, mpsc},
+    thread,
+    time::{Duration, Instant},
+};
+use time::{OffsetDateTime, UtcOffset};
+use tokio::sync::mpsc as tokio_mpsc;
+use tokio_util::sync::CancellationToken;
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Largest file or diff copied to the clipboard; OSC 52 terminals often cap lower
+const MAX_COPY_BYTES: u64 = 512 * 1024;
+
+/// Compare two version strings (e.g., "0.4.1" vs "0.3.7")
+/// Returns true if `new` is newer than `current`
+fn is_newer_version(new: &str, current: &str) -> bool {
+    let parse = |v: &str| -> Vec<u32> { v.split('.').filter_map(|s| s.parse().ok()).collect() };
+    let new_parts = parse(new);
+    let cur_parts = parse(current);
+
+    for i in 0..new_parts.len().max(cur_parts.len()) {
+        let n = new_parts.get(i).copied().unwrap_or(0);
+        let c = cur_parts.get(i).copied().unwrap_or(0);
+        if n > c {
+            return true;
+        }
+        if n < c {
+            return false;
+        }
+    }
+    false
+}
+
+mod branches;
+mod conflicts;
+mod input;
+mod jobs;
+mod log;
+mod review;
+pub mod snapshot;
+mod snapshots;
+mod stash;
+mod ui;
+#[cfg(test)]
+mod ui_tests;
+mod update;
+
+use crate::{
+    actions, blame, bookmarks, branch, changelog, clipboard, commit, config, confirm, conflict,
+    control, export, file_ops, forge, git, git_diff_loader, git_ops, gitignore, highlight, hooks,
+    keymap, list_nav, lock_screen, multiplexer, openrouter, preview_cache, preview_loader, refs,
+    theme,
+};
+
+use branch::{BranchListItem, BranchUi};
+use branches::{BranchInput, BranchInputKind, BranchPickerMode};
+use commit::{CommitFocus, CommitState};
+use conflict::{ConflictFile, ConflictResolution};
+use conflicts::ConflictUi;
+use git::{GitDiffMode, GitSection, GitState, display_width, truncate_middle};
+use input::handle_event;
+use jobs::{
+    CiStatuses, GitRefreshJobOutput, JobResult, LogDiffJobOutput, PendingJob, QueuedGitJob,
+};
+use list_nav::ListNav;
+use log::{
+    CommitFind, LogDetailMode, LogPaneFocus, LogSubTab, LogUi, LogZoom, ResetPicker,
+    split_query_tokens,
+};
+use snapshots::SnapshotUi;
+use stash::{StashConfirmAction, StashInput, StashToggle, StashUi};
+use ui::draw_ui;
+use update::Msg;
+
+const THEME_ORDER: [theme::Theme; 6] = [
+    theme::Theme::Terminal,
+    theme::Theme::Mocha,
+    theme::Theme::TokyoNightStorm,
+    theme::Theme::GruvboxDarkHard,
+    theme::Theme::Nord,
+    theme::Theme::Dracula,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Tab {
+    Explorer,
+    Git,
+    Log,
+    Terminal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum GitFooterAction {
+    Stage,
+    Unstage,
+    Discard,
+    Commit,
+}
+
+/// Git tab action that `.` replays on the current selection
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RepeatAction {
+    Footer(GitFooterAction),
+    JumpAnchor { files: bool, forward: bool },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AuthorPickerMode {
+    LogFilter,
+    /// `--author` for the next commit
+    CommitAuthor,
+    /// Two identities of the same person, joined in `.mailmap`
+    Mailmap,
+    /// `Co-authored-by:` trailers of the commit message, several at once
+    CoAuthors,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum AppAction {
+    SwitchTab(Tab),
+    RefreshGit,
+    CopyRepoPath,
+    RemoveStaleLocks,
+    DismissGitNotice,
+    /// Name a branch for the detached HEAD
+    NewBranchHere,
+    GitDiffScrollTo(u16),
+    OpenCommandPalette,
+    OpenThemePicker,
+    Navigate(PathBuf),
+    /// Open a dropdown of the ancestors folded into the breadcrumb "…"
+    ShowHiddenCrumbs(Vec<PathBuf>),
+    EnterDir,
+    GoParent,
+    Select(usize),
+    SelectGitSection(GitSection),
+    SelectGitFile(usize),
+    SelectGitTreeItem(usize),
+    /// Row of the recent-commits strip under the Git tree
+    OpenRecentCommit(usize),
+    SelectBlameLine(usize),
+    ToggleGitTreeExpand,
+    RevertHunk(usize),
+    StashHunk(usize),
+    ExpandHunkContext(usize),
+    RevertBlock(usize),
+    /// Show more (`true`) or less context around every hunk
+    DiffContext(bool),
+    /// Stash the hunk at the top of the diff
+    StashHunkInView,
+    ToggleFullFile,
+    CycleFullFileSource,
+    /// Unified or side by side, in the current tab's diff
+    ToggleDiffMode,
+    JumpDiffAnchor {
+        files: bool,
+        forward: bool,
+    },
+    StartDiffSelection,
+    YankDiffSelection(DiffYank),
+    ClearDiffSelection,
+    RepeatLastAction,
+    SelectAllGitFiles,
+    UndoRevert,
+    RedoRevert,
+    ToggleCommitDrawer,
+    FocusCommitMessage,
+    GenerateCommitMessage,
+    ToggleCommitAdvanced,
+    ToggleCommitAmend,
+    PickCommitAuthor,
+    PickCoAuthors,
+    /// Recall a past commit message into the drawer
+    OpenMessagePicker,
+    PickCommitMessage(usize),
+    /// Jump to a match of "find in this commit"
+    PickCommitFind(usize),
+    /// Row of the "highlight this file as" picker
+    PickSyntax(usize),
+    ResetCommitOverrides,
+    FocusCommitDate,
+    /// Guided conventional-commit header in the drawer
+    ToggleConventionalCommit,
+    CycleCommitType(i32),
+    ToggleBreakingChange,
+    FocusCommitScope,
+    AcceptConfirm,
+    CancelConfirm,
+    ClearGitLog,
+    LogSwitch(LogSubTab),
+    ToggleReflogOp(git_ops::ReflogOp),
+    LogDiffBase(git_ops::DiffBase),
+    LogDiffScrollTo(u16),
+    LogDetail(LogDetailMode),
+    LogToggleZoom,
+    LogInspect,
+    LogCloseInspect,
+    LogInspectCopyPrimary,
+    LogInspectCopySecondary,
+    LogFocusDiff,
+    LogFocusFiles,
+    LogAdjustLeft(i16),
+    SelectLogItem(usize),
+    SelectRefItem(usize),
+    SelectLogFile(usize),
+    /// Mark or unmark a History row for cherry-picking
+    ToggleLogMark(usize),
+    CycleReflogOp,
+    CycleLogDiffBase,
+    LoadMoreLog,
+    LogCherryPick,
+    /// Revert the selected commit; `true` leaves the result uncommitted
+    LogRevert(bool),
+    LogReset,
+    LogCheckout,
+    LogStashApply,
+    LogStashPop,
+    LogStashDrop,
+
+    CloseOperationPopup,
+    RetryOperation,
+    CopyChangelog,
+    ExportChangelog,
+    CloseChangelog,
+    MergeContinue,
+    MergeAbort,
+    RebaseContinue,
+    RebaseAbort,
+    RebaseSkip,
+    AmContinue,
+    AmSkip,
+    AmAbort,
+    CherryPickContinue,
+    CherryPickSkip,
+    CherryPickAbort,
+    RevertContinue,
+    RevertSkip,
+    RevertAbort,
+    OpenReview,
+    ReviewApprove,
+    ReviewUnstage,
+    ReviewBack,
+    CloseReview,
+    /// Leave a review that reached its end for the commit drawer
+    FinishReview,
+    ConflictPrev,
+    ConflictNext,
+    ConflictUseOurs,
+    ConflictUseTheirs,
+    ConflictUseBoth,
+    MarkResolved,
+    OpenBranchPicker,
+    OpenLogBranchPicker,
+    CloseBranchPicker,
+    SelectBranch(usize),
+    SelectLogBranch(usize),
+    ConfirmLogBranchPicker,
+
+    OpenAuthorPicker,
+    CloseAuthorPicker,
+    SelectAuthor(usize),
+    ConfirmAuthorPicker,
+    BranchCheckout,
+    BranchNew,
+    BranchRename,
+    BranchDelete,
+    BranchUpstream,
+    BranchDescribe,
+
+    OpenStashPicker,
+    CloseStashPicker,
+    CloseSnapshots,
+    OpenBookmarks,
+    CloseBookmarks,
+    SelectBookmark(usize),
+    BookmarkOpen,
+    BookmarkRename,
+    BookmarkMoveUp,
+    BookmarkMoveDown,
+    BookmarkDelete,
+    /// Give the selected bookmark a quick-jump digit, or clear it when it has that one
+    BookmarkAssignKey(char),
+    SelectSnapshot(usize),
+    SnapshotDiff,
+    SnapshotRestore,
+    SnapshotDelete,
+    SelectStash(usize),
+    StashApply,
+    StashPop,
+    StashDrop,
+    CloseTagPicker,
+    SelectTag(usize),
+    TagShow,
+    TagPush,
+    TagPushAll,
+    TagDelete,
+    OpenRemotes,
+    CloseRemotes,
+    SelectRemote(usize),
+    RemoteUse,
+    RemoteAdd,
+    RemoteRename,
+    /// Change the URL of the selected remote; `true` for the push URL
+    RemoteUrl(bool),
+    RemoteRemove,
+    ToggleStashOption(StashToggle),
+    SubmitStash,
+    /// Pull with this strategy from the pull options
+    PullStrategy(git_ops::PullStrategy),
+    TogglePullAutostash,
+    /// Focus a row of the push dialog; toggles flip and the remote moves on
+    PushField(PushField),
+    SubmitPush,
+    /// Flip "check it out" for a branch made from a History commit
+    ToggleNewBranchCheckout,
+    /// Show the diff of a file listed in the directory summary
+    OpenDirSummaryFile(usize),
+    EnterSubmodule,
+    LeaveSubmodule,
+    Submodule(git_ops::SubmoduleOp),
+
+    GitFetch,
+    GitPull,
+    GitPush,
+    ToggleGitStage,
+    /// Show the key bindings in effect
+    OpenKeyHelp,
+    OpenFileFinder,
+    LockScreen,
+    /// Row of the file finder
+    PickFoundFile(usize),
+    GitStageAllVisible,
+    GitUnstageAllVisible,
+    GitFooter(GitFooterAction),
+    ToggleHidden,
+    AddToGitignore,
+    UndoFileOp,
+    RedoFileOp,
+    NewEntry,
+    RenameEntry,
+    DeleteEntry,
+    OpenInEditor,
+    RefreshFiles,
+    ToggleExplorerZoom,
+    ToggleAutoRefresh,
+    /// Go to the bookmark with this quick-jump digit
+    JumpToBookmark(char),
+    AcceptUpdate,
+    CancelUpdate,
+    Quit,
+    None,
+    ContextMenuAction(usize),
+}
+
+#[derive(Clone)]
+pub(crate) struct ClickZone {
+    rect: Rect,
+    action: AppAction,
+}
+
+#[derive(Clone)]
+pub(crate) struct FileEntry {
+    name: String,
+    path: PathBuf,
+    is_dir: bool,
+    is_symlink: bool,
+    is_exec: bool,
+    is_hidden: bool,
+    size: u64,
+}
+
+pub(crate) struct ContextMenu {
+    x: u16,
+    y: u16,
+    selected: usize,
+    options: Vec<(String, ContextCommand)>,
+}
+
+#[derive(Clone)]
+enum ContextCommand {
+    AddBookmark,
+    RemoveBookmark,
+    ManageBookmarks,
+    CopyPath,
+    CopyRelPath,
+    CopyFileUri,
+    CopyContents,
+    NewEntry,
+    Rename,
+    Delete,
+
+    GitStage,
+    GitUnstage,
+    GitToggleStage,
+    GitDiscard,
+    GitStageAll,
+    GitUnstageAll,
+    GitOpenInExplorer,
+    GitCopyPath,
+    GitCopyRelPath,
+    GitCopyFileUri,
+    GitCopyContents,
+    GitCopyDiff,
+    GitAddToGitignore,
+    Blame,
+    SubmoduleEnter,
+    Submodule(git_ops::SubmoduleOp),
+
+    OpenFileInPane,
+    OpenDiffInPane,
+
+    LogCopySha,
+    LogCopySubject,
+    LogCherryPick,
+    /// Revert the selected commit; `true` leaves the result uncommitted
+    LogRevert(bool),
+    /// Create a branch at the selected commit
+    LogNewBranch,
+    /// Tag the selected commit
+    LogNewTag,
+    /// Reset the current branch to the selected commit
+    LogReset,
+    /// Check out the selected History or Reflog commit, detaching HEAD
+    LogCheckout,
+    LogCopyCommand,
+
+    RefCheckout(refs::RefEntry),
+    RefDelete(refs::RefEntry),
+    /// Show commits on the ref that HEAD lacks
+    RefCompare(String),
+    RefBundle(String),
+
+    BundleFetch,
+    BundlePull,
+
+    /// Index into the configured external actions
+    External(usize),
+    Navigate(PathBuf),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DiscardMode {
+    Worktree,
+    Untracked,
+    AllChanges,
+}
+
+#[derive(Clone, Debug)]
+struct DiscardItem {
+    path: String,
+    mode: DiscardMode,
+}
+
+/// Action behind the shared confirmation dialog
+#[derive(Clone, Debug)]
+enum ConfirmAction {
+    Discard(Vec<DiscardItem>),
+    Delete(PathBuf),
+    /// Entry that could not be moved to the trash
+    DeletePermanently(PathBuf),
+    Stash(StashConfirmAction, String),
+    Checkout(branch::BranchEntry),
+    /// Commit to check out on a detached HEAD
+    CheckoutCommit(String),
+    /// `git push` command line built by the push dialog
+    Push(String),
+    /// Checkout or pull that local changes got in the way of
+    AutostashRetry(String),
+    ApplyPatches(Vec<PathBuf>),
+    BundleFetch(PathBuf),
+    /// Bundle file and the branch in it to pull
+    BundlePull(PathBuf, String),
+    /// Commits to apply, oldest first
+    CherryPick(Vec<String>),
+    /// Commit to revert and whether to skip committing the result
+    RevertCommit(String, bool),
+    /// Branch that `git branch -d` refused to delete because it is not merged
+    ForceDeleteBranch(String),
+    DeleteTag(String),
+    /// Tags to push; empty pushes every tag
+    PushTags(Vec<String>),
+    RemoveRemote(String),
+    /// Commit to move the current branch to
+    ResetTo(String, git_ops::ResetMode),
+}
+
+/// Bundle file (and revisions, when creating) being typed
+struct BundleInput {
+    kind: BundleInputKind,
+    text: String,
+}
+
+/// Guided `git init` for a folder that is not a repository yet
+struct InitWizard {
+    step: InitStep,
+    branch: String,
+    /// 0 is no `.gitignore`, otherwise `gitignore::TEMPLATES[template - 1]`
+    template: usize,
+}
+
+/// `.gitignore` template being previewed against the repository's current file
+struct GitignorePicker {
+    selected: usize,
+    scroll_y: u16,
+    existing: String,
+}
+
+/// Name and message of a tag being created on a commit
+struct TagInput {
+    /// Full hash of the commit to tag
+    target: String,
+    /// Short hash and subject of the commit, for the title
+    label: String,
+    name: String,
+    /// Empty for a lightweight tag
+    message: String,
+    focus_message: bool,
+}
+
+/// Commit templates of this repository, then its past commit messages newest
+/// first, for the commit drawer
+struct MessagePicker {
+    selected: usize,
+    /// File name and message of each template, listed above the history
+    templates: Vec<(String, String)>,
+}
+
+/// The `?` cheatsheet: a filter over every key, and how far it is scrolled
+#[derive(Default)]
+struct KeyHelp {
+    query: String,
+    scroll: u16,
+}
+
+/// "Find file": the files of the repository, filtered as you type
+#[derive(Default)]
+struct FileFinder {
+    query: String,
+    /// Repository-relative paths; empty until the listing arrives
+    files: Vec<String>,
+    /// Indices into `files`, best match first
+    filtered: Vec<usize>,
+    selected: usize,
+    /// `git ls-files` running in the background
+    rx: Option<mpsc::Receiver<Result<Vec<String>, String>>>,
+}
+
+impl FileFinder {
+    fn update_filtered(&mut self) {
+        let query = self.query.trim().to_lowercase();
+        let tokens: Vec<&str> = query.split_whitespace().collect();
+        let mut matches: Vec<(i32, usize)> = Vec::new();
+        for (i, path) in self.files.iter().enumerate() {
+            let hay = path.to_lowercase();
+            let name = hay.rsplit('/').next().unwrap_or(&hay);
+            let mut score = 0i32;
+            let mut ok = true;
+            for t in &tokens {
+                match token_score(&hay, t) {
+                    // Hits in the file name count twice
+                    Some(s) => score += s + token_score(name, t).unwrap_or(0),
+                    None => {
+                        ok = false;
+                        break;
+                    }
+                }
+            }
+            if ok {
+                matches.push((score, i));
+            }
+        }
+        // Shorter paths first among equals
+        matches.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then_with(|| self.files[a.1].len().cmp(&self.files[b.1].len()))
+        });
+        self.filtered = matches.into_iter().map(|(_, i)| i).collect();
+        self.selected = 0;
+    }
+
+    fn selected_path(&self) -> Option<&str> {
+        self.filtered
+            .get(self.selected)
+            .and_then(|&i| self.files.get(i))
+            .map(String::as_str)
+    }
+}
+
+/// Language chooser for highlighting one file
+struct SyntaxPicker {
+    /// The file, absolute
+    path: PathBuf,
+    /// Row: detect, plain text, then `highlight::LANGUAGES`
+    selected: usize,
+}
+
+/// Guided release: next version, notes from the commits since the last tag, push
+struct ReleaseWizard {
+    step: ReleaseStep,
+    last_tag: Option<String>,
+    /// The last tag's version, or `v0.0.0` for a first release
+    base: changelog::Version,
+    /// Commits since the last tag
+    commit_count: usize,
+    suggested: changelog::Bump,
+    /// Index into `Bump::ALL`, or `Bump::ALL.len()` for the custom name
+    selected: usize,
+    custom: String,
+    /// Release notes grouped by commit type, as they go into the tag message
+    notes: String,
+    notes_scroll: u16,
+    include_notes: bool,
+    remote: Option<String>,
+    push: bool,
+}
+
+impl ReleaseWizard {
+    fn tag_name(&self) -> String {
+        match changelog::Bump::ALL.get(self.selected) {
+            Some(bump) => self.base.bump(*bump).to_string(),
+            None => self.custom.trim().to_string(),
+        }
+    }
+
+    /// Never empty, so the tag is always annotated
+    fn message(&self) -> String {
+        let mut message = format!("Release {}", self.tag_name());
+        if self.include_notes && !self.notes.is_empty() {
+            message.push_str("\n\n");
+            message.push_str(&self.notes);
+        }
+        message
+    }
+}
+
+/// Markdown changelog between two refs, to copy or save
+struct ChangelogView {
+    /// The range as typed, for the title
+    range: String,
+    markdown: String,
+    scroll_y: u16,
+    /// File name being typed after "Export"
+    export_path: Option<String>,
+}
+
+/// Name or URL being typed in the remote manager
+struct RemoteInput {
+    kind: RemoteInputKind,
+    name: String,
+    url: String,
+    focus_url: bool,
+}
+
+enum RemoteInputKind {
+    Add,
+    /// Current name of the remote
+    Rename(String),
+    /// Remote whose URL changes; `true` changes only the push URL
+    Url(String, bool),
+}
+
+/// Remote, ref and flags for a push
+struct PushInput {
+    remotes: Vec<String>,
+    /// Index into `remotes`
+    remote: usize,
+    /// `branch` or `src:dst`
+    refspec: String,
+    force_with_lease: bool,
+    set_upstream: bool,
+    /// Remote and ref as the dialog opened; pushing those without flags is the
+    /// plain push, to the upstream or the picked remote
+    plain: (usize, String),
+    focus: PushField,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PushField {
+    Remote,
+    Ref,
+    ForceWithLease,
+    SetUpstream,
+}
+
+impl PushField {
+    const ALL: [PushField; 4] = [
+        PushField::Remote,
+        PushField::Ref,
+        PushField::ForceWithLease,
+        PushField::SetUpstream,
+    ];
+}
+
+impl PushInput {
+    fn cycle_focus(&mut self, forward: bool) {
+        let n = PushField::ALL.len();
+        let at = PushField::ALL
+            .iter()
+            .position(|f| *f == self.focus)
+            .unwrap_or(0);
+        let next = if forward {
+            (at + 1) % n
+        } else {
+            (at + n - 1) % n
+        };
+        self.focus = PushField::ALL[next];
+    }
+
+    fn cycle_remote(&mut self, forward: bool) {
+        let n = self.remotes.len().max(1);
+        self.remote = if forward {
+            (self.remote + 1) % n
+        } else {
+            (self.remote + n - 1) % n
+        };
+    }
+
+    fn options(&self) -> git_ops::PushOptions {
+        git_ops::PushOptions {
+            force_with_lease: self.force_with_lease,
+            set_upstream: self.set_upstream,
+            target: Some((self.remotes[self.remote].clone(), self.refspec.clone())),
+        }
+    }
+
+    fn is_plain(&self) -> bool {
+        !self.force_with_lease
+            && !self.set_upstream
+            && (self.remote, self.refspec.as_str()) == (self.plain.0, self.plain.1.as_str())
+    }
+}
+
+/// URL and destination folder being typed for a clone
+struct CloneInput {
+    url: String,
+    /// Relative to the Explorer folder; defaults to the repository name
+    dest: String,
+    focus_dest: bool,
+}
+
+enum CloneMsg {
+    Progress(String),
+    Done(Result<(), String>),
+}
+
+struct CloneJob {
+    rx: mpsc::Receiver<CloneMsg>,
+    title: String,
+    dest: PathBuf,
+    /// Progress so far; a redrawn counter replaces its previous line
+    lines: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum InitStep {
+    Branch,
+    Gitignore,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ReleaseStep {
+    Version,
+    Notes,
+    Push,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BundleInputKind {
+    /// File followed by the refs or ranges to include
+    Create,
+    Fetch,
+    Pull,
+}
+
+/// Name being typed for a new or renamed Explorer entry
+struct ExplorerInput {
+    kind: ExplorerInputKind,
+    text: String,
+}
+
+enum ExplorerInputKind {
+    /// A trailing `/` creates a folder
+    Create,
+    /// Names containing `/` move the entry, relative to its folder
+    Rename(PathBuf),
+}
+
+pub(crate) struct TerminalState {
+    parser: vt100::Parser,
+    pty_writer: Option<Box<dyn Write + Send>>,
+    pty_reader_rx: Option<mpsc::Receiver<Vec<u8>>>,
+    active: bool,
+}
+
+impl TerminalState {
+    fn new() -> Self {
+        Self {
+            parser: vt100::Parser::new(24, 80, 0),
+            pty_writer: None,
+            pty_reader_rx: None,
+            active: false,
+        }
+    }
+
+    fn spawn_shell(&mut self, cols: u16, rows: u16, cwd: &PathBuf) {
+        if self.active {
+            return;
+        }
+
+        let pty_system = NativePtySystem::default();
+        let pair = match pty_system.openpty(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        }) {
+            Ok(p) => p,
+            Err(_) => return,
+        };
+
+        let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string());
+        let mut cmd = CommandBuilder::new(&shell);
+        cmd.cwd(cwd);
+
+        let _child = match pair.slave.spawn_command(cmd) {
+            Ok(c) => c,
+            Err(_) => return,
+        };
+
+        self.parser = vt100::Parser::new(rows, cols, 1000);
+        self.pty_writer = Some(pair.master.take_writer().unwrap());
+
+        // Read PTY output in background thread
+        let mut reader = pair.master.try_clone_reader().unwrap();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        if tx.send(buf[..n].to_vec()).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+        self.pty_reader_rx = Some(rx);
+        self.active = true;
+    }
+
+    fn poll_output(&mut self) {
+        if let Some(rx) = &self.pty_reader_rx {
+            while let Ok(data) = rx.try_recv() {
+                self.parser.process(&data);
+            }
+        }
+    }
+
+    fn write_input(&mut self, data: &[u8]) {
+        if let Some(writer) = &mut self.pty_writer {
+            let _ = writer.write_all(data);
+            let _ = writer.flush();
+        }
+    }
+
+    fn resize(&mut self, cols: u16, rows: u16) {
+        self.parser.set_size(rows, cols);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct GitLogEntry {
+    pub(crate) when: Instant,
+    pub(crate) cmd: String,
+    pub(crate) ok: bool,
+    pub(crate) detail: Option<String>,
+    /// How long the command ran
+    pub(crate) elapsed: Duration,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PersistedUiSettings {
+    #[serde(default)]
+    log_left_width: Option<u16>,
+    #[serde(default)]
+    git_left_width: Option<u16>,
+    #[serde(default)]
+    theme: Option<theme::Theme>,
+
+    #[serde(default)]
+    wrap_diff: Option<bool>,
+    #[serde(default)]
+    syntax_highlight: Option<bool>,
+    #[serde(default)]
+    diff_line_numbers: Option<bool>,
+    #[serde(default)]
+    binary_hexdump: Option<bool>,
+
+    #[serde(default)]
+    git_side_by_side: Option<bool>,
+    #[serde(default)]
+    git_zoom_diff: Option<bool>,
+    #[serde(default)]
+    log_side_by_side: Option<bool>,
+
+    #[serde(default)]
+    log_zoom: Option<LogZoom>,
+    #[serde(default)]
+    log_detail_mode: Option<LogDetailMode>,
+    #[serde(default)]
+    log_date_column: Option<bool>,
+    #[serde(default)]
+    log_author_column: Option<bool>,
+    #[serde(default)]
+    log_refs_sidebar: Option<bool>,
+
+    #[serde(default)]
+    top_bar: Option<TopBarSegments>,
+
+    /// Last pull options per repository root
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pull_options: BTreeMap<String, git_ops::PullOptions>,
+}
+
+/// Optional segments of the top bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+struct TopBarSegments {
+    clock: bool,
+    branch: bool,
+    ahead_behind: bool,
+    operation: bool,
+}
+
+impl Default for TopBarSegments {
+    fn default() -> Self {
+        Self {
+            clock: false,
+            branch: true,
+            ahead_behind: true,
+            operation: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TopBarSegment {
+    Clock,
+    Branch,
+    AheadBehind,
+    Operation,
+}
+
+pub(crate) use git_ops::GitOperation;
+
+/// Which pane of a two-pane tab (Explorer, Git) takes paging keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PaneFocus {
+    /// File list / status tree
+    List,
+    /// Preview / diff
+    Detail,
+}
+
+/// Explorer view zoom modes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub(crate) enum ExplorerZoom {
+    #[default]
+    ThreeColumn, // Parent | Current | Preview
+    TwoColumn,   // Current | Preview
+    PreviewOnly, // Full preview
+}
+
+pub(crate) struct OperationPopup {
+    title: String,
+    body: String,
+    ok: bool,
+    scroll_y: u16,
+    /// Operation command to run again, offered when it timed out
+    retry: Option<String>,
+}
+
+impl OperationPopup {
+    fn new(title: String, body: String, ok: bool) -> Self {
+        Self {
+            title,
+            body,
+            ok,
+            scroll_y: 0,
+            retry: None,
+        }
+    }
+}
+
+pub(crate) struct ThemePickerUi {
+    open: bool,
+    list_state: ListState,
+}
+
+impl ThemePickerUi {
+    fn new() -> Self {
+        Self {
+            open: false,
+            list_state: ListState::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CommandId {
+    ToggleHidden,
+    ToggleWrapDiff,
+    ToggleSyntaxHighlight,
+    HighlightAs,
+    ToggleDiffLineNumbers,
+    ToggleBinaryHexdump,
+    ToggleHistoryDate,
+    ToggleHistoryAuthor,
+    ToggleRefsSidebar,
+    SearchFullHistory,
+    FindInCommit,
+    GoToCommit,
+    LoadMoreHistory,
+    LoadAllHistory,
+    SelectTheme,
+    RefreshGit,
+    GitFetch,
+    GitPull,
+    GitPush,
+    OpenBranchPicker,
+    NewBranch,
+    OpenAuthorPicker,
+    OpenStashPicker,
+    OpenTagPicker,
+    OpenRemotes,
+    SubmoduleUpdate,
+    SubmoduleInit,
+    SubmoduleSync,
+    LeaveSubmodule,
+    ReviewStaged,
+    CreateSnapshot,
+    OpenSnapshots,
+    OpenBookmarks,
+    ExportViewText,
+    ExportViewMarkdown,
+    ExportViewFile,
+    OpenFileInPane,
+    OpenDiffInPane,
+    ToggleTopBarClock,
+    ToggleTopBarBranch,
+    ToggleTopBarAheadBehind,
+    ToggleTopBarOperation,
+    ReloadConfig,
+    LockScreen,
+    KeyBindings,
+    FindFile,
+    RemoveStaleLocks,
+    ClearGitLog,
+    QuickStash,
+    WipCommit,
+    UndoWip,
+    ApplyPatches,
+    CreateBundle,
+    FetchBundle,
+    NewOrphanBranch,
+    InitRepo,
+    CloneRepo,
+    CutRelease,
+    GenerateChangelog,
+    MapAuthors,
+    GitignoreTemplate,
+    BlameFile,
+    PullBundle,
+    CheckUpdate,
+    Quit,
+}
+
+const COMMAND_PALETTE_ITEMS: &[(CommandId, &str)] = &[
+    (CommandId::ToggleHidden, "Toggle hidden files"),
+    (CommandId::ToggleWrapDiff, "Toggle diff wrap"),
+    (CommandId::ToggleSyntaxHighlight, "Toggle syntax highlight"),
+    (CommandId::HighlightAs, "Highlight this file as…"),
+    (CommandId::ToggleDiffLineNumbers, "Toggle diff line numbers"),
+    (
+        CommandId::ToggleBinaryHexdump,
+        "Toggle hexdump of binary changes",
+    ),
+    (CommandId::ToggleHistoryDate, "History: toggle date column"),
+    (
+        CommandId::ToggleHistoryAuthor,
+        "History: toggle author column",
+    ),
+    (CommandId::ToggleRefsSidebar, "History: toggle refs sidebar"),
+    (
+        CommandId::SearchFullHistory,
+        "History: search all commits for filter",
+    ),
+    (CommandId::FindInCommit, "History: find in this commit…"),
+    (CommandId::GoToCommit, "History: go to commit…"),
+    (CommandId::LoadMoreHistory, "History: load next page"),
+    (CommandId::LoadAllHistory, "History: load all commits"),
+    (CommandId::SelectTheme, "Select theme…"),
+    (CommandId::RefreshGit, "Git: refresh status"),
+    (CommandId::OpenBranchPicker, "Checkout branch…"),
+    (CommandId::NewBranch, "Git: new branch…"),
+    (CommandId::OpenAuthorPicker, "Filter by author…"),
+    (
+        CommandId::MapAuthors,
+        "Git: map author identities (.mailmap)…",
+    ),
+    (CommandId::OpenStashPicker, "Stash…"),
+    (CommandId::OpenTagPicker, "Tags…"),
+    (CommandId::CutRelease, "Git: cut release…"),
+    (CommandId::GenerateChangelog, "Git: changelog between refs…"),
+    (CommandId::OpenRemotes, "Git: remotes…"),
+    (CommandId::SubmoduleUpdate, "Git: update submodules"),
+    (CommandId::SubmoduleInit, "Git: init submodules"),
+    (CommandId::SubmoduleSync, "Git: sync submodule URLs"),
+    (CommandId::LeaveSubmodule, "Git: back to superproject"),
+    (CommandId::ReviewStaged, "Git: review staged changes"),
+    (CommandId::CreateSnapshot, "Git: snapshot working tree…"),
+    (CommandId::OpenSnapshots, "Snapshots…"),
+    (CommandId::OpenBookmarks, "Bookmarks…"),
+    (CommandId::ExportViewText, "Export view: copy as text"),
+    (
+        CommandId::ExportViewMarkdown,
+        "Export view: copy as markdown",
+    ),
+    (CommandId::ExportViewFile, "Export view to file…"),
+    (CommandId::OpenFileInPane, "Open file in tmux/zellij pane"),
+    (CommandId::OpenDiffInPane, "Open diff in tmux/zellij pane"),
+    (CommandId::ToggleTopBarClock, "Top bar: toggle clock"),
+    (CommandId::ToggleTopBarBranch, "Top bar: toggle branch"),
+    (
+        CommandId::ToggleTopBarAheadBehind,
+        "Top bar: toggle ahead/behind",
+    ),
+    (
+        CommandId::ToggleTopBarOperation,
+        "Top bar: toggle operation",
+    ),
+    (CommandId::GitFetch, "Git: fetch --prune"),
+    (CommandId::GitPull, "Git: pull…"),
+    (CommandId::GitPush, "Git: push"),
+    (CommandId::ReloadConfig, "Reload config"),
+    (CommandId::LockScreen, "Lock the screen"),
+    (CommandId::KeyBindings, "Help: key bindings"),
+    (CommandId::FindFile, "Find file…"),
+    (CommandId::RemoveStaleLocks, "Git: remove stale lock file"),
+    (CommandId::ClearGitLog, "Clear git command log"),
+    (CommandId::QuickStash, "Git: stash changes…"),
+    (CommandId::WipCommit, "Git: WIP commit (park all changes)"),
+    (CommandId::UndoWip, "Git: undo WIP commit"),
+    (CommandId::ApplyPatches, "Git: apply patches (am)…"),
+    (CommandId::NewOrphanBranch, "Git: new orphan branch…"),
+    (CommandId::InitRepo, "Git: initialize repository…"),
+    (CommandId::CloneRepo, "Git: clone repository…"),
+    (
+        CommandId::GitignoreTemplate,
+        "Git: add .gitignore template…",
+    ),
+    (CommandId::BlameFile, "Git: blame file"),
+    (CommandId::CreateBundle, "Git: create bundle…"),
+    (CommandId::FetchBundle, "Git: fetch from bundle…"),
+    (CommandId::PullBundle, "Git: pull from bundle…"),
+    (CommandId::CheckUpdate, "Check for updates"),
+    (CommandId::Quit, "Quit"),
+];
+
+pub(crate) struct CommandPaletteUi {
+    open: bool,
+    list_state: ListState,
+}
+
+impl CommandPaletteUi {
+    fn new() -> Self {
+        Self {
+            open: false,
+            list_state: ListState::default(),
+        }
+    }
+}
+
+struct BookmarksUi {
+    open: bool,
+    list_state: ListState,
+    /// Name being edited for the selected bookmark
+    rename: Option<String>,
+    status: Option<String>,
+}
+
+impl BookmarksUi {
+    fn new() -> Self {
+        Self {
+            open: false,
+            list_state: ListState::default(),
+            rename: None,
+            status: None,
+        }
+    }
+}
+
+struct TagUi {
+    open: bool,
+    tags: Vec<git_ops::TagEntry>,
+    list_state: ListState,
+    status: Option<String>,
+}
+
+impl TagUi {
+    fn new() -> Self {
+        Self {
+            open: false,
+            tags: Vec::new(),
+            list_state: ListState::default(),
+            status: None,
+        }
+    }
+
+    fn selected_tag(&self) -> Option<&git_ops::TagEntry> {
+        self.tags.get(self.list_state.selected()?)
+    }
+
+    fn move_selection(&mut self, delta: i32) {
+        let len = self.tags.len();
+        if len == 0 {
+            self.list_state.select(None);
+            return;
+        }
+
+        let cur = self.list_state.selected().unwrap_or(0) as i32;
+        let next = (cur + delta).clamp(0, len.saturating_sub(1) as i32);
+        self.list_state.select(Some(next as usize));
+    }
+}
+
+struct RemoteUi {
+    open: bool,
+    remotes: Vec<git_ops::RemoteEntry>,
+    list_state: ListState,
+    status: Option<String>,
+}
+
+impl RemoteUi {
+    fn new() -> Self {
+        Self {
+            open: false,
+            remotes: Vec::new(),
+            list_state: ListState::default(),
+            status: None,
+        }
+    }
+
+    fn selected_remote(&self) -> Option<&git_ops::RemoteEntry> {
+        self.remotes.get(self.list_state.selected()?)
+    }
+
+    fn move_selection(&mut self, delta: i32) {
+        let len = self.remotes.len();
+        if len == 0 {
+            self.list_state.select(None);
+            return;
+        }
+
+        let cur = self.list_state.selected().unwrap_or(0) as i32;
+        let next = (cur + delta).clamp(0, len.saturating_sub(1) as i32);
+        self.list_state.select(Some(next as usize));
+    }
+}
+
+pub(crate) struct AuthorUi {
+    open: bool,
+    query: String,
+    authors: Vec<String>,
+    filtered: Vec<usize>,
+    list_state: ListState,
+    status: Option<String>,
+    /// Identity picked first in Mailmap mode; the second pick is mapped onto it
+    canonical: Option<String>,
+    /// Identities ticked in CoAuthors mode, in the order they were ticked
+    marked: Vec<String>,
+}
+
+impl AuthorUi {
+    fn new() -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            authors: Vec::new(),
+            filtered: Vec::new(),
+            list_state: ListState::default(),
+            status: None,
+            canonical: None,
+            marked: Vec::new(),
+        }
+    }
+
+    fn toggle_marked(&mut self) {
+        let Some(author) = self.selected_author().map(str::to_string) else {
+            return;
+        };
+        if let Some(pos) = self.marked.iter().position(|m| *m == author) {
+            self.marked.remove(pos);
+        } else {
+            self.marked.push(author);
+        }
+    }
+
+    fn set_authors(&mut self, authors: Vec<String>) {
+        self.query.clear();
+        self.authors = authors;
+        self.update_filtered();
+    }
+
+    fn selected_author(&self) -> Option<&str> {
+        let sel = self.list_state.selected()?;
+        let idx = *self.filtered.get(sel)?;
+        self.authors.get(idx).map(|s| s.as_str())
+    }
+
+    fn update_filtered(&mut self) {
+        let prev = self
+            .list_state
+            .selected()
+            .and_then(|sel| self.filtered.get(sel).copied());
+
+        let query = self.query.trim().to_lowercase();
+        let tokens: Vec<String> = query
+            .split_whitespace()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let mut matches: Vec<(i32, usize)> = Vec::new();
+        for (i, s) in self.authors.iter().enumerate() {
+            if tokens.is_empty() {
+                matches.push((0, i));
+                continue;
+            }
+
+            let hay = s.to_lowercase();
+            let mut score = 0i32;
+            let mut ok = true;
+
+            for t in &tokens {
+                if let Some(s) = token_score(hay.as_str(), t.as_str()) {
+                    score += s;
+                } else {
+                    ok = false;
+                    break;
+                }
+            }
+
+            if ok {
+                matches.push((score, i));
+            }
+        }
+
+        matches.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        self.filtered.clear();
+        self.filtered.extend(matches.into_iter().map(|(_, i)| i));
+
+        if self.filtered.is_empty() {
+            self.list_state.select(None);
+            return;
+        }
+
+        if let Some(prev) = prev.and_then(|idx| self.filtered.iter().position(|i| *i == idx)) {
+            self.list_state.select(Some(prev));
+        } else {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    fn move_selection(&mut self, delta: i32) {
+        let len = self.filtered.len();
+        if len == 0 {
+            self.list_state.select(None);
+            return;
+        }
+
+        let cur = self.list_state.selected().unwrap_or(0) as i32;
+        let next = (cur + delta).clamp(0, len.saturating_sub(1) as i32);
+        self.list_state.select(Some(next as usize));
+    }
+}
+
+/// Commits read for release notes, version bumps and changelogs
+const RELEASE_MAX_COMMITS: usize = 5_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct DiffRenderCacheKey {
+    pub(crate) theme: theme::Theme,
+    pub(crate) generation: u64,
+    pub(crate) mode: GitDiffMode,
+    pub(crate) width: u16,
+    pub(crate) wrap: bool,
+    pub(crate) syntax_highlight: bool,
+    pub(crate) line_numbers: bool,
+    pub(crate) selection: Option<(usize, usize)>,
+    pub(crate) scroll_x: u16,
+}
+
+/// Line selection in a unified diff pane, as indices into the tab's raw diff lines
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct DiffSelection {
+    pub(crate) tab: Tab,
+    pub(crate) generation: u64,
+    pub(crate) anchor: usize,
+    pub(crate) cursor: usize,
+    /// Scroll the cursor into view on the next render
+    pub(crate) follow: bool,
+}
+
+impl DiffSelection {
+    pub(crate) fn range(&self) -> (usize, usize) {
+        (self.anchor.min(self.cursor), self.anchor.max(self.cursor))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DiffYank {
+    NewLines,
+    Patch,
+    Markers,
+}
+
+pub(crate) struct DiffRenderCache {
+    pub(crate) key: Option<DiffRenderCacheKey>,
+    pub(crate) lines: Vec<Line<'static>>,
+}
+
+impl DiffRenderCache {
+    fn new() -> Self {
+        Self {
+            key: None,
+            lines: Vec::new(),
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.key = None;
+        self.lines.clear();
+    }
+
+    /// Scroll rows of the rendered hunk headers (or file headers when `files` is set).
+    /// `wrap_width` accounts for soft-wrapped unified lines taking several rows.
+    fn anchor_rows(&self, files: bool, wrap_width: Option<usize>) -> Vec<usize> {
+        let mut rows = Vec::new();
+        let mut row = 0usize;
+        for line in &self.lines {
+            let first = line.spans.first().map(|s| s.content.as_ref()).unwrap_or("");
+            let hit = if files {
+                first.starts_with("📄 ")
+            } else {
+                first.starts_with("@@")
+            };
+            if hit {
+                rows.push(row);
+            }
+            row += match wrap_width {
+                Some(w) => line.width().max(1).div_ceil(w.max(1)),
+                None => 1,
+            };
+        }
+        rows
+    }
+}
+
+fn next_anchor_row(rows: &[usize], current: u16, forward: bool) -> Option<u16> {
+    let current = current as usize;
+    let row = if forward {
+        rows.iter().copied().find(|r| *r > current)
+    } else {
+        rows.iter().rev().copied().find(|r| *r < current)
+    }?;
+    Some(row.min(u16::MAX as usize) as u16)
+}
+
+pub(crate) struct App {
+    pub(crate) current_path: PathBuf, // Explorer's current directory (changes with navigation)
+    pub(crate) startup_path: PathBuf, // Initial directory (fixed, used for Git)
+    pub(crate) files: Vec<FileEntry>,
+    pub(crate) list_state: ListState,
+    pub(crate) preview_scroll: u16,
+    pub(crate) preview_scroll_offset: usize, // Independent scroll offset for preview panel
+    pub(crate) should_quit: bool,
+    pub(crate) show_hidden: bool,
+
+    pub(crate) current_tab: Tab,
+
+    pub(crate) git: GitState,
+    pub(crate) git_operation: Option<GitOperation>,
+    /// Lock files found in the git dir at the last refresh
+    git_locks: Vec<PathBuf>,
+    /// Startup warning about a missing or outdated git
+    git_notice: Option<String>,
+    pub(crate) branch_ui: BranchUi,
+    pub(crate) branch_picker_mode: BranchPickerMode,
+    author_picker_mode: AuthorPickerMode,
+    pub(crate) author_ui: AuthorUi,
+    pub(crate) stash_ui: StashUi,
+    pub(crate) snapshot_ui: SnapshotUi,
+    tag_ui: TagUi,
+    remote_ui: RemoteUi,
+    /// Remote that Push, Pull and Fetch use instead of the upstream, with the
+    /// repository it was picked in
+    target_remote: Option<(PathBuf, String)>,
+    /// Repositories left by entering a submodule, outermost first
+    superprojects: Vec<PathBuf>,
+    pub(crate) conflict_ui: ConflictUi,
+    pub(crate) review: crate::review::ReviewState,
+    pub(crate) blame: blame::BlameUi,
+    /// `[behavior] review_before_commit`
+    pub(crate) review_before_commit: bool,
+    /// `[behavior] count_prefix`
+    count_prefix: bool,
+    pub(crate) commit: CommitState,
+    /// Git commands that change the repo; one at a time, others queue
+    pub(crate) pending_job: Option<PendingJob>,
+    /// History, reflog and stash listing and searches
+    log_job: Option<PendingJob>,
+    ai_job: Option<PendingJob>,
+    job_queue: VecDeque<QueuedGitJob>,
+    pub(crate) git_refresh_job: Option<PendingJob>,
+    pub(crate) git_refresh_request_id: u64,
+    pub(crate) git_diff_loader: git_diff_loader::GitDiffLoader,
+    pub(crate) git_diff_cancel_token: Option<CancellationToken>,
+    pub(crate) git_diff_result_rx: tokio_mpsc::Receiver<git_diff_loader::GitDiffResult>,
+    /// New-side line to scroll the Git diff to once it has loaded.
+    pub(crate) pending_diff_line: Option<u32>,
+    /// Commit to select in Log history once it has loaded.
+    pub(crate) pending_log_commit: Option<String>,
+    pub(crate) log_diff_job: Option<PendingJob>,
+    log_count_job: Option<PendingJob>,
+    /// Open confirmation dialog, shared by every action that asks first
+    confirm: Option<confirm::ConfirmDialog<ConfirmAction>>,
+    confirm_settings: confirm::ConfirmSettings,
+    /// First press of a double-press action, shown in the footer until it runs or expires
+    armed: Option<confirm::Armed>,
+    last_action: Option<RepeatAction>,
+    pub(crate) operation_popup: Option<OperationPopup>,
+    pub(crate) theme_picker: ThemePickerUi,
+    pub(crate) command_palette: CommandPaletteUi,
+    pub(crate) git_log: VecDeque<GitLogEntry>,
+    pub(crate) log_ui: LogUi,
+    pub(crate) terminal: TerminalState,
+
+    pub(crate) wrap_diff: bool,
+    pub(crate) diff_line_numbers: bool,
+    /// Diff small binaries as hexdumps instead of only their sizes
+    pub(crate) binary_hexdump: bool,
+    pub(crate) diff_selection: Option<DiffSelection>,
+    pub(crate) syntax_highlight: bool,
+    /// Language picked per file (absolute path) for this session; `None` is plain text
+    pub(crate) syntax_overrides: HashMap<PathBuf, Option<&'static str>>,
+    syntax_picker: Option<SyntaxPicker>,
+    pub(crate) git_zoom_diff: bool,
+    pub(crate) explorer_zoom: ExplorerZoom,
+    pub(crate) git_left_width: u16,
+
+    pub(crate) theme: theme::Theme,
+    pub(crate) palette: theme::Palette,
+    /// Syntax colors from `[syntax.<theme>]`, over each theme's own
+    syntax_colors: Vec<(theme::Theme, theme::SyntaxRole, Color)>,
+
+    pub(crate) git_diff_cache: DiffRenderCache,
+    pub(crate) log_diff_cache: DiffRenderCache,
+
+    pub(crate) explorer_parent_x: u16,
+    pub(crate) explorer_current_x: u16,
+    pub(crate) explorer_preview_x: u16,
+    pub(crate) git_diff_x: u16,
+    pub(crate) log_files_x: u16,
+    pub(crate) log_diff_x: u16,
+    /// Tab content below the top bar and banners, for click-to-focus
+    pub(crate) content_area: Rect,
+    pub(crate) explorer_focus: PaneFocus,
+    pub(crate) git_focus: PaneFocus,
+    nav_keys: list_nav::NavKeys,
+
+    pub(crate) zones: Vec<ClickZone>,
+    pub(crate) last_click: Option<(Instant, usize)>,
+    pub(crate) bookmarks: Vec<bookmarks::Bookmark>,
+    bookmarks_ui: BookmarksUi,
+
+    // Auto-refresh
+    pub(crate) last_dir_check: Instant,
+    pub(crate) dir_mtime: Option<std::time::SystemTime>,
+    pub(crate) auto_refresh: bool,
+
+    // Update confirmation
+    pub(crate) update_confirm: Option<String>, // Some(new_version) when update available
+    pub(crate) update_in_progress: bool,
+    pub(crate) spinner_frame: usize,
+
+    /// The stash dialog (`z`)
+    stash_input: Option<StashInput>,
+    push_input: Option<PushInput>,
+    /// Last push from the dialog that was not the plain one; it reports in a popup
+    /// like the plain push
+    custom_push: Option<String>,
+    pub(crate) new_branch_input: Option<String>,
+    /// The new branch starts without history (`git switch --orphan`)
+    new_branch_orphan: bool,
+    /// Commit the new branch starts at instead of HEAD; it is created without
+    /// switching to it unless `new_branch_checkout` is set
+    new_branch_from: Option<String>,
+    new_branch_checkout: bool,
+    branch_input: Option<BranchInput>,
+    init_wizard: Option<InitWizard>,
+    gitignore_picker: Option<GitignorePicker>,
+    clone_input: Option<CloneInput>,
+    tag_input: Option<TagInput>,
+    release_wizard: Option<ReleaseWizard>,
+    reset_picker: Option<ResetPicker>,
+    /// Pull options being picked
+    pull_picker: Option<git_ops::PullOptions>,
+    /// Last pull options per repository root, kept in `ui.json`
+    pull_options: BTreeMap<String, git_ops::PullOptions>,
+    message_picker: Option<MessagePicker>,
+    key_help: Option<KeyHelp>,
+    file_finder: Option<FileFinder>,
+    commit_find: Option<CommitFind>,
+    /// Last "find in this commit" term, offered again on the next open
+    commit_find_query: String,
+    /// Remembered commit messages, see `[behavior] commit_history`
+    commit_messages: commit::MessageHistory,
+    commit_history_limit: usize,
+    /// `from..to` being typed for "Git: changelog between refs…"
+    changelog_input: Option<String>,
+    changelog_view: Option<ChangelogView>,
+    remote_input: Option<RemoteInput>,
+    clone_job: Option<CloneJob>,
+    pub(crate) snapshot_name_input: Option<String>,
+    pub(crate) export_path_input: Option<String>,
+    /// Patch files or folders for `git am`, separated by spaces
+    pub(crate) am_path_input: Option<String>,
+    bundle_input: Option<BundleInput>,
+    explorer_input: Option<ExplorerInput>,
+    /// Explorer creates, renames and deletes, for u / Ctrl+r
+    file_history: file_ops::FileHistory,
+    pub(crate) goto_commit_input: Option<String>,
+
+    pub(crate) context_menu: Option<ContextMenu>,
+    pub(crate) pending_menu_action: Option<(usize, bool)>,
+
+    pub(crate) picker: Picker,
+    pub(crate) image_state: Option<StatefulProtocol>,
+    pub(crate) current_image_path: Option<PathBuf>,
+    pub(crate) preview_error: Option<String>,
+    pub(crate) status_message: Option<(String, Instant)>,
+    pub(crate) status_ttl: Duration,
+
+    pub(crate) pending_clipboard: Option<String>,
+    /// Where settings and history are read and written
+    pub(crate) dirs: Dirs,
+    pub(crate) bookmarks_path: Option<PathBuf>,
+    pub(crate) ui_settings_path: Option<PathBuf>,
+    pub(crate) external_actions: Vec<actions::ExternalAction>,
+    pub(crate) hooks: hooks::Hooks,
+    keymap: keymap::Keymap,
+    ai_config: config::AiConfig,
+    forge_config: config::ForgeConfig,
+    privacy_config: config::PrivacyConfig,
+    /// Drawn instead of everything else while set
+    lock_screen: Option<lock_screen::LockScreen>,
+    /// Last key, click or paste, for `[privacy] lock_after`
+    last_input: Instant,
+    ci: CiStatuses,
+    clipboard_config: config::ClipboardConfig,
+    features: config::Features,
+    /// Reads an environment variable, such as a token or the lock passphrase
+    getenv: fn(&str) -> Option<String>,
+    config_mtime: Option<std::time::SystemTime>,
+    last_config_check: Instant,
+    pub(crate) pane_templates: multiplexer::PaneTemplates,
+    top_bar: TopBarSegments,
+    /// Cached clock text, keyed by minute since the epoch.
+    clock: Option<(u64, String)>,
+    /// Unix time used for ages instead of the system clock (headless snapshots)
+    fixed_now: Option<i64>,
+    /// Last mouse position, for hover tooltips.
+    hover_pos: Option<(u16, u16)>,
+    pub(crate) needs_full_redraw: bool,
+
+    // Undo/Redo for file operations (revert)
+    pub(crate) undo_stack: Vec<UndoEntry>,
+    pub(crate) redo_stack: Vec<UndoEntry>,
+
+    // Preview cache (kept for potential future use with async loader)
+    #[allow(dead_code)]
+    pub(crate) preview_cache: Arc<preview_cache::PreviewCache>,
+
+    // Async preview loading
+    pub(crate) preview_loader: preview_loader::PreviewLoader,
+    pub(crate) preview_cancel_token: Option<CancellationToken>,
+    pub(crate) preview_result_rx: tokio_mpsc::Receiver<preview_loader::PreviewResult>,
+    pub(crate) preview_content: Option<String>,
+    pub(crate) preview_loading: bool,
+
+    // Preloading for adjacent files
+    pub(crate) preload_cancel_tokens: Vec<CancellationToken>,
+    pub(crate) preloaded_paths: BTreeSet<PathBuf>,
+
+    // Syntax highlighting cache for visible lines only
+    pub(crate) highlight_cache: Option<highlight::HighlightCache>,
+}
+
+/// Represents a file change that can be undone/redone
+#[derive(Clone, Debug)]
+pub(crate) struct UndoEntry {
+    /// Description of the operation
+    description: String,
+    /// File path (absolute)
+    file_path: PathBuf,
+    /// Content before the operation
+    old_content: String,
+    /// Content after the operation
+    new_content: String,
+}
+
+impl App {
+    fn new(
+        start_path: PathBuf,
+        picker: Picker,
+        preview_loader: preview_loader::PreviewLoader,
+        preview_result_rx: tokio_mpsc::Receiver<preview_loader::PreviewResult>,
+        git_diff_loader: git_diff_loader::GitDiffLoader,
+        git_diff_result_rx: tokio_mpsc::Receiver<git_diff_loader::GitDiffResult>,
+        dirs: Dirs,
+    ) -> Self {
+        git_ops::set_debug_dir(dirs.state.clone());
+        let mut app = Self {
+            current_path: start_path.clone(),
+            startup_path: start_path,
+            files: Vec::new(),
+            list_state: ListState::default(),
+            preview_scroll: 0,
+            preview_scroll_offset: 0,
+            should_quit: false,
+            show_hidden: false,
+
+            current_tab: Tab::Git,
+
+            git: GitState::new(),
+            git_operation: None,
+            git_locks: Vec::new(),
+            git_notice: git_ops::detect_git(),
+            branch_ui: BranchUi::new(),
+            branch_picker_mode: BranchPickerMode::Checkout,
+            author_picker_mode: AuthorPickerMode::LogFilter,
+            author_ui: AuthorUi::new(),
+            stash_ui: StashUi::new(),
+            snapshot_ui: SnapshotUi::new(),
+            tag_ui: TagUi::new(),
+            remote_ui: RemoteUi::new(),
+            target_remote: None,
+            superprojects: Vec::new(),
+            conflict_ui: ConflictUi::new(),
+            review: crate::review::ReviewState::default(),
+            blame: blame::BlameUi::default(),
+            review_before_commit: false,
+            count_prefix: false,
+            commit: CommitState::new(),
+            pending_job: None,
+            log_job: None,
+            ai_job: None,
+            job_queue: VecDeque::new(),
+            git_refresh_job: None,
+            git_refresh_request_id: 0,
+            git_diff_loader,
+            git_diff_cancel_token: None,
+            pending_diff_line: None,
+            pending_log_commit: None,
+            git_diff_result_rx,
+            log_diff_job: None,
+            log_count_job: None,
+            confirm: None,
+            confirm_settings: confirm::ConfirmSettings::default(),
+            armed: None,
+            last_action: None,
+            operation_popup: None,
+            theme_picker: ThemePickerUi::new(),
+            command_palette: CommandPaletteUi::new(),
+            git_log: VecDeque::new(),
+            log_ui: LogUi::new(),
+            terminal: TerminalState::new(),
+
+            wrap_diff: true,
+            diff_line_numbers: false,
+            binary_hexdump: false,
+            diff_selection: None,
+            syntax_highlight: true,
+            git_zoom_diff: false,
+            explorer_zoom: ExplorerZoom::ThreeColumn,
+            git_left_width: 40,
+
+            theme: theme::Theme::Terminal,
+            palette: theme::palette(theme::Theme::Terminal),
+            syntax_colors: Vec::new(),
+
+            syntax_overrides: HashMap::new(),
+            syntax_picker: None,
+            file_finder: None,
+            git_diff_cache: DiffRenderCache::new(),
+            log_diff_cache: DiffRenderCache::new(),
+
+            explorer_parent_x: 0,
+            explorer_current_x: 0,
+            explorer_preview_x: 0,
+            git_diff_x: 0,
+            log_files_x: 0,
+            log_diff_x: 0,
+            content_area: Rect::default(),
+            explorer_focus: PaneFocus::List,
+            git_focus: PaneFocus::List,
+            nav_keys: list_nav::NavKeys::default(),
+
+            zones: Vec::new(),
+            last_click: None,
+            bookmarks: bookmarks::defaults(&env::home_dir().unwrap_or_else(|| PathBuf::from("/"))),
+            bookmarks_ui: BookmarksUi::new(),
+            last_dir_check: Instant::now(),
+            dir_mtime: None,
+            auto_refresh: true,
+            update_confirm: None,
+            update_in_progress: false,
+            spinner_frame: 0,
+            stash_input: None,
+            push_input: None,
+            custom_push: None,
+            new_branch_input: None,
+            new_branch_orphan: false,
+            new_branch_from: None,
+            new_branch_checkout: false,
+            branch_input: None,
+            init_wizard: None,
+            gitignore_picker: None,
+            clone_input: None,
+            tag_input: None,
+            release_wizard: None,
+            reset_picker: None,
+            pull_picker: None,
+            pull_options: BTreeMap::new(),
+            message_picker: None,
+            key_help: None,
+            commit_find: None,
+            commit_find_query: String::new(),
+            commit_messages: commit::MessageHistory::default(),
+            commit_history_limit: commit::DEFAULT_MESSAGE_HISTORY,
+            changelog_input: None,
+            changelog_view: None,
+            remote_input: None,
+            clone_job: None,
+            snapshot_name_input: None,
+            export_path_input: None,
+            am_path_input: None,
+            bundle_input: None,
+            explorer_input: None,
+            file_history: file_ops::FileHistory::default(),
+            goto_commit_input: None,
+            context_menu: None,
+            pending_menu_action: None,
+            picker,
+            image_state: None,
+            current_image_path: None,
+            preview_error: None,
+            status_message: None,
+            status_ttl: Duration::from_secs(2),
+            pending_clipboard: None,
+            bookmarks_path: dirs.config_file("bookmarks.tsv"),
+            ui_settings_path: dirs.config_file("ui.json"),
+            external_actions: Vec::new(),
+            hooks: hooks::Hooks::default(),
+            keymap: keymap::Keymap::default(),
+            ai_config: config::AiConfig::default(),
+            forge_config: config::ForgeConfig::default(),
+            privacy_config: config::PrivacyConfig::default(),
+            lock_screen: None,
+            last_input: Instant::now(),
+            ci: CiStatuses::default(),
+            clipboard_config: config::ClipboardConfig::default(),
+            features: config::Features::from_env(|var| env::var(var).ok()),
+            getenv: |var| env::var(var).ok(),
+            config_mtime: None,
+            last_config_check: Instant::now(),
+            pane_templates: multiplexer::PaneTemplates::default(),
+            top_bar: TopBarSegments::default(),
+            clock: None,
+            fixed_now: None,
+            hover_pos: None,
+            needs_full_redraw: false,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            preview_cache: Arc::new(preview_cache::PreviewCache::new(256)),
+
+            preview_loader,
+            preview_cancel_token: None,
+            preview_result_rx,
+            preview_content: None,
+            preview_loading: false,
+
+            preload_cancel_tokens: Vec::new(),
+            preloaded_paths: BTreeSet::new(),
+
+            highlight_cache: None,
+            dirs,
+        };
+        match migrate_legacy_config(&app.dirs) {
+            Ok(true) => app.set_status("Moved settings to ~/.config/lzgit"),
+            Ok(false) => {}
+            Err(e) => app.set_status(format!("Settings migration failed: {}", e)),
+        }
+        app.load_persisted_bookmarks();
+        app.load_persisted_ui_settings();
+        app.load_message_history();
+        if let Err(e) = app.load_config() {
+            app.set_status(e);
+        }
+        app.load_files();
+        if !app.files.is_empty() {
+            app.list_state.select(Some(0));
+            app.update_preview();
+        }
+        app.git.refresh(&app.startup_path);
+        app.update_git_operation();
+        // Load diff for initially selected file
+        if app.git.selected_tree_entry().is_some() {
+            app.request_git_diff_update();
+        }
+        app
+    }
+
+    fn refresh_git_state(&mut self) {
+        self.start_git_refresh_job();
+    }
+
+    fn start_git_refresh_job(&mut self) {
+        if self.git_refresh_job.is_some() {
+            self.set_status("Busy");
+            return;
+        }
+
+        self.git_refresh_request_id = self.git_refresh_request_id.wrapping_add(1);
+        let request_id = self.git_refresh_request_id;
+        let startup_path = self.startup_path.clone();
+        let want_staged_diff = self.review_before_commit;
+
+        let (tx, rx) = mpsc::channel();
+        self.git_refresh_job = Some(PendingJob {
+            rx,
+            lost: JobResult::GitRefresh {
+                request_id,
+                current_path: self.current_path.clone(),
+                result: Err("Git refresh job disconnected".to_string()),
+            },
+        });
+
+        thread::spawn(move || {
+            let result = (|| -> Result<Box<GitRefreshJobOutput>, String> {
+                let mut git = GitState::new();
+                git.refresh(&startup_path);
+                let stats = |staged| {
+                    git.repo_root
+                        .as_deref()
+                        .and_then(|root| git_ops::diff_numstat(root, staged).ok())
+                        .unwrap_or_default()
+                };
+                let staged_stats = stats(true);
+                let worktree_stats = stats(false);
+                let staged_diff = git
+                    .repo_root
+                    .as_deref()
+                    .filter(|_| want_staged_diff)
+                    .and_then(|root| git_ops::staged_diff(root).ok());
+                let recent_commits = git
+                    .repo_root
+                    .as_deref()
+                    .and_then(|root| git_ops::list_history(root, git::RECENT_COMMITS, None).ok())
+                    .unwrap_or_default();
+                let signing = git.repo_root.as_deref().and_then(git_ops::commit_signing);
+                let repo_state = git
+                    .repo_root
+                    .as_deref()
+                    .map(git_ops::repo_state)
+                    .unwrap_or_default();
+                Ok(Box::new(GitRefreshJobOutput {
+                    repo_root: git.repo_root,
+                    branch: git.branch,
+                    detached: git.detached,
+                    upstream: git.upstream,
+                    ahead: git.ahead,
+                    behind: git.behind,
+                    entries: git.entries,
+                    staged_stats,
+                    worktree_stats,
+                    staged_diff,
+                    recent_commits,
+                    signing,
+                    repo_state,
+                }))
+            })();
+
+            let _ = tx.send(JobResult::GitRefresh {
+                request_id,
+                current_path: startup_path,
+                result,
+            });
+        });
+    }
+
+    fn request_git_diff_update(&mut self) {
+        // Cancel any pending git diff request
+        if let Some(token) = self.git_diff_cancel_token.take() {
+            token.cancel();
+        }
+
+        self.git.diff_request_id = self.git.diff_request_id.wrapping_add(1);
+        self.git.dir_summary_selected = 0;
+        let request_id = self.git.diff_request_id;
+
+        self.git.clear_diff_key();
+        self.git.diff_scroll_y = 0;
+        self.git.diff_scroll_x = 0;
+        // Reset full file view when selection changes
+        self.git.show_full_file = false;
+        self.git.full_file_content = None;
+        self.git.full_file_head = None;
+        self.git.full_file_highlighted = None;
+        self.git.full_file_scroll_y = 0;
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.git.diff_lines.clear();
+            self.git.diff_generation = self.git.diff_generation.wrapping_add(1);
+            self.git_diff_cache.invalidate();
+            return;
+        };
+
+        let Some(entry) = self.git.selected_tree_entry().cloned() else {
+            self.git.diff_lines.clear();
+            self.git.diff_generation = self.git.diff_generation.wrapping_add(1);
+            self.git_diff_cache.invalidate();
+            return;
+        };
+
+        self.git.diff_lines = vec!["Loading diff…".to_string()];
+        self.git.diff_generation = self.git.diff_generation.wrapping_add(1);
+        self.git_diff_cache.invalidate();
+
+        let context = self.git.diff_context.get(&entry.path).copied();
+        let path = entry.path;
+        let is_untracked = entry.is_untracked;
+        let staged = entry.x != ' ' && entry.x != '?';
+
+        // Use async git diff loader
+        let cancel_token = self.git_diff_loader.request_diff(
+            repo_root,
+            path,
+            is_untracked,
+            staged,
+            git_diff_loader::DiffOptions {
+                context,
+                hexdump: self.binary_hexdump,
+            },
+            request_id,
+        );
+        self.git_diff_cancel_token = Some(cancel_token);
+    }
+
+    /// Look for an operation in progress and lock files right away; refreshes do
+    /// this in the background.
+    fn update_git_operation(&mut self) {
+        let state = self
+            .git
+            .repo_root
+            .as_deref()
+            .map(git_ops::repo_state)
+            .unwrap_or_default();
+        self.set_repo_state(state);
+    }
+
+    pub(crate) fn set_repo_state(&mut self, state: git_ops::RepoState) {
+        self.git_operation = state.operation;
+        self.git_locks = state.locks;
+    }
+
+    /// Whether one of our own jobs may be running git right now.
+    fn git_jobs_running(&self) -> bool {
+        self.pending_job.is_some()
+            || self.git_refresh_job.is_some()
+            || self.log_job.is_some()
+            || self.log_diff_job.is_some()
+            || self.log_count_job.is_some()
+            || self.ai_job.is_some()
+            || self.clone_job.is_some()
+            || self.git_diff_loader.busy()
+    }
+
+    /// Remove lock files left by a crashed git process, refusing while git runs.
+    fn remove_stale_locks(&mut self) {
+        self.update_git_operation();
+        if self.git_locks.is_empty() {
+            self.set_status("No lock files found");
+            return;
+        }
+        let running_here = self
+            .git
+            .repo_root
+            .as_deref()
+            .is_some_and(git_ops::git_process_running);
+        if self.git_jobs_running() || running_here {
+            self.set_status("A git process is still running; leaving the lock in place");
+            return;
+        }
+        match git_ops::remove_lock_files(&self.git_locks) {
+            Ok(()) => {
+                let count = self.git_locks.len();
+                self.git_locks.clear();
+                self.set_status(format!("Removed {} stale lock file(s)", count));
+                self.start_git_refresh_job();
+            }
+            Err(e) => self.set_status(e),
+        }
+    }
+
+    /// Run the operation in the popup again, e.g. a fetch that timed out.
+    fn retry_operation(&mut self) {
+        let Some(cmd) = self.operation_popup.take().and_then(|p| p.retry) else {
+            return;
+        };
+        self.start_operation_job(&cmd, true);
+    }
+
+    fn cycle_full_file_source(&mut self) {
+        self.git.full_file_source = self.git.full_file_source.next();
+        self.set_status(match self.git.full_file_source {
+            git::FullFileSource::WorkTree => "Full file: working tree",
+            git::FullFileSource::Head => "Full file: HEAD",
+            git::FullFileSource::Both => "Full file: HEAD | working tree",
+        });
+    }
+
+    fn toggle_full_file_view(&mut self) {
+        self.git.show_full_file = !self.git.show_full_file;
+
+        if self.git.show_full_file {
+            // Load the full file content
+            let Some(repo_root) = self.git.repo_root.clone() else {
+                self.git.full_file_content = Some("Not a git repository".to_string());
+                return;
+            };
+
+            let Some(entry) = self.git.selected_tree_entry().cloned() else {
+                self.git.full_file_content = Some("No file selected".to_string());
+                return;
+            };
+
+            self.git.full_file_head = match git_ops::head_file(&repo_root, &entry.path) {
+                Ok(Some(content)) => Some(content),
+                Ok(None) => Some(format!("{} is not in HEAD", entry.path)),
+                Err(e) => Some(format!("Could not read HEAD version: {}", e)),
+            };
+            self.git.full_file_highlighted = None;
+
+            let file_path = repo_root.join(&entry.path);
+            match std::fs::read_to_string(&file_path) {
+                Ok(content) => {
+                    self.git.full_file_content = Some(content);
+                    self.git.full_file_scroll_y = 0;
+                    self.git_diff_cache.invalidate();
+                }
+                Err(e) => {
+                    // Try to read as binary
+                    if file_path.exists() {
+                        self.git.full_file_content =
+                            Some(format!("Binary file or read error: {}", e));
+                    } else {
+                        self.git.full_file_content =
+                            Some(format!("File not found: {}", entry.path));
+                    }
+                }
+            }
+            self.set_status("Full file view (s: working tree/HEAD/both, F: back to diff)");
+        } else {
+            self.git.full_file_content = None;
+            self.git.full_file_head = None;
+            self.git.full_file_highlighted = None;
+            self.git_diff_cache.invalidate();
+            self.set_status("Diff view");
+        }
+    }
+
+    /// Text of the current view: the Git tab diff, the Log tab's commit/show output,
+    /// or the Log list (with filters applied) when the list has focus.
+    fn export_view(&self, format: export::ExportFormat) -> Result<String, String> {
+        match self.current_tab {
+            Tab::Git => {
+                if self.git.diff_lines.is_empty() {
+                    return Err("No diff to export".to_string());
+                }
+                let title = self
+                    .git
+                    .selected_path()
+                    .unwrap_or_else(|| "Working tree diff".to_string());
+                Ok(export::diff(&title, &self.git.diff_lines, format))
+            }
+            Tab::Log if self.log_ui.focus == LogPaneFocus::Commits => {
+                let (title, headers, rows): (&str, &[&str], Vec<Vec<String>>) =
+                    match self.log_ui.subtab {
+                        LogSubTab::History => (
+                            "History",
+                            &["Commit", "Date", "Author", "Subject"],
+                            self.log_ui
+                                .history_filtered
+                                .iter()
+                                .filter_map(|i| self.log_ui.history.get(*i))
+                                .map(|e| {
+                                    vec![
+                                        e.short.clone(),
+                                        e.date.clone(),
+                                        e.author.clone(),
+                                        e.subject.clone(),
+                                    ]
+                                })
+                                .collect(),
+                        ),
+                        LogSubTab::Reflog => (
+                            "Reflog",
+                            &["Entry", "Commit", "Subject"],
+                            self.log_ui
+                                .reflog_filtered
+                                .iter()
+                                .filter_map(|i| self.log_ui.reflog.get(*i))
+                                .map(|e| {
+                                    vec![
+                                        e.selector.clone(),
+                                        e.hash.chars().take(7).collect(),
+                                        e.subject.clone(),
+                                    ]
+                                })
+                                .collect(),
+                        ),
+                        LogSubTab::Stash => (
+                            "Stash",
+                            &["Entry", "Subject"],
+                            self.log_ui
+                                .stash_filtered
+                                .iter()
+                                .filter_map(|i| self.log_ui.stash.get(*i))
+                                .map(|e| vec![e.selector.clone(), e.subject.clone()])
+                                .collect(),
+                        ),
+                        LogSubTab::Commands => (
+                            "Commands",
+                            &["Command"],
+                            self.git_log.iter().map(|c| vec![c.cmd.clone()]).collect(),
+                        ),
+                    };
+                if rows.is_empty() {
+                    return Err("Nothing to export".to_string());
+                }
+                Ok(export::table(title, headers, &rows, format))
+            }
+            Tab::Log => {
+                if self.log_ui.diff_lines.is_empty() {
+                    return Err("Nothing to export".to_string());
+                }
+                let title = match self.log_ui.subtab {
+                    LogSubTab::History => self
+                        .selected_history_entry()
+                        .map(|e| format!("{} {}", e.short, e.subject)),
+                    _ => self.selected_log_subject(),
+                }
+                .unwrap_or_else(|| "Diff".to_string());
+                Ok(export::diff(&title, &self.log_ui.diff_lines, format))
+            }
+            _ => Err("Export works in the Git and Log tabs".to_string()),
+        }
+    }
+
+    fn export_view_to_clipboard(&mut self, format: export::ExportFormat) {
+        match self.export_view(format) {
+            Ok(text) => self.request_copy_to_clipboard(text),
+            Err(e) => self.set_status(e),
+        }
+    }
+
+    /// Apply the patch file or folder selected in the Explorer, or ask for paths.
+    fn start_apply_patches(&mut self) {
+        if self.git.repo_root.is_none() {
+            self.set_status("Not a git repository");
+            return;
+        }
+        let selected = self
+            .selected_file()
+            .filter(|_| self.current_tab == Tab::Explorer)
+            .map(|f| f.path.clone())
+            .filter(|p| !patch_files(p).is_empty());
+        match selected {
+            Some(path) => self.confirm_apply_patches(&path.to_string_lossy()),
+            None => self.am_path_input = Some(String::new()),
+        }
+    }
+
+    /// Show the series in `input` (space-separated files or folders) before `git am`.
+    fn confirm_apply_patches(&mut self, input: &str) {
+        let mut patches = Vec::new();
+        for part in input.split_whitespace() {
+            let path = self.current_path.join(part);
+            let found = patch_files(&path);
+            if found.is_empty() {
+                self.set_status(format!("No patches in {}", part));
+                return;
+            }
+            patches.extend(found);
+        }
+        if patches.is_empty() {
+            return;
+        }
+
+        let mut subjects = Vec::new();
+        for path in &patches {
+            let text = fs::read_to_string(path).unwrap_or_default();
+            let found = git_ops::patch_subjects(&text);
+            if found.is_empty() {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                subjects.push(format!("{} (no subject)", name));
+            }
+            subjects.extend(found);
+        }
+
+        const SHOWN: usize = 12;
+        let mut lines = vec![
+            format!("{} patches from {} files:", subjects.len(), patches.len()),
+            String::new(),
+        ];
+        lines.extend(subjects.iter().take(SHOWN).map(|s| format!("  {}", s)));
+        if subjects.len() > SHOWN {
+            lines.push(format!("  … and {} more", subjects.len() - SHOWN));
+        }
+        self.confirm = Some(confirm::ConfirmDialog {
+            title: " Apply Patches ".to_string(),
+            lines,
+            confirm_label: "Apply".to_string(),
+            action: ConfirmAction::ApplyPatches(patches),
+        });
+    }
+
+    fn apply_patches(&mut self, patches: Vec<PathBuf>) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let cmd = format!("git am ({} files)", patches.len());
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::am_apply(&repo_root, &patches)
+        });
+    }
+
+    /// Run a checkout or pull that git refused over local changes again, with
+    /// the changes stashed around it.
+    fn autostash_retry(&mut self, cmd: String) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let job = format!("autostash {}", cmd);
+        if let Some(args) = cmd.strip_prefix("git pull ") {
+            let (options, target) = git_ops::PullOptions::parse(args);
+            self.start_git_job(job, true, false, move || {
+                git_ops::with_autostash(&repo_root, || {
+                    let target = target.as_ref().map(|(r, b)| (r.as_str(), b.as_str()));
+                    git_ops::pull(&repo_root, options, target)
+                })
+            });
+            return;
+        }
+        let Some(args) = cmd.strip_prefix("git checkout ") else {
+            return;
+        };
+        let (flag, target) = args
+            .split_once(' ')
+            .filter(|(flag, _)| flag.starts_with('-'))
+            .unwrap_or(("", args));
+        // `-b name start` created the branch before its checkout failed
+        let target = match flag {
+            "-b" => target.split(' ').next().unwrap_or(target),
+            _ => target,
+        };
+        let detach = flag == "--detach";
+        let branch = branch::BranchEntry {
+            name: target.to_string(),
+            is_current: false,
+            is_remote: flag == "--track",
+            upstream: None,
+            track: None,
+            description: None,
+            worktree: None,
+        };
+        let hook_config = self.active_hooks();
+        let env = self.hook_env(&[("LZGIT_TARGET", target.to_string())]);
+        self.start_git_job(job, true, false, move || {
+            git_ops::with_autostash(&repo_root, || {
+                hook_config.run(hooks::HookOp::Checkout, &repo_root, &env, || {
+                    if detach {
+                        git_ops::checkout_detached(&repo_root, &branch.name)
+                    } else {
+                        git_ops::checkout_branch_entry(&repo_root, &branch)
+                    }
+                })
+            })
+        });
+    }
+
+    fn open_init_wizard(&mut self) {
+        if self.git.repo_root.is_some() {
+            self.set_status("Already a git repository");
+            return;
+        }
+        self.init_wizard = Some(InitWizard {
+            step: InitStep::Branch,
+            branch: git_ops::default_branch_name(&self.startup_path),
+            template: 1,
+        });
+    }
+
+    fn init_wizard_key(&mut self, key: &KeyEvent) {
+        let Some(wizard) = self.init_wizard.as_mut() else {
+            return;
+        };
+        match wizard.step {
+            InitStep::Branch => match key.code {
+                KeyCode::Enter if !wizard.branch.trim().is_empty() => {
+                    wizard.step = InitStep::Gitignore;
+                }
+                KeyCode::Backspace => {
+                    wizard.branch.pop();
+                }
+                KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    wizard.branch.push(ch);
+                }
+                _ => {}
+            },
+            InitStep::Gitignore => {
+                if let Some(nav) = self.nav_keys.key(key, true) {
+                    let last = gitignore::TEMPLATES.len() as i32;
+                    wizard.template =
+                        (wizard.template as i32 + nav.delta()).clamp(0, last) as usize;
+                    return;
+                }
+                match key.code {
+                    KeyCode::Backspace => wizard.step = InitStep::Branch,
+                    KeyCode::Enter => self.init_repo(),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// `git init` where lzgit was started, with an initial commit.
+    fn init_repo(&mut self) {
+        let Some(wizard) = self.init_wizard.take() else {
+            return;
+        };
+        let dir = self.startup_path.clone();
+        let branch = wizard.branch.trim().to_string();
+        let body = wizard
+            .template
+            .checked_sub(1)
+            .and_then(|i| gitignore::TEMPLATES.get(i))
+            .map(|t| t.body);
+        let cmd = format!("git init {}", branch);
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::init_repo(&dir, &branch, body)
+        });
+    }
+
+    /// Ask for the bundle file and revisions, starting from `revs`.
+    fn start_create_bundle(&mut self, revs: &str) {
+        let Some(repo_root) = self.git.repo_root.as_ref() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let name = repo_root
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "repo".to_string());
+        let stem = revs.rsplit('/').next().unwrap_or_default();
+        let file = if stem.is_empty() || revs.contains("..") {
+            format!("{}.bundle", name)
+        } else {
+            format!("{}-{}.bundle", name, stem)
+        };
+        self.bundle_input = Some(BundleInput {
+            kind: BundleInputKind::Create,
+            text: format!("{} {}", file, revs).trim_end().to_string(),
+        });
+    }
+
+    /// Fetch or pull from the `.bundle` file selected in the Explorer, or ask for one.
+    fn start_bundle_import(&mut self, kind: BundleInputKind) {
+        if self.git.repo_root.is_none() {
+            self.set_status("Not a git repository");
+            return;
+        }
+        match self.selected_bundle() {
+            Some(path) => self.confirm_bundle_import(kind, &path.to_string_lossy()),
+            None => {
+                self.bundle_input = Some(BundleInput {
+                    kind,
+                    text: String::new(),
+                })
+            }
+        }
+    }
+
+    fn selected_bundle(&self) -> Option<PathBuf> {
+        self.selected_file()
+            .filter(|f| self.current_tab == Tab::Explorer && !f.is_dir)
+            .map(|f| f.path.clone())
+            .filter(|p| p.extension().is_some_and(|e| e == "bundle"))
+    }
+
+    fn submit_bundle_input(&mut self) {
+        let Some(input) = self.bundle_input.take() else {
+            return;
+        };
+        match input.kind {
+            BundleInputKind::Create => self.create_bundle(&input.text),
+            kind => self.confirm_bundle_import(kind, input.text.trim()),
+        }
+    }
+
+    /// `input` is the bundle file followed by refs or ranges; the current branch
+    /// when none are given.
+    fn create_bundle(&mut self, input: &str) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let mut parts = input.split_whitespace();
+        let Some(file) = parts.next() else {
+            return;
+        };
+        let mut revs: Vec<String> = parts.map(str::to_string).collect();
+        if revs.is_empty() {
+            if self.git.branch.is_empty() {
+                self.set_status("Name the refs to bundle");
+                return;
+            }
+            revs.push(self.git.branch.clone());
+        }
+        // Relative paths land in the repository root, like exports
+        let target = repo_root.join(file);
+        let cmd = format!("git bundle create {} {}", file, revs.join(" "));
+        self.start_git_job(cmd, false, false, move || {
+            git_ops::bundle_create(&repo_root, &target, &revs)
+        });
+    }
+
+    /// List what the bundle carries before fetching or pulling from it.
+    fn confirm_bundle_import(&mut self, kind: BundleInputKind, file: &str) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        if file.is_empty() {
+            return;
+        }
+        let path = self.current_path.join(file);
+        let heads = match git_ops::bundle_heads(&repo_root, &path) {
+            Ok(heads) => heads,
+            Err(e) => {
+                self.set_status(format!("Not a usable bundle: {}", e));
+                return;
+            }
+        };
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        const SHOWN: usize = 12;
+        let mut lines = vec![format!("{} refs in {}:", heads.len(), name), String::new()];
+        lines.extend(heads.iter().take(SHOWN).map(|h| format!("  {}", h)));
+        if heads.len() > SHOWN {
+            lines.push(format!("  … and {} more", heads.len() - SHOWN));
+        }
+        lines.push(String::new());
+
+        let (title, label, action) = if kind == BundleInputKind::Pull {
+            let branches: Vec<&str> = heads
+                .iter()
+                .filter_map(|h| h.strip_prefix("refs/heads/"))
+                .collect();
+            let branch = if branches.contains(&self.git.branch.as_str()) {
+                self.git.branch.clone()
+            } else if let [only] = branches.as_slice() {
+                only.to_string()
+            } else {
+                self.set_status(format!("{} has no branch {}", name, self.git.branch));
+                return;
+            };
+            lines.push(format!(
+                "Rebase {} onto {} from the bundle.",
+                self.git.branch, branch
+            ));
+            (
+                " Pull From Bundle ",
+                "Pull",
+                ConfirmAction::BundlePull(path, branch),
+            )
+        } else {
+            lines.push("Branches go to bundle/<name>; tags are fetched as-is.".to_string());
+            (
+                " Fetch From Bundle ",
+                "Fetch",
+                ConfirmAction::BundleFetch(path),
+            )
+        };
+        self.confirm = Some(confirm::ConfirmDialog {
+            title: title.to_string(),
+            lines,
+            confirm_label: label.to_string(),
+            action,
+        });
+    }
+
+    fn fetch_bundle(&mut self, path: PathBuf) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        let cmd = format!("fetch bundle {}", path.display());
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::bundle_fetch(&repo_root, &path)
+        });
+    }
+
+    fn pull_bundle(&mut self, path: PathBuf, branch: String) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        let cmd = format!("pull bundle {} {}", path.display(), branch);
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::bundle_pull(&repo_root, &path, &branch)
+        });
+    }
+
+    fn export_view_to_file(&mut self, path: &str) {
+        let path = path.trim();
+        if path.is_empty() {
+            return;
+        }
+        // Relative paths land in the repository root, like other files lzgit writes
+        let base = self
+            .git
+            .repo_root
+            .clone()
+            .unwrap_or_else(|| self.current_path.clone());
+        let target = base.join(path);
+        let format = export::ExportFormat::for_path(&target);
+        let text = match self.export_view(format) {
+            Ok(t) => t,
+            Err(e) => {
+                self.set_status(e);
+                return;
+            }
+        };
+        match fs::write(&target, text) {
+            Ok(()) => self.set_status(format!("Exported to {}", target.display())),
+            Err(e) => self.set_status(format!("Export failed: {}", e)),
+        }
+    }
+
+    fn open_author_picker(&mut self) {
+        self.author_picker_mode = AuthorPickerMode::LogFilter;
+        self.context_menu = None;
+        self.commit.open = false;
+        self.branch_ui.open = false;
+        self.stash_ui.open = false;
+
+        if self.git.repo_root.is_none() {
+            self.set_status("Not a git repository");
+            return;
+        }
+
+        let mut unique = BTreeSet::new();
+        for e in &self.log_ui.history {
+            let a = e.author.trim();
+            if !a.is_empty() {
+                unique.insert(a.to_string());
+            }
+        }
+
+        let authors: Vec<String> = unique.into_iter().collect();
+        if authors.is_empty() {
+            self.set_status("No authors loaded");
+            return;
+        }
+
+        self.author_ui.open = true;
+        self.author_ui.set_authors(authors);
+    }
+
+    /// Pick the `--author` of the next commit from past identities.
+    fn open_commit_author_picker(&mut self) {
+        self.context_menu = None;
+        self.branch_ui.open = false;
+        self.stash_ui.open = false;
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.commit.set_status("Not a git repository");
+            return;
+        };
+        match git_ops::list_author_idents(&repo_root, 5000, true) {
+            Ok(authors) if !authors.is_empty() => {
+                self.author_picker_mode = AuthorPickerMode::CommitAuthor;
+                self.author_ui.open = true;
+                self.author_ui.set_authors(authors);
+            }
+            Ok(_) => self.commit.set_status("No past authors"),
+            Err(e) => self.commit.set_status(e),
+        }
+    }
+
+    /// Credit others with `Co-authored-by:` trailers, picked from past identities.
+    fn open_co_author_picker(&mut self) {
+        self.context_menu = None;
+        self.branch_ui.open = false;
+        self.stash_ui.open = false;
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.commit.set_status("Not a git repository");
+            return;
+        };
+        match git_ops::list_author_idents(&repo_root, 5000, true) {
+            Ok(mut authors) if !authors.is_empty() => {
+                let marked = commit::co_authors(&self.commit.message);
+                for author in &marked {
+                    if !authors.contains(author) {
+                        authors.push(author.clone());
+                    }
+                }
+                self.author_picker_mode = AuthorPickerMode::CoAuthors;
+                self.author_ui.open = true;
+                self.author_ui.set_authors(authors);
+                self.author_ui.marked = marked;
+            }
+            Ok(_) => self.commit.set_status("No past authors"),
+            Err(e) => self.commit.set_status(e),
+        }
+    }
+
+    /// Keep `message` for the message picker, whether or not the commit goes through.
+    fn remember_commit_message(&mut self, repo_root: &Path, message: &str) {
+        self.commit_messages
+            .remember(repo_root, message, self.commit_history_limit);
+        if let Some(path) = self.dirs.state_file("commit_messages.json")
+            && let Err(e) = self.commit_messages.save(&path)
+        {
+            self.commit
+                .set_status(format!("Commit message history: {}", e));
+        }
+    }
+
+    fn open_message_picker(&mut self) {
+        let Some(repo_root) = self.git.repo_root.as_deref() else {
+            self.commit.set_status("Not a git repository");
+            return;
+        };
+        let templates: Vec<(String, String)> = git_ops::commit_templates(repo_root)
+            .into_iter()
+            .map(|(path, text)| {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                (name, commit::template_message(&text))
+            })
+            .filter(|(_, message)| !message.trim().is_empty())
+            .collect();
+        if templates.is_empty() && self.commit_messages.messages(repo_root).is_empty() {
+            self.commit
+                .set_status("No past commit messages or templates");
+            return;
+        }
+        self.message_picker = Some(MessagePicker {
+            selected: 0,
+            templates,
+        });
+    }
+
+    /// The picker's rows: each template with its file name, then past messages.
+    fn message_picker_rows(&self) -> Vec<(Option<&str>, &str)> {
+        let Some(picker) = &self.message_picker else {
+            return Vec::new();
+        };
+        let history = self
+            .git
+            .repo_root
+            .as_deref()
+            .map(|root| self.commit_messages.messages(root))
+            .unwrap_or_default();
+        picker
+            .templates
+            .iter()
+            .map(|(name, message)| (Some(name.as_str()), message.as_str()))
+            .chain(history.iter().map(|message| (None, message.as_str())))
+            .collect()
+    }
+
+    fn message_picker_key(&mut self, key: &KeyEvent) {
+        let (Some(repo_root), Some(picker)) =
+            (self.git.repo_root.clone(), self.message_picker.as_ref())
+        else {
+            self.message_picker = None;
+            return;
+        };
+        let len = self.message_picker_rows().len();
+        let templates = picker.templates.len();
+        let selected = picker.selected;
+        if let Some(nav) = self.nav_keys.key(key, false) {
+            let last = len.saturating_sub(1) as i32;
+            let row = (selected as i32 + nav.delta()).clamp(0, last) as usize;
+            if let Some(picker) = self.message_picker.as_mut() {
+                picker.selected = row;
+            }
+            return;
+        }
+        match key.code {
+            KeyCode::Enter => self.update(Msg::Action(AppAction::PickCommitMessage(selected))),
+            // Templates live in their files; only past messages can be forgotten
+            KeyCode::Char('d') | KeyCode::Delete if selected >= templates => {
+                self.commit_messages
+                    .forget(&repo_root, selected - templates);
+                if len > 1 {
+                    if let Some(picker) = self.message_picker.as_mut() {
+                        picker.selected = selected.min(len - 2);
+                    }
+                } else {
+                    self.message_picker = None;
+                }
+                if let Some(path) = self.dirs.state_file("commit_messages.json")
+                    && let Err(e) = self.commit_messages.save(&path)
+                {
+                    self.commit
+                        .set_status(format!("Commit message history: {}", e));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Replace the drawer's message with a template or a past one, ready to edit.
+    fn pick_commit_message(&mut self, idx: usize) {
+        let Some(message) = self
+            .message_picker_rows()
+            .get(idx)
+            .map(|(_, message)| message.to_string())
+        else {
+            self.message_picker = None;
+            return;
+        };
+        self.message_picker = None;
+        self.commit.message = message;
+        self.commit.cursor = self.commit.message.chars().count();
+        self.commit.scroll_y = 0;
+        self.commit.focus = CommitFocus::Message;
+        self.commit.open = true;
+    }
+
+    /// Pick two identities as they were committed, the real one first, and map the
+    /// second onto it in `.mailmap`.
+    fn open_mailmap_picker(&mut self) {
+        self.context_menu = None;
+        self.branch_ui.open = false;
+        self.stash_ui.open = false;
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        match git_ops::list_author_idents(&repo_root, 5000, false) {
+            Ok(authors) if authors.len() > 1 => {
+                self.author_picker_mode = AuthorPickerMode::Mailmap;
+                self.author_ui.open = true;
+                self.author_ui.canonical = None;
+                self.author_ui.set_authors(authors);
+            }
+            Ok(_) => self.set_status("Only one author identity"),
+            Err(e) => self.set_status(e),
+        }
+    }
+
+    fn close_author_picker(&mut self) {
+        self.author_ui.canonical = None;
+        self.author_ui.marked.clear();
+        self.author_ui.open = false;
+        self.author_ui.query.clear();
+        self.author_ui.authors.clear();
+        self.author_ui.filtered.clear();
+        self.author_ui.list_state.select(None);
+        self.author_ui.status = None;
+    }
+
+    fn confirm_author_picker(&mut self) {
+        let Some(author) = self.author_ui.selected_author().map(str::to_string) else {
+            self.set_status("No author selected");
+            return;
+        };
+        if self.author_picker_mode == AuthorPickerMode::CommitAuthor {
+            self.commit.set_status(format!("Committing as {}", author));
+            self.commit.author = Some(author);
+            self.commit.advanced = true;
+            self.close_author_picker();
+            return;
+        }
+        if self.author_picker_mode == AuthorPickerMode::Mailmap {
+            self.map_author(author);
+            return;
+        }
+        if self.author_picker_mode == AuthorPickerMode::CoAuthors {
+            // Enter with nothing ticked adds the selected identity
+            let mut authors = std::mem::take(&mut self.author_ui.marked);
+            if authors.is_empty() {
+                authors.push(author);
+            }
+            self.commit.message = commit::set_co_authors(&self.commit.message, &authors);
+            self.commit.cursor = self.commit.cursor.min(self.commit.message.chars().count());
+            self.commit.focus = CommitFocus::Message;
+            self.commit.set_status(match authors.len() {
+                1 => format!("Co-authored by {}", authors[0]),
+                n => format!("Co-authored by {} people", n),
+            });
+            self.close_author_picker();
+            return;
+        }
+
+        self.set_filter_author(author.as_str());
+        self.log_ui.update_filtered();
+        self.refresh_log_diff();
+        self.close_author_picker();
+    }
+
+    /// First pick in Mailmap mode: the real identity; second: the one mapped onto it.
+    fn map_author(&mut self, author: String) {
+        let Some(canonical) = self.author_ui.canonical.clone() else {
+            self.author_ui.canonical = Some(author);
+            self.author_ui.query.clear();
+            self.author_ui.update_filtered();
+            return;
+        };
+        if canonical == author {
+            self.set_status("Pick a different identity to map");
+            return;
+        }
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        self.close_author_picker();
+        match git_ops::add_mailmap_entry(&repo_root, &canonical, &author) {
+            Ok(true) => {
+                self.set_status(format!("Mapped {} onto {} in .mailmap", author, canonical));
+                self.refresh_git_state();
+                if self.current_tab == Tab::Log {
+                    self.refresh_log_data();
+                }
+            }
+            Ok(false) => self.set_status("Already in .mailmap"),
+            Err(e) => self.set_status(e),
+        }
+    }
+
+    fn set_filter_author(&mut self, author: &str) {
+        let author_token = if author.chars().any(|c| c.is_whitespace()) {
+            format!("@\"{}\"", author)
+        } else {
+            format!("@{}", author)
+        };
+
+        let tokens = split_query_tokens(self.log_ui.filter_query.as_str());
+        let mut out: Vec<String> = Vec::new();
+        for t in tokens {
+            let tt = t.trim();
+            if tt.starts_with('@') {
+                continue;
+            }
+            if tt.starts_with("author:") || tt.starts_with("a:") {
+                continue;
+            }
+            out.push(tt.to_string());
+        }
+        out.push(author_token);
+        self.log_ui.filter_query = out.join(" ");
+    }
+
+    fn open_tag_picker(&mut self) {
+        self.context_menu = None;
+        self.commit.open = false;
+        self.branch_ui.open = false;
+        self.stash_ui.open = false;
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        match git_ops::list_tags(&repo_root) {
+            Ok(tags) => {
+                self.tag_ui.open = true;
+                self.tag_ui.status = None;
+                self.tag_ui.list_state.select(None);
+                self.tag_ui.tags = tags;
+                self.tag_ui.move_selection(0);
+            }
+            Err(e) => {
+                self.set_status(e);
+            }
+        }
+    }
+
+    fn close_tag_picker(&mut self) {
+        self.tag_ui.open = false;
+        self.tag_ui.tags.clear();
+        self.tag_ui.list_state.select(None);
+        self.tag_ui.status = None;
+    }
+
+    fn reload_tag_picker(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        match git_ops::list_tags(&repo_root) {
+            Ok(tags) => {
+                self.tag_ui.tags = tags;
+                self.tag_ui.move_selection(0);
+            }
+            Err(e) => self.tag_ui.status = Some(e),
+        }
+    }
+
+    /// Show the history of the selected tag in the Log tab.
+    fn show_selected_tag(&mut self) {
+        let Some(name) = self.tag_ui.selected_tag().map(|t| t.name.clone()) else {
+            self.tag_ui.status = Some("No tag selected".to_string());
+            return;
+        };
+        self.close_tag_picker();
+        self.current_tab = Tab::Log;
+        self.set_log_subtab(LogSubTab::History);
+        self.log_ui.history_ref = Some(name);
+        self.refresh_log_data();
+    }
+
+    fn delete_selected_tag(&mut self) {
+        let Some(name) = self.tag_ui.selected_tag().map(|t| t.name.clone()) else {
+            self.tag_ui.status = Some("No tag selected".to_string());
+            return;
+        };
+        self.request_confirm(
+            confirm::ConfirmKind::Delete,
+            true,
+            confirm::ConfirmDialog {
+                title: " Delete Tag ".to_string(),
+                lines: vec![format!("Delete tag `{}`?", name)],
+                confirm_label: "Delete".to_string(),
+                action: ConfirmAction::DeleteTag(name),
+            },
+        );
+    }
+
+    fn delete_tag(&mut self, name: String) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        if self.log_ui.history_ref.as_deref() == Some(name.as_str()) {
+            self.log_ui.history_ref = None;
+        }
+        self.start_git_job(format!("git tag -d {}", name), true, false, move || {
+            git_ops::delete_tag(&repo_root, &name)
+        });
+    }
+
+    /// Ask before pushing the selected tag, or every tag with `all`.
+    fn push_tags_confirm(&mut self, all: bool) {
+        let names = if all {
+            Vec::new()
+        } else if let Some(tag) = self.tag_ui.selected_tag() {
+            vec![tag.name.clone()]
+        } else {
+            self.tag_ui.status = Some("No tag selected".to_string());
+            return;
+        };
+        let line = match names.first() {
+            Some(name) => format!("Push tag `{}`?", name),
+            None => format!("Push all {} tags?", self.tag_ui.tags.len()),
+        };
+        self.request_confirm(
+            confirm::ConfirmKind::Push,
+            false,
+            confirm::ConfirmDialog {
+                title: " Push Tags ".to_string(),
+                lines: vec![line],
+                confirm_label: "Push".to_string(),
+                action: ConfirmAction::PushTags(names),
+            },
+        );
+    }
+
+    fn push_tags(&mut self, names: Vec<String>) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let remote = match git_ops::default_remote(&repo_root) {
+            Ok(remote) => remote,
+            Err(e) => {
+                if self.tag_ui.open {
+                    self.tag_ui.status = Some(e.clone());
+                }
+                self.set_status(e);
+                return;
+            }
+        };
+        let target = if names.is_empty() {
+            "--tags".to_string()
+        } else {
+            names.join(" ")
+        };
+        let cmd = format!("git push {} {}", remote, target);
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::push_tags(&repo_root, &remote, &names)
+        });
+    }
+
+    fn tag_selected_commit(&mut self) {
+        if self.git.repo_root.is_none() {
+            self.set_status("Not a git repository");
+            return;
+        }
+        let Some(entry) = self.selected_history_entry() else {
+            self.set_status("No commit selected");
+            return;
+        };
+        self.tag_input = Some(TagInput {
+            target: entry.hash.clone(),
+            label: format!("{} {}", entry.short, entry.subject),
+            name: String::new(),
+            message: String::new(),
+            focus_message: false,
+        });
+    }
+
+    fn tag_input_key(&mut self, key: &KeyEvent) {
+        let Some(input) = self.tag_input.as_mut() else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let field = if input.focus_message {
+            &mut input.message
+        } else {
+            &mut input.name
+        };
+        match key.code {
+            KeyCode::Tab | KeyCode::BackTab => input.focus_message = !input.focus_message,
+            KeyCode::Enter if ctrl => self.create_tag(),
+            KeyCode::Char('s') if ctrl => self.create_tag(),
+            KeyCode::Enter if input.focus_message => input.message.push('\n'),
+            KeyCode::Enter => input.focus_message = true,
+            KeyCode::Backspace => {
+                field.pop();
+            }
+            KeyCode::Char(ch) if !ctrl => field.push(ch),
+            _ => {}
+        }
+    }
+
+    fn create_tag(&mut self) {
+        let Some(input) = self.tag_input.take() else {
+            return;
+        };
+        let name = input.name.trim().to_string();
+        if name.is_empty() {
+            self.tag_input = Some(input);
+            return;
+        }
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let (target, message) = (input.target, input.message.trim().to_string());
+        let short = &target[..target.len().min(7)];
+        let cmd = if message.is_empty() {
+            format!("git tag {} {}", name, short)
+        } else {
+            format!("git tag -a {} {}", name, short)
+        };
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::create_tag(&repo_root, &name, &target, &message)
+        });
+    }
+
+    fn open_release_wizard(&mut self) {
+        let Some(repo_root) = self.git.repo_root.as_ref() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let last_tag = match git_ops::latest_tag(repo_root) {
+            Ok(tag) => tag,
+            Err(e) => {
+                self.set_status(e);
+                return;
+            }
+        };
+        let range = match &last_tag {
+            Some(tag) => format!("{}..HEAD", tag),
+            None => "HEAD".to_string(),
+        };
+        let commits = match git_ops::list_history(repo_root, RELEASE_MAX_COMMITS, Some(&range)) {
+            Ok(commits) => commits,
+            Err(e) => {
+                self.set_status(e);
+                return;
+            }
+        };
+        if commits.is_empty() {
+            self.set_status("Nothing to release since the last tag");
+            return;
+        }
+        let remote = match self.target_remote() {
+            Some(remote) => Some(remote.to_string()),
+            None => git_ops::default_remote(repo_root).ok(),
+        };
+        let suggested = changelog::suggested_bump(&commits);
+        let base = last_tag
+            .as_deref()
+            .and_then(changelog::Version::parse)
+            .unwrap_or_default();
+        self.release_wizard = Some(ReleaseWizard {
+            step: ReleaseStep::Version,
+            custom: base.bump(suggested).to_string(),
+            base,
+            last_tag,
+            commit_count: commits.len(),
+            suggested,
+            selected: changelog::Bump::ALL
+                .iter()
+                .position(|b| *b == suggested)
+                .unwrap_or(0),
+            notes: changelog::plain_text(&changelog::group(&commits)),
+            notes_scroll: 0,
+            include_notes: true,
+            push: remote.is_some(),
+            remote,
+        });
+    }
+
+    fn release_wizard_key(&mut self, key: &KeyEvent) {
+        let Some(wizard) = self.release_wizard.as_mut() else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match wizard.step {
+            ReleaseStep::Version => {
+                if let Some(nav) = self.nav_keys.key(key, false) {
+                    let last = changelog::Bump::ALL.len() as i32;
+                    wizard.selected =
+                        (wizard.selected as i32 + nav.delta()).clamp(0, last) as usize;
+                    return;
+                }
+                let custom = wizard.selected == changelog::Bump::ALL.len();
+                match key.code {
+                    KeyCode::Enter if !wizard.tag_name().is_empty() => {
+                        wizard.step = ReleaseStep::Notes;
+                    }
+                    KeyCode::Backspace if custom => {
+                        wizard.custom.pop();
+                    }
+                    KeyCode::Char(ch) if custom && !ctrl && ch != ' ' => wizard.custom.push(ch),
+                    _ => {}
+                }
+            }
+            ReleaseStep::Notes => {
+                if let Some(nav) = self.nav_keys.key(key, false) {
+                    let lines = wizard.notes.lines().count() as i32;
+                    wizard.notes_scroll =
+                        (wizard.notes_scroll as i32 + nav.delta()).clamp(0, lines) as u16;
+                    return;
+                }
+                match key.code {
+                    KeyCode::Char(' ') => wizard.include_notes = !wizard.include_notes,
+                    KeyCode::Backspace => wizard.step = ReleaseStep::Version,
+                    KeyCode::Enter => wizard.step = ReleaseStep::Push,
+                    _ => {}
+                }
+            }
+            ReleaseStep::Push => match key.code {
+                KeyCode::Char(' ') if wizard.remote.is_some() => wizard.push = !wizard.push,
+                KeyCode::Backspace => wizard.step = ReleaseStep::Notes,
+                KeyCode::Enter => self.cut_release(),
+                _ => {}
+            },
+        }
+    }
+
+    /// Annotated tag on HEAD with the wizard's notes, pushed when asked.
+    fn cut_release(&mut self) {
+        let Some(wizard) = self.release_wizard.take() else {
+            return;
+        };
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let (name, message) = (wizard.tag_name(), wizard.message());
+        let remote = wizard.remote.filter(|_| wizard.push);
+        let cmd = match &remote {
+            Some(remote) => format!("release {} to {}", name, remote),
+            None => format!("release {}", name),
+        };
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::create_tag(&repo_root, &name, "HEAD", &message)?;
+            match remote {
+                Some(remote) => {
+                    git_ops::push_tags(&repo_root, &remote, std::slice::from_ref(&name))
+                        .map_err(|e| format!("Tagged {}, but the push failed: {}", name, e))
+                }
+                None => Ok(()),
+            }
+        });
+    }
+
+    /// Ask for the range, starting from the last tag up to HEAD.
+    fn open_changelog_input(&mut self) {
+        let Some(repo_root) = self.git.repo_root.as_ref() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let range = match git_ops::latest_tag(repo_root) {
+            Ok(Some(tag)) => format!("{}..HEAD", tag),
+            _ => String::new(),
+        };
+        self.changelog_input = Some(range);
+    }
+
+    fn changelog_input_key(&mut self, key: &KeyEvent) {
+        let Some(input) = self.changelog_input.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Enter => {
+                if let Some(range) = self.changelog_input.take() {
+                    self.generate_changelog(range.trim());
+                }
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => input.push(ch),
+            _ => {}
+        }
+    }
+
+    /// Changelog for `from..to` (`to` defaults to HEAD), linked to the web page of
+    /// the picked or default remote when it has one.
+    fn generate_changelog(&mut self, range: &str) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let (from, to) = match range.split_once("..") {
+            Some((from, to)) => (from, to.trim_start_matches('.')),
+            None => (range, ""),
+        };
+        let from = Some(from).filter(|f| !f.is_empty());
+        let to = if to.is_empty() { "HEAD" } else { to };
+        let revs = match from {
+            Some(from) => format!("{}..{}", from, to),
+            None => to.to_string(),
+        };
+        let commits = match git_ops::list_history(&repo_root, RELEASE_MAX_COMMITS, Some(&revs)) {
+            Ok(commits) => commits,
+            Err(e) => {
+                self.set_status(e);
+                return;
+            }
+        };
+        let web = self.remote_web_url(&repo_root);
+        let groups = changelog::group(&commits);
+        self.changelog_view = Some(ChangelogView {
+            range: revs,
+            markdown: changelog::markdown(from, to, &groups, web.as_deref()),
+            scroll_y: 0,
+            export_path: None,
+        });
+    }
+
+    fn changelog_view_key(&mut self, key: &KeyEvent) {
+        let Some(view) = self.changelog_view.as_mut() else {
+            return;
+        };
+        if let Some(path) = view.export_path.as_mut() {
+            match key.code {
+                KeyCode::Enter => {
+                    let path = view.export_path.take().unwrap_or_default();
+                    self.export_changelog(&path);
+                }
+                KeyCode::Backspace => {
+                    path.pop();
+                }
+                KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    path.push(ch);
+                }
+                _ => {}
+            }
+            return;
+        }
+        if let Some(nav) = self.nav_keys.key(key, true) {
+            let lines = view.markdown.lines().count() as u16;
+            view.scroll_y = nav.scroll_u16(view.scroll_y).min(lines.saturating_sub(1));
+            return;
+        }
+        let action = match key.code {
+            KeyCode::Char('y') => AppAction::CopyChangelog,
+            KeyCode::Char('e') => AppAction::ExportChangelog,
+            KeyCode::Enter | KeyCode::Char('q') => AppAction::CloseChangelog,
+            _ => return,
+        };
+        self.update(Msg::Action(action));
+    }
+
+    /// Relative paths land in the repository root, as with "Export view to file".
+    fn export_changelog(&mut self, path: &str) {
+        let path = path.trim();
+        let (Some(view), Some(repo_root)) = (&self.changelog_view, &self.git.repo_root) else {
+            return;
+        };
+        if path.is_empty() {
+            return;
+        }
+        let target = repo_root.join(path);
+        match fs::write(&target, &view.markdown) {
+            Ok(()) => self.set_status(format!("Exported to {}", target.display())),
+            Err(e) => self.set_status(format!("Export failed: {}", e)),
+        }
+    }
+
+    /// The remote picked in the remote manager for this repository, if any.
+    /// Web page of the picked remote, or of the default one.
+    fn remote_web_url(&self, repo_root: &Path) -> Option<String> {
+        let name = match self.target_remote() {
+            Some(remote) => remote.to_string(),
+            None => git_ops::default_remote(repo_root).ok()?,
+        };
+        let remotes = git_ops::list_remotes(repo_root).ok()?;
+        let remote = remotes.into_iter().find(|r| r.name == name)?;
+        git_ops::web_url(&remote.fetch_url)
+    }
+
+    fn target_remote(&self) -> Option<&str> {
+        let (root, name) = self.target_remote.as_ref()?;
+        (self.git.repo_root.as_ref() == Some(root)).then_some(name.as_str())
+    }
+
+    /// `base` (fetch, pull or push) aimed at the picked remote, or unchanged when
+    /// it goes to the upstream.
+    fn remote_command(&self, base: &str) -> String {
+        match self.target_remote() {
+            Some(remote) if base == "git fetch --prune" => format!("{} {}", base, remote),
+            Some(remote) if !self.git.branch.is_empty() => {
+                format!("{} {} {}", base, remote, self.git.branch)
+            }
+            _ => base.to_string(),
+        }
+    }
+
+    fn fetch(&mut self) {
+        let cmd = self.remote_command("git fetch --prune");
+        self.start_operation_job(&cmd, true);
+    }
+
+    /// Keys for the hint bar, for what has the focus and what is selected there
+    fn hint_bar(&self) -> Vec<(String, &'static str)> {
+        use keymap::KeyScope;
+        let (context, scope) = match self.current_tab {
+            _ if self.blame.open => ("Blame", None),
+            Tab::Terminal => return Vec::new(),
+            Tab::Git if self.commit.open => ("Commit drawer", None),
+            Tab::Git if self.review.open => ("Review", None),
+            Tab::Git if self.stash_ui.open => return Vec::new(),
+            Tab::Log if self.log_ui.filter_edit || self.log_ui.inspect.open => {
+                return Vec::new();
+            }
+            tab if self.diff_selection_range(tab).is_some() => ("Diff selection", None),
+            Tab::Explorer => ("Explorer", Some(KeyScope::Explorer)),
+            Tab::Git
+                if self
+                    .git
+                    .selected_tree_entry()
+                    .is_some_and(|e| e.is_conflict) =>
+            {
+                ("Git conflicts", Some(KeyScope::Git))
+            }
+            Tab::Git => ("Git", Some(KeyScope::Git)),
+            Tab::Log => ("Log", Some(KeyScope::Log)),
+        };
+        self.keymap.bar(context, scope)
+    }
+
+    /// The action `[keys.*]` binds `key` to where it was pressed, or the help
+    /// overlay for `?`. Nothing while the tab is taking text or a panel is over it.
+    fn keymap_action(&self, key: &KeyEvent) -> Option<AppAction> {
+        let scope = match self.current_tab {
+            Tab::Explorer => keymap::KeyScope::Explorer,
+            Tab::Git if self.commit.open || self.review.open || self.stash_ui.open => return None,
+            Tab::Git => keymap::KeyScope::Git,
+            Tab::Log if self.log_ui.filter_edit || self.log_ui.inspect.open => return None,
+            Tab::Log => keymap::KeyScope::Log,
+            Tab::Terminal => return None,
+        };
+        if self.diff_selection.is_some() {
+            return None;
+        }
+        self.keymap.lookup(scope, key).or_else(|| {
+            // Built-in keys that no tab handles itself
+            [AppAction::OpenKeyHelp, AppAction::OpenFileFinder]
+                .into_iter()
+                .find(|action| keymap::builtin(action).is_some_and(|(_, spec)| spec.matches(key)))
+        })
+    }
+
+    /// Rows of the key bindings overlay: a heading per context, the current tab's first,
+    /// then key and action, keeping the rows that match every word of `query`.
+    fn key_help_rows(&self, query: &str) -> Vec<(String, String)> {
+        let mut groups: Vec<(&str, Vec<(String, String)>)> = Vec::new();
+        for scope in keymap::KeyScope::ALL {
+            let mut rows = Vec::new();
+            for bindable in keymap::BINDABLE {
+                let keys = self.keymap.effective(scope, &bindable.action);
+                if !keys.is_empty() {
+                    let keys: Vec<String> = keys.iter().map(ToString::to_string).collect();
+                    rows.push((keys.join(", "), bindable.label.to_string()));
+                }
+            }
+            groups.push((scope.label(), rows));
+        }
+        for hint in keymap::BUILTIN_KEYS {
+            let scope = keymap::KeyScope::ALL
+                .into_iter()
+                .find(|s| s.label() == hint.context);
+            if scope.is_some_and(|s| self.keymap.takes_over(s, hint.keys)) {
+                continue;
+            }
+            let row = (hint.keys.to_string(), hint.what.to_string());
+            match groups
+                .iter_mut()
+                .find(|(context, _)| *context == hint.context)
+            {
+                Some((_, rows)) => rows.push(row),
+                None => groups.push((hint.context, vec![row])),
+            }
+        }
+        // Listed by their config name, ready to bind
+        let unbound: Vec<(String, String)> = keymap::BINDABLE
+            .iter()
+            .filter(|bindable| {
+                keymap::KeyScope::ALL
+                    .iter()
+                    .all(|scope| self.keymap.effective(*scope, &bindable.action).is_empty())
+            })
+            .map(|bindable| (bindable.name.to_string(), bindable.label.to_string()))
+            .collect();
+        groups.push(("Unbound", unbound));
+        // The keys of the tab in use come first
+        let current = match self.current_tab {
+            Tab::Explorer => keymap::KeyScope::Explorer.label(),
+            Tab::Git => keymap::KeyScope::Git.label(),
+            Tab::Log => keymap::KeyScope::Log.label(),
+            Tab::Terminal => "Terminal",
+        };
+        if let Some(i) = groups.iter().position(|(context, _)| *context == current) {
+            let group = groups.remove(i);
+            groups.insert(0, group);
+        }
+
+        let query = query.trim().to_lowercase();
+        let tokens: Vec<&str> = query.split_whitespace().collect();
+        let mut rows = Vec::new();
+        for (context, group) in groups {
+            let shown: Vec<(String, String)> = group
+                .into_iter()
+                .filter(|(key, label)| {
+                    let hay = format!("{} {} {}", context, key, label).to_lowercase();
+                    tokens.iter().all(|t| token_score(&hay, t).is_some())
+                })
+                .collect();
+            if !shown.is_empty() {
+                rows.push((String::new(), context.to_string()));
+                rows.extend(shown);
+            }
+        }
+        rows
+    }
+
+    fn key_help_key(&mut self, key: &KeyEvent) {
+        let Some(help) = self.key_help.as_mut() else {
+            return;
+        };
+        if let Some(nav) = self.nav_keys.key(key, false) {
+            help.scroll = nav.scroll_u16(help.scroll);
+            return;
+        }
+        match key.code {
+            KeyCode::Enter => self.key_help = None,
+            KeyCode::Char('?') if help.query.is_empty() => self.key_help = None,
+            KeyCode::Backspace => {
+                help.query.pop();
+                help.scroll = 0;
+            }
+            KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                help.query.push(ch);
+                help.scroll = 0;
+            }
+            _ => {}
+        }
+    }
+
+    /// Open the pull options on the ones last used in this repository.
+    fn pull(&mut self) {
+        let Some(repo_root) = self.git.repo_root.as_ref() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let key = repo_root.to_string_lossy().into_owned();
+        let options = self.pull_options.get(&key).copied().unwrap_or_default();
+        self.pull_picker = Some(options);
+    }
+
+    fn pull_picker_key(&mut self, key: &KeyEvent) {
+        let Some(options) = self.pull_picker.as_mut() else {
+            return;
+        };
+        if let Some(nav) = self.nav_keys.key(key, false) {
+            let all = &git_ops::PullStrategy::ALL;
+            let at = all.iter().position(|s| *s == options.strategy).unwrap_or(0) as i32;
+            options.strategy = all[(at + nav.delta()).clamp(0, all.len() as i32 - 1) as usize];
+            return;
+        }
+        let action = match key.code {
+            KeyCode::Char('m') => AppAction::PullStrategy(git_ops::PullStrategy::Merge),
+            KeyCode::Char('r') => AppAction::PullStrategy(git_ops::PullStrategy::Rebase),
+            KeyCode::Char('f') => AppAction::PullStrategy(git_ops::PullStrategy::FfOnly),
+            KeyCode::Char('a') | KeyCode::Char(' ') => AppAction::TogglePullAutostash,
+            KeyCode::Enter => {
+                self.submit_pull();
+                return;
+            }
+            _ => return,
+        };
+        self.update(Msg::Action(action));
+    }
+
+    fn pick_pull_strategy(&mut self, strategy: git_ops::PullStrategy) {
+        if let Some(options) = self.pull_picker.as_mut() {
+            options.strategy = strategy;
+            self.submit_pull();
+        }
+    }
+
+    /// Pull with the picked options and remember them for this repository.
+    fn submit_pull(&mut self) {
+        let Some(options) = self.pull_picker.take() else {
+            return;
+        };
+        let Some(repo_root) = self.git.repo_root.as_ref() else {
+            return;
+        };
+        let key = repo_root.to_string_lossy().into_owned();
+        self.pull_options.insert(key, options);
+        self.save_persisted_ui_settings();
+        let cmd = self.remote_command(&format!("git pull {}", options.args().join(" ")));
+        self.start_operation_job(&cmd, true);
+    }
+
+    fn open_remote_manager(&mut self) {
+        self.context_menu = None;
+        self.commit.open = false;
+        self.branch_ui.open = false;
+        self.stash_ui.open = false;
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        match git_ops::list_remotes(&repo_root) {
+            Ok(remotes) => {
+                self.remote_ui.open = true;
+                self.remote_ui.status = None;
+                self.remote_ui.list_state.select(None);
+                self.remote_ui.remotes = remotes;
+                self.remote_ui.move_selection(0);
+            }
+            Err(e) => {
+                self.set_status(e);
+            }
+        }
+    }
+
+    fn close_remote_manager(&mut self) {
+        self.remote_ui.open = false;
+        self.remote_ui.remotes.clear();
+        self.remote_ui.list_state.select(None);
+        self.remote_ui.status = None;
+        self.remote_input = None;
+    }
+
+    fn reload_remote_manager(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        match git_ops::list_remotes(&repo_root) {
+            Ok(remotes) => {
+                self.remote_ui.remotes = remotes;
+                self.remote_ui.move_selection(0);
+            }
+            Err(e) => self.remote_ui.status = Some(e),
+        }
+    }
+
+    /// Make Push, Pull and Fetch use the selected remote, or the upstream again
+    /// when it already is the one they use.
+    fn use_selected_remote(&mut self) {
+        let Some(name) = self.remote_ui.selected_remote().map(|r| r.name.clone()) else {
+            self.remote_ui.status = Some("No remote selected".to_string());
+            return;
+        };
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        let msg = if self.target_remote() == Some(name.as_str()) {
+            self.target_remote = None;
+            "Push, Pull and Fetch use the upstream".to_string()
+        } else {
+            let msg = format!("Push, Pull and Fetch use `{}`", name);
+            self.target_remote = Some((repo_root, name));
+            msg
+        };
+        self.remote_ui.status = Some(msg.clone());
+        self.set_status(msg);
+    }
+
+    fn remote_add(&mut self) {
+        self.remote_input = Some(RemoteInput {
+            kind: RemoteInputKind::Add,
+            name: String::new(),
+            url: String::new(),
+            focus_url: false,
+        });
+    }
+
+    fn remote_rename_selected(&mut self) {
+        let Some(name) = self.remote_ui.selected_remote().map(|r| r.name.clone()) else {
+            self.remote_ui.status = Some("No remote selected".to_string());
+            return;
+        };
+        self.remote_input = Some(RemoteInput {
+            kind: RemoteInputKind::Rename(name.clone()),
+            name,
+            url: String::new(),
+            focus_url: false,
+        });
+    }
+
+    fn remote_url_selected(&mut self, push: bool) {
+        let Some(remote) = self.remote_ui.selected_remote() else {
+            self.remote_ui.status = Some("No remote selected".to_string());
+            return;
+        };
+        let url = if push {
+            remote.push_url.clone()
+        } else {
+            remote.fetch_url.clone()
+        };
+        self.remote_input = Some(RemoteInput {
+            kind: RemoteInputKind::Url(remote.name.clone(), push),
+            name: remote.name.clone(),
+            url,
+            focus_url: true,
+        });
+    }
+
+    fn remove_selected_remote(&mut self) {
+        let Some(name) = self.remote_ui.selected_remote().map(|r| r.name.clone()) else {
+            self.remote_ui.status = Some("No remote selected".to_string());
+            return;
+        };
+        self.request_confirm(
+            confirm::ConfirmKind::Delete,
+            true,
+            confirm::ConfirmDialog {
+                title: " Remove Remote ".to_string(),
+                lines: vec![
+                    format!("Remove remote `{}`?", name),
+                    String::new(),
+                    "Its remote-tracking branches are deleted too.".to_string(),
+                ],
+                confirm_label: "Remove".to_string(),
+                action: ConfirmAction::RemoveRemote(name),
+            },
+        );
+    }
+
+    fn remove_remote(&mut self, name: String) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        self.start_git_job(
+            format!("git remote remove {}", name),
+            true,
+            false,
+            move || git_ops::remove_remote(&repo_root, &name),
+        );
+    }
+
+    fn remote_input_key(&mut self, key: &KeyEvent) {
+        let Some(input) = self.remote_input.as_mut() else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        // Add is the only form with both fields
+        let both = matches!(input.kind, RemoteInputKind::Add);
+        let field = if input.focus_url {
+            &mut input.url
+        } else {
+            &mut input.name
+        };
+        match key.code {
+            KeyCode::Tab | KeyCode::BackTab if both => input.focus_url = !input.focus_url,
+            KeyCode::Enter if both && !input.focus_url => input.focus_url = true,
+            KeyCode::Enter => self.submit_remote_input(),
+            KeyCode::Backspace => {
+                field.pop();
+            }
+            KeyCode::Char(ch) if !ctrl => field.push(ch),
+            _ => {}
+        }
+    }
+
+    fn submit_remote_input(&mut self) {
+        let Some(input) = self.remote_input.take() else {
+            return;
+        };
+        let (name, url) = (input.name.trim().to_string(), input.url.trim().to_string());
+        let missing = match &input.kind {
+            RemoteInputKind::Add => name.is_empty() || url.is_empty(),
+            RemoteInputKind::Rename(_) => name.is_empty(),
+            RemoteInputKind::Url(..) => url.is_empty(),
+        };
+        if missing {
+            self.remote_input = Some(input);
+            return;
+        }
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        match input.kind {
+            RemoteInputKind::Add => {
+                let cmd = format!("git remote add {} {}", name, url);
+                self.start_git_job(cmd, true, false, move || {
+                    git_ops::add_remote(&repo_root, &name, &url)
+                });
+            }
+            RemoteInputKind::Rename(old) => {
+                if old == name {
+                    return;
+                }
+                let cmd = format!("git remote rename {} {}", old, name);
+                self.start_git_job(cmd, true, false, move || {
+                    git_ops::rename_remote(&repo_root, &old, &name)
+                });
+            }
+            RemoteInputKind::Url(remote, push) => {
+                let flag = if push { "--push " } else { "" };
+                let cmd = format!("git remote set-url {}{} {}", flag, remote, url);
+                self.start_git_job(cmd, true, false, move || {
+                    git_ops::set_remote_url(&repo_root, &remote, &url, push)
+                });
+            }
+        }
+    }
+
+    /// Jump to the selected file's change in an older (`delta > 0`) or newer commit.
+    /// Jump the active diff pane to the next/previous hunk header, or file header when
+    /// `files` is set. In the Git tab, file jumps past the last header move to the next
+    /// changed file in the tree.
+    /// Jump to the next/previous hunk or file, repeated for a count prefix.
+    fn jump_diff_anchor(&mut self, files: bool, forward: bool) {
+        if self.current_tab == Tab::Git {
+            self.last_action = Some(RepeatAction::JumpAnchor { files, forward });
+        }
+        for _ in 0..self.nav_keys.repeat() {
+            if !self.step_diff_anchor(files, forward) {
+                break;
+            }
+        }
+    }
+
+    fn step_diff_anchor(&mut self, files: bool, forward: bool) -> bool {
+        let missing = format!(
+            "No {} {}",
+            if forward { "next" } else { "previous" },
+            if files { "file" } else { "hunk" }
+        );
+        if self.current_tab == Tab::Git {
+            let wrap_width = (self.git.diff_mode == GitDiffMode::Unified && self.wrap_diff)
+                .then(|| self.git_diff_cache.key.map(|k| k.width as usize))
+                .flatten();
+            let rows = self.git_diff_cache.anchor_rows(files, wrap_width);
+            if let Some(row) = next_anchor_row(&rows, self.git.diff_scroll_y, forward) {
+                self.git.diff_scroll_y = row;
+                return true;
+            } else if files && self.git.tree_move_to_file(forward) {
+                // The next file's diff loads in the background; stop repeating here
+                self.request_git_diff_update();
+            } else {
+                self.set_status(missing);
+            }
+            return false;
+        }
+
+        let wrap_width = (self.log_ui.diff_mode == GitDiffMode::Unified && self.wrap_diff)
+            .then(|| self.log_diff_cache.key.map(|k| k.width as usize))
+            .flatten();
+        let rows = self.log_diff_cache.anchor_rows(files, wrap_width);
+        match next_anchor_row(&rows, self.log_ui.diff_scroll_y, forward) {
+            Some(row) => {
+                self.log_ui.diff_scroll_y = row;
+                self.log_ui.focus = LogPaneFocus::Diff;
+                true
+            }
+            None => {
+                self.set_status(missing);
+                false
+            }
+        }
+    }
+
+    fn diff_lines_for(&self, tab: Tab) -> &[String] {
+        match tab {
+            Tab::Log => &self.log_ui.diff_lines,
+            _ => &self.git.diff_lines,
+        }
+    }
+
+    fn diff_generation_for(&self, tab: Tab) -> u64 {
+        match tab {
+            Tab::Log => self.log_ui.diff_generation,
+            _ => self.git.diff_generation,
+        }
+    }
+
+    /// Selected raw line range in `tab`'s diff, if the selection still matches its content
+    pub(crate) fn diff_selection_range(&self, tab: Tab) -> Option<(usize, usize)> {
+        self.diff_selection
+            .filter(|s| s.tab == tab && s.generation == self.diff_generation_for(tab))
+            .map(|s| s.range())
+    }
+
+    /// Rendered row of each raw diff line in `tab`'s unified view, mirroring the
+    /// layout of the unified renderers (hidden meta lines share the next row).
+    fn diff_line_rows(&self, tab: Tab) -> Vec<usize> {
+        let lines = self.diff_lines_for(tab);
+        let log_layout = tab == Tab::Log;
+        let diff_start = if log_layout {
+            lines
+                .iter()
+                .position(|l| l.starts_with("diff --git "))
+                .unwrap_or(lines.len())
+        } else {
+            0
+        };
+
+        let mut rows = Vec::with_capacity(lines.len());
+        let mut row = 0usize;
+        let mut first_file = true;
+        for (idx, l) in lines.iter().enumerate() {
+            if idx < diff_start {
+                rows.push(row);
+                row += 1;
+                // Blank line between the commit header and the diff
+                if idx + 1 == diff_start && diff_start < lines.len() {
+                    row += 1;
+                }
+                continue;
+            }
+            if l.starts_with("index ") || l.starts_with("--- ") || l.starts_with("+++ ") {
+                rows.push(row);
+                continue;
+            }
+            if log_layout && l.starts_with("@@") {
+                row += 1;
+            }
+            if log_layout && l.starts_with("diff --git") {
+                if !first_file {
+                    row += 2;
+                }
+                first_file = false;
+            }
+            rows.push(row);
+            row += 1;
+        }
+        rows
+    }
+
+    fn start_diff_selection(&mut self) {
+        let tab = self.current_tab;
+        let scroll = usize::from(match tab {
+            Tab::Git => self.git.diff_scroll_y,
+            Tab::Log => self.log_ui.diff_scroll_y,
+            _ => return,
+        });
+
+        let rows = self.diff_line_rows(tab);
+        let mut numbers = git::LineNumberTracker::default();
+        let code: Vec<bool> = self
+            .diff_lines_for(tab)
+            .iter()
+            .map(|l| {
+                let (old, new) = numbers.advance(l);
+                old.is_some() || new.is_some()
+            })
+            .collect();
+        let Some(start) = (0..code.len())
+            .find(|i| code[*i] && rows[*i] >= scroll)
+            .or_else(|| code.iter().position(|c| *c))
+        else {
+            self.set_status("No diff lines to select");
+            return;
+        };
+
+        // Selection is drawn on the unified view only
+        match tab {
+            Tab::Git => self.git.diff_mode = GitDiffMode::Unified,
+            _ => {
+                self.log_ui.diff_mode = GitDiffMode::Unified;
+                self.log_ui.focus = LogPaneFocus::Diff;
+            }
+        }
+        self.diff_selection = Some(DiffSelection {
+            tab,
+            generation: self.diff_generation_for(tab),
+            anchor: start,
+            cursor: start,
+            follow: true,
+        });
+        self.set_status("Select: j/k extend  y new lines  p patch  Y with markers  v/Esc cancel");
+    }
+
+    fn move_diff_selection(&mut self, delta: isize) {
+        let Some(mut sel) = self.diff_selection else {
+            return;
+        };
+        let last = self.diff_lines_for(sel.tab).len().saturating_sub(1);
+        sel.cursor = sel.cursor.saturating_add_signed(delta).min(last);
+        sel.follow = true;
+        self.diff_selection = Some(sel);
+    }
+
+    /// Scroll position for `tab`'s diff that keeps a just-moved selection cursor visible
+    pub(crate) fn follow_diff_selection(
+        &mut self,
+        tab: Tab,
+        scroll_y: u16,
+        viewport_h: usize,
+    ) -> u16 {
+        let Some(sel) = self
+            .diff_selection
+            .as_mut()
+            .filter(|s| s.tab == tab && s.follow)
+        else {
+            return scroll_y;
+        };
+        sel.follow = false;
+        let cursor = sel.cursor;
+        let Some(row) = self.diff_line_rows(tab).get(cursor).copied() else {
+            return scroll_y;
+        };
+        let scroll = scroll_y as usize;
+        let scroll = if row < scroll {
+            row
+        } else if viewport_h > 0 && row >= scroll + viewport_h {
+            row + 1 - viewport_h
+        } else {
+            scroll
+        };
+        scroll.min(u16::MAX as usize) as u16
+    }
+
+    fn yank_diff_selection(&mut self, kind: DiffYank) {
+        let Some(sel) = self.diff_selection.take() else {
+            return;
+        };
+        let lines = self.diff_lines_for(sel.tab);
+        let (start, end) = sel.range();
+        if start >= lines.len() {
+            return;
+        }
+        let end = end.min(lines.len() - 1);
+        let text = match kind {
+            DiffYank::NewLines => git::selection_new_text(lines, start, end),
+            DiffYank::Patch => git::selection_patch(lines, start, end),
+            DiffYank::Markers => lines[start..=end].join("\n"),
+        };
+        if text.is_empty() {
+            self.set_status("Nothing to copy in selection");
+            return;
+        }
+        self.request_copy_to_clipboard(text);
+    }
+
+    fn handle_git_footer(&mut self, action: GitFooterAction) {
+        if self.git.repo_root.is_none() {
+            self.set_status("Not a git repository");
+            return;
+        }
+        if action != GitFooterAction::Commit {
+            self.last_action = Some(RepeatAction::Footer(action));
+        }
+
+        match action {
+            GitFooterAction::Stage => {
+                let Some(repo_root) = self.git.repo_root.clone() else {
+                    self.set_status("Not a git repository");
+                    return;
+                };
+
+                let mut paths: Vec<String> = self.git.selected_tree_paths();
+
+                if paths.is_empty() {
+                    self.set_status("No selection");
+                    return;
+                }
+
+                paths.sort();
+
+                let cmd = if paths.len() == 1 {
+                    format!("git add -- {}", paths[0])
+                } else {
+                    format!("git add ({})", paths.len())
+                };
+
+                self.start_git_job(cmd, true, false, move || {
+                    git_ops::stage_paths(&repo_root, &paths)
+                });
+            }
+            GitFooterAction::Unstage => {
+                let Some(repo_root) = self.git.repo_root.clone() else {
+                    self.set_status("Not a git repository");
+                    return;
+                };
+
+                let paths: Vec<String> = self.git.selected_tree_paths();
+
+                if paths.is_empty() {
+                    self.set_status("No selection");
+                    return;
+                }
+
+                let mut staged_paths: Vec<String> = Vec::new();
+                for p in paths {
+                    if let Some(e) = self.git.entries.iter().find(|e| e.path == p) {
+                        let staged = e.x != ' ' && e.x != '?';
+                        if staged {
+                            staged_paths.push(p);
+                        }
+                    }
+                }
+
+                if staged_paths.is_empty() {
+                    self.set_status("Nothing staged in selection");
+                    return;
+                }
+
+                staged_paths.sort();
+
+                let cmd = if staged_paths.len() == 1 {
+                    format!("git restore --staged -- {}", staged_paths[0])
+                } else {
+                    format!("git restore --staged ({})", staged_paths.len())
+                };
+
+                self.start_git_job(cmd, true, false, move || {
+                    git_ops::unstage_paths(&repo_root, &staged_paths)
+                });
+            }
+            GitFooterAction::Discard => {
+                let paths = self.selected_git_paths();
+                if paths.is_empty() {
+                    self.set_status("No selection");
+                    return;
+                }
+
+                let mut items: Vec<DiscardItem> = Vec::new();
+                for p in paths {
+                    if let Some(entry) = self.git.entries.iter().find(|e| e.path == p) {
+                        if entry.is_conflict {
+                            self.set_status("Cannot discard conflicts");
+                            return;
+                        }
+
+                        let staged = entry.x != ' ' && entry.x != '?';
+                        let mode = if entry.is_untracked {
+                            DiscardMode::Untracked
+                        } else if staged {
+                            DiscardMode::AllChanges
+                        } else {
+                            DiscardMode::Worktree
+                        };
+
+                        items.push(DiscardItem { path: p, mode });
+                    }
+                }
+
+                if items.is_empty() {
+                    self.set_status("No selection");
+                    return;
+                }
+
+                self.open_discard_confirm(items);
+            }
+            GitFooterAction::Commit => {
+                if !self.commit.open {
+                    self.commit.open = true;
+                    self.commit.focus = CommitFocus::Message;
+                    return;
+                }
+
+                let Some(repo_root) = self.git.repo_root.clone() else {
+                    self.commit.set_status("Not a git repository");
+                    return;
+                };
+                // Amending can just reword HEAD
+                let staged = match git_ops::has_staged_changes(&repo_root) {
+                    Ok(staged) => staged,
+                    Err(e) => {
+                        self.commit.set_status(e);
+                        return;
+                    }
+                };
+                if !staged && !self.commit.amend {
+                    self.commit.set_status("No staged changes");
+                    return;
+                }
+                if self.review_before_commit && staged {
+                    match git_ops::staged_diff(&repo_root) {
+                        Ok(diff) => self.review.check(&diff),
+                        Err(e) => {
+                            self.commit.set_status(e);
+                            return;
+                        }
+                    }
+                    if !self.review.complete {
+                        self.commit
+                            .set_status("Review the staged changes first (Ctrl+R)");
+                        return;
+                    }
+                }
+
+                let msg = self.commit.message.clone();
+                if msg.trim().is_empty() {
+                    self.commit.set_status("Empty commit message");
+                    return;
+                }
+
+                self.remember_commit_message(&repo_root, &msg);
+                self.commit.busy = true;
+                let cmd = if self.commit.amend {
+                    "git commit --amend".to_string()
+                } else {
+                    "git commit".to_string()
+                };
+                let hook_config = self.active_hooks();
+                let env = self.hook_env(&[("LZGIT_COMMIT_MESSAGE", msg.clone())]);
+                let overrides = self.commit.overrides();
+                self.start_git_job(cmd, true, true, move || {
+                    hook_config.run(hooks::HookOp::Commit, &repo_root, &env, || {
+                        git_ops::commit_message(&repo_root, &msg, &overrides)
+                    })
+                });
+            }
+        }
+    }
+
+    fn toggle_stage_for_selection(&mut self) {
+        let paths: Vec<String> = self.git.selected_tree_paths();
+
+        if paths.is_empty() {
+            self.set_status("No selection");
+            return;
+        }
+
+        let mut staged_count = 0usize;
+        let mut known = 0usize;
+        for p in &paths {
+            if let Some(e) = self.git.entries.iter().find(|e| &e.path == p) {
+                known += 1;
+                let staged = e.x != ' ' && e.x != '?';
+                if staged {
+                    staged_count += 1;
+                }
+            }
+        }
+
+        if known > 0 && staged_count == known {
+            self.handle_git_footer(GitFooterAction::Unstage);
+        } else {
+            self.handle_git_footer(GitFooterAction::Stage);
+        }
+    }
+
+    /// Replay the last stage/unstage/discard or hunk jump on the current selection.
+    fn repeat_last_action(&mut self) {
+        match self.last_action {
+            Some(RepeatAction::Footer(action)) => self.handle_git_footer(action),
+            Some(RepeatAction::JumpAnchor { files, forward }) => {
+                self.jump_diff_anchor(files, forward)
+            }
+            None => self.set_status("Nothing to repeat"),
+        }
+    }
+
+    fn select_all_git_filtered(&mut self) {
+        self.git.selected_paths.clear();
+        for abs in &self.git.filtered {
+            if let Some(e) = self.git.entries.get(*abs) {
+                self.git.selected_paths.insert(e.path.clone());
+            }
+        }
+        self.git.selection_anchor = Some(0);
+        if !self.git.filtered.is_empty() {
+            self.git.list_state.select(Some(0));
+        }
+    }
+
+    fn stage_all_visible(&mut self) {
+        self.git.selected_paths.clear();
+        for abs in &self.git.filtered {
+            if let Some(e) = self.git.entries.get(*abs) {
+                if !e.is_conflict {
+                    self.git.selected_paths.insert(e.path.clone());
+                }
+            }
+        }
+        self.handle_git_footer(GitFooterAction::Stage);
+    }
+
+    fn unstage_all_visible(&mut self) {
+        self.git.selected_paths.clear();
+        for abs in &self.git.filtered {
+            if let Some(e) = self.git.entries.get(*abs) {
+                let staged = e.x != ' ' && e.x != '?';
+                if staged {
+                    self.git.selected_paths.insert(e.path.clone());
+                }
+            }
+        }
+        self.handle_git_footer(GitFooterAction::Unstage);
+    }
+
+    fn toggle_commit_advanced(&mut self) {
+        self.commit.advanced = !self.commit.advanced;
+        if !self.commit.advanced {
+            self.commit.focus = CommitFocus::Message;
+        }
+    }
+
+    /// Upstream that already has HEAD, going by ahead/behind: amending would
+    /// rewrite a pushed commit.
+    fn head_pushed(&self) -> Option<&str> {
+        self.git.upstream.as_deref().filter(|_| self.git.ahead == 0)
+    }
+
+    /// Park every change in a `WIP:` commit, without hooks.
+    fn wip_commit(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        self.start_git_job("wip commit".to_string(), true, false, move || {
+            git_ops::wip_commit(&repo_root)
+        });
+    }
+
+    /// Take back a WIP commit that has not been pushed; its changes end up staged.
+    fn undo_wip(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        if let Some(upstream) = self.head_pushed() {
+            let msg = format!(
+                "HEAD is already on {}: undo the WIP commit by hand",
+                upstream
+            );
+            self.set_status(msg);
+            return;
+        }
+        self.start_git_job("undo wip".to_string(), true, false, move || {
+            git_ops::undo_wip(&repo_root)
+        });
+    }
+
+    fn toggle_commit_amend(&mut self) {
+        if self.commit.amend {
+            self.commit.stop_amend();
+            self.commit.status = None;
+            return;
+        }
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.commit.set_status("Not a git repository");
+            return;
+        };
+        match git_ops::head_message(&repo_root) {
+            Ok(message) => self.commit.start_amend(&message),
+            Err(e) => {
+                self.commit.set_status(e);
+                return;
+            }
+        }
+        self.commit.focus = CommitFocus::Message;
+        let status = match self.head_pushed() {
+            Some(upstream) => format!(
+                "HEAD is already on {}: amending rewrites pushed history",
+                upstream
+            ),
+            None => "Amending HEAD".to_string(),
+        };
+        self.commit.set_status(status);
+    }
+
+    fn start_ai_generate(&mut self) {
+        if !self.commit.open {
+            self.commit.open = true;
+        }
+        if !self.features.network {
+            self.commit
+                .set_status("AI is disabled (network features off)");
+            return;
+        }
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.commit.set_status("Not a git repository");
+            return;
+        };
+
+        match git_ops::has_staged_changes(&repo_root) {
+            Ok(true) => {}
+            Ok(false) => {
+                self.commit.set_status("No staged changes");
+                return;
+            }
+            Err(e) => {
+                self.commit.set_status(e);
+                return;
+            }
+        }
+
+        self.commit.busy = true;
+        self.commit.set_status("Generating...");
+
+        let ai = self.ai_config.clone();
+        self.start_ai_job(move || {
+            let cfg = openrouter::OpenRouterConfig::from_env(
+                ai.api_key_env.as_deref(),
+                ai.model.as_deref(),
+            )?;
+            let diff = git_ops::staged_diff(&repo_root)?;
+            openrouter::generate_commit_message(&cfg, &diff)
+        });
+    }
+
+    fn open_discard_confirm(&mut self, items: Vec<DiscardItem>) {
+        let n = items.len();
+        let title = if n == 1 {
+            match items[0].mode {
+                DiscardMode::Worktree => " Discard Changes ",
+                DiscardMode::Untracked => " Delete Untracked ",
+                DiscardMode::AllChanges => " Discard All Changes ",
+            }
+        } else {
+            " Discard "
+        };
+
+        let count = |mode| items.iter().filter(|i| i.mode == mode).count();
+        let (work, all, untracked) = (
+            count(DiscardMode::Worktree),
+            count(DiscardMode::AllChanges),
+            count(DiscardMode::Untracked),
+        );
+
+        let mut lines = Vec::new();
+        if n == 1 {
+            lines.push(format!("File: {}", items[0].path));
+        } else {
+            lines.push(format!("Files: {}", n));
+        }
+        lines.push(String::new());
+        if work > 0 {
+            lines.push(format!("Revert unstaged: {}", work));
+        }
+        if all > 0 {
+            lines.push(format!("Reset staged+unstaged: {}", all));
+        }
+        if untracked > 0 {
+            lines.push(format!("Delete untracked: {}", untracked));
+        }
+
+        self.request_confirm(
+            confirm::ConfirmKind::Discard,
+            n > 1 || untracked > 0,
+            confirm::ConfirmDialog {
+                title: title.to_string(),
+                lines,
+                confirm_label: "Discard".to_string(),
+                action: ConfirmAction::Discard(items),
+            },
+        );
+    }
+
+    fn discard_items(&mut self, items: Vec<DiscardItem>) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        let n = items.len();
+        let cmd = format!("discard ({})", n);
+
+        self.start_git_job(cmd, true, false, move || {
+            for item in items {
+                let res = match item.mode {
+                    DiscardMode::Worktree => git_ops::discard_worktree_path(&repo_root, &item.path),
+                    DiscardMode::Untracked => {
+                        git_ops::discard_untracked_path(&repo_root, &item.path)
+                    }
+                    DiscardMode::AllChanges => {
+                        git_ops::discard_all_changes_path(&repo_root, &item.path)
+                    }
+                };
+                if let Err(e) = res {
+                    return Err(format!("{}: {}", item.path, e));
+                }
+            }
+            Ok(())
+        });
+    }
+
+    /// Ask before `dialog.action` unless the `[confirm]` setting for `kind` says not to.
+    fn request_confirm(
+        &mut self,
+        kind: confirm::ConfirmKind,
+        risky: bool,
+        dialog: confirm::ConfirmDialog<ConfirmAction>,
+    ) {
+        if !self.confirm_settings.mode(kind).should_ask(risky) {
+            self.run_confirm_action(dialog.action);
+        } else if self.confirm_settings.double_press && kind.double_pressable() {
+            let target = dialog.lines.join("\n");
+            if self.armed.take().is_some_and(|a| a.fires(kind, &target)) {
+                self.run_confirm_action(dialog.action);
+            } else {
+                let label = format!(
+                    "press again to {} ({})",
+                    dialog.confirm_label.to_lowercase(),
+                    dialog.lines.first().map(String::as_str).unwrap_or("")
+                );
+                self.armed = Some(confirm::Armed::new(kind, target, label));
+            }
+        } else {
+            self.confirm = Some(dialog);
+        }
+    }
+
+    fn accept_confirm(&mut self) {
+        if let Some(dialog) = self.confirm.take() {
+            self.run_confirm_action(dialog.action);
+        }
+    }
+
+    fn run_confirm_action(&mut self, action: ConfirmAction) {
+        match action {
+            ConfirmAction::Discard(items) => self.discard_items(items),
+            ConfirmAction::Delete(target) => self.delete_path(target),
+            ConfirmAction::DeletePermanently(target) => self.delete_path_permanently(target),
+            ConfirmAction::Stash(action, selector) => self.run_stash_action(action, selector),
+            ConfirmAction::Checkout(branch) => self.checkout_branch(branch),
+            ConfirmAction::CheckoutCommit(hash) => self.checkout_commit(hash),
+            ConfirmAction::ResetTo(hash, mode) => self.reset_to_commit(hash, mode),
+            ConfirmAction::Push(cmd) => self.start_operation_job(&cmd, true),
+            ConfirmAction::AutostashRetry(cmd) => self.autostash_retry(cmd),
+            ConfirmAction::ApplyPatches(patches) => self.apply_patches(patches),
+            ConfirmAction::CherryPick(hashes) => self.cherry_pick(hashes),
+            ConfirmAction::RevertCommit(hash, no_commit) => self.revert_commit(hash, no_commit),
+            ConfirmAction::ForceDeleteBranch(name) => self.delete_branch(name, true),
+            ConfirmAction::DeleteTag(name) => self.delete_tag(name),
+            ConfirmAction::PushTags(names) => self.push_tags(names),
+            ConfirmAction::RemoveRemote(name) => self.remove_remote(name),
+            ConfirmAction::BundleFetch(path) => self.fetch_bundle(path),
+            ConfirmAction::BundlePull(path, branch) => self.pull_bundle(path, branch),
+        }
+    }
+
+    /// Open the push dialog on the current branch and where a plain push goes: the
+    /// picked remote, else the upstream's, else `origin`.
+    fn push(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let remotes: Vec<String> = match git_ops::list_remotes(&repo_root) {
+            Ok(list) => list.into_iter().map(|r| r.name).collect(),
+            Err(e) => {
+                self.set_status(e);
+                return;
+            }
+        };
+        if remotes.is_empty() {
+            self.set_status("No remote to push to; add one under Git: remotes");
+            return;
+        }
+        let upstream_remote = self.git.upstream.as_deref().and_then(|upstream| {
+            remotes
+                .iter()
+                .position(|r| upstream.starts_with(&format!("{}/", r)))
+        });
+        let remote = self
+            .target_remote()
+            .and_then(|name| remotes.iter().position(|r| r == name))
+            .or(upstream_remote)
+            .or_else(|| remotes.iter().position(|r| r == "origin"))
+            .unwrap_or(0);
+        let branch = self.git.branch.clone();
+        self.push_input = Some(PushInput {
+            remotes,
+            remote,
+            refspec: branch.clone(),
+            force_with_lease: false,
+            set_upstream: self.git.upstream.is_none() && !branch.is_empty(),
+            plain: (remote, branch),
+            focus: PushField::Ref,
+        });
+    }
+
+    fn push_input_key(&mut self, key: &KeyEvent) {
+        let Some(input) = self.push_input.as_mut() else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match (key.code, input.focus) {
+            (KeyCode::Tab | KeyCode::Down, _) => input.cycle_focus(true),
+            (KeyCode::BackTab | KeyCode::Up, _) => input.cycle_focus(false),
+            (KeyCode::Enter, _) => self.update(Msg::Action(AppAction::SubmitPush)),
+            (KeyCode::Left, PushField::Remote) => input.cycle_remote(false),
+            (KeyCode::Right | KeyCode::Char(' '), PushField::Remote) => input.cycle_remote(true),
+            (KeyCode::Char(' '), field @ (PushField::ForceWithLease | PushField::SetUpstream)) => {
+                self.update(Msg::Action(AppAction::PushField(field)))
+            }
+            (KeyCode::Backspace, PushField::Ref) => {
+                input.refspec.pop();
+            }
+            (KeyCode::Char(ch), PushField::Ref) if !ctrl && !ch.is_whitespace() => {
+                input.refspec.push(ch)
+            }
+            _ => {}
+        }
+    }
+
+    /// Push as set up in the dialog, asking first as configured; `smart` asks for
+    /// forced pushes and for the default branches.
+    fn submit_push_input(&mut self) {
+        let Some(input) = self.push_input.as_ref() else {
+            return;
+        };
+        let refspec = input.refspec.trim().to_string();
+        if refspec.is_empty() {
+            self.set_status("Enter a branch or ref to push");
+            return;
+        }
+        let remote = input.remotes[input.remote].clone();
+        let dst = refspec.rsplit(':').next().unwrap_or(&refspec);
+        let risky = input.force_with_lease || matches!(dst, "main" | "master");
+        let (cmd, line) = if input.is_plain() {
+            let line = match self.target_remote() {
+                Some(remote) => format!("Push `{}` to `{}`?", refspec, remote),
+                None if self.git.upstream.is_some() => {
+                    format!("Push `{}` to its upstream?", refspec)
+                }
+                None => format!("Push `{}` to `{}`?", refspec, remote),
+            };
+            (self.remote_command("git push"), line)
+        } else {
+            let options = git_ops::PushOptions {
+                target: Some((remote.clone(), refspec.clone())),
+                ..input.options()
+            };
+            let line = if options.force_with_lease {
+                format!("Force-push `{}` to `{}` (with lease)?", refspec, remote)
+            } else {
+                format!("Push `{}` to `{}`?", refspec, remote)
+            };
+            let cmd = format!("git push {}", options.args().join(" "));
+            self.custom_push = Some(cmd.clone());
+            (cmd, line)
+        };
+        self.push_input = None;
+        self.request_confirm(
+            confirm::ConfirmKind::Push,
+            risky,
+            confirm::ConfirmDialog {
+                title: " Push ".to_string(),
+                lines: vec![line],
+                confirm_label: "Push".to_string(),
+                action: ConfirmAction::Push(cmd),
+            },
+        );
+    }
+
+    fn show_delete_confirm(&mut self) {
+        let Some(file) = self.selected_file().cloned() else {
+            self.set_status("No selection");
+            return;
+        };
+        let name = file
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| file.path.display().to_string());
+        let mut lines = vec![format!("Delete: {}", name)];
+        if file.is_dir {
+            lines.push("(including all contents)".to_string());
+        }
+        let risky = file.is_dir || file.size > 0;
+        self.request_confirm(
+            confirm::ConfirmKind::Delete,
+            risky,
+            confirm::ConfirmDialog {
+                title: if file.is_dir {
+                    " Delete Folder ".to_string()
+                } else {
+                    " Delete File ".to_string()
+                },
+                lines,
+                confirm_label: "Delete".to_string(),
+                action: ConfirmAction::Delete(file.path.clone()),
+            },
+        );
+    }
+
+    fn delete_path(&mut self, path: PathBuf) {
+        match file_ops::trash(&path) {
+            Ok(op) => {
+                self.set_status(format!("Done: {} (u to undo)", op.describe()));
+                self.file_history.record(op);
+                self.load_files();
+            }
+            // Always asks: this delete cannot be undone
+            Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.display().to_string());
+                self.confirm = Some(confirm::ConfirmDialog {
+                    title: " Delete Permanently ".to_string(),
+                    lines: vec![
+                        format!("Delete: {}", name),
+                        "The trash is on another filesystem; this cannot be undone".to_string(),
+                    ],
+                    confirm_label: "Delete".to_string(),
+                    action: ConfirmAction::DeletePermanently(path),
+                });
+            }
+            Err(e) => {
+                self.set_status(format!("Delete failed: {}", e));
+            }
+        }
+    }
+
+    fn delete_path_permanently(&mut self, path: PathBuf) {
+        match file_ops::remove_entry(&path) {
+            Ok(()) => {
+                self.set_status(format!("Deleted {} permanently", path.display()));
+                self.load_files();
+            }
+            Err(e) => self.set_status(format!("Delete failed: {}", e)),
+        }
+    }
+
+    fn open_explorer_rename(&mut self) {
+        let Some(file) = self.selected_file().filter(|f| f.name != "..") else {
+            self.set_status("No file selected");
+            return;
+        };
+        self.explorer_input = Some(ExplorerInput {
+            text: file.name.clone(),
+            kind: ExplorerInputKind::Rename(file.path.clone()),
+        });
+    }
+
+    fn open_explorer_create(&mut self) {
+        self.explorer_input = Some(ExplorerInput {
+            kind: ExplorerInputKind::Create,
+            text: String::new(),
+        });
+    }
+
+    fn submit_explorer_input(&mut self) {
+        let Some(input) = self.explorer_input.take() else {
+            return;
+        };
+        let text = input.text.trim();
+        if text.is_empty() {
+            return;
+        }
+
+        let (op, result) = match input.kind {
+            ExplorerInputKind::Create => {
+                let is_dir = text.ends_with('/');
+                let path = self.current_path.join(text.trim_end_matches('/'));
+                let result = match path.parent() {
+                    Some(parent) => fs::create_dir_all(parent),
+                    None => Ok(()),
+                }
+                .and_then(|_| file_ops::create(&path, is_dir));
+                (file_ops::FileOp::Create { path, is_dir }, result)
+            }
+            ExplorerInputKind::Rename(from) => {
+                let base = from.parent().unwrap_or(&self.current_path);
+                let to = base.join(text);
+                let result = file_ops::move_path(&from, &to);
+                (file_ops::FileOp::Move { from, to }, result)
+            }
+        };
+
+        match result {
+            Ok(()) => {
+                self.set_status(format!("Done: {} (u to undo)", op.describe()));
+                self.select_explorer_path(op.target());
+                self.file_history.record(op);
+            }
+            Err(e) => self.set_status(format!("Failed to {}: {}", op.describe(), e)),
+        }
+    }
+
+    fn undo_file_op(&mut self) {
+        match self.file_history.undo() {
+            Ok(Some(op)) => {
+                self.set_status(format!("Undid {} (Ctrl+r to redo)", op.describe()));
+                match &op {
+                    file_ops::FileOp::Move { from, .. } => self.select_explorer_path(from),
+                    file_ops::FileOp::Trash { path, .. } => self.select_explorer_path(path),
+                    file_ops::FileOp::Create { .. } => self.load_files(),
+                }
+            }
+            Ok(None) => self.set_status("Nothing to undo"),
+            Err(e) => self.set_status(e),
+        }
+    }
+
+    fn redo_file_op(&mut self) {
+        match self.file_history.redo() {
+            Ok(Some(op)) => {
+                self.set_status(format!("Redid {}", op.describe()));
+                match &op {
+                    file_ops::FileOp::Trash { .. } => self.load_files(),
+                    _ => self.select_explorer_path(op.target()),
+                }
+            }
+            Ok(None) => self.set_status("Nothing to redo"),
+            Err(e) => self.set_status(e),
+        }
+    }
+
+    /// Reload the listing and select `path` if it is in the current folder.
+    fn select_explorer_path(&mut self, path: &Path) {
+        self.load_files();
+        if let Some(idx) = self.files.iter().position(|f| f.path == path) {
+            self.list_state.select(Some(idx));
+            self.update_preview();
+        }
+    }
+
+    fn revert_hunk(&mut self, hunk_idx: usize) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        let Some(hunk) = self.git.diff_hunks.get(hunk_idx) else {
+            self.set_status("Invalid hunk");
+            return;
+        };
+
+        // Build patch content from hunk lines
+        let patch_content = hunk.lines.join("\n") + "\n";
+
+        self.start_git_job("revert hunk".to_string(), true, false, move || {
+            git_ops::apply_patch_reverse(&repo_root, &patch_content)
+        });
+    }
+
+    fn revert_block(&mut self, block_idx: usize) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        let Some(block) = self.git.change_blocks.get(block_idx).cloned() else {
+            self.set_status("Invalid block");
+            return;
+        };
+
+        // Direct file manipulation: replace new_lines with old_lines
+        let file_path = repo_root.join(&block.file_path);
+        let new_start = block.new_start as usize;
+        let new_lines = block.new_lines.clone();
+        let old_lines = block.old_lines.clone();
+
+        // Read the file
+        let content = match std::fs::read_to_string(&file_path) {
+            Ok(c) => c,
+            Err(e) => {
+                self.set_status(format!("Failed to read file: {}", e));
+                return;
+            }
+        };
+
+        let lines: Vec<&str> = content.lines().collect();
+
+        // Calculate the range to replace (1-indexed to 0-indexed)
+        let start_idx = new_start.saturating_sub(1);
+        let end_idx = start_idx + new_lines.len();
+
+        if end_idx > lines.len() {
+            self.set_status("Line numbers out of range");
+            return;
+        }
+
+        // Build new content: lines before + old_lines + lines after
+        let mut new_content = String::new();
+        for line in &lines[..start_idx] {
+            new_content.push_str(line);
+            new_content.push('\n');
+        }
+        for line in &old_lines {
+            new_content.push_str(line);
+            new_content.push('\n');
+        }
+        for line in &lines[end_idx..] {
+            new_content.push_str(line);
+            new_content.push('\n');
+        }
+
+        // Handle trailing newline
+        if !content.ends_with('\n') && new_content.ends_with('\n') {
+            new_content.pop();
+        }
+
+        // Save undo entry before writing
+        self.undo_stack.push(UndoEntry {
+            description: format!("Revert change in {}", block.file_path),
+            file_path: file_path.clone(),
+            old_content: content.clone(),
+            new_content: new_content.clone(),
+        });
+        // Clear redo stack when new action is performed
+        self.redo_stack.clear();
+        // Limit undo stack size to 50 entries
+        if self.undo_stack.len() > 50 {
+            self.undo_stack.remove(0);
+        }
+
+        // Write the file
+        if let Err(e) = std::fs::write(&file_path, &new_content) {
+            self.set_status(format!("Failed to write file: {}", e));
+            // Remove the undo entry since write failed
+            self.undo_stack.pop();
+            return;
+        }
+
+        self.set_status("Reverted (Ctrl+Z to undo)");
+        self.refresh_git_state();
+    }
+
+    /// Undo the last revert operation
+    fn undo_revert(&mut self) {
+        let Some(entry) = self.undo_stack.pop() else {
+            self.set_status("Nothing to undo");
+            return;
+        };
+
+        // Write the old content back
+        if let Err(e) = std::fs::write(&entry.file_path, &entry.old_content) {
+            self.set_status(format!("Undo failed: {}", e));
+            // Put the entry back since we couldn't undo
+            self.undo_stack.push(entry);
+            return;
+        }
+
+        // Move to redo stack
+        self.redo_stack.push(entry);
+        // Limit redo stack size
+        if self.redo_stack.len() > 50 {
+            self.redo_stack.remove(0);
+        }
+
+        self.set_status("Undone (Ctrl+Shift+Z to redo)");
+        self.refresh_git_state();
+    }
+
+    /// Redo the last undone operation
+    fn redo_revert(&mut self) {
+        let Some(entry) = self.redo_stack.pop() else {
+            self.set_status("Nothing to redo");
+            return;
+        };
+
+        // Write the new content
+        if let Err(e) = std::fs::write(&entry.file_path, &entry.new_content) {
+            self.set_status(format!("Redo failed: {}", e));
+            // Put the entry back since we couldn't redo
+            self.redo_stack.push(entry);
+            return;
+        }
+
+        // Move back to undo stack
+        self.undo_stack.push(entry);
+
+        self.set_status("Redone (Ctrl+Z to undo)");
+        self.refresh_git_state();
+    }
+
+    fn start_operation_job(&mut self, cmd: &str, refresh: bool) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        self.set_status(format!("Running: {}", cmd));
+
+        match cmd {
+            "git merge --continue" => {
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    git_ops::merge_continue(&repo_root)
+                });
+            }
+            "git merge --abort" => {
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    git_ops::merge_abort(&repo_root)
+                });
+            }
+            "git rebase --continue" => {
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    git_ops::rebase_continue(&repo_root)
+                });
+            }
+            "git rebase --abort" => {
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    git_ops::rebase_abort(&repo_root)
+                });
+            }
+            "git rebase --skip" => {
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    git_ops::rebase_skip(&repo_root)
+                });
+            }
+            "git am --continue" | "git am --skip" | "git am --abort" => {
+                let flag = cmd.trim_start_matches("git am ").to_string();
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    git_ops::am_step(&repo_root, &flag)
+                });
+            }
+            "git cherry-pick --continue" | "git cherry-pick --skip" | "git cherry-pick --abort" => {
+                let flag = cmd.trim_start_matches("git cherry-pick ").to_string();
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    git_ops::cherry_pick_step(&repo_root, &flag)
+                });
+            }
+            "git revert --continue" | "git revert --skip" | "git revert --abort" => {
+                let flag = cmd.trim_start_matches("git revert ").to_string();
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    git_ops::revert_step(&repo_root, &flag)
+                });
+            }
+            _ if cmd.starts_with("git fetch --prune") => {
+                let remote = cmd
+                    .trim_start_matches("git fetch --prune")
+                    .trim()
+                    .to_string();
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    git_ops::fetch_prune(
+                        &repo_root,
+                        Some(remote.as_str()).filter(|r| !r.is_empty()),
+                    )
+                });
+            }
+            _ if cmd.starts_with("git pull ") => {
+                let (options, target) =
+                    git_ops::PullOptions::parse(cmd.trim_start_matches("git pull "));
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    git_ops::pull(
+                        &repo_root,
+                        options,
+                        target.as_ref().map(|(r, b)| (r.as_str(), b.as_str())),
+                    )
+                });
+            }
+            _ if cmd.starts_with("git push") => {
+                let options = git_ops::PushOptions::parse(cmd.trim_start_matches("git push"));
+                let hook_config = self.active_hooks();
+                let env = self.hook_env(&[]);
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    hook_config.run(hooks::HookOp::Push, &repo_root, &env, || {
+                        git_ops::push_with(&repo_root, &options)
+                    })
+                });
+            }
+            _ if cmd.starts_with("git switch --orphan ") => {
+                let name = cmd.trim_start_matches("git switch --orphan ").to_string();
+                let hook_config = self.active_hooks();
+                let env = self.hook_env(&[("LZGIT_TARGET", name.clone())]);
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    hook_config.run(hooks::HookOp::Checkout, &repo_root, &env, || {
+                        git_ops::create_orphan_branch(&repo_root, &name)
+                    })
+                });
+            }
+            _ if cmd.starts_with("git checkout -b ") => {
+                let name = cmd.trim_start_matches("git checkout -b ").to_string();
+                let hook_config = self.active_hooks();
+                let env = self.hook_env(&[("LZGIT_TARGET", name.clone())]);
+                self.start_git_job(cmd.to_string(), refresh, false, move || {
+                    hook_config.run(hooks::HookOp::Checkout, &repo_root, &env, || {
+                        git_ops::create_branch(&repo_root, &name)
+                    })
+                });
+            }
+            _ if cmd.starts_with("update lzgit ") => {
+                let version = cmd.strip_prefix("update lzgit ").unwrap_or("").to_string();
+                self.start_git_job(cmd.to_string(), false, false, move || {
+                    // Download pre-built binary from GitHub Releases
+                    let platform = if cfg!(target_os = "linux") && cfg!(target_arch = "x86_64") {
+                        "linux-x86_64"
+                    } else if cfg!(target_os = "linux") && cfg!(target_arch = "aarch64") {
+                        "linux-aarch64"
+                    } else if cfg!(target_os = "macos") && cfg!(target_arch = "x86_64") {
+                        "macos-x86_64"
+                    } else if cfg!(target_os = "macos") && cfg!(target_arch = "aarch64") {
+                        "macos-aarch64"
+                    } else {
+                        return Err("Unsupported platform".to_string());
+                    };
+
+                    if version.is_empty() {
+                        return Err("No version specified".to_string());
+                    }
+
+                    let url = format!(
+                        "https://github.com/FanFusion/lzgit/releases/download/v{}/lzgit-{}",
+                        version, platform
+                    );
+
+                    let resp = ureq::AgentBuilder::new()
+                        .timeout(std::time::Duration::from_secs(120))
+                        .build()
+                        .get(&url)
+                        .call()
+                        .map_err(|e| format!("Download failed ({}): {}", url, e))?;
+
+                    if resp.status() != 200 {
+                        return Err(format!("HTTP {} from {}", resp.status(), url));
+                    }
+
+                    use std::io::Read;
+                    let mut bytes = Vec::new();
+                    resp.into_reader()
+                        .read_to_end(&mut bytes)
+                        .map_err(|e| format!("Read failed: {}", e))?;
+
+                    let home =
+                        std::env::var_os("HOME").ok_or_else(|| "HOME not set".to_string())?;
+
+                    // Install to both ~/.cargo/bin and ~/.local/bin
+                    let cargo_bin = std::path::PathBuf::from(&home).join(".cargo/bin/lzgit");
+                    let local_bin = std::path::PathBuf::from(&home).join(".local/bin/lzgit");
+
+                    for bin_path in [&cargo_bin, &local_bin] {
+                        if let Some(parent) = bin_path.parent() {
+                            let _ = std::fs::create_dir_all(parent);
+                        }
+
+                        // Write to temp file first, then rename (handles "text file busy")
+                        let temp_path = bin_path.with_extension("new");
+                        std::fs::write(&temp_path, &bytes)
+                            .map_err(|e| format!("Write {:?}: {}", temp_path, e))?;
+
+                        #[cfg(unix)]
+                        {
+                            use std::os::unix::fs::PermissionsExt;
+                            let _ = std::fs::set_permissions(
+                                &temp_path,
+                                std::fs::Permissions::from_mode(0o755),
+                            );
+                        }
+
+                        // Remove old file first (works even if running), then rename
+                        let _ = std::fs::remove_file(bin_path);
+                        std::fs::rename(&temp_path, bin_path)
+                            .map_err(|e| format!("Rename {:?}: {}", bin_path, e))?;
+                    }
+
+                    Ok(())
+                });
+            }
+            _ => {
+                self.set_status("Unknown operation");
+            }
+        }
+    }
+
+    fn load_files(&mut self) {
+        self.files.clear();
+        let read_path = if self.current_path.exists() {
+            self.current_path.clone()
+        } else {
+            PathBuf::from("/")
+        };
+
+        if let Ok(entries) = fs::read_dir(&read_path) {
+            let mut items: Vec<FileEntry> = entries
+                .filter_map(|e| e.ok())
+                .map(|entry| {
+                    let path = entry.path();
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let metadata = entry.metadata().ok();
+                    let file_type = entry.file_type().ok();
+
+                    let is_dir = file_type.map(|t| t.is_dir()).unwrap_or(false);
+                    let is_symlink = file_type.map(|t| t.is_symlink()).unwrap_or(false);
+                    let is_hidden = name.starts_with('.');
+
+                    let is_exec = metadata
+                        .as_ref()
+                        .map(|m| {
+                            #[cfg(unix)]
+                            {
+                                use std::os::unix::fs::PermissionsExt;
+                                m.permissions().mode() & 0o111 != 0
+                            }
+                            #[cfg(not(unix))]
+                            false
+                        })
+                        .unwrap_or(false);
+
+                    let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+
+                    FileEntry {
+                        name,
+                        path,
+                        is_dir,
+                        is_symlink,
+                        is_exec,
+                        is_hidden,
+                        size,
+                    }
+                })
+                .filter(|f| self.show_hidden || !f.is_hidden)
+                .collect();
+
+            items.sort_by(|a, b| match (a.is_dir, b.is_dir) {
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            });
+
+            if read_path.parent().is_some() {
+                items.insert(
+                    0,
+                    FileEntry {
+                        name: "..".to_string(),
+                        path: read_path.clone(),
+                        is_dir: true,
+                        is_symlink: false,
+                        is_exec: false,
+                        is_hidden: false,
+                        size: 0,
+                    },
+                );
+            }
+
+            self.files = items;
+        }
+        self.preview_scroll = 0;
+        self.update_preview();
+        // Update directory modification time
+        self.dir_mtime = fs::metadata(&self.current_path)
+            .ok()
+            .and_then(|m| m.modified().ok());
+    }
+
+    fn check_auto_refresh(&mut self) {
+        if !self.auto_refresh {
+            return;
+        }
+        // Only check every second
+        if self.last_dir_check.elapsed() < Duration::from_secs(1) {
+            return;
+        }
+        self.last_dir_check = Instant::now();
+
+        // Get current mtime of directory
+        let current_mtime = fs::metadata(&self.current_path)
+            .ok()
+            .and_then(|m| m.modified().ok());
+
+        // If mtime changed, refresh
+        if current_mtime != self.dir_mtime {
+            let selected_name = self.selected_file().map(|f| f.name.clone());
+            self.load_files();
+            // Try to restore selection
+            if let Some(name) = selected_name {
+                if let Some(idx) = self.files.iter().position(|f| f.name == name) {
+                    self.list_state.select(Some(idx));
+                }
+            }
+        }
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        self.list_state.selected()
+    }
+
+    fn selected_file(&self) -> Option<&FileEntry> {
+        self.selected_index().and_then(|i| self.files.get(i))
+    }
+
+    /// Get the file entries adjacent to the current selection (prev and next).
+    /// Returns (prev_file, next_file), where either can be None if at boundaries.
+    fn adjacent_files(&self) -> (Option<&FileEntry>, Option<&FileEntry>) {
+        let Some(idx) = self.selected_index() else {
+            return (None, None);
+        };
+
+        let prev = if idx > 0 {
+            self.files.get(idx - 1)
+        } else {
+            None
+        };
+
+        let next = self.files.get(idx + 1);
+
+        (prev, next)
+    }
+
+    /// Check if a file should be preloaded.
+    /// Skip directories, images, and very large files.
+    fn should_preload(&self, file: &FileEntry) -> bool {
+        if file.is_dir {
+            return false;
+        }
+
+        // Skip image files
+        if let Some(ext) = file.path.extension().and_then(|s| s.to_str()) {
+            let ext_lower = ext.to_lowercase();
+            if matches!(
+                ext_lower.as_str(),
+                "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp"
+            ) {
+                return false;
+            }
+        }
+
+        // Skip very large files (> 5MB)
+        if let Ok(metadata) = fs::metadata(&file.path) {
+            if metadata.len() > 5 * 1024 * 1024 {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn is_ssh_session() -> bool {
+        env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some()
+    }
+
+    fn set_status<S: Into<String>>(&mut self, msg: S) {
+        self.status_message = Some((msg.into(), Instant::now()));
+    }
+
+    fn toggle_diff_line_numbers(&mut self) {
+        self.diff_line_numbers = !self.diff_line_numbers;
+        self.set_status(if self.diff_line_numbers {
+            "Diff line numbers: on"
+        } else {
+            "Diff line numbers: off"
+        });
+    }
+
+    fn toggle_top_bar_segment(&mut self, segment: TopBarSegment) {
+        let (flag, name) = match segment {
+            TopBarSegment::Clock => (&mut self.top_bar.clock, "clock"),
+            TopBarSegment::Branch => (&mut self.top_bar.branch, "branch"),
+            TopBarSegment::AheadBehind => (&mut self.top_bar.ahead_behind, "ahead/behind"),
+            TopBarSegment::Operation => (&mut self.top_bar.operation, "operation"),
+        };
+        *flag = !*flag;
+        let msg = format!("Top bar {}: {}", name, if *flag { "on" } else { "off" });
+        self.set_status(msg);
+        self.tick_clock();
+    }
+
+    /// Current unix time for relative ages.
+    pub(crate) fn now(&self) -> i64 {
+        self.fixed_now.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0)
+        })
+    }
+
+    /// Refresh the cached local time shown in the top bar, once per minute.
+    fn tick_clock(&mut self) {
+        if !self.top_bar.clock {
+            self.clock = None;
+            return;
+        }
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        if self.clock.as_ref().is_some_and(|(m, _)| *m == now / 60) {
+            return;
+        }
+        let offset = local_offset_at(now as i64);
+        self.clock = Some((now / 60, hh_mm(now as i64, offset)));
+    }
+
+    fn set_theme(&mut self, theme: theme::Theme) {
+        self.theme = theme;
+        self.palette = theme::palette(theme);
+        for (_, role, color) in self.syntax_colors.iter().filter(|(t, ..)| *t == theme) {
+            self.palette.syntax.set(*role, *color);
+        }
+        highlight::set_syntax_colors(self.palette.syntax);
+        self.git.full_file_highlighted = None;
+        self.highlight_cache = None;
+        self.git_diff_cache.invalidate();
+        self.log_diff_cache.invalidate();
+    }
+
+    fn open_theme_picker(&mut self) {
+        if self.theme_picker.open {
+            self.theme_picker.open = false;
+            return;
+        }
+
+        self.context_menu = None;
+        self.pending_menu_action = None;
+        self.command_palette.open = false;
+
+        let current = THEME_ORDER
+            .iter()
+            .position(|t| *t == self.theme)
+            .unwrap_or(0);
+        self.theme_picker.open = true;
+        self.theme_picker.list_state.select(Some(current));
+    }
+
+    fn close_theme_picker(&mut self) {
+        self.theme_picker.open = false;
+    }
+
+    fn move_theme_picker(&mut self, delta: i32) {
+        let len = THEME_ORDER.len();
+        if len == 0 {
+            self.theme_picker.list_state.select(None);
+            return;
+        }
+
+        let cur = self.theme_picker.list_state.selected().unwrap_or(0) as i32;
+        let next = (cur + delta).rem_euclid(len as i32) as usize;
+        self.theme_picker.list_state.select(Some(next));
+    }
+
+    fn apply_theme_picker_selection(&mut self) {
+        let Some(idx) = self.theme_picker.list_state.selected() else {
+            return;
+        };
+        let Some(theme) = THEME_ORDER.get(idx).copied() else {
+            return;
+        };
+
+        self.set_theme(theme);
+        self.save_persisted_ui_settings();
+        self.set_status(format!("Theme: {}", theme.label()));
+        self.close_theme_picker();
+    }
+
+    fn open_command_palette(&mut self) {
+        if self.operation_popup.is_some()
+            || self.confirm.is_some()
+            || self.branch_ui.open
+            || self.stash_ui.open
+            || self.log_ui.inspect.open
+        {
+            return;
+        }
+
+        if self.command_palette.open {
+            self.command_palette.open = false;
+            return;
+        }
+
+        self.context_menu = None;
+        self.pending_menu_action = None;
+        self.theme_picker.open = false;
+
+        self.command_palette.open = true;
+        self.command_palette.list_state.select(Some(0));
+    }
+
+    fn close_command_palette(&mut self) {
+        self.command_palette.open = false;
+    }
+
+    fn move_command_palette(&mut self, delta: i32) {
+        let len = COMMAND_PALETTE_ITEMS.len();
+        if len == 0 {
+            self.command_palette.list_state.select(None);
+            return;
+        }
+
+        let cur = self.command_palette.list_state.selected().unwrap_or(0) as i32;
+        let next = (cur + delta).rem_euclid(len as i32) as usize;
+        self.command_palette.list_state.select(Some(next));
+    }
+
+    fn run_command_palette_selection(&mut self) {
+        let Some(idx) = self.command_palette.list_state.selected() else {
+            return;
+        };
+        let Some((cmd, _)) = COMMAND_PALETTE_ITEMS.get(idx).copied() else {
+            return;
+        };
+        self.close_command_palette();
+        self.run_command(cmd);
+    }
+
+    fn run_command(&mut self, cmd: CommandId) {
+        match cmd {
+            CommandId::ToggleHidden => {
+                self.show_hidden = !self.show_hidden;
+                self.load_files();
+                self.set_status(if self.show_hidden {
+                    "Hidden files: shown"
+                } else {
+                    "Hidden files: hidden"
+                });
+            }
+            CommandId::ToggleWrapDiff => {
+                self.wrap_diff = !self.wrap_diff;
+                self.set_status(if self.wrap_diff {
+                    "Diff wrap: on"
+                } else {
+                    "Diff wrap: off"
+                });
+            }
+            CommandId::ToggleSyntaxHighlight => {
+                self.syntax_highlight = !self.syntax_highlight;
+                self.set_status(if self.syntax_highlight {
+                    "Syntax highlight: on"
+                } else {
+                    "Syntax highlight: off"
+                });
+            }
+            CommandId::HighlightAs => self.open_syntax_picker(),
+            CommandId::ToggleDiffLineNumbers => {
+                self.toggle_diff_line_numbers();
+            }
+            CommandId::ToggleBinaryHexdump => {
+                self.binary_hexdump = !self.binary_hexdump;
+                self.set_status(if self.binary_hexdump {
+                    "Binary hexdump: on"
+                } else {
+                    "Binary hexdump: off"
+                });
+                self.request_git_diff_update();
+                self.refresh_log_diff();
+            }
+            CommandId::ToggleHistoryDate => {
+                self.log_ui.show_date_column = !self.log_ui.show_date_column;
+                self.set_status(if self.log_ui.show_date_column {
+                    "History date column: on"
+                } else {
+                    "History date column: off"
+                });
+            }
+            CommandId::ToggleHistoryAuthor => {
+                self.log_ui.show_author_column = !self.log_ui.show_author_column;
+                self.set_status(if self.log_ui.show_author_column {
+                    "History author column: on"
+                } else {
+                    "History author column: off"
+                });
+            }
+            CommandId::ToggleRefsSidebar => self.toggle_refs_sidebar(),
+            CommandId::SearchFullHistory => {
+                self.current_tab = Tab::Log;
+                self.log_ui.set_subtab(LogSubTab::History);
+                self.search_full_history();
+            }
+            CommandId::FindInCommit => self.open_commit_find(),
+            CommandId::GoToCommit => self.open_goto_commit(),
+            CommandId::LoadMoreHistory => {
+                self.current_tab = Tab::Log;
+                self.log_ui.set_subtab(LogSubTab::History);
+                self.load_more_log_data();
+            }
+            CommandId::LoadAllHistory => self.load_all_history(),
+            CommandId::SelectTheme => {
+                self.open_theme_picker();
+            }
+            CommandId::RefreshGit => {
+                self.refresh_git_state();
+                self.set_status("Git refreshed");
+            }
+            CommandId::GitFetch => self.fetch(),
+            CommandId::GitPull => self.pull(),
+            CommandId::GitPush => self.push(),
+            CommandId::OpenBranchPicker => self.open_branch_picker(),
+            CommandId::NewBranch => {
+                self.new_branch_input = Some(String::new());
+            }
+            CommandId::NewOrphanBranch => {
+                if self.git.repo_root.is_none() {
+                    self.set_status("Not a git repository");
+                } else {
+                    self.new_branch_input = Some(String::new());
+                    self.new_branch_orphan = true;
+                }
+            }
+            CommandId::InitRepo => self.open_init_wizard(),
+            CommandId::CutRelease => self.open_release_wizard(),
+            CommandId::GenerateChangelog => self.open_changelog_input(),
+            CommandId::MapAuthors => self.open_mailmap_picker(),
+            CommandId::CloneRepo => {
+                self.clone_input = Some(CloneInput {
+                    url: String::new(),
+                    dest: String::new(),
+                    focus_dest: false,
+                });
+            }
+            CommandId::GitignoreTemplate => self.open_gitignore_picker(),
+            CommandId::BlameFile => self.open_blame(),
+            CommandId::OpenAuthorPicker => self.open_author_picker(),
+            CommandId::OpenStashPicker => self.open_stash_picker(),
+            CommandId::OpenTagPicker => self.open_tag_picker(),
+            CommandId::OpenRemotes => self.open_remote_manager(),
+            CommandId::SubmoduleUpdate => self.run_submodule_op(git_ops::SubmoduleOp::Update),
+            CommandId::SubmoduleInit => self.run_submodule_op(git_ops::SubmoduleOp::Init),
+            CommandId::SubmoduleSync => self.run_submodule_op(git_ops::SubmoduleOp::Sync),
+            CommandId::LeaveSubmodule => self.leave_submodule(),
+            CommandId::ReviewStaged => self.open_review(),
+            CommandId::CreateSnapshot => {
+                if self.git.repo_root.is_none() {
+                    self.set_status("Not a git repository");
+                } else {
+                    self.snapshot_name_input = Some(String::new());
+                }
+            }
+            CommandId::OpenSnapshots => self.open_snapshot_picker(),
+            CommandId::OpenBookmarks => self.open_bookmarks_manager(),
+            CommandId::ExportViewText => self.export_view_to_clipboard(export::ExportFormat::Plain),
+            CommandId::ExportViewMarkdown => {
+                self.export_view_to_clipboard(export::ExportFormat::Markdown)
+            }
+            CommandId::ApplyPatches => self.start_apply_patches(),
+            CommandId::CreateBundle => {
+                let branch = self.git.branch.clone();
+                self.start_create_bundle(&branch);
+            }
+            CommandId::FetchBundle => self.start_bundle_import(BundleInputKind::Fetch),
+            CommandId::PullBundle => self.start_bundle_import(BundleInputKind::Pull),
+            CommandId::ExportViewFile => {
+                self.export_path_input = Some("lzgit-export.md".to_string());
+            }
+            CommandId::OpenFileInPane => self.open_in_pane(false),
+            CommandId::OpenDiffInPane => self.open_in_pane(true),
+            CommandId::ToggleTopBarClock => self.toggle_top_bar_segment(TopBarSegment::Clock),
+            CommandId::ToggleTopBarBranch => self.toggle_top_bar_segment(TopBarSegment::Branch),
+            CommandId::ToggleTopBarAheadBehind => {
+                self.toggle_top_bar_segment(TopBarSegment::AheadBehind)
+            }
+            CommandId::ToggleTopBarOperation => {
+                self.toggle_top_bar_segment(TopBarSegment::Operation)
+            }
+            CommandId::ReloadConfig => self.reload_config(),
+            CommandId::LockScreen => self.show_lock_screen(),
+            CommandId::KeyBindings => self.key_help = Some(KeyHelp::default()),
+            CommandId::FindFile => self.open_file_finder(),
+            CommandId::RemoveStaleLocks => self.remove_stale_locks(),
+            CommandId::ClearGitLog => {
+                self.git_log.clear();
+                self.log_ui.command_state.select(None);
+                self.log_ui.diff_lines.clear();
+                self.set_status("Commands cleared");
+            }
+            CommandId::QuickStash => self.open_stash_input(),
+            CommandId::WipCommit => self.wip_commit(),
+            CommandId::UndoWip => self.undo_wip(),
+            CommandId::CheckUpdate => {
+                self.check_for_updates();
+            }
+            CommandId::Quit => self.should_quit = true,
+        }
+    }
+
+    fn check_for_updates(&mut self) {
+        if !self.feature_allowed(self.features.network, "Network access") {
+            return;
+        }
+        self.set_status("Checking for updates...");
+
+        // Fetch VERSION file from raw.githubusercontent.com (no API rate limit)
+        let result: Result<String, String> = (|| {
+            let resp = ureq::AgentBuilder::new()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .get("https://raw.githubusercontent.com/FanFusion/lzgit/main/VERSION")
+                .call()
+                .map_err(|e| format!("Network error: {}", e))?;
+
+            let latest = resp
+                .into_string()
+                .map_err(|e| format!("Read error: {}", e))?
+                .trim()
+                .to_string();
+
+            Ok(latest)
+        })();
+
+        match result {
+            Ok(latest) => {
+                if latest == VERSION {
+                    self.set_status(&format!("You're up to date! (v{})", VERSION));
+                } else if is_newer_version(&latest, VERSION) {
+                    // Only show update if latest is actually newer
+                    self.update_confirm = Some(latest);
+                } else {
+                    // Current version is newer (dev build or unreleased)
+                    self.set_status(&format!("You're up to date! (v{} > v{})", VERSION, latest));
+                }
+            }
+            Err(e) => {
+                self.set_status(&format!("Update check failed: {}", e));
+            }
+        }
+    }
+
+    fn confirm_update(&mut self) {
+        if let Some(new_version) = self.update_confirm.take() {
+            if !self.feature_allowed(self.features.network, "Network access") {
+                return;
+            }
+            self.set_status(&format!("Updating to v{}...", new_version));
+            self.update_in_progress = true;
+            self.start_operation_job(&format!("update lzgit {}", new_version), false);
+        }
+    }
+
+    fn maybe_expire_status(&mut self) -> bool {
+        let should_clear = self
+            .status_message
+            .as_ref()
+            .is_some_and(|(_, t)| t.elapsed() >= self.status_ttl);
+        if should_clear {
+            self.status_message = None;
+        }
+        should_clear
+    }
+
+    fn tick_pending_menu_action(&mut self) -> bool {
+        let Some((idx, armed)) = self.pending_menu_action else {
+            return false;
+        };
+
+        if armed {
+            self.pending_menu_action = None;
+            self.execute_menu_action(idx);
+            true
+        } else {
+            self.pending_menu_action = Some((idx, true));
+            false
+        }
+    }
+
+    fn update_context_menu_hover(&mut self, row: u16, col: u16) {
+        let Some(menu) = &mut self.context_menu else {
+            return;
+        };
+
+        let width = 30u16;
+        let height = menu.options.len() as u16 + 2;
+
+        if col < menu.x || col >= menu.x + width {
+            return;
+        }
+        if row <= menu.y || row >= menu.y + height - 1 {
+            return;
+        }
+
+        let idx = (row - menu.y - 1) as usize;
+        if idx < menu.options.len() {
+            menu.selected = idx;
+        }
+    }
+
+    fn request_copy_to_clipboard<S: Into<String>>(&mut self, text: S) {
+        self.pending_clipboard = Some(text.into());
+    }
+
+    fn take_pending_clipboard(&mut self) -> Option<String> {
+        self.pending_clipboard.take()
+    }
+
+    fn load_persisted_bookmarks(&mut self) {
+        let Some(path) = self.bookmarks_path.clone() else {
+            return;
+        };
+
+        let data = fs::read_to_string(&path).ok();
+        let Some(data) = data else {
+            return;
+        };
+
+        let defaults = std::mem::take(&mut self.bookmarks);
+        self.bookmarks = bookmarks::parse(&data, defaults);
+    }
+
+    fn save_persisted_bookmarks(&mut self) {
+        let Some(path) = self.bookmarks_path.clone() else {
+            self.set_status("Cannot save favorites: no config dir");
+            return;
+        };
+
+        let content = bookmarks::format(&self.bookmarks);
+
+        if let Some(parent) = path.parent()
+            && let Err(e) = fs::create_dir_all(parent)
+        {
+            self.set_status(format!("Cannot save favorites: {}", e));
+            return;
+        }
+
+        let tmp = path.with_extension("tmp");
+        if fs::write(&tmp, content).is_err() || fs::rename(&tmp, &path).is_err() {
+            let _ = fs::remove_file(&tmp);
+            self.set_status("Failed to save favorites");
+        }
+    }
+
+    fn open_bookmarks_manager(&mut self) {
+        self.bookmarks_ui.open = true;
+        self.bookmarks_ui.rename = None;
+        self.bookmarks_ui.status = None;
+        let sel = self
+            .bookmarks
+            .iter()
+            .position(|b| b.path == self.current_path)
+            .or(if self.bookmarks.is_empty() {
+                None
+            } else {
+                Some(0)
+            });
+        self.bookmarks_ui.list_state.select(sel);
+    }
+
+    fn close_bookmarks_manager(&mut self) {
+        self.bookmarks_ui.open = false;
+        self.bookmarks_ui.rename = None;
+        self.bookmarks_ui.status = None;
+    }
+
+    fn move_bookmark_selection(&mut self, delta: i32) {
+        let len = self.bookmarks.len();
+        if len == 0 {
+            self.bookmarks_ui.list_state.select(None);
+            return;
+        }
+        let cur = self.bookmarks_ui.list_state.selected().unwrap_or(0) as i32;
+        let next = (cur + delta).clamp(0, len as i32 - 1);
+        self.bookmarks_ui.list_state.select(Some(next as usize));
+    }
+
+    /// Move the selected bookmark up or down in the list.
+    fn reorder_selected_bookmark(&mut self, delta: i32) {
+        let Some(cur) = self.bookmarks_ui.list_state.selected() else {
+            return;
+        };
+        let next = cur as i32 + delta;
+        if next < 0 || next as usize >= self.bookmarks.len() {
+            return;
+        }
+        self.bookmarks.swap(cur, next as usize);
+        self.bookmarks_ui.list_state.select(Some(next as usize));
+        self.save_persisted_bookmarks();
+    }
+
+    fn delete_selected_bookmark(&mut self) {
+        let Some(cur) = self.bookmarks_ui.list_state.selected() else {
+            return;
+        };
+        if cur >= self.bookmarks.len() {
+            return;
+        }
+        let removed = self.bookmarks.remove(cur);
+        let len = self.bookmarks.len();
+        self.bookmarks_ui.list_state.select(if len == 0 {
+            None
+        } else {
+            Some(cur.min(len - 1))
+        });
+        self.bookmarks_ui.status = Some(format!("Removed {}", removed.name));
+        self.save_persisted_bookmarks();
+    }
+
+    fn start_bookmark_rename(&mut self) {
+        let Some(b) = self
+            .bookmarks_ui
+            .list_state
+            .selected()
+            .and_then(|i| self.bookmarks.get(i))
+        else {
+            return;
+        };
+        self.bookmarks_ui.rename = Some(b.name.clone());
+    }
+
+    fn commit_bookmark_rename(&mut self) {
+        let Some(name) = self.bookmarks_ui.rename.take() else {
+            return;
+        };
+        let name = name.trim().to_string();
+        if name.is_empty() || name.contains('\t') {
+            self.bookmarks_ui.status = Some("Invalid name".to_string());
+            return;
+        }
+        if let Some(b) = self
+            .bookmarks_ui
+            .list_state
+            .selected()
+            .and_then(|i| self.bookmarks.get_mut(i))
+        {
+            b.name = name;
+            self.save_persisted_bookmarks();
+        }
+    }
+
+    /// Give the selected bookmark quick-jump `key`; pressing its current key clears it.
+    fn assign_bookmark_key(&mut self, key: char) {
+        let Some(cur) = self.bookmarks_ui.list_state.selected() else {
+            return;
+        };
+        let Some(current) = self.bookmarks.get(cur).map(|b| b.key) else {
+            return;
+        };
+        let new_key = if current == Some(key) {
+            None
+        } else {
+            Some(key)
+        };
+        for (i, b) in self.bookmarks.iter_mut().enumerate() {
+            if i == cur {
+                b.key = new_key;
+            } else if new_key.is_some() && b.key == new_key {
+                b.key = None;
+            }
+        }
+        self.bookmarks_ui.status = Some(match new_key {
+            Some(k) => format!("Alt+{} jumps here", k),
+            None => "Quick key cleared".to_string(),
+        });
+        self.save_persisted_bookmarks();
+    }
+
+    fn open_selected_bookmark(&mut self) {
+        let Some(path) = self
+            .bookmarks_ui
+            .list_state
+            .selected()
+            .and_then(|i| self.bookmarks.get(i))
+            .map(|b| b.path.clone())
+        else {
+            return;
+        };
+        self.close_bookmarks_manager();
+        self.current_tab = Tab::Explorer;
+        self.navigate_to(path);
+    }
+
+    fn jump_to_bookmark_key(&mut self, key: char) {
+        let Some(b) = self.bookmarks.iter().find(|b| b.key == Some(key)).cloned() else {
+            self.set_status(format!("No bookmark on Alt+{}", key));
+            return;
+        };
+        self.current_tab = Tab::Explorer;
+        self.navigate_to(b.path);
+        self.set_status(format!("Bookmark: {}", b.name));
+    }
+
+    fn load_persisted_ui_settings(&mut self) {
+        let Some(path) = self.ui_settings_path.clone() else {
+            return;
+        };
+
+        let data = fs::read_to_string(&path).ok();
+        let Some(data) = data else {
+            return;
+        };
+
+        let settings: PersistedUiSettings = match serde_json::from_str(&data) {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+
+        if let Some(w) = settings.log_left_width {
+            self.log_ui.left_width = w.clamp(32, 90);
+        }
+        if let Some(w) = settings.git_left_width {
+            self.git_left_width = w.clamp(32, 90);
+        }
+
+        if let Some(theme) = settings.theme {
+            self.set_theme(theme);
+        }
+
+        if let Some(wrap) = settings.wrap_diff {
+            self.wrap_diff = wrap;
+        }
+        if let Some(syntax) = settings.syntax_highlight {
+            self.syntax_highlight = syntax;
+        }
+        if let Some(numbers) = settings.diff_line_numbers {
+            self.diff_line_numbers = numbers;
+        }
+        if let Some(hexdump) = settings.binary_hexdump {
+            self.binary_hexdump = hexdump;
+        }
+
+        if let Some(side) = settings.git_side_by_side {
+            self.git.diff_mode = if side {
+                GitDiffMode::SideBySide
+            } else {
+                GitDiffMode::Unified
+            };
+        }
+        if let Some(z) = settings.git_zoom_diff {
+            self.git_zoom_diff = z;
+        }
+
+        if let Some(side) = settings.log_side_by_side {
+            self.log_ui.diff_mode = if side {
+                GitDiffMode::SideBySide
+            } else {
+                GitDiffMode::Unified
+            };
+        }
+
+        if let Some(z) = settings.log_zoom {
+            self.log_ui.zoom = z;
+        }
+
+        if let Some(m) = settings.log_detail_mode {
+            self.log_ui.detail_mode = m;
+        }
+        if let Some(show) = settings.log_date_column {
+            self.log_ui.show_date_column = show;
+        }
+        if let Some(show) = settings.log_author_column {
+            self.log_ui.show_author_column = show;
+        }
+        if let Some(open) = settings.log_refs_sidebar {
+            self.log_ui.refs.open = open;
+        }
+
+        if let Some(segments) = settings.top_bar {
+            self.top_bar = segments;
+        }
+        self.pull_options = settings.pull_options;
+    }
+
+    fn save_persisted_ui_settings(&mut self) {
+        let Some(path) = self.ui_settings_path.clone() else {
+            return;
+        };
+
+        let settings = PersistedUiSettings {
+            log_left_width: Some(self.log_ui.left_width),
+            git_left_width: Some(self.git_left_width),
+            theme: Some(self.theme),
+            wrap_diff: Some(self.wrap_diff),
+            syntax_highlight: Some(self.syntax_highlight),
+            diff_line_numbers: Some(self.diff_line_numbers),
+            binary_hexdump: Some(self.binary_hexdump),
+            git_side_by_side: Some(self.git.diff_mode == GitDiffMode::SideBySide),
+            git_zoom_diff: Some(self.git_zoom_diff),
+            log_side_by_side: Some(self.log_ui.diff_mode == GitDiffMode::SideBySide),
+            log_zoom: Some(self.log_ui.zoom),
+            log_detail_mode: Some(self.log_ui.detail_mode),
+            log_date_column: Some(self.log_ui.show_date_column),
+            log_author_column: Some(self.log_ui.show_author_column),
+            log_refs_sidebar: Some(self.log_ui.refs.open),
+            top_bar: Some(self.top_bar),
+            pull_options: self.pull_options.clone(),
+        };
+
+        let content = match serde_json::to_string(&settings) {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+
+        if let Some(parent) = path.parent() {
+            if fs::create_dir_all(parent).is_err() {
+                return;
+            }
+        }
+
+        let tmp = path.with_extension("tmp");
+        if fs::write(&tmp, content).is_err() || fs::rename(&tmp, &path).is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+    }
+
+    fn update_preview(&mut self) {
+        self.preview_error = None;
+        self.preview_scroll_offset = 0; // Reset preview scroll when changing files
+
+        // Cancel any pending preview load
+        if let Some(token) = self.preview_cancel_token.take() {
+            token.cancel();
+        }
+        self.preview_loader.cancel_current();
+
+        let Some(file) = self.selected_file() else {
+            self.image_state = None;
+            self.current_image_path = None;
+            self.preview_content = None;
+            self.preview_loading = false;
+            self.highlight_cache = None;
+            return;
+        };
+
+        if file.is_dir {
+            self.image_state = None;
+            self.current_image_path = None;
+            self.preview_content = None;
+            self.preview_loading = false;
+            self.highlight_cache = None;
+            return;
+        }
+
+        let path = file.path.clone();
+        let is_image = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .map(|ext| ext.to_lowercase())
+            .is_some_and(|ext| {
+                matches!(
+                    ext.as_str(),
+                    "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp"
+                )
+            });
+
+        if is_image {
+            // Handle image files synchronously (as before)
+            self.preview_content = None;
+            self.preview_loading = false;
+            self.highlight_cache = None;
+
+            if self.current_image_path.as_ref() == Some(&path) {
+                return;
+            }
+
+            match image::ImageReader::open(&path)
+                .and_then(|r| r.with_guessed_format())
+                .and_then(|r| r.decode().map_err(std::io::Error::other))
+            {
+                Ok(dyn_img) => {
+                    let proto = self.picker.new_resize_protocol(dyn_img);
+                    self.image_state = Some(proto);
+                    self.current_image_path = Some(path);
+                }
+                Err(e) => {
+                    self.preview_error = Some(format!("Image Error: {}", e));
+                    self.image_state = None;
+                    self.current_image_path = None;
+                }
+            }
+        } else {
+            // Handle text files asynchronously
+            self.image_state = None;
+            self.current_image_path = None;
+
+            // Check cache first for instant display
+            if let Some(cached) = self.preview_cache.get(&path) {
+                self.preview_loading = false;
+                if cached.is_binary {
+                    self.preview_content = None;
+                    self.preview_error = Some("Binary file".to_string());
+                    self.highlight_cache = None;
+                } else {
+                    let mut display_content = cached.text.clone();
+                    if cached.truncated {
+                        display_content.push_str("\n\n... (file truncated, too large to preview)");
+                    }
+                    self.preview_content = Some(display_content);
+                    self.preview_error = None;
+                    // Clear highlight cache when content changes
+                    self.highlight_cache = None;
+                }
+                // Trigger preloading for adjacent files after using cache
+                self.preload_adjacent_files();
+            } else {
+                // Not in cache, request async load
+                self.preview_loading = true;
+                self.preview_content = None;
+                self.highlight_cache = None;
+
+                // Request async preview load
+                let cancel_token = self.preview_loader.request_preview_sync(path);
+                self.preview_cancel_token = Some(cancel_token);
+            }
+        }
+    }
+
+    /// Preload previews for files adjacent to the current selection.
+    /// This provides instant navigation when moving between files.
+    fn preload_adjacent_files(&mut self) {
+        // Cancel any existing preload operations
+        for token in self.preload_cancel_tokens.drain(..) {
+            token.cancel();
+        }
+        self.preloaded_paths.clear();
+
+        let (prev, next) = self.adjacent_files();
+
+        // Collect paths to preload first to avoid borrow issues
+        let mut paths_to_preload = Vec::new();
+
+        // Check previous file
+        if let Some(file) = prev {
+            if self.should_preload(file) && !self.preview_cache.get(&file.path).is_some() {
+                paths_to_preload.push(file.path.clone());
+            }
+        }
+
+        // Check next file
+        if let Some(file) = next {
+            if self.should_preload(file) && !self.preview_cache.get(&file.path).is_some() {
+                paths_to_preload.push(file.path.clone());
+            }
+        }
+
+        // Now preload the collected paths
+        for path in paths_to_preload {
+            let cancel_token = self.preview_loader.request_preview_sync(path.clone());
+            self.preload_cancel_tokens.push(cancel_token);
+            self.preloaded_paths.insert(path);
+        }
+    }
+
+    /// Handle a preview result from the async loader.
+    fn handle_preview_result(&mut self, result: preview_loader::PreviewResult) {
+        use preview_loader::PreviewResult;
+
+        self.preview_loading = false;
+
+        match result {
+            PreviewResult::Ready {
+                path,
+                content,
+                truncated,
+            } => {
+                // Store in cache for future instant access
+                let cache_content = preview_cache::PreviewContent {
+                    text: content.clone(),
+                    is_binary: false,
+                    truncated,
+                };
+                self.preview_cache.insert(path.clone(), cache_content);
+
+                let mut display_content = content;
+                if truncated {
+                    display_content.push_str("\n\n... (file truncated, too large to preview)");
+                }
+                self.preview_content = Some(display_content);
+                self.preview_error = None;
+                // Clear highlight cache when content changes
+                self.highlight_cache = None;
+
+                // Trigger preloading for adjacent files after successful load
+                self.preload_adjacent_files();
+            }
+            PreviewResult::Partial {
+                path: _,
+                content,
+                start_line: _,
+                lines_loaded: _,
+                has_more_before,
+                has_more_after,
+            } => {
+                // Don't cache partial results as they're not complete
+                let mut display_content = String::new();
+                if has_more_before {
+                    display_content.push_str("... (scroll up for more)\n\n");
+                }
+                display_content.push_str(&content);
+                if has_more_after {
+                    display_content.push_str("\n\n... (scroll down for more)");
+                }
+                self.preview_content = Some(display_content);
+                self.preview_error = None;
+                // Clear highlight cache when content changes
+                self.highlight_cache = None;
+
+                // Also trigger preloading for partial results
+                self.preload_adjacent_files();
+            }
+            PreviewResult::Binary { path } => {
+                // Store binary flag in cache
+                let cache_content = preview_cache::PreviewContent {
+                    text: String::new(),
+                    is_binary: true,
+                    truncated: false,
+                };
+                self.preview_cache.insert(path, cache_content);
+
+                self.preview_content = None;
+                self.preview_error = Some("Binary file".to_string());
+                self.highlight_cache = None;
+            }
+            PreviewResult::Error { path: _, error } => {
+                self.preview_content = None;
+                self.preview_error = Some(error);
+                self.highlight_cache = None;
+            }
+            PreviewResult::Cancelled => {
+                // Ignore cancelled results, a new preview request should be pending
+            }
+        }
+    }
+
+    fn navigate_to(&mut self, path: PathBuf) {
+        if let Ok(canonical) = path.canonicalize() {
+            self.current_path = canonical;
+            self.load_files();
+            self.list_state
+                .select(if self.files.is_empty() { None } else { Some(0) });
+        } else if path.exists() {
+            self.current_path = path;
+            self.load_files();
+            self.list_state
+                .select(if self.files.is_empty() { None } else { Some(0) });
+        }
+        self.update_preview();
+    }
+
+    fn enter_selected(&mut self) {
+        if let Some(file) = self.selected_file().cloned()
+            && file.is_dir
+        {
+            if file.name == ".." {
+                self.go_parent();
+            } else {
+                self.navigate_to(file.path);
+            }
+        }
+    }
+
+    fn go_parent(&mut self) {
+        if let Some(parent) = self.current_path.parent() {
+            let old_name = self
+                .current_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string());
+            let parent_path = parent.to_path_buf();
+            self.navigate_to(parent_path);
+
+            if let Some(name) = old_name
+                && let Some(idx) = self.files.iter().position(|f| f.name == name)
+            {
+                self.list_state.select(Some(idx));
+            }
+        }
+        self.update_preview();
+    }
+
+    fn open_selected_in_editor(&mut self) {
+        if !self.feature_allowed(self.features.shell, "Running commands") {
+            return;
+        }
+        let Some(file) = self.selected_file() else {
+            return;
+        };
+        if file.is_dir {
+            return;
+        }
+
+        let editor = env::var("EDITOR").ok().filter(|s| !s.trim().is_empty());
+        let cmd = editor.unwrap_or_else(|| "vim".to_string());
+
+        // Properly leave TUI mode
+        let _ = disable_raw_mode();
+        let _ = execute!(
+            io::stdout(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            crossterm::cursor::Show
+        );
+        let _ = io::stdout().flush();
+
+        // Run editor
+        let status = std::process::Command::new(cmd.as_str())
+            .arg(file.path.as_os_str())
+            .stdin(std::process::Stdio::inherit())
+            .stdout(std::process::Stdio::inherit())
+            .stderr(std::process::Stdio::inherit())
+            .status();
+
+        // Restore TUI mode - order matters!
+        let _ = enable_raw_mode();
+        let _ = execute!(
+            io::stdout(),
+            EnterAlternateScreen,
+            crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
+            crossterm::terminal::Clear(crossterm::terminal::ClearType::Purge),
+            crossterm::cursor::MoveTo(0, 0),
+            crossterm::cursor::Hide,
+            EnableMouseCapture
+        );
+        let _ = io::stdout().flush();
+
+        match status {
+            Ok(s) if s.success() => self.set_status("Editor closed"),
+            Ok(_) => self.set_status("Editor exited with error"),
+            Err(e) => self.set_status(format!("Editor failed: {}", e)),
+        }
+
+        // Request full terminal redraw after editor
+        self.needs_full_redraw = true;
+        self.load_files();
+        self.update_preview();
+    }
+
+    fn handle_click(&mut self, row: u16, col: u16, modifiers: KeyModifiers) {
+        if self.theme_picker.open || self.command_palette.open {
+            self.context_menu = None;
+            self.pending_menu_action = None;
+
+            let (tw, th) = crossterm::terminal::size().unwrap_or((0, 0));
+            let area = Rect::new(0, 0, tw, th);
+
+            if self.command_palette.open {
+                let w = area.width.min(56).saturating_sub(2).max(32);
+                let desired_h = COMMAND_PALETTE_ITEMS.len() as u16 + 6;
+                let h = desired_h.min(area.height.saturating_sub(2)).max(10);
+                let x = area.x + (area.width.saturating_sub(w)) / 2;
+                let y = area.y + (area.height.saturating_sub(h)) / 2;
+                let modal = Rect::new(x, y, w, h);
+
+                if col < modal.x
+                    || col >= modal.x + modal.width
+                    || row < modal.y
+                    || row >= modal.y + modal.height
+                {
+                    self.command_palette.open = false;
+                    return;
+                }
+
+                let inner = modal.inner(Margin {
+                    vertical: 1,
+                    horizontal: 2,
+                });
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(1)])
+                    .split(inner);
+
+                let list_inner = rows[0].inner(Margin {
+                    vertical: 1,
+                    horizontal: 1,
+                });
+
+                if row >= list_inner.y && row < list_inner.y + list_inner.height {
+                    let offset = self.command_palette.list_state.offset();
+                    let idx = offset + (row - list_inner.y) as usize;
+                    if idx < COMMAND_PALETTE_ITEMS.len() {
+                        let was_selected = self.command_palette.list_state.selected() == Some(idx);
+                        self.command_palette.list_state.select(Some(idx));
+                        if was_selected {
+                            self.run_command_palette_selection();
+                        }
+                    }
+                }
+                return;
+            }
+
+            if self.theme_picker.open {
+                let w = 35u16.min(area.width.saturating_sub(2)).max(30);
+                let h = 11u16.min(area.height.saturating_sub(2)).max(9);
+                let x = area.x + (area.width.saturating_sub(w)) / 2;
+                let y = area.y + (area.height.saturating_sub(h)) / 2;
+                let modal = Rect::new(x, y, w, h);
+
+                if col < modal.x
+                    || col >= modal.x + modal.width
+                    || row < modal.y
+                    || row >= modal.y + modal.height
+                {
+                    self.theme_picker.open = false;
+                    return;
+                }
+
+                let inner = modal.inner(Margin {
+                    vertical: 1,
+                    horizontal: 2,
+                });
+                let rows = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(1)])
+                    .split(inner);
+
+                let list_inner = rows[0].inner(Margin {
+                    vertical: 1,
+                    horizontal: 1,
+                });
+
+                if row >= list_inner.y && row < list_inner.y + list_inner.height {
+                    let offset = self.theme_picker.list_state.offset();
+                    let idx = offset + (row - list_inner.y) as usize;
+                    if idx < THEME_ORDER.len() {
+                        let was_selected = self.theme_picker.list_state.selected() == Some(idx);
+                        self.theme_picker.list_state.select(Some(idx));
+                        if was_selected {
+                            self.apply_theme_picker_selection();
+                        }
+                    }
+                }
+                return;
+            }
+        }
+
+        if self.context_menu.is_some() {
+            let mut hit_menu = false;
+            for zone in self.zones.iter().rev() {
+                if row >= zone.rect.y
+                    && row < zone.rect.y + zone.rect.height
+                    && col >= zone.rect.x
+                    && col < zone.rect.x + zone.rect.width
+                {
+                    if let AppAction::ContextMenuAction(_) = zone.action {
+                        hit_menu = true;
+                    }
+                    break;
+                }
+            }
+
+            if !hit_menu {
+                self.context_menu = None;
+                self.pending_menu_action = None;
+                return;
+            }
+        }
+
+        self.focus_pane_at(row, col);
+
+        let mut action = AppAction::None;
+
+        for zone in self.zones.iter().rev() {
+            if row >= zone.rect.y
+                && row < zone.rect.y + zone.rect.height
+                && col >= zone.rect.x
+                && col < zone.rect.x + zone.rect.width
+            {
+                action = zone.action.clone();
+                break;
+            }
+        }
+
+        self.dispatch(action, row, col, modifiers);
+    }
+
+    /// Carry out `action`. Clicks pass the cell they landed on, which places menus
+    /// such as the hidden breadcrumbs, and the modifiers held for range selection.
+    fn dispatch(&mut self, action: AppAction, row: u16, col: u16, modifiers: KeyModifiers) {
+        match action {
+            AppAction::SwitchTab(tab) => {
+                if tab == Tab::Terminal
+                    && !self.feature_allowed(self.features.terminal, "The terminal")
+                {
+                    return;
+                }
+                self.current_tab = tab;
+                self.context_menu = None;
+                if tab == Tab::Git {
+                    self.start_git_refresh_job();
+                } else if tab == Tab::Log {
+                    self.refresh_log_data();
+                }
+            }
+            AppAction::RefreshGit => {
+                self.start_git_refresh_job();
+            }
+            AppAction::CopyRepoPath => {
+                if let Some(root) = self.git.repo_root.clone() {
+                    self.request_copy_to_clipboard(root.to_string_lossy().to_string());
+                }
+            }
+            AppAction::RemoveStaleLocks => self.remove_stale_locks(),
+            AppAction::DismissGitNotice => self.git_notice = None,
+            AppAction::NewBranchHere => self.new_branch_input = Some(String::new()),
+            AppAction::OpenCommandPalette => {
+                self.open_command_palette();
+            }
+            AppAction::OpenThemePicker => self.open_theme_picker(),
+            AppAction::Navigate(path) => self.navigate_to(path),
+            AppAction::ShowHiddenCrumbs(paths) => {
+                let options = paths
+                    .into_iter()
+                    .map(|p| {
+                        let label = p.to_string_lossy().to_string();
+                        (
+                            format!(" 📁 {} ", truncate_middle(&label, 24)),
+                            ContextCommand::Navigate(p),
+                        )
+                    })
+                    .collect();
+                self.context_menu = Some(ContextMenu {
+                    x: col,
+                    y: row + 1,
+                    selected: 0,
+                    options,
+                });
+            }
+            AppAction::EnterDir => self.enter_selected(),
+            AppAction::GoParent => self.go_parent(),
+            AppAction::Select(idx) => {
+                let now = Instant::now();
+                let is_double_click = if let Some((last_time, last_idx)) = self.last_click {
+                    idx == last_idx && now.duration_since(last_time) < Duration::from_millis(400)
+                } else {
+                    false
+                };
+
+                self.list_state.select(Some(idx));
+                self.update_preview();
+                self.preview_scroll = 0;
+
+                if is_double_click {
+                    self.enter_selected();
+                    self.last_click = None;
+                } else {
+                    self.last_click = Some((now, idx));
+                }
+            }
+            AppAction::SelectGitSection(section) => {
+                self.git.set_section(section);
+                self.git.selected_paths.clear();
+                self.git.selection_anchor = None;
+                self.request_git_diff_update();
+            }
+            AppAction::SelectGitFile(idx) => {
+                self.git.select_filtered(idx);
+                self.request_git_diff_update();
+
+                let Some(abs) = self.git.filtered.get(idx).copied() else {
+                    return;
+                };
+                let Some(entry) = self.git.entries.get(abs) else {
+                    return;
+                };
+
+                if modifiers.contains(KeyModifiers::SHIFT) {
+                    let anchor = self.git.selection_anchor.unwrap_or(idx);
+                    let (a, b) = if anchor <= idx {
+                        (anchor, idx)
+                    } else {
+                        (idx, anchor)
+                    };
+                    self.git.selected_paths.clear();
+                    for i in a..=b {
+                        if let Some(abs) = self.git.filtered.get(i).copied()
+                            && let Some(e) = self.git.entries.get(abs)
+                        {
+                            self.git.selected_paths.insert(e.path.clone());
+                        }
+                    }
+                } else if modifiers.contains(KeyModifiers::CONTROL) {
+                    if self.git.selected_paths.contains(&entry.path) {
+                        self.git.selected_paths.remove(&entry.path);
+                    } else {
+                        self.git.selected_paths.insert(entry.path.clone());
+                    }
+                    self.git.selection_anchor = Some(idx);
+                } else {
+                    self.git.selected_paths.clear();
+                    self.git.selected_paths.insert(entry.path.clone());
+                    self.git.selection_anchor = Some(idx);
+                }
+            }
+            AppAction::OpenRecentCommit(idx) => self.open_recent_commit(idx),
+            AppAction::SelectBlameLine(idx) => self.blame.selected = idx,
+            AppAction::SelectGitTreeItem(idx) => {
+                self.git.select_tree(idx);
+
+                // Handle selection based on item type
+                if let Some(item) = self.git.flat_tree.get(idx) {
+                    use git::FlatNodeType;
+                    match item.node_type {
+                        FlatNodeType::Section | FlatNodeType::Directory => {
+                            // Toggle expand/collapse on click
+                            self.git.toggle_tree_expand();
+                        }
+                        FlatNodeType::File => {
+                            // Handle file selection with modifiers
+                            if let Some(entry_idx) = item.entry_idx {
+                                if let Some(entry) = self.git.entries.get(entry_idx) {
+                                    if modifiers.contains(KeyModifiers::SHIFT) {
+                                        let anchor = self.git.selection_anchor.unwrap_or(idx);
+                                        let (a, b) = if anchor <= idx {
+                                            (anchor, idx)
+                                        } else {
+                                            (idx, anchor)
+                                        };
+                                        self.git.selected_paths.clear();
+                                        for i in a..=b {
+                                            if let Some(item) = self.git.flat_tree.get(i) {
+                                                if item.node_type == FlatNodeType::File {
+                                                    if let Some(e_idx) = item.entry_idx {
+                                                        if let Some(e) = self.git.entries.get(e_idx)
+                                                        {
+                                                            self.git
+                                                                .selected_paths
+                                                                .insert(e.path.clone());
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    } else if modifiers.contains(KeyModifiers::CONTROL) {
+                                        if self.git.selected_paths.contains(&entry.path) {
+                                            self.git.selected_paths.remove(&entry.path);
+                                        } else {
+                                            self.git.selected_paths.insert(entry.path.clone());
+                                        }
+                                        self.git.selection_anchor = Some(idx);
+                                    } else {
+                                        self.git.selected_paths.clear();
+                                        self.git.selection_anchor = Some(idx);
+                                    }
+                                    self.request_git_diff_update();
+                                }
+                            }
+                        }
+                        FlatNodeType::Submodule => self.request_git_diff_update(),
+                    }
+                }
+            }
+            AppAction::ToggleGitTreeExpand => {
+                self.git.toggle_tree_expand();
+            }
+            AppAction::RevertHunk(hunk_idx) => {
+                self.revert_hunk(hunk_idx);
+            }
+            AppAction::StashHunk(hunk_idx) => {
+                self.stash_hunk(hunk_idx);
+            }
+            AppAction::ExpandHunkContext(hunk_idx) => {
+                self.expand_diff_context(Some(hunk_idx), true);
+            }
+            AppAction::RevertBlock(block_idx) => {
+                self.revert_block(block_idx);
+            }
+            AppAction::DiffContext(grow) => self.expand_diff_context(None, grow),
+            AppAction::StashHunkInView => match self.git.hunk_at_scroll() {
+                Some(idx) => self.stash_hunk(idx),
+                None => self.set_status("No hunk to stash"),
+            },
+            AppAction::ToggleFullFile => self.toggle_full_file_view(),
+            AppAction::CycleFullFileSource => self.cycle_full_file_source(),
+            AppAction::ToggleDiffMode => {
+                let mode = if self.current_tab == Tab::Log {
+                    self.log_ui.focus = LogPaneFocus::Diff;
+                    &mut self.log_ui.diff_mode
+                } else {
+                    &mut self.git.diff_mode
+                };
+                *mode = match mode {
+                    GitDiffMode::Unified => GitDiffMode::SideBySide,
+                    GitDiffMode::SideBySide => GitDiffMode::Unified,
+                };
+            }
+            AppAction::JumpDiffAnchor { files, forward } => self.jump_diff_anchor(files, forward),
+            AppAction::StartDiffSelection => self.start_diff_selection(),
+            AppAction::YankDiffSelection(kind) => self.yank_diff_selection(kind),
+            AppAction::ClearDiffSelection => self.diff_selection = None,
+            AppAction::RepeatLastAction => self.repeat_last_action(),
+            AppAction::SelectAllGitFiles => self.select_all_git_filtered(),
+            AppAction::UndoRevert => self.undo_revert(),
+            AppAction::RedoRevert => self.redo_revert(),
+            AppAction::ToggleCommitDrawer => {
+                self.commit.open = !self.commit.open;
+                if self.commit.open {
+                    self.commit.focus = CommitFocus::Message;
+                }
+            }
+            AppAction::FocusCommitMessage => {
+                self.commit.focus = CommitFocus::Message;
+            }
+            AppAction::ToggleCommitAdvanced => self.toggle_commit_advanced(),
+            AppAction::ToggleCommitAmend => self.toggle_commit_amend(),
+            AppAction::PickCommitAuthor => self.open_commit_author_picker(),
+            AppAction::PickCoAuthors => self.open_co_author_picker(),
+            AppAction::OpenMessagePicker => self.open_message_picker(),
+            AppAction::PickCommitMessage(idx) => self.pick_commit_message(idx),
+            AppAction::PickCommitFind(idx) => {
+                if let Some(find) = self.commit_find.as_mut() {
+                    find.selected = idx;
+                }
+                self.jump_to_commit_find();
+                self.commit_find = None;
+            }
+            AppAction::PickSyntax(idx) => self.pick_syntax(idx),
+            AppAction::ResetCommitOverrides => self.commit.clear_overrides(),
+            AppAction::FocusCommitDate => self.commit.focus = CommitFocus::Date,
+            AppAction::ToggleConventionalCommit => self.commit.toggle_conventional(),
+            AppAction::CycleCommitType(delta) => {
+                self.commit.cycle_conventional_type(delta);
+                self.commit.focus = CommitFocus::Type;
+            }
+            AppAction::ToggleBreakingChange => self.commit.toggle_breaking(),
+            AppAction::FocusCommitScope => self.commit.focus = CommitFocus::Scope,
+            AppAction::GenerateCommitMessage => {
+                self.start_ai_generate();
+            }
+            AppAction::AcceptConfirm => self.accept_confirm(),
+            AppAction::CancelConfirm => self.confirm = None,
+            AppAction::ClearGitLog => {
+                self.git_log.clear();
+                self.log_ui.command_state.select(None);
+                self.log_ui.diff_lines.clear();
+                self.set_status("Commands cleared");
+            }
+            AppAction::LogSwitch(subtab) => {
+                self.set_log_subtab(subtab);
+            }
+            AppAction::LogDiffBase(base) => {
+                self.set_log_diff_base(base);
+            }
+            AppAction::ToggleReflogOp(op) => {
+                self.log_ui.toggle_reflog_op(op);
+                self.refresh_log_diff();
+            }
+            AppAction::LogDetail(mode) => {
+                self.log_ui.inspect.close();
+                self.log_ui.set_detail_mode(mode);
+                self.refresh_log_diff();
+            }
+            AppAction::LogToggleZoom => {
+                self.toggle_log_zoom();
+            }
+            AppAction::LogInspect => {
+                if self.log_ui.inspect.open {
+                    self.log_ui.inspect.close();
+                } else {
+                    self.open_log_inspect();
+                }
+            }
+            AppAction::LogCloseInspect => {
+                self.log_ui.inspect.close();
+            }
+            AppAction::LogInspectCopyPrimary => {
+                if let Some(s) = self
+                    .selected_log_hash()
+                    .or_else(|| self.selected_log_command())
+                {
+                    self.request_copy_to_clipboard(s);
+                }
+                self.log_ui.inspect.close();
+            }
+            AppAction::LogInspectCopySecondary => {
+                if let Some(s) = self.selected_log_subject() {
+                    self.request_copy_to_clipboard(s);
+                } else if !self.log_ui.inspect.body.is_empty() {
+                    self.request_copy_to_clipboard(self.log_ui.inspect.body.clone());
+                }
+                self.log_ui.inspect.close();
+            }
+            AppAction::LogFocusDiff => {
+                self.log_ui.focus = LogPaneFocus::Diff;
+            }
+            AppAction::LogDiffScrollTo(row) => {
+                self.log_ui.diff_scroll_y = row;
+                self.log_ui.focus = LogPaneFocus::Diff;
+            }
+            AppAction::GitDiffScrollTo(row) => {
+                self.git.diff_scroll_y = row;
+            }
+            AppAction::LogFocusFiles => {
+                self.log_ui.focus = LogPaneFocus::Files;
+            }
+            AppAction::LogAdjustLeft(delta) => {
+                self.adjust_log_left_width(delta);
+            }
+            AppAction::SelectLogItem(idx) => {
+                self.select_log_item(idx);
+                if self.log_ui.subtab == LogSubTab::History {
+                    if modifiers.contains(KeyModifiers::SHIFT) {
+                        self.toggle_log_mark(idx, true);
+                    } else if modifiers.contains(KeyModifiers::CONTROL) {
+                        self.toggle_log_mark(idx, false);
+                    }
+                }
+            }
+            AppAction::SelectRefItem(idx) => self.select_ref_item(idx),
+            AppAction::SelectLogFile(idx) => {
+                self.select_log_file(idx);
+            }
+            AppAction::ToggleLogMark(idx) => self.toggle_log_mark(idx, false),
+            AppAction::CycleReflogOp => {
+                self.log_ui.cycle_reflog_op();
+                self.refresh_log_diff();
+            }
+            AppAction::CycleLogDiffBase => self.cycle_log_diff_base(),
+            AppAction::LoadMoreLog => self.load_more_log_data(),
+            AppAction::LogCherryPick => self.confirm_cherry_pick(),
+            AppAction::LogRevert(no_commit) => self.confirm_revert_commit(no_commit),
+            AppAction::LogReset => self.open_reset_picker(),
+            AppAction::LogCheckout => self.confirm_checkout_commit(),
+            AppAction::LogStashApply => self.stash_apply_log_selected(),
+            AppAction::LogStashPop => self.open_stash_confirm_log_selected(StashConfirmAction::Pop),
+            AppAction::LogStashDrop => {
+                self.open_stash_confirm_log_selected(StashConfirmAction::Drop)
+            }
+            AppAction::CloseOperationPopup => {
+                self.operation_popup = None;
+            }
+            AppAction::RetryOperation => self.retry_operation(),
+            AppAction::CopyChangelog => {
+                if let Some(view) = &self.changelog_view {
+                    self.request_copy_to_clipboard(view.markdown.clone());
+                }
+            }
+            AppAction::ExportChangelog => {
+                if let Some(view) = self.changelog_view.as_mut() {
+                    view.export_path = Some("lzgit-changelog.md".to_string());
+                }
+            }
+            AppAction::CloseChangelog => self.changelog_view = None,
+            AppAction::MergeContinue => self.start_operation_job("git merge --continue", true),
+            AppAction::MergeAbort => self.start_operation_job("git merge --abort", true),
+            AppAction::RebaseContinue => self.start_operation_job("git rebase --continue", true),
+            AppAction::RebaseAbort => self.start_operation_job("git rebase --abort", true),
+            AppAction::RebaseSkip => self.start_operation_job("git rebase --skip", true),
+            AppAction::AmContinue => self.start_operation_job("git am --continue", true),
+            AppAction::AmSkip => self.start_operation_job("git am --skip", true),
+            AppAction::AmAbort => self.start_operation_job("git am --abort", true),
+            AppAction::CherryPickContinue => {
+                self.start_operation_job("git cherry-pick --continue", true)
+            }
+            AppAction::CherryPickSkip => self.start_operation_job("git cherry-pick --skip", true),
+            AppAction::CherryPickAbort => self.start_operation_job("git cherry-pick --abort", true),
+            AppAction::RevertContinue => self.start_operation_job("git revert --continue", true),
+            AppAction::RevertSkip => self.start_operation_job("git revert --skip", true),
+            AppAction::RevertAbort => self.start_operation_job("git revert --abort", true),
+            AppAction::OpenReview => self.open_review(),
+            AppAction::ReviewApprove => self.review.approve(),
+            AppAction::ReviewUnstage => self.review_unstage(),
+            AppAction::ReviewBack => self.review.back(),
+            AppAction::CloseReview => self.review.open = false,
+            AppAction::FinishReview => {
+                self.review.open = false;
+                self.commit.open = true;
+            }
+            AppAction::ConflictPrev => self.change_conflict_block(-1),
+            AppAction::ConflictNext => self.change_conflict_block(1),
+            AppAction::ConflictUseOurs => self.apply_conflict_resolution(ConflictResolution::Ours),
+            AppAction::ConflictUseTheirs => {
+                self.apply_conflict_resolution(ConflictResolution::Theirs)
+            }
+            AppAction::ConflictUseBoth => self.apply_conflict_resolution(ConflictResolution::Both),
+            AppAction::MarkResolved => self.mark_conflict_resolved(),
+            AppAction::OpenBranchPicker => self.open_branch_picker(),
+            AppAction::OpenLogBranchPicker => self.open_log_branch_picker(),
+            AppAction::CloseBranchPicker => self.close_branch_picker(),
+            AppAction::SelectBranch(idx) => {
+                self.branch_ui.list_state.select(Some(idx));
+            }
+            AppAction::SelectLogBranch(idx) => {
+                let was_selected = self.branch_ui.list_state.selected() == Some(idx);
+                self.branch_ui.list_state.select(Some(idx));
+                if was_selected {
+                    self.confirm_log_branch_picker();
+                }
+            }
+            AppAction::ConfirmLogBranchPicker => self.confirm_log_branch_picker(),
+            AppAction::OpenAuthorPicker => self.open_author_picker(),
+            AppAction::CloseAuthorPicker => self.close_author_picker(),
+            AppAction::SelectAuthor(idx) => {
+                let was_selected = self.author_ui.list_state.selected() == Some(idx);
+                self.author_ui.list_state.select(Some(idx));
+                if self.author_picker_mode == AuthorPickerMode::CoAuthors {
+                    self.author_ui.toggle_marked();
+                } else if was_selected {
+                    self.confirm_author_picker();
+                }
+            }
+            AppAction::ConfirmAuthorPicker => self.confirm_author_picker(),
+            AppAction::BranchCheckout => self.branch_checkout_selected(),
+            AppAction::BranchNew => self.branch_new(),
+            AppAction::BranchRename => self.branch_rename_selected(),
+            AppAction::BranchDelete => self.branch_delete_selected(),
+            AppAction::BranchUpstream => self.branch_upstream_selected(),
+            AppAction::BranchDescribe => self.branch_describe_selected(),
+            AppAction::OpenStashPicker => self.open_stash_picker(),
+            AppAction::CloseStashPicker => self.close_stash_picker(),
+            AppAction::CloseSnapshots => self.close_snapshot_picker(),
+            AppAction::OpenBookmarks => self.open_bookmarks_manager(),
+            AppAction::CloseBookmarks => self.close_bookmarks_manager(),
+            AppAction::SelectBookmark(idx) => {
+                if self.bookmarks_ui.list_state.selected() == Some(idx) {
+                    self.open_selected_bookmark();
+                } else {
+                    self.bookmarks_ui.list_state.select(Some(idx));
+                }
+            }
+            AppAction::BookmarkOpen => self.open_selected_bookmark(),
+            AppAction::BookmarkRename => self.start_bookmark_rename(),
+            AppAction::BookmarkMoveUp => self.reorder_selected_bookmark(-1),
+            AppAction::BookmarkMoveDown => self.reorder_selected_bookmark(1),
+            AppAction::BookmarkDelete => self.delete_selected_bookmark(),
+            AppAction::BookmarkAssignKey(key) => self.assign_bookmark_key(key),
+            AppAction::SelectSnapshot(idx) => {
+                if self.snapshot_ui.list_state.selected() == Some(idx) {
+                    self.diff_selected_snapshot();
+                } else {
+                    self.snapshot_ui.list_state.select(Some(idx));
+                }
+            }
+            AppAction::SnapshotDiff => self.diff_selected_snapshot(),
+            AppAction::SnapshotRestore => self.restore_selected_snapshot(),
+            AppAction::SnapshotDelete => self.delete_selected_snapshot(),
+            AppAction::SelectStash(idx) => {
+                self.stash_ui.list_state.select(Some(idx));
+            }
+            AppAction::StashApply => self.stash_apply_selected(),
+            AppAction::StashPop => {
+                self.stash_ui.status = None;
+                let Some(sel) = self.stash_ui.selected_stash() else {
+                    self.set_stash_status("No stash selected");
+                    return;
+                };
+                self.open_stash_confirm(StashConfirmAction::Pop, sel.selector.clone());
+            }
+            AppAction::StashDrop => {
+                self.stash_ui.status = None;
+                let Some(sel) = self.stash_ui.selected_stash() else {
+                    self.set_stash_status("No stash selected");
+                    return;
+                };
+                self.open_stash_confirm(StashConfirmAction::Drop, sel.selector.clone());
+            }
+            AppAction::CloseTagPicker => self.close_tag_picker(),
+            AppAction::SelectTag(idx) => {
+                if self.tag_ui.list_state.selected() == Some(idx) {
+                    self.show_selected_tag();
+                } else {
+                    self.tag_ui.list_state.select(Some(idx));
+                }
+            }
+            AppAction::TagShow => self.show_selected_tag(),
+            AppAction::TagPush => self.push_tags_confirm(false),
+            AppAction::TagPushAll => self.push_tags_confirm(true),
+            AppAction::TagDelete => self.delete_selected_tag(),
+            AppAction::OpenRemotes => self.open_remote_manager(),
+            AppAction::ToggleStashOption(toggle) => {
+                if let Some(input) = self.stash_input.as_mut() {
+                    input.toggle(toggle);
+                    input.focus = Some(toggle);
+                }
+            }
+            AppAction::SubmitStash => self.submit_stash_input(),
+            AppAction::PushField(field) => {
+                if let Some(input) = self.push_input.as_mut() {
+                    match field {
+                        PushField::Remote => input.cycle_remote(true),
+                        PushField::Ref => {}
+                        PushField::ForceWithLease => {
+                            input.force_with_lease = !input.force_with_lease
+                        }
+                        PushField::SetUpstream => input.set_upstream = !input.set_upstream,
+                    }
+                    input.focus = field;
+                }
+            }
+            AppAction::SubmitPush => self.submit_push_input(),
+            AppAction::PullStrategy(strategy) => self.pick_pull_strategy(strategy),
+            AppAction::TogglePullAutostash => {
+                if let Some(options) = self.pull_picker.as_mut() {
+                    options.autostash = !options.autostash;
+                }
+            }
+            AppAction::OpenDirSummaryFile(idx) => self.open_dir_summary_file(idx),
+            AppAction::ToggleNewBranchCheckout => {
+                self.new_branch_checkout = !self.new_branch_checkout;
+            }
+            AppAction::EnterSubmodule => self.enter_selected_submodule(),
+            AppAction::LeaveSubmodule => self.leave_submodule(),
+            AppAction::Submodule(op) => self.run_submodule_op(op),
+            AppAction::CloseRemotes => self.close_remote_manager(),
+            AppAction::SelectRemote(idx) => {
+                if self.remote_ui.list_state.selected() == Some(idx) {
+                    self.use_selected_remote();
+                } else {
+                    self.remote_ui.list_state.select(Some(idx));
+                }
+            }
+            AppAction::RemoteUse => self.use_selected_remote(),
+            AppAction::RemoteAdd => self.remote_add(),
+            AppAction::RemoteRename => self.remote_rename_selected(),
+            AppAction::RemoteUrl(push) => self.remote_url_selected(push),
+            AppAction::RemoteRemove => self.remove_selected_remote(),
+            AppAction::GitFetch => self.fetch(),
+            AppAction::GitPull => self.pull(),
+            AppAction::OpenKeyHelp => self.key_help = Some(KeyHelp::default()),
+            AppAction::OpenFileFinder => self.open_file_finder(),
+            AppAction::LockScreen => self.show_lock_screen(),
+            AppAction::PickFoundFile(idx) => {
+                if let Some(finder) = self.file_finder.as_mut() {
+                    finder.selected = idx;
+                }
+                self.open_found_file(false);
+            }
+            AppAction::GitPush => self.push(),
+            AppAction::ToggleGitStage => self.toggle_stage_for_selection(),
+            AppAction::GitStageAllVisible => self.stage_all_visible(),
+            AppAction::GitUnstageAllVisible => self.unstage_all_visible(),
+            AppAction::GitFooter(action) => {
+                self.handle_git_footer(action);
+            }
+            AppAction::ToggleHidden => {
+                self.show_hidden = !self.show_hidden;
+                self.load_files();
+            }
+            AppAction::AddToGitignore => self.add_selected_to_gitignore(),
+            AppAction::UndoFileOp => self.undo_file_op(),
+            AppAction::RedoFileOp => self.redo_file_op(),
+            AppAction::NewEntry => self.open_explorer_create(),
+            AppAction::RenameEntry => self.open_explorer_rename(),
+            AppAction::DeleteEntry => self.show_delete_confirm(),
+            AppAction::OpenInEditor => self.open_selected_in_editor(),
+            AppAction::RefreshFiles => {
+                self.load_files();
+                self.set_status("Refreshed");
+            }
+            AppAction::ToggleExplorerZoom => self.toggle_explorer_zoom(),
+            AppAction::ToggleAutoRefresh => {
+                self.auto_refresh = !self.auto_refresh;
+                self.set_status(if self.auto_refresh {
+                    "Auto-refresh: on"
+                } else {
+                    "Auto-refresh: off"
+                });
+            }
+            AppAction::JumpToBookmark(key) => self.jump_to_bookmark_key(key),
+            AppAction::AcceptUpdate => self.confirm_update(),
+            AppAction::CancelUpdate => self.update_confirm = None,
+            AppAction::Quit => self.should_quit = true,
+            AppAction::ContextMenuAction(idx) => {
+                if let Some(menu) = &mut self.context_menu {
+                    menu.selected = idx;
+                }
+                self.pending_menu_action = Some((idx, false));
+            }
+            AppAction::None => {}
+        }
+    }
+
+    fn handle_context_click(&mut self, row: u16, col: u16, modifiers: KeyModifiers) {
+        let mut action = AppAction::None;
+        for zone in self.zones.iter().rev() {
+            if row >= zone.rect.y
+                && row < zone.rect.y + zone.rect.height
+                && col >= zone.rect.x
+                && col < zone.rect.x + zone.rect.width
+            {
+                action = zone.action.clone();
+                break;
+            }
+        }
+
+        match action {
+            AppAction::Select(idx) => {
+                self.list_state.select(Some(idx));
+                self.update_preview();
+                self.preview_scroll = 0;
+            }
+            AppAction::SelectGitSection(section) => {
+                self.git.set_section(section);
+                self.git.selected_paths.clear();
+                self.git.selection_anchor = None;
+                self.request_git_diff_update();
+            }
+            AppAction::SelectGitFile(idx) => {
+                self.git.select_filtered(idx);
+                self.request_git_diff_update();
+
+                let Some(abs) = self.git.filtered.get(idx).copied() else {
+                    return;
+                };
+                let Some(entry) = self.git.entries.get(abs) else {
+                    return;
+                };
+
+                if modifiers.contains(KeyModifiers::SHIFT) {
+                    let anchor = self.git.selection_anchor.unwrap_or(idx);
+                    let (a, b) = if anchor <= idx {
+                        (anchor, idx)
+                    } else {
+                        (idx, anchor)
+                    };
+                    self.git.selected_paths.clear();
+                    for i in a..=b {
+                        if let Some(abs) = self.git.filtered.get(i).copied()
+                            && let Some(e) = self.git.entries.get(abs)
+                        {
+                            self.git.selected_paths.insert(e.path.clone());
+                        }
+                    }
+                } else if modifiers.contains(KeyModifiers::CONTROL) {
+                    if self.git.selected_paths.contains(&entry.path) {
+                        self.git.selected_paths.remove(&entry.path);
+                    } else {
+                        self.git.selected_paths.insert(entry.path.clone());
+                    }
+                    self.git.selection_anchor = Some(idx);
+                } else {
+                    self.git.selected_paths.clear();
+                    self.git.selected_paths.insert(entry.path.clone());
+                    self.git.selection_anchor = Some(idx);
+                }
+            }
+            AppAction::SelectLogItem(idx) => {
+                self.select_log_item(idx);
+            }
+            AppAction::SelectRefItem(idx) if self.log_ui.refs.entry_at(idx).is_some() => {
+                self.log_ui.refs.list_state.select(Some(idx));
+                self.log_ui.refs.menu_target = Some(idx);
+            }
+            _ => {}
+        }
+    }
+
+    fn open_context_menu(&mut self, row: u16, col: u16) {
+        let mut options: Vec<(String, ContextCommand)> = Vec::new();
+
+        // The branch picker only offers configured branch actions
+        if self.branch_ui.open {
+            self.push_external_actions(&mut options, actions::ActionContext::Branch);
+            if !options.is_empty() {
+                self.context_menu = Some(ContextMenu {
+                    x: col,
+                    y: row,
+                    selected: 0,
+                    options,
+                });
+            }
+            return;
+        }
+
+        match self.current_tab {
+            Tab::Explorer => {
+                options.push((" 📋 Copy Path ".to_string(), ContextCommand::CopyPath));
+                options.push((
+                    " 📄 Copy Relative Path ".to_string(),
+                    ContextCommand::CopyRelPath,
+                ));
+                options.push((
+                    " 🔗 Copy File URI ".to_string(),
+                    ContextCommand::CopyFileUri,
+                ));
+
+                let current_path = if let Some(idx) = self.selected_index() {
+                    if let Some(f) = self.files.get(idx) {
+                        if f.is_dir {
+                            f.path.clone()
+                        } else {
+                            self.current_path.clone()
+                        }
+                    } else {
+                        self.current_path.clone()
+                    }
+                } else {
+                    self.current_path.clone()
+                };
+
+                let is_bookmarked = self.bookmarks.iter().any(|b| b.path == current_path);
+                if is_bookmarked {
+                    options.push((
+                        " 🚫 Remove Bookmark ".to_string(),
+                        ContextCommand::RemoveBookmark,
+                    ));
+                } else {
+                    options.push((" 🔖 Add Bookmark ".to_string(), ContextCommand::AddBookmark));
+                }
+                options.push((
+                    " 📚 Manage Bookmarks ".to_string(),
+                    ContextCommand::ManageBookmarks,
+                ));
+
+                if self.selected_file().is_some_and(|f| !f.is_dir) {
+                    options.push((
+                        " 📝 Copy File Contents ".to_string(),
+                        ContextCommand::CopyContents,
+                    ));
+                }
+                options.push((" ➕ New file/folder ".to_string(), ContextCommand::NewEntry));
+                options.push((" ✏️  Rename ".to_string(), ContextCommand::Rename));
+                options.push((" 🗑️  Delete ".to_string(), ContextCommand::Delete));
+
+                if self.git.repo_root.is_some() {
+                    options.push((
+                        " 🙈 Add to .gitignore ".to_string(),
+                        ContextCommand::GitAddToGitignore,
+                    ));
+                    if self.selected_file().is_some_and(|f| !f.is_dir) {
+                        options.push((" 👤 Blame ".to_string(), ContextCommand::Blame));
+                    }
+                    if self.selected_bundle().is_some() {
+                        options.push((
+                            " 📦 Fetch From Bundle ".to_string(),
+                            ContextCommand::BundleFetch,
+                        ));
+                        options.push((
+                            " 📦 Pull From Bundle ".to_string(),
+                            ContextCommand::BundlePull,
+                        ));
+                    }
+                }
+
+                if self.selected_file().is_some_and(|f| !f.is_dir) {
+                    self.push_pane_options(&mut options, self.git.repo_root.is_some());
+                    self.push_external_actions(&mut options, actions::ActionContext::File);
+                }
+            }
+            Tab::Git if self.git.selected_submodule().is_some() => {
+                options.push((
+                    " ⤵ Enter Submodule ".to_string(),
+                    ContextCommand::SubmoduleEnter,
+                ));
+                for (label, op) in [
+                    (" ⟳ Update ", git_ops::SubmoduleOp::Update),
+                    (" Init ", git_ops::SubmoduleOp::Init),
+                    (" Sync URL ", git_ops::SubmoduleOp::Sync),
+                ] {
+                    options.push((label.to_string(), ContextCommand::Submodule(op)));
+                }
+            }
+            Tab::Git => {
+                let paths = self.selected_git_paths();
+
+                options.push((
+                    " ✅ Toggle Stage ".to_string(),
+                    ContextCommand::GitToggleStage,
+                ));
+                options.push((" + Stage ".to_string(), ContextCommand::GitStage));
+                options.push((" - Unstage ".to_string(), ContextCommand::GitUnstage));
+
+                let discard_label = if paths.len() == 1 {
+                    " ↩ Discard… ".to_string()
+                } else {
+                    format!(" ↩ Discard… ({}) ", paths.len())
+                };
+                options.push((discard_label, ContextCommand::GitDiscard));
+
+                options.push((" Stage All ".to_string(), ContextCommand::GitStageAll));
+                options.push((" Unstage All ".to_string(), ContextCommand::GitUnstageAll));
+
+                let ignore_label = if paths.len() <= 1 {
+                    " 🙈 Add to .gitignore ".to_string()
+                } else {
+                    format!(" 🙈 Add to .gitignore ({}) ", paths.len())
+                };
+                options.push((ignore_label, ContextCommand::GitAddToGitignore));
+
+                options.push((" 📋 Copy Path ".to_string(), ContextCommand::GitCopyPath));
+                options.push((
+                    " 📄 Copy Relative Path ".to_string(),
+                    ContextCommand::GitCopyRelPath,
+                ));
+                options.push((
+                    " 🔗 Copy File URI ".to_string(),
+                    ContextCommand::GitCopyFileUri,
+                ));
+                if self.git.selected_tree_entry().is_some() {
+                    options.push((
+                        " 📝 Copy File Contents ".to_string(),
+                        ContextCommand::GitCopyContents,
+                    ));
+                    options.push((" ± Copy Diff ".to_string(), ContextCommand::GitCopyDiff));
+                    options.push((" 👤 Blame ".to_string(), ContextCommand::Blame));
+                }
+                options.push((
+                    " 📂 Open In Explorer ".to_string(),
+                    ContextCommand::GitOpenInExplorer,
+                ));
+
+                if self.git.selected_tree_entry().is_some() {
+                    self.push_pane_options(&mut options, true);
+                    self.push_external_actions(&mut options, actions::ActionContext::File);
+                }
+                if !self.git.branch.is_empty() {
+                    self.push_external_actions(&mut options, actions::ActionContext::Branch);
+                }
+            }
+            Tab::Log if self.log_ui.refs.menu_target.is_some() => {
+                let target = self.log_ui.refs.menu_target.take();
+                let Some(entry) = target.and_then(|i| self.log_ui.refs.entry_at(i)).cloned() else {
+                    return;
+                };
+                if !entry.is_current {
+                    options.push((
+                        " ⎇ Checkout ".to_string(),
+                        ContextCommand::RefCheckout(entry.clone()),
+                    ));
+                    options.push((
+                        " ⇄ Compare with HEAD ".to_string(),
+                        ContextCommand::RefCompare(entry.name.clone()),
+                    ));
+                }
+                options.push((
+                    " 📦 Bundle… ".to_string(),
+                    ContextCommand::RefBundle(entry.name.clone()),
+                ));
+                if entry.kind != refs::RefKind::Remote && !entry.is_current {
+                    options.push((" 🗑 Delete ".to_string(), ContextCommand::RefDelete(entry)));
+                }
+                if options.is_empty() {
+                    return;
+                }
+            }
+            Tab::Log => match self.log_ui.subtab {
+                LogSubTab::History => {
+                    if self.selected_history_entry().is_none() {
+                        return;
+                    }
+
+                    options.push((" 📋 Copy SHA ".to_string(), ContextCommand::LogCopySha));
+                    options.push((
+                        " 📋 Copy Subject ".to_string(),
+                        ContextCommand::LogCopySubject,
+                    ));
+                    let pick_label = if self.log_ui.marked.is_empty() {
+                        " 🍒 Cherry-pick ".to_string()
+                    } else {
+                        format!(" 🍒 Cherry-pick {} Marked ", self.log_ui.marked.len())
+                    };
+                    options.push((pick_label, ContextCommand::LogCherryPick));
+                    options.push((" ↶ Revert ".to_string(), ContextCommand::LogRevert(false)));
+                    options.push((
+                        " ↶ Revert Without Committing ".to_string(),
+                        ContextCommand::LogRevert(true),
+                    ));
+                    options.push((" ⟲ Reset To Here… ".to_string(), ContextCommand::LogReset));
+                    options.push((
+                        " ⎇ Checkout Commit ".to_string(),
+                        ContextCommand::LogCheckout,
+                    ));
+                    options.push((
+                        " 🌿 New Branch Here… ".to_string(),
+                        ContextCommand::LogNewBranch,
+                    ));
+                    options.push((" 🏷 Tag Commit… ".to_string(), ContextCommand::LogNewTag));
+                    if multiplexer::Multiplexer::detect().is_some() {
+                        options.push((
+                            " 🪟 Open Diff In Pane ".to_string(),
+                            ContextCommand::OpenDiffInPane,
+                        ));
+                    }
+                    self.push_external_actions(&mut options, actions::ActionContext::Commit);
+                }
+                LogSubTab::Reflog => {
+                    if self.selected_reflog_entry().is_none() {
+                        return;
+                    }
+
+                    options.push((" 📋 Copy SHA ".to_string(), ContextCommand::LogCopySha));
+                    options.push((
+                        " 📋 Copy Subject ".to_string(),
+                        ContextCommand::LogCopySubject,
+                    ));
+                    options.push((
+                        " ⎇ Checkout Commit ".to_string(),
+                        ContextCommand::LogCheckout,
+                    ));
+                    if multiplexer::Multiplexer::detect().is_some() {
+                        options.push((
+                            " 🪟 Open Diff In Pane ".to_string(),
+                            ContextCommand::OpenDiffInPane,
+                        ));
+                    }
+                    self.push_external_actions(&mut options, actions::ActionContext::Commit);
+                }
+                LogSubTab::Stash => {
+                    let Some(_entry) = self.selected_stash_entry() else {
+                        return;
+                    };
+                    options.push((" 📋 Copy Selector ".to_string(), ContextCommand::LogCopySha));
+                    options.push((
+                        " 📋 Copy Subject ".to_string(),
+                        ContextCommand::LogCopySubject,
+                    ));
+                }
+                LogSubTab::Commands => {
+                    let Some(entry) = self
+                        .log_ui
+                        .command_state
+                        .selected()
+                        .and_then(|i| self.git_log.get(i))
+                    else {
+                        return;
+                    };
+                    options.push((
+                        " 📋 Copy Command ".to_string(),
+                        ContextCommand::LogCopyCommand,
+                    ));
+                    let _ = entry;
+                }
+            },
+            Tab::Terminal => return, // No context menu for terminal
+        }
+
+        self.context_menu = Some(ContextMenu {
+            x: col,
+            y: row,
+            selected: 0,
+            options,
+        });
+    }
+
+    fn execute_menu_action(&mut self, action_idx: usize) {
+        if let Some(menu) = &self.context_menu
+            && let Some((_, action)) = menu.options.get(action_idx)
+        {
+            match action {
+                ContextCommand::CopyPath => {
+                    if let Some(file) = self.selected_file() {
+                        self.request_copy_to_clipboard(file.path.to_string_lossy().to_string());
+                    }
+                }
+                ContextCommand::CopyRelPath => {
+                    if let Some(file) = self.selected_file() {
+                        let rel = file
+                            .path
+                            .strip_prefix(&self.current_path)
+                            .ok()
+                            .map(|p| p.to_string_lossy().to_string())
+                            .or_else(|| {
+                                file.path
+                                    .file_name()
+                                    .map(|s| s.to_string_lossy().to_string())
+                            })
+                            .unwrap_or_else(|| file.path.to_string_lossy().to_string());
+                        self.request_copy_to_clipboard(rel);
+                    }
+                }
+                ContextCommand::CopyFileUri => {
+                    if let Some(file) = self.selected_file() {
+                        self.request_copy_to_clipboard(clipboard::file_uri(&file.path));
+                    }
+                }
+                ContextCommand::CopyContents => {
+                    if let Some(path) = self.selected_file().map(|f| f.path.clone()) {
+                        self.copy_file_contents(&path);
+                    }
+                }
+                ContextCommand::AddBookmark => {
+                    let target = if let Some(file) = self.selected_file() {
+                        if file.is_dir {
+                            file.path.clone()
+                        } else {
+                            self.current_path.clone()
+                        }
+                    } else {
+                        self.current_path.clone()
+                    };
+                    let name = target
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or("Root".to_string());
+                    if !self.bookmarks.iter().any(|b| b.path == target) {
+                        let mut bookmark = bookmarks::Bookmark::new(name, target.clone());
+                        bookmark.stored = bookmarks::contract_home(&target);
+                        self.bookmarks.push(bookmark);
+                        self.save_persisted_bookmarks();
+                    }
+                }
+                ContextCommand::RemoveBookmark => {
+                    let target = if let Some(file) = self.selected_file() {
+                        if file.is_dir {
+                            file.path.clone()
+                        } else {
+                            self.current_path.clone()
+                        }
+                    } else {
+                        self.current_path.clone()
+                    };
+                    self.bookmarks.retain(|b| b.path != target);
+                    self.save_persisted_bookmarks();
+                }
+                ContextCommand::ManageBookmarks => self.open_bookmarks_manager(),
+                ContextCommand::NewEntry => self.open_explorer_create(),
+                ContextCommand::Rename => self.open_explorer_rename(),
+                ContextCommand::Delete => self.show_delete_confirm(),
+                ContextCommand::GitStage => self.handle_git_footer(GitFooterAction::Stage),
+                ContextCommand::GitUnstage => self.handle_git_footer(GitFooterAction::Unstage),
+                ContextCommand::GitToggleStage => self.toggle_stage_for_selection(),
+                ContextCommand::GitDiscard => self.handle_git_footer(GitFooterAction::Discard),
+                ContextCommand::GitStageAll => self.stage_all_visible(),
+                ContextCommand::GitUnstageAll => self.unstage_all_visible(),
+                ContextCommand::GitOpenInExplorer => self.open_selected_git_path_in_explorer(),
+                ContextCommand::GitCopyPath => self.copy_selected_git_path(true),
+                ContextCommand::GitCopyRelPath => self.copy_selected_git_path(false),
+                ContextCommand::GitCopyFileUri => self.copy_selected_git_uris(),
+                ContextCommand::GitCopyContents => {
+                    if let (Some(root), Some(entry)) =
+                        (self.git.repo_root.clone(), self.git.selected_tree_entry())
+                    {
+                        let path = root.join(&entry.path);
+                        self.copy_file_contents(&path);
+                    }
+                }
+                ContextCommand::GitCopyDiff => self.copy_selected_git_diff(),
+                ContextCommand::Blame => self.open_blame(),
+                ContextCommand::GitAddToGitignore => self.add_selected_to_gitignore(),
+                ContextCommand::SubmoduleEnter => self.enter_selected_submodule(),
+                ContextCommand::Submodule(op) => self.run_submodule_op(*op),
+                ContextCommand::OpenFileInPane => self.open_in_pane(false),
+                ContextCommand::OpenDiffInPane => self.open_in_pane(true),
+                ContextCommand::LogCopySha => {
+                    if let Some(hash) = self.selected_log_hash() {
+                        self.request_copy_to_clipboard(hash);
+                    }
+                }
+                ContextCommand::LogCopySubject => {
+                    if let Some(s) = self.selected_log_subject() {
+                        self.request_copy_to_clipboard(s);
+                    }
+                }
+                ContextCommand::LogCherryPick => self.confirm_cherry_pick(),
+                ContextCommand::LogRevert(no_commit) => self.confirm_revert_commit(*no_commit),
+                ContextCommand::LogNewBranch => self.new_branch_at_selected_commit(),
+                ContextCommand::LogNewTag => self.tag_selected_commit(),
+                ContextCommand::LogReset => self.open_reset_picker(),
+                ContextCommand::LogCheckout => self.confirm_checkout_commit(),
+                ContextCommand::LogCopyCommand => {
+                    if let Some(s) = self.selected_log_command() {
+                        self.request_copy_to_clipboard(s);
+                    }
+                }
+                ContextCommand::External(idx) => {
+                    let idx = *idx;
+                    self.run_external_action(idx);
+                }
+                ContextCommand::Navigate(path) => {
+                    let path = path.clone();
+                    self.navigate_to(path);
+                }
+                ContextCommand::RefCheckout(entry) => {
+                    let entry = entry.clone();
+                    self.checkout_ref(entry);
+                }
+                ContextCommand::RefDelete(entry) => {
+                    let entry = entry.clone();
+                    self.delete_ref(entry);
+                }
+                ContextCommand::RefCompare(name) => {
+                    self.log_ui.history_ref = Some(format!("HEAD..{}", name));
+                    self.log_ui.set_subtab(LogSubTab::History);
+                    self.refresh_log_data();
+                }
+                ContextCommand::RefBundle(name) => {
+                    let name = name.clone();
+                    self.start_create_bundle(&name);
+                }
+                ContextCommand::BundleFetch => self.start_bundle_import(BundleInputKind::Fetch),
+                ContextCommand::BundlePull => self.start_bundle_import(BundleInputKind::Pull),
+            }
+        }
+        self.context_menu = None;
+    }
+
+    fn load_message_history(&mut self) {
+        let Some(path) = self.dirs.state_file("commit_messages.json") else {
+            return;
+        };
+        match commit::MessageHistory::load(&path) {
+            Ok(history) => self.commit_messages = history,
+            Err(e) => self.set_status(format!("Commit message history: {}", e)),
+        }
+    }
+
+    /// Whether `feature` is enabled; otherwise explain why in the status bar.
+    fn feature_allowed(&mut self, enabled: bool, what: &str) -> bool {
+        if !enabled {
+            self.set_status(format!("{} is disabled in this environment", what));
+        }
+        enabled
+    }
+
+    /// Hooks to run, or none when shell-outs are disabled.
+    fn active_hooks(&self) -> hooks::Hooks {
+        if self.features.shell {
+            self.hooks.clone()
+        } else {
+            hooks::Hooks::default()
+        }
+    }
+
+    /// Apply `config.toml` on top of the state remembered in `ui.json`.
+    fn load_config(&mut self) -> Result<(), String> {
+        let Some(path) = self.dirs.config_file("config.toml") else {
+            return Ok(());
+        };
+        self.config_mtime = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let data = match fs::read_to_string(&path) {
+            Ok(d) => d,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(format!("config.toml: {}", e)),
+        };
+        let cfg = config::parse(&data).map_err(|errors| {
+            let more = match errors.len() {
+                1 => String::new(),
+                n => format!(" (+{} more)", n - 1),
+            };
+            format!("config.toml {}{}", errors[0], more)
+        })?;
+
+        self.syntax_colors = cfg.syntax;
+        self.set_theme(cfg.ui.theme.unwrap_or(self.theme));
+        if let Some(wrap) = cfg.ui.wrap_diff {
+            self.wrap_diff = wrap;
+        }
+        if let Some(syntax) = cfg.ui.syntax_highlight {
+            self.syntax_highlight = syntax;
+        }
+        if let Some(numbers) = cfg.ui.diff_line_numbers {
+            self.diff_line_numbers = numbers;
+        }
+        if let Some(hexdump) = cfg.ui.binary_hexdump {
+            self.binary_hexdump = hexdump;
+        }
+        if let Some(hidden) = cfg.ui.show_hidden {
+            self.show_hidden = hidden;
+        }
+        if let Some(auto) = cfg.behavior.auto_refresh {
+            self.auto_refresh = auto;
+        }
+        self.review_before_commit = cfg.behavior.review_before_commit;
+        self.count_prefix = cfg.behavior.count_prefix;
+        self.commit_history_limit = cfg
+            .behavior
+            .commit_history
+            .unwrap_or(commit::DEFAULT_MESSAGE_HISTORY);
+        git_ops::set_timeouts(
+            cfg.timeouts
+                .local
+                .unwrap_or(git_ops::DEFAULT_LOCAL_TIMEOUT_SECS),
+            cfg.timeouts
+                .network
+                .unwrap_or(git_ops::DEFAULT_NETWORK_TIMEOUT_SECS),
+        );
+
+        let bar = &mut self.top_bar;
+        bar.clock = cfg.top_bar.clock.unwrap_or(bar.clock);
+        bar.branch = cfg.top_bar.branch.unwrap_or(bar.branch);
+        bar.ahead_behind = cfg.top_bar.ahead_behind.unwrap_or(bar.ahead_behind);
+        bar.operation = cfg.top_bar.operation.unwrap_or(bar.operation);
+
+        self.pane_templates = cfg.panes;
+        self.hooks = cfg.hooks;
+        self.external_actions = cfg.actions;
+        self.keymap = cfg.keymap;
+        self.ai_config = cfg.ai;
+        self.forge_config = cfg.forge;
+        self.privacy_config = cfg.privacy;
+        self.clipboard_config = cfg.clipboard;
+        self.confirm_settings = cfg.confirm;
+        self.features = self.features.restrict(cfg.features);
+        if !self.features.terminal && self.current_tab == Tab::Terminal {
+            self.current_tab = Tab::Explorer;
+        }
+        Ok(())
+    }
+
+    fn reload_config(&mut self) {
+        self.keymap = keymap::Keymap::default();
+        self.ai_config = config::AiConfig::default();
+        self.forge_config = config::ForgeConfig::default();
+        self.privacy_config = config::PrivacyConfig::default();
+        self.clipboard_config = config::ClipboardConfig::default();
+        self.confirm_settings = confirm::ConfirmSettings::default();
+        self.review_before_commit = false;
+        self.count_prefix = false;
+        self.commit_history_limit = commit::DEFAULT_MESSAGE_HISTORY;
+        self.syntax_colors.clear();
+        self.set_theme(self.theme);
+        self.features = config::Features::from_env(self.getenv);
+        git_ops::set_timeouts(
+            git_ops::DEFAULT_LOCAL_TIMEOUT_SECS,
+            git_ops::DEFAULT_NETWORK_TIMEOUT_SECS,
+        );
+        self.pane_templates = multiplexer::PaneTemplates::default();
+        self.hooks = hooks::Hooks::default();
+        self.external_actions.clear();
+        match self.load_config() {
+            Ok(()) => {
+                self.load_files();
+                self.request_git_diff_update();
+                self.set_status("Config reloaded");
+            }
+            Err(e) => self.set_status(e),
+        }
+    }
+
+    /// Hide everything behind the lock screen, asking for the passphrase from
+    /// `[privacy] passphrase_env` when it is set.
+    fn show_lock_screen(&mut self) {
+        let passphrase = self
+            .privacy_config
+            .passphrase_env
+            .as_deref()
+            .and_then(self.getenv);
+        self.lock_screen = Some(lock_screen::LockScreen::new(passphrase));
+    }
+
+    fn lock_screen_when_idle(&mut self) {
+        let Some(minutes) = self.privacy_config.lock_after.filter(|m| *m > 0) else {
+            return;
+        };
+        if self.lock_screen.is_none()
+            && self.last_input.elapsed() >= Duration::from_secs(minutes * 60)
+        {
+            self.show_lock_screen();
+        }
+    }
+
+    /// Reload the config when `config.toml` changes on disk.
+    fn check_config_changed(&mut self) {
+        if self.last_config_check.elapsed() < Duration::from_secs(2) {
+            return;
+        }
+        self.last_config_check = Instant::now();
+        let Some(path) = self.dirs.config_file("config.toml") else {
+            return;
+        };
+        let mtime = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        if mtime != self.config_mtime {
+            self.reload_config();
+        }
+    }
+
+    fn handle_control_request(&mut self, req: control::ControlRequest) {
+        match req {
+            control::ControlRequest::FocusFile { path, line } => self.focus_git_file(&path, line),
+            control::ControlRequest::ShowCommit { rev } => self.show_log_commit(&rev),
+            control::ControlRequest::Refresh => {
+                self.refresh_git_state();
+                if self.current_tab == Tab::Log {
+                    self.refresh_log_data();
+                }
+                self.set_status("Refreshed");
+            }
+        }
+    }
+
+    /// Open the Git tab with `path` selected, scrolling the diff near `line`.
+    fn focus_git_file(&mut self, path: &str, line: Option<u32>) {
+        self.current_tab = Tab::Git;
+        self.git.refresh(&self.current_path);
+        self.update_git_operation();
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        // Accept repo-relative, cwd-relative or absolute paths
+        let candidate = Path::new(path);
+        let abs = if candidate.is_absolute() {
+            candidate.to_path_buf()
+        } else if repo_root.join(candidate).exists() {
+            repo_root.join(candidate)
+        } else {
+            self.current_path.join(candidate)
+        };
+        let rel = abs
+            .strip_prefix(&repo_root)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| path.to_string());
+
+        if !self.git.select_by_path(&rel) {
+            self.git.expand_all();
+            if !self.git.select_by_path(&rel) {
+                self.set_status(format!("No changes in {}", rel));
+                return;
+            }
+        }
+
+        self.pending_diff_line = line;
+        self.request_git_diff_update();
+    }
+
+    /// Scroll the Git diff so the hunk row for new-side `line` is near the top.
+    /// Show more context around the hunks of the selected file, or go back to
+    /// git's default, keeping `hunk` (or the one on screen) in view.
+    fn expand_diff_context(&mut self, hunk: Option<usize>, grow: bool) {
+        let Some(entry) = self.git.selected_tree_entry() else {
+            return;
+        };
+        if entry.is_untracked {
+            self.set_status("Untracked files are shown in full");
+            return;
+        }
+        let path = entry.path.clone();
+        let current = self
+            .git
+            .diff_context
+            .get(&path)
+            .copied()
+            .unwrap_or(git::DIFF_CONTEXT);
+        let next = if grow {
+            current + git::DIFF_CONTEXT_STEP
+        } else {
+            git::DIFF_CONTEXT
+        };
+        if next == current {
+            self.set_status("Context is already at the default");
+            return;
+        }
+        if next == git::DIFF_CONTEXT {
+            self.git.diff_context.remove(&path);
+        } else {
+            self.git.diff_context.insert(path, next);
+        }
+
+        let line = hunk
+            .or_else(|| self.git.hunk_at_scroll())
+            .and_then(|idx| self.git.hunk_first_change(idx));
+        self.request_git_diff_update();
+        self.pending_diff_line = line;
+        self.set_status(format!("Context: {} lines (+ for more, - to reset)", next));
+    }
+
+    fn scroll_git_diff_to_line(&mut self, line: u32) {
+        let rows = self.diff_line_rows(Tab::Git);
+        let mut tracker = git::LineNumberTracker::default();
+        let mut best: Option<usize> = None;
+        for (idx, l) in self.git.diff_lines.iter().enumerate() {
+            let (_, new) = tracker.advance(l);
+            if let Some(n) = new
+                && n <= line
+            {
+                best = Some(idx);
+            }
+        }
+        if let Some(row) = best.and_then(|idx| rows.get(idx)) {
+            self.git.diff_scroll_y = row.saturating_sub(3).min(u16::MAX as usize) as u16;
+        }
+    }
+
+    /// Blame the file selected in the Git tree or the Explorer.
+    fn open_blame(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let path = match self.current_tab {
+            Tab::Git => self.git.selected_tree_entry().map(|e| e.path.clone()),
+            Tab::Explorer => self
+                .selected_file()
+                .filter(|f| !f.is_dir)
+                .and_then(|f| f.path.strip_prefix(&repo_root).ok())
+                .map(|p| p.to_string_lossy().to_string()),
+            _ => None,
+        };
+        let Some(path) = path else {
+            self.set_status("Select a file to blame");
+            return;
+        };
+
+        let lines = match git_ops::blame_file(&repo_root, &path) {
+            Ok(lines) => lines,
+            Err(e) => {
+                self.set_status(e);
+                return;
+            }
+        };
+        let first_line = lines.first().map(|l| l.content.as_str());
+        let ext = self.syntax_for(&path, first_line);
+        let highlighted = match ext.and_then(highlight::new_highlighter) {
+            Some(mut hl) if self.syntax_highlight => {
+                let text: Vec<&str> = lines.iter().map(|l| l.content.as_str()).collect();
+                hl.highlight_lines(&text.join("\n"), self.palette.bg)
+            }
+            _ => Vec::new(),
+        };
+        self.blame.show(path, lines, highlighted);
+    }
+
+    /// List the repository's files in the background and start filtering them.
+    fn open_file_finder(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(git_ops::list_files(&repo_root));
+        });
+        self.file_finder = Some(FileFinder {
+            rx: Some(rx),
+            ..FileFinder::default()
+        });
+    }
+
+    fn poll_file_finder(&mut self) {
+        let Some(finder) = self.file_finder.as_mut() else {
+            return;
+        };
+        let result = match finder.rx.as_ref().map(|rx| rx.try_recv()) {
+            Some(Ok(result)) => result,
+            Some(Err(mpsc::TryRecvError::Disconnected)) => Err("listing stopped".to_string()),
+            _ => return,
+        };
+        finder.rx = None;
+        match result {
+            Ok(files) => {
+                finder.files = files;
+                finder.update_filtered();
+            }
+            Err(e) => {
+                self.file_finder = None;
+                self.set_status(format!("Find file: {}", e));
+            }
+        }
+    }
+
+    fn file_finder_key(&mut self, key: &KeyEvent) {
+        let Some(finder) = self.file_finder.as_mut() else {
+            return;
+        };
+        if let Some(nav) = self.nav_keys.key(key, false) {
+            let last = finder.filtered.len().saturating_sub(1) as i32;
+            finder.selected = (finder.selected as i32 + nav.delta()).clamp(0, last) as usize;
+            return;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => {
+                let idx = finder.selected;
+                self.update(Msg::Action(AppAction::PickFoundFile(idx)));
+            }
+            KeyCode::Char('e') if ctrl => self.open_found_file(true),
+            KeyCode::Backspace => {
+                finder.query.pop();
+                finder.update_filtered();
+            }
+            KeyCode::Char(ch) if !ctrl => {
+                finder.query.push(ch);
+                finder.update_filtered();
+            }
+            _ => {}
+        }
+    }
+
+    /// Open the finder's pick: its diff in the Git tab when it has changes and
+    /// `explorer` is false, otherwise the file selected in the Explorer.
+    fn open_found_file(&mut self, explorer: bool) {
+        let Some(path) = self
+            .file_finder
+            .take()
+            .and_then(|finder| finder.selected_path().map(str::to_string))
+        else {
+            return;
+        };
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        if !explorer && self.git.entries.iter().any(|e| e.path == path) {
+            self.focus_git_file(&path, None);
+        } else {
+            if path.split('/').any(|part| part.starts_with('.')) {
+                self.show_hidden = true;
+            }
+            self.reveal_in_explorer(&repo_root.join(&path));
+        }
+    }
+
+    /// Extension to highlight `path` (relative to the repository, or absolute) as: the
+    /// language picked for it this session, else what its name or `#!` line implies.
+    pub(crate) fn syntax_for<'a>(
+        &self,
+        path: &'a str,
+        first_line: Option<&str>,
+    ) -> Option<&'a str> {
+        let full = match self.git.repo_root.as_deref() {
+            Some(root) => root.join(path),
+            None => PathBuf::from(path),
+        };
+        if let Some(picked) = self.syntax_overrides.get(&full) {
+            return *picked;
+        }
+        highlight::detect_syntax(path, first_line)
+    }
+
+    /// Pick the language of the file selected in this tab.
+    fn open_syntax_picker(&mut self) {
+        let repo_root = self.git.repo_root.clone().unwrap_or_default();
+        let path = match self.current_tab {
+            Tab::Explorer => self
+                .selected_file()
+                .filter(|f| !f.is_dir)
+                .map(|f| f.path.clone()),
+            Tab::Git => self
+                .git
+                .selected_tree_entry()
+                .map(|e| repo_root.join(&e.path)),
+            Tab::Log => self
+                .log_ui
+                .files_state
+                .selected()
+                .and_then(|sel| self.log_ui.files.get(sel))
+                .map(|f| repo_root.join(&f.path)),
+            Tab::Terminal => None,
+        };
+        let Some(path) = path else {
+            self.set_status("Select a file to highlight");
+            return;
+        };
+        let selected = match self.syntax_overrides.get(&path) {
+            None => 0,
+            Some(None) => 1,
+            Some(Some(ext)) => highlight::LANGUAGES
+                .iter()
+                .position(|(_, e)| e == ext)
+                .map_or(0, |i| i + 2),
+        };
+        self.syntax_picker = Some(SyntaxPicker { path, selected });
+    }
+
+    fn syntax_picker_key(&mut self, key: &KeyEvent) {
+        let Some(picker) = self.syntax_picker.as_mut() else {
+            return;
+        };
+        if let Some(nav) = self.nav_keys.key(key, true) {
+            let last = highlight::LANGUAGES.len() as i32 + 1;
+            picker.selected = (picker.selected as i32 + nav.delta()).clamp(0, last) as usize;
+            return;
+        }
+        if key.code == KeyCode::Enter {
+            let selected = picker.selected;
+            self.update(Msg::Action(AppAction::PickSyntax(selected)));
+        }
+    }
+
+    /// Highlight the picker's file as row `idx` until lzgit exits.
+    fn pick_syntax(&mut self, idx: usize) {
+        let Some(picker) = self.syntax_picker.take() else {
+            return;
+        };
+        let name = picker
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let label = match idx {
+            0 => {
+                self.syntax_overrides.remove(&picker.path);
+                "detected".to_string()
+            }
+            1 => {
+                self.syntax_overrides.insert(picker.path, None);
+                "plain text".to_string()
+            }
+            _ => {
+                let Some((label, ext)) = highlight::LANGUAGES.get(idx - 2) else {
+                    return;
+                };
+                self.syntax_overrides.insert(picker.path, Some(ext));
+                label.to_string()
+            }
+        };
+        self.git.full_file_highlighted = None;
+        self.highlight_cache = None;
+        self.git_diff_cache.invalidate();
+        self.log_diff_cache.invalidate();
+        self.set_status(format!("Highlighting {} as {}", name, label));
+    }
+
+    fn blame_key(&mut self, key: &KeyEvent) {
+        if let Some(nav) = self.nav_keys.key(key, true) {
+            self.blame.move_selection(nav.delta());
+            return;
+        }
+        match key.code {
+            KeyCode::Char('q') => self.blame.close(),
+            KeyCode::Enter => {
+                let Some(line) = self.blame.selected_line() else {
+                    return;
+                };
+                if line.is_uncommitted() {
+                    self.set_status("Not committed yet");
+                    return;
+                }
+                let hash = line.hash.clone();
+                self.blame.close();
+                self.show_log_commit(&hash);
+            }
+            KeyCode::Char('y') => {
+                if let Some(hash) = self.blame.selected_line().map(|l| l.hash.clone()) {
+                    self.request_copy_to_clipboard(hash);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Environment passed to hook scripts, on top of the operation and repo.
+    fn hook_env(&self, extra: &[(&str, String)]) -> Vec<(String, String)> {
+        let mut env = vec![("LZGIT_BRANCH".to_string(), self.git.branch.clone())];
+        env.extend(extra.iter().map(|(k, v)| (k.to_string(), v.clone())));
+        env
+    }
+
+    /// Pane entries for the file context menu, when running inside tmux/zellij.
+    fn push_pane_options(&self, options: &mut Vec<(String, ContextCommand)>, with_diff: bool) {
+        if multiplexer::Multiplexer::detect().is_none() {
+            return;
+        }
+        options.push((
+            " 🪟 Open In Pane ".to_string(),
+            ContextCommand::OpenFileInPane,
+        ));
+        if with_diff {
+            options.push((
+                " 🪟 Open Diff In Pane ".to_string(),
+                ContextCommand::OpenDiffInPane,
+            ));
+        }
+    }
+
+    /// Open the selected file in `$EDITOR`, or its diff in a pager, in a new
+    /// tmux/zellij pane so the TUI keeps running.
+    fn open_in_pane(&mut self, diff: bool) {
+        if !self.feature_allowed(self.features.shell, "Running commands") {
+            return;
+        }
+        let Some(mux) = multiplexer::Multiplexer::detect() else {
+            self.set_status("Not running inside tmux or zellij");
+            return;
+        };
+        let repo_root = self.git.repo_root.clone();
+        let cwd = repo_root
+            .clone()
+            .unwrap_or_else(|| self.current_path.clone());
+
+        let command = match self.current_tab {
+            Tab::Explorer | Tab::Terminal => {
+                let Some(file) = self.selected_file().filter(|f| !f.is_dir) else {
+                    self.set_status("No file selected");
+                    return;
+                };
+                let abs = file.path.clone();
+                if !diff {
+                    Some(multiplexer::editor_command(&abs.to_string_lossy()))
+                } else if repo_root.is_some() {
+                    let path = abs.to_string_lossy().to_string();
+                    Some(multiplexer::pager_command(&["diff", "HEAD", "--", &path]))
+                } else {
+                    None
+                }
+            }
+            Tab::Git => {
+                let Some(entry) = self.git.selected_tree_entry().cloned() else {
+                    self.set_status("No file selected");
+                    return;
+                };
+                if !diff {
+                    Some(multiplexer::editor_command(&entry.path))
+                } else if entry.is_untracked {
+                    Some(multiplexer::pager_command(&[
+                        "diff",
+                        "--no-index",
+                        "--",
+                        "/dev/null",
+                        &entry.path,
+                    ]))
+                } else if entry.x != ' ' && entry.x != '?' {
+                    Some(multiplexer::pager_command(&[
+                        "diff",
+                        "--cached",
+                        "--",
+                        &entry.path,
+                    ]))
+                } else {
+                    Some(multiplexer::pager_command(&["diff", "--", &entry.path]))
+                }
+            }
+            Tab::Log => {
+                let file = self
+                    .log_ui
+                    .files_state
+                    .selected()
+                    .and_then(|i| self.log_ui.files.get(i))
+                    .filter(|_| self.log_ui.focus == LogPaneFocus::Files)
+                    .map(|f| f.path.clone());
+                match (diff, self.selected_log_hash(), file) {
+                    (true, Some(hash), Some(path)) if self.log_ui.subtab != LogSubTab::Commands => {
+                        Some(multiplexer::pager_command(&["show", &hash, "--", &path]))
+                    }
+                    (true, Some(hash), None) if self.log_ui.subtab != LogSubTab::Commands => {
+                        Some(multiplexer::pager_command(&["show", &hash]))
+                    }
+                    (false, _, Some(path)) => Some(multiplexer::editor_command(&path)),
+                    _ => None,
+                }
+            }
+        };
+
+        let Some(command) = command else {
+            self.set_status("Nothing to open");
+            return;
+        };
+        match multiplexer::open_pane(mux, &self.pane_templates, &cwd, &command) {
+            Ok(()) => self.set_status(format!("Opened in {} pane", mux.name())),
+            Err(e) => self.set_status(format!("{}: {}", mux.name(), e)),
+        }
+    }
+
+    fn push_external_actions(
+        &self,
+        options: &mut Vec<(String, ContextCommand)>,
+        context: actions::ActionContext,
+    ) {
+        for (idx, action) in self.external_actions.iter().enumerate() {
+            if action.context == context {
+                let icon = if action.is_url() { "🔗" } else { "⚙" };
+                options.push((
+                    format!(" {} {} ", icon, action.label),
+                    ContextCommand::External(idx),
+                ));
+            }
+        }
+    }
+
+    /// Template values for an action in `context`, taken from the current selection.
+    fn external_action_vars(
+        &self,
+        context: actions::ActionContext,
+    ) -> Option<Vec<(&'static str, String)>> {
+        let repo_root = self.git.repo_root.clone();
+        let repo = repo_root
+            .clone()
+            .unwrap_or_else(|| self.current_path.clone());
+        let mut vars = vec![("repo", repo.to_string_lossy().to_string())];
+
+        match context {
+            actions::ActionContext::Commit => {
+                let (hash, subject) = match self.log_ui.subtab {
+                    LogSubTab::History => self
+                        .selected_history_entry()
+                        .map(|e| (e.hash.clone(), e.subject.clone()))?,
+                    LogSubTab::Reflog => self
+                        .selected_reflog_entry()
+                        .map(|e| (e.hash.clone(), e.subject.clone()))?,
+                    _ => return None,
+                };
+                vars.push(("short", hash.chars().take(7).collect()));
+                vars.push(("sha", hash));
+                vars.push(("subject", subject));
+            }
+            actions::ActionContext::File => {
+                let abs = if self.current_tab == Tab::Git {
+                    repo.join(&self.git.selected_tree_entry()?.path)
+                } else {
+                    self.selected_file().filter(|f| !f.is_dir)?.path.clone()
+                };
+                let rel = repo_root
+                    .as_ref()
+                    .and_then(|root| abs.strip_prefix(root).ok())
+                    .unwrap_or(abs.as_path())
+                    .to_string_lossy()
+                    .to_string();
+                vars.push(("path", rel));
+                vars.push(("abs_path", abs.to_string_lossy().to_string()));
+            }
+            actions::ActionContext::Branch => {
+                let branch = if self.branch_ui.open {
+                    self.branch_ui.selected_branch()?.name
+                } else {
+                    self.git.branch.clone()
+                };
+                if branch.is_empty() {
+                    return None;
+                }
+                vars.push(("branch", branch));
+            }
+        }
+        Some(vars)
+    }
+
+    fn run_external_action(&mut self, idx: usize) {
+        let Some(action) = self.external_actions.get(idx).cloned() else {
+            return;
+        };
+        let allowed = if action.is_url() {
+            self.feature_allowed(self.features.network, "Opening URLs")
+        } else {
+            self.feature_allowed(self.features.shell, "Running commands")
+        };
+        if !allowed {
+            return;
+        }
+        let Some(vars) = self.external_action_vars(action.context) else {
+            self.set_status(format!("{}: nothing selected", action.label));
+            return;
+        };
+        let vars: Vec<(&str, &str)> = vars.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        let expanded = action.expand(&vars);
+        let cwd = self
+            .git
+            .repo_root
+            .clone()
+            .unwrap_or_else(|| self.current_path.clone());
+
+        let cmd = format!("action {}", action.label);
+        self.start_git_job(cmd, false, false, move || {
+            actions::run(&action, &expanded, &cwd)
+        });
+    }
+
+    fn selected_git_paths(&self) -> Vec<String> {
+        self.git.selected_tree_paths()
+    }
+
+    fn toggle_explorer_zoom(&mut self) {
+        self.explorer_zoom = match self.explorer_zoom {
+            ExplorerZoom::ThreeColumn => ExplorerZoom::TwoColumn,
+            ExplorerZoom::TwoColumn => ExplorerZoom::PreviewOnly,
+            ExplorerZoom::PreviewOnly => ExplorerZoom::ThreeColumn,
+        };
+    }
+
+    /// Clicking inside a pane focuses it.
+    fn focus_pane_at(&mut self, row: u16, col: u16) {
+        let area = self.content_area;
+        if row < area.y || row >= area.y + area.height {
+            return;
+        }
+        match self.current_tab {
+            Tab::Explorer if col >= self.explorer_preview_x => {
+                self.explorer_focus = PaneFocus::Detail;
+            }
+            Tab::Explorer if col >= self.explorer_current_x => {
+                self.explorer_focus = PaneFocus::List;
+            }
+            Tab::Git => {
+                self.git_focus = if col >= self.git_diff_x {
+                    PaneFocus::Detail
+                } else {
+                    PaneFocus::List
+                };
+            }
+            _ => {}
+        }
+    }
+
+    /// Scroll whichever view the Git tab's right pane is showing.
+    fn scroll_git_detail(&mut self, nav: ListNav) {
+        if let Some(summary) = self.git.dir_summary() {
+            let last = summary.files.len().saturating_sub(1) as i32;
+            let row = self.git.dir_summary_selected as i32 + nav.delta();
+            self.git.dir_summary_selected = row.clamp(0, last) as usize;
+            return;
+        }
+        let scroll = if self
+            .git
+            .selected_tree_entry()
+            .is_some_and(|e| e.is_conflict)
+        {
+            &mut self.conflict_ui.scroll_y
+        } else if self.git.show_full_file {
+            &mut self.git.full_file_scroll_y
+        } else {
+            &mut self.git.diff_scroll_y
+        };
+        *scroll = nav.scroll_u16(*scroll);
+    }
+
+    /// Select a file of the directory summary in the tree and show its diff.
+    fn open_dir_summary_file(&mut self, idx: usize) {
+        let Some(summary) = self.git.dir_summary() else {
+            return;
+        };
+        let Some(file) = summary.files.get(idx) else {
+            return;
+        };
+        if self.git.reveal_file(&file.path, summary.section) {
+            self.git_focus = PaneFocus::List;
+            self.request_git_diff_update();
+        }
+    }
+
+    fn move_explorer_selection(&mut self, delta: i32) {
+        if self.files.is_empty() {
+            return;
+        }
+        let cur = self.selected_index().unwrap_or(0) as i32;
+        let next = (cur + delta).clamp(0, self.files.len() as i32 - 1);
+        if next != cur {
+            self.list_state.select(Some(next as usize));
+            self.update_preview();
+            self.preview_scroll = 0;
+        }
+    }
+
+    /// `1`-`3` switch to the Git, Log and Explorer tabs.
+    fn switch_tab_by_number(&mut self, c: char) {
+        let tab = match c {
+            '1' => Tab::Git,
+            '2' => Tab::Log,
+            '3' => Tab::Explorer,
+            _ => return,
+        };
+        self.dispatch(AppAction::SwitchTab(tab), 0, 0, KeyModifiers::NONE);
+    }
+
+    /// Digits start a count prefix only where the next key reaches a list or pane.
+    fn accepts_count(&self) -> bool {
+        self.count_prefix
+            && matches!(self.current_tab, Tab::Explorer | Tab::Git | Tab::Log)
+            && self.operation_popup.is_none()
+            && self.context_menu.is_none()
+            && !self.theme_picker.open
+            && !self.command_palette.open
+            && !self.stash_ui.open
+            && !self.bookmarks_ui.open
+            && !self.snapshot_ui.open
+            && !self.tag_ui.open
+            && !self.remote_ui.open
+            && !self.branch_ui.open
+            && !self.author_ui.open
+            && self.update_confirm.is_none()
+            && self.stash_input.is_none()
+            && self.push_input.is_none()
+            && self.new_branch_input.is_none()
+            && self.snapshot_name_input.is_none()
+            && self.export_path_input.is_none()
+            && self.am_path_input.is_none()
+            && self.bundle_input.is_none()
+            && self.branch_input.is_none()
+            && self.init_wizard.is_none()
+            && self.gitignore_picker.is_none()
+            && self.clone_input.is_none()
+            && self.tag_input.is_none()
+            && self.release_wizard.is_none()
+            && self.reset_picker.is_none()
+            && self.pull_picker.is_none()
+            && self.message_picker.is_none()
+            && self.key_help.is_none()
+            && self.syntax_picker.is_none()
+            && self.file_finder.is_none()
+            && self.commit_find.is_none()
+            && self.changelog_input.is_none()
+            && self.changelog_view.is_none()
+            && self.remote_input.is_none()
+            && self.explorer_input.is_none()
+            && self.goto_commit_input.is_none()
+            && !(self.current_tab == Tab::Git && self.commit.open)
+            && !(self.current_tab == Tab::Log
+                && (self.log_ui.filter_edit || self.log_ui.inspect.open))
+    }
+
+    /// A lone `1`-`3` that no movement followed was a tab switch after all, and a
+    /// `[` or `]` that no `f` followed does its own thing.
+    fn expire_pending_keys(&mut self) {
+        let now = Instant::now();
+        if let Some(n @ 1..=3) = self.nav_keys.take_stale_count(now)
+            && let Some(c) = char::from_digit(n, 10)
+        {
+            self.switch_tab_by_number(c);
+        }
+        if let Some(bracket) = self.nav_keys.take_stale_bracket(now) {
+            self.bracket_key(bracket);
+        }
+    }
+
+    /// `[` and `]` on their own: the older or newer revision of the file picked in
+    /// History's file list, otherwise narrow or widen the left pane.
+    fn bracket_key(&mut self, bracket: char) {
+        let newer = bracket == ']';
+        match self.current_tab {
+            Tab::Git => self.adjust_git_left_width(if newer { 2 } else { -2 }),
+            Tab::Log
+                if self.log_ui.subtab == LogSubTab::History
+                    && self.log_ui.detail_mode == LogDetailMode::Files
+                    && self.log_ui.focus != LogPaneFocus::Commits =>
+            {
+                self.step_log_file_revision(if newer { -1 } else { 1 });
+            }
+            Tab::Log => self.adjust_log_left_width(if newer { 2 } else { -2 }),
+            Tab::Explorer | Tab::Terminal => {}
+        }
+    }
+
+    /// Apply a movement to the focused list or pane of the current tab.
+    fn navigate_focused(&mut self, nav: ListNav) {
+        match self.current_tab {
+            Tab::Explorer => match self.explorer_focus {
+                PaneFocus::List => self.move_explorer_selection(nav.delta()),
+                PaneFocus::Detail => {
+                    // Render clamps the offset to the last page
+                    self.preview_scroll_offset = nav.scroll(self.preview_scroll_offset);
+                }
+            },
+            Tab::Git => match self.git_focus {
+                PaneFocus::List => {
+                    let git = &mut self.git;
+                    match nav {
+                        ListNav::Top => {
+                            git.recent_selected = None;
+                            git.tree_goto_first();
+                        }
+                        ListNav::Bottom => {
+                            git.recent_selected = None;
+                            git.tree_goto_last();
+                        }
+                        ListNav::By(n) if n < 0 => (0..-n).for_each(|_| {
+                            if !git.recent_move(false) {
+                                git.tree_move_up();
+                            }
+                        }),
+                        ListNav::By(n) => (0..n).for_each(|_| {
+                            if !git.recent_move(true) {
+                                git.tree_move_down();
+                            }
+                        }),
+                    }
+                    self.request_git_diff_update();
+                }
+                PaneFocus::Detail => self.scroll_git_detail(nav),
+            },
+            Tab::Log => match self.log_ui.focus {
+                LogPaneFocus::Commits => self.move_log_selection(nav.delta()),
+                LogPaneFocus::Files => self.move_log_file_selection(nav.delta()),
+                LogPaneFocus::Diff => {
+                    self.log_ui.diff_scroll_y = nav.scroll_u16(self.log_ui.diff_scroll_y);
+                }
+            },
+            Tab::Terminal => {}
+        }
+    }
+
+    fn adjust_git_left_width(&mut self, delta: i16) {
+        let cur = self.git_left_width as i16;
+        let next = (cur + delta).clamp(32, 90);
+        self.git_left_width = next as u16;
+    }
+
+    fn copy_selected_git_path(&mut self, absolute: bool) {
+        let paths = self.selected_git_paths();
+        let Some(first) = paths.first() else {
+            self.set_status("No selection");
+            return;
+        };
+
+        if absolute {
+            let Some(root) = self.git.repo_root.clone() else {
+                self.set_status("Not a git repository");
+                return;
+            };
+            let p = root.join(first);
+            self.request_copy_to_clipboard(p.to_string_lossy().to_string());
+        } else {
+            self.request_copy_to_clipboard(first.clone());
+        }
+    }
+
+    /// Copy `file://` URIs for the selected paths, one per line as in `text/uri-list`.
+    fn copy_selected_git_uris(&mut self) {
+        let Some(root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let paths = self.selected_git_paths();
+        if paths.is_empty() {
+            self.set_status("No selection");
+            return;
+        }
+        let uris: Vec<String> = paths
+            .iter()
+            .map(|p| clipboard::file_uri(&root.join(p)))
+            .collect();
+        self.request_copy_to_clipboard(uris.join("\n"));
+    }
+
+    /// Copy a text file's contents, refusing binaries and anything over [`MAX_COPY_BYTES`].
+    fn copy_file_contents(&mut self, path: &Path) {
+        match fs::metadata(path) {
+            Ok(meta) if meta.len() > MAX_COPY_BYTES => {
+                self.set_status(format!(
+                    "Too large to copy ({} KiB, limit {} KiB)",
+                    meta.len() / 1024,
+                    MAX_COPY_BYTES / 1024
+                ));
+                return;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                self.set_status(format!("Copy failed: {}", e));
+                return;
+            }
+        }
+        match fs::read(path) {
+            Ok(bytes) if bytes.contains(&0) => self.set_status("Binary file, not copied"),
+            Ok(bytes) => match String::from_utf8(bytes) {
+                Ok(text) => self.request_copy_to_clipboard(text),
+                Err(_) => self.set_status("Not UTF-8 text, not copied"),
+            },
+            Err(e) => self.set_status(format!("Copy failed: {}", e)),
+        }
+    }
+
+    /// Copy the selected file's diff from the section it is listed in.
+    fn copy_selected_git_diff(&mut self) {
+        let Some(root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let Some(item) = self.git.selected_tree_item() else {
+            self.set_status("No selection");
+            return;
+        };
+        let (path, section) = (item.path.clone(), item.section);
+        let result = match section {
+            git::GitSection::Untracked => git_ops::diff_untracked(&root, &path),
+            git::GitSection::Staged => git_ops::diff_path(&root, &path, true, None),
+            git::GitSection::Working | git::GitSection::Conflicts | git::GitSection::Submodules => {
+                git_ops::diff_path(&root, &path, false, None)
+            }
+        };
+        match result {
+            Ok(diff) if diff.trim().is_empty() => self.set_status("No diff for this file"),
+            Ok(diff) if diff.len() as u64 > MAX_COPY_BYTES => self.set_status(format!(
+                "Diff too large to copy ({} KiB)",
+                diff.len() / 1024
+            )),
+            Ok(diff) => self.request_copy_to_clipboard(diff),
+            Err(e) => self.set_status(format!("Copy failed: {}", e)),
+        }
+    }
+
+    fn open_selected_git_path_in_explorer(&mut self) {
+        let paths = self.selected_git_paths();
+        let Some(first) = paths.first() else {
+            self.set_status("No selection");
+            return;
+        };
+        let Some(root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        self.reveal_in_explorer(&root.join(first));
+    }
+
+    /// Show `abs` selected in its folder in the Explorer.
+    fn reveal_in_explorer(&mut self, abs: &Path) {
+        let Some(parent) = abs.parent() else {
+            return;
+        };
+
+        self.current_tab = Tab::Explorer;
+        self.navigate_to(parent.to_path_buf());
+        self.load_files();
+
+        if let Some(name) = abs.file_name().map(|s| s.to_string_lossy().to_string())
+            && let Some(idx) = self.files.iter().position(|f| f.name == name)
+        {
+            self.list_state.select(Some(idx));
+            self.update_preview();
+        }
+    }
+
+    fn clone_input_key(&mut self, key: &KeyEvent) {
+        let Some(input) = self.clone_input.as_mut() else {
+            return;
+        };
+        let field = if input.focus_dest {
+            &mut input.dest
+        } else {
+            &mut input.url
+        };
+        match key.code {
+            KeyCode::Tab | KeyCode::BackTab => input.focus_dest = !input.focus_dest,
+            KeyCode::Enter if !input.focus_dest => {
+                if input.dest.is_empty() && !input.url.trim().is_empty() {
+                    input.dest = git_ops::clone_dir_name(&input.url);
+                }
+                input.focus_dest = true;
+            }
+            KeyCode::Enter => self.start_clone(),
+            KeyCode::Backspace => {
+                field.pop();
+            }
+            KeyCode::Char(ch) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                field.push(ch);
+            }
+            _ => {}
+        }
+    }
+
+    /// Clone in the background, streaming git's progress into the operation popup.
+    fn start_clone(&mut self) {
+        if self.clone_job.is_some() {
+            self.set_status("A clone is already running");
+            return;
+        }
+        let Some(input) = self.clone_input.take() else {
+            return;
+        };
+        let url = input.url.trim().to_string();
+        if url.is_empty() {
+            self.set_status("Enter a repository URL");
+            return;
+        }
+        let dest = match input.dest.trim() {
+            "" => git_ops::clone_dir_name(&url),
+            dest => dest.to_string(),
+        };
+        let dest = self.current_path.join(dest);
+        if fs::read_dir(&dest).is_ok_and(|mut d| d.next().is_some()) {
+            self.set_status(format!("{} exists and is not empty", dest.display()));
+            return;
+        }
+
+        let title = format!("git clone {}", url);
+        let (tx, rx) = mpsc::channel();
+        let target = dest.clone();
+        thread::spawn(move || {
+            let result = git_ops::clone_repo(&url, &target, |line| {
+                let _ = tx.send(CloneMsg::Progress(line.to_string()));
+            });
+            let _ = tx.send(CloneMsg::Done(result));
+        });
+        self.operation_popup = Some(OperationPopup::new(
+            title.clone(),
+            "Starting…".to_string(),
+            true,
+        ));
+        self.clone_job = Some(CloneJob {
+            rx,
+            title,
+            dest,
+            lines: Vec::new(),
+        });
+    }
+
+    fn poll_clone_job(&mut self) {
+        let Some(job) = self.clone_job.as_mut() else {
+            return;
+        };
+        let mut done = None;
+        while let Ok(msg) = job.rx.try_recv() {
+            match msg {
+                CloneMsg::Progress(line) => {
+                    let label = |l: &str| l.rsplit_once(':').map(|(a, _)| a.to_string());
+                    let redraw = label(&line).is_some()
+                        && job.lines.last().and_then(|l| label(l)) == label(&line);
+                    if redraw {
+                        job.lines.pop();
+                    }
+                    job.lines.push(line);
+                }
+                CloneMsg::Done(result) => done = Some(result),
+            }
+        }
+
+        let popup = self
+            .operation_popup
+            .as_mut()
+            .filter(|p| p.title == job.title);
+        let Some(result) = done else {
+            if let Some(popup) = popup {
+                popup.body = job.lines.join("\n");
+                popup.scroll_y = job.lines.len().saturating_sub(8) as u16;
+            }
+            return;
+        };
+
+        let Some(job) = self.clone_job.take() else {
+            return;
+        };
+        let mut body = job.lines.join("\n");
+        match result {
+            Ok(()) => {
+                body.push_str(&format!("\n\nCloned into {}", job.dest.display()));
+                self.operation_popup = Some(OperationPopup::new(job.title, body, true));
+                self.open_repo(job.dest);
+                self.set_status("Cloned");
+            }
+            Err(e) => {
+                if !body.ends_with(&e) {
+                    body.push_str(&format!("\n\n{}", e));
+                }
+                self.operation_popup = Some(OperationPopup::new(job.title, body, false));
+                self.set_status("Clone failed");
+            }
+        }
+    }
+
+    /// Make `path` the working repository, as if lzgit had been started there.
+    fn open_repo(&mut self, path: PathBuf) {
+        self.navigate_to(path);
+        self.startup_path = self.current_path.clone();
+        self.log_ui.history_ref = None;
+        self.review = crate::review::ReviewState::default();
+        self.current_tab = Tab::Git;
+        self.refresh_git_state();
+    }
+
+    /// Work in the submodule selected in the Git tree as its own repository, until
+    /// [`leave_submodule`](Self::leave_submodule).
+    fn enter_selected_submodule(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let Some(sub) = self.git.selected_submodule() else {
+            self.set_status("No submodule selected");
+            return;
+        };
+        if sub.state == git_ops::SubmoduleState::Uninitialized {
+            self.set_status("Submodule is not checked out; update it first");
+            return;
+        }
+        let path = repo_root.join(&sub.path);
+        let mut superprojects = std::mem::take(&mut self.superprojects);
+        superprojects.push(repo_root);
+        self.open_repo(path);
+        self.superprojects = superprojects;
+    }
+
+    fn leave_submodule(&mut self) {
+        let mut superprojects = std::mem::take(&mut self.superprojects);
+        let Some(parent) = superprojects.pop() else {
+            self.set_status("Not inside a submodule");
+            return;
+        };
+        self.open_repo(parent);
+        self.superprojects = superprojects;
+    }
+
+    /// Init, update or sync the submodule selected in the Git tree, or all of them
+    /// when the selection is elsewhere.
+    fn run_submodule_op(&mut self, op: git_ops::SubmoduleOp) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        if self.git.submodules.is_empty() {
+            self.set_status("No submodules");
+            return;
+        }
+        let path = self.git.selected_submodule().map(|sub| sub.path.clone());
+        let mut cmd = format!("git {}", op.args().join(" "));
+        if let Some(path) = &path {
+            cmd.push_str(&format!(" -- {}", path));
+        }
+        self.set_status(format!("Running: {}", cmd));
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::submodule_op(&repo_root, op, path.as_deref())
+        });
+    }
+
+    fn open_gitignore_picker(&mut self) {
+        let Some(repo_root) = self.git.repo_root.as_ref() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let existing = fs::read_to_string(repo_root.join(".gitignore")).unwrap_or_default();
+        self.gitignore_picker = Some(GitignorePicker {
+            selected: 0,
+            scroll_y: 0,
+            existing,
+        });
+    }
+
+    fn gitignore_picker_key(&mut self, key: &KeyEvent) {
+        let Some(picker) = self.gitignore_picker.as_mut() else {
+            return;
+        };
+        if let Some(nav) = self.nav_keys.key(key, true) {
+            let last = gitignore::TEMPLATES.len() as i32 - 1;
+            picker.selected = (picker.selected as i32 + nav.delta()).clamp(0, last) as usize;
+            picker.scroll_y = 0;
+            return;
+        }
+        match key.code {
+            KeyCode::Char('J') => picker.scroll_y = picker.scroll_y.saturating_add(1),
+            KeyCode::Char('K') => picker.scroll_y = picker.scroll_y.saturating_sub(1),
+            KeyCode::Enter => self.write_gitignore_template(),
+            _ => {}
+        }
+    }
+
+    /// Save the previewed `.gitignore` to the repository root.
+    fn write_gitignore_template(&mut self) {
+        let Some(picker) = self.gitignore_picker.take() else {
+            return;
+        };
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            return;
+        };
+        let template = &gitignore::TEMPLATES[picker.selected];
+        let text = gitignore::apply(&picker.existing, template);
+        if text == picker.existing {
+            self.set_status(format!(
+                ".gitignore already has the {} patterns",
+                template.name
+            ));
+            return;
+        }
+        match fs::write(repo_root.join(".gitignore"), text) {
+            Ok(()) => {
+                self.set_status(format!(
+                    "Added the {} template to .gitignore",
+                    template.name
+                ));
+                self.refresh_git_state();
+            }
+            Err(e) => self.set_status(format!("Could not write .gitignore: {}", e)),
+        }
+    }
+
+    fn add_selected_to_gitignore(&mut self) {
+        if self.git.repo_root.is_none() {
+            self.git.refresh(&self.current_path);
+        }
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        let mut patterns: Vec<String> = match self.current_tab {
+            Tab::Explorer => {
+                let Some(file) = self.selected_file() else {
+                    self.set_status("No selection");
+                    return;
+                };
+
+                let Ok(rel) = file.path.strip_prefix(&repo_root) else {
+                    self.set_status("Selection not in repo");
+                    return;
+                };
+
+                let mut p = rel.to_string_lossy().to_string();
+                if file.is_dir && !p.ends_with('/') {
+                    p.push('/');
+                }
+                vec![p]
+            }
+            Tab::Git => self.selected_git_paths(),
+            Tab::Log | Tab::Terminal => {
+                self.set_status("Not available here");
+                return;
+            }
+        };
+
+        if patterns.is_empty() {
+            self.set_status("No selection");
+            return;
+        }
+
+        for p in patterns.iter_mut() {
+            let is_dir = repo_root.join(p.as_str()).is_dir();
+            if is_dir && !p.ends_with('/') {
+                p.push('/');
+            }
+        }
+
+        patterns.sort();
+        patterns.dedup();
+
+        match git_ops::add_to_gitignore(&repo_root, &patterns) {
+            Ok(0) => {
+                self.set_status("Already ignored");
+            }
+            Ok(n) => {
+                self.set_status(format!("Added {} to .gitignore", n));
+                self.refresh_git_state();
+            }
+            Err(e) => {
+                self.set_status(e);
+            }
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME` or `~/.config`.
+fn xdg_config_home() -> Option<PathBuf> {
+    let home = env::home_dir()?;
+    Some(
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".config")),
+    )
+}
+
+/// Directory holding lzgit's settings files.
+fn config_dir() -> Option<PathBuf> {
+    Some(xdg_config_home()?.join("lzgit"))
+}
+
+/// Where lzgit keeps its files. The app only ever uses these, so a test can give
+/// it a scratch folder instead of the user's.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Dirs {
+    /// Settings: `~/.config/lzgit`
+    pub(crate) config: Option<PathBuf>,
+    /// Logs and history: `~/.local/state/lzgit`
+    pub(crate) state: Option<PathBuf>,
+}
+
+impl Dirs {
+    /// The XDG locations for the current user.
+    pub(crate) fn from_env() -> Self {
+        Self {
+            config: config_dir(),
+            state: crate::state_dir(),
+        }
+    }
+
+    fn config_file(&self, name: &str) -> Option<PathBuf> {
+        Some(self.config.as_ref()?.join(name))
+    }
+
+    fn state_file(&self, name: &str) -> Option<PathBuf> {
+        Some(self.state.as_ref()?.join(name))
+    }
+}
+
+/// Settings files that used to live in the shared `te/` directory.
+const MIGRATED_CONFIG_FILES: &[&str] = &["bookmarks.tsv", "ui.json"];
+
+/// Copy settings from the old `te/` folder next to the config directory the first
+/// time lzgit runs with its own. The old files are left in place since `te/` is shared.
+fn migrate_legacy_config(dirs: &Dirs) -> io::Result<bool> {
+    let Some(dir) = &dirs.config else {
+        return Ok(false);
+    };
+    let Some(legacy) = dir.parent().map(|base| base.join("te")) else {
+        return Ok(false);
+    };
+    migrate_config_dir(&legacy, dir)
+}
+
+/// Copy the known settings files from `legacy` into `dir` unless `dir` already exists.
+fn migrate_config_dir(legacy: &Path, dir: &Path) -> io::Result<bool> {
+    if dir.exists() || !legacy.is_dir() {
+        return Ok(false);
+    }
+
+    let mut migrated = false;
+    for name in MIGRATED_CONFIG_FILES {
+        let old = legacy.join(name);
+        if old.is_file() {
+            fs::create_dir_all(dir)?;
+            fs::copy(&old, dir.join(name))?;
+            migrated = true;
+        }
+    }
+    Ok(migrated)
+}
+
+/// The local UTC offset read at startup. `time` only reads it while the process
+/// has a single thread, as another one could be changing the environment meanwhile.
+static LOCAL_OFFSET: OnceLock<UtcOffset> = OnceLock::new();
+
+/// Remember the local UTC offset for the clock; call before any thread starts.
+pub fn capture_local_offset() {
+    let _ = LOCAL_OFFSET.set(UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC));
+}
+
+/// The UTC offset at the Unix time `secs`: the current rules where `time` can still
+/// read them, otherwise the offset at startup.
+fn local_offset_at(secs: i64) -> UtcOffset {
+    OffsetDateTime::from_unix_timestamp(secs)
+        .ok()
+        .and_then(|at| UtcOffset::local_offset_at(at).ok())
+        .or_else(|| LOCAL_OFFSET.get().copied())
+        .unwrap_or(UtcOffset::UTC)
+}
+
+/// `HH:MM` of the Unix time `secs` at `offset`.
+fn hh_mm(secs: i64, offset: UtcOffset) -> String {
+    let secs = (secs + i64::from(offset.whole_seconds())).rem_euclid(86_400);
+    format!("{:02}:{:02}", secs / 3600, secs % 3600 / 60)
+}
+
+pub(crate) fn format_size(size: u64) -> String {
+    if size < 1024 {
+        format!("{}B", size)
+    } else if size < 1024 * 1024 {
+        format!("{:.1}K", size as f64 / 1024.0)
+    } else if size < 1024 * 1024 * 1024 {
+        format!("{:.1}M", size as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.1}G", size as f64 / (1024.0 * 1024.0 * 1024.0))
+    }
+}
+
+/// `path` itself when it is a patch file, or the patch files in a folder such as
+/// the output of `git format-patch`, in name order.
+fn patch_files(path: &Path) -> Vec<PathBuf> {
+    let is_patch = |p: &Path| {
+        p.extension()
+            .is_some_and(|e| matches!(e.to_str(), Some("patch" | "mbox" | "eml" | "diff")))
+    };
+    if path.is_file() {
+        return if is_patch(path) {
+            vec![path.to_path_buf()]
+        } else {
+            Vec::new()
+        };
+    }
+    let mut found: Vec<PathBuf> = fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.is_file() && is_patch(p))
+                .collect()
+        })
+        .unwrap_or_default();
+    found.sort();
+    found
+}
+
+fn fuzzy_score(haystack: &str, needle: &str) -> Option<i32> {
+    let n = needle.trim();
+    if n.is_empty() {
+        return Some(0);
+    }
+
+    let mut score: i32 = 0;
+    let mut last_match: Option<usize> = None;
+    let mut pos = 0usize;
+
+    for ch in n.chars() {
+        let mut found_at: Option<usize> = None;
+        for (i, hc) in haystack[pos..].char_indices() {
+            if hc == ch {
+                found_at = Some(pos + i);
+                break;
+            }
+        }
+        let idx = found_at?;
+
+        score += 10;
+        if let Some(prev) = last_match {
+            if idx == prev + 1 {
+                score += 15;
+            } else {
+                let gap = idx.saturating_sub(prev + 1) as i32;
+                score -= gap.min(30);
+            }
+        } else {
+            score += (30 - idx as i32).max(0);
+        }
+
+        last_match = Some(idx);
+        pos = idx + ch.len_utf8();
+    }
+
+    Some(score)
+}
+
+fn token_score(haystack: &str, token: &str) -> Option<i32> {
+    let t = token.trim();
+    if t.is_empty() {
+        return Some(0);
+    }
+
+    if haystack.contains(t) {
+        return Some(200 + (t.chars().count() as i32) * 5);
+    }
+
+    let score = fuzzy_score(haystack, t)?;
+    let len = t.chars().count() as i32;
+
+    if len >= 4 && score < len * 10 {
+        return None;
+    }
+
+    Some(score)
+}
+
+fn git_decoration_tokens(decoration: &str) -> Vec<String> {
+    let deco = decoration.trim();
+    if deco.is_empty() {
+        return Vec::new();
+    }
+
+    let mut text = deco;
+    if let Some(stripped) = text.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        text = stripped;
+    }
+
+    let mut out = Vec::new();
+    for token in text.split(", ") {
+        let t = token.trim();
+        if t.is_empty() {
+            continue;
+        }
+
+        if let Some(rest) = t.strip_prefix("HEAD -> ") {
+            out.push("HEAD".to_string());
+            if !rest.trim().is_empty() {
+                out.push(rest.trim().to_string());
+            }
+            continue;
+        }
+
+        if let Some(rest) = t.strip_prefix("tag: ") {
+            if !rest.trim().is_empty() {
+                out.push(format!("tag:{}", rest.trim()));
+            }
+            continue;
+        }
+
+        out.push(t.to_string());
+    }
+
+    out
+}
+
+/// Indices of breadcrumbs to fold into "…" so the rest fits in `available` columns.
+/// Keeps the first component when possible and as many trailing ones as fit.
+fn breadcrumb_hidden_range(crumbs: &[(String, PathBuf)], available: usize) -> Range<usize> {
+    const SEP: usize = 3;
+    let widths: Vec<usize> = crumbs.iter().map(|(l, _)| display_width(l)).collect();
+    let total: usize = widths.iter().sum::<usize>() + SEP * widths.len().saturating_sub(1);
+    if total <= available || widths.len() < 2 {
+        return 0..0;
+    }
+
+    // "…" plus its separator
+    let ellipsis = 3 + SEP;
+    let last = widths.len() - 1;
+    let keep_first = widths[0] + SEP + ellipsis + widths[last] <= available;
+    let mut used = ellipsis + widths[last] + if keep_first { widths[0] + SEP } else { 0 };
+    let mut tail_start = last;
+    let min_tail = if keep_first { 2 } else { 1 };
+    while tail_start > min_tail && used + widths[tail_start - 1] + SEP <= available {
+        tail_start -= 1;
+        used += widths[tail_start] + SEP;
+    }
+
+    if keep_first {
+        1..tail_start
+    } else {
+        0..tail_start
+    }
+}
+
+/// What `lzgit` was started with
+pub struct Options {
+    /// Directory to open; the Git tab uses the repository around it
+    pub start_path: PathBuf,
+    /// Listen for editor requests here
+    pub socket_path: Option<PathBuf>,
+    /// File (absolute path) and line to select in the Git tab
+    pub select: Option<(String, Option<u32>)>,
+}
+
+/// Run the app on a terminal that is already in raw mode, until the user quits.
+pub async fn run(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    options: Options,
+) -> io::Result<()> {
+    let Options {
+        start_path,
+        socket_path,
+        select,
+    } = options;
+
+    // Create async preview loader
+    let (preview_loader, preview_result_rx) = preview_loader::PreviewLoader::new();
+
+    // Create async git diff loader
+    let (git_diff_loader, git_diff_result_rx) = git_diff_loader::GitDiffLoader::new();
+
+    // Half blocks until the terminal answers the graphics probe below
+    let mut app = App::new(
+        start_path,
+        Picker::halfblocks(),
+        preview_loader,
+        preview_result_rx,
+        git_diff_loader,
+        git_diff_result_rx,
+        Dirs::from_env(),
+    );
+
+    // Optional control socket; keep the server alive so its socket file is cleaned up on exit
+    let (control_tx, mut control_rx) = tokio_mpsc::unbounded_channel();
+    let _control_server = socket_path.and_then(|path| {
+        match control::ControlServer::start(&path, control_tx.clone()) {
+            Ok(server) => Some(server),
+            Err(e) => {
+                app.set_status(format!("Control socket: {}", e));
+                None
+            }
+        }
+    });
+
+    if let Some((path, line)) = select {
+        app.focus_git_file(&path, line);
+    }
+
+    // Show the UI before the slow parts of startup: syntax definitions load in the
+    // background, and the image protocol probe waits on the terminal's reply, which
+    // has to happen before the event stream starts reading stdin.
+    highlight::preload();
+    app.update(Msg::Tick);
+    terminal.draw(|f| {
+        let zones = draw_ui(f, &mut app);
+        app.zones = zones;
+    })?;
+    if !App::is_ssh_session()
+        && let Ok(picker) = Picker::from_query_stdio()
+    {
+        app.picker = picker;
+        // An image already on screen was encoded for half blocks
+        if app.current_image_path.take().is_some() {
+            app.update_preview();
+        }
+    }
+
+    // Create event stream for async terminal event handling
+    let mut event_stream = EventStream::new();
+
+    loop {
+        let mut zones = Vec::new();
+        app.update(Msg::Tick);
+        // Force full terminal refresh if needed (e.g., after external editor)
+        if app.needs_full_redraw {
+            app.needs_full_redraw = false;
+            terminal.clear()?;
+        }
+        terminal.draw(|f| {
+            zones = draw_ui(f, &mut app);
+        })?;
+        app.zones = zones;
+
+        if let Some(state) = &mut app.image_state
+            && let Some(Err(e)) = state.last_encoding_result()
+        {
+            app.preview_error = Some(format!("Image Error: {}", e));
+            app.image_state = None;
+            app.current_image_path = None;
+        }
+
+        if app.update_in_progress {
+            app.spinner_frame = app.spinner_frame.wrapping_add(1);
+        }
+
+        // Use tokio::select! to handle terminal events and preview results concurrently
+        let poll_timeout = tokio::time::sleep(Duration::from_millis(100));
+        tokio::pin!(poll_timeout);
+
+        tokio::select! {
+            // Handle preview loader results
+            Some(result) = app.preview_result_rx.recv() => {
+                app.update(Msg::Preview(result));
+            }
+            // Handle git diff loader results
+            Some(result) = app.git_diff_result_rx.recv() => {
+                app.update(Msg::GitDiff(result));
+            }
+            // Handle control socket requests
+            Some(req) = control_rx.recv() => {
+                app.update(Msg::Control(req));
+            }
+            // Handle terminal events
+            Some(event_result) = event_stream.next() => {
+                if let Ok(event) = event_result {
+                    app.update(Msg::Event(event));
+                }
+            }
+            // Timeout - allows background polling to continue
+            _ = &mut poll_timeout => {}
+        }
+
+        if let Some(text) = app.take_pending_clipboard() {
+            let command = app
+                .clipboard_config
+                .command
+                .as_deref()
+                .filter(|_| app.features.shell);
+            let methods = clipboard::chain(
+                app.clipboard_config.order.as_deref(),
+                command,
+                App::is_ssh_session(),
+            );
+            match clipboard::copy(terminal.backend_mut(), &text, &methods, command) {
+                Ok(msg) | Err(msg) => app.set_status(msg),
+            }
+        }
+
+        if app.should_quit {
+            break;
+        }
+    }
+
+    app.save_persisted_bookmarks();
+    app.save_persisted_ui_settings();
+    file_ops::clear_trash();
+    Ok(())
+}
@@ -0,0 +1,40 @@
+//! Reviewing staged hunks before a commit
+
+use super::*;
+
+impl App {
+    /// Step through the staged hunks one at a time, replacing the diff pane.
+    pub(crate) fn open_review(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        match git_ops::staged_diff(&repo_root) {
+            Ok(diff) => {
+                self.review.start(&diff);
+                if self.review.hunks.is_empty() {
+                    self.review.open = false;
+                    self.set_status("No staged changes");
+                    return;
+                }
+                self.context_menu = None;
+                self.current_tab = Tab::Git;
+                self.git_focus = PaneFocus::Detail;
+            }
+            Err(e) => self.set_status(e),
+        }
+    }
+
+    pub(crate) fn review_unstage(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let Some(patch) = self.review.unstage() else {
+            return;
+        };
+        self.start_git_job("unstage hunk".to_string(), true, false, move || {
+            git_ops::unstage_patch(&repo_root, &patch)
+        });
+    }
+}
@@ -0,0 +1,360 @@
+//! Headless rendering of fixed screens for layout and theme review
+//!
+//! `lzgit --render-snapshot <scenario>` builds a small sample repository, runs the
+//! app on a [`TestBackend`] until its background jobs are done and writes the screen
+//! to `<scenario>.txt`. `--output <path>` picks another file (`-` for stdout) and
+//! `--ansi` keeps the colors as escape sequences, so `cat` shows the themed screen.
+//!
+//! Commit dates and the clock are fixed, so a scenario renders the same on every
+//! run; the ui tests compare them against the golden files in `tests/snapshots`.
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    queue,
+    style::{Attribute, Print, SetAttribute, SetBackgroundColor, SetForegroundColor},
+};
+use ratatui::{
+    Terminal,
+    backend::{IntoCrossterm, TestBackend},
+    buffer::Buffer,
+    style::{Modifier, Style},
+};
+use ratatui_image::picker::Picker;
+
+use crate::app::{App, Dirs, Tab, draw_ui, update::Msg};
+use crate::{git_diff_loader, preview_loader};
+
+pub const WIDTH: u16 = 120;
+pub const HEIGHT: u16 = 32;
+
+/// Commit dates start here, one hour apart (2024-03-01 12:00 UTC)
+const EPOCH: i64 = 1_709_294_400;
+/// What the app takes as the current time: a day after the sample commits
+const NOW: i64 = EPOCH + 30 * 3_600;
+
+/// How long [`settle`] waits for background jobs
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scenario {
+    /// Git tab with staged, unstaged and untracked changes
+    Git,
+    /// Git tab in the middle of a merge with a conflicted file
+    Conflict,
+    /// Log tab showing History with branches and a tag
+    History,
+}
+
+impl Scenario {
+    pub const ALL: [Scenario; 3] = [Scenario::Git, Scenario::Conflict, Scenario::History];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scenario::Git => "git",
+            Scenario::Conflict => "conflict",
+            Scenario::History => "history",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Scenario> {
+        Self::ALL.into_iter().find(|s| s.name() == name)
+    }
+}
+
+/// Handle `--render-snapshot`: render `name` and write it to `output`, or to
+/// `<name>.txt` when no output is given.
+pub async fn run(name: &str, output: Option<PathBuf>, ansi: bool) -> Result<(), String> {
+    let Some(scenario) = Scenario::parse(name) else {
+        let names: Vec<&str> = Scenario::ALL.iter().map(|s| s.name()).collect();
+        return Err(format!(
+            "unknown scenario `{}` (expected one of: {})",
+            name,
+            names.join(", ")
+        ));
+    };
+    let buffer = render(scenario).await?;
+    let text = if ansi {
+        ansi_text(&buffer)
+    } else {
+        plain_text(&buffer)
+    };
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.txt", name)));
+    if output == Path::new("-") {
+        io::stdout()
+            .write_all(text.as_bytes())
+            .map_err(|e| e.to_string())
+    } else {
+        fs::write(&output, text).map_err(|e| format!("{}: {}", output.display(), e))
+    }
+}
+
+/// Build the sample repository for `scenario` in a scratch folder and render it.
+pub async fn render(scenario: Scenario) -> Result<Buffer, String> {
+    render_under(&env::temp_dir(), scenario).await
+}
+
+/// [`render`] with the scratch folder under `base`. The top bar shows the
+/// repository path, so the golden files need the same `base` on every machine.
+pub async fn render_under(base: &Path, scenario: Scenario) -> Result<Buffer, String> {
+    let scratch = base.join("lzgit-snapshot").join(scenario.name());
+    let _ = fs::remove_dir_all(&scratch);
+    let repo = scratch.join("sample");
+    let result = match sample_repo(&repo, scenario) {
+        Ok(()) => render_in(&repo, scenario).await,
+        Err(e) => Err(e),
+    };
+    let _ = fs::remove_dir_all(&scratch);
+    result
+}
+
+async fn render_in(repo: &Path, scenario: Scenario) -> Result<Buffer, String> {
+    let (preview_loader, preview_result_rx) = preview_loader::PreviewLoader::new();
+    let (git_diff_loader, git_diff_result_rx) = git_diff_loader::GitDiffLoader::new();
+    let mut app = App::new(
+        repo.to_path_buf(),
+        Picker::halfblocks(),
+        preview_loader,
+        preview_result_rx,
+        git_diff_loader,
+        git_diff_result_rx,
+        // The user's settings, such as the theme, would change the screen
+        Dirs::default(),
+    );
+    app.fixed_now = Some(NOW);
+    app.top_bar.clock = false;
+    let mut terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).map_err(|e| e.to_string())?;
+    settle(&mut app, &mut terminal).await?;
+
+    if scenario == Scenario::History {
+        app.current_tab = Tab::Log;
+        app.set_log_subtab(crate::app::LogSubTab::History);
+        app.refresh_log_data();
+        settle(&mut app, &mut terminal).await?;
+    }
+    Ok(terminal.backend().buffer().clone())
+}
+
+/// Run frames until no background job is left, as the main loop would, and render
+/// the result.
+pub(crate) async fn settle(
+    app: &mut App,
+    terminal: &mut Terminal<TestBackend>,
+) -> Result<(), String> {
+    let deadline = Instant::now() + SETTLE_TIMEOUT;
+    // A few quiet frames let diff and preview results arrive
+    let mut quiet = 0;
+    while quiet < 3 {
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "jobs still running:\n{}",
+                plain_text(terminal.backend().buffer())
+            ));
+        }
+        app.update(Msg::Tick);
+        while let Ok(result) = app.git_diff_result_rx.try_recv() {
+            app.update(Msg::GitDiff(result));
+        }
+        while let Ok(result) = app.preview_result_rx.try_recv() {
+            app.update(Msg::Preview(result));
+        }
+        draw(app, terminal)?;
+        let idle = app.pending_job.is_none()
+            && app.job_queue.is_empty()
+            && app.git_refresh_job.is_none()
+            && app.log_job.is_none()
+            && app.ci.rx.is_none()
+            && app
+                .file_finder
+                .as_ref()
+                .is_none_or(|finder| finder.rx.is_none());
+        quiet = if idle { quiet + 1 } else { 0 };
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    Ok(())
+}
+
+pub(crate) fn draw(app: &mut App, terminal: &mut Terminal<TestBackend>) -> Result<(), String> {
+    let mut zones = Vec::new();
+    terminal
+        .draw(|f| zones = draw_ui(f, app))
+        .map_err(|e| e.to_string())?;
+    app.zones = zones;
+    Ok(())
+}
+
+/// The screen as text, one line per row without trailing spaces.
+pub fn plain_text(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    for row in 0..buffer.area.height {
+        let line: String = (0..buffer.area.width)
+            .map(|column| buffer[(column, row)].symbol())
+            .collect();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// The screen with SGR escape sequences wherever the style changes.
+pub fn ansi_text(buffer: &Buffer) -> String {
+    let mut out: Vec<u8> = Vec::new();
+    for row in 0..buffer.area.height {
+        let mut current: Option<Style> = None;
+        for column in 0..buffer.area.width {
+            let cell = &buffer[(column, row)];
+            let style = cell.style();
+            if current != Some(style) {
+                let _ = queue_style(&mut out, style);
+                current = Some(style);
+            }
+            let _ = queue!(out, Print(cell.symbol()));
+        }
+        let _ = queue!(out, SetAttribute(Attribute::Reset), Print("\n"));
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn queue_style(out: &mut Vec<u8>, style: Style) -> io::Result<()> {
+    queue!(out, SetAttribute(Attribute::Reset))?;
+    if let Some(fg) = style.fg {
+        queue!(out, SetForegroundColor(fg.into_crossterm()))?;
+    }
+    if let Some(bg) = style.bg {
+        queue!(out, SetBackgroundColor(bg.into_crossterm()))?;
+    }
+    for (modifier, attribute) in [
+        (Modifier::BOLD, Attribute::Bold),
+        (Modifier::DIM, Attribute::Dim),
+        (Modifier::ITALIC, Attribute::Italic),
+        (Modifier::UNDERLINED, Attribute::Underlined),
+        (Modifier::REVERSED, Attribute::Reverse),
+        (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+    ] {
+        if style.add_modifier.contains(modifier) {
+            queue!(out, SetAttribute(attribute))?;
+        }
+    }
+    Ok(())
+}
+
+/// Sample history shared by every scenario, then the scenario's own state.
+fn sample_repo(repo: &Path, scenario: Scenario) -> Result<(), String> {
+    fs::create_dir_all(repo).map_err(|e| e.to_string())?;
+    let mut sample = Sample { repo, commits: 0 };
+    sample.git(&["init", "-q", "-b", "main"])?;
+    sample.commit(
+        &[
+            ("README.md", "# Sample\n\nA repository for snapshots.\n"),
+            ("src/main.rs", "fn main() {\n    println!(\"hello\");\n}\n"),
+        ],
+        "Initial commit",
+    )?;
+    sample.commit(
+        &[(
+            "src/lib.rs",
+            "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+        )],
+        "Add the library",
+    )?;
+    sample.git(&["tag", "v0.1.0"])?;
+    sample.git(&["checkout", "-q", "-b", "feature/greeting"])?;
+    sample.commit(
+        &[(
+            "src/main.rs",
+            "fn main() {\n    println!(\"hello, world\");\n}\n",
+        )],
+        "Greet the world",
+    )?;
+    sample.git(&["checkout", "-q", "main"])?;
+    sample.commit(
+        &[(
+            "src/main.rs",
+            "fn main() {\n    println!(\"hello, sample\");\n}\n",
+        )],
+        "Greet the sample",
+    )?;
+
+    match scenario {
+        Scenario::Git => {
+            sample.write(
+                "src/lib.rs",
+                "pub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\n\
+                 pub fn sub(a: i32, b: i32) -> i32 {\n    a - b\n}\n",
+            )?;
+            sample.git(&["add", "src/lib.rs"])?;
+            sample.write(
+                "README.md",
+                "# Sample\n\nA repository for layout snapshots.\n",
+            )?;
+            sample.write("notes.txt", "todo\n")?;
+        }
+        Scenario::Conflict => {
+            // Fails with a conflict in src/main.rs, which is the point
+            sample.try_git(&["merge", "-q", "feature/greeting"])?;
+        }
+        Scenario::History => {}
+    }
+    Ok(())
+}
+
+struct Sample<'a> {
+    repo: &'a Path,
+    commits: i64,
+}
+
+impl Sample<'_> {
+    fn write(&self, rel: &str, contents: &str) -> Result<(), String> {
+        let path = self.repo.join(rel);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+
+    fn commit(&mut self, files: &[(&str, &str)], message: &str) -> Result<(), String> {
+        for (rel, contents) in files {
+            self.write(rel, contents)?;
+        }
+        self.git(&["add", "-A"])?;
+        self.git(&["commit", "-q", "-m", message])?;
+        self.commits += 1;
+        Ok(())
+    }
+
+    fn git(&self, args: &[&str]) -> Result<(), String> {
+        if self.try_git(args)? {
+            Ok(())
+        } else {
+            Err(format!("git {} failed", args.join(" ")))
+        }
+    }
+
+    /// Run git with a fixed identity and date and without the user's config, so
+    /// hashes come out the same everywhere.
+    fn try_git(&self, args: &[&str]) -> Result<bool, String> {
+        let date = format!("@{} +0000", EPOCH + self.commits * 3_600);
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(self.repo)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_AUTHOR_NAME", "Sample Author")
+            .env("GIT_AUTHOR_EMAIL", "author@example.com")
+            .env("GIT_COMMITTER_NAME", "Sample Author")
+            .env("GIT_COMMITTER_EMAIL", "author@example.com")
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .map_err(|e| e.to_string())?;
+        Ok(status.success())
+    }
+}
@@ -0,0 +1,161 @@
+//! Snapshots of the working tree: taking, diffing, restoring and deleting them
+//!
+//! Not to be confused with [`super::snapshot`], which renders frames for tests.
+
+use super::*;
+
+pub(crate) struct SnapshotUi {
+    pub(crate) open: bool,
+    pub(crate) snapshots: Vec<git_ops::SnapshotEntry>,
+    pub(crate) list_state: ListState,
+    pub(crate) status: Option<String>,
+}
+
+impl SnapshotUi {
+    pub(crate) fn new() -> Self {
+        Self {
+            open: false,
+            snapshots: Vec::new(),
+            list_state: ListState::default(),
+            status: None,
+        }
+    }
+
+    pub(crate) fn selected_snapshot(&self) -> Option<&git_ops::SnapshotEntry> {
+        self.snapshots.get(self.list_state.selected()?)
+    }
+
+    pub(crate) fn move_selection(&mut self, delta: i32) {
+        let len = self.snapshots.len();
+        if len == 0 {
+            self.list_state.select(None);
+            return;
+        }
+
+        let cur = self.list_state.selected().unwrap_or(0) as i32;
+        let next = (cur + delta).clamp(0, len.saturating_sub(1) as i32);
+        self.list_state.select(Some(next as usize));
+    }
+}
+
+impl App {
+    pub(crate) fn open_snapshot_picker(&mut self) {
+        self.context_menu = None;
+        self.commit.open = false;
+        self.branch_ui.open = false;
+        self.stash_ui.open = false;
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        match git_ops::list_snapshots(&repo_root) {
+            Ok(snapshots) => {
+                self.snapshot_ui.open = true;
+                self.snapshot_ui.status = None;
+                self.snapshot_ui.list_state.select(if snapshots.is_empty() {
+                    None
+                } else {
+                    Some(0)
+                });
+                self.snapshot_ui.snapshots = snapshots;
+            }
+            Err(e) => {
+                self.set_status(e);
+            }
+        }
+    }
+
+    pub(crate) fn close_snapshot_picker(&mut self) {
+        self.snapshot_ui.open = false;
+        self.snapshot_ui.snapshots.clear();
+        self.snapshot_ui.list_state.select(None);
+        self.snapshot_ui.status = None;
+    }
+
+    pub(crate) fn create_snapshot(&mut self, name: &str) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        let name = name.trim().replace(char::is_whitespace, "-");
+        if name.is_empty() {
+            return;
+        }
+
+        let cmd = format!("snapshot {}", name);
+        self.start_git_job(cmd, false, false, move || {
+            git_ops::create_snapshot(&repo_root, &name)
+        });
+    }
+
+    /// Show the selected snapshot's diff against the working tree in a popup.
+    pub(crate) fn diff_selected_snapshot(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.snapshot_ui.status = Some("Not a git repository".to_string());
+            return;
+        };
+        let Some(name) = self.snapshot_ui.selected_snapshot().map(|s| s.name.clone()) else {
+            self.snapshot_ui.status = Some("No snapshot selected".to_string());
+            return;
+        };
+
+        let title = format!("diff snapshot {}", name);
+        let popup = match git_ops::snapshot_diff(&repo_root, &name) {
+            Ok(diff) if diff.trim().is_empty() => {
+                OperationPopup::new(title, "Working tree matches snapshot".to_string(), true)
+            }
+            Ok(diff) => OperationPopup::new(title, diff, true),
+            Err(e) => OperationPopup::new(title, e, false),
+        };
+        self.close_snapshot_picker();
+        self.operation_popup = Some(popup);
+    }
+
+    pub(crate) fn restore_selected_snapshot(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.snapshot_ui.status = Some("Not a git repository".to_string());
+            return;
+        };
+        let Some(name) = self.snapshot_ui.selected_snapshot().map(|s| s.name.clone()) else {
+            self.snapshot_ui.status = Some("No snapshot selected".to_string());
+            return;
+        };
+
+        let cmd = format!("restore snapshot {}", name);
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::restore_snapshot(&repo_root, &name)
+        });
+        self.close_snapshot_picker();
+    }
+
+    pub(crate) fn delete_selected_snapshot(&mut self) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.snapshot_ui.status = Some("Not a git repository".to_string());
+            return;
+        };
+        let Some(name) = self.snapshot_ui.selected_snapshot().map(|s| s.name.clone()) else {
+            self.snapshot_ui.status = Some("No snapshot selected".to_string());
+            return;
+        };
+
+        match git_ops::delete_snapshot(&repo_root, &name) {
+            Ok(()) => {
+                self.snapshot_ui.snapshots.retain(|s| s.name != name);
+                let len = self.snapshot_ui.snapshots.len();
+                let sel = self.snapshot_ui.list_state.selected().unwrap_or(0);
+                self.snapshot_ui.list_state.select(if len == 0 {
+                    None
+                } else {
+                    Some(sel.min(len - 1))
+                });
+                self.snapshot_ui.status = Some(format!("Deleted {}", name));
+            }
+            Err(e) => {
+                self.snapshot_ui.status = Some(e);
+            }
+        }
+    }
+}
@@ -0,0 +1,433 @@
+//! Stashes: the stash picker, the stash dialog and the Log tab's Stash list
+
+use super::*;
+
+/// Message and flags for a new stash
+pub(crate) struct StashInput {
+    pub(crate) message: String,
+    pub(crate) options: git_ops::StashOptions,
+    /// Paths selected in the Git tree when the dialog opened
+    pub(crate) paths: Vec<String>,
+    pub(crate) only_selected: bool,
+    /// Toggle with the keyboard focus; `None` while typing the message
+    pub(crate) focus: Option<StashToggle>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum StashToggle {
+    IncludeUntracked,
+    KeepIndex,
+    Staged,
+    OnlySelected,
+}
+
+impl StashInput {
+    pub(crate) fn toggles(&self) -> Vec<StashToggle> {
+        let mut toggles = vec![
+            StashToggle::IncludeUntracked,
+            StashToggle::KeepIndex,
+            StashToggle::Staged,
+        ];
+        if !self.paths.is_empty() {
+            toggles.push(StashToggle::OnlySelected);
+        }
+        toggles
+    }
+
+    pub(crate) fn is_on(&self, toggle: StashToggle) -> bool {
+        match toggle {
+            StashToggle::IncludeUntracked => self.options.include_untracked,
+            StashToggle::KeepIndex => self.options.keep_index,
+            StashToggle::Staged => self.options.staged,
+            StashToggle::OnlySelected => self.only_selected,
+        }
+    }
+
+    /// Flip `toggle`; `--staged` goes with neither of the others, so turning one
+    /// on turns those off.
+    pub(crate) fn toggle(&mut self, toggle: StashToggle) {
+        let on = !self.is_on(toggle);
+        let options = &mut self.options;
+        match toggle {
+            StashToggle::IncludeUntracked => options.include_untracked = on,
+            StashToggle::KeepIndex => options.keep_index = on,
+            StashToggle::Staged => options.staged = on,
+            StashToggle::OnlySelected => self.only_selected = on,
+        }
+        if on && toggle == StashToggle::Staged {
+            options.include_untracked = false;
+            options.keep_index = false;
+        } else if on
+            && matches!(
+                toggle,
+                StashToggle::IncludeUntracked | StashToggle::KeepIndex
+            )
+        {
+            options.staged = false;
+        }
+    }
+
+    /// Move the focus through the message and the toggles.
+    pub(crate) fn cycle_focus(&mut self, forward: bool) {
+        let toggles = self.toggles();
+        let idx = self
+            .focus
+            .and_then(|f| toggles.iter().position(|t| *t == f));
+        let n = toggles.len() + 1;
+        // The message is slot 0, the toggles follow
+        let slot = idx.map_or(0, |i| i + 1);
+        let next = if forward {
+            (slot + 1) % n
+        } else {
+            (slot + n - 1) % n
+        };
+        self.focus = next.checked_sub(1).map(|i| toggles[i]);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum StashConfirmAction {
+    Pop,
+    Drop,
+}
+
+pub(crate) struct StashUi {
+    pub(crate) open: bool,
+    pub(crate) query: String,
+    pub(crate) stashes: Vec<git_ops::StashEntry>,
+    pub(crate) filtered: Vec<usize>,
+    pub(crate) list_state: ListState,
+    pub(crate) confirm: Option<(StashConfirmAction, String)>,
+    pub(crate) status: Option<String>,
+}
+
+impl StashUi {
+    pub(crate) fn new() -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            stashes: Vec::new(),
+            filtered: Vec::new(),
+            list_state: ListState::default(),
+            confirm: None,
+            status: None,
+        }
+    }
+
+    pub(crate) fn selected_stash(&self) -> Option<&git_ops::StashEntry> {
+        let sel = self.list_state.selected()?;
+        let idx = *self.filtered.get(sel)?;
+        self.stashes.get(idx)
+    }
+
+    pub(crate) fn update_filtered(&mut self) {
+        let prev = self
+            .list_state
+            .selected()
+            .and_then(|sel| self.filtered.get(sel).copied());
+
+        let query = self.query.trim().to_lowercase();
+        let tokens: Vec<String> = query
+            .split_whitespace()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        let mut matches: Vec<(i32, usize)> = Vec::new();
+        for (i, s) in self.stashes.iter().enumerate() {
+            if tokens.is_empty() {
+                matches.push((0, i));
+                continue;
+            }
+
+            let hay = format!("{} {}", s.selector, s.subject).to_lowercase();
+            let mut score = 0i32;
+            let mut ok = true;
+
+            for t in &tokens {
+                if let Some(s) = token_score(hay.as_str(), t.as_str()) {
+                    score += s;
+                } else {
+                    ok = false;
+                    break;
+                }
+            }
+
+            if ok {
+                matches.push((score, i));
+            }
+        }
+
+        matches.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        self.filtered.clear();
+        self.filtered.extend(matches.into_iter().map(|(_, i)| i));
+
+        if self.filtered.is_empty() {
+            self.list_state.select(None);
+            return;
+        }
+
+        if let Some(prev) = prev.and_then(|idx| self.filtered.iter().position(|i| *i == idx)) {
+            self.list_state.select(Some(prev));
+        } else {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    pub(crate) fn move_selection(&mut self, delta: i32) {
+        let len = self.filtered.len();
+        if len == 0 {
+            self.list_state.select(None);
+            return;
+        }
+
+        let cur = self.list_state.selected().unwrap_or(0) as i32;
+        let next = (cur + delta).clamp(0, len.saturating_sub(1) as i32);
+        self.list_state.select(Some(next as usize));
+    }
+}
+
+impl App {
+    pub(crate) fn open_stash_picker(&mut self) {
+        self.context_menu = None;
+        self.commit.open = false;
+        self.branch_ui.open = false;
+
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        match git_ops::list_stashes(&repo_root, 200) {
+            Ok(stashes) => {
+                self.stash_ui.open = true;
+                self.stash_ui.query.clear();
+                self.stash_ui.status = None;
+                self.stash_ui.confirm = None;
+                self.stash_ui.stashes = stashes;
+                self.stash_ui.update_filtered();
+            }
+            Err(e) => {
+                self.set_status(e);
+            }
+        }
+    }
+
+    pub(crate) fn close_stash_picker(&mut self) {
+        self.stash_ui.open = false;
+        self.stash_ui.query.clear();
+        self.stash_ui.stashes.clear();
+        self.stash_ui.filtered.clear();
+        self.stash_ui.list_state.select(None);
+        self.stash_ui.confirm = None;
+        self.stash_ui.status = None;
+    }
+
+    pub(crate) fn set_stash_status<S: Into<String>>(&mut self, msg: S) {
+        let msg = msg.into();
+        if self.stash_ui.open {
+            self.stash_ui.status = Some(msg);
+        } else {
+            self.set_status(msg);
+        }
+    }
+
+    pub(crate) fn stash_apply_selector(&mut self, selector: String) -> bool {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_stash_status("Not a git repository");
+            return false;
+        };
+
+        let cmd = format!("git stash apply {}", selector);
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::stash_apply(&repo_root, &selector)
+        });
+        true
+    }
+
+    pub(crate) fn stash_apply_log_selected(&mut self) {
+        let Some(entry) = self.selected_stash_entry() else {
+            self.set_status("No stash selected");
+            return;
+        };
+
+        let _ = self.stash_apply_selector(entry.selector.clone());
+    }
+
+    pub(crate) fn open_stash_confirm(&mut self, action: StashConfirmAction, selector: String) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_stash_status("Not a git repository");
+            return;
+        };
+
+        let (kind, verb, risky) = match action {
+            StashConfirmAction::Pop => (
+                confirm::ConfirmKind::StashPop,
+                "Pop",
+                git_ops::is_dirty(&repo_root).unwrap_or(true),
+            ),
+            StashConfirmAction::Drop => (confirm::ConfirmKind::StashDrop, "Drop", true),
+        };
+        self.request_confirm(
+            kind,
+            risky,
+            confirm::ConfirmDialog {
+                title: " Confirm ".to_string(),
+                lines: vec![
+                    format!("About to {} {}", verb.to_lowercase(), selector),
+                    String::new(),
+                    "Continue?".to_string(),
+                ],
+                confirm_label: verb.to_string(),
+                action: ConfirmAction::Stash(action, selector),
+            },
+        );
+    }
+
+    pub(crate) fn open_stash_confirm_log_selected(&mut self, action: StashConfirmAction) {
+        let Some(entry) = self.selected_stash_entry() else {
+            self.set_status("No stash selected");
+            return;
+        };
+
+        self.open_stash_confirm(action, entry.selector.clone());
+    }
+
+    pub(crate) fn stash_apply_selected(&mut self) {
+        self.stash_ui.status = None;
+
+        let Some(sel) = self.stash_ui.selected_stash() else {
+            self.set_stash_status("No stash selected");
+            return;
+        };
+
+        if self.stash_apply_selector(sel.selector.clone()) {
+            if self.stash_ui.open {
+                self.close_stash_picker();
+            }
+        }
+    }
+
+    pub(crate) fn run_stash_action(&mut self, action: StashConfirmAction, selector: String) {
+        self.stash_ui.status = None;
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_stash_status("Not a git repository");
+            return;
+        };
+
+        match action {
+            StashConfirmAction::Pop => {
+                let rr = repo_root.clone();
+                let sel = selector.clone();
+                let cmd = format!("git stash pop {}", sel);
+                self.start_git_job(cmd, true, false, move || git_ops::stash_pop(&rr, &sel));
+            }
+            StashConfirmAction::Drop => {
+                let rr = repo_root.clone();
+                let sel = selector.clone();
+                let cmd = format!("git stash drop {}", sel);
+                self.start_git_job(cmd, true, false, move || git_ops::stash_drop(&rr, &sel));
+            }
+        }
+
+        self.close_stash_picker();
+    }
+
+    pub(crate) fn open_stash_input(&mut self) {
+        if self.git.repo_root.is_none() {
+            self.set_status("Not a git repository");
+            return;
+        }
+        self.stash_input = Some(StashInput {
+            message: String::new(),
+            options: git_ops::StashOptions::default(),
+            paths: self.selected_git_paths(),
+            only_selected: false,
+            focus: None,
+        });
+    }
+
+    pub(crate) fn stash_input_key(&mut self, key: &KeyEvent) {
+        let Some(input) = self.stash_input.as_mut() else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match (key.code, input.focus) {
+            (KeyCode::Tab | KeyCode::Down, _) => input.cycle_focus(true),
+            (KeyCode::BackTab | KeyCode::Up, _) => input.cycle_focus(false),
+            (KeyCode::Enter, _) => self.update(Msg::Action(AppAction::SubmitStash)),
+            (KeyCode::Char(' '), Some(toggle)) => {
+                self.update(Msg::Action(AppAction::ToggleStashOption(toggle)))
+            }
+            (KeyCode::Backspace, None) => {
+                input.message.pop();
+            }
+            (KeyCode::Char(ch), None) if !ctrl => input.message.push(ch),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn submit_stash_input(&mut self) {
+        let Some(input) = self.stash_input.take() else {
+            return;
+        };
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+        let message = input.message.trim().to_string();
+        let options = input.options;
+        let paths = if input.only_selected {
+            input.paths
+        } else {
+            Vec::new()
+        };
+        let mut cmd = String::from("git stash push");
+        for arg in options.args() {
+            cmd.push_str(&format!(" {}", arg));
+        }
+        if !paths.is_empty() {
+            cmd.push_str(&format!(" -- {}", paths.join(" ")));
+        }
+        self.set_status(format!("Running: {}", cmd));
+        self.start_git_job(cmd, true, false, move || {
+            git_ops::stash_push(&repo_root, &message, options, &paths)
+        });
+    }
+
+    /// Park a single hunk in a new stash, leaving the rest of the file in place.
+    pub(crate) fn stash_hunk(&mut self, hunk_idx: usize) {
+        let Some(repo_root) = self.git.repo_root.clone() else {
+            self.set_status("Not a git repository");
+            return;
+        };
+
+        let Some(entry) = self.git.selected_tree_entry().cloned() else {
+            self.set_status("No file selected");
+            return;
+        };
+        if entry.is_untracked || entry.is_conflict {
+            self.set_status("Cannot stash hunks of this file");
+            return;
+        }
+
+        let Some(hunk) = self.git.diff_hunks.get(hunk_idx) else {
+            self.set_status("Invalid hunk");
+            return;
+        };
+
+        let patch_content = hunk.lines.join("\n") + "\n";
+        let staged = entry.x != ' ' && entry.x != '?';
+        let header = hunk
+            .lines
+            .iter()
+            .find(|l| l.starts_with("@@"))
+            .cloned()
+            .unwrap_or_default();
+        let message = format!("hunk {} {}", entry.path, header.trim());
+
+        self.start_git_job("stash hunk".to_string(), true, false, move || {
+            git_ops::stash_patch(&repo_root, &patch_content, staged, &message)
+        });
+    }
+}
@@ -0,0 +1,396 @@
+//! Branch dialogs: the branch picker and the name, upstream and description prompts
+
+use super::*;
+
+/// The branch picker: local and remote branches and what to do with the selected one
+pub(crate) fn render_branch_picker(
+    f: &mut Frame,
+    app: &mut App,
+    area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    let w = area.width.min(90).saturating_sub(2).max(50);
+    let h = area.height.min(20).saturating_sub(2).max(10);
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + (area.height.saturating_sub(h)) / 2;
+    let modal = Rect::new(x, y, w, h);
+
+    zones.push(ClickZone {
+        rect: area,
+        action: AppAction::CloseBranchPicker,
+    });
+
+    f.render_widget(Clear, modal);
+
+    let title = match app.branch_picker_mode {
+        BranchPickerMode::Checkout => " Branches ",
+        BranchPickerMode::LogView => " View Branch ",
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.accent_primary))
+        .title(title);
+    f.render_widget(block.clone(), modal);
+
+    let inner = modal.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+
+    // The selected branch's description, when it has one, sits above the buttons
+    let description = app
+        .branch_ui
+        .selected_branch()
+        .and_then(|b| b.description)
+        .filter(|_| app.branch_picker_mode == BranchPickerMode::Checkout);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(description.is_some() as u16),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let query = Paragraph::new(format!("Filter: {}", app.branch_ui.query))
+        .style(Style::default().fg(app.palette.fg));
+    f.render_widget(query, rows[0]);
+
+    let list_items: Vec<ListItem> = app
+        .branch_ui
+        .items
+        .iter()
+        .map(|item| match item {
+            BranchListItem::Header(t) => ListItem::new(Span::styled(
+                t.clone(),
+                Style::default()
+                    .fg(app.palette.accent_tertiary)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            BranchListItem::Branch { idx, depth } => {
+                let b = &app.branch_ui.branches[*idx];
+                // `+` like `git branch` for a checkout in another worktree
+                let cur = if b.is_current {
+                    "* "
+                } else if b.worktree.is_some() {
+                    "+ "
+                } else {
+                    "  "
+                };
+                let kind = if b.is_remote { "[R] " } else { "[L] " };
+
+                let indent = "  ".repeat((*depth).min(6));
+                let mut spans = vec![Span::raw(format!("{}{}{}{}", cur, kind, indent, b.name))];
+                if let Some(up) = &b.upstream {
+                    spans.push(Span::styled(
+                        format!("  {}", up),
+                        Style::default().fg(app.palette.border_inactive),
+                    ));
+                }
+                let (ahead, behind) = b.ahead_behind();
+                if ahead > 0 {
+                    spans.push(Span::styled(
+                        format!(" ↑{}", ahead),
+                        Style::default().fg(app.palette.diff_add_fg),
+                    ));
+                }
+                if behind > 0 {
+                    spans.push(Span::styled(
+                        format!(" ↓{}", behind),
+                        Style::default().fg(app.palette.diff_del_fg),
+                    ));
+                }
+                if b.upstream_gone() {
+                    spans.push(Span::styled(
+                        " gone",
+                        Style::default().fg(app.palette.diff_del_fg),
+                    ));
+                }
+                if let Some(path) = &b.worktree {
+                    spans.push(Span::styled(
+                        format!("  in {}", path),
+                        Style::default().fg(app.palette.accent_tertiary),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            }
+        })
+        .collect();
+
+    let list = List::new(list_items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_set(ratatui::symbols::border::PLAIN)
+                .border_style(Style::default().fg(app.palette.border_inactive))
+                .title(" Branches "),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(app.palette.accent_primary)
+                .fg(app.palette.btn_fg)
+                .add_modifier(Modifier::BOLD),
+        );
+    f.render_stateful_widget(list, rows[1], &mut app.branch_ui.list_state);
+
+    let list_inner = rows[1].inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    let start = app.branch_ui.list_state.offset();
+    let end = (start + list_inner.height as usize).min(app.branch_ui.items.len());
+    for (i, idx) in (start..end).enumerate() {
+        let rect = Rect::new(list_inner.x, list_inner.y + i as u16, list_inner.width, 1);
+        let selectable = matches!(
+            app.branch_ui.items.get(idx),
+            Some(BranchListItem::Branch { .. })
+        );
+        if !selectable {
+            continue;
+        }
+        let action = if app.branch_picker_mode == BranchPickerMode::LogView {
+            AppAction::SelectLogBranch(idx)
+        } else {
+            AppAction::SelectBranch(idx)
+        };
+        zones.push(ClickZone { rect, action });
+    }
+
+    let buttons: Vec<(&str, AppAction, Color)> = match app.branch_picker_mode {
+        BranchPickerMode::Checkout => vec![
+            (
+                " Checkout ",
+                AppAction::BranchCheckout,
+                app.palette.accent_secondary,
+            ),
+            (" New ", AppAction::BranchNew, app.palette.btn_bg),
+            (" Rename ", AppAction::BranchRename, app.palette.btn_bg),
+            (" Delete ", AppAction::BranchDelete, app.palette.btn_bg),
+            (" Upstream ", AppAction::BranchUpstream, app.palette.btn_bg),
+            (" Describe ", AppAction::BranchDescribe, app.palette.btn_bg),
+            (" Close ", AppAction::CloseBranchPicker, app.palette.btn_bg),
+        ],
+        BranchPickerMode::LogView => vec![
+            (
+                " View ",
+                AppAction::ConfirmLogBranchPicker,
+                app.palette.accent_secondary,
+            ),
+            (" Close ", AppAction::CloseBranchPicker, app.palette.btn_bg),
+        ],
+    };
+
+    if let Some(text) = description {
+        let lines: Vec<&str> = text.lines().collect();
+        let more = if lines.len() > 1 { " …" } else { "" };
+        let first = git::truncate_to_width(
+            lines.first().copied().unwrap_or_default(),
+            (rows[2].width as usize).saturating_sub(3 + more.len()),
+        );
+        f.render_widget(
+            Paragraph::new(format!("✎ {}{}", first, more))
+                .style(Style::default().fg(app.palette.border_inactive)),
+            rows[2],
+        );
+    }
+
+    let mut x = rows[3].x;
+    for (label, action, color) in buttons {
+        let w = label.len() as u16;
+        if x + w > rows[3].right() {
+            break;
+        }
+        let rect = Rect::new(x, rows[3].y, w, 1);
+        let style = Style::default()
+            .bg(color)
+            .fg(app.palette.btn_fg)
+            .add_modifier(Modifier::BOLD);
+        f.render_widget(Paragraph::new(label).style(style), rect);
+        zones.push(ClickZone { rect, action });
+        x += w + 2;
+    }
+
+    if let Some(msg) = app.branch_ui.status.as_deref() {
+        let used = x - rows[3].x;
+        f.render_widget(
+            Paragraph::new(msg).style(Style::default().fg(app.palette.btn_bg)),
+            Rect::new(x, rows[3].y, rows[3].width.saturating_sub(used), 1),
+        );
+    }
+}
+
+/// Name prompt for a branch at the selected commit
+pub(crate) fn render_new_branch_input(
+    f: &mut Frame,
+    app: &App,
+    input: &str,
+    area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    let from_commit = app.new_branch_from.is_some();
+    let w = area.width.min(50).saturating_sub(2).max(40);
+    let h = if from_commit { 8u16 } else { 7 };
+    let h = h.min(area.height.saturating_sub(2)).max(6);
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + (area.height.saturating_sub(h)) / 2;
+    let modal = Rect::new(x, y, w, h);
+
+    zones.push(ClickZone {
+        rect: area,
+        action: AppAction::None,
+    });
+
+    f.render_widget(Clear, modal);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.accent_primary))
+        .title(" New Branch ");
+    f.render_widget(block.clone(), modal);
+
+    let inner = modal.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); if from_commit { 4 } else { 3 }])
+        .split(inner);
+
+    let prompt = if app.new_branch_orphan {
+        "Orphan branch name (starts with no history):".to_string()
+    } else if let Some(start) = &app.new_branch_from {
+        format!("Branch name (starts at {}):", &start[..start.len().min(7)])
+    } else {
+        "Enter branch name:".to_string()
+    };
+    f.render_widget(
+        Paragraph::new(prompt).style(Style::default().fg(app.palette.fg)),
+        rows[0],
+    );
+
+    let input_style = Style::default()
+        .fg(app.palette.fg)
+        .bg(app.palette.selection_bg);
+    let display_input = format!("{}_", input);
+    f.render_widget(Paragraph::new(display_input).style(input_style), rows[1]);
+
+    let hint = if from_commit {
+        let checkbox = if app.new_branch_checkout {
+            "▣"
+        } else {
+            "□"
+        };
+        f.render_widget(
+            Paragraph::new(format!("{} Check it out", checkbox))
+                .style(Style::default().fg(app.palette.fg)),
+            rows[2],
+        );
+        zones.push(ClickZone {
+            rect: rows[2],
+            action: AppAction::ToggleNewBranchCheckout,
+        });
+        "Enter to create · Tab toggles · Esc to cancel"
+    } else {
+        "Enter to create · Esc to cancel"
+    };
+    f.render_widget(
+        Paragraph::new(hint).style(Style::default().fg(app.palette.border_inactive)),
+        rows[rows.len() - 1],
+    );
+}
+
+/// Name, upstream or description prompt for the selected branch
+pub(crate) fn render_branch_input(
+    f: &mut Frame,
+    app: &App,
+    input: &BranchInput,
+    area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    let multiline = matches!(input.kind, BranchInputKind::Description(_));
+    let w = area
+        .width
+        .min(if multiline { 72 } else { 60 })
+        .saturating_sub(2)
+        .max(40);
+    let h = if multiline { 14u16 } else { 7 };
+    let h = h.min(area.height.saturating_sub(2)).max(6);
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + (area.height.saturating_sub(h)) / 2;
+    let modal = Rect::new(x, y, w, h);
+
+    zones.push(ClickZone {
+        rect: area,
+        action: AppAction::None,
+    });
+
+    f.render_widget(Clear, modal);
+
+    let (title, prompt, hint) = match &input.kind {
+        BranchInputKind::Rename(old) => (
+            " Rename Branch ",
+            format!("New name for `{}`:", old),
+            "Enter to rename · Esc to cancel",
+        ),
+        BranchInputKind::Upstream(branch) => (
+            " Set Upstream ",
+            format!("Upstream for `{}` (empty to stop tracking):", branch),
+            "Enter to set · Esc to cancel",
+        ),
+        BranchInputKind::Description(branch) => (
+            " Branch Description ",
+            format!("Description of `{}` (empty to remove):", branch),
+            "Ctrl+S to save · Enter for a new line · Esc to cancel",
+        ),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.accent_primary))
+        .title(title);
+    f.render_widget(block.clone(), modal);
+
+    let inner = modal.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            if multiline {
+                Constraint::Min(1)
+            } else {
+                Constraint::Length(1)
+            },
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    f.render_widget(
+        Paragraph::new(prompt).style(Style::default().fg(app.palette.fg)),
+        rows[0],
+    );
+
+    let input_style = Style::default()
+        .fg(app.palette.fg)
+        .bg(app.palette.selection_bg);
+    // Keep the end of a long description in view
+    let display_input = format!("{}_", input.text);
+    let lines: Vec<&str> = display_input.split('\n').collect();
+    let skip = lines.len().saturating_sub(rows[1].height as usize);
+    f.render_widget(
+        Paragraph::new(lines[skip..].join("\n")).style(input_style),
+        rows[1],
+    );
+
+    f.render_widget(
+        Paragraph::new(hint).style(Style::default().fg(app.palette.border_inactive)),
+        rows[2],
+    );
+}
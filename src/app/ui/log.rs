@@ -0,0 +1,277 @@
+//! Dialogs over the Log tab: inspect, reset, find in commit and go to commit
+//!
+//! The tab itself is drawn by [`super::tabs::render_log_tab`].
+
+use super::*;
+
+/// The inspect popup for the selected Log entry
+pub(crate) fn render_log_inspect(f: &mut Frame, app: &App, area: Rect, zones: &mut Vec<ClickZone>) {
+    zones.push(ClickZone {
+        rect: area,
+        action: AppAction::LogCloseInspect,
+    });
+
+    let w = area.width.min(90).saturating_sub(2).max(50);
+    let h = area.height.saturating_sub(4).min(28).max(12);
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + (area.height.saturating_sub(h)) / 2;
+    let modal = Rect::new(x, y, w, h);
+
+    f.render_widget(Clear, modal);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.accent_secondary))
+        .title(app.log_ui.inspect.title.as_str());
+    f.render_widget(block.clone(), modal);
+
+    let inner = modal.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+
+    let body_h = inner.height.saturating_sub(1);
+    let body_area = Rect::new(inner.x, inner.y, inner.width, body_h);
+    let buttons_y = inner.y + body_h;
+
+    let para = Paragraph::new(app.log_ui.inspect.body.as_str())
+        .wrap(Wrap { trim: false })
+        .scroll((app.log_ui.inspect.scroll_y, 0));
+    f.render_widget(para, body_area);
+
+    let primary_label = match app.log_ui.subtab {
+        LogSubTab::Commands => " Copy Cmd (y) ".to_string(),
+        _ => " Copy SHA (y) ".to_string(),
+    };
+    let secondary_label = match app.log_ui.subtab {
+        LogSubTab::Commands => " Copy Output (Y) ".to_string(),
+        _ => " Copy Subject (Y) ".to_string(),
+    };
+
+    let mut bx = inner.x;
+    for (label, action, color) in [
+        (
+            primary_label.as_str(),
+            AppAction::LogInspectCopyPrimary,
+            app.palette.accent_primary,
+        ),
+        (
+            secondary_label.as_str(),
+            AppAction::LogInspectCopySecondary,
+            app.palette.accent_tertiary,
+        ),
+        (" Close ", AppAction::LogCloseInspect, app.palette.btn_bg),
+    ] {
+        let bw = label.len() as u16;
+        let rect = Rect::new(bx, buttons_y, bw, 1);
+        let style = Style::default()
+            .bg(color)
+            .fg(app.palette.btn_fg)
+            .add_modifier(Modifier::BOLD);
+        f.render_widget(Paragraph::new(label).style(style), rect);
+        zones.push(ClickZone { rect, action });
+        bx += bw + 2;
+    }
+}
+
+/// Soft, mixed or hard reset to the selected commit
+pub(crate) fn render_reset_picker(
+    f: &mut Frame,
+    app: &App,
+    picker: &ResetPicker,
+    area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    let w = area.width.min(72).saturating_sub(2).max(48);
+    let h = 11u16.min(area.height.saturating_sub(2)).max(6);
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + (area.height.saturating_sub(h)) / 2;
+    let modal = Rect::new(x, y, w, h);
+
+    zones.push(ClickZone {
+        rect: area,
+        action: AppAction::None,
+    });
+
+    f.render_widget(Clear, modal);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.accent_primary))
+        .title(" Reset To Commit ");
+    f.render_widget(block.clone(), modal);
+
+    let inner = modal.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    let dim = Style::default().fg(app.palette.border_inactive);
+    let text = Style::default().fg(app.palette.fg);
+    let selected = Style::default()
+        .fg(app.palette.fg)
+        .bg(app.palette.selection_bg);
+    let mut lines = vec![
+        Line::styled(format!("{}  {}", picker.short, picker.subject), dim),
+        Line::styled("Move the current branch here, keeping changes:", text),
+    ];
+    for mode in git_ops::ResetMode::ALL {
+        let what = match mode {
+            git_ops::ResetMode::Soft => "staged",
+            git_ops::ResetMode::Mixed => "unstaged, in the working tree",
+            git_ops::ResetMode::Hard => "none, every change is discarded",
+        };
+        lines.push(Line::styled(
+            format!("  {:<6} {}", mode.label(), what),
+            if mode == picker.mode { selected } else { text },
+        ));
+    }
+    lines.push(Line::raw(""));
+    lines.push(Line::styled("s/m/h or Enter to reset · Esc to cancel", dim));
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Find in the selected commit's diff
+pub(crate) fn render_commit_find(
+    f: &mut Frame,
+    app: &App,
+    find: &CommitFind,
+    area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    // Kept low so the diff line it jumps to stays in view above it
+    let list_rows = find.matches.len().clamp(1, 8) as u16;
+    let w = area.width.min(90).saturating_sub(2).max(48);
+    let h = (list_rows + 6).min(area.height.saturating_sub(2)).max(6);
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + area.height.saturating_sub(h + 2);
+    let modal = Rect::new(x, y, w, h);
+
+    zones.push(ClickZone {
+        rect: area,
+        action: AppAction::None,
+    });
+
+    f.render_widget(Clear, modal);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.accent_primary))
+        .title(" Find in This Commit ");
+    f.render_widget(block.clone(), modal);
+
+    let inner = modal.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    let dim = Style::default().fg(app.palette.border_inactive);
+    let text = Style::default().fg(app.palette.fg);
+    let selected = Style::default()
+        .fg(app.palette.fg)
+        .bg(app.palette.selection_bg);
+    let row = |i: u16| Rect::new(inner.x, inner.y + i, inner.width, 1);
+    f.render_widget(
+        Paragraph::new(format!("{}_", find.query)).style(selected),
+        row(0),
+    );
+    let count = match find.matches.len() {
+        _ if find.query.is_empty() => "Type to search the changed lines".to_string(),
+        0 => "No match".to_string(),
+        1 => "1 match".to_string(),
+        n => format!("{} of {} matches", find.selected + 1, n),
+    };
+    f.render_widget(Paragraph::new(count).style(dim), row(1));
+
+    let rows = inner.height.saturating_sub(4) as usize;
+    let skip = (find.selected + 1).saturating_sub(rows);
+    for (i, m) in find.matches.iter().enumerate().skip(skip).take(rows) {
+        let rect = row(2 + (i - skip) as u16);
+        let place = format!("{}:{}", m.path, m.number);
+        let code = app
+            .log_ui
+            .diff_lines
+            .get(m.line)
+            .map(|l| l.trim_end())
+            .unwrap_or_default();
+        let place_w = (inner.width as usize / 2).max(12);
+        let spans = vec![
+            Span::raw(format!("{}  ", truncate_middle(&place, place_w))),
+            Span::styled(
+                git::truncate_to_width(code, (inner.width as usize).saturating_sub(place_w + 2)),
+                dim,
+            ),
+        ];
+        let style = if i == find.selected { selected } else { text };
+        f.render_widget(Paragraph::new(Line::from(spans).style(style)), rect);
+        zones.push(ClickZone {
+            rect,
+            action: AppAction::PickCommitFind(i),
+        });
+    }
+    f.render_widget(
+        Paragraph::new("↑/↓ to jump · Enter to stay there · Esc to close").style(dim),
+        row(inner.height.saturating_sub(1)),
+    );
+}
+
+/// Hash or ref prompt for going to a commit
+pub(crate) fn render_goto_commit_input(
+    f: &mut Frame,
+    app: &App,
+    input: &str,
+    area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    let w = area.width.min(50).saturating_sub(2).max(40);
+    let h = 7u16.min(area.height.saturating_sub(2)).max(6);
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + (area.height.saturating_sub(h)) / 2;
+    let modal = Rect::new(x, y, w, h);
+
+    zones.push(ClickZone {
+        rect: area,
+        action: AppAction::None,
+    });
+
+    f.render_widget(Clear, modal);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.accent_primary))
+        .title(" Go to commit ");
+    f.render_widget(block.clone(), modal);
+
+    let inner = modal.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    f.render_widget(
+        Paragraph::new("SHA, tag or ref (HEAD~5, main@{2}):")
+            .style(Style::default().fg(app.palette.fg)),
+        rows[0],
+    );
+
+    let input_style = Style::default()
+        .fg(app.palette.fg)
+        .bg(app.palette.selection_bg);
+    let display_input = format!("{}_", input);
+    f.render_widget(Paragraph::new(display_input).style(input_style), rows[1]);
+
+    f.render_widget(
+        Paragraph::new("Enter to jump · Esc to cancel")
+            .style(Style::default().fg(app.palette.border_inactive)),
+        rows[2],
+    );
+}
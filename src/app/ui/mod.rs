@@ -0,0 +1,4088 @@
+//! Drawing the app: the tabs, the footer and every dialog on top
+//!
+//! [`draw_ui`] renders a frame from the app's state and returns the click zones
+//! for the next mouse event. The branch, stash, snapshot and Log dialogs are drawn in
+//! their own modules.
+
+mod branches;
+mod log;
+mod snapshots;
+mod stash;
+pub mod tabs;
+
+use super::*;
+
+/// Draw the shared yes/no dialog; clicking outside it cancels.
+fn render_confirm_dialog(
+    f: &mut ratatui::Frame,
+    app: &App,
+    area: Rect,
+    dialog: &confirm::ConfirmDialog<ConfirmAction>,
+    zones: &mut Vec<ClickZone>,
+) {
+    let w = area.width.min(70).saturating_sub(2).max(40);
+    let h = (dialog.lines.len() as u16 + 6)
+        .min(area.height.saturating_sub(2))
+        .max(7);
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + (area.height.saturating_sub(h)) / 2;
+    let modal = Rect::new(x, y, w, h);
+
+    zones.push(ClickZone {
+        rect: area,
+        action: AppAction::CancelConfirm,
+    });
+
+    f.render_widget(Clear, modal);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.btn_bg))
+        .title(dialog.title.as_str());
+    f.render_widget(block.clone(), modal);
+
+    let inner = modal.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+
+    let mut lines: Vec<Line> = dialog.lines.iter().map(|l| Line::raw(l.as_str())).collect();
+    lines.push(Line::raw(""));
+    lines.push(Line::raw("Confirm? (y/n)"));
+
+    let text_h = inner.height.saturating_sub(2);
+    f.render_widget(
+        Paragraph::new(lines)
+            .style(Style::default().fg(app.palette.fg))
+            .wrap(Wrap { trim: false }),
+        Rect::new(inner.x, inner.y, inner.width, text_h),
+    );
+
+    let confirm_label = format!(" {} ", dialog.confirm_label);
+    let buttons_y = inner.y + inner.height.saturating_sub(1);
+    let mut bx = inner.x;
+    for (label, action, color) in [
+        (
+            confirm_label.as_str(),
+            AppAction::AcceptConfirm,
+            app.palette.btn_bg,
+        ),
+        (
+            " Cancel ",
+            AppAction::CancelConfirm,
+            app.palette.border_inactive,
+        ),
+    ] {
+        let bw = label.len() as u16;
+        let style = Style::default()
+            .bg(color)
+            .fg(app.palette.btn_fg)
+            .add_modifier(Modifier::BOLD);
+        let rect = Rect::new(bx, buttons_y, bw, 1);
+        f.render_widget(Paragraph::new(label).style(style), rect);
+        zones.push(ClickZone { rect, action });
+        bx += bw + 2;
+    }
+}
+
+/// One-line warning about lock files left in the git dir, with a remove button.
+/// One-line warning above the tab content, with a single button on the right.
+/// Author and date override rows of the commit drawer.
+/// Type, scope and breaking-change flag of the guided conventional-commit header.
+fn render_commit_guided(f: &mut Frame, app: &App, area: Rect, zones: &mut Vec<ClickZone>) {
+    let Some(parts) = &app.commit.conventional else {
+        return;
+    };
+    let label = |focused: bool| {
+        if focused {
+            Style::default()
+                .fg(app.palette.accent_primary)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(app.palette.border_inactive)
+        }
+    };
+    let value = Style::default()
+        .fg(app.palette.fg)
+        .add_modifier(Modifier::BOLD);
+    let kind = commit::CONVENTIONAL_TYPES[parts.kind.min(commit::CONVENTIONAL_TYPES.len() - 1)];
+    let scope = if parts.scope.is_empty() && app.commit.focus != CommitFocus::Scope {
+        Span::styled("none", label(false).add_modifier(Modifier::ITALIC))
+    } else {
+        Span::styled(parts.scope.clone(), value)
+    };
+    let header_len = app
+        .commit
+        .message
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count();
+    let count_style = if header_len > commit::HEADER_LIMIT {
+        Style::default()
+            .fg(app.palette.btn_bg)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        label(false)
+    };
+
+    // Each piece with what clicking it does
+    let pieces: Vec<(Span, AppAction)> = vec![
+        (
+            Span::styled("Type  ", label(app.commit.focus == CommitFocus::Type)),
+            AppAction::CycleCommitType(0),
+        ),
+        (
+            Span::styled("◂ ", label(false)),
+            AppAction::CycleCommitType(-1),
+        ),
+        (
+            Span::styled(format!("{:<8}", kind), value),
+            AppAction::CycleCommitType(1),
+        ),
+        (
+            Span::styled(" ▸", label(false)),
+            AppAction::CycleCommitType(1),
+        ),
+        (
+            Span::styled("    Scope  ", label(app.commit.focus == CommitFocus::Scope)),
+            AppAction::FocusCommitScope,
+        ),
+        (scope, AppAction::FocusCommitScope),
+        (
+            Span::styled(
+                if parts.breaking {
+                    "    Breaking ▣"
+                } else {
+                    "    Breaking □"
+                },
+                if parts.breaking {
+                    Style::default()
+                        .fg(app.palette.btn_bg)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    label(false)
+                },
+            ),
+            AppAction::ToggleBreakingChange,
+        ),
+        (
+            Span::styled(
+                format!("    {}/{}", header_len, commit::HEADER_LIMIT),
+                count_style,
+            ),
+            AppAction::None,
+        ),
+    ];
+
+    let mut x = area.x;
+    let mut scope_end = None;
+    for (span, action) in &pieces {
+        let w = (display_width(&span.content) as u16).min(area.right().saturating_sub(x));
+        if *action == AppAction::FocusCommitScope {
+            scope_end = Some(x + w);
+        }
+        if w > 0 && *action != AppAction::None {
+            zones.push(ClickZone {
+                rect: Rect::new(x, area.y, w, 1),
+                action: action.clone(),
+            });
+        }
+        x += w;
+    }
+    let spans: Vec<Span> = pieces.into_iter().map(|(span, _)| span).collect();
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+    if app.commit.focus == CommitFocus::Scope
+        && let Some(end) = scope_end
+    {
+        f.set_cursor_position((end.min(area.right().saturating_sub(1)), area.y));
+    }
+}
+
+fn render_commit_advanced(f: &mut Frame, app: &App, area: Rect, zones: &mut Vec<ClickZone>) {
+    let label_style = Style::default().fg(app.palette.border_inactive);
+    let placeholder = Style::default()
+        .fg(app.palette.border_inactive)
+        .add_modifier(Modifier::ITALIC);
+    let button = |color: Color| {
+        Style::default()
+            .bg(color)
+            .fg(app.palette.btn_fg)
+            .add_modifier(Modifier::BOLD)
+    };
+
+    let author_row = Rect::new(area.x, area.y, area.width, 1);
+    let author = match &app.commit.author {
+        Some(a) => Span::styled(a.clone(), Style::default().fg(app.palette.fg)),
+        None => Span::styled("default identity", placeholder),
+    };
+    let author_w = (display_width(&author.content) as u16 + 9).min(area.width);
+    f.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("Author   ", label_style),
+            author,
+        ])),
+        author_row,
+    );
+    let mut x = author_row.x + author_w + 1;
+    for (label, action, color) in [
+        (
+            " Pick… ",
+            AppAction::PickCommitAuthor,
+            app.palette.accent_primary,
+        ),
+        (
+            " Co-authors… ",
+            AppAction::PickCoAuthors,
+            app.palette.accent_primary,
+        ),
+        (
+            " Reset ",
+            AppAction::ResetCommitOverrides,
+            app.palette.btn_bg,
+        ),
+    ] {
+        let w = label.chars().count() as u16;
+        if x + w > author_row.right() {
+            break;
+        }
+        let rect = Rect::new(x, author_row.y, w, 1);
+        f.render_widget(Paragraph::new(label).style(button(color)), rect);
+        zones.push(ClickZone { rect, action });
+        x += w + 1;
+    }
+
+    let date_row = Rect::new(area.x, area.y + 1, area.width, 1);
+    let focused = app.commit.focus == CommitFocus::Date;
+    let date = if app.commit.date.is_empty() {
+        Span::styled("now (e.g. 2024-03-01 14:00 or \"2 days ago\")", placeholder)
+    } else {
+        Span::styled(app.commit.date.clone(), Style::default().fg(app.palette.fg))
+    };
+    let date_label = if focused {
+        Style::default()
+            .fg(app.palette.accent_primary)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        label_style
+    };
+    f.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("Date     ", date_label),
+            date,
+        ])),
+        date_row,
+    );
+    zones.push(ClickZone {
+        rect: date_row,
+        action: AppAction::FocusCommitDate,
+    });
+    if focused {
+        let col = display_width(&app.commit.date) as u16 + 9;
+        f.set_cursor_position((
+            (date_row.x + col).min(date_row.right().saturating_sub(1)),
+            date_row.y,
+        ));
+    }
+}
+
+fn render_banner(
+    f: &mut Frame,
+    app: &App,
+    area: Rect,
+    text: &str,
+    button: &str,
+    action: AppAction,
+    zones: &mut Vec<ClickZone>,
+) {
+    let button_w = display_width(button) as u16;
+    let text_w = area.width.saturating_sub(button_w + 1);
+    let warn = Style::default()
+        .fg(app.palette.btn_fg)
+        .bg(app.palette.accent_secondary)
+        .add_modifier(Modifier::BOLD);
+
+    f.render_widget(Block::default().style(warn), area);
+    f.render_widget(
+        Paragraph::new(truncate_middle(text, text_w as usize)).style(warn),
+        Rect::new(area.x, area.y, text_w, 1),
+    );
+    let rect = Rect::new(area.right().saturating_sub(button_w), area.y, button_w, 1);
+    f.render_widget(
+        Paragraph::new(button.to_string()).style(
+            Style::default()
+                .fg(app.palette.btn_fg)
+                .bg(app.palette.btn_bg)
+                .add_modifier(Modifier::BOLD),
+        ),
+        rect,
+    );
+    zones.push(ClickZone { rect, action });
+}
+
+/// Warning about lock files left in the git dir, with a remove button.
+fn render_lock_banner(f: &mut Frame, app: &App, area: Rect, zones: &mut Vec<ClickZone>) {
+    let names: Vec<String> = app
+        .git_locks
+        .iter()
+        .map(|p| {
+            app.git
+                .repo_root
+                .as_deref()
+                .and_then(|root| p.strip_prefix(root).ok())
+                .unwrap_or(p)
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    let text = format!(
+        " ⚠ {} exists: git is running elsewhere or crashed, and operations will fail ",
+        names.join(", ")
+    );
+    render_banner(
+        f,
+        app,
+        area,
+        &text,
+        "[Remove stale lock]",
+        AppAction::RemoveStaleLocks,
+        zones,
+    );
+}
+
+/// Nothing of the repository, just how to get back in
+fn render_lock_screen(f: &mut Frame, app: &App, lock: &lock_screen::LockScreen, area: Rect) {
+    let dim = Style::default().fg(app.palette.border_inactive);
+    let mut lines = vec![
+        Line::styled(
+            "🔒 lzgit is locked",
+            Style::default()
+                .fg(app.palette.accent_primary)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Line::raw(""),
+    ];
+    if lock.needs_passphrase() {
+        lines.push(Line::styled(
+            format!("Passphrase: {}", "•".repeat(lock.typed.chars().count())),
+            Style::default().fg(app.palette.fg),
+        ));
+        if lock.wrong {
+            lines.push(Line::styled(
+                "Wrong passphrase",
+                Style::default().fg(app.palette.diff_del_fg),
+            ));
+        }
+        lines.push(Line::raw(""));
+        lines.push(Line::styled("Type the passphrase and press Enter", dim));
+    } else {
+        lines.push(Line::styled("Press any key to unlock", dim));
+    }
+    let h = lines.len() as u16;
+    let y = area.y + area.height.saturating_sub(h) / 2;
+    f.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center),
+        Rect::new(area.x, y, area.width, h.min(area.height)),
+    );
+}
+
+pub(crate) fn draw_ui(f: &mut Frame, app: &mut App) -> Vec<ClickZone> {
+    let mut zones = Vec::new();
+    let area = f.area();
+
+    f.render_widget(Block::default().bg(app.palette.bg), area);
+    if let Some(lock) = &app.lock_screen {
+        render_lock_screen(f, app, lock, area);
+        return zones;
+    }
+
+    let main_layout = if app.current_tab == Tab::Git {
+        let commit_h = match (app.commit.open, app.commit.advanced) {
+            (false, _) => 1,
+            (true, false) => 11,
+            (true, true) => 13,
+        } + u16::from(app.commit.open && app.commit.conventional.is_some());
+        let footer_h = if app.git_zoom_diff { 0 } else { 3 };
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(commit_h),
+                Constraint::Length(footer_h),
+            ])
+            .split(area)
+    } else {
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(3),
+            ])
+            .split(area)
+    };
+
+    let top_bar = main_layout[0];
+    let mut content_area = main_layout[1];
+    if !app.git_locks.is_empty() && app.current_tab != Tab::Terminal && content_area.height > 4 {
+        let banner = Rect::new(content_area.x, content_area.y, content_area.width, 1);
+        content_area.y += 1;
+        content_area.height -= 1;
+        render_lock_banner(f, app, banner, &mut zones);
+    }
+    if let Some(notice) = &app.git_notice
+        && app.current_tab != Tab::Terminal
+        && content_area.height > 4
+    {
+        let banner = Rect::new(content_area.x, content_area.y, content_area.width, 1);
+        content_area.y += 1;
+        content_area.height -= 1;
+        let text = format!(" ⚠ {} ", notice);
+        render_banner(
+            f,
+            app,
+            banner,
+            &text,
+            "[Dismiss]",
+            AppAction::DismissGitNotice,
+            &mut zones,
+        );
+    }
+    app.content_area = content_area;
+    let (commit_area, footer_area) = if app.current_tab == Tab::Git {
+        (Some(main_layout[2]), main_layout[3])
+    } else {
+        (None, main_layout[2])
+    };
+
+    let top_block = Block::default().borders(Borders::BOTTOM).border_style(
+        Style::default()
+            .fg(app.palette.border_inactive)
+            .bg(app.palette.bg),
+    );
+    f.render_widget(top_block.clone(), top_bar);
+
+    let tabs_y = top_bar.y;
+    let mut tab_x = top_bar.x + 1;
+    for (label, tab) in [
+        (" Git ", Tab::Git),
+        (" History ", Tab::Log),
+        (" Explorer ", Tab::Explorer),
+        (" Terminal ", Tab::Terminal),
+    ] {
+        if tab == Tab::Terminal && !app.features.terminal {
+            continue;
+        }
+        let width = label.len() as u16;
+        let is_active = app.current_tab == tab;
+        let style = if is_active {
+            Style::default()
+                .bg(app.palette.accent_primary)
+                .fg(app.palette.btn_fg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().bg(app.palette.bg).fg(app.palette.fg)
+        };
+        f.render_widget(
+            Paragraph::new(label).style(style),
+            Rect::new(tab_x, tabs_y, width, 1),
+        );
+        zones.push(ClickZone {
+            rect: Rect::new(tab_x, tabs_y, width, 1),
+            action: AppAction::SwitchTab(tab),
+        });
+        tab_x += width + 1;
+    }
+
+    if let Some((_, clock)) = &app.clock
+        && !clock.is_empty()
+    {
+        let text = format!(" {} ", clock);
+        let w = display_width(&text) as u16;
+        let x = top_bar.right().saturating_sub(w + 1);
+        if x > tab_x {
+            f.render_widget(
+                Paragraph::new(text).style(Style::default().fg(app.palette.size_color)),
+                Rect::new(x, tabs_y, w, 1),
+            );
+        }
+    }
+
+    let second_row_y = top_bar.y + 1;
+
+    match app.current_tab {
+        Tab::Explorer => {
+            let mut breadcrumb_x = top_bar.x + 2;
+            let breadcrumb_y = second_row_y;
+
+            let home_txt = " 🏠 Home ";
+            let home_width = home_txt.len() as u16;
+            f.render_widget(
+                Paragraph::new(Span::styled(
+                    home_txt,
+                    Style::default().fg(app.palette.accent_secondary).bold(),
+                )),
+                Rect::new(breadcrumb_x, breadcrumb_y, home_width, 1),
+            );
+            zones.push(ClickZone {
+                rect: Rect::new(breadcrumb_x, breadcrumb_y, home_width, 1),
+                action: AppAction::Navigate(env::home_dir().unwrap_or_else(|| PathBuf::from("/"))),
+            });
+            breadcrumb_x += home_width;
+
+            let path_str = app.current_path.to_string_lossy();
+            let components: Vec<&str> = path_str
+                .split(std::path::MAIN_SEPARATOR)
+                .filter(|s| !s.is_empty())
+                .collect();
+
+            f.render_widget(
+                Paragraph::new(Span::raw(" / ")),
+                Rect::new(breadcrumb_x, breadcrumb_y, 3, 1),
+            );
+            breadcrumb_x += 3;
+
+            let mut acc_path = PathBuf::from("/");
+            let crumbs: Vec<(String, PathBuf)> = components
+                .iter()
+                .enumerate()
+                .map(|(i, part)| {
+                    if cfg!(windows) && i == 0 {
+                        acc_path = PathBuf::from(part);
+                    } else {
+                        acc_path.push(part);
+                    }
+                    (format!(" {} ", part), acc_path.clone())
+                })
+                .collect();
+
+            // Collapse middle components into "…" when the full trail does not fit
+            let available = top_bar
+                .right()
+                .saturating_sub(2)
+                .saturating_sub(breadcrumb_x) as usize;
+            let hidden = breadcrumb_hidden_range(&crumbs, available);
+
+            let ellipsis = " … ";
+            for (i, (label, path)) in crumbs.iter().enumerate() {
+                if hidden.contains(&i) {
+                    if i != hidden.start {
+                        continue;
+                    }
+                    let w = display_width(ellipsis) as u16;
+                    let rect = Rect::new(breadcrumb_x, breadcrumb_y, w, 1);
+                    f.render_widget(
+                        Paragraph::new(Span::styled(
+                            ellipsis,
+                            Style::default()
+                                .fg(app.palette.accent_secondary)
+                                .add_modifier(Modifier::BOLD),
+                        )),
+                        rect,
+                    );
+                    zones.push(ClickZone {
+                        rect,
+                        action: AppAction::ShowHiddenCrumbs(
+                            crumbs[hidden.clone()]
+                                .iter()
+                                .map(|(_, p)| p.clone())
+                                .collect(),
+                        ),
+                    });
+                    breadcrumb_x += w;
+                } else {
+                    let width = display_width(label) as u16;
+                    if breadcrumb_x + width > top_bar.width - 2 {
+                        break;
+                    }
+
+                    let style = if i == crumbs.len() - 1 {
+                        Style::default()
+                            .fg(app.palette.accent_primary)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(app.palette.fg)
+                    };
+
+                    f.render_widget(
+                        Paragraph::new(Span::styled(label.as_str(), style)),
+                        Rect::new(breadcrumb_x, breadcrumb_y, width, 1),
+                    );
+
+                    zones.push(ClickZone {
+                        rect: Rect::new(breadcrumb_x, breadcrumb_y, width, 1),
+                        action: AppAction::Navigate(path.clone()),
+                    });
+
+                    breadcrumb_x += width;
+                }
+
+                if i < crumbs.len() - 1 {
+                    f.render_widget(
+                        Paragraph::new(Span::styled(
+                            " › ",
+                            Style::default().fg(app.palette.border_inactive),
+                        )),
+                        Rect::new(breadcrumb_x, breadcrumb_y, 3, 1),
+                    );
+                    breadcrumb_x += 3;
+                }
+            }
+        }
+        Tab::Git => {
+            let repo = app
+                .git
+                .repo_root
+                .as_ref()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| "(not a git repo)".to_string());
+            let branch = if let Some(short) = &app.git.detached {
+                format!("HEAD detached at {}", short)
+            } else if app.git.branch.is_empty() {
+                "(unknown)".to_string()
+            } else {
+                app.git.branch.clone()
+            };
+            let op = match app.git_operation {
+                Some(GitOperation::Rebase) => "  REBASE ",
+                Some(GitOperation::Merge) => "  MERGE ",
+                Some(GitOperation::Am) => "  AM ",
+                Some(GitOperation::CherryPick) => "  CHERRY-PICK ",
+                Some(GitOperation::Revert) => "  REVERT ",
+                None => "",
+            };
+
+            let width = top_bar.width.saturating_sub(2);
+            let base_x = top_bar.x + 2;
+            let segments = app.top_bar;
+            let enabled = app.pending_job.is_none();
+
+            // Buttons are drawn right-aligned below; leave room for them
+            let mut button_labels: Vec<&str> = match app.git_operation {
+                Some(GitOperation::Merge) => vec!["[Continue]", "[Abort]"],
+                Some(
+                    GitOperation::Rebase
+                    | GitOperation::Am
+                    | GitOperation::CherryPick
+                    | GitOperation::Revert,
+                ) => {
+                    vec!["[Continue]", "[Skip]", "[Abort]"]
+                }
+                None => Vec::new(),
+            };
+            // Shows which remote Push, Pull and Fetch use when one was picked
+            let remote_label = app.target_remote().map(|r| format!("[@{}]", r));
+            if app.git.repo_root.is_some() {
+                button_labels.extend(["[Push]", "[Pull]", "[Fetch]"]);
+                button_labels.extend(remote_label.as_deref());
+                if !app.superprojects.is_empty() {
+                    button_labels.push("[↰ Parent]");
+                }
+                if app.git.detached.is_some() {
+                    button_labels.push("[+ Branch Here]");
+                }
+            }
+            let buttons_w: usize = button_labels.iter().map(|l| display_width(l) + 1).sum();
+
+            let branch_text = format!("{} ▼", branch);
+            let branch_segment = if segments.branch {
+                format!("   Branch: {}", branch_text)
+            } else {
+                String::new()
+            };
+            let ahead_behind = if segments.ahead_behind {
+                format!("   ↑{} ↓{}", app.git.ahead, app.git.behind)
+            } else {
+                String::new()
+            };
+            let op = if segments.operation { op } else { "" };
+            let queued = if app.job_queue.is_empty() {
+                String::new()
+            } else {
+                format!("   ⧗ {} queued", app.job_queue.len())
+            };
+            let refresh_icon = "⟳";
+            let fixed_w = display_width(" Repo: ")
+                + display_width(&branch_segment)
+                + display_width(&ahead_behind)
+                + display_width(op)
+                + display_width(&queued)
+                + 2
+                + 3;
+            let repo_max = (width as usize).saturating_sub(buttons_w + fixed_w).max(8);
+            let repo_shown = truncate_middle(&repo, repo_max);
+
+            let mut spans: Vec<Span> = Vec::new();
+            let mut x = base_x;
+            spans.push(Span::raw(" Repo: "));
+            x += display_width(" Repo: ") as u16;
+
+            let repo_rect = Rect::new(x, second_row_y, display_width(&repo_shown) as u16, 1);
+            spans.push(Span::raw(repo_shown.clone()));
+            x += repo_rect.width;
+            if app.git.repo_root.is_some() {
+                zones.push(ClickZone {
+                    rect: repo_rect,
+                    action: AppAction::CopyRepoPath,
+                });
+            }
+
+            if segments.branch {
+                spans.push(Span::raw("   Branch: "));
+                x += display_width("   Branch: ") as u16;
+                let branch_w = display_width(&branch_text) as u16;
+                spans.push(Span::styled(
+                    branch_text.clone(),
+                    Style::default()
+                        .fg(if app.git.detached.is_some() {
+                            app.palette.diff_del_fg
+                        } else {
+                            app.palette.accent_secondary
+                        })
+                        .add_modifier(Modifier::BOLD),
+                ));
+                zones.push(ClickZone {
+                    rect: Rect::new(x, second_row_y, branch_w, 1),
+                    action: AppAction::OpenBranchPicker,
+                });
+                x += branch_w;
+            }
+
+            let middle = format!("{}{}{}  ", ahead_behind, op, queued);
+            x += display_width(&middle) as u16;
+            spans.push(Span::raw(middle));
+            spans.push(Span::styled(
+                format!(" {} ", refresh_icon),
+                Style::default()
+                    .fg(app.palette.btn_fg)
+                    .bg(app.palette.accent_secondary)
+                    .add_modifier(Modifier::BOLD),
+            ));
+
+            f.render_widget(
+                Paragraph::new(Line::from(spans)).style(Style::default().fg(app.palette.fg)),
+                Rect::new(base_x, second_row_y, width, 1),
+            );
+
+            let refresh_rect = Rect::new(x, second_row_y, 3, 1);
+            if enabled {
+                zones.push(ClickZone {
+                    rect: refresh_rect,
+                    action: AppAction::RefreshGit,
+                });
+            }
+
+            // Full path tooltip over the bar's bottom border while hovering a shortened path
+            if repo_shown != repo
+                && app
+                    .hover_pos
+                    .is_some_and(|(col, row)| repo_rect.contains(Position::new(col, row)))
+            {
+                let tip = format!(" {} ", repo);
+                let tip_w =
+                    (display_width(&tip) as u16).min(top_bar.right().saturating_sub(repo_rect.x));
+                f.render_widget(
+                    Paragraph::new(tip).style(
+                        Style::default()
+                            .fg(app.palette.fg)
+                            .bg(app.palette.selection_bg),
+                    ),
+                    Rect::new(repo_rect.x, second_row_y + 1, tip_w, 1),
+                );
+            }
+
+            let mut cursor = base_x + width;
+
+            if let Some(op) = app.git_operation {
+                let buttons: Vec<(&str, AppAction, Color)> = match op {
+                    GitOperation::Merge => vec![
+                        (
+                            "[Continue]",
+                            AppAction::MergeContinue,
+                            app.palette.accent_tertiary,
+                        ),
+                        ("[Abort]", AppAction::MergeAbort, app.palette.btn_bg),
+                    ],
+                    GitOperation::Rebase => vec![
+                        (
+                            "[Continue]",
+                            AppAction::RebaseContinue,
+                            app.palette.accent_tertiary,
+                        ),
+                        (
+                            "[Skip]",
+                            AppAction::RebaseSkip,
+                            app.palette.accent_secondary,
+                        ),
+                        ("[Abort]", AppAction::RebaseAbort, app.palette.btn_bg),
+                    ],
+                    GitOperation::Am => vec![
+                        (
+                            "[Continue]",
+                            AppAction::AmContinue,
+                            app.palette.accent_tertiary,
+                        ),
+                        ("[Skip]", AppAction::AmSkip, app.palette.accent_secondary),
+                        ("[Abort]", AppAction::AmAbort, app.palette.btn_bg),
+                    ],
+                    GitOperation::CherryPick => vec![
+                        (
+                            "[Continue]",
+                            AppAction::CherryPickContinue,
+                            app.palette.accent_tertiary,
+                        ),
+                        (
+                            "[Skip]",
+                            AppAction::CherryPickSkip,
+                            app.palette.accent_secondary,
+                        ),
+                        ("[Abort]", AppAction::CherryPickAbort, app.palette.btn_bg),
+                    ],
+                    GitOperation::Revert => vec![
+                        (
+                            "[Continue]",
+                            AppAction::RevertContinue,
+                            app.palette.accent_tertiary,
+                        ),
+                        (
+                            "[Skip]",
+                            AppAction::RevertSkip,
+                            app.palette.accent_secondary,
+                        ),
+                        ("[Abort]", AppAction::RevertAbort, app.palette.btn_bg),
+                    ],
+                };
+
+                for (label, action, bg) in buttons.into_iter().rev() {
+                    let w = label.len() as u16;
+                    if cursor <= top_bar.x + 2 + w {
+                        break;
+                    }
+                    let x = cursor.saturating_sub(w);
+                    let rect = Rect::new(x, second_row_y, w, 1);
+                    let style = Style::default()
+                        .bg(if enabled {
+                            bg
+                        } else {
+                            app.palette.border_inactive
+                        })
+                        .fg(if enabled {
+                            app.palette.btn_fg
+                        } else {
+                            app.palette.fg
+                        })
+                        .add_modifier(Modifier::BOLD);
+                    f.render_widget(Paragraph::new(label).style(style), rect);
+                    if enabled {
+                        zones.push(ClickZone { rect, action });
+                    }
+                    cursor = x.saturating_sub(1);
+                }
+            }
+
+            if app.git.repo_root.is_some() {
+                let mut remote_buttons = vec![
+                    ("[Push]", AppAction::GitPush, app.palette.accent_secondary),
+                    ("[Pull]", AppAction::GitPull, app.palette.accent_tertiary),
+                    ("[Fetch]", AppAction::GitFetch, app.palette.accent_primary),
+                ];
+                if let Some(label) = remote_label.as_deref() {
+                    remote_buttons.push((label, AppAction::OpenRemotes, app.palette.menu_bg));
+                }
+                // Inside a submodule entered from the Git tab
+                if !app.superprojects.is_empty() {
+                    remote_buttons.push((
+                        "[↰ Parent]",
+                        AppAction::LeaveSubmodule,
+                        app.palette.menu_bg,
+                    ));
+                }
+                // Commits made on a detached HEAD are easy to lose without a branch
+                if app.git.detached.is_some() {
+                    remote_buttons.push((
+                        "[+ Branch Here]",
+                        AppAction::NewBranchHere,
+                        app.palette.accent_tertiary,
+                    ));
+                }
+                for (label, action, bg) in remote_buttons {
+                    let w = display_width(label) as u16;
+                    if cursor <= top_bar.x + 2 + w {
+                        break;
+                    }
+                    let x = cursor.saturating_sub(w);
+                    let rect = Rect::new(x, second_row_y, w, 1);
+                    let style = Style::default()
+                        .bg(if enabled {
+                            bg
+                        } else {
+                            app.palette.border_inactive
+                        })
+                        .fg(if enabled {
+                            app.palette.btn_fg
+                        } else {
+                            app.palette.fg
+                        })
+                        .add_modifier(Modifier::BOLD);
+                    f.render_widget(Paragraph::new(label).style(style), rect);
+                    if enabled {
+                        zones.push(ClickZone { rect, action });
+                    }
+                    cursor = x.saturating_sub(1);
+                }
+            }
+        }
+        Tab::Log => {
+            let sub = match app.log_ui.subtab {
+                LogSubTab::History => "History",
+                LogSubTab::Reflog => "Reflog",
+                LogSubTab::Stash => "Stash",
+                LogSubTab::Commands => "Commands",
+            };
+
+            let branch = if app.git.branch.is_empty() {
+                "(unknown)".to_string()
+            } else {
+                app.git.branch.clone()
+            };
+
+            let width = top_bar.width.saturating_sub(2);
+            let base_x = top_bar.x + 2;
+
+            let mut spans: Vec<Span> = Vec::new();
+            spans.push(Span::raw(format!(" History: {}   ", sub)));
+            spans.push(Span::raw("View: "));
+
+            let view_ref = app.log_ui.history_ref.as_deref().unwrap_or_else(|| {
+                if branch.is_empty() {
+                    "HEAD"
+                } else {
+                    branch.as_str()
+                }
+            });
+
+            let branch_text = format!("{} ▼", view_ref);
+            let branch_prefix_len = format!(" History: {}   View: ", sub).len();
+            let branch_x = base_x.saturating_add(branch_prefix_len as u16);
+            let branch_w = branch_text.len() as u16;
+
+            spans.push(Span::styled(
+                branch_text.clone(),
+                Style::default()
+                    .fg(app.palette.accent_secondary)
+                    .add_modifier(Modifier::BOLD),
+            ));
+            zones.push(ClickZone {
+                rect: Rect::new(branch_x, second_row_y, branch_w, 1),
+                action: AppAction::OpenLogBranchPicker,
+            });
+
+            spans.push(Span::raw(format!(
+                "   (current: {})",
+                if branch.is_empty() {
+                    "HEAD"
+                } else {
+                    branch.as_str()
+                }
+            )));
+
+            f.render_widget(
+                Paragraph::new(Line::from(spans)).style(Style::default().fg(app.palette.fg)),
+                Rect::new(base_x, second_row_y, width, 1),
+            );
+        }
+        Tab::Terminal => {
+            // Show terminal title
+            let title = " Terminal (shell) ";
+            f.render_widget(
+                Paragraph::new(title).style(Style::default().fg(app.palette.accent_secondary)),
+                Rect::new(top_bar.x + 2, second_row_y, title.len() as u16, 1),
+            );
+        }
+    }
+    match app.current_tab {
+        _ if app.blame.open => {
+            ui::tabs::render_blame_view(app, f, content_area, &mut zones);
+        }
+        Tab::Explorer => {
+            ui::tabs::render_explorer_tab(app, f, content_area, &mut zones);
+        }
+        Tab::Git => {
+            ui::tabs::render_git_tab(app, f, content_area, &mut zones);
+        }
+        Tab::Log => {
+            ui::tabs::render_log_tab(app, f, content_area, &mut zones);
+        }
+        Tab::Terminal => {
+            // Poll terminal output
+            app.terminal.poll_output();
+
+            let term_block = Block::default()
+                .borders(Borders::ALL)
+                .border_set(ratatui::symbols::border::PLAIN)
+                .border_style(Style::default().fg(app.palette.border_inactive))
+                .title(" Terminal ");
+            let inner = term_block.inner(content_area);
+            f.render_widget(term_block, content_area);
+
+            // Spawn shell if not active (use inner dimensions)
+            if !app.terminal.active {
+                app.terminal
+                    .spawn_shell(inner.width, inner.height, &app.current_path);
+            }
+
+            // Render terminal screen
+            let screen = app.terminal.parser.screen();
+            let rows = screen.size().0.min(inner.height);
+            let cols = screen.size().1.min(inner.width);
+            let mut lines: Vec<Line> = Vec::new();
+            for row in 0..rows {
+                let mut spans: Vec<Span> = Vec::new();
+                for col in 0..cols {
+                    let cell = screen.cell(row, col);
+                    if let Some(cell) = cell {
+                        let ch = cell.contents();
+                        let fg = match cell.fgcolor() {
+                            vt100::Color::Default => app.palette.fg,
+                            vt100::Color::Idx(i) => idx_to_color(i),
+                            vt100::Color::Rgb(r, g, b) => Color::Rgb(r, g, b),
+                        };
+                        let bg = match cell.bgcolor() {
+                            vt100::Color::Default => app.palette.bg,
+                            vt100::Color::Idx(i) => idx_to_color(i),
+                            vt100::Color::Rgb(r, g, b) => Color::Rgb(r, g, b),
+                        };
+                        let mut style = Style::default().fg(fg).bg(bg);
+                        if cell.bold() {
+                            style = style.add_modifier(Modifier::BOLD);
+                        }
+                        spans.push(Span::styled(
+                            if ch.is_empty() {
+                                " ".to_string()
+                            } else {
+                                ch.to_string()
+                            },
+                            style,
+                        ));
+                    } else {
+                        spans.push(Span::raw(" "));
+                    }
+                }
+                lines.push(Line::from(spans));
+            }
+            f.render_widget(Paragraph::new(lines), inner);
+        }
+    }
+
+    if let Some(commit_area) = commit_area {
+        if app.commit.open {
+            let commit_block = Block::default()
+                .borders(Borders::ALL)
+                .border_set(ratatui::symbols::border::PLAIN)
+                .border_style(Style::default().fg(app.palette.accent_primary))
+                .title(" Commit ");
+            f.render_widget(commit_block.clone(), commit_area);
+
+            let inner = commit_area.inner(Margin {
+                vertical: 1,
+                horizontal: 1,
+            });
+
+            let advanced_h = if app.commit.advanced { 2 } else { 0 };
+            let guided_h = u16::from(app.commit.conventional.is_some());
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(1),
+                    Constraint::Length(guided_h),
+                    Constraint::Length(5),
+                    Constraint::Length(advanced_h),
+                    Constraint::Length(1),
+                    Constraint::Length(1),
+                    Constraint::Length(1),
+                ])
+                .split(inner);
+
+            let model = openrouter::model_name(app.ai_config.model.as_deref());
+            let overrides = app.commit.overrides();
+            let mut header = format!("Message    AI: {}", model);
+            if !app.commit.advanced && (overrides.author.is_some() || overrides.date.is_some()) {
+                header.push_str("    advanced options set");
+            }
+            let mut spans = vec![Span::styled(
+                header,
+                Style::default()
+                    .fg(app.palette.fg)
+                    .add_modifier(Modifier::BOLD),
+            )];
+            if let Some(signing) = app.commit.signing {
+                spans.push(Span::styled(
+                    format!("    signed ({})", signing),
+                    Style::default().fg(app.palette.diff_add_fg),
+                ));
+            }
+            if app.commit.amend {
+                spans.push(Span::styled(
+                    "    amending HEAD",
+                    Style::default().fg(app.palette.accent_primary),
+                ));
+                if let Some(upstream) = app.head_pushed() {
+                    spans.push(Span::styled(
+                        format!(" (already on {})", upstream),
+                        Style::default()
+                            .fg(app.palette.btn_bg)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                }
+            }
+            if app.commit.conventional.is_some()
+                && let Some(warning) = commit::header_warning(&app.commit.message)
+            {
+                spans.push(Span::styled(
+                    format!("    {}", warning),
+                    Style::default()
+                        .fg(app.palette.btn_bg)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            f.render_widget(Paragraph::new(Line::from(spans)), rows[0]);
+            if app.commit.conventional.is_some() {
+                render_commit_guided(f, app, rows[1], &mut zones);
+            }
+
+            let input_border = if app.commit.focus == CommitFocus::Message {
+                app.palette.accent_primary
+            } else {
+                app.palette.border_inactive
+            };
+            let input_block = Block::default()
+                .borders(Borders::ALL)
+                .border_set(ratatui::symbols::border::PLAIN)
+                .border_style(Style::default().fg(input_border))
+                .title(" Commit Message ");
+
+            let input_inner = rows[2].inner(Margin {
+                vertical: 1,
+                horizontal: 1,
+            });
+            app.commit
+                .ensure_cursor_visible(input_inner.height as usize);
+
+            let input_lines: Vec<Line> = if app.commit.message.is_empty() {
+                vec![Line::from(Span::styled(
+                    "Type commit message...",
+                    Style::default().fg(app.palette.border_inactive),
+                ))]
+            } else {
+                app.commit.message.lines().map(Line::raw).collect()
+            };
+
+            let input = Paragraph::new(input_lines)
+                .block(input_block)
+                .wrap(Wrap { trim: false })
+                .scroll((app.commit.scroll_y, 0));
+            f.render_widget(input, rows[2]);
+
+            zones.push(ClickZone {
+                rect: rows[2],
+                action: AppAction::FocusCommitMessage,
+            });
+
+            if app.commit.focus == CommitFocus::Message {
+                let (line, col) = app.commit.cursor_line_col();
+                let rel_y = (line as i64 - app.commit.scroll_y as i64).max(0) as u16;
+                let cursor_y = input_inner.y.saturating_add(rel_y);
+                let cursor_x = input_inner
+                    .x
+                    .saturating_add(col as u16)
+                    .min(input_inner.x + input_inner.width.saturating_sub(1));
+                if cursor_y >= input_inner.y && cursor_y < input_inner.y + input_inner.height {
+                    f.set_cursor_position((cursor_x, cursor_y));
+                }
+            }
+
+            if app.commit.advanced {
+                render_commit_advanced(f, app, rows[3], &mut zones);
+            }
+
+            let status_text = app.commit.status.as_deref().unwrap_or(if app.commit.busy {
+                "Working..."
+            } else {
+                ""
+            });
+            f.render_widget(
+                Paragraph::new(status_text).style(Style::default().fg(app.palette.fg)),
+                rows[4],
+            );
+
+            let reviewed = !app.review_before_commit || app.review.complete;
+            let advanced_label = if app.commit.advanced {
+                " Advanced ▾ "
+            } else {
+                " Advanced ▸ "
+            };
+            let amend_label = if app.commit.amend {
+                " Amend ▣ "
+            } else {
+                " Amend □ "
+            };
+            let guided_label = if app.commit.conventional.is_some() {
+                " Guided ▣ "
+            } else {
+                " Guided □ "
+            };
+            let mut x = rows[5].x;
+            for (label, action, color, enabled) in [
+                (
+                    " AI Generate ",
+                    AppAction::GenerateCommitMessage,
+                    app.palette.accent_tertiary,
+                    !app.commit.busy,
+                ),
+                (
+                    " Recent ",
+                    AppAction::OpenMessagePicker,
+                    app.palette.accent_primary,
+                    !app.commit.busy,
+                ),
+                (
+                    " Review ",
+                    AppAction::OpenReview,
+                    app.palette.accent_primary,
+                    !app.commit.busy,
+                ),
+                (
+                    " Commit ",
+                    AppAction::GitFooter(GitFooterAction::Commit),
+                    app.palette.accent_secondary,
+                    !app.commit.busy && reviewed,
+                ),
+                (
+                    amend_label,
+                    AppAction::ToggleCommitAmend,
+                    app.palette.accent_primary,
+                    !app.commit.busy,
+                ),
+                (
+                    guided_label,
+                    AppAction::ToggleConventionalCommit,
+                    app.palette.accent_primary,
+                    !app.commit.busy,
+                ),
+                (
+                    advanced_label,
+                    AppAction::ToggleCommitAdvanced,
+                    app.palette.border_inactive,
+                    !app.commit.busy,
+                ),
+                (
+                    " Close ",
+                    AppAction::ToggleCommitDrawer,
+                    app.palette.btn_bg,
+                    true,
+                ),
+            ] {
+                let w = label.chars().count() as u16;
+                let bg = if enabled {
+                    color
+                } else {
+                    app.palette.border_inactive
+                };
+                let fg = if enabled {
+                    app.palette.btn_fg
+                } else {
+                    app.palette.fg
+                };
+                let style = Style::default().bg(bg).fg(fg).add_modifier(Modifier::BOLD);
+                let rect = Rect::new(x, rows[5].y, w, 1);
+                f.render_widget(Paragraph::new(label).style(style), rect);
+                if enabled {
+                    zones.push(ClickZone { rect, action });
+                }
+                x += w + 2;
+            }
+
+            let hint = if app.commit.conventional.is_some() {
+                "Tab type/scope  ←→ type  ! breaking  Ctrl+T guided off  Ctrl+L recent  \
+                 Ctrl+Enter commit  Esc close"
+            } else if app.commit.advanced {
+                "Ctrl+G AI  Ctrl+R review  Ctrl+A advanced  Ctrl+O author  Ctrl+K co-authors  \
+                 Tab date  Esc close"
+            } else {
+                "Ctrl+G AI  Ctrl+L recent  Ctrl+T guided  Ctrl+R review  Ctrl+E amend  \
+                 Ctrl+A advanced  Ctrl+Enter commit  Esc close"
+            };
+            f.render_widget(
+                Paragraph::new(hint).style(Style::default().fg(app.palette.border_inactive)),
+                rows[6],
+            );
+        } else {
+            let sep = Block::default()
+                .borders(Borders::TOP)
+                .border_set(ratatui::symbols::border::PLAIN)
+                .border_style(Style::default().fg(app.palette.border_inactive));
+            f.render_widget(sep, commit_area);
+
+            let label = " Commit ▸ ";
+            let w = label.len().min(commit_area.width as usize) as u16;
+            f.render_widget(
+                Paragraph::new(label).style(
+                    Style::default()
+                        .fg(app.palette.fg)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Rect::new(commit_area.x + 2, commit_area.y, w, 1),
+            );
+            zones.push(ClickZone {
+                rect: Rect::new(commit_area.x, commit_area.y, commit_area.width, 1),
+                action: AppAction::ToggleCommitDrawer,
+            });
+        }
+    }
+
+    let footer_block = Block::default()
+        .borders(Borders::TOP)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.border_inactive));
+    f.render_widget(footer_block, footer_area);
+
+    let btn_y = footer_area.y + 1;
+    let mut btn_x = footer_area.x + 2;
+
+    let mut buttons: Vec<(String, AppAction, Color, bool)> = Vec::new();
+    match app.current_tab {
+        Tab::Explorer => {
+            buttons.push((
+                " Menu (^P) ".to_string(),
+                AppAction::OpenCommandPalette,
+                app.palette.accent_primary,
+                true,
+            ));
+            buttons.push((
+                " ⬅ Back (h) ".to_string(),
+                AppAction::GoParent,
+                app.palette.accent_primary,
+                true,
+            ));
+            buttons.push((
+                " ⏎ Enter (l) ".to_string(),
+                AppAction::EnterDir,
+                app.palette.accent_secondary,
+                true,
+            ));
+            buttons.push((
+                " 👁 Hidden (.) ".to_string(),
+                AppAction::ToggleHidden,
+                app.palette.accent_tertiary,
+                true,
+            ));
+            buttons.push((
+                " 🔖 Bookmarks (b) ".to_string(),
+                AppAction::OpenBookmarks,
+                app.palette.accent_secondary,
+                true,
+            ));
+            buttons.push((
+                " ✖ Quit (q) ".to_string(),
+                AppAction::Quit,
+                app.palette.btn_bg,
+                true,
+            ));
+        }
+        Tab::Git => {
+            buttons.push((
+                " Menu (^P) ".to_string(),
+                AppAction::OpenCommandPalette,
+                app.palette.accent_primary,
+                true,
+            ));
+            let enabled = app.pending_job.is_none() && !app.commit.busy && !app.branch_ui.open;
+            // Staging actions queue behind a running job instead of being rejected
+            let queueable = !app.commit.busy && !app.branch_ui.open;
+            let in_conflict_view = app.git.selected_tree_entry().is_some_and(|e| e.is_conflict);
+
+            if in_conflict_view {
+                buttons.push((
+                    " < Prev (p) ".to_string(),
+                    AppAction::ConflictPrev,
+                    app.palette.accent_tertiary,
+                    queueable,
+                ));
+                buttons.push((
+                    " Next (n) > ".to_string(),
+                    AppAction::ConflictNext,
+                    app.palette.accent_tertiary,
+                    queueable,
+                ));
+                buttons.push((
+                    " Ours (o) ".to_string(),
+                    AppAction::ConflictUseOurs,
+                    app.palette.accent_primary,
+                    enabled,
+                ));
+                buttons.push((
+                    " Theirs (t) ".to_string(),
+                    AppAction::ConflictUseTheirs,
+                    app.palette.accent_secondary,
+                    enabled,
+                ));
+                buttons.push((
+                    " Both (b) ".to_string(),
+                    AppAction::ConflictUseBoth,
+                    app.palette.accent_tertiary,
+                    enabled,
+                ));
+                buttons.push((
+                    " Mark (a) ".to_string(),
+                    AppAction::MarkResolved,
+                    app.palette.exe_color,
+                    enabled,
+                ));
+                buttons.push((
+                    " ✎ Commit… ".to_string(),
+                    AppAction::ToggleCommitDrawer,
+                    app.palette.accent_primary,
+                    true,
+                ));
+            } else {
+                buttons.push((
+                    " ␠ Toggle ".to_string(),
+                    AppAction::ToggleGitStage,
+                    app.palette.accent_primary,
+                    queueable,
+                ));
+                buttons.push((
+                    " + Stage ".to_string(),
+                    AppAction::GitFooter(GitFooterAction::Stage),
+                    app.palette.accent_secondary,
+                    queueable,
+                ));
+                buttons.push((
+                    " - Unstage ".to_string(),
+                    AppAction::GitFooter(GitFooterAction::Unstage),
+                    app.palette.accent_tertiary,
+                    queueable,
+                ));
+                buttons.push((
+                    " ↩ Discard ".to_string(),
+                    AppAction::GitFooter(GitFooterAction::Discard),
+                    app.palette.btn_bg,
+                    queueable,
+                ));
+                buttons.push((
+                    " + All (A) ".to_string(),
+                    AppAction::GitStageAllVisible,
+                    app.palette.accent_secondary,
+                    queueable,
+                ));
+                buttons.push((
+                    " - All (U) ".to_string(),
+                    AppAction::GitUnstageAllVisible,
+                    app.palette.accent_tertiary,
+                    queueable,
+                ));
+                buttons.push((
+                    " Branch (B) ".to_string(),
+                    AppAction::OpenBranchPicker,
+                    app.palette.accent_tertiary,
+                    enabled,
+                ));
+                buttons.push((
+                    " ✎ Commit… ".to_string(),
+                    AppAction::ToggleCommitDrawer,
+                    app.palette.accent_primary,
+                    true,
+                ));
+            }
+
+            buttons.push((
+                " ✖ Quit (q) ".to_string(),
+                AppAction::Quit,
+                app.palette.btn_bg,
+                true,
+            ));
+        }
+        Tab::Log => {
+            buttons.push((
+                " Menu (^P) ".to_string(),
+                AppAction::OpenCommandPalette,
+                app.palette.accent_primary,
+                true,
+            ));
+
+            buttons.push((
+                " Diff (d) ".to_string(),
+                AppAction::LogDetail(LogDetailMode::Diff),
+                app.palette.accent_primary,
+                app.log_ui.subtab != LogSubTab::Commands,
+            ));
+            buttons.push((
+                " Changed (f) ".to_string(),
+                AppAction::LogDetail(LogDetailMode::Files),
+                app.palette.accent_primary,
+                app.log_ui.subtab != LogSubTab::Commands,
+            ));
+            buttons.push((
+                " Inspect (i) ".to_string(),
+                AppAction::LogInspect,
+                app.palette.accent_secondary,
+                true,
+            ));
+            buttons.push((
+                " Zoom (z) ".to_string(),
+                AppAction::LogToggleZoom,
+                app.palette.accent_tertiary,
+                true,
+            ));
+            buttons.push((
+                " < ([) ".to_string(),
+                AppAction::LogAdjustLeft(-2),
+                app.palette.btn_bg,
+                app.log_ui.zoom == LogZoom::None,
+            ));
+            buttons.push((
+                " > (]) ".to_string(),
+                AppAction::LogAdjustLeft(2),
+                app.palette.btn_bg,
+                app.log_ui.zoom == LogZoom::None,
+            ));
+            buttons.push((
+                " Clear Cmd (x) ".to_string(),
+                AppAction::ClearGitLog,
+                app.palette.btn_bg,
+                app.log_ui.subtab == LogSubTab::Commands,
+            ));
+            buttons.push((
+                " ✖ Quit (q) ".to_string(),
+                AppAction::Quit,
+                app.palette.btn_bg,
+                true,
+            ));
+        }
+        Tab::Terminal => {
+            buttons.push((
+                " Type to interact with shell ".to_string(),
+                AppAction::None,
+                app.palette.border_inactive,
+                false,
+            ));
+        }
+    }
+
+    let available = footer_area.width.saturating_sub(4);
+    loop {
+        let total: u16 = buttons
+            .iter()
+            .map(|(label, _, _, _)| label.len() as u16 + 2)
+            .sum();
+        if total <= available || buttons.len() <= 1 {
+            break;
+        }
+        let drop_idx = buttons.len().saturating_sub(2);
+        buttons.remove(drop_idx);
+    }
+
+    for (label, action, color, enabled) in buttons {
+        let width = label.len() as u16;
+        if btn_x + width >= footer_area.x + footer_area.width {
+            break;
+        }
+
+        let bg = if enabled {
+            color
+        } else {
+            app.palette.border_inactive
+        };
+        let fg = if enabled {
+            app.palette.btn_fg
+        } else {
+            app.palette.fg
+        };
+        let btn_style = Style::default().bg(bg).fg(fg).add_modifier(Modifier::BOLD);
+
+        f.render_widget(
+            Paragraph::new(label.as_str()).style(btn_style),
+            Rect::new(btn_x, btn_y, width, 1),
+        );
+
+        if enabled {
+            zones.push(ClickZone {
+                rect: Rect::new(btn_x, btn_y, width, 1),
+                action,
+            });
+        }
+
+        btn_x += width + 2;
+    }
+
+    if let Some(armed) = app.armed.as_ref().filter(|a| a.is_live()) {
+        let used = btn_x.saturating_sub(footer_area.x);
+        let available = footer_area.width.saturating_sub(used).saturating_sub(2);
+        let badge = " ARMED ";
+        if available as usize > badge.len() {
+            let badge_style = Style::default()
+                .bg(app.palette.diff_del_fg)
+                .fg(app.palette.btn_fg)
+                .add_modifier(Modifier::BOLD);
+            f.render_widget(
+                Paragraph::new(Line::from(vec![
+                    Span::styled(badge, badge_style),
+                    Span::raw(" "),
+                    Span::styled(
+                        armed.label.as_str(),
+                        Style::default().fg(app.palette.diff_del_fg),
+                    ),
+                ])),
+                Rect::new(btn_x, btn_y, available, 1),
+            );
+        }
+    } else if let Some(pending) = app
+        .nav_keys
+        .pending_count()
+        .map(|count| count.to_string())
+        .or_else(|| app.nav_keys.pending_bracket().map(String::from))
+    {
+        let used = btn_x.saturating_sub(footer_area.x);
+        let available = footer_area.width.saturating_sub(used).saturating_sub(2);
+        f.render_widget(
+            Paragraph::new(pending).style(
+                Style::default()
+                    .fg(app.palette.accent_primary)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Rect::new(btn_x, btn_y, available, 1),
+        );
+    } else if let Some((msg, _)) = app.status_message.as_ref() {
+        let used = btn_x.saturating_sub(footer_area.x);
+        let available = footer_area.width.saturating_sub(used).saturating_sub(2);
+        if available > 0 {
+            f.render_widget(
+                Paragraph::new(msg.as_str()).style(Style::default().fg(app.palette.fg)),
+                Rect::new(btn_x, btn_y, available, 1),
+            );
+        }
+    } else {
+        let used = btn_x.saturating_sub(footer_area.x);
+        let available = footer_area.width.saturating_sub(used).saturating_sub(2);
+        if available > 0 {
+            match app.current_tab {
+                Tab::Explorer => {
+                    let hint = "Ctrl+P menu  T theme  Tab pane  a new  u undo";
+                    let w = hint.len().min(available as usize) as u16;
+                    f.render_widget(
+                        Paragraph::new(hint)
+                            .style(Style::default().fg(app.palette.border_inactive)),
+                        Rect::new(btn_x, btn_y, w, 1),
+                    );
+                }
+                Tab::Git => {
+                    let hint =
+                        "Ctrl+P menu  T theme  Tab pane  z stash  S stash hunk  N new branch";
+                    let w = hint.len().min(available as usize) as u16;
+                    f.render_widget(
+                        Paragraph::new(hint)
+                            .style(Style::default().fg(app.palette.border_inactive)),
+                        Rect::new(btn_x, btn_y, w, 1),
+                    );
+                }
+                Tab::Log => {
+                    let prefix = "/ filter  ";
+                    let author = "@author ▼";
+                    let suffix = "  ref:tag  Ctrl+L clear";
+
+                    let mut spans: Vec<Span> = Vec::new();
+                    spans.push(Span::raw(prefix));
+                    spans.push(Span::styled(
+                        author,
+                        Style::default().fg(app.palette.accent_tertiary),
+                    ));
+                    spans.push(Span::raw(suffix));
+
+                    let line = Line::from(spans);
+                    f.render_widget(
+                        Paragraph::new(line)
+                            .style(Style::default().fg(app.palette.border_inactive)),
+                        Rect::new(btn_x, btn_y, available, 1),
+                    );
+
+                    let author_x = btn_x.saturating_add(prefix.len() as u16);
+                    let author_w = author.len() as u16;
+                    if author_x + author_w <= btn_x + available {
+                        zones.push(ClickZone {
+                            rect: Rect::new(author_x, btn_y, author_w, 1),
+                            action: AppAction::OpenAuthorPicker,
+                        });
+                    }
+                }
+                Tab::Terminal => {
+                    let hint = "Ctrl+P menu  T theme";
+                    let w = hint.len().min(available as usize) as u16;
+                    f.render_widget(
+                        Paragraph::new(hint)
+                            .style(Style::default().fg(app.palette.border_inactive)),
+                        Rect::new(btn_x, btn_y, w, 1),
+                    );
+                }
+            }
+        }
+    }
+
+    if footer_area.height >= 3 {
+        let mut spans: Vec<Span> = Vec::new();
+        for (key, what) in app.hint_bar() {
+            if !spans.is_empty() {
+                spans.push(Span::raw("  "));
+            }
+            spans.push(Span::styled(
+                key,
+                Style::default().fg(app.palette.accent_primary),
+            ));
+            spans.push(Span::styled(
+                format!(" {}", what),
+                Style::default().fg(app.palette.border_inactive),
+            ));
+        }
+        f.render_widget(
+            Paragraph::new(Line::from(spans)),
+            Rect::new(
+                footer_area.x + 2,
+                footer_area.y + 2,
+                footer_area.width.saturating_sub(4),
+                1,
+            ),
+        );
+    }
+
+    if app.author_ui.open {
+        let w = area.width.min(74).saturating_sub(2).max(46);
+        let h = area.height.min(18).saturating_sub(2).max(10);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::CloseAuthorPicker,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.btn_bg))
+            .title(match (app.author_picker_mode, &app.author_ui.canonical) {
+                (AuthorPickerMode::LogFilter, _) => " Author ".to_string(),
+                (AuthorPickerMode::CommitAuthor, _) => " Commit as ".to_string(),
+                (AuthorPickerMode::Mailmap, None) => {
+                    " Map author: pick the real identity ".to_string()
+                }
+                (AuthorPickerMode::Mailmap, Some(canonical)) => {
+                    format!(" Map onto {} ", canonical)
+                }
+                (AuthorPickerMode::CoAuthors, _) => match app.author_ui.marked.len() {
+                    0 => " Co-authors ".to_string(),
+                    n => format!(" Co-authors ({} ticked) ", n),
+                },
+            });
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+
+        let co_authors = app.author_picker_mode == AuthorPickerMode::CoAuthors;
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(u16::from(co_authors)),
+            ])
+            .split(inner);
+        if co_authors {
+            f.render_widget(
+                Paragraph::new("Tab to tick · Enter to add the trailers · Esc to cancel")
+                    .style(Style::default().fg(app.palette.border_inactive)),
+                rows[2],
+            );
+        }
+
+        let query = Paragraph::new(format!("Filter: {}", app.author_ui.query))
+            .style(Style::default().fg(app.palette.fg));
+        f.render_widget(query, rows[0]);
+
+        let items: Vec<ListItem> = app
+            .author_ui
+            .filtered
+            .iter()
+            .filter_map(|idx| app.author_ui.authors.get(*idx))
+            .map(|a| {
+                let mut spans = Vec::new();
+                if co_authors {
+                    spans.push(Span::raw(if app.author_ui.marked.contains(a) {
+                        "▣ "
+                    } else {
+                        "□ "
+                    }));
+                }
+                spans.push(Span::styled(
+                    "● ",
+                    Style::default().fg(theme::author_color(&app.palette, a)),
+                ));
+                spans.push(Span::raw(a.clone()));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(app.palette.selection_bg)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("▶ ");
+
+        f.render_stateful_widget(list, rows[1], &mut app.author_ui.list_state);
+
+        let list_inner = rows[1].inner(Margin {
+            vertical: 0,
+            horizontal: 0,
+        });
+
+        if list_inner.height > 0 {
+            let offset = app.author_ui.list_state.offset();
+            let end = (offset + list_inner.height as usize).min(app.author_ui.filtered.len());
+            for (row_idx, _idx) in app.author_ui.filtered[offset..end].iter().enumerate() {
+                let rect = Rect::new(
+                    list_inner.x,
+                    list_inner.y + row_idx as u16,
+                    list_inner.width,
+                    1,
+                );
+                zones.push(ClickZone {
+                    rect,
+                    action: AppAction::SelectAuthor(offset + row_idx),
+                });
+            }
+        }
+    }
+
+    if app.branch_ui.open {
+        branches::render_branch_picker(f, app, area, &mut zones);
+    }
+
+    if app.stash_ui.open {
+        stash::render_stash_picker(f, app, area, &mut zones);
+    }
+
+    if let Some(menu) = &app.context_menu {
+        let width = 30u16.min(area.width.saturating_sub(2));
+        let height = (menu.options.len() as u16 + 2).min(area.height.saturating_sub(2));
+        if width < 6 || height < 4 {
+        } else {
+            let max_x = area
+                .x
+                .saturating_add(area.width)
+                .saturating_sub(width)
+                .saturating_sub(1);
+            let max_y = area
+                .y
+                .saturating_add(area.height)
+                .saturating_sub(height)
+                .saturating_sub(1);
+
+            let menu_area = Rect::new(menu.x.min(max_x), menu.y.min(max_y), width, height);
+
+            f.render_widget(Clear, menu_area);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_set(ratatui::symbols::border::PLAIN)
+                .border_style(Style::default().fg(app.palette.accent_secondary))
+                .bg(app.palette.menu_bg);
+
+            f.render_widget(block.clone(), menu_area);
+
+            let inner = menu_area.inner(Margin {
+                vertical: 1,
+                horizontal: 1,
+            });
+
+            let visible = (inner.height as usize).min(menu.options.len());
+            for (i, (label, _)) in menu.options.iter().take(visible).enumerate() {
+                let item_area = Rect::new(inner.x, inner.y + i as u16, inner.width, 1);
+
+                let is_selected = i == menu.selected;
+                let style = if is_selected {
+                    Style::default()
+                        .bg(app.palette.selection_bg)
+                        .fg(app.palette.fg)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(app.palette.fg)
+                };
+
+                f.render_widget(Paragraph::new(label.as_str()).style(style), item_area);
+
+                zones.push(ClickZone {
+                    rect: item_area,
+                    action: AppAction::ContextMenuAction(i),
+                });
+            }
+        }
+    }
+
+    if app.confirm.is_none()
+        && !app.branch_ui.open
+        && app.context_menu.is_none()
+        && !app.log_ui.inspect.open
+        && app.operation_popup.is_none()
+    {
+        if app.command_palette.open {
+            let w = area.width.min(56).saturating_sub(2).max(32);
+            let desired_h = COMMAND_PALETTE_ITEMS.len() as u16 + 6;
+            let h = desired_h.min(area.height.saturating_sub(2)).max(10);
+            let x = area.x + (area.width.saturating_sub(w)) / 2;
+            let y = area.y + (area.height.saturating_sub(h)) / 2;
+            let modal = Rect::new(x, y, w, h);
+
+            f.render_widget(Clear, modal);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_set(ratatui::symbols::border::PLAIN)
+                .border_style(Style::default().fg(app.palette.accent_primary))
+                .title(" Menu (Ctrl+P) ");
+            f.render_widget(block.clone(), modal);
+
+            let inner = modal.inner(Margin {
+                vertical: 1,
+                horizontal: 2,
+            });
+
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)])
+                .split(inner);
+
+            let list_items: Vec<ListItem> = COMMAND_PALETTE_ITEMS
+                .iter()
+                .map(|(_, label)| ListItem::new(format!("  {}", label)))
+                .collect();
+
+            let list = List::new(list_items)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_set(ratatui::symbols::border::PLAIN)
+                        .border_style(Style::default().fg(app.palette.border_inactive))
+                        .title(" Commands "),
+                )
+                .highlight_style(
+                    Style::default()
+                        .bg(app.palette.accent_primary)
+                        .fg(app.palette.btn_fg)
+                        .add_modifier(Modifier::BOLD),
+                );
+            f.render_stateful_widget(list, rows[0], &mut app.command_palette.list_state);
+
+            let hint = "j/k move  Enter run  Esc close";
+            f.render_widget(
+                Paragraph::new(hint).style(Style::default().fg(app.palette.border_inactive)),
+                rows[1],
+            );
+        }
+
+        if app.theme_picker.open {
+            let w = 35u16.min(area.width.saturating_sub(2)).max(30);
+            let h = 11u16.min(area.height.saturating_sub(2)).max(9);
+            let x = area.x + (area.width.saturating_sub(w)) / 2;
+            let y = area.y + (area.height.saturating_sub(h)) / 2;
+            let modal = Rect::new(x, y, w, h);
+
+            f.render_widget(Clear, modal);
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_set(ratatui::symbols::border::PLAIN)
+                .border_style(Style::default().fg(app.palette.accent_primary))
+                .title(" Select Theme ");
+            f.render_widget(block.clone(), modal);
+
+            let inner = modal.inner(Margin {
+                vertical: 1,
+                horizontal: 2,
+            });
+
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)])
+                .split(inner);
+
+            let list_items: Vec<ListItem> = THEME_ORDER
+                .iter()
+                .enumerate()
+                .map(|(i, t)| {
+                    let current = if *t == app.theme { "*" } else { " " };
+                    ListItem::new(format!("{} {} {}", current, i + 1, t.label()))
+                })
+                .collect();
+
+            let list = List::new(list_items)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_set(ratatui::symbols::border::PLAIN)
+                        .border_style(Style::default().fg(app.palette.border_inactive))
+                        .title(" Themes "),
+                )
+                .highlight_style(
+                    Style::default()
+                        .bg(app.palette.accent_primary)
+                        .fg(app.palette.btn_fg)
+                        .add_modifier(Modifier::BOLD),
+                );
+            f.render_stateful_widget(list, rows[0], &mut app.theme_picker.list_state);
+
+            let hint = "j/k move  Enter apply  1-5 quick  Esc";
+            f.render_widget(
+                Paragraph::new(hint).style(Style::default().fg(app.palette.border_inactive)),
+                rows[1],
+            );
+        }
+    }
+
+    if app.confirm.is_none() && app.log_ui.inspect.open {
+        log::render_log_inspect(f, app, area, &mut zones);
+    }
+
+    if app.confirm.is_none() && !app.log_ui.inspect.open {
+        if let Some(popup) = &app.operation_popup {
+            zones.push(ClickZone {
+                rect: area,
+                action: AppAction::CloseOperationPopup,
+            });
+
+            let w = area.width.min(90).saturating_sub(2).max(44);
+            let h = area.height.min(14).saturating_sub(2).max(7);
+            let x = area.x + (area.width.saturating_sub(w)) / 2;
+            let y = area.y + (area.height.saturating_sub(h)) / 2;
+            let modal = Rect::new(x, y, w, h);
+
+            f.render_widget(Clear, modal);
+
+            let border = if popup.ok {
+                app.palette.accent_secondary
+            } else {
+                app.palette.btn_bg
+            };
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_set(ratatui::symbols::border::PLAIN)
+                .border_style(Style::default().fg(border))
+                .title(popup.title.as_str());
+            f.render_widget(block.clone(), modal);
+
+            let inner = modal.inner(Margin {
+                vertical: 1,
+                horizontal: 2,
+            });
+
+            let body_h = inner.height.saturating_sub(1);
+            let body_area = Rect::new(inner.x, inner.y, inner.width, body_h);
+            let buttons_y = inner.y + body_h;
+
+            let para = Paragraph::new(popup.body.as_str())
+                .wrap(Wrap { trim: false })
+                .scroll((popup.scroll_y, 0));
+            f.render_widget(para, body_area);
+
+            let label = " Close (Esc) ";
+            let bw = label.len() as u16;
+            let rect = Rect::new(inner.x, buttons_y, bw, 1);
+            let style = Style::default()
+                .bg(app.palette.btn_bg)
+                .fg(app.palette.btn_fg)
+                .add_modifier(Modifier::BOLD);
+            f.render_widget(Paragraph::new(label).style(style), rect);
+            zones.push(ClickZone {
+                rect,
+                action: AppAction::CloseOperationPopup,
+            });
+
+            if popup.retry.is_some() {
+                let label = " Retry (r) ";
+                let rect = Rect::new(rect.right() + 1, buttons_y, label.len() as u16, 1);
+                let style = Style::default()
+                    .bg(app.palette.accent_secondary)
+                    .fg(app.palette.btn_fg)
+                    .add_modifier(Modifier::BOLD);
+                f.render_widget(Paragraph::new(label).style(style), rect);
+                zones.push(ClickZone {
+                    rect,
+                    action: AppAction::RetryOperation,
+                });
+            }
+        }
+    }
+
+    // Update confirmation dialog
+    if let Some(new_version) = &app.update_confirm {
+        let w = area.width.min(55).saturating_sub(2).max(40);
+        let h = 7u16.min(area.height.saturating_sub(2)).max(5);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Update Available ");
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+
+        let lines = vec![
+            Line::raw(format!("New version: v{} -> v{}", VERSION, new_version)),
+            Line::raw(""),
+            Line::raw("Update now? (y/n)"),
+        ];
+
+        f.render_widget(
+            Paragraph::new(lines).style(Style::default().fg(app.palette.fg)),
+            inner,
+        );
+    }
+
+    if let Some(input) = &app.stash_input {
+        stash::render_stash_input(f, app, input, area, &mut zones);
+    }
+
+    if let Some(input) = &app.push_input {
+        let w = area.width.min(64).saturating_sub(2).max(44);
+        let h = 10u16.min(area.height.saturating_sub(2)).max(7);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Push ");
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let mut constraints = vec![Constraint::Length(1); 6];
+        constraints.extend([Constraint::Min(0), Constraint::Length(1)]);
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(inner);
+
+        let label = Style::default().fg(app.palette.fg);
+        let dim = Style::default().fg(app.palette.border_inactive);
+        let focused = Style::default()
+            .fg(app.palette.fg)
+            .bg(app.palette.selection_bg);
+        let remote = &input.remotes[input.remote];
+        let fields = [
+            (PushField::Remote, format!("Remote  ◂ {} ▸", remote), 0),
+            (PushField::Ref, format!("Ref     {}", input.refspec), 1),
+            (
+                PushField::ForceWithLease,
+                format!(
+                    "{} Force, unless the remote moved (--force-with-lease)",
+                    if input.force_with_lease { "▣" } else { "□" }
+                ),
+                3,
+            ),
+            (
+                PushField::SetUpstream,
+                format!(
+                    "{} Set as upstream (-u)",
+                    if input.set_upstream { "▣" } else { "□" }
+                ),
+                4,
+            ),
+        ];
+        for (field, text, row) in fields {
+            let (text, style) = if input.focus == field {
+                let cursor = if field == PushField::Ref { "_" } else { "" };
+                (format!("{}{}", text, cursor), focused)
+            } else {
+                (text, label)
+            };
+            f.render_widget(Paragraph::new(text).style(style), rows[row]);
+            zones.push(ClickZone {
+                rect: rows[row],
+                action: AppAction::PushField(field),
+            });
+        }
+        let command = if input.is_plain() {
+            app.remote_command("git push")
+        } else {
+            format!("git push {}", input.options().args().join(" "))
+        };
+        f.render_widget(Paragraph::new(command).style(dim), rows[6]);
+
+        let bottom = rows[rows.len() - 1];
+        let button = " Push ";
+        let button_w = button.len() as u16;
+        f.render_widget(
+            Paragraph::new("Enter to push · Tab, ←/→ and Space to change · Esc to cancel")
+                .style(dim),
+            Rect::new(
+                bottom.x,
+                bottom.y,
+                bottom.width.saturating_sub(button_w + 1),
+                1,
+            ),
+        );
+        let button_rect = Rect::new(
+            bottom.right().saturating_sub(button_w),
+            bottom.y,
+            button_w.min(bottom.width),
+            1,
+        );
+        f.render_widget(
+            Paragraph::new(button).style(
+                Style::default()
+                    .bg(app.palette.accent_secondary)
+                    .fg(app.palette.btn_fg)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            button_rect,
+        );
+        zones.push(ClickZone {
+            rect: button_rect,
+            action: AppAction::SubmitPush,
+        });
+    }
+
+    if let Some(ref input) = app.new_branch_input {
+        branches::render_new_branch_input(f, app, input, area, &mut zones);
+    }
+
+    if let Some(ref input) = app.snapshot_name_input {
+        snapshots::render_snapshot_name_input(f, app, input, area, &mut zones);
+    }
+
+    if let Some(ref input) = app.export_path_input {
+        let w = area.width.min(50).saturating_sub(2).max(40);
+        let h = 7u16.min(area.height.saturating_sub(2)).max(6);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Export view ");
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        f.render_widget(
+            Paragraph::new("Save to file (.md for markdown):")
+                .style(Style::default().fg(app.palette.fg)),
+            rows[0],
+        );
+
+        let input_style = Style::default()
+            .fg(app.palette.fg)
+            .bg(app.palette.selection_bg);
+        let display_input = format!("{}_", input);
+        f.render_widget(Paragraph::new(display_input).style(input_style), rows[1]);
+
+        f.render_widget(
+            Paragraph::new("Enter to export · Esc to cancel")
+                .style(Style::default().fg(app.palette.border_inactive)),
+            rows[2],
+        );
+    }
+
+    if let Some(ref input) = app.am_path_input {
+        let w = area.width.min(64).saturating_sub(2).max(48);
+        let h = 7u16.min(area.height.saturating_sub(2)).max(6);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Apply patches (git am) ");
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        f.render_widget(
+            Paragraph::new("Patch files or folders, separated by spaces:")
+                .style(Style::default().fg(app.palette.fg)),
+            rows[0],
+        );
+
+        let input_style = Style::default()
+            .fg(app.palette.fg)
+            .bg(app.palette.selection_bg);
+        let display_input = format!("{}_", input);
+        f.render_widget(Paragraph::new(display_input).style(input_style), rows[1]);
+
+        f.render_widget(
+            Paragraph::new("Enter to review the series · Esc to cancel")
+                .style(Style::default().fg(app.palette.border_inactive)),
+            rows[2],
+        );
+    }
+
+    if let Some(wizard) = &app.init_wizard {
+        let rows_needed = match wizard.step {
+            InitStep::Branch => 3,
+            InitStep::Gitignore => gitignore::TEMPLATES.len() as u16 + 4,
+        };
+        let w = area.width.min(60).saturating_sub(2).max(44);
+        let h = (rows_needed + 4).min(area.height.saturating_sub(2)).max(6);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Initialize repository ");
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let dim = Style::default().fg(app.palette.border_inactive);
+        let mut lines = vec![Line::styled(
+            format!("git init in {}", app.startup_path.display()),
+            dim,
+        )];
+        match wizard.step {
+            InitStep::Branch => {
+                lines.push(Line::styled(
+                    "Default branch name:",
+                    Style::default().fg(app.palette.fg),
+                ));
+                lines.push(Line::styled(
+                    format!("{}_", wizard.branch),
+                    Style::default()
+                        .fg(app.palette.fg)
+                        .bg(app.palette.selection_bg),
+                ));
+                lines.push(Line::raw(""));
+                lines.push(Line::styled("Enter to continue · Esc to cancel", dim));
+            }
+            InitStep::Gitignore => {
+                lines.push(Line::styled(
+                    format!("Initial commit on {} with a .gitignore:", wizard.branch),
+                    Style::default().fg(app.palette.fg),
+                ));
+                let names =
+                    std::iter::once("None").chain(gitignore::TEMPLATES.iter().map(|t| t.name));
+                for (i, name) in names.enumerate() {
+                    let style = if i == wizard.template {
+                        Style::default()
+                            .fg(app.palette.fg)
+                            .bg(app.palette.selection_bg)
+                    } else {
+                        Style::default().fg(app.palette.fg)
+                    };
+                    lines.push(Line::styled(format!("  {}", name), style));
+                }
+                lines.push(Line::raw(""));
+                lines.push(Line::styled(
+                    "Enter to initialize · Backspace back · Esc to cancel",
+                    dim,
+                ));
+            }
+        }
+        f.render_widget(Paragraph::new(lines), inner);
+    }
+
+    if let Some(wizard) = &app.release_wizard {
+        let notes_h = wizard.notes.lines().count().clamp(1, 12) as u16;
+        let rows_needed = match wizard.step {
+            ReleaseStep::Version => changelog::Bump::ALL.len() as u16 + 4,
+            ReleaseStep::Notes => notes_h + 4,
+            ReleaseStep::Push => 5,
+        };
+        let w = area.width.min(72).saturating_sub(2).max(48);
+        let h = (rows_needed + 4).min(area.height.saturating_sub(2)).max(6);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Cut Release ");
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let dim = Style::default().fg(app.palette.border_inactive);
+        let text = Style::default().fg(app.palette.fg);
+        let selected = Style::default()
+            .fg(app.palette.fg)
+            .bg(app.palette.selection_bg);
+        let since = match &wizard.last_tag {
+            Some(tag) => format!("{} commits since {}", wizard.commit_count, tag),
+            None => format!("{} commits, no earlier tag", wizard.commit_count),
+        };
+        let mut lines = vec![Line::styled(since, dim)];
+        match wizard.step {
+            ReleaseStep::Version => {
+                lines.push(Line::styled("Version to tag HEAD as:", text));
+                for (i, bump) in changelog::Bump::ALL.iter().enumerate() {
+                    let hint = if *bump == wizard.suggested {
+                        "  (suggested)"
+                    } else {
+                        ""
+                    };
+                    lines.push(Line::styled(
+                        format!("  {:<6} {}{}", bump.label(), wizard.base.bump(*bump), hint),
+                        if i == wizard.selected { selected } else { text },
+                    ));
+                }
+                let custom = wizard.selected == changelog::Bump::ALL.len();
+                lines.push(Line::styled(
+                    format!(
+                        "  Custom {}{}",
+                        wizard.custom,
+                        if custom { "_" } else { "" }
+                    ),
+                    if custom { selected } else { text },
+                ));
+                lines.push(Line::raw(""));
+                lines.push(Line::styled("Enter to continue · Esc to cancel", dim));
+            }
+            ReleaseStep::Notes => {
+                let mark = if wizard.include_notes { "▣" } else { "□" };
+                lines.push(Line::styled(
+                    format!(
+                        "{} Changelog in the tag message of {}",
+                        mark,
+                        wizard.tag_name()
+                    ),
+                    text,
+                ));
+                let notes: Vec<Line> = wizard
+                    .notes
+                    .lines()
+                    .skip(wizard.notes_scroll as usize)
+                    .take(notes_h as usize)
+                    .map(|l| {
+                        Line::styled(
+                            format!("  {}", l),
+                            if wizard.include_notes { text } else { dim },
+                        )
+                    })
+                    .collect();
+                let pad = notes_h as usize - notes.len();
+                lines.extend(notes);
+                lines.extend(std::iter::repeat_n(Line::raw(""), pad + 1));
+                lines.push(Line::styled(
+                    "Space toggles · Enter to continue · Backspace back · Esc to cancel",
+                    dim,
+                ));
+            }
+            ReleaseStep::Push => {
+                lines.push(Line::styled(
+                    format!("Annotated tag {} on HEAD", wizard.tag_name()),
+                    text,
+                ));
+                match &wizard.remote {
+                    Some(remote) => {
+                        let mark = if wizard.push { "▣" } else { "□" };
+                        lines.push(Line::styled(
+                            format!("{} Push it to {}", mark, remote),
+                            text,
+                        ));
+                    }
+                    None => lines.push(Line::styled("No remote to push to", dim)),
+                }
+                lines.push(Line::raw(""));
+                lines.push(Line::styled(
+                    "Enter to cut the release · Backspace back · Esc to cancel",
+                    dim,
+                ));
+            }
+        }
+        f.render_widget(Paragraph::new(lines), inner);
+    }
+
+    if let Some(picker) = &app.reset_picker {
+        log::render_reset_picker(f, app, picker, area, &mut zones);
+    }
+
+    if let Some(options) = &app.pull_picker {
+        let w = area.width.min(64).saturating_sub(2).max(44);
+        let h = 10u16.min(area.height.saturating_sub(2)).max(6);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Pull ");
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let dim = Style::default().fg(app.palette.border_inactive);
+        let text = Style::default().fg(app.palette.fg);
+        let selected = Style::default()
+            .fg(app.palette.fg)
+            .bg(app.palette.selection_bg);
+        let row = |i: u16| Rect::new(inner.x, inner.y + i, inner.width, 1);
+        f.render_widget(
+            Paragraph::new("Bring in the remote commits by:").style(text),
+            row(0),
+        );
+        for (i, strategy) in git_ops::PullStrategy::ALL.into_iter().enumerate() {
+            let label = match strategy {
+                git_ops::PullStrategy::Merge => "merge      a merge commit when both moved",
+                git_ops::PullStrategy::Rebase => "rebase     local commits replayed on top",
+                git_ops::PullStrategy::FfOnly => "ff-only    refuse unless it fast-forwards",
+            };
+            let style = if strategy == options.strategy {
+                selected
+            } else {
+                text
+            };
+            let rect = row(1 + i as u16);
+            f.render_widget(Paragraph::new(format!("  {}", label)).style(style), rect);
+            zones.push(ClickZone {
+                rect,
+                action: AppAction::PullStrategy(strategy),
+            });
+        }
+        let checkbox = if options.autostash { "▣" } else { "□" };
+        let rect = row(5);
+        f.render_widget(
+            Paragraph::new(format!(
+                "{} Stash local changes around it (--autostash)",
+                checkbox
+            ))
+            .style(text),
+            rect,
+        );
+        zones.push(ClickZone {
+            rect,
+            action: AppAction::TogglePullAutostash,
+        });
+        f.render_widget(
+            Paragraph::new("m/r/f or Enter to pull · a autostash · Esc to cancel").style(dim),
+            row(inner.height.saturating_sub(1)),
+        );
+    }
+
+    if let Some(find) = &app.commit_find {
+        log::render_commit_find(f, app, find, area, &mut zones);
+    }
+
+    if let Some(help) = app.key_help.as_ref() {
+        let rows = app.key_help_rows(&help.query);
+        let w = area.width.min(76).saturating_sub(2).max(40);
+        let h = (rows.len() as u16 + 6)
+            .min(area.height.saturating_sub(2))
+            .max(6);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Key Bindings ");
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let dim = Style::default().fg(app.palette.border_inactive);
+        let heading = Style::default()
+            .fg(app.palette.accent_secondary)
+            .add_modifier(Modifier::BOLD);
+        let text = Style::default().fg(app.palette.fg);
+        f.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled("Filter: ", dim),
+                Span::styled(format!("{}_", help.query), text),
+            ])),
+            Rect::new(inner.x, inner.y, inner.width, 1),
+        );
+        let mut lines: Vec<Line> = rows
+            .iter()
+            .map(|(key, label)| {
+                if key.is_empty() {
+                    Line::styled(label.clone(), heading)
+                } else {
+                    Line::from(vec![
+                        Span::styled(format!("  {:<16}", key), text),
+                        Span::styled(label.clone(), dim),
+                    ])
+                }
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::styled("No keys match", dim));
+        }
+        let list = Rect::new(
+            inner.x,
+            inner.y + 2,
+            inner.width,
+            inner.height.saturating_sub(3),
+        );
+        let max_scroll = (lines.len() as u16).saturating_sub(list.height);
+        f.render_widget(
+            Paragraph::new(lines).scroll((help.scroll.min(max_scroll), 0)),
+            list,
+        );
+        f.render_widget(
+            Paragraph::new("Type to filter · Rebind in config.toml · Esc to close").style(dim),
+            Rect::new(
+                inner.x,
+                inner.y + inner.height.saturating_sub(1),
+                inner.width,
+                1,
+            ),
+        );
+    }
+
+    if let Some(finder) = &app.file_finder {
+        let w = area.width.min(90).saturating_sub(2).max(40);
+        let h = area.height.saturating_sub(4).clamp(8, 24);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Find File ");
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let dim = Style::default().fg(app.palette.border_inactive);
+        let text = Style::default().fg(app.palette.fg);
+        let selected = Style::default()
+            .fg(app.palette.fg)
+            .bg(app.palette.selection_bg);
+        let count = if finder.rx.is_some() {
+            "listing files…".to_string()
+        } else {
+            format!("{} of {}", finder.filtered.len(), finder.files.len())
+        };
+        f.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled("> ", dim),
+                Span::styled(format!("{}_", finder.query), text),
+                Span::styled(format!("  {}", count), dim),
+            ])),
+            Rect::new(inner.x, inner.y, inner.width, 1),
+        );
+
+        let rows = inner.height.saturating_sub(3) as usize;
+        let skip = (finder.selected + 1).saturating_sub(rows);
+        for (row, &idx) in finder.filtered.iter().enumerate().skip(skip).take(rows) {
+            let Some(path) = finder.files.get(idx) else {
+                continue;
+            };
+            let rect = Rect::new(inner.x, inner.y + 2 + (row - skip) as u16, inner.width, 1);
+            let mut spans = vec![Span::raw(truncate_middle(
+                path,
+                (inner.width as usize).saturating_sub(10),
+            ))];
+            if app.git.entries.iter().any(|e| e.path == *path) {
+                spans.push(Span::styled("  changed", dim));
+            }
+            let style = if row == finder.selected {
+                selected
+            } else {
+                text
+            };
+            f.render_widget(Paragraph::new(Line::from(spans).style(style)), rect);
+            zones.push(ClickZone {
+                rect,
+                action: AppAction::PickFoundFile(row),
+            });
+        }
+        f.render_widget(
+            Paragraph::new("Enter: diff if changed, else Explorer · Ctrl+E: Explorer · Esc")
+                .style(dim),
+            Rect::new(
+                inner.x,
+                inner.y + inner.height.saturating_sub(1),
+                inner.width,
+                1,
+            ),
+        );
+    }
+
+    if let Some(picker) = &app.syntax_picker {
+        let rows: Vec<&str> = ["Detect automatically", "Plain text"]
+            .into_iter()
+            .chain(highlight::LANGUAGES.iter().map(|(label, _)| *label))
+            .collect();
+        let w = area.width.min(44).saturating_sub(2).max(32);
+        let h = (rows.len() as u16 + 6)
+            .min(area.height.saturating_sub(2))
+            .max(8);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let name = picker
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(format!(" Highlight {} As ", name));
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let dim = Style::default().fg(app.palette.border_inactive);
+        let text = Style::default().fg(app.palette.fg);
+        let selected = Style::default()
+            .fg(app.palette.fg)
+            .bg(app.palette.selection_bg);
+        let visible = inner.height.saturating_sub(2) as usize;
+        let skip = (picker.selected + 1).saturating_sub(visible);
+        for (i, label) in rows.iter().enumerate().skip(skip).take(visible) {
+            let rect = Rect::new(inner.x, inner.y + (i - skip) as u16, inner.width, 1);
+            let style = if i == picker.selected { selected } else { text };
+            f.render_widget(Paragraph::new(*label).style(style), rect);
+            zones.push(ClickZone {
+                rect,
+                action: AppAction::PickSyntax(i),
+            });
+        }
+        f.render_widget(
+            Paragraph::new("Kept until lzgit exits · Esc to cancel").style(dim),
+            Rect::new(
+                inner.x,
+                inner.y + inner.height.saturating_sub(1),
+                inner.width,
+                1,
+            ),
+        );
+    }
+
+    if let Some(picker) = &app.message_picker {
+        let messages = app.message_picker_rows();
+        let preview: Vec<&str> = messages
+            .get(picker.selected)
+            .map(|(_, m)| {
+                m.lines()
+                    .skip(1)
+                    .skip_while(|l| l.trim().is_empty())
+                    .take(4)
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Preview, blank lines and the hint below the list
+        let extra = preview.len() as u16 + if preview.is_empty() { 2 } else { 3 };
+        let w = area.width.min(84).saturating_sub(2).max(48);
+        let h = (messages.len() as u16 + extra + 2)
+            .min(area.height.saturating_sub(2))
+            .max(6);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(if picker.templates.is_empty() {
+                " Recent Commit Messages "
+            } else {
+                " Commit Templates & Recent Messages "
+            });
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let dim = Style::default().fg(app.palette.border_inactive);
+        let text = Style::default().fg(app.palette.fg);
+        let selected = Style::default()
+            .fg(app.palette.fg)
+            .bg(app.palette.selection_bg);
+        let rows = inner.height.saturating_sub(extra) as usize;
+        let skip = (picker.selected + 1).saturating_sub(rows);
+        for (i, (template, message)) in messages.iter().enumerate().skip(skip).take(rows) {
+            let rect = Rect::new(inner.x, inner.y + (i - skip) as u16, inner.width, 1);
+            let subject = message.lines().next().unwrap_or_default();
+            let more = message.lines().count().saturating_sub(1);
+            let label = template
+                .map(|name| format!("{}  ", name))
+                .unwrap_or_default();
+            let mut spans = vec![
+                Span::styled(
+                    label.clone(),
+                    Style::default().fg(app.palette.accent_secondary),
+                ),
+                Span::raw(truncate_middle(
+                    subject,
+                    (inner.width as usize)
+                        .saturating_sub(12)
+                        .saturating_sub(label.chars().count()),
+                )),
+            ];
+            if more > 0 {
+                spans.push(Span::styled(format!("  +{} lines", more), dim));
+            }
+            let style = if i == picker.selected { selected } else { text };
+            f.render_widget(Paragraph::new(Line::from(spans).style(style)), rect);
+            zones.push(ClickZone {
+                rect,
+                action: AppAction::PickCommitMessage(i),
+            });
+        }
+
+        let mut lines = Vec::new();
+        if !preview.is_empty() {
+            lines.push(Line::raw(""));
+            lines.extend(preview.iter().map(|l| Line::styled(l.to_string(), dim)));
+        }
+        lines.push(Line::raw(""));
+        lines.push(Line::styled(
+            if picker.selected < picker.templates.len() {
+                "Enter to edit it · Esc to cancel"
+            } else {
+                "Enter to edit it · d to forget · Esc to cancel"
+            },
+            dim,
+        ));
+        let used = rows.min(messages.len()) as u16;
+        let rest = Rect::new(
+            inner.x,
+            inner.y + used,
+            inner.width,
+            inner.height.saturating_sub(used),
+        );
+        f.render_widget(Paragraph::new(lines), rest);
+    }
+
+    if let Some(input) = &app.changelog_input {
+        let w = area.width.min(60).saturating_sub(2).max(44);
+        let h = 7u16.min(area.height.saturating_sub(2)).max(6);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Changelog ");
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let dim = Style::default().fg(app.palette.border_inactive);
+        let lines = vec![
+            Line::styled(
+                "Commits in from..to (v1.0..v1.1, or a ref for ref..HEAD):",
+                Style::default().fg(app.palette.fg),
+            ),
+            Line::styled(
+                format!("{}_", input),
+                Style::default()
+                    .fg(app.palette.fg)
+                    .bg(app.palette.selection_bg),
+            ),
+            Line::raw(""),
+            Line::styled("Enter to generate · Esc to cancel", dim),
+        ];
+        f.render_widget(Paragraph::new(lines), inner);
+    }
+
+    if let Some(view) = &app.changelog_view {
+        let w = area.width.min(100).saturating_sub(4).max(44);
+        let h = area.height.saturating_sub(4).max(8);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(format!(" Changelog {} ", view.range));
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let body_h = inner.height.saturating_sub(2);
+        let lines: Vec<Line> = view
+            .markdown
+            .lines()
+            .map(|l| {
+                let style = if l.starts_with("### ") {
+                    Style::default()
+                        .fg(app.palette.accent_secondary)
+                        .add_modifier(Modifier::BOLD)
+                } else if l.starts_with("## ") {
+                    Style::default()
+                        .fg(app.palette.accent_primary)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(app.palette.fg)
+                };
+                Line::styled(l, style)
+            })
+            .collect();
+        f.render_widget(
+            Paragraph::new(lines)
+                .wrap(Wrap { trim: false })
+                .scroll((view.scroll_y, 0)),
+            Rect::new(inner.x, inner.y, inner.width, body_h),
+        );
+
+        let buttons_y = inner.bottom().saturating_sub(1);
+        if let Some(path) = &view.export_path {
+            f.render_widget(
+                Paragraph::new(Line::from(vec![
+                    Span::styled("Save as: ", Style::default().fg(app.palette.fg)),
+                    Span::styled(
+                        format!("{}_", path),
+                        Style::default()
+                            .fg(app.palette.fg)
+                            .bg(app.palette.selection_bg),
+                    ),
+                    Span::styled(
+                        "  Enter to save · Esc to cancel",
+                        Style::default().fg(app.palette.border_inactive),
+                    ),
+                ])),
+                Rect::new(inner.x, buttons_y, inner.width, 1),
+            );
+        } else {
+            let mut bx = inner.x;
+            for (label, action) in [
+                (" Copy (y) ", AppAction::CopyChangelog),
+                (" Export (e) ", AppAction::ExportChangelog),
+                (" Close (Esc) ", AppAction::CloseChangelog),
+            ] {
+                let rect = Rect::new(bx, buttons_y, label.len() as u16, 1);
+                let style = Style::default()
+                    .bg(app.palette.btn_bg)
+                    .fg(app.palette.btn_fg)
+                    .add_modifier(Modifier::BOLD);
+                f.render_widget(Paragraph::new(label).style(style), rect);
+                zones.push(ClickZone { rect, action });
+                bx = rect.right() + 1;
+            }
+        }
+    }
+
+    if let Some(input) = &app.clone_input {
+        let w = area.width.min(70).saturating_sub(2).max(48);
+        let h = 9u16.min(area.height.saturating_sub(2)).max(8);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Clone repository ");
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let label = Style::default().fg(app.palette.fg);
+        let field = |text: &str, focused: bool| {
+            if focused {
+                Line::styled(
+                    format!("{}_", text),
+                    Style::default()
+                        .fg(app.palette.fg)
+                        .bg(app.palette.selection_bg),
+                )
+            } else {
+                Line::styled(text.to_string(), label)
+            }
+        };
+        let lines = vec![
+            Line::styled("Repository URL:", label),
+            field(&input.url, !input.focus_dest),
+            Line::styled(
+                format!("Destination (in {}):", app.current_path.display()),
+                label,
+            ),
+            field(&input.dest, input.focus_dest),
+            Line::raw(""),
+            Line::styled(
+                "Enter to continue · Tab to switch field · Esc to cancel",
+                Style::default().fg(app.palette.border_inactive),
+            ),
+        ];
+        f.render_widget(Paragraph::new(lines), inner);
+    }
+
+    if let Some(input) = &app.tag_input {
+        let w = area.width.min(70).saturating_sub(2).max(48);
+        let h = 14u16.min(area.height.saturating_sub(2)).max(10);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(format!(
+                " Tag {} ",
+                git::truncate_to_width(&input.label, w.saturating_sub(8) as usize)
+            ));
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        let label = Style::default().fg(app.palette.fg);
+        let focused = Style::default()
+            .fg(app.palette.fg)
+            .bg(app.palette.selection_bg);
+        f.render_widget(Paragraph::new("Name:").style(label), rows[0]);
+        let name = if input.focus_message {
+            Paragraph::new(input.name.as_str()).style(label)
+        } else {
+            Paragraph::new(format!("{}_", input.name)).style(focused)
+        };
+        f.render_widget(name, rows[1]);
+        f.render_widget(
+            Paragraph::new("Message (empty for a lightweight tag):").style(label),
+            rows[2],
+        );
+
+        // Keep the end of a long message in view
+        let mut message = input.message.clone();
+        if input.focus_message {
+            message.push('_');
+        }
+        let lines: Vec<&str> = message.split('\n').collect();
+        let skip = lines.len().saturating_sub(rows[3].height as usize);
+        let style = if input.focus_message { focused } else { label };
+        f.render_widget(
+            Paragraph::new(lines[skip..].join("\n")).style(style),
+            rows[3],
+        );
+
+        f.render_widget(
+            Paragraph::new("Ctrl+S to create · Tab to switch field · Esc to cancel")
+                .style(Style::default().fg(app.palette.border_inactive)),
+            rows[4],
+        );
+    }
+
+    if let Some(input) = &app.remote_input {
+        let w = area.width.min(70).saturating_sub(2).max(48);
+        let h = 9u16.min(area.height.saturating_sub(2)).max(7);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let title = match &input.kind {
+            RemoteInputKind::Add => " Add Remote ".to_string(),
+            RemoteInputKind::Rename(old) => format!(" Rename `{}` ", old),
+            RemoteInputKind::Url(name, false) => format!(" URL of `{}` ", name),
+            RemoteInputKind::Url(name, true) => format!(" Push URL of `{}` ", name),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(title);
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        let label = Style::default().fg(app.palette.fg);
+        let focused = Style::default()
+            .fg(app.palette.fg)
+            .bg(app.palette.selection_bg);
+        let field = |text: &str, focus: bool| {
+            if focus {
+                Paragraph::new(format!("{}_", text)).style(focused)
+            } else {
+                Paragraph::new(text.to_string()).style(label)
+            }
+        };
+        let mut fields: Vec<(&str, &str, bool)> = Vec::new();
+        if !matches!(input.kind, RemoteInputKind::Url(..)) {
+            fields.push(("Name:", input.name.as_str(), !input.focus_url));
+        }
+        if !matches!(input.kind, RemoteInputKind::Rename(_)) {
+            fields.push(("URL:", input.url.as_str(), input.focus_url));
+        }
+        for (i, (title, text, focus)) in fields.into_iter().enumerate() {
+            f.render_widget(Paragraph::new(title).style(label), rows[i * 2]);
+            f.render_widget(field(text, focus), rows[i * 2 + 1]);
+        }
+
+        let hint = if matches!(input.kind, RemoteInputKind::Add) {
+            "Enter to add · Tab to switch field · Esc to cancel"
+        } else {
+            "Enter to save · Esc to cancel"
+        };
+        f.render_widget(
+            Paragraph::new(hint).style(Style::default().fg(app.palette.border_inactive)),
+            rows[5],
+        );
+    }
+
+    if let Some(picker) = &app.gitignore_picker {
+        let w = area.width.saturating_sub(4).clamp(40, 90);
+        let h = area.height.saturating_sub(4).clamp(8, 26);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" .gitignore template ");
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner);
+        let cols = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(14), Constraint::Min(1)])
+            .split(rows[0]);
+
+        let names: Vec<Line> = gitignore::TEMPLATES
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let style = if i == picker.selected {
+                    Style::default()
+                        .fg(app.palette.fg)
+                        .bg(app.palette.selection_bg)
+                } else {
+                    Style::default().fg(app.palette.fg)
+                };
+                Line::styled(format!(" {:<11}", t.name), style)
+            })
+            .collect();
+        f.render_widget(Paragraph::new(names), cols[0]);
+
+        // The file as it will be saved; appended patterns are highlighted
+        let template = &gitignore::TEMPLATES[picker.selected];
+        let text = gitignore::apply(&picker.existing, template);
+        let kept = if text.starts_with(&picker.existing) {
+            picker.existing.lines().count()
+        } else {
+            0
+        };
+        let preview: Vec<Line> = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let fg = if i < kept {
+                    app.palette.border_inactive
+                } else {
+                    app.palette.diff_add_fg
+                };
+                Line::styled(line.to_string(), Style::default().fg(fg))
+            })
+            .collect();
+        let preview_block = Block::default()
+            .borders(Borders::LEFT)
+            .border_style(Style::default().fg(app.palette.border_inactive));
+        f.render_widget(
+            Paragraph::new(preview)
+                .block(preview_block)
+                .scroll((picker.scroll_y, 0)),
+            cols[1],
+        );
+
+        let hint = if text == picker.existing {
+            "Already in .gitignore · Esc to close"
+        } else {
+            "Enter to save to .gitignore · J/K scroll · Esc to cancel"
+        };
+        f.render_widget(
+            Paragraph::new(hint).style(Style::default().fg(app.palette.border_inactive)),
+            rows[1],
+        );
+    }
+
+    if let Some(input) = &app.bundle_input {
+        let w = area.width.min(64).saturating_sub(2).max(48);
+        let h = 7u16.min(area.height.saturating_sub(2)).max(6);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let (title, prompt, hint) = match input.kind {
+            BundleInputKind::Create => (
+                " Create bundle ",
+                "Bundle file, then refs or ranges (main, v1.0..main, --all):",
+                "Enter to create · Esc to cancel",
+            ),
+            BundleInputKind::Fetch => (
+                " Fetch from bundle ",
+                "Bundle file:",
+                "Enter to review its refs · Esc to cancel",
+            ),
+            BundleInputKind::Pull => (
+                " Pull from bundle ",
+                "Bundle file:",
+                "Enter to review its refs · Esc to cancel",
+            ),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(title);
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        f.render_widget(
+            Paragraph::new(prompt).style(Style::default().fg(app.palette.fg)),
+            rows[0],
+        );
+
+        let input_style = Style::default()
+            .fg(app.palette.fg)
+            .bg(app.palette.selection_bg);
+        let display_input = format!("{}_", input.text);
+        f.render_widget(Paragraph::new(display_input).style(input_style), rows[1]);
+
+        f.render_widget(
+            Paragraph::new(hint).style(Style::default().fg(app.palette.border_inactive)),
+            rows[2],
+        );
+    }
+
+    if let Some(input) = &app.branch_input {
+        branches::render_branch_input(f, app, input, area, &mut zones);
+    }
+
+    if let Some(input) = &app.explorer_input {
+        let w = area.width.min(60).saturating_sub(2).max(40);
+        let h = 7u16.min(area.height.saturating_sub(2)).max(6);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::None,
+        });
+
+        f.render_widget(Clear, modal);
+
+        let (title, prompt) = match input.kind {
+            ExplorerInputKind::Create => (" New ", "Name (end with / for a folder):"),
+            ExplorerInputKind::Rename(_) => (" Rename ", "New name (a/b moves it):"),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(title);
+        f.render_widget(block.clone(), modal);
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        f.render_widget(
+            Paragraph::new(prompt).style(Style::default().fg(app.palette.fg)),
+            rows[0],
+        );
+
+        let input_style = Style::default()
+            .fg(app.palette.fg)
+            .bg(app.palette.selection_bg);
+        f.render_widget(
+            Paragraph::new(format!("{}_", input.text)).style(input_style),
+            rows[1],
+        );
+
+        f.render_widget(
+            Paragraph::new("Enter to confirm · Esc to cancel")
+                .style(Style::default().fg(app.palette.border_inactive)),
+            rows[2],
+        );
+    }
+
+    if let Some(ref input) = app.goto_commit_input {
+        log::render_goto_commit_input(f, app, input, area, &mut zones);
+    }
+
+    if app.snapshot_ui.open {
+        snapshots::render_snapshot_picker(f, app, area, &mut zones);
+    }
+
+    if app.tag_ui.open {
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::CloseTagPicker,
+        });
+
+        let w = area.width.min(96).saturating_sub(2).max(60);
+        let h = area.height.min(22).saturating_sub(2).max(12);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Tags ");
+        f.render_widget(block.clone(), modal);
+        zones.push(ClickZone {
+            rect: modal,
+            action: AppAction::None,
+        });
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(inner);
+
+        let now = app.now();
+        let name_w = app
+            .tag_ui
+            .tags
+            .iter()
+            .map(|t| t.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(32);
+        let dim = Style::default().fg(app.palette.border_inactive);
+        let list_items: Vec<ListItem> = app
+            .tag_ui
+            .tags
+            .iter()
+            .map(|t| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        git::pad_to_width(git::truncate_to_width(&t.name, name_w), name_w),
+                        Style::default().fg(app.palette.fg),
+                    ),
+                    Span::styled(if t.annotated { "  ◆ " } else { "  ◇ " }, dim),
+                    Span::styled(
+                        format!(
+                            "{}  {:>8}  ",
+                            t.target,
+                            git_ops::relative_age(t.timestamp, now)
+                        ),
+                        Style::default().fg(app.palette.size_color),
+                    ),
+                    Span::styled(t.subject.clone(), dim),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(list_items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_set(ratatui::symbols::border::PLAIN)
+                    .border_style(Style::default().fg(app.palette.border_inactive))
+                    .title(format!(
+                        " Tags ({})  ◆ annotated  ◇ lightweight ",
+                        app.tag_ui.tags.len()
+                    )),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(app.palette.selection_bg)
+                    .fg(app.palette.fg)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("▎ ");
+
+        f.render_stateful_widget(list, rows[0], &mut app.tag_ui.list_state);
+
+        let list_inner = rows[0].inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        let start = app.tag_ui.list_state.offset();
+        let end = (start + list_inner.height as usize).min(app.tag_ui.tags.len());
+        for (i, idx) in (start..end).enumerate() {
+            let rect = Rect::new(list_inner.x, list_inner.y + i as u16, list_inner.width, 1);
+            zones.push(ClickZone {
+                rect,
+                action: AppAction::SelectTag(idx),
+            });
+        }
+
+        let mut bx = rows[1].x;
+        for (label, action, color) in [
+            (" Show ", AppAction::TagShow, app.palette.accent_secondary),
+            (" Push (p) ", AppAction::TagPush, app.palette.accent_primary),
+            (
+                " Push All (P) ",
+                AppAction::TagPushAll,
+                app.palette.accent_primary,
+            ),
+            (" Delete (x) ", AppAction::TagDelete, app.palette.btn_bg),
+            (" Close ", AppAction::CloseTagPicker, app.palette.menu_bg),
+        ] {
+            let bw = label.len() as u16;
+            let rect = Rect::new(bx, rows[1].y, bw, 1);
+            let style = Style::default()
+                .bg(color)
+                .fg(app.palette.btn_fg)
+                .add_modifier(Modifier::BOLD);
+            f.render_widget(Paragraph::new(label).style(style), rect);
+            zones.push(ClickZone { rect, action });
+            bx += bw + 2;
+        }
+
+        if let Some(msg) = app.tag_ui.status.as_deref() {
+            let used = bx.saturating_sub(rows[1].x);
+            f.render_widget(
+                Paragraph::new(msg).style(Style::default().fg(app.palette.btn_bg)),
+                Rect::new(bx, rows[1].y, rows[1].width.saturating_sub(used), 1),
+            );
+        }
+    }
+
+    if app.remote_ui.open {
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::CloseRemotes,
+        });
+
+        let w = area.width.min(96).saturating_sub(2).max(60);
+        let h = area.height.min(22).saturating_sub(2).max(12);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Remotes ");
+        f.render_widget(block.clone(), modal);
+        zones.push(ClickZone {
+            rect: modal,
+            action: AppAction::None,
+        });
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(inner);
+
+        let target = app.target_remote().map(str::to_string);
+        let name_w = app
+            .remote_ui
+            .remotes
+            .iter()
+            .map(|r| r.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(24);
+        let dim = Style::default().fg(app.palette.border_inactive);
+        let list_items: Vec<ListItem> = app
+            .remote_ui
+            .remotes
+            .iter()
+            .map(|r| {
+                let mut spans = vec![
+                    Span::styled(
+                        if target.as_deref() == Some(r.name.as_str()) {
+                            "★ "
+                        } else {
+                            "  "
+                        },
+                        Style::default().fg(app.palette.accent_secondary),
+                    ),
+                    Span::styled(
+                        git::pad_to_width(git::truncate_to_width(&r.name, name_w), name_w),
+                        Style::default().fg(app.palette.fg),
+                    ),
+                    Span::styled(
+                        format!("  {}", r.fetch_url),
+                        Style::default().fg(app.palette.size_color),
+                    ),
+                ];
+                if r.push_url != r.fetch_url {
+                    spans.push(Span::styled(format!("  → {}", r.push_url), dim));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let title = match target.as_deref() {
+            Some(name) => format!(
+                " Remotes ({})  ★ Push/Pull/Fetch use `{}` ",
+                app.remote_ui.remotes.len(),
+                name
+            ),
+            None => format!(
+                " Remotes ({})  Push/Pull/Fetch use the upstream ",
+                app.remote_ui.remotes.len()
+            ),
+        };
+        let list = List::new(list_items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_set(ratatui::symbols::border::PLAIN)
+                    .border_style(Style::default().fg(app.palette.border_inactive))
+                    .title(title),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(app.palette.selection_bg)
+                    .fg(app.palette.fg)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("▎ ");
+
+        f.render_stateful_widget(list, rows[0], &mut app.remote_ui.list_state);
+
+        let list_inner = rows[0].inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        let start = app.remote_ui.list_state.offset();
+        let end = (start + list_inner.height as usize).min(app.remote_ui.remotes.len());
+        for (i, idx) in (start..end).enumerate() {
+            let rect = Rect::new(list_inner.x, list_inner.y + i as u16, list_inner.width, 1);
+            zones.push(ClickZone {
+                rect,
+                action: AppAction::SelectRemote(idx),
+            });
+        }
+
+        let mut bx = rows[1].x;
+        for (label, action, color) in [
+            (
+                " Use (Enter) ",
+                AppAction::RemoteUse,
+                app.palette.accent_secondary,
+            ),
+            (
+                " Add (a) ",
+                AppAction::RemoteAdd,
+                app.palette.accent_primary,
+            ),
+            (
+                " Rename (r) ",
+                AppAction::RemoteRename,
+                app.palette.accent_primary,
+            ),
+            (
+                " URL (u) ",
+                AppAction::RemoteUrl(false),
+                app.palette.accent_primary,
+            ),
+            (
+                " Push URL (U) ",
+                AppAction::RemoteUrl(true),
+                app.palette.accent_primary,
+            ),
+            (" Remove (x) ", AppAction::RemoteRemove, app.palette.btn_bg),
+            (" Close ", AppAction::CloseRemotes, app.palette.menu_bg),
+        ] {
+            let bw = label.len() as u16;
+            if bx + bw > rows[1].right() {
+                break;
+            }
+            let rect = Rect::new(bx, rows[1].y, bw, 1);
+            let style = Style::default()
+                .bg(color)
+                .fg(app.palette.btn_fg)
+                .add_modifier(Modifier::BOLD);
+            f.render_widget(Paragraph::new(label).style(style), rect);
+            zones.push(ClickZone { rect, action });
+            bx += bw + 2;
+        }
+
+        if let Some(msg) = app.remote_ui.status.as_deref() {
+            let used = bx.saturating_sub(rows[1].x);
+            f.render_widget(
+                Paragraph::new(msg).style(Style::default().fg(app.palette.btn_bg)),
+                Rect::new(bx, rows[1].y, rows[1].width.saturating_sub(used), 1),
+            );
+        }
+    }
+
+    if app.bookmarks_ui.open {
+        zones.push(ClickZone {
+            rect: area,
+            action: AppAction::CloseBookmarks,
+        });
+
+        let w = area.width.min(96).saturating_sub(2).max(60);
+        let h = area.height.min(22).saturating_sub(2).max(12);
+        let x = area.x + (area.width.saturating_sub(w)) / 2;
+        let y = area.y + (area.height.saturating_sub(h)) / 2;
+        let modal = Rect::new(x, y, w, h);
+
+        f.render_widget(Clear, modal);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(ratatui::symbols::border::PLAIN)
+            .border_style(Style::default().fg(app.palette.accent_primary))
+            .title(" Bookmarks ");
+        f.render_widget(block.clone(), modal);
+        zones.push(ClickZone {
+            rect: modal,
+            action: AppAction::None,
+        });
+
+        let inner = modal.inner(Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(0),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        let list_items: Vec<ListItem> = app
+            .bookmarks
+            .iter()
+            .map(|b| {
+                let key = b
+                    .key
+                    .map(|k| format!("[{}] ", k))
+                    .unwrap_or_else(|| "    ".to_string());
+                let shown_path = b
+                    .stored
+                    .clone()
+                    .unwrap_or_else(|| b.path.to_string_lossy().to_string());
+                ListItem::new(Line::from(vec![
+                    Span::styled(key, Style::default().fg(app.palette.accent_secondary)),
+                    Span::styled(
+                        format!("{} {}", b.icon(), b.name),
+                        Style::default().fg(app.palette.fg),
+                    ),
+                    Span::styled(
+                        format!("  {}", shown_path),
+                        Style::default().fg(app.palette.size_color),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(list_items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_set(ratatui::symbols::border::PLAIN)
+                    .border_style(Style::default().fg(app.palette.border_inactive))
+                    .title(format!(" Bookmarks ({}) ", app.bookmarks.len())),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(app.palette.selection_bg)
+                    .fg(app.palette.fg)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("▎ ");
+
+        f.render_stateful_widget(list, rows[0], &mut app.bookmarks_ui.list_state);
+
+        let list_inner = rows[0].inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        let start = app.bookmarks_ui.list_state.offset();
+        let end = (start + list_inner.height as usize).min(app.bookmarks.len());
+        for (i, idx) in (start..end).enumerate() {
+            let rect = Rect::new(list_inner.x, list_inner.y + i as u16, list_inner.width, 1);
+            zones.push(ClickZone {
+                rect,
+                action: AppAction::SelectBookmark(idx),
+            });
+        }
+
+        if let Some(name) = app.bookmarks_ui.rename.as_deref() {
+            f.render_widget(
+                Paragraph::new(Line::from(vec![
+                    Span::styled("Rename: ", Style::default().fg(app.palette.accent_primary)),
+                    Span::styled(format!("{}▏", name), Style::default().fg(app.palette.fg)),
+                ])),
+                rows[1],
+            );
+        } else {
+            let mut bx = rows[1].x;
+            for (label, action, color) in [
+                (
+                    " Open (⏎) ",
+                    AppAction::BookmarkOpen,
+                    app.palette.accent_primary,
+                ),
+                (
+                    " Rename (r) ",
+                    AppAction::BookmarkRename,
+                    app.palette.accent_secondary,
+                ),
+                (
+                    " ▲ (K) ",
+                    AppAction::BookmarkMoveUp,
+                    app.palette.accent_tertiary,
+                ),
+                (
+                    " ▼ (J) ",
+                    AppAction::BookmarkMoveDown,
+                    app.palette.accent_tertiary,
+                ),
+                (
+                    " Delete (x) ",
+                    AppAction::BookmarkDelete,
+                    app.palette.btn_bg,
+                ),
+                (" Close ", AppAction::CloseBookmarks, app.palette.menu_bg),
+            ] {
+                let bw = display_width(label) as u16;
+                let rect = Rect::new(bx, rows[1].y, bw, 1);
+                let style = Style::default()
+                    .bg(color)
+                    .fg(app.palette.btn_fg)
+                    .add_modifier(Modifier::BOLD);
+                f.render_widget(Paragraph::new(label).style(style), rect);
+                zones.push(ClickZone { rect, action });
+                bx += bw + 2;
+            }
+        }
+
+        let hint = app
+            .bookmarks_ui
+            .status
+            .clone()
+            .unwrap_or_else(|| "1-9 assign Alt+digit quick key · Esc close".to_string());
+        f.render_widget(
+            Paragraph::new(hint).style(Style::default().fg(app.palette.border_inactive)),
+            rows[2],
+        );
+    }
+
+    // Last, so it shows above the picker that asked
+    if let Some(dialog) = &app.confirm {
+        render_confirm_dialog(f, app, area, dialog, &mut zones);
+    }
+
+    if app.update_in_progress {
+        let spinner_chars = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+        let spinner = spinner_chars[app.spinner_frame % spinner_chars.len()];
+        let text = format!(" {} Updating... ", spinner);
+        let w = text.len() as u16 + 2;
+        let x = area.x + area.width.saturating_sub(w + 1);
+        let y = area.y + area.height.saturating_sub(2);
+        let rect = Rect::new(x, y, w, 1);
+
+        f.render_widget(
+            Paragraph::new(text).style(
+                Style::default()
+                    .fg(app.palette.bg)
+                    .bg(app.palette.accent_primary),
+            ),
+            rect,
+        );
+    }
+
+    zones
+}
+
+fn idx_to_color(idx: u8) -> Color {
+    match idx {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        7 => Color::White,
+        8 => Color::DarkGray,
+        9 => Color::LightRed,
+        10 => Color::LightGreen,
+        11 => Color::LightYellow,
+        12 => Color::LightBlue,
+        13 => Color::LightMagenta,
+        14 => Color::LightCyan,
+        15 => Color::Gray,
+        n => Color::Indexed(n),
+    }
+}
@@ -0,0 +1,189 @@
+//! Snapshot dialogs: the snapshot picker and the name prompt
+
+use super::*;
+
+/// Name prompt for a new snapshot
+pub(crate) fn render_snapshot_name_input(
+    f: &mut Frame,
+    app: &App,
+    input: &str,
+    area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    let w = area.width.min(50).saturating_sub(2).max(40);
+    let h = 7u16.min(area.height.saturating_sub(2)).max(6);
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + (area.height.saturating_sub(h)) / 2;
+    let modal = Rect::new(x, y, w, h);
+
+    zones.push(ClickZone {
+        rect: area,
+        action: AppAction::None,
+    });
+
+    f.render_widget(Clear, modal);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.accent_primary))
+        .title(" Snapshot ");
+    f.render_widget(block.clone(), modal);
+
+    let inner = modal.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    f.render_widget(
+        Paragraph::new("Enter snapshot name:").style(Style::default().fg(app.palette.fg)),
+        rows[0],
+    );
+
+    let input_style = Style::default()
+        .fg(app.palette.fg)
+        .bg(app.palette.selection_bg);
+    let display_input = format!("{}_", input);
+    f.render_widget(Paragraph::new(display_input).style(input_style), rows[1]);
+
+    f.render_widget(
+        Paragraph::new("Enter to save · Esc to cancel")
+            .style(Style::default().fg(app.palette.border_inactive)),
+        rows[2],
+    );
+}
+
+/// The snapshot picker with diff, restore and delete
+pub(crate) fn render_snapshot_picker(
+    f: &mut Frame,
+    app: &mut App,
+    area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    zones.push(ClickZone {
+        rect: area,
+        action: AppAction::CloseSnapshots,
+    });
+
+    let w = area.width.min(96).saturating_sub(2).max(60);
+    let h = area.height.min(22).saturating_sub(2).max(12);
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + (area.height.saturating_sub(h)) / 2;
+    let modal = Rect::new(x, y, w, h);
+
+    f.render_widget(Clear, modal);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.accent_primary))
+        .title(" Snapshots ");
+    f.render_widget(block.clone(), modal);
+    zones.push(ClickZone {
+        rect: modal,
+        action: AppAction::None,
+    });
+
+    let inner = modal.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let list_items: Vec<ListItem> = app
+        .snapshot_ui
+        .snapshots
+        .iter()
+        .map(|s| {
+            ListItem::new(Line::from(vec![
+                Span::styled(s.name.clone(), Style::default().fg(app.palette.fg)),
+                Span::styled(
+                    format!("  {}  {}", s.date, s.short),
+                    Style::default().fg(app.palette.size_color),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(list_items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_set(ratatui::symbols::border::PLAIN)
+                .border_style(Style::default().fg(app.palette.border_inactive))
+                .title(format!(" Snapshots ({}) ", app.snapshot_ui.snapshots.len())),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(app.palette.selection_bg)
+                .fg(app.palette.fg)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▎ ");
+
+    f.render_stateful_widget(list, rows[0], &mut app.snapshot_ui.list_state);
+
+    let list_inner = rows[0].inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    let start = app.snapshot_ui.list_state.offset();
+    let end = (start + list_inner.height as usize).min(app.snapshot_ui.snapshots.len());
+    for (i, idx) in (start..end).enumerate() {
+        let rect = Rect::new(list_inner.x, list_inner.y + i as u16, list_inner.width, 1);
+        zones.push(ClickZone {
+            rect,
+            action: AppAction::SelectSnapshot(idx),
+        });
+    }
+
+    let mut bx = rows[1].x;
+    for (label, action, color) in [
+        (
+            " Diff (d) ",
+            AppAction::SnapshotDiff,
+            app.palette.accent_secondary,
+        ),
+        (
+            " Restore (r) ",
+            AppAction::SnapshotRestore,
+            app.palette.accent_primary,
+        ),
+        (
+            " Delete (x) ",
+            AppAction::SnapshotDelete,
+            app.palette.btn_bg,
+        ),
+        (" Close ", AppAction::CloseSnapshots, app.palette.menu_bg),
+    ] {
+        let bw = label.len() as u16;
+        let rect = Rect::new(bx, rows[1].y, bw, 1);
+        let style = Style::default()
+            .bg(color)
+            .fg(app.palette.btn_fg)
+            .add_modifier(Modifier::BOLD);
+        f.render_widget(Paragraph::new(label).style(style), rect);
+        zones.push(ClickZone { rect, action });
+        bx += bw + 2;
+    }
+
+    if let Some(msg) = app.snapshot_ui.status.as_deref() {
+        let used = bx.saturating_sub(rows[1].x);
+        f.render_widget(
+            Paragraph::new(msg).style(Style::default().fg(app.palette.btn_bg)),
+            Rect::new(bx, rows[1].y, rows[1].width.saturating_sub(used), 1),
+        );
+    }
+}
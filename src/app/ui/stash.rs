@@ -0,0 +1,252 @@
+//! Stash dialogs: the stash picker and the new-stash dialog
+
+use super::*;
+
+/// The stash picker with apply, pop and drop
+pub(crate) fn render_stash_picker(
+    f: &mut Frame,
+    app: &mut App,
+    area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    zones.push(ClickZone {
+        rect: area,
+        action: AppAction::CloseStashPicker,
+    });
+
+    let w = area.width.min(96).saturating_sub(2).max(60);
+    let h = area.height.min(22).saturating_sub(2).max(12);
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + (area.height.saturating_sub(h)) / 2;
+    let modal = Rect::new(x, y, w, h);
+
+    f.render_widget(Clear, modal);
+
+    let title = if app.stash_ui.query.trim().is_empty() {
+        " Stash (S) ".to_string()
+    } else {
+        format!(" Stash (S)  filter: {} ", app.stash_ui.query.trim())
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.accent_primary))
+        .title(title);
+    f.render_widget(block.clone(), modal);
+
+    let inner = modal.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let filter_hint = "Type to filter  Backspace delete  Ctrl+U clear";
+    let filter_style = if app.stash_ui.query.trim().is_empty() {
+        Style::default().fg(app.palette.border_inactive)
+    } else {
+        Style::default().fg(app.palette.accent_primary)
+    };
+    f.render_widget(Paragraph::new(filter_hint).style(filter_style), rows[0]);
+
+    let list_items: Vec<ListItem> = app
+        .stash_ui
+        .filtered
+        .iter()
+        .filter_map(|idx| app.stash_ui.stashes.get(*idx))
+        .map(|s| ListItem::new(format!("{}  {}", s.selector, s.subject)))
+        .collect();
+
+    let list = List::new(list_items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_set(ratatui::symbols::border::PLAIN)
+                .border_style(Style::default().fg(app.palette.border_inactive))
+                .title(format!(" Stashes ({}) ", app.stash_ui.filtered.len())),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(app.palette.selection_bg)
+                .fg(app.palette.fg)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("▎ ");
+
+    f.render_stateful_widget(list, rows[1], &mut app.stash_ui.list_state);
+
+    let list_inner = rows[1].inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    let start = app.stash_ui.list_state.offset();
+    let end = (start + list_inner.height as usize).min(app.stash_ui.filtered.len());
+    for (i, idx) in (start..end).enumerate() {
+        let rect = Rect::new(list_inner.x, list_inner.y + i as u16, list_inner.width, 1);
+        zones.push(ClickZone {
+            rect,
+            action: AppAction::SelectStash(idx),
+        });
+    }
+
+    let mut bx = rows[2].x;
+    for (label, action, color) in [
+        (
+            " Apply (a) ",
+            AppAction::StashApply,
+            app.palette.accent_secondary,
+        ),
+        (" Pop (p) ", AppAction::StashPop, app.palette.accent_primary),
+        (" Drop (d) ", AppAction::StashDrop, app.palette.btn_bg),
+        (" Close ", AppAction::CloseStashPicker, app.palette.menu_bg),
+    ] {
+        let bw = label.len() as u16;
+        let rect = Rect::new(bx, rows[2].y, bw, 1);
+        let style = Style::default()
+            .bg(color)
+            .fg(app.palette.btn_fg)
+            .add_modifier(Modifier::BOLD);
+        f.render_widget(Paragraph::new(label).style(style), rect);
+        zones.push(ClickZone { rect, action });
+        bx += bw + 2;
+    }
+
+    if let Some(msg) = app.stash_ui.status.as_deref() {
+        f.render_widget(
+            Paragraph::new(msg).style(Style::default().fg(app.palette.btn_bg)),
+            Rect::new(
+                rows[2].x + 48,
+                rows[2].y,
+                rows[2].width.saturating_sub(48),
+                1,
+            ),
+        );
+    }
+}
+
+/// The new-stash dialog: message and flags
+pub(crate) fn render_stash_input(
+    f: &mut Frame,
+    app: &App,
+    input: &StashInput,
+    area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    let toggles = input.toggles();
+    let w = area.width.min(64).saturating_sub(2).max(44);
+    let h = (toggles.len() as u16 + 8)
+        .min(area.height.saturating_sub(2))
+        .max(7);
+    let x = area.x + (area.width.saturating_sub(w)) / 2;
+    let y = area.y + (area.height.saturating_sub(h)) / 2;
+    let modal = Rect::new(x, y, w, h);
+
+    zones.push(ClickZone {
+        rect: area,
+        action: AppAction::None,
+    });
+
+    f.render_widget(Clear, modal);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(app.palette.accent_primary))
+        .title(" Stash Changes ");
+    f.render_widget(block.clone(), modal);
+
+    let inner = modal.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    let mut constraints = vec![Constraint::Length(1); 3 + toggles.len()];
+    constraints.extend([Constraint::Min(0), Constraint::Length(1)]);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(inner);
+
+    let label = Style::default().fg(app.palette.fg);
+    let focused = Style::default()
+        .fg(app.palette.fg)
+        .bg(app.palette.selection_bg);
+    f.render_widget(Paragraph::new("Message (optional):").style(label), rows[0]);
+    let message = if input.focus.is_none() {
+        Paragraph::new(format!("{}_", input.message)).style(focused)
+    } else {
+        Paragraph::new(input.message.clone()).style(label)
+    };
+    f.render_widget(message, rows[1]);
+
+    for (i, toggle) in toggles.into_iter().enumerate() {
+        let text = match toggle {
+            StashToggle::IncludeUntracked => {
+                "Include untracked files (--include-untracked)".to_string()
+            }
+            StashToggle::KeepIndex => "Keep staged changes in the index (--keep-index)".to_string(),
+            StashToggle::Staged => "Only staged changes (--staged)".to_string(),
+            StashToggle::OnlySelected if input.paths.len() == 1 => {
+                format!("Only {}", input.paths[0])
+            }
+            StashToggle::OnlySelected => {
+                format!("Only the {} selected files", input.paths.len())
+            }
+        };
+        let checkbox = if input.is_on(toggle) { "▣" } else { "□" };
+        let style = if input.focus == Some(toggle) {
+            focused
+        } else {
+            label
+        };
+        let row = rows[3 + i];
+        f.render_widget(
+            Paragraph::new(format!("{} {}", checkbox, text)).style(style),
+            row,
+        );
+        zones.push(ClickZone {
+            rect: row,
+            action: AppAction::ToggleStashOption(toggle),
+        });
+    }
+
+    let bottom = rows[rows.len() - 1];
+    let button = " Stash ";
+    let button_w = button.len() as u16;
+    f.render_widget(
+        Paragraph::new("Enter to stash · Tab and Space for options · Esc to cancel")
+            .style(Style::default().fg(app.palette.border_inactive)),
+        Rect::new(
+            bottom.x,
+            bottom.y,
+            bottom.width.saturating_sub(button_w + 1),
+            1,
+        ),
+    );
+    let button_rect = Rect::new(
+        bottom.right().saturating_sub(button_w),
+        bottom.y,
+        button_w.min(bottom.width),
+        1,
+    );
+    f.render_widget(
+        Paragraph::new(button).style(
+            Style::default()
+                .bg(app.palette.accent_primary)
+                .fg(app.palette.btn_fg)
+                .add_modifier(Modifier::BOLD),
+        ),
+        button_rect,
+    );
+    zones.push(ClickZone {
+        rect: button_rect,
+        action: AppAction::SubmitStash,
+    });
+}
@@ -0,0 +1,137 @@
+//! Blame view - one file with the commit, author and age of every line
+
+use ratatui::{
+    Frame,
+    layout::{Margin, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+};
+
+use crate::app::{App, AppAction, ClickZone};
+use crate::git::{self, pad_to_width};
+use crate::git_ops;
+
+use super::pane_block;
+
+const AUTHOR_W: usize = 14;
+
+/// Render the blame of `app.blame.path` over the whole content area
+pub fn render_blame_view(app: &mut App, f: &mut Frame, area: Rect, zones: &mut Vec<ClickZone>) {
+    let title = format!(
+        " Blame: {} (Enter=commit, y=copy hash, q=close) ",
+        app.blame.path
+    );
+    let block = pane_block(&app.palette, title, true);
+    f.render_widget(block, area);
+
+    let inner = area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    let viewport_h = inner.height as usize;
+    if viewport_h == 0 {
+        return;
+    }
+
+    let now = app.now();
+    // Keep the selection on screen
+    let blame = &mut app.blame;
+    if blame.selected < blame.scroll_y {
+        blame.scroll_y = blame.selected;
+    } else if blame.selected >= blame.scroll_y + viewport_h {
+        blame.scroll_y = blame.selected + 1 - viewport_h;
+    }
+
+    let number_w = blame.lines.len().to_string().len();
+    let dim = Style::default().fg(app.palette.border_inactive);
+
+    let mut lines: Vec<Line> = Vec::with_capacity(viewport_h);
+    for idx in blame.scroll_y..(blame.scroll_y + viewport_h).min(blame.lines.len()) {
+        let line = &blame.lines[idx];
+        // Only the first line of a run from the same commit repeats its details
+        let first = idx == 0 || blame.lines[idx - 1].hash != line.hash;
+        let mut spans = if !first {
+            vec![Span::styled(
+                format!("{:<w$}", "┆", w = 8 + AUTHOR_W + 10),
+                dim,
+            )]
+        } else if line.is_uncommitted() {
+            vec![Span::styled(
+                pad_to_width("uncommitted".to_string(), 8 + AUTHOR_W + 10),
+                Style::default().fg(app.palette.diff_add_fg),
+            )]
+        } else {
+            vec![
+                Span::styled(
+                    format!("{} ", line.short()),
+                    Style::default().fg(app.palette.accent_secondary),
+                ),
+                Span::styled(
+                    pad_to_width(git::truncate_to_width(&line.author, AUTHOR_W - 1), AUTHOR_W),
+                    Style::default().fg(app.palette.fg),
+                ),
+                Span::styled(
+                    format!("{:>8} ", git_ops::relative_age(line.timestamp, now)),
+                    dim,
+                ),
+            ]
+        };
+        spans.push(Span::styled(
+            format!("{:>w$} │ ", idx + 1, w = number_w),
+            dim,
+        ));
+        match blame.highlighted.get(idx) {
+            Some(code) => spans.extend(code.spans.iter().cloned()),
+            None => spans.push(Span::styled(
+                line.content.clone(),
+                Style::default().fg(app.palette.fg),
+            )),
+        }
+
+        let mut line = Line::from(spans);
+        if idx == blame.selected {
+            line = line.style(
+                Style::default()
+                    .bg(app.palette.selection_bg)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+        lines.push(line);
+        zones.push(ClickZone {
+            rect: Rect::new(
+                inner.x,
+                inner.y + (idx - blame.scroll_y) as u16,
+                inner.width,
+                1,
+            ),
+            action: AppAction::SelectBlameLine(idx),
+        });
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+
+    // Summary of the selected line's commit on the bottom border
+    if let Some(line) = blame.selected_line().filter(|l| !l.is_uncommitted()) {
+        let summary = format!(" {} {} ", line.short(), line.summary);
+        let w = (summary.chars().count() as u16).min(area.width.saturating_sub(4));
+        f.render_widget(
+            Paragraph::new(summary).style(Style::default().fg(app.palette.accent_primary)),
+            Rect::new(area.x + 2, area.bottom().saturating_sub(1), w, 1),
+        );
+    }
+
+    if blame.lines.len() > viewport_h {
+        let mut state = ScrollbarState::new(blame.lines.len())
+            .position(blame.scroll_y)
+            .viewport_content_length(viewport_h);
+        f.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None)
+                .track_symbol(Some("│"))
+                .thumb_symbol("█"),
+            Rect::new(area.right().saturating_sub(1), inner.y, 1, inner.height),
+            &mut state,
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Conflict view - ours and theirs side by side for the selected conflict block
+
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+};
+
+use crate::app::{App, AppAction, ClickZone, PaneFocus};
+use crate::git::{self, pad_to_width};
+
+use super::pane_block;
+
+/// Render the conflict resolution view
+pub(super) fn render_conflict_view(
+    app: &mut App,
+    f: &mut Frame,
+    diff_area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    let title = app
+        .conflict_ui
+        .path
+        .as_deref()
+        .map(|p| format!(" Conflicts: {} ", p))
+        .unwrap_or_else(|| " Conflicts ".to_string());
+
+    let block = pane_block(&app.palette, title, app.git_focus == PaneFocus::Detail);
+    f.render_widget(block.clone(), diff_area);
+
+    let inner = diff_area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let sep_style = Style::default().fg(app.palette.border_inactive);
+    let ours_header_style = Style::default()
+        .fg(app.palette.diff_add_fg)
+        .bg(app.palette.diff_add_bg)
+        .add_modifier(Modifier::BOLD);
+    let theirs_header_style = Style::default()
+        .fg(app.palette.accent_primary)
+        .bg(app.palette.diff_hunk_bg)
+        .add_modifier(Modifier::BOLD);
+
+    let inner_w = rows[0].width as usize;
+    let sep_w = 1usize;
+    let left_w = inner_w.saturating_sub(sep_w) / 2;
+    let right_w = inner_w.saturating_sub(sep_w).saturating_sub(left_w);
+
+    let (count, ours_title, theirs_title) = if let Some(file) = &app.conflict_ui.file {
+        let n = file.blocks.len();
+        let cur = app.conflict_ui.selected_block + 1;
+        (
+            n,
+            format!(" ◀ Ours ({}/{}) ", cur.min(n.max(1)), n),
+            " Theirs ▶ ".to_string(),
+        )
+    } else {
+        (0, " ◀ Ours ".to_string(), " Theirs ▶ ".to_string())
+    };
+
+    let header = Line::from(vec![
+        Span::styled(pad_to_width(ours_title, left_w), ours_header_style),
+        Span::styled("│", sep_style),
+        Span::styled(pad_to_width(theirs_title, right_w), theirs_header_style),
+    ]);
+    f.render_widget(Paragraph::new(header), rows[0]);
+
+    let mut content_lines: Vec<Line> = Vec::new();
+    if let Some(file) = &app.conflict_ui.file {
+        if file.blocks.is_empty() {
+            content_lines.push(Line::raw("No conflict markers found"));
+        } else {
+            let idx = app.conflict_ui.selected_block.min(file.blocks.len() - 1);
+            let block = &file.blocks[idx];
+            let n = block.ours.len().max(block.theirs.len());
+
+            let gutter_style = Style::default().fg(app.palette.diff_gutter_fg);
+            let ours_style = Style::default()
+                .fg(app.palette.diff_add_fg)
+                .bg(app.palette.diff_add_bg);
+            let theirs_style = Style::default()
+                .fg(app.palette.accent_primary)
+                .bg(app.palette.diff_hunk_bg);
+            let empty_ours_style = Style::default().bg(app.palette.diff_add_bg);
+            let empty_theirs_style = Style::default().bg(app.palette.diff_hunk_bg);
+
+            let gutter_w = 4usize;
+            let content_left_w = left_w.saturating_sub(gutter_w);
+            let content_right_w = right_w.saturating_sub(gutter_w);
+
+            for i in 0..n {
+                let has_left = i < block.ours.len();
+                let has_right = i < block.theirs.len();
+                let left = block.ours.get(i).cloned().unwrap_or_default();
+                let right = block.theirs.get(i).cloned().unwrap_or_default();
+
+                let left_ln = if has_left {
+                    format!("{:>3} ", i + 1)
+                } else {
+                    "    ".to_string()
+                };
+                let right_ln = if has_right {
+                    format!("{:>3} ", i + 1)
+                } else {
+                    "    ".to_string()
+                };
+
+                let left = pad_to_width(
+                    git::slice_chars(&left, app.git.diff_scroll_x as usize, content_left_w),
+                    content_left_w,
+                );
+                let right = pad_to_width(
+                    git::slice_chars(&right, app.git.diff_scroll_x as usize, content_right_w),
+                    content_right_w,
+                );
+
+                let left_style = if has_left {
+                    ours_style
+                } else {
+                    empty_ours_style
+                };
+                let right_style = if has_right {
+                    theirs_style
+                } else {
+                    empty_theirs_style
+                };
+
+                content_lines.push(Line::from(vec![
+                    Span::styled(left_ln, gutter_style),
+                    Span::styled(left, left_style),
+                    Span::styled("│", sep_style),
+                    Span::styled(right_ln, gutter_style),
+                    Span::styled(right, right_style),
+                ]));
+            }
+        }
+    } else {
+        content_lines.push(Line::raw("Failed to load conflict file"));
+    }
+
+    let max_scroll = content_lines.len().saturating_sub(rows[1].height as usize);
+    app.conflict_ui.scroll_y = app
+        .conflict_ui
+        .scroll_y
+        .min(max_scroll.min(u16::MAX as usize) as u16);
+
+    let para = Paragraph::new(content_lines)
+        .scroll((app.conflict_ui.scroll_y, 0))
+        .wrap(Wrap { trim: false });
+    f.render_widget(para, rows[1]);
+
+    zones.push(ClickZone {
+        rect: rows[1],
+        action: AppAction::None,
+    });
+
+    let enabled = !app.commit.busy && app.pending_job.is_none();
+    let mut x = rows[2].x;
+    for (label, action, color) in [
+        (
+            " < Prev ",
+            AppAction::ConflictPrev,
+            app.palette.accent_tertiary,
+        ),
+        (
+            " Next > ",
+            AppAction::ConflictNext,
+            app.palette.accent_tertiary,
+        ),
+        (
+            " Ours ",
+            AppAction::ConflictUseOurs,
+            app.palette.accent_primary,
+        ),
+        (
+            " Theirs ",
+            AppAction::ConflictUseTheirs,
+            app.palette.accent_secondary,
+        ),
+        (
+            " Both ",
+            AppAction::ConflictUseBoth,
+            app.palette.accent_tertiary,
+        ),
+        (
+            " Mark Resolved ",
+            AppAction::MarkResolved,
+            app.palette.exe_color,
+        ),
+    ] {
+        let w = label.len() as u16;
+        if x + w > rows[2].x + rows[2].width {
+            break;
+        }
+        let bg = if enabled {
+            color
+        } else {
+            app.palette.border_inactive
+        };
+        let fg = if enabled {
+            app.palette.btn_fg
+        } else {
+            app.palette.fg
+        };
+        let style = Style::default().bg(bg).fg(fg).add_modifier(Modifier::BOLD);
+        let rect = Rect::new(x, rows[2].y, w, 1);
+        f.render_widget(Paragraph::new(label).style(style), rect);
+        if enabled {
+            zones.push(ClickZone { rect, action });
+        }
+        x += w + 1;
+    }
+
+    if count == 0 {
+        let msg = "No conflicts";
+        let w = msg.len().min(rows[2].width as usize) as u16;
+        f.render_widget(
+            Paragraph::new(msg).style(Style::default().fg(app.palette.border_inactive)),
+            Rect::new(rows[2].x, rows[2].y, w, 1),
+        );
+    }
+}
@@ -10,7 +10,10 @@ use ratatui::{
 use ratatui_image::StatefulImage;
 use std::fs;
 
-use crate::{App, AppAction, ClickZone, ExplorerZoom, format_size, highlight};
+use crate::app::{App, AppAction, ClickZone, ExplorerZoom, PaneFocus, format_size};
+use crate::highlight;
+
+use super::pane_block;
 
 /// Render the Explorer tab with configurable layout (z to cycle).
 pub fn render_explorer_tab(
@@ -166,11 +169,11 @@ fn render_parent_pane(app: &mut App, f: &mut Frame, area: Rect, click_zones: &mu
 
 /// Render the file/folder list with icons.
 fn render_file_list(app: &mut App, f: &mut Frame, area: Rect, click_zones: &mut Vec<ClickZone>) {
-    let list_block = Block::default()
-        .borders(Borders::ALL)
-        .border_set(ratatui::symbols::border::PLAIN)
-        .border_style(Style::default().fg(app.palette.accent_primary))
-        .title(format!(" Files ({}) ", app.files.len()));
+    let list_block = pane_block(
+        &app.palette,
+        format!(" Files ({}) ", app.files.len()),
+        app.explorer_focus == PaneFocus::List,
+    );
 
     let items: Vec<ListItem> = app
         .files
@@ -314,12 +317,11 @@ fn render_preview(app: &mut App, f: &mut Frame, area: Rect, click_zones: &mut Ve
         let preview_limited = preview_text;
 
         // Extract file info first to avoid borrow issues
-        let file_ext = app
-            .selected_file()
-            .filter(|f| !f.is_dir)
-            .and_then(|f| f.path.extension())
-            .and_then(|s| s.to_str())
-            .map(|s| s.to_string());
+        let file_ext = app.selected_file().filter(|f| !f.is_dir).and_then(|f| {
+            let path = f.path.to_string_lossy();
+            app.syntax_for(&path, preview_limited.lines().next())
+                .map(|s| s.to_string())
+        });
 
         // Syntax highlighting with increased limit for larger files
         let total_lines = preview_limited.lines().count();
@@ -379,11 +381,11 @@ fn render_preview(app: &mut App, f: &mut Frame, area: Rect, click_zones: &mut Ve
             format!(" Preview ({} lines) ", line_count)
         };
 
-        let p_block = Block::default()
-            .borders(Borders::ALL)
-            .border_set(ratatui::symbols::border::PLAIN)
-            .border_style(Style::default().fg(app.palette.border_inactive))
-            .title(title);
+        let p_block = pane_block(
+            &app.palette,
+            title,
+            app.explorer_focus == PaneFocus::Detail,
+        );
 
         // Clamp scroll to keep content visible (can't scroll past last line)
         let visible_height = area.height.saturating_sub(2) as usize; // Account for border
@@ -5,18 +5,21 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{
-        Block, Borders, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState,
-        Wrap,
-    },
+    widgets::{List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
 };
 
 use crate::git::{
-    self, FlatNodeType, GitDiffCellKind, GitDiffMode, GitDiffRow, GitSection,
+    self, FlatNodeType, FullFileSource, GitDiffCellKind, GitDiffMode, GitDiffRow, GitSection,
     build_side_by_side_rows, display_width, pad_to_width,
 };
+use crate::git_ops::SubmoduleState;
 use crate::highlight::{Highlighter, new_highlighter};
-use crate::{App, AppAction, ClickZone, DiffRenderCacheKey};
+use crate::app::{App, AppAction, ClickZone, DiffRenderCacheKey, PaneFocus, Tab};
+
+use super::conflicts::render_conflict_view;
+use super::minimap::{mark_selected, render_diff_minimap, scrollbar_track};
+use super::pane_block;
+use super::review::render_review_view;
 
 /// Render the Git tab content: tree view on left, diff on right
 pub fn render_git_tab(
@@ -50,24 +53,136 @@ pub fn render_git_tab(
     // Determine which view to render on the right
     let in_conflict_view = app.git.selected_tree_entry().is_some_and(|e| e.is_conflict);
 
-    if in_conflict_view {
+    if app.review.open {
+        render_review_view(app, f, diff_area, zones);
+    } else if in_conflict_view {
         render_conflict_view(app, f, diff_area, zones);
     } else if app.git.show_full_file {
         render_full_file_view(app, f, diff_area);
+    } else if let Some(summary) = app.git.dir_summary() {
+        render_dir_summary(app, f, diff_area, zones, &summary);
     } else {
         render_diff_view(app, f, diff_area, zones);
     }
 }
 
+/// Changed files under the selected directory with a combined diffstat; with the
+/// pane focused, j/k pick a file and Enter opens its diff
+fn render_dir_summary(
+    app: &mut App,
+    f: &mut Frame,
+    area: Rect,
+    zones: &mut Vec<ClickZone>,
+    summary: &git::DirSummary,
+) {
+    let focused = app.git_focus == PaneFocus::Detail;
+    let title = format!(" {}/ ", summary.path);
+    f.render_widget(pane_block(&app.palette, title, focused), area);
+    let inner = area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    if inner.height < 3 {
+        return;
+    }
+
+    let (added, deleted) = summary.totals();
+    let count = summary.files.len();
+    let header = format!(
+        "{} file{} changed, {} insertion{}(+), {} deletion{}(-)",
+        count,
+        if count == 1 { "" } else { "s" },
+        added,
+        if added == 1 { "" } else { "s" },
+        deleted,
+        if deleted == 1 { "" } else { "s" },
+    );
+    let dim = Style::default().fg(app.palette.border_inactive);
+    f.render_widget(
+        Paragraph::new(header).style(Style::default().fg(app.palette.fg)),
+        Rect::new(inner.x, inner.y, inner.width, 1),
+    );
+    let hint = if focused {
+        "j/k to pick · Enter to open"
+    } else {
+        "Tab to pick a file · click to open"
+    };
+    f.render_widget(
+        Paragraph::new(hint).style(dim),
+        Rect::new(inner.x, inner.bottom() - 1, inner.width, 1),
+    );
+
+    let prefix = format!("{}/", summary.path);
+    let names: Vec<&str> = summary
+        .files
+        .iter()
+        .map(|file| file.path.strip_prefix(&prefix).unwrap_or(&file.path))
+        .collect();
+    let name_w = names
+        .iter()
+        .map(|n| display_width(n))
+        .max()
+        .unwrap_or(0)
+        .min(inner.width as usize / 2);
+    let max = summary
+        .files
+        .iter()
+        .filter_map(|file| file.stats)
+        .map(|(a, d)| a + d)
+        .max()
+        .unwrap_or(0);
+    let count_w = max.to_string().len().max(3);
+    let bar_w = (inner.width as usize).saturating_sub(name_w + count_w + 8);
+
+    app.git.dir_summary_selected = app.git.dir_summary_selected.min(count.saturating_sub(1));
+    let rows = inner.height.saturating_sub(3) as usize;
+    let skip = (app.git.dir_summary_selected + 1).saturating_sub(rows);
+    for (i, file) in summary.files.iter().enumerate().skip(skip).take(rows) {
+        let rect = Rect::new(inner.x, inner.y + 2 + (i - skip) as u16, inner.width, 1);
+        let name = pad_to_width(git::truncate_middle(names[i], name_w), name_w);
+        let mut spans = vec![
+            Span::styled(
+                format!("{} ", file.status),
+                Style::default().fg(app.palette.accent_primary),
+            ),
+            Span::raw(format!("{} │ ", name)),
+        ];
+        match file.stats {
+            Some((a, d)) => {
+                let (plus, minus) = git::diffstat_bar(a, d, max, bar_w);
+                spans.push(Span::raw(format!("{:>w$} ", a + d, w = count_w)));
+                spans.push(Span::styled(
+                    "+".repeat(plus),
+                    Style::default().fg(app.palette.diff_add_fg),
+                ));
+                spans.push(Span::styled(
+                    "-".repeat(minus),
+                    Style::default().fg(app.palette.diff_del_fg),
+                ));
+            }
+            None => spans.push(Span::styled(format!("{:>w$}", "—", w = count_w), dim)),
+        }
+        let mut line = Line::from(spans);
+        if focused && i == app.git.dir_summary_selected {
+            line = line.style(Style::default().bg(app.palette.selection_bg));
+        }
+        f.render_widget(Paragraph::new(line), rect);
+        zones.push(ClickZone {
+            rect,
+            action: AppAction::OpenDirSummaryFile(i),
+        });
+    }
+}
+
 /// Render the tree view panel (left side)
 fn render_tree_view(app: &mut App, f: &mut Frame, tree_area: Rect, zones: &mut Vec<ClickZone>) {
     let (staged, working, untracked, conflicts) = app.git.section_counts();
     let total = staged + working + untracked + conflicts;
-    let tree_block = Block::default()
-        .borders(Borders::ALL)
-        .border_set(ratatui::symbols::border::PLAIN)
-        .border_style(Style::default().fg(app.palette.accent_primary))
-        .title(format!(" Git ({}) ", total));
+    let tree_block = pane_block(
+        &app.palette,
+        format!(" Git ({}) ", total),
+        app.git_focus == PaneFocus::List,
+    );
     f.render_widget(tree_block.clone(), tree_area);
 
     let tree_inner = tree_area.inner(Margin {
@@ -75,6 +190,24 @@ fn render_tree_view(app: &mut App, f: &mut Frame, tree_area: Rect, zones: &mut V
         horizontal: 1,
     });
 
+    // Recent commits take the bottom rows when the pane is tall enough
+    let recent = app.git.recent_commits.len().min(git::RECENT_COMMITS);
+    let shown = if tree_inner.height as usize >= recent + 7 { recent } else { 0 };
+    app.git.recent_shown = shown;
+    let tree_inner = if shown > 0 {
+        let strip_h = shown as u16 + 1;
+        let strip = Rect::new(
+            tree_inner.x,
+            tree_inner.bottom() - strip_h,
+            tree_inner.width,
+            strip_h,
+        );
+        render_recent_commits(app, f, strip, zones);
+        Rect::new(tree_inner.x, tree_inner.y, tree_inner.width, tree_inner.height - strip_h)
+    } else {
+        tree_inner
+    };
+
     // Build tree items for rendering
     let tree_items: Vec<ListItem> = app
         .git
@@ -92,6 +225,7 @@ fn render_tree_view(app: &mut App, f: &mut Frame, tree_area: Rect, zones: &mut V
                         GitSection::Working => working,
                         GitSection::Untracked => untracked,
                         GitSection::Conflicts => conflicts,
+                        GitSection::Submodules => app.git.submodules.len(),
                     };
                     let label = format!("{}{} {} ({})", indent, arrow, item.name, count);
                     // Conflicts section gets red/warning color
@@ -128,6 +262,7 @@ fn render_tree_view(app: &mut App, f: &mut Frame, tree_area: Rect, zones: &mut V
                                 GitSection::Working => e.y.to_string(),
                                 GitSection::Untracked => "?".to_string(),
                                 GitSection::Conflicts => format!("{}{}", e.x, e.y),
+                                GitSection::Submodules => String::new(),
                             };
 
                             // Conflict files get red styling
@@ -164,6 +299,26 @@ fn render_tree_view(app: &mut App, f: &mut Frame, tree_area: Rect, zones: &mut V
                                 ));
                             }
 
+                            let stats = match item.section {
+                                GitSection::Staged => app.git.staged_stats.get(&e.path),
+                                GitSection::Working => app.git.worktree_stats.get(&e.path),
+                                _ => None,
+                            };
+                            if let Some(&(added, deleted)) = stats {
+                                if added > 0 {
+                                    spans.push(Span::styled(
+                                        format!(" +{}", added),
+                                        Style::default().fg(app.palette.diff_add_fg),
+                                    ));
+                                }
+                                if deleted > 0 {
+                                    spans.push(Span::styled(
+                                        format!(" -{}", deleted),
+                                        Style::default().fg(app.palette.diff_del_fg),
+                                    ));
+                                }
+                            }
+
                             let mut list_item = ListItem::new(Line::from(spans));
                             if is_selected {
                                 list_item =
@@ -178,17 +333,67 @@ fn render_tree_view(app: &mut App, f: &mut Frame, tree_area: Rect, zones: &mut V
                         indent, item.name
                     ))]))
                 }
+                FlatNodeType::Submodule => {
+                    let dim = Style::default().fg(app.palette.border_inactive);
+                    let mut spans = vec![
+                        Span::raw(indent),
+                        Span::styled("◫ ", dim),
+                        Span::styled(&item.name, Style::default().fg(app.palette.dir_color)),
+                    ];
+                    if let Some(sub) = app.git.submodules.iter().find(|s| s.path == item.path) {
+                        let (state, color) = match sub.state {
+                            SubmoduleState::Current => ("", app.palette.fg),
+                            SubmoduleState::Uninitialized => {
+                                (" not checked out", app.palette.accent_tertiary)
+                            }
+                            SubmoduleState::Moved => (" moved", app.palette.accent_secondary),
+                            SubmoduleState::Conflict => (" conflict", app.palette.diff_del_fg),
+                        };
+                        spans.push(Span::styled(format!(" {}", sub.short()), dim));
+                        if !sub.branch.is_empty() {
+                            spans.push(Span::styled(
+                                format!(" ⎇ {}", sub.branch),
+                                Style::default().fg(app.palette.accent_primary),
+                            ));
+                        }
+                        if sub.ahead > 0 {
+                            spans.push(Span::styled(
+                                format!(" ↑{}", sub.ahead),
+                                Style::default().fg(app.palette.diff_add_fg),
+                            ));
+                        }
+                        if sub.behind > 0 {
+                            spans.push(Span::styled(
+                                format!(" ↓{}", sub.behind),
+                                Style::default().fg(app.palette.diff_del_fg),
+                            ));
+                        }
+                        spans.push(Span::styled(state, Style::default().fg(color)));
+                        if sub.dirty {
+                            spans.push(Span::styled(
+                                " ● dirty",
+                                Style::default().fg(app.palette.accent_secondary),
+                            ));
+                        }
+                    }
+                    ListItem::new(Line::from(spans))
+                }
             }
         })
         .collect();
 
+    // The selection sits in the recent-commits strip instead
+    let (highlight, symbol) = if app.git.recent_selected.is_some() {
+        (Style::default().add_modifier(Modifier::BOLD), " ")
+    } else {
+        let style = Style::default()
+            .bg(app.palette.selection_bg)
+            .add_modifier(Modifier::BOLD);
+        (style, "▎")
+    };
     let tree_list = List::new(tree_items)
-        .highlight_style(
-            Style::default()
-                .bg(app.palette.selection_bg)
-                .add_modifier(Modifier::BOLD),
-        )
-        .highlight_symbol("▎");
+        .highlight_style(highlight)
+        .highlight_symbol(symbol);
 
     f.render_stateful_widget(tree_list, tree_inner, &mut app.git.tree_state.clone());
 
@@ -214,256 +419,126 @@ fn render_tree_view(app: &mut App, f: &mut Frame, tree_area: Rect, zones: &mut V
             .position(app.git.tree_state.selected().unwrap_or(0));
         f.render_stateful_widget(
             scrollbar,
-            tree_area.inner(Margin {
-                vertical: 1,
-                horizontal: 0,
-            }),
+            Rect::new(tree_area.x, tree_inner.y, tree_area.width, tree_inner.height),
             &mut scroll_state,
         );
     }
 }
 
-/// Render the conflict resolution view
-fn render_conflict_view(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mut Vec<ClickZone>) {
-    let title = app
-        .conflict_ui
-        .path
-        .as_deref()
-        .map(|p| format!(" Conflicts: {} ", p))
-        .unwrap_or_else(|| " Conflicts ".to_string());
-
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_set(ratatui::symbols::border::PLAIN)
-        .border_style(Style::default().fg(app.palette.border_inactive))
-        .title(title);
-    f.render_widget(block.clone(), diff_area);
-
-    let inner = diff_area.inner(Margin {
-        vertical: 1,
-        horizontal: 1,
-    });
-
-    let rows = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(1),
-            Constraint::Min(0),
-            Constraint::Length(1),
-        ])
-        .split(inner);
-
-    let sep_style = Style::default().fg(app.palette.border_inactive);
-    let ours_header_style = Style::default()
-        .fg(app.palette.diff_add_fg)
-        .bg(app.palette.diff_add_bg)
-        .add_modifier(Modifier::BOLD);
-    let theirs_header_style = Style::default()
-        .fg(app.palette.accent_primary)
-        .bg(app.palette.diff_hunk_bg)
-        .add_modifier(Modifier::BOLD);
-
-    let inner_w = rows[0].width as usize;
-    let sep_w = 1usize;
-    let left_w = inner_w.saturating_sub(sep_w) / 2;
-    let right_w = inner_w.saturating_sub(sep_w).saturating_sub(left_w);
-
-    let (count, ours_title, theirs_title) = if let Some(file) = &app.conflict_ui.file {
-        let n = file.blocks.len();
-        let cur = app.conflict_ui.selected_block + 1;
-        (
-            n,
-            format!(" ◀ Ours ({}/{}) ", cur.min(n.max(1)), n),
-            " Theirs ▶ ".to_string(),
-        )
-    } else {
-        (0, " ◀ Ours ".to_string(), " Theirs ▶ ".to_string())
-    };
-
-    let header = Line::from(vec![
-        Span::styled(pad_to_width(ours_title, left_w), ours_header_style),
-        Span::styled("│", sep_style),
-        Span::styled(pad_to_width(theirs_title, right_w), theirs_header_style),
-    ]);
-    f.render_widget(Paragraph::new(header), rows[0]);
-
-    let mut content_lines: Vec<Line> = Vec::new();
-    if let Some(file) = &app.conflict_ui.file {
-        if file.blocks.is_empty() {
-            content_lines.push(Line::raw("No conflict markers found"));
-        } else {
-            let idx = app.conflict_ui.selected_block.min(file.blocks.len() - 1);
-            let block = &file.blocks[idx];
-            let n = block.ours.len().max(block.theirs.len());
-
-            let gutter_style = Style::default().fg(app.palette.diff_gutter_fg);
-            let ours_style = Style::default()
-                .fg(app.palette.diff_add_fg)
-                .bg(app.palette.diff_add_bg);
-            let theirs_style = Style::default()
-                .fg(app.palette.accent_primary)
-                .bg(app.palette.diff_hunk_bg);
-            let empty_ours_style = Style::default().bg(app.palette.diff_add_bg);
-            let empty_theirs_style = Style::default().bg(app.palette.diff_hunk_bg);
-
-            let gutter_w = 4usize;
-            let content_left_w = left_w.saturating_sub(gutter_w);
-            let content_right_w = right_w.saturating_sub(gutter_w);
-
-            for i in 0..n {
-                let has_left = i < block.ours.len();
-                let has_right = i < block.theirs.len();
-                let left = block.ours.get(i).cloned().unwrap_or_default();
-                let right = block.theirs.get(i).cloned().unwrap_or_default();
-
-                let left_ln = if has_left {
-                    format!("{:>3} ", i + 1)
-                } else {
-                    "    ".to_string()
-                };
-                let right_ln = if has_right {
-                    format!("{:>3} ", i + 1)
-                } else {
-                    "    ".to_string()
-                };
-
-                let left = pad_to_width(
-                    git::slice_chars(&left, app.git.diff_scroll_x as usize, content_left_w),
-                    content_left_w,
-                );
-                let right = pad_to_width(
-                    git::slice_chars(&right, app.git.diff_scroll_x as usize, content_right_w),
-                    content_right_w,
-                );
-
-                let left_style = if has_left {
-                    ours_style
-                } else {
-                    empty_ours_style
-                };
-                let right_style = if has_right {
-                    theirs_style
-                } else {
-                    empty_theirs_style
-                };
-
-                content_lines.push(Line::from(vec![
-                    Span::styled(left_ln, gutter_style),
-                    Span::styled(left, left_style),
-                    Span::styled("│", sep_style),
-                    Span::styled(right_ln, gutter_style),
-                    Span::styled(right, right_style),
-                ]));
-            }
-        }
-    } else {
-        content_lines.push(Line::raw("Failed to load conflict file"));
-    }
-
-    let para = Paragraph::new(content_lines)
-        .scroll((app.conflict_ui.scroll_y, 0))
-        .wrap(Wrap { trim: false });
-    f.render_widget(para, rows[1]);
-
-    zones.push(ClickZone {
-        rect: rows[1],
-        action: AppAction::None,
-    });
-
-    let enabled = !app.commit.busy && app.pending_job.is_none();
-    let mut x = rows[2].x;
-    for (label, action, color) in [
-        (
-            " < Prev ",
-            AppAction::ConflictPrev,
-            app.palette.accent_tertiary,
-        ),
-        (
-            " Next > ",
-            AppAction::ConflictNext,
-            app.palette.accent_tertiary,
-        ),
-        (
-            " Ours ",
-            AppAction::ConflictUseOurs,
-            app.palette.accent_primary,
-        ),
-        (
-            " Theirs ",
-            AppAction::ConflictUseTheirs,
-            app.palette.accent_secondary,
-        ),
-        (
-            " Both ",
-            AppAction::ConflictUseBoth,
-            app.palette.accent_tertiary,
-        ),
-        (
-            " Mark Resolved ",
-            AppAction::MarkResolved,
-            app.palette.exe_color,
-        ),
-    ] {
-        let w = label.len() as u16;
-        if x + w > rows[2].x + rows[2].width {
-            break;
-        }
-        let bg = if enabled {
-            color
-        } else {
-            app.palette.border_inactive
-        };
-        let fg = if enabled {
-            app.palette.btn_fg
-        } else {
-            app.palette.fg
-        };
-        let style = Style::default().bg(bg).fg(fg).add_modifier(Modifier::BOLD);
-        let rect = Rect::new(x, rows[2].y, w, 1);
-        f.render_widget(Paragraph::new(label).style(style), rect);
-        if enabled {
-            zones.push(ClickZone { rect, action });
+/// Strip of the latest commits under the tree; Enter or a click shows one in History.
+fn render_recent_commits(app: &App, f: &mut Frame, area: Rect, zones: &mut Vec<ClickZone>) {
+    let dim = Style::default().fg(app.palette.border_inactive);
+    let width = area.width as usize;
+    let title = " Recent ";
+    let mut lines = vec![Line::styled(
+        format!("──{}{}", title, "─".repeat(width.saturating_sub(title.len() + 2))),
+        dim,
+    )];
+
+    let now = app.now();
+    let focused = app.git_focus == PaneFocus::List;
+    let rows = area.height as usize - 1;
+    for (i, commit) in app.git.recent_commits.iter().take(rows).enumerate() {
+        let age = crate::git_ops::relative_age(commit.timestamp, now);
+        let subject_w = width.saturating_sub(commit.short.len() + age.len() + 2);
+        let subject = pad_to_width(git::truncate_to_width(&commit.subject, subject_w), subject_w);
+        let mut line = Line::from(vec![
+            Span::styled(
+                commit.short.clone(),
+                Style::default().fg(app.palette.accent_secondary),
+            ),
+            Span::raw(" "),
+            Span::styled(subject, Style::default().fg(app.palette.fg)),
+            Span::raw(" "),
+            Span::styled(age, dim),
+        ]);
+        if focused && app.git.recent_selected == Some(i) {
+            line = line.style(Style::default().bg(app.palette.selection_bg));
         }
-        x += w + 1;
-    }
+        lines.push(line);
 
-    if count == 0 {
-        let msg = "No conflicts";
-        let w = msg.len().min(rows[2].width as usize) as u16;
-        f.render_widget(
-            Paragraph::new(msg).style(Style::default().fg(app.palette.border_inactive)),
-            Rect::new(rows[2].x, rows[2].y, w, 1),
-        );
+        zones.push(ClickZone {
+            rect: Rect::new(area.x, area.y + 1 + i as u16, area.width, 1),
+            action: AppAction::OpenRecentCommit(i),
+        });
     }
+    f.render_widget(Paragraph::new(lines), area);
 }
 
-/// Render the full file view (when F key is pressed)
+/// Render the full file view (when F key is pressed): the working tree, HEAD, or
+/// both side by side with one shared scroll offset
 fn render_full_file_view(app: &mut App, f: &mut Frame, diff_area: Rect) {
     let file_name = app
         .git
         .selected_tree_entry()
-        .map(|e| e.path.as_str())
-        .unwrap_or("File");
-    let diff_block = Block::default()
-        .borders(Borders::ALL)
-        .border_set(ratatui::symbols::border::PLAIN)
-        .border_style(Style::default().fg(app.palette.border_inactive))
-        .title(format!(" {} (F=diff) ", file_name));
+        .map(|e| e.path.clone())
+        .unwrap_or_else(|| "File".to_string());
 
-    let content = app.git.full_file_content.as_deref().unwrap_or("No content");
+    if app.syntax_highlight && app.git.full_file_highlighted.is_none() {
+        let first_line = app
+            .git
+            .full_file_content
+            .as_deref()
+            .or(app.git.full_file_head.as_deref())
+            .and_then(|text| text.lines().next());
+        let ext = app.syntax_for(&file_name, first_line).unwrap_or_default();
+        let bg = app.palette.bg;
+        let highlight = |text: Option<&String>| {
+            let text = text.map(String::as_str).unwrap_or_default();
+            new_highlighter(ext).map(|mut hl| hl.highlight_lines(text, bg))
+        };
+        if let (Some(work), Some(head)) = (
+            highlight(app.git.full_file_content.as_ref()),
+            highlight(app.git.full_file_head.as_ref()),
+        ) {
+            app.git.full_file_highlighted = Some((work, head));
+        }
+    }
 
-    // Simple line rendering without syntax highlight for performance
-    let lines: Vec<Line> = content.lines().map(Line::raw).collect();
+    let work = full_file_lines(app, false);
+    let head = full_file_lines(app, true);
+    let focused = app.git_focus == PaneFocus::Detail;
+
+    let panes: Vec<(Rect, String, Vec<Line>)> = match app.git.full_file_source {
+        FullFileSource::WorkTree => vec![(
+            diff_area,
+            format!(" {} · working tree (s=HEAD, F=diff) ", file_name),
+            work,
+        )],
+        FullFileSource::Head => vec![(
+            diff_area,
+            format!(" {} · HEAD (s=both, F=diff) ", file_name),
+            head,
+        )],
+        FullFileSource::Both => {
+            let halves = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(diff_area);
+            vec![
+                (halves[0], format!(" HEAD · {} ", file_name), head),
+                (
+                    halves[1],
+                    " Working tree (s=working tree, F=diff) ".to_string(),
+                    work,
+                ),
+            ]
+        }
+    };
 
-    let lines_len = lines.len();
+    let lines_len = panes.iter().map(|p| p.2.len()).max().unwrap_or(0);
     let viewport_h = diff_area.height.saturating_sub(2) as usize;
     let max_scroll = lines_len.saturating_sub(viewport_h);
     let scroll_y = (app.git.full_file_scroll_y as usize).min(max_scroll);
-
-    let para = Paragraph::new(lines)
-        .block(diff_block)
-        .scroll((scroll_y as u16, 0));
-    f.render_widget(para, diff_area);
+    app.git.full_file_scroll_y = scroll_y as u16;
+
+    for (area, title, lines) in panes {
+        let block = pane_block(&app.palette, title, focused);
+        let para = Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll_y as u16, 0));
+        f.render_widget(para, area);
+    }
 
     // Scrollbar
     if lines_len > viewport_h {
@@ -488,17 +563,58 @@ fn render_full_file_view(app: &mut App, f: &mut Frame, diff_area: Rect) {
     }
 }
 
+/// Numbered lines of the working-tree or HEAD file, highlighted when available
+fn full_file_lines(app: &App, head: bool) -> Vec<Line<'static>> {
+    let (content, highlighted) = if head {
+        (
+            app.git.full_file_head.as_deref(),
+            app.git.full_file_highlighted.as_ref().map(|h| &h.1),
+        )
+    } else {
+        (
+            app.git.full_file_content.as_deref(),
+            app.git.full_file_highlighted.as_ref().map(|h| &h.0),
+        )
+    };
+    let content = content.unwrap_or("No content");
+    let number_style = Style::default().fg(app.palette.border_inactive);
+    let width = content.lines().count().max(1).to_string().len();
+
+    content
+        .lines()
+        .enumerate()
+        .map(|(idx, raw)| {
+            let mut spans = vec![Span::styled(
+                format!("{:>w$} ", idx + 1, w = width),
+                number_style,
+            )];
+            match highlighted
+                .filter(|_| app.syntax_highlight)
+                .and_then(|h| h.get(idx))
+            {
+                Some(line) => spans.extend(line.spans.iter().cloned()),
+                None => spans.push(Span::raw(raw.to_string())),
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
 /// Render the diff view (unified or side-by-side)
 fn render_diff_view(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mut Vec<ClickZone>) {
     let mode_label = match app.git.diff_mode {
         GitDiffMode::SideBySide => "SxS",
         GitDiffMode::Unified => "Unified",
     };
-    let diff_block = Block::default()
-        .borders(Borders::ALL)
-        .border_set(ratatui::symbols::border::PLAIN)
-        .border_style(Style::default().fg(app.palette.border_inactive))
-        .title(format!(" Diff ({}) ", mode_label));
+    let context = app
+        .git
+        .selected_tree_entry()
+        .and_then(|e| app.git.diff_context.get(&e.path));
+    let title = match context {
+        Some(n) => format!(" Diff ({}, {} lines context) ", mode_label, n),
+        None => format!(" Diff ({}) ", mode_label),
+    };
+    let diff_block = pane_block(&app.palette, title, app.git_focus == PaneFocus::Detail);
 
     let cache_width = diff_area.width.saturating_sub(2).max(1);
     let cache_scroll_x = if app.git.diff_mode == GitDiffMode::SideBySide && !app.wrap_diff {
@@ -513,6 +629,8 @@ fn render_diff_view(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mut V
         width: cache_width,
         wrap: app.wrap_diff,
         syntax_highlight: app.syntax_highlight,
+        line_numbers: app.diff_line_numbers,
+        selection: app.diff_selection_range(Tab::Git),
         scroll_x: cache_scroll_x,
     };
 
@@ -520,7 +638,11 @@ fn render_diff_view(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mut V
         app.git_diff_cache.lines.clone()
     } else {
         let computed: Vec<Line> = if app.git.repo_root.is_none() {
-            vec![Line::raw("Not a git repository")]
+            vec![
+                Line::raw("Not a git repository"),
+                Line::raw(""),
+                Line::raw("Press I to initialize one here"),
+            ]
         } else if app.git.diff_lines.is_empty() {
             vec![Line::raw("No selection")]
         } else {
@@ -537,6 +659,7 @@ fn render_diff_view(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mut V
     let wrap_unified = app.git.diff_mode == GitDiffMode::Unified && app.wrap_diff;
 
     let viewport_h = diff_area.height.saturating_sub(2) as usize;
+    app.git.diff_scroll_y = app.follow_diff_selection(Tab::Git, app.git.diff_scroll_y, viewport_h);
     let total_lines = diff_lines.len();
     let max_y = if viewport_h == 0 {
         0
@@ -557,6 +680,7 @@ fn render_diff_view(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mut V
     // Clamp to u16::MAX to avoid overflow, then clamp to max_y
     let max_y_u16 = max_y.min(u16::MAX as usize) as u16;
     app.git.diff_scroll_y = app.git.diff_scroll_y.min(max_y_u16);
+    app.git.note_diff_scroll();
 
     let x_scroll = if app.git.diff_mode == GitDiffMode::Unified && !wrap_unified {
         app.git.diff_scroll_x
@@ -600,6 +724,14 @@ fn render_diff_view(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mut V
             diff_area.inner(Margin { vertical: 1, horizontal: 0 }),
             &mut scroll_state,
         );
+        render_diff_minimap(
+            app,
+            f,
+            scrollbar_track(diff_area),
+            &app.git_diff_cache.lines,
+            AppAction::GitDiffScrollTo,
+            zones,
+        );
     }
 
     // Render revert buttons for visible changes
@@ -608,11 +740,11 @@ fn render_diff_view(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mut V
 
 /// Render unified diff lines
 fn render_unified_diff(app: &App, diff_area: Rect) -> Vec<Line<'static>> {
+    let first_line = git::diff_first_line(&app.git.diff_lines, 0);
     let ext = app
         .git
         .selected_tree_entry()
-        .and_then(|e| std::path::Path::new(e.path.as_str()).extension())
-        .and_then(|s| s.to_str());
+        .and_then(|e| app.syntax_for(e.path.as_str(), first_line));
 
     let mut highlighter: Option<Highlighter> = if app.syntax_highlight {
         ext.and_then(new_highlighter)
@@ -622,9 +754,20 @@ fn render_unified_diff(app: &App, diff_area: Rect) -> Vec<Line<'static>> {
 
     let content_w = diff_area.width.saturating_sub(2).max(1) as usize;
 
-    let mut out = Vec::new();
-    for l in &app.git.diff_lines {
+    let mut out: Vec<Line<'static>> = Vec::new();
+    let mut numbers = git::LineNumberTracker::default();
+    let selection = app.diff_selection_range(Tab::Git);
+    let mut selected_from: Option<usize> = None;
+    let moved = git::moved_lines(&app.git.diff_lines);
+    for (idx, l) in app.git.diff_lines.iter().enumerate() {
+        if let Some(from) = selected_from.take() {
+            mark_selected(&mut out[from..]);
+        }
+        if selection.is_some_and(|(start, end)| (start..=end).contains(&idx)) {
+            selected_from = Some(out.len());
+        }
         let t = l.as_str();
+        let line_no = numbers.advance(t);
         if t.starts_with("@@") {
             out.push(Line::from(vec![Span::styled(
                 pad_to_width(t.to_string(), content_w),
@@ -643,12 +786,12 @@ fn render_unified_diff(app: &App, diff_area: Rect) -> Vec<Line<'static>> {
                 .and_then(|s| s.split(" b/").next())
                 .unwrap_or(t);
 
-            // Update highlighter for this file's extension
+            // Update highlighter for this file's language
             if app.syntax_highlight {
-                let ext = std::path::Path::new(full_path)
-                    .extension()
-                    .and_then(|s| s.to_str());
-                highlighter = ext.and_then(new_highlighter);
+                let first_line = git::diff_first_line(&app.git.diff_lines, idx);
+                highlighter = app
+                    .syntax_for(full_path, first_line)
+                    .and_then(new_highlighter);
             }
 
             // Show filename first, then directory
@@ -687,16 +830,24 @@ fn render_unified_diff(app: &App, diff_area: Rect) -> Vec<Line<'static>> {
 
         let (prefix, code) = t.split_at(t.chars().next().map(|c| c.len_utf8()).unwrap_or(0));
         let (bg, is_code) = match prefix {
+            "+" | "-" if moved[idx] => (app.palette.diff_moved_bg, true),
             "+" if !t.starts_with("+++") => (app.palette.diff_add_bg, true),
             "-" if !t.starts_with("---") => (app.palette.diff_del_bg, true),
             " " => (app.palette.bg, true),
             _ => (app.palette.bg, false),
         };
 
-        let fill = content_w.saturating_sub(display_width(t));
+        let gutter = (is_code && app.diff_line_numbers).then(|| {
+            Span::styled(
+                git::line_number_gutter(line_no),
+                Style::default().fg(app.palette.diff_gutter_fg).bg(bg),
+            )
+        });
+        let code_w = content_w.saturating_sub(gutter.as_ref().map_or(0, |g| g.width()));
+        let fill = code_w.saturating_sub(display_width(t));
 
         if is_code {
-            if let Some(hl) = highlighter.as_mut() {
+            let mut line = if let Some(hl) = highlighter.as_mut() {
                 let mut line = hl.highlight_diff_code_with_prefix(
                     prefix,
                     code,
@@ -707,13 +858,17 @@ fn render_unified_diff(app: &App, diff_area: Rect) -> Vec<Line<'static>> {
                     line.spans
                         .push(Span::styled(" ".repeat(fill), Style::default().bg(bg)));
                 }
-                out.push(line);
+                line
             } else {
-                out.push(Line::from(vec![Span::styled(
-                    pad_to_width(t.to_string(), content_w),
+                Line::from(vec![Span::styled(
+                    pad_to_width(t.to_string(), code_w),
                     Style::default().fg(app.palette.fg).bg(bg),
-                )]));
+                )])
+            };
+            if let Some(g) = gutter {
+                line.spans.insert(0, g);
             }
+            out.push(line);
         } else {
             out.push(Line::from(vec![Span::styled(
                 pad_to_width(t.to_string(), content_w),
@@ -722,6 +877,10 @@ fn render_unified_diff(app: &App, diff_area: Rect) -> Vec<Line<'static>> {
         }
     }
 
+    if let Some(from) = selected_from {
+        mark_selected(&mut out[from..]);
+    }
+
     out
 }
 
@@ -777,11 +936,11 @@ fn render_side_by_side_diff(app: &App, diff_area: Rect) -> Vec<Line<'static>> {
     let mut hl_old: Option<Highlighter> = None;
     let mut hl_new: Option<Highlighter> = None;
     if app.syntax_highlight {
+        let first_line = git::diff_first_line(&app.git.diff_lines, 0);
         let ext = app
             .git
             .selected_tree_entry()
-            .and_then(|e| std::path::Path::new(e.path.as_str()).extension())
-            .and_then(|s| s.to_str());
+            .and_then(|e| app.syntax_for(e.path.as_str(), first_line));
         hl_old = ext.and_then(new_highlighter);
         hl_new = ext.and_then(new_highlighter);
     }
@@ -819,11 +978,12 @@ fn render_side_by_side_diff(app: &App, diff_area: Rect) -> Vec<Line<'static>> {
                         .and_then(|s| s.split(" b/").next())
                         .unwrap_or(t.as_str());
 
-                    // Update highlighters for this file's extension
+                    // Update highlighters for this file's language
                     if app.syntax_highlight {
-                        let ext = std::path::Path::new(full_path)
-                            .extension()
-                            .and_then(|s| s.to_str());
+                        let start = app.git.diff_lines.iter().position(|l| *l == t);
+                        let first_line =
+                            start.and_then(|i| git::diff_first_line(&app.git.diff_lines, i));
+                        let ext = app.syntax_for(full_path, first_line);
                         hl_old = ext.and_then(new_highlighter);
                         hl_new = ext.and_then(new_highlighter);
                     }
@@ -861,6 +1021,9 @@ fn render_side_by_side_diff(app: &App, diff_area: Rect) -> Vec<Line<'static>> {
             }
             GitDiffRow::Split { old, new } => {
                 let old_style = match old.kind {
+                    GitDiffCellKind::Delete if old.moved => Style::default()
+                        .fg(app.palette.fg)
+                        .bg(app.palette.diff_moved_bg),
                     GitDiffCellKind::Delete => Style::default()
                         .fg(app.palette.fg)
                         .bg(app.palette.diff_del_bg),
@@ -873,6 +1036,9 @@ fn render_side_by_side_diff(app: &App, diff_area: Rect) -> Vec<Line<'static>> {
                         .bg(app.palette.bg),
                 };
                 let new_style = match new.kind {
+                    GitDiffCellKind::Add if new.moved => Style::default()
+                        .fg(app.palette.fg)
+                        .bg(app.palette.diff_moved_bg),
                     GitDiffCellKind::Add => Style::default()
                         .fg(app.palette.fg)
                         .bg(app.palette.diff_add_bg),
@@ -902,11 +1068,13 @@ fn render_side_by_side_diff(app: &App, diff_area: Rect) -> Vec<Line<'static>> {
                         .unwrap_or_else(|| empty_right.clone());
 
                     let old_bg = match old.kind {
+                        GitDiffCellKind::Delete if old.moved => app.palette.diff_moved_bg,
                         GitDiffCellKind::Delete => app.palette.diff_del_bg,
                         GitDiffCellKind::Context | GitDiffCellKind::Add => app.palette.bg,
                         GitDiffCellKind::Empty => app.palette.bg,
                     };
                     let new_bg = match new.kind {
+                        GitDiffCellKind::Add if new.moved => app.palette.diff_moved_bg,
                         GitDiffCellKind::Add => app.palette.diff_add_bg,
                         GitDiffCellKind::Context | GitDiffCellKind::Delete => app.palette.bg,
                         GitDiffCellKind::Empty => app.palette.bg,
@@ -1072,6 +1240,21 @@ fn render_revert_buttons(app: &App, f: &mut Frame, diff_area: Rect, zones: &mut
                     rect: btn_rect,
                     action: AppAction::RevertHunk(hunk_idx),
                 });
+
+                let stash_rect = Rect::new(btn_x.saturating_sub(4), screen_y, 3, 1);
+                f.render_widget(Paragraph::new(" ⇣ ").style(btn_style), stash_rect);
+                zones.push(ClickZone {
+                    rect: stash_rect,
+                    action: AppAction::StashHunk(hunk_idx),
+                });
+
+                // More context above and below the hunk
+                let expand_rect = Rect::new(btn_x.saturating_sub(8), screen_y, 3, 1);
+                f.render_widget(Paragraph::new(" ⤢ ").style(btn_style), expand_rect);
+                zones.push(ClickZone {
+                    rect: expand_rect,
+                    action: AppAction::ExpandHunkContext(hunk_idx),
+                });
             }
         }
     }
@@ -13,10 +13,18 @@ use crate::git::{
     self, GitDiffCellKind, GitDiffMode, GitDiffRow, build_side_by_side_rows, display_width,
     pad_to_width,
 };
+use crate::forge::CiStatus;
 use crate::git_ops;
 use crate::highlight::{Highlighter, new_highlighter};
+use crate::refs::RefItem;
 use crate::theme;
-use crate::{App, AppAction, ClickZone, DiffRenderCacheKey, LogDetailMode, LogSubTab, LogZoom};
+use crate::app::{
+    App, AppAction, ClickZone, DiffRenderCacheKey, LogDetailMode, LogPaneFocus, LogSubTab, LogZoom,
+    Tab,
+};
+
+use super::minimap::{mark_selected, render_diff_minimap, scrollbar_track};
+use super::pane_block;
 
 /// Render the Log tab content: subtab selector, commit list, and diff view
 pub fn render_log_tab(
@@ -27,6 +35,26 @@ pub fn render_log_tab(
 ) {
     let zoom = app.log_ui.zoom;
 
+    // Refs rail on the far left, unless the diff is zoomed or the screen is narrow
+    let show_refs = app.log_ui.refs.open && zoom != LogZoom::Diff && content_area.width >= 80;
+    let content_area = if show_refs {
+        let rail_w = (content_area.width / 5).clamp(20, 32);
+        render_refs_sidebar(
+            app,
+            f,
+            Rect::new(content_area.x, content_area.y, rail_w, content_area.height),
+            zones,
+        );
+        Rect::new(
+            content_area.x + rail_w,
+            content_area.y,
+            content_area.width - rail_w,
+            content_area.height,
+        )
+    } else {
+        content_area
+    };
+
     let (subtab_area, list_area, diff_area) = match zoom {
         LogZoom::None => {
             let chunks = Layout::default()
@@ -91,6 +119,86 @@ pub fn render_log_tab(
     }
 }
 
+/// Render the refs sidebar: branches, remotes and tags as a collapsible tree
+fn render_refs_sidebar(app: &mut App, f: &mut Frame, area: Rect, zones: &mut Vec<ClickZone>) {
+    let palette = app.palette;
+    let refs = &app.log_ui.refs;
+    let viewed = app.log_ui.history_ref.as_deref();
+
+    let items: Vec<ListItem> = refs
+        .items
+        .iter()
+        .map(|item| {
+            let arrow = if refs.is_collapsed(item) { "▸" } else { "▾" };
+            match item {
+                RefItem::Section(kind) => {
+                    let count = refs.refs.iter().filter(|r| r.kind == *kind).count();
+                    ListItem::new(Line::from(vec![
+                        Span::styled(
+                            format!("{} {}", arrow, kind.label()),
+                            Style::default()
+                                .fg(palette.accent_primary)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(
+                            format!(" {}", count),
+                            Style::default().fg(palette.size_color),
+                        ),
+                    ]))
+                }
+                RefItem::Folder { label, depth, .. } => ListItem::new(Span::styled(
+                    format!("{}{} {}/", "  ".repeat(*depth), arrow, label),
+                    Style::default().fg(palette.dir_color),
+                )),
+                RefItem::Ref { idx, depth } => {
+                    let Some(entry) = refs.refs.get(*idx) else {
+                        return ListItem::new("");
+                    };
+                    let label = entry.name.rsplit('/').next().unwrap_or(&entry.name);
+                    let is_viewed = match viewed {
+                        Some(v) => v == entry.name,
+                        None => entry.is_current,
+                    };
+                    let mut style = Style::default().fg(palette.fg);
+                    if is_viewed {
+                        style = style.fg(palette.accent_secondary).add_modifier(Modifier::BOLD);
+                    }
+                    let marker = if entry.is_current { "● " } else { "  " };
+                    ListItem::new(Line::from(vec![
+                        Span::raw("  ".repeat(*depth)),
+                        Span::styled(marker, Style::default().fg(palette.accent_primary)),
+                        Span::styled(label.to_string(), style),
+                    ]))
+                }
+            }
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_set(ratatui::symbols::border::PLAIN)
+                .border_style(Style::default().fg(palette.border_inactive))
+                .title(" Refs (b) "),
+        )
+        .highlight_style(Style::default().bg(palette.selection_bg));
+    f.render_stateful_widget(list, area, &mut app.log_ui.refs.list_state);
+
+    let inner = area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    let offset = app.log_ui.refs.list_state.offset();
+    let end = (offset + inner.height as usize).min(app.log_ui.refs.items.len());
+    for (i, idx) in (offset..end).enumerate() {
+        zones.push(ClickZone {
+            rect: Rect::new(inner.x, inner.y + i as u16, inner.width, 1),
+            action: AppAction::SelectRefItem(idx),
+        });
+    }
+}
+
 /// Render the subtab selector (History, Reflog, Stash, Commands)
 fn render_subtab_selector(app: &App, f: &mut Frame, subtab_area: Rect, zones: &mut Vec<ClickZone>) {
     let mut x = subtab_area.x;
@@ -129,6 +237,23 @@ fn render_subtab_selector(app: &App, f: &mut Frame, subtab_area: Rect, zones: &m
 
 /// Render the log list (history, reflog, stash, or commands)
 fn render_log_list(app: &mut App, f: &mut Frame, list_area: Rect, zones: &mut Vec<ClickZone>) {
+    let list_area = if app.log_ui.subtab == LogSubTab::Reflog && list_area.height > 3 {
+        render_reflog_chips(
+            app,
+            f,
+            Rect::new(list_area.x, list_area.y, list_area.width, 1),
+            zones,
+        );
+        Rect::new(
+            list_area.x,
+            list_area.y + 1,
+            list_area.width,
+            list_area.height - 1,
+        )
+    } else {
+        list_area
+    };
+
     let (title, items_len) = match app.log_ui.subtab {
         LogSubTab::History => (" History ", app.log_ui.history_filtered.len()),
         LogSubTab::Reflog => (" Reflog ", app.log_ui.reflog_filtered.len()),
@@ -153,11 +278,28 @@ fn render_log_list(app: &mut App, f: &mut Frame, list_area: Rect, zones: &mut Ve
             Style::default().fg(app.palette.size_color)
         };
 
+        let mut spans = vec![Span::raw(format!("{}({})  ", title, items_len))];
+        if app.log_ui.subtab == LogSubTab::History
+            && let Some(total) = app.log_ui.history_total
+            && app.log_ui.history.len() < total
+        {
+            spans.push(Span::styled(
+                format!("{} of ~{} loaded  ", app.log_ui.history.len(), total),
+                Style::default().fg(app.palette.size_color),
+            ));
+        }
+        if app.log_ui.subtab == LogSubTab::History && !app.log_ui.marked.is_empty() {
+            spans.push(Span::styled(
+                format!("{} marked  ", app.log_ui.marked.len()),
+                Style::default()
+                    .fg(app.palette.accent_secondary)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        spans.push(Span::styled(filter_label, filter_style));
+
         (
-            Line::from(vec![
-                Span::raw(format!("{}({})  ", title, items_len)),
-                Span::styled(filter_label, filter_style),
-            ]),
+            Line::from(spans),
             if app.log_ui.filter_edit || !q.is_empty() {
                 app.palette.accent_primary
             } else {
@@ -171,19 +313,30 @@ fn render_log_list(app: &mut App, f: &mut Frame, list_area: Rect, zones: &mut Ve
         )
     };
 
-    let list_block = Block::default()
-        .borders(Borders::ALL)
-        .border_set(ratatui::symbols::border::PLAIN)
-        .border_style(Style::default().fg(border_color))
-        .title(list_title);
+    let list_focused = app.log_ui.focus == LogPaneFocus::Commits;
+    let mut list_block = pane_block(&app.palette, list_title, list_focused);
+    if !list_focused {
+        list_block = list_block.border_style(Style::default().fg(border_color));
+    }
 
+    // Borders plus the highlight symbol
+    let columns = HistoryColumns {
+        date: app.log_ui.show_date_column,
+        author: app.log_ui.show_author_column,
+        width: list_area.width.saturating_sub(4) as usize,
+        now: app.now(),
+    };
     let list_items: Vec<ListItem> = match app.log_ui.subtab {
         LogSubTab::History => app
             .log_ui
             .history_filtered
             .iter()
             .filter_map(|idx| app.log_ui.history.get(*idx))
-            .map(|e| ListItem::new(log_history_line(e, app.palette)))
+            .map(|e| {
+                let marked = app.log_ui.marked.contains(&e.hash);
+                let ci = app.ci.get(&e.hash);
+                ListItem::new(log_history_line(e, marked, ci, app.palette, &columns))
+            })
             .collect(),
         LogSubTab::Reflog => app
             .log_ui
@@ -206,7 +359,8 @@ fn render_log_list(app: &mut App, f: &mut Frame, list_area: Rect, zones: &mut Ve
                 .map(|e| {
                     let age = now.duration_since(e.when).as_secs();
                     let tag = if e.ok { "ok" } else { "err" };
-                    ListItem::new(format!("[{tag}] +{age}s  {}", e.cmd))
+                    let took = e.elapsed.as_secs_f64();
+                    ListItem::new(format!("[{tag}] +{age}s  {took:.1}s  {}", e.cmd))
                 })
                 .collect()
         }
@@ -279,6 +433,35 @@ fn render_log_list(app: &mut App, f: &mut Frame, list_area: Rect, zones: &mut Ve
     }
 }
 
+/// Render the reflog operation filter chips (toggle by click, `o` cycles)
+fn render_reflog_chips(app: &App, f: &mut Frame, area: Rect, zones: &mut Vec<ClickZone>) {
+    let mut x = area.x;
+    let max_x = area.x + area.width;
+    for op in git_ops::REFLOG_OPS {
+        let label = format!(" {} ", op.label());
+        let w = label.len() as u16;
+        if x >= max_x {
+            break;
+        }
+        let clipped_w = w.min(max_x.saturating_sub(x));
+        let style = if app.log_ui.reflog_ops.contains(&op) {
+            Style::default()
+                .bg(app.palette.accent_secondary)
+                .fg(app.palette.btn_fg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(app.palette.size_color)
+        };
+        let rect = Rect::new(x, area.y, clipped_w, 1);
+        f.render_widget(Paragraph::new(label).style(style), rect);
+        zones.push(ClickZone {
+            rect,
+            action: AppAction::ToggleReflogOp(op),
+        });
+        x += w + 1;
+    }
+}
+
 /// Render the diff/detail view
 fn render_log_diff(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mut Vec<ClickZone>) {
     let files_mode =
@@ -321,11 +504,11 @@ fn render_files_sidebar(
         .get(app.log_ui.history_state.selected().unwrap_or(0))
         .map(|e| (e.subject.as_str(), e.short.as_str(), e.author.as_str()));
 
-    let file_block = Block::default()
-        .borders(Borders::ALL)
-        .border_set(ratatui::symbols::border::PLAIN)
-        .border_style(Style::default().fg(app.palette.border_inactive))
-        .title(format!(" Files ({}) ", app.log_ui.files.len()));
+    let file_block = pane_block(
+        &app.palette,
+        format!(" Files ({}) ", app.log_ui.files.len()),
+        app.log_ui.focus == LogPaneFocus::Files,
+    );
 
     // Render sidebar block
     f.render_widget(file_block.clone(), files_area);
@@ -359,7 +542,7 @@ fn render_files_sidebar(
             ),
             Span::styled(
                 format!(" {}", author),
-                Style::default().fg(app.palette.border_inactive),
+                Style::default().fg(theme::author_color(&app.palette, author)),
             ),
         ]);
         let sep_line = Line::from(vec![Span::styled(
@@ -388,20 +571,34 @@ fn render_files_sidebar(
                 Some(i) => (&file.path[..i + 1], &file.path[i + 1..]),
                 None => ("", file.path.as_str()),
             };
-            let status_color = match file.status.as_str() {
-                "M" => app.palette.accent_secondary, // Modified
-                "A" => app.palette.diff_add_fg,      // Added
-                "D" => app.palette.diff_del_fg,      // Deleted
-                "R" => app.palette.accent_primary,   // Renamed
+            let kind = file.kind();
+            let status_color = match kind {
+                'M' => app.palette.accent_secondary, // Modified
+                'A' => app.palette.diff_add_fg,      // Added
+                'D' => app.palette.diff_del_fg,      // Deleted
+                'R' | 'C' => app.palette.accent_primary, // Renamed / copied
                 _ => app.palette.fg,
             };
-            let mut spans = vec![
-                Span::styled(
-                    format!("{} ", file.status),
-                    Style::default().fg(status_color),
-                ),
-                Span::styled(filename.to_string(), Style::default().fg(app.palette.fg)),
-            ];
+            let status_label = match (kind, file.similarity()) {
+                ('R', Some(pct)) => format!("➜ {}% ", pct),
+                ('C', Some(pct)) => format!("⧉ {}% ", pct),
+                _ => format!("{} ", kind),
+            };
+            let mut spans = vec![Span::styled(status_label, Style::default().fg(status_color))];
+            // Renames show the old name when it differs from the new one
+            if let Some(old) = file.old_path.as_deref() {
+                let old_name = old.rsplit('/').next().unwrap_or(old);
+                if old_name != filename {
+                    spans.push(Span::styled(
+                        format!("{} → ", old_name),
+                        Style::default().fg(app.palette.border_inactive),
+                    ));
+                }
+            }
+            spans.push(Span::styled(
+                filename.to_string(),
+                Style::default().fg(app.palette.fg),
+            ));
             // Add line change stats
             if let (Some(adds), Some(dels)) = (file.additions, file.deletions) {
                 spans.push(Span::raw(" "));
@@ -427,6 +624,15 @@ fn render_files_sidebar(
                     Style::default().fg(app.palette.border_inactive),
                 ));
             }
+            if let Some(old) = file.old_path.as_deref() {
+                let old_dir = old.rfind('/').map(|i| &old[..i]).unwrap_or("");
+                if old_dir != dir.trim_end_matches('/') {
+                    spans.push(Span::styled(
+                        format!(" (from {})", if old_dir.is_empty() { "." } else { old_dir }),
+                        Style::default().fg(app.palette.border_inactive),
+                    ));
+                }
+            }
             ListItem::new(Line::from(spans))
         })
         .collect();
@@ -475,11 +681,7 @@ fn render_diff_content(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mu
         LogSubTab::Commands => " Command Output ",
     };
 
-    let diff_block = Block::default()
-        .borders(Borders::ALL)
-        .border_set(ratatui::symbols::border::PLAIN)
-        .border_style(Style::default().fg(app.palette.border_inactive))
-        .title(diff_title);
+    let diff_block = pane_block(&app.palette, diff_title, app.log_ui.focus == LogPaneFocus::Diff);
 
     let cache_width = diff_area.width.saturating_sub(2).max(1);
     let cache_scroll_x = if app.log_ui.diff_mode == GitDiffMode::Unified && !app.wrap_diff {
@@ -494,6 +696,8 @@ fn render_diff_content(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mu
         width: cache_width,
         wrap: app.wrap_diff,
         syntax_highlight: app.syntax_highlight,
+        line_numbers: app.diff_line_numbers,
+        selection: app.diff_selection_range(Tab::Log),
         scroll_x: cache_scroll_x,
     };
 
@@ -527,6 +731,8 @@ fn render_diff_content(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mu
     let wrap_unified = app.log_ui.diff_mode == GitDiffMode::Unified && app.wrap_diff;
 
     let viewport_h = diff_area.height.saturating_sub(2) as usize;
+    app.log_ui.diff_scroll_y =
+        app.follow_diff_selection(Tab::Log, app.log_ui.diff_scroll_y, viewport_h);
     let total_lines = diff_lines.len();
     let max_y = if viewport_h == 0 {
         0
@@ -596,6 +802,20 @@ fn render_diff_content(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mu
         rect: diff_area,
         action: AppAction::LogFocusDiff,
     });
+    if total_lines > viewport_h {
+        render_diff_minimap(
+            app,
+            f,
+            scrollbar_track(diff_area),
+            &app.log_diff_cache.lines,
+            AppAction::LogDiffScrollTo,
+            zones,
+        );
+    }
+
+    if app.log_ui.subtab == LogSubTab::History && app.log_ui.commit_parent_count > 1 {
+        render_diff_base_selector(app, f, diff_area, zones);
+    }
 
     if let Some(msg) = app.log_ui.status.as_deref() {
         zones.push(ClickZone {
@@ -615,6 +835,44 @@ fn render_diff_content(app: &mut App, f: &mut Frame, diff_area: Rect, zones: &mu
     }
 }
 
+/// Render the merge parent selector (parent N / combined) on the diff title row
+fn render_diff_base_selector(
+    app: &App,
+    f: &mut Frame,
+    diff_area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    let mut options: Vec<(String, git_ops::DiffBase)> = (0..app.log_ui.commit_parent_count)
+        .map(|i| (format!(" parent {} ", i + 1), git_ops::DiffBase::Parent(i)))
+        .collect();
+    options.push((" combined ".to_string(), git_ops::DiffBase::Combined));
+
+    let total_w: u16 = options.iter().map(|(l, _)| l.len() as u16 + 1).sum();
+    let max_x = diff_area.x + diff_area.width.saturating_sub(2);
+    let mut x = max_x.saturating_sub(total_w).max(diff_area.x + 1);
+    for (label, base) in options {
+        let w = label.len() as u16;
+        if x + w > max_x {
+            break;
+        }
+        let style = if app.log_ui.diff_base == base {
+            Style::default()
+                .bg(app.palette.accent_primary)
+                .fg(app.palette.btn_fg)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().bg(app.palette.bg).fg(app.palette.size_color)
+        };
+        let rect = Rect::new(x, diff_area.y, w, 1);
+        f.render_widget(Paragraph::new(label).style(style), rect);
+        zones.push(ClickZone {
+            rect,
+            action: AppAction::LogDiffBase(base),
+        });
+        x += w + 1;
+    }
+}
+
 /// Render unified diff for log view
 fn render_log_unified_diff(
     app: &App,
@@ -622,7 +880,7 @@ fn render_log_unified_diff(
     header_lines: &[String],
     diff_only_lines: &[String],
 ) -> Vec<Line<'static>> {
-    let mut out = Vec::new();
+    let mut out: Vec<Line<'static>> = Vec::new();
     let mut highlighter: Option<Highlighter> = None;
 
     let content_w = diff_area.width.saturating_sub(2).max(1) as usize;
@@ -660,13 +918,26 @@ fn render_log_unified_diff(
     }
 
     let mut first_file = true;
-    for l in diff_only_lines {
+    let mut numbers = git::LineNumberTracker::default();
+    // Selection indices refer to the full diff, header lines included
+    let selection = app.diff_selection_range(Tab::Log);
+    let mut selected_from: Option<usize> = None;
+    let moved = git::moved_lines(diff_only_lines);
+    for (idx, l) in diff_only_lines.iter().enumerate() {
+        if let Some(from) = selected_from.take() {
+            mark_selected(&mut out[from..]);
+        }
+        let raw_idx = header_lines.len() + idx;
+        if selection.is_some_and(|(start, end)| (start..=end).contains(&raw_idx)) {
+            selected_from = Some(out.len());
+        }
         let t = l.as_str();
+        let line_no = numbers.advance(t);
 
         if app.syntax_highlight {
             if let Some(p) = t.strip_prefix("+++ b/") {
-                let ext = std::path::Path::new(p).extension().and_then(|s| s.to_str());
-                highlighter = ext.and_then(new_highlighter);
+                let first_line = git::diff_first_line(diff_only_lines, idx);
+                highlighter = app.syntax_for(p, first_line).and_then(new_highlighter);
             }
         }
 
@@ -734,6 +1005,8 @@ fn render_log_unified_diff(
 
         let (prefix, code) = t.split_at(t.chars().next().map(|c| c.len_utf8()).unwrap_or(0));
         let (bg, prefix_fg, is_code) = match prefix {
+            "+" if moved[idx] => (app.palette.diff_moved_bg, app.palette.diff_add_fg, true),
+            "-" if moved[idx] => (app.palette.diff_moved_bg, app.palette.diff_del_fg, true),
             "+" if !t.starts_with("+++") => {
                 (app.palette.diff_add_bg, app.palette.diff_add_fg, true)
             }
@@ -744,10 +1017,18 @@ fn render_log_unified_diff(
             _ => (app.palette.bg, app.palette.fg, false),
         };
 
-        let fill = content_w.saturating_sub(display_width(t));
+        let gutter = (is_code && app.diff_line_numbers).then(|| {
+            Span::styled(
+                git::line_number_gutter(line_no),
+                Style::default().fg(app.palette.diff_gutter_fg).bg(bg),
+            )
+        });
+        let fill = content_w
+            .saturating_sub(gutter.as_ref().map_or(0, |g| g.width()))
+            .saturating_sub(display_width(t));
 
         if is_code {
-            if let Some(hl) = highlighter.as_mut() {
+            let mut line = if let Some(hl) = highlighter.as_mut() {
                 let mut line = hl.highlight_diff_code_with_prefix(
                     prefix,
                     code,
@@ -758,7 +1039,7 @@ fn render_log_unified_diff(
                     line.spans
                         .push(Span::styled(" ".repeat(fill), Style::default().bg(bg)));
                 }
-                out.push(line);
+                line
             } else {
                 // Without syntax highlight, still color the prefix
                 let mut spans = vec![
@@ -768,8 +1049,12 @@ fn render_log_unified_diff(
                 if fill > 0 {
                     spans.push(Span::styled(" ".repeat(fill), Style::default().bg(bg)));
                 }
-                out.push(Line::from(spans));
+                Line::from(spans)
+            };
+            if let Some(g) = gutter {
+                line.spans.insert(0, g);
             }
+            out.push(line);
         } else {
             out.push(Line::from(vec![Span::styled(
                 pad_to_width(t.to_string(), content_w),
@@ -778,6 +1063,10 @@ fn render_log_unified_diff(
         }
     }
 
+    if let Some(from) = selected_from {
+        mark_selected(&mut out[from..]);
+    }
+
     out
 }
 
@@ -869,7 +1158,10 @@ fn render_log_side_by_side_diff(
             GitDiffRow::Meta(t) => {
                 if app.syntax_highlight {
                     if let Some(p) = t.strip_prefix("+++ b/") {
-                        let ext = std::path::Path::new(p).extension().and_then(|s| s.to_str());
+                        let start = diff_only_lines.iter().position(|l| *l == t);
+                        let first_line =
+                            start.and_then(|i| git::diff_first_line(diff_only_lines, i));
+                        let ext = app.syntax_for(p, first_line);
                         hl_old = ext.and_then(new_highlighter);
                         hl_new = ext.and_then(new_highlighter);
                     }
@@ -935,6 +1227,9 @@ fn render_log_side_by_side_diff(
             }
             GitDiffRow::Split { old, new } => {
                 let old_style = match old.kind {
+                    GitDiffCellKind::Delete if old.moved => Style::default()
+                        .fg(app.palette.fg)
+                        .bg(app.palette.diff_moved_bg),
                     GitDiffCellKind::Delete => Style::default()
                         .fg(app.palette.fg)
                         .bg(app.palette.diff_del_bg),
@@ -947,6 +1242,9 @@ fn render_log_side_by_side_diff(
                         .bg(app.palette.bg),
                 };
                 let new_style = match new.kind {
+                    GitDiffCellKind::Add if new.moved => Style::default()
+                        .fg(app.palette.fg)
+                        .bg(app.palette.diff_moved_bg),
                     GitDiffCellKind::Add => Style::default()
                         .fg(app.palette.fg)
                         .bg(app.palette.diff_add_bg),
@@ -975,11 +1273,13 @@ fn render_log_side_by_side_diff(
                         .cloned()
                         .unwrap_or_else(|| empty_right.clone());
                     let old_bg = match old.kind {
+                        GitDiffCellKind::Delete if old.moved => app.palette.diff_moved_bg,
                         GitDiffCellKind::Delete => app.palette.diff_del_bg,
                         GitDiffCellKind::Context | GitDiffCellKind::Add => app.palette.bg,
                         GitDiffCellKind::Empty => app.palette.bg,
                     };
                     let new_bg = match new.kind {
+                        GitDiffCellKind::Add if new.moved => app.palette.diff_moved_bg,
                         GitDiffCellKind::Add => app.palette.diff_add_bg,
                         GitDiffCellKind::Context | GitDiffCellKind::Delete => app.palette.bg,
                         GitDiffCellKind::Empty => app.palette.bg,
@@ -1173,9 +1473,76 @@ fn git_decoration_spans(decoration: &str, palette: theme::Palette) -> Vec<Span<'
     spans
 }
 
-fn log_history_line(e: &git_ops::CommitEntry, palette: theme::Palette) -> Line<'static> {
+/// Optional aligned columns of the history list.
+struct HistoryColumns {
+    date: bool,
+    author: bool,
+    /// Usable row width
+    width: usize,
+    /// Current unix time, for relative dates
+    now: i64,
+}
+
+const DATE_COLUMN_WIDTH: usize = 8;
+
+/// Cut `spans` to `width` columns, ending in `…` when something was dropped, and pad.
+fn fit_spans(spans: Vec<Span<'static>>, width: usize) -> Vec<Span<'static>> {
+    let total: usize = spans.iter().map(|s| display_width(&s.content)).sum();
+    if total <= width {
+        let mut out = spans;
+        out.push(Span::raw(" ".repeat(width - total)));
+        return out;
+    }
+
+    let mut out = Vec::new();
+    let mut left = width.saturating_sub(1);
+    for span in spans {
+        let w = display_width(&span.content);
+        if w <= left {
+            left -= w;
+            out.push(span);
+            continue;
+        }
+        let cut = git::truncate_to_width(&span.content, left);
+        left -= display_width(&cut);
+        out.push(Span::styled(cut, span.style));
+        break;
+    }
+    out.push(Span::raw(format!("…{}", " ".repeat(left))));
+    out
+}
+
+fn log_history_line(
+    e: &git_ops::CommitEntry,
+    marked: bool,
+    ci: Option<CiStatus>,
+    palette: theme::Palette,
+    columns: &HistoryColumns,
+) -> Line<'static> {
     let mut spans: Vec<Span<'static>> = Vec::new();
 
+    // Marked for cherry-pick
+    if marked {
+        spans.push(Span::styled(
+            "▣ ",
+            Style::default()
+                .fg(palette.accent_secondary)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    if let Some(ci) = ci {
+        let color = match ci {
+            CiStatus::Success => palette.diff_add_fg,
+            CiStatus::Pending => palette.accent_tertiary,
+            CiStatus::Failure => palette.diff_del_fg,
+        };
+        spans.push(Span::styled(
+            format!("{} ", ci.icon()),
+            Style::default().fg(color),
+        ));
+    }
+
     // Subject first - most important info
     spans.push(Span::styled(
         e.subject.clone(),
@@ -1189,14 +1556,60 @@ fn log_history_line(e: &git_ops::CommitEntry, palette: theme::Palette) -> Line<'
         spans.extend(dec_spans);
     }
 
-    // Hash at the end, dimmed
-    spans.push(Span::raw("  "));
-    spans.push(Span::styled(
-        e.short.clone(),
-        Style::default().fg(palette.size_color),
-    ));
+    let hash = Span::styled(e.short.clone(), Style::default().fg(palette.size_color));
+    let signature = signature_badge(e.signature, palette);
+    // Columns drop out as the pane narrows, author first, so the subject keeps room
+    let show_date = columns.date && columns.width >= 36;
+    let author_width = (columns.width / 5).clamp(8, 16);
+    let show_author = columns.author && columns.width >= 48;
+    if !show_date && !show_author {
+        // Hash at the end, dimmed
+        spans.push(Span::raw("  "));
+        spans.extend(signature);
+        spans.push(hash);
+        return Line::from(spans);
+    }
 
-    Line::from(spans)
+    let mut right: Vec<Span<'static>> = Vec::new();
+    if show_date {
+        let age = git_ops::relative_age(e.timestamp, columns.now);
+        right.push(Span::styled(
+            format!("  {:>width$}", age, width = DATE_COLUMN_WIDTH),
+            Style::default().fg(palette.size_color),
+        ));
+    }
+    if show_author {
+        right.push(Span::raw("  "));
+        right.push(Span::styled(
+            pad_to_width(git::truncate_to_width(&e.author, author_width), author_width),
+            Style::default().fg(theme::author_color(&palette, &e.author)),
+        ));
+    }
+    right.push(Span::raw("  "));
+    right.extend(signature);
+    right.push(hash);
+
+    let right_width: usize = right.iter().map(|s| display_width(&s.content)).sum();
+    let mut line = fit_spans(spans, columns.width.saturating_sub(right_width));
+    line.extend(right);
+    Line::from(line)
+}
+
+/// `✓` for a good signature, `?` for one that can't be trusted, `✗` for a bad one
+fn signature_badge(
+    signature: git_ops::Signature,
+    palette: theme::Palette,
+) -> Option<Span<'static>> {
+    let color = match signature {
+        git_ops::Signature::None => return None,
+        git_ops::Signature::Good => palette.diff_add_fg,
+        git_ops::Signature::Untrusted | git_ops::Signature::Unknown => palette.accent_tertiary,
+        git_ops::Signature::Bad => palette.diff_del_fg,
+    };
+    Some(Span::styled(
+        format!("{} ", signature.icon()),
+        Style::default().fg(color),
+    ))
 }
 
 fn log_reflog_line(e: &git_ops::ReflogEntry, palette: theme::Palette) -> Line<'static> {
@@ -0,0 +1,105 @@
+//! Diff pane decorations: change markers on the scrollbar and line selection
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+};
+
+use crate::app::{App, AppAction, ClickZone};
+
+/// Track cells of the diff scrollbar, between its begin and end arrows.
+pub(super) fn scrollbar_track(diff_area: Rect) -> Rect {
+    Rect::new(
+        diff_area.x + diff_area.width.saturating_sub(1),
+        diff_area.y + 2,
+        1,
+        diff_area.height.saturating_sub(4),
+    )
+}
+
+/// Paint add/delete markers on the scrollbar track of a diff pane so the shape of a
+/// change is visible at a glance. Each marked cell is clickable and scrolls the diff
+/// to the first changed line it covers.
+pub(super) fn render_diff_minimap(
+    app: &App,
+    f: &mut Frame,
+    track: Rect,
+    lines: &[Line],
+    jump: fn(u16) -> AppAction,
+    zones: &mut Vec<ClickZone>,
+) {
+    let cells = track.height as usize;
+    let total = lines.len();
+    if cells == 0 || total == 0 {
+        return;
+    }
+
+    let kinds: Vec<(bool, bool)> = lines
+        .iter()
+        .map(|l| {
+            let add = l
+                .spans
+                .iter()
+                .any(|s| s.style.bg == Some(app.palette.diff_add_bg));
+            let del = l
+                .spans
+                .iter()
+                .any(|s| s.style.bg == Some(app.palette.diff_del_bg));
+            // Moved code is both, which the mixed color already stands for
+            let moved = l
+                .spans
+                .iter()
+                .any(|s| s.style.bg == Some(app.palette.diff_moved_bg));
+            (add || moved, del || moved)
+        })
+        .collect();
+
+    for cell in 0..cells {
+        let start = cell * total / cells;
+        let end = ((cell + 1) * total / cells).clamp(start + 1, total);
+        let mut add = false;
+        let mut del = false;
+        let mut first = None;
+        for (i, (a, d)) in kinds[start..end].iter().enumerate() {
+            if (*a || *d) && first.is_none() {
+                first = Some(start + i);
+            }
+            add |= *a;
+            del |= *d;
+        }
+        let Some(first) = first else {
+            continue;
+        };
+
+        let color = match (add, del) {
+            (true, true) => app.palette.accent_secondary,
+            (true, false) => app.palette.diff_add_fg,
+            _ => app.palette.diff_del_fg,
+        };
+        let y = track.y + cell as u16;
+        if let Some(c) = f.buffer_mut().cell_mut((track.x, y)) {
+            // Keep the thumb glyph so the scroll position stays readable
+            if c.symbol() != "█" {
+                c.set_symbol("┃");
+            }
+            c.set_style(Style::default().fg(color));
+        }
+        // Land a few rows above the change so it has some context
+        let target = first.saturating_sub(3).min(u16::MAX as usize) as u16;
+        zones.push(ClickZone {
+            rect: Rect::new(track.x, y, 1, 1),
+            action: jump(target),
+        });
+    }
+}
+
+/// Highlight rendered rows that belong to the diff line selection.
+pub(super) fn mark_selected(lines: &mut [Line<'static>]) {
+    for line in lines {
+        for span in &mut line.spans {
+            span.style = span.style.add_modifier(Modifier::REVERSED);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Tab rendering modules
+
+mod blame;
+mod conflicts;
+mod explorer;
+mod git;
+mod log;
+mod minimap;
+mod review;
+
+pub use blame::render_blame_view;
+pub use explorer::render_explorer_tab;
+pub use git::render_git_tab;
+pub use log::render_log_tab;
+
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders},
+};
+
+use crate::theme::Palette;
+
+/// Bordered pane; the focused one gets the accent border and a `●` in its title bar.
+fn pane_block<'a>(palette: &Palette, title: impl Into<Line<'a>>, focused: bool) -> Block<'a> {
+    let color = if focused {
+        palette.accent_primary
+    } else {
+        palette.border_inactive
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(ratatui::symbols::border::PLAIN)
+        .border_style(Style::default().fg(color))
+        .title(title);
+    if focused {
+        block.title(Line::from(" ● ").right_aligned())
+    } else {
+        block
+    }
+}
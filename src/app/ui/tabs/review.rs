@@ -0,0 +1,180 @@
+//! Review view - the staged hunks one at a time before a commit
+
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+
+use crate::app::{App, AppAction, ClickZone, PaneFocus};
+use crate::git::{self, pad_to_width};
+
+use super::pane_block;
+
+/// Render the pre-commit review: one staged hunk at a time with approve/unstage
+pub(super) fn render_review_view(
+    app: &mut App,
+    f: &mut Frame,
+    diff_area: Rect,
+    zones: &mut Vec<ClickZone>,
+) {
+    let total = app.review.hunks.len();
+    let title = if app.review.at_end() {
+        " Review staged changes ".to_string()
+    } else {
+        format!(" Review {}/{} ", app.review.index + 1, total)
+    };
+    let block = pane_block(&app.palette, title, app.git_focus == PaneFocus::Detail);
+    f.render_widget(block, diff_area);
+
+    let inner = diff_area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let (approved, unstaged) = app.review.counts();
+    let counts = Span::styled(
+        format!("  ✓ {}  ↶ {}", approved, unstaged),
+        Style::default().fg(app.palette.border_inactive),
+    );
+    let content_w = rows[1].width as usize;
+    let mut content_lines: Vec<Line> = Vec::new();
+    let header = if let Some(hunk) = app.review.current() {
+        for l in &hunk.lines {
+            let style = if l.starts_with("@@") {
+                Style::default()
+                    .fg(app.palette.fg)
+                    .bg(app.palette.diff_hunk_bg)
+                    .add_modifier(Modifier::BOLD)
+            } else if l.starts_with('+') {
+                Style::default()
+                    .fg(app.palette.diff_add_fg)
+                    .bg(app.palette.diff_add_bg)
+            } else if l.starts_with('-') {
+                Style::default()
+                    .fg(app.palette.diff_del_fg)
+                    .bg(app.palette.diff_del_bg)
+            } else {
+                Style::default().fg(app.palette.fg)
+            };
+            let text = git::slice_chars(l, app.git.diff_scroll_x as usize, content_w);
+            content_lines.push(Line::from(Span::styled(
+                pad_to_width(text, content_w),
+                style,
+            )));
+        }
+        let verdict = match app.review.verdict(app.review.index) {
+            Some(crate::review::Verdict::Approved) => "  (approved)",
+            Some(crate::review::Verdict::Unstaged) => "  (unstaged)",
+            None => "",
+        };
+        Line::from(vec![
+            Span::styled(
+                format!("📄 {}", hunk.path),
+                Style::default()
+                    .fg(app.palette.accent_primary)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(verdict, Style::default().fg(app.palette.accent_secondary)),
+            counts,
+        ])
+    } else {
+        content_lines.push(Line::raw(format!(
+            "Reached the end: {} approved, {} unstaged.",
+            approved, unstaged
+        )));
+        if app.review_before_commit {
+            content_lines.push(Line::raw(if app.review.complete {
+                "Commit is unlocked."
+            } else {
+                "Some staged hunks still need approval; review again with Ctrl+R."
+            }));
+        }
+        content_lines.push(Line::raw(
+            "Enter opens the commit drawer, Backspace goes back.",
+        ));
+        Line::from(vec![
+            Span::styled(
+                "All staged hunks reviewed",
+                Style::default()
+                    .fg(app.palette.diff_add_fg)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            counts,
+        ])
+    };
+    f.render_widget(Paragraph::new(header), rows[0]);
+
+    let max_scroll = content_lines.len().saturating_sub(rows[1].height as usize);
+    app.review.scroll_y = app
+        .review
+        .scroll_y
+        .min(max_scroll.min(u16::MAX as usize) as u16);
+    f.render_widget(
+        Paragraph::new(content_lines).scroll((app.review.scroll_y, 0)),
+        rows[1],
+    );
+
+    let at_end = app.review.at_end();
+    let enabled = app.pending_job.is_none();
+    let mut x = rows[2].x;
+    for (label, action, color, shown) in [
+        (
+            " Approve (space) ",
+            AppAction::ReviewApprove,
+            app.palette.exe_color,
+            !at_end,
+        ),
+        (
+            " Unstage (u) ",
+            AppAction::ReviewUnstage,
+            app.palette.btn_bg,
+            !at_end,
+        ),
+        (
+            " < Back ",
+            AppAction::ReviewBack,
+            app.palette.accent_tertiary,
+            true,
+        ),
+        (
+            " Close ",
+            AppAction::CloseReview,
+            app.palette.border_inactive,
+            true,
+        ),
+    ] {
+        if !shown {
+            continue;
+        }
+        let w = label.chars().count() as u16;
+        if x + w > rows[2].x + rows[2].width {
+            break;
+        }
+        let bg = if enabled {
+            color
+        } else {
+            app.palette.border_inactive
+        };
+        let style = Style::default()
+            .bg(bg)
+            .fg(app.palette.btn_fg)
+            .add_modifier(Modifier::BOLD);
+        let rect = Rect::new(x, rows[2].y, w, 1);
+        f.render_widget(Paragraph::new(label).style(style), rect);
+        if enabled {
+            zones.push(ClickZone { rect, action });
+        }
+        x += w + 1;
+    }
+}
@@ -0,0 +1,1560 @@
+//! Scripted sessions against throwaway repositories
+//!
+//! A [`Session`] runs the whole `App` on a [`TestBackend`]: key and mouse events go
+//! through the same handler as the terminal's, background jobs are waited for, and
+//! every step is rendered so tests can assert what is on screen as well as what git
+//! ends up with. The `--render-snapshot` scenarios are checked against the golden
+//! files in `tests/snapshots`; run with `LZGIT_UPDATE_SNAPSHOTS=1` to rewrite them.
+
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    path::Path,
+    process::Command,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering as AtomicOrdering},
+        mpsc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::{Terminal, backend::TestBackend};
+use ratatui_image::picker::Picker;
+use tempfile::TempDir;
+
+use crate::app::{
+    App, AppAction, CommandId, Dirs, JobResult, LogSubTab, PendingJob, Tab, snapshot, update::Msg,
+};
+use crate::{
+    bookmarks::Bookmark, config, forge, git, git_diff_loader, git_ops, hooks, multiplexer,
+    preview_loader,
+};
+
+const WIDTH: u16 = 140;
+const HEIGHT: u16 = 40;
+
+/// Keeps git's global and system config out of the commands a [`TestRepo`] runs,
+/// and lets them commit without asking for an identity or an editor.
+const GIT_ENV: [(&str, &str); 7] = [
+    ("GIT_CONFIG_GLOBAL", "/dev/null"),
+    ("GIT_CONFIG_NOSYSTEM", "1"),
+    ("GIT_AUTHOR_NAME", "Test"),
+    ("GIT_AUTHOR_EMAIL", "test@example.com"),
+    ("GIT_COMMITTER_NAME", "Test"),
+    ("GIT_COMMITTER_EMAIL", "test@example.com"),
+    ("GIT_EDITOR", "true"),
+];
+
+/// A temporary repository on `main` with one empty commit.
+pub struct TestRepo {
+    dir: TempDir,
+}
+
+impl TestRepo {
+    pub fn new() -> Self {
+        let repo = Self {
+            dir: TempDir::new().unwrap(),
+        };
+        repo.git(&["init", "-q", "-b", "main"]);
+        // The app's own git commands read the repository's config, not our env
+        repo.git(&["config", "user.name", "Test"]);
+        repo.git(&["config", "user.email", "test@example.com"]);
+        repo.git(&["commit", "-q", "--allow-empty", "-m", "initial"]);
+        repo
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Run git in the repository and return its stdout; panics when git fails.
+    pub fn git(&self, args: &[&str]) -> String {
+        let out = self.try_git(args);
+        assert!(out.0, "git {:?} failed: {}", args, out.1);
+        out.1
+    }
+
+    /// Run git, returning whether it succeeded and its stdout (stderr on failure).
+    pub fn try_git(&self, args: &[&str]) -> (bool, String) {
+        let out = Command::new("git")
+            .args(args)
+            .current_dir(self.path())
+            .envs(GIT_ENV)
+            .output()
+            .unwrap();
+        let text = if out.status.success() {
+            &out.stdout
+        } else {
+            &out.stderr
+        };
+        (
+            out.status.success(),
+            String::from_utf8_lossy(text).into_owned(),
+        )
+    }
+
+    pub fn write(&self, rel: &str, contents: &str) {
+        let path = self.path().join(rel);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(path, contents).unwrap();
+    }
+
+    pub fn read(&self, rel: &str) -> String {
+        fs::read_to_string(self.path().join(rel)).unwrap()
+    }
+
+    /// Write `files` and commit them with `message`.
+    pub fn commit(&self, files: &[(&str, &str)], message: &str) {
+        for (rel, contents) in files {
+            self.write(rel, contents);
+        }
+        self.git(&["add", "-A"]);
+        self.git(&["commit", "-q", "-m", message]);
+    }
+}
+
+/// The app running on a fixed-size [`TestBackend`] in a [`TestRepo`].
+pub struct Session {
+    pub repo: TestRepo,
+    pub app: App,
+    terminal: Terminal<TestBackend>,
+}
+
+impl Session {
+    /// Start the app in `repo` and wait for its first refresh. Must run inside a
+    /// tokio runtime, since the preview and diff loaders are tasks.
+    pub async fn start(repo: TestRepo) -> Self {
+        let (preview_loader, preview_result_rx) = preview_loader::PreviewLoader::new();
+        let (git_diff_loader, git_diff_result_rx) = git_diff_loader::GitDiffLoader::new();
+        let app = App::new(
+            repo.path().to_path_buf(),
+            Picker::halfblocks(),
+            preview_loader,
+            preview_result_rx,
+            git_diff_loader,
+            git_diff_result_rx,
+            // Settings and history stay with the test instead of the user's
+            Dirs {
+                config: Some(repo.path().join(".git/lzgit-config")),
+                state: Some(repo.path().join(".git/lzgit-state")),
+            },
+        );
+        let mut session = Self {
+            repo,
+            app,
+            terminal: Terminal::new(TestBackend::new(WIDTH, HEIGHT)).unwrap(),
+        };
+        session.settle().await;
+        session
+    }
+
+    /// Run frames until no background job is left, as the main loop would, and
+    /// render the result.
+    pub async fn settle(&mut self) {
+        if let Err(e) = snapshot::settle(&mut self.app, &mut self.terminal).await {
+            panic!("{}", e);
+        }
+    }
+
+    /// Send `msg` through [`App::update`] and wait for what it started.
+    pub async fn update(&mut self, msg: Msg) {
+        self.app.update(msg);
+        self.settle().await;
+    }
+
+    pub async fn send(&mut self, event: Event) {
+        self.update(Msg::Event(event)).await;
+    }
+
+    pub async fn press(&mut self, code: KeyCode) {
+        self.key(code, KeyModifiers::NONE).await;
+    }
+
+    pub async fn key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        self.send(Event::Key(KeyEvent::new(code, modifiers))).await;
+    }
+
+    /// Type `text` one character at a time.
+    pub async fn type_text(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.press(KeyCode::Char(ch)).await;
+        }
+    }
+
+    /// Left click on a cell.
+    pub async fn click(&mut self, column: u16, row: u16) {
+        for kind in [
+            MouseEventKind::Down(MouseButton::Left),
+            MouseEventKind::Up(MouseButton::Left),
+        ] {
+            self.app.update(Msg::Event(Event::Mouse(MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })));
+        }
+        self.settle().await;
+    }
+
+    /// Left click on the first place `text` is shown; panics when it is not.
+    pub async fn click_text(&mut self, text: &str) {
+        let Some((column, row)) = self.find(text) else {
+            panic!("{:?} is not on screen:\n{}", text, self.screen());
+        };
+        self.click(column, row).await;
+    }
+
+    /// Column and row where `text` starts on screen.
+    pub fn find(&self, text: &str) -> Option<(u16, u16)> {
+        let buffer = self.terminal.backend().buffer();
+        for row in 0..buffer.area.height {
+            let mut line = String::new();
+            let mut columns = Vec::new();
+            for column in 0..buffer.area.width {
+                let symbol = buffer[(column, row)].symbol();
+                for _ in 0..symbol.len() {
+                    columns.push(column);
+                }
+                line.push_str(symbol);
+            }
+            if let Some(at) = line.find(text) {
+                return Some((columns[at], row));
+            }
+        }
+        None
+    }
+
+    /// The rendered screen, one line per row.
+    pub fn screen(&self) -> String {
+        snapshot::plain_text(self.terminal.backend().buffer())
+    }
+
+    pub fn assert_shows(&self, text: &str) {
+        assert!(
+            self.find(text).is_some(),
+            "{:?} is not on screen:\n{}",
+            text,
+            self.screen()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_space_stages_the_selected_file() {
+    let repo = TestRepo::new();
+    repo.commit(&[("notes.txt", "one\n")], "add notes");
+    repo.write("notes.txt", "one\ntwo\n");
+
+    let mut session = Session::start(repo).await;
+    assert!(session.app.current_tab == Tab::Git);
+    session.assert_shows("notes.txt");
+    session.assert_shows("+ two");
+
+    session.press(KeyCode::Char(' ')).await;
+    assert_eq!(
+        session.repo.git(&["diff", "--cached", "--name-only"]),
+        "notes.txt\n"
+    );
+    session.assert_shows("Staged");
+}
+
+/// Start a git job that runs until the returned sender is used or dropped.
+fn hold_git_job(session: &mut Session) -> mpsc::Sender<()> {
+    let (release, wait) = mpsc::channel::<()>();
+    session
+        .app
+        .start_git_job("git fetch".to_string(), false, false, move || {
+            let _ = wait.recv();
+            Ok(())
+        });
+    release
+}
+
+#[tokio::test]
+async fn test_review_unstage_queues_behind_a_running_job() {
+    let repo = TestRepo::new();
+    repo.commit(&[("notes.txt", "one\n")], "add notes");
+    repo.write("notes.txt", "one\ntwo\n");
+    repo.git(&["add", "notes.txt"]);
+
+    let mut session = Session::start(repo).await;
+    session.update(Msg::Action(AppAction::OpenReview)).await;
+    assert!(session.app.review.open);
+    let release = hold_git_job(&mut session);
+    session.app.update(Msg::Action(AppAction::ReviewUnstage));
+    assert_eq!(session.app.job_queue.len(), 1);
+
+    drop(release);
+    session.settle().await;
+    assert_eq!(session.repo.git(&["diff", "--cached", "--name-only"]), "");
+}
+
+#[tokio::test]
+async fn test_hunk_and_stash_jobs_queue_behind_a_running_job() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "a\n"), ("b.txt", "b\n")], "add files");
+    repo.write("a.txt", "stashed\n");
+    repo.git(&["stash", "-q"]);
+    repo.write("b.txt", "b\nmore\n");
+
+    let mut session = Session::start(repo).await;
+    assert!(!session.app.git.diff_hunks.is_empty());
+    let release = hold_git_job(&mut session);
+    session.app.revert_hunk(0);
+    assert!(session.app.stash_apply_selector("stash@{0}".to_string()));
+    session
+        .app
+        .run_stash_action(super::StashConfirmAction::Drop, "stash@{0}".to_string());
+    assert_eq!(session.app.job_queue.len(), 3);
+
+    drop(release);
+    session.settle().await;
+    assert_eq!(session.repo.read("b.txt"), "b\n");
+    assert_eq!(session.repo.read("a.txt"), "stashed\n");
+    assert_eq!(session.repo.git(&["stash", "list"]), "");
+}
+
+#[tokio::test]
+async fn test_stage_button_queues_behind_a_running_job() {
+    let repo = TestRepo::new();
+    repo.commit(&[("notes.txt", "one\n")], "add notes");
+    repo.write("notes.txt", "one\ntwo\n");
+
+    let mut session = Session::start(repo).await;
+    let release = hold_git_job(&mut session);
+    snapshot::draw(&mut session.app, &mut session.terminal).unwrap();
+
+    // Clicked without settling, since that would wait for the held job
+    let (column, row) = session.find("+ Stage").unwrap();
+    for kind in [
+        MouseEventKind::Down(MouseButton::Left),
+        MouseEventKind::Up(MouseButton::Left),
+    ] {
+        session.app.update(Msg::Event(Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })));
+    }
+    assert_eq!(session.app.job_queue.len(), 1);
+
+    release.send(()).unwrap();
+    session.settle().await;
+    assert_eq!(
+        session.repo.git(&["diff", "--cached", "--name-only"]),
+        "notes.txt\n"
+    );
+}
+
+#[tokio::test]
+async fn test_directory_summary_drills_into_a_file() {
+    let repo = TestRepo::new();
+    repo.commit(&[("src/a.rs", "a\n"), ("src/b.rs", "b\n")], "add sources");
+    repo.write("src/a.rs", "a\nmore\n");
+    repo.write("src/b.rs", "b\nlater\n");
+
+    let mut session = Session::start(repo).await;
+    // The first refresh is synchronous and leaves the line counts to the next one
+    session.app.refresh_git_state();
+    session.settle().await;
+    let dir = session
+        .app
+        .git
+        .flat_tree
+        .iter()
+        .position(|item| item.node_type == git::FlatNodeType::Directory)
+        .unwrap();
+    session.app.git.select_tree(dir);
+    session.app.request_git_diff_update();
+    session.settle().await;
+    session.assert_shows("2 files changed, 2 insertions(+), 0 deletions(-)");
+
+    session.press(KeyCode::Tab).await;
+    session.press(KeyCode::Char('j')).await;
+    session.press(KeyCode::Enter).await;
+    let entry = session.app.git.selected_tree_entry().unwrap();
+    assert_eq!(entry.path, "src/b.rs");
+    session.assert_shows("+ later");
+}
+
+#[tokio::test]
+async fn test_bracket_f_jumps_between_files() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "a\n"), ("b.txt", "b\n")], "add a and b");
+    // Longer than the pane, so the diff can scroll
+    repo.write("a.txt", &"a2\n".repeat(60));
+    repo.write("b.txt", "b2\n");
+
+    let mut session = Session::start(repo).await;
+    session.app.current_tab = Tab::Git;
+    session.app.refresh_git_state();
+    session.settle().await;
+    let a = session
+        .app
+        .git
+        .flat_tree
+        .iter()
+        .position(|item| item.name == "a.txt");
+    session.app.git.select_tree(a.unwrap());
+    session.app.request_git_diff_update();
+    session.settle().await;
+    let path = |session: &Session| session.app.git.selected_tree_entry().unwrap().path.clone();
+
+    // The file's own header goes to the top first, then the next file
+    session.press(KeyCode::Char(']')).await;
+    assert_eq!(session.app.nav_keys.pending_bracket(), Some(']'));
+    session.press(KeyCode::Char('f')).await;
+    assert_eq!(path(&session), "a.txt");
+    assert!(session.app.git.diff_scroll_y > 0);
+    session.press(KeyCode::Char(']')).await;
+    session.press(KeyCode::Char('f')).await;
+    assert_eq!(path(&session), "b.txt");
+    session.press(KeyCode::Char('[')).await;
+    session.press(KeyCode::Char('f')).await;
+    assert_eq!(path(&session), "a.txt");
+
+    // Any other key lets the bracket narrow the tree first
+    let width = session.app.git_left_width;
+    session.press(KeyCode::Char('[')).await;
+    assert_eq!(session.app.git_left_width, width);
+    session.press(KeyCode::Char('j')).await;
+    assert_eq!(session.app.git_left_width, width - 2);
+    assert_eq!(path(&session), "b.txt");
+    assert_eq!(session.app.nav_keys.pending_bracket(), None);
+}
+
+#[tokio::test]
+async fn test_commit_from_the_drawer() {
+    let repo = TestRepo::new();
+    repo.write("new.txt", "hello\n");
+
+    let mut session = Session::start(repo).await;
+    session.press(KeyCode::Char(' ')).await;
+    session.press(KeyCode::Char('c')).await;
+    session.type_text("Add new.txt").await;
+    session.key(KeyCode::Enter, KeyModifiers::CONTROL).await;
+
+    assert_eq!(
+        session.repo.git(&["log", "-1", "--format=%s"]),
+        "Add new.txt\n"
+    );
+    assert_eq!(session.repo.git(&["status", "--porcelain"]), "");
+    session.assert_shows("Git (0)");
+}
+
+#[tokio::test]
+async fn test_recall_a_past_commit_message() {
+    let repo = TestRepo::new();
+    repo.write("a.txt", "a\n");
+    repo.write("b.txt", "b\n");
+
+    let mut session = Session::start(repo).await;
+    session.press(KeyCode::Char(' ')).await;
+    session.press(KeyCode::Char('c')).await;
+    session.type_text("Add a").await;
+    session.key(KeyCode::Enter, KeyModifiers::CONTROL).await;
+    assert_eq!(session.repo.git(&["log", "-1", "--format=%s"]), "Add a\n");
+
+    session.press(KeyCode::Char(' ')).await;
+    session.press(KeyCode::Char('c')).await;
+    session.key(KeyCode::Char('l'), KeyModifiers::CONTROL).await;
+    session.assert_shows("Recent Commit Messages");
+    session.click_text("Add a").await;
+    assert!(session.app.message_picker.is_none());
+    assert_eq!(session.app.commit.message, "Add a");
+    session.type_text(" and b").await;
+    session.key(KeyCode::Enter, KeyModifiers::CONTROL).await;
+    assert_eq!(
+        session.repo.git(&["log", "-1", "--format=%s"]),
+        "Add a and b\n"
+    );
+    let root = session.app.git.repo_root.clone().unwrap();
+    assert_eq!(
+        session.app.commit_messages.messages(&root),
+        ["Add a and b", "Add a"]
+    );
+}
+
+#[tokio::test]
+async fn test_start_from_a_commit_template() {
+    let repo = TestRepo::new();
+    repo.write(".gitmessage", "feat: \n\n# Say why\nRefs: \n");
+    repo.write("a.txt", "a\n");
+
+    let mut session = Session::start(repo).await;
+    session.press(KeyCode::Char(' ')).await;
+    session.press(KeyCode::Char('c')).await;
+    session.press(KeyCode::Up).await;
+    session.assert_shows("Commit Templates & Recent Messages");
+    session.assert_shows(".gitmessage");
+    session.press(KeyCode::Enter).await;
+    assert!(session.app.message_picker.is_none());
+    assert_eq!(session.app.commit.message, "feat: \n\nRefs:");
+
+    // With a message typed, Up no longer opens the list
+    session.press(KeyCode::Up).await;
+    assert!(session.app.message_picker.is_none());
+}
+
+#[tokio::test]
+async fn test_guided_conventional_commit() {
+    let repo = TestRepo::new();
+    repo.write("a.txt", "a\n");
+
+    let mut session = Session::start(repo).await;
+    session.press(KeyCode::Char(' ')).await;
+    session.press(KeyCode::Char('c')).await;
+    session.key(KeyCode::Char('t'), KeyModifiers::CONTROL).await;
+    assert_eq!(session.app.commit.message, "feat: ");
+    session.assert_shows("Breaking □");
+
+    session.press(KeyCode::Char('f')).await;
+    session.press(KeyCode::Char('!')).await;
+    session.press(KeyCode::Tab).await;
+    session.type_text("ui").await;
+    session.press(KeyCode::Tab).await;
+    session.type_text(&"x".repeat(70)).await;
+    session.assert_shows("first line is 80 characters, over 72");
+    for _ in 0..65 {
+        session.press(KeyCode::Backspace).await;
+    }
+    session.key(KeyCode::Enter, KeyModifiers::CONTROL).await;
+    assert_eq!(
+        session.repo.git(&["log", "-1", "--format=%s"]),
+        "fix(ui)!: xxxxx\n"
+    );
+    // The next commit starts from the same type and scope
+    assert_eq!(session.app.commit.message, "fix(ui): ");
+}
+
+#[tokio::test]
+async fn test_signing_failure_explains_itself() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "commit.gpgsign", "true"]);
+    repo.git(&["config", "gpg.program", "false"]);
+    repo.write("a.txt", "a\n");
+
+    let mut session = Session::start(repo).await;
+    session.press(KeyCode::Char(' ')).await;
+    session.press(KeyCode::Char('c')).await;
+    session.assert_shows("signed (GPG)");
+    session.type_text("signed").await;
+    session.key(KeyCode::Enter, KeyModifiers::CONTROL).await;
+    session.assert_shows("Commit signing failed");
+    session.assert_shows("gpg failed to sign the data");
+    assert!(session.app.commit.open);
+    assert_eq!(session.app.commit.message, "signed");
+}
+
+#[tokio::test]
+async fn test_lock_screen() {
+    let repo = TestRepo::new();
+    repo.write("secret.txt", "token\n");
+
+    let mut session = Session::start(repo).await;
+    session.assert_shows("secret.txt");
+    session.update(Msg::Command(CommandId::LockScreen)).await;
+    session.assert_shows("lzgit is locked");
+    assert!(!session.screen().contains("secret.txt"));
+    // The key that unlocks does nothing else
+    session.press(KeyCode::Char('c')).await;
+    session.assert_shows("secret.txt");
+    assert!(!session.app.commit.open);
+
+    session.app.privacy_config.lock_after = Some(1);
+    session.app.privacy_config.passphrase_env = Some("LZGIT_TEST_PASSPHRASE".to_string());
+    session.app.getenv = |var| (var == "LZGIT_TEST_PASSPHRASE").then(|| "sesame".to_string());
+    session.settle().await;
+    assert!(session.app.lock_screen.is_none());
+    session.app.last_input -= Duration::from_secs(60);
+    session.settle().await;
+    session.assert_shows("Type the passphrase and press Enter");
+
+    session.type_text("sesam").await;
+    session.assert_shows("Passphrase: •••••");
+    session.press(KeyCode::Enter).await;
+    session.assert_shows("Wrong passphrase");
+    session.type_text("sesame").await;
+    session.press(KeyCode::Enter).await;
+    session.assert_shows("secret.txt");
+}
+
+#[tokio::test]
+async fn test_pick_co_authors() {
+    let repo = TestRepo::new();
+    for author in ["Ada <ada@example.com>", "Bob <bob@example.com>"] {
+        let arg = format!("--author={}", author);
+        repo.git(&["commit", "-q", "--allow-empty", "-m", "pair", &arg]);
+    }
+    repo.write("a.txt", "a\n");
+
+    let mut session = Session::start(repo).await;
+    session.press(KeyCode::Char(' ')).await;
+    session.press(KeyCode::Char('c')).await;
+    session.type_text("Pair on a").await;
+    session.key(KeyCode::Char('k'), KeyModifiers::CONTROL).await;
+    session.assert_shows("Tab to tick");
+    session.type_text("bob").await;
+    session.press(KeyCode::Tab).await;
+    for _ in 0..3 {
+        session.press(KeyCode::Backspace).await;
+    }
+    session.type_text("ada").await;
+    session.press(KeyCode::Tab).await;
+    session.assert_shows("Co-authors (2 ticked)");
+    session.press(KeyCode::Enter).await;
+    assert!(!session.app.author_ui.open);
+    assert!(session.app.commit.open);
+    assert_eq!(
+        session.app.commit.message,
+        "Pair on a\n\nCo-authored-by: Bob <bob@example.com>\n\
+         Co-authored-by: Ada <ada@example.com>"
+    );
+
+    session.key(KeyCode::Enter, KeyModifiers::CONTROL).await;
+    assert_eq!(
+        session.repo.git(&[
+            "log",
+            "-1",
+            "--format=%(trailers:key=Co-authored-by,valueonly)"
+        ]),
+        "Bob <bob@example.com>\nAda <ada@example.com>\n\n"
+    );
+}
+
+#[tokio::test]
+async fn test_amend_warns_when_head_is_pushed() {
+    let repo = TestRepo::new();
+    let remote = TempDir::new().unwrap();
+    repo.git(&["init", "-q", "--bare", remote.path().to_str().unwrap()]);
+    repo.git(&["remote", "add", "origin", remote.path().to_str().unwrap()]);
+    repo.commit(&[("notes.txt", "one\n")], "Add notes");
+    repo.git(&["push", "-q", "-u", "origin", "main"]);
+
+    let mut session = Session::start(repo).await;
+    session.press(KeyCode::Char('c')).await;
+    session.key(KeyCode::Char('e'), KeyModifiers::CONTROL).await;
+    session.assert_shows("Add notes");
+    session.assert_shows("(already on origin/main)");
+    session.type_text(" for the team").await;
+    session.key(KeyCode::Enter, KeyModifiers::CONTROL).await;
+
+    assert_eq!(
+        session.repo.git(&["log", "--format=%s"]),
+        "Add notes for the team\ninitial\n"
+    );
+    assert!(!session.app.commit.amend);
+}
+
+#[tokio::test]
+async fn test_push_dialog_sets_upstream_and_forces_with_lease() {
+    let repo = TestRepo::new();
+    let remote = TempDir::new().unwrap();
+    repo.git(&["init", "-q", "--bare", remote.path().to_str().unwrap()]);
+    repo.git(&["remote", "add", "origin", remote.path().to_str().unwrap()]);
+
+    let mut session = Session::start(repo).await;
+    session.click_text("[Push]").await;
+    session.assert_shows("Remote  ◂ origin ▸");
+    session.assert_shows("▣ Set as upstream (-u)");
+    session.assert_shows("git push -u origin main");
+    session.press(KeyCode::Enter).await;
+    assert_eq!(
+        session.repo.git(&["rev-parse", "--abbrev-ref", "main@{u}"]),
+        "origin/main\n"
+    );
+
+    session.assert_shows("Success");
+    session.press(KeyCode::Esc).await;
+    session
+        .repo
+        .git(&["commit", "-q", "--amend", "--allow-empty", "-m", "reworded"]);
+    session.click_text("[Push]").await;
+    session.assert_shows("□ Set as upstream (-u)");
+    session.click_text("□ Force, unless the remote moved").await;
+    session.assert_shows("git push --force-with-lease origin main");
+    session.press(KeyCode::Enter).await;
+    assert_eq!(
+        session.repo.git(&["rev-parse", "origin/main"]),
+        session.repo.git(&["rev-parse", "HEAD"])
+    );
+}
+
+#[tokio::test]
+async fn test_pull_options_are_remembered() {
+    let repo = TestRepo::new();
+    let remote = TempDir::new().unwrap();
+    repo.git(&["init", "-q", "--bare", remote.path().to_str().unwrap()]);
+    repo.git(&["remote", "add", "origin", remote.path().to_str().unwrap()]);
+    repo.git(&["push", "-q", "-u", "origin", "main"]);
+
+    let mut session = Session::start(repo).await;
+    session.click_text("[Pull]").await;
+    session.assert_shows("Bring in the remote commits by:");
+    session.press(KeyCode::Char('a')).await;
+    session.assert_shows("▣ Stash local changes around it (--autostash)");
+    session.press(KeyCode::Char('f')).await;
+    session.assert_shows("git pull --ff-only --autostash");
+
+    session.press(KeyCode::Esc).await;
+    session.click_text("[Pull]").await;
+    let options = session.app.pull_picker.unwrap();
+    assert_eq!(options.strategy, git_ops::PullStrategy::FfOnly);
+    assert!(options.autostash);
+}
+
+#[tokio::test]
+async fn test_stash_dialog_with_message_and_untracked() {
+    let repo = TestRepo::new();
+    repo.commit(&[("notes.txt", "one\n")], "add notes");
+    repo.write("notes.txt", "one\ntwo\n");
+    repo.write("scratch.txt", "tmp\n");
+
+    let mut session = Session::start(repo).await;
+    session.press(KeyCode::Char('z')).await;
+    session.assert_shows(" Stash Changes ");
+    session.type_text("wip notes").await;
+    session.click_text("□ Include untracked files").await;
+    session.assert_shows("▣ Include untracked files");
+    session.press(KeyCode::Enter).await;
+
+    assert_eq!(
+        session.repo.git(&["stash", "list", "--format=%s"]),
+        "On main: wip notes\n"
+    );
+    assert_eq!(session.repo.git(&["status", "--porcelain"]), "");
+    session.assert_shows("Git (0)");
+}
+
+#[tokio::test]
+async fn test_cut_release_with_changelog() {
+    let repo = TestRepo::new();
+    repo.git(&["tag", "v1.2.0"]);
+    repo.commit(&[("a.txt", "a\n")], "feat(ui): release wizard");
+    repo.commit(&[("b.txt", "b\n")], "fix: empty tag list");
+
+    let mut session = Session::start(repo).await;
+    session.update(Msg::Command(CommandId::CutRelease)).await;
+    session.assert_shows(" Cut Release ");
+    session.assert_shows("2 commits since v1.2.0");
+    session.assert_shows("Minor  v1.3.0  (suggested)");
+    session.press(KeyCode::Enter).await;
+    session.assert_shows("- ui: release wizard");
+    session.press(KeyCode::Enter).await;
+    session.assert_shows("No remote to push to");
+    session.press(KeyCode::Enter).await;
+
+    let short = |rev: &str| session.repo.git(&["log", "-1", "--format=%h", rev]);
+    let notes = format!(
+        "Release v1.3.0\n\nFeatures:\n- ui: release wizard ({})\n\nBug Fixes:\n\
+         - empty tag list ({})\n\n",
+        short("HEAD~1").trim(),
+        short("HEAD").trim()
+    );
+    assert_eq!(
+        session
+            .repo
+            .git(&["tag", "-l", "--format=%(contents)", "v1.3.0"]),
+        notes
+    );
+    assert!(session.app.release_wizard.is_none());
+}
+
+#[tokio::test]
+async fn test_hard_reset_from_history_asks_first() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "a\n")], "add a");
+    repo.commit(&[("b.txt", "b\n")], "add b");
+    repo.write("a.txt", "edited\n");
+
+    let mut session = Session::start(repo).await;
+    session.app.current_tab = Tab::Log;
+    session.app.set_log_subtab(LogSubTab::History);
+    session.app.refresh_log_data();
+    session.settle().await;
+    session.press(KeyCode::Down).await;
+    session.press(KeyCode::Char('X')).await;
+    session.assert_shows(" Reset To Commit ");
+    session.press(KeyCode::Char('h')).await;
+    session.assert_shows(" Hard Reset ");
+    assert!(session.app.reset_picker.is_none());
+    session.press(KeyCode::Enter).await;
+
+    assert_eq!(
+        session.repo.git(&["log", "--format=%s"]),
+        "add a\ninitial\n"
+    );
+    assert_eq!(session.repo.git(&["status", "--porcelain"]), "");
+    assert_eq!(session.repo.read("a.txt"), "a\n");
+}
+
+#[tokio::test]
+async fn test_keys_from_config_rebind_the_git_tab() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "a\n")], "add a");
+    fs::write(repo.path().join("a.txt"), "changed\n").unwrap();
+
+    let mut session = Session::start(repo).await;
+    let cfg = config::parse("[keys.git]\nstage_all = \"ctrl+g\"\npull = \"A\"\n").unwrap();
+    session.app.keymap = cfg.keymap;
+    session.app.current_tab = Tab::Git;
+    session.settle().await;
+
+    session.press(KeyCode::Char('A')).await;
+    session.assert_shows(" Pull ");
+    session.press(KeyCode::Esc).await;
+    assert_eq!(session.repo.git(&["diff", "--cached", "--name-only"]), "");
+
+    session.key(KeyCode::Char('g'), KeyModifiers::CONTROL).await;
+    assert_eq!(
+        session.repo.git(&["diff", "--cached", "--name-only"]),
+        "a.txt\n"
+    );
+
+    session.press(KeyCode::Char('?')).await;
+    session.assert_shows("Key Bindings");
+    session.assert_shows("ctrl+g");
+    session.assert_shows("Stage everything shown");
+    session.press(KeyCode::Esc).await;
+    assert!(session.app.key_help.is_none());
+
+    let cfg =
+        config::parse("[keys.global]\ntheme_picker = \"ctrl+y\"\nquit = \"ctrl+q\"\n").unwrap();
+    session.app.keymap = cfg.keymap;
+    session.key(KeyCode::Char('y'), KeyModifiers::CONTROL).await;
+    assert!(session.app.theme_picker.open);
+    session.press(KeyCode::Esc).await;
+    session.key(KeyCode::Char('q'), KeyModifiers::CONTROL).await;
+    assert!(session.app.should_quit);
+}
+
+#[test]
+fn test_legacy_config_is_copied_once() {
+    let tmp = TempDir::new().unwrap();
+    let legacy = tmp.path().join("te");
+    let dir = tmp.path().join("lzgit");
+    fs::create_dir_all(&legacy).unwrap();
+    fs::write(legacy.join("bookmarks.tsv"), "w\t/work\n").unwrap();
+    fs::write(legacy.join("ui.json"), "{}").unwrap();
+    fs::write(legacy.join("other.json"), "{}").unwrap();
+
+    assert!(super::migrate_config_dir(&legacy, &dir).unwrap());
+    assert_eq!(
+        fs::read_to_string(dir.join("bookmarks.tsv")).unwrap(),
+        "w\t/work\n"
+    );
+    assert!(dir.join("ui.json").is_file());
+    assert!(!dir.join("other.json").exists());
+    // te/ belongs to another app too, so the originals stay.
+    assert!(legacy.join("bookmarks.tsv").is_file());
+
+    fs::write(legacy.join("ui.json"), r#"{"theme":"nord"}"#).unwrap();
+    assert!(!super::migrate_config_dir(&legacy, &dir).unwrap());
+    assert_eq!(fs::read_to_string(dir.join("ui.json")).unwrap(), "{}");
+}
+
+#[test]
+fn test_legacy_config_leaves_an_existing_config_alone() {
+    let tmp = TempDir::new().unwrap();
+    let legacy = tmp.path().join("te");
+    let dir = tmp.path().join("lzgit");
+    fs::create_dir_all(&legacy).unwrap();
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(legacy.join("ui.json"), "{}").unwrap();
+    fs::write(dir.join("config.toml"), "[ui]\ntheme = \"nord\"\n").unwrap();
+
+    assert!(!super::migrate_config_dir(&legacy, &dir).unwrap());
+    assert_eq!(
+        fs::read_to_string(dir.join("config.toml")).unwrap(),
+        "[ui]\ntheme = \"nord\"\n"
+    );
+    assert!(!dir.join("ui.json").exists());
+    assert!(
+        !super::migrate_config_dir(&tmp.path().join("missing"), &tmp.path().join("new")).unwrap()
+    );
+    assert!(!tmp.path().join("new").exists());
+}
+
+#[tokio::test]
+async fn test_reload_takes_actions_and_hooks_from_config_toml() {
+    let mut session = Session::start(TestRepo::new()).await;
+    let dir = session.repo.path().join(".git/lzgit-config");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("config.toml"),
+        "[[actions]]\ncontext = \"commit\"\nlabel = \"Show\"\ntemplate = \"git show {sha}\"\n\n\
+         [hooks]\nbefore_commit = \"true\"\n\n[panes]\ntmux = \"tmux split-window {command}\"\n",
+    )
+    .unwrap();
+    session.app.reload_config();
+    assert_eq!(session.app.external_actions.len(), 1);
+    assert_eq!(session.app.external_actions[0].label, "Show");
+    assert_ne!(session.app.hooks, hooks::Hooks::default());
+    assert!(session.app.pane_templates.tmux.is_some());
+
+    // Dropping them from the file drops them from the app.
+    fs::write(dir.join("config.toml"), "[ui]\n").unwrap();
+    session.app.reload_config();
+    assert!(session.app.external_actions.is_empty());
+    assert_eq!(session.app.hooks, hooks::Hooks::default());
+    assert_eq!(
+        session.app.pane_templates,
+        multiplexer::PaneTemplates::default()
+    );
+}
+
+#[tokio::test]
+async fn test_clock_runs_in_process_without_the_shell() {
+    let mut session = Session::start(TestRepo::new()).await;
+    let utc = time::UtcOffset::UTC;
+    assert_eq!(super::hh_mm(13 * 3600 + 7 * 60, utc), "13:07");
+    assert_eq!(super::hh_mm(-60, utc), "23:59");
+    let india = time::UtcOffset::from_hms(5, 30, 0).unwrap();
+    assert_eq!(super::hh_mm(20 * 3600, india), "01:30");
+
+    session.app.features.shell = false;
+    session.app.top_bar.clock = true;
+    session.settle().await;
+    let (_, clock) = session.app.clock.clone().unwrap();
+    assert_eq!(clock.len(), 5);
+    session.assert_shows(&clock);
+}
+
+#[tokio::test]
+async fn test_key_help_filters_the_cheatsheet() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "a\n")], "add a");
+
+    let mut session = Session::start(repo).await;
+    session.app.current_tab = Tab::Git;
+    session.settle().await;
+
+    session.press(KeyCode::Char('?')).await;
+    session.assert_shows("Stage everything shown");
+    session.assert_shows("Commit drawer");
+
+    session.type_text("blame").await;
+    session.assert_shows("Filter: blame_");
+    session.assert_shows("Copy the hash");
+    assert!(session.find("Stage everything shown").is_none());
+
+    session.type_text(" xyzzy").await;
+    session.assert_shows("No keys match");
+    session.press(KeyCode::Enter).await;
+    assert!(session.app.key_help.is_none());
+}
+
+#[tokio::test]
+async fn test_highlight_this_file_as_another_language() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "a\n")], "add a");
+    repo.write("deploy", "#!/usr/bin/env bash\necho hi\n");
+
+    let mut session = Session::start(repo).await;
+    session.app.current_tab = Tab::Git;
+    session.settle().await;
+    let file = session
+        .app
+        .git
+        .flat_tree
+        .iter()
+        .position(|item| item.node_type == git::FlatNodeType::File)
+        .unwrap();
+    session.app.git.select_tree(file);
+    session.app.request_git_diff_update();
+    session.settle().await;
+    session.assert_shows("echo hi");
+    assert_eq!(
+        session
+            .app
+            .syntax_for("deploy", Some("#!/usr/bin/env bash")),
+        Some("sh")
+    );
+
+    session.update(Msg::Command(CommandId::HighlightAs)).await;
+    session.assert_shows("Highlight deploy As");
+    session.click_text("Python").await;
+    assert!(session.app.syntax_picker.is_none());
+    assert_eq!(
+        session.app.syntax_for("deploy", Some("#!/bin/sh")),
+        Some("py")
+    );
+
+    session.update(Msg::Command(CommandId::HighlightAs)).await;
+    session.press(KeyCode::Home).await;
+    session.press(KeyCode::Char('j')).await;
+    session.press(KeyCode::Enter).await;
+    assert_eq!(session.app.syntax_for("deploy", Some("#!/bin/sh")), None);
+}
+
+#[tokio::test]
+async fn test_find_file_opens_its_diff_or_the_explorer() {
+    let repo = TestRepo::new();
+    repo.commit(
+        &[
+            ("src/app/main.rs", "fn main() {}\n"),
+            ("src/lib.rs", "pub fn lib() {}\n"),
+            ("docs/manual.md", "# Manual\n"),
+        ],
+        "add files",
+    );
+    repo.write("src/lib.rs", "pub fn lib() -> u8 { 1 }\n");
+
+    let mut session = Session::start(repo).await;
+    session.app.current_tab = Tab::Log;
+    session.settle().await;
+
+    session.key(KeyCode::Char('o'), KeyModifiers::CONTROL).await;
+    session.assert_shows("Find File");
+    session.assert_shows("3 of 3");
+    session.type_text("slib").await;
+    session.assert_shows("1 of 3");
+    session.press(KeyCode::Enter).await;
+    assert!(session.app.file_finder.is_none());
+    assert_eq!(session.app.current_tab, Tab::Git);
+    assert_eq!(
+        session.app.git.selected_tree_entry().unwrap().path,
+        "src/lib.rs"
+    );
+
+    session.key(KeyCode::Char('o'), KeyModifiers::CONTROL).await;
+    session.type_text("manual").await;
+    session.press(KeyCode::Enter).await;
+    assert_eq!(session.app.current_tab, Tab::Explorer);
+    assert_eq!(session.app.selected_file().unwrap().name, "manual.md");
+}
+
+#[tokio::test]
+async fn test_find_in_commit_jumps_the_diff() {
+    let repo = TestRepo::new();
+    let long: String = (1..=80).map(|i| format!("line {}\n", i)).collect();
+    repo.commit(
+        &[("a.txt", &long), ("b.txt", "x\nthe Needle\n")],
+        "add files",
+    );
+
+    let mut session = Session::start(repo).await;
+    session.app.current_tab = Tab::Log;
+    session.app.set_log_subtab(LogSubTab::History);
+    session.app.refresh_log_data();
+    session.settle().await;
+    assert_eq!(session.app.log_ui.diff_scroll_y, 0);
+
+    session.key(KeyCode::Char('f'), KeyModifiers::CONTROL).await;
+    session.assert_shows("Find in This Commit");
+    session.type_text("needle").await;
+    session.assert_shows("b.txt:2");
+    session.assert_shows("1 match");
+    session.press(KeyCode::Enter).await;
+
+    assert!(session.app.commit_find.is_none());
+    assert!(session.app.log_ui.diff_scroll_y > 60);
+    session.assert_shows("+the Needle");
+}
+
+#[tokio::test]
+async fn test_checkout_commit_detaches_and_offers_a_branch() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "a\n")], "add a");
+    repo.commit(&[("b.txt", "b\n")], "add b");
+
+    let mut session = Session::start(repo).await;
+    session.app.current_tab = Tab::Log;
+    session.app.set_log_subtab(LogSubTab::History);
+    session.app.refresh_log_data();
+    session.settle().await;
+    session.press(KeyCode::Down).await;
+    session.press(KeyCode::Char('O')).await;
+    assert_eq!(session.repo.git(&["branch", "--show-current"]), "");
+
+    session.app.current_tab = Tab::Git;
+    session.settle().await;
+    let short = session.repo.git(&["rev-parse", "--short", "HEAD"]);
+    session.assert_shows(&format!("HEAD detached at {}", short.trim()));
+    session.click_text("[+ Branch Here]").await;
+    session.type_text("rescue").await;
+    session.press(KeyCode::Enter).await;
+
+    assert_eq!(session.repo.git(&["branch", "--show-current"]), "rescue\n");
+    assert_eq!(session.repo.git(&["log", "-1", "--format=%s"]), "add a\n");
+    assert!(session.app.git.detached.is_none());
+}
+
+#[tokio::test]
+async fn test_checkout_over_local_changes_stashes_and_restores_them() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "1\n2\n3\n4\n5\n")], "add a");
+    repo.commit(&[("a.txt", "one\n2\n3\n4\n5\n")], "edit a");
+    std::fs::write(repo.path().join("a.txt"), "one\n2\n3\n4\nfive\n").unwrap();
+
+    let mut session = Session::start(repo).await;
+    session.app.current_tab = Tab::Log;
+    session.app.set_log_subtab(LogSubTab::History);
+    session.app.refresh_log_data();
+    session.settle().await;
+    session.press(KeyCode::Down).await;
+    session.press(KeyCode::Char('O')).await;
+    if session.app.confirm.is_some() {
+        session.press(KeyCode::Char('y')).await;
+    }
+    session.assert_shows("Local Changes In The Way");
+    session.press(KeyCode::Char('y')).await;
+
+    assert_eq!(session.repo.git(&["log", "-1", "--format=%s"]), "add a\n");
+    let a = std::fs::read_to_string(session.repo.path().join("a.txt")).unwrap();
+    assert_eq!(a, "1\n2\n3\n4\nfive\n");
+    assert_eq!(session.repo.git(&["stash", "list"]), "");
+}
+
+#[tokio::test]
+async fn test_branch_from_history_commit_and_check_it_out() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "a\n")], "add a");
+    repo.commit(&[("b.txt", "b\n")], "add b");
+
+    let mut session = Session::start(repo).await;
+    session.app.current_tab = Tab::Log;
+    session.app.set_log_subtab(LogSubTab::History);
+    session.app.refresh_log_data();
+    session.settle().await;
+    session.press(KeyCode::Down).await;
+    session.app.new_branch_at_selected_commit();
+    session.settle().await;
+    session.assert_shows("□ Check it out");
+    session.click_text("□ Check it out").await;
+    session.assert_shows("▣ Check it out");
+    session.type_text("fix-a").await;
+    session.press(KeyCode::Enter).await;
+
+    assert_eq!(session.repo.git(&["branch", "--show-current"]), "fix-a\n");
+    assert_eq!(session.repo.git(&["log", "-1", "--format=%s"]), "add a\n");
+    assert_eq!(session.app.git.branch, "fix-a");
+}
+
+#[tokio::test]
+async fn test_changelog_between_refs_links_to_the_forge() {
+    let repo = TestRepo::new();
+    repo.git(&["tag", "v1.0.0"]);
+    repo.git(&["remote", "add", "origin", "git@github.com:me/tool.git"]);
+    repo.commit(&[("a.txt", "a\n")], "feat(ui): changelog popup");
+
+    let mut session = Session::start(repo).await;
+    session
+        .update(Msg::Command(CommandId::GenerateChangelog))
+        .await;
+    session.assert_shows("v1.0.0..HEAD_");
+    session.press(KeyCode::Enter).await;
+    session.assert_shows(" Changelog v1.0.0..HEAD ");
+    session.assert_shows("### Features");
+    session.assert_shows("https://github.com/me/tool/compare/v1.0.0...HEAD");
+
+    session.press(KeyCode::Char('y')).await;
+    let copied = session.app.pending_clipboard.clone().unwrap();
+    assert!(copied.contains("- **ui:** changelog popup (["));
+    session.click_text(" Export (e) ").await;
+    session.press(KeyCode::Enter).await;
+    assert_eq!(session.repo.read("lzgit-changelog.md"), copied);
+}
+
+#[tokio::test]
+async fn test_history_shows_ci_status_from_the_forge() {
+    let repo = TestRepo::new();
+    repo.git(&[
+        "remote",
+        "add",
+        "origin",
+        "https://git.example.com/me/tool.git",
+    ]);
+    repo.commit(&[("a.txt", "a\n")], "feat: green build");
+    let head = repo.git(&["rev-parse", "HEAD"]).trim().to_string();
+
+    // A forge that passes HEAD and fails everything else
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let api = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let served = requests.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            served.fetch_add(1, AtomicOrdering::SeqCst);
+            let state = if line.contains(&head) {
+                "success"
+            } else {
+                "failure"
+            };
+            let body = format!("{{\"state\":\"{}\",\"total_count\":1}}", state);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+        }
+    });
+
+    let mut session = Session::start(repo).await;
+    session.app.forge_config = config::ForgeConfig {
+        provider: Some(forge::ForgeKind::Gitea),
+        api_url: Some(api),
+        token_env: None,
+    };
+    session.app.current_tab = Tab::Log;
+    session.app.set_log_subtab(LogSubTab::History);
+    session.app.refresh_log_data();
+    session.settle().await;
+    session.assert_shows("✓ feat: green build");
+    session.assert_shows("✗ initial");
+
+    // Finished builds are cached
+    session.app.refresh_log_data();
+    session.settle().await;
+    assert_eq!(requests.load(AtomicOrdering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_reflog_chips_filter_by_operation() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "a\n")], "first");
+    repo.git(&["checkout", "-q", "-b", "topic"]);
+    repo.commit(&[("a.txt", "b\n")], "second");
+    repo.git(&["commit", "-q", "--amend", "-m", "second, amended"]);
+    repo.git(&["checkout", "-q", "main"]);
+    repo.git(&["cherry-pick", "topic"]);
+    repo.git(&["reset", "-q", "--hard", "HEAD~1"]);
+
+    let mut session = Session::start(repo).await;
+    session.app.current_tab = Tab::Log;
+    session.app.set_log_subtab(LogSubTab::Reflog);
+    session.app.refresh_log_data();
+    session.settle().await;
+    let shown = |app: &App| -> Vec<String> {
+        let ui = &app.log_ui;
+        ui.reflog_filtered
+            .iter()
+            .map(|&i| ui.reflog[i].subject.clone())
+            .collect()
+    };
+    assert_eq!(shown(&session.app).len(), session.app.log_ui.reflog.len());
+
+    session
+        .update(Msg::Action(AppAction::ToggleReflogOp(
+            git_ops::ReflogOp::Reset,
+        )))
+        .await;
+    assert_eq!(shown(&session.app), ["reset: moving to HEAD~1"]);
+
+    session
+        .update(Msg::Action(AppAction::ToggleReflogOp(
+            git_ops::ReflogOp::Checkout,
+        )))
+        .await;
+    assert_eq!(
+        shown(&session.app),
+        [
+            "reset: moving to HEAD~1",
+            "checkout: moving from topic to main",
+            "checkout: moving from main to topic",
+        ]
+    );
+
+    session
+        .update(Msg::Action(AppAction::ToggleReflogOp(
+            git_ops::ReflogOp::Reset,
+        )))
+        .await;
+    session
+        .update(Msg::Action(AppAction::ToggleReflogOp(
+            git_ops::ReflogOp::Checkout,
+        )))
+        .await;
+    session
+        .update(Msg::Action(AppAction::ToggleReflogOp(
+            git_ops::ReflogOp::Commit,
+        )))
+        .await;
+    assert_eq!(
+        shown(&session.app),
+        [
+            "cherry-pick: second, amended",
+            "commit (amend): second, amended",
+            "commit: second",
+            "commit: first",
+            "commit (initial): initial",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_resolve_merge_conflict_with_ours() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "base\n")], "base");
+    repo.git(&["checkout", "-q", "-b", "other"]);
+    repo.commit(&[("a.txt", "theirs\n")], "theirs");
+    repo.git(&["checkout", "-q", "main"]);
+    repo.commit(&[("a.txt", "ours\n")], "ours");
+    assert!(!repo.try_git(&["merge", "-q", "other"]).0);
+
+    let mut session = Session::start(repo).await;
+    session.assert_shows("MERGE");
+    session.assert_shows(" Ours (o) ");
+
+    session.press(KeyCode::Char('o')).await;
+    assert_eq!(session.repo.read("a.txt"), "ours\n");
+    session.press(KeyCode::Char('a')).await;
+    assert_eq!(
+        session
+            .repo
+            .git(&["diff", "--name-only", "--diff-filter=U"]),
+        ""
+    );
+
+    session.click_text("[Continue]").await;
+    assert!(
+        !session
+            .repo
+            .try_git(&["rev-parse", "-q", "--verify", "MERGE_HEAD"])
+            .0
+    );
+    assert_eq!(
+        session
+            .repo
+            .git(&["log", "-1", "--format=%p"])
+            .split(' ')
+            .count(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_hint_bar_follows_the_focus() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "base\n")], "base");
+    repo.git(&["checkout", "-q", "-b", "other"]);
+    repo.commit(&[("a.txt", "theirs\n")], "theirs");
+    repo.git(&["checkout", "-q", "main"]);
+    repo.commit(&[("a.txt", "ours\n")], "ours");
+    assert!(!repo.try_git(&["merge", "-q", "other"]).0);
+
+    let mut session = Session::start(repo).await;
+    session.assert_shows("n p conflicts  o ours  t theirs  b both  a resolved");
+
+    session.press(KeyCode::Char('a')).await;
+    session.assert_shows("space stage  c commit  v select lines");
+
+    session.press(KeyCode::Char('c')).await;
+    session.assert_shows("ctrl+enter commit  ctrl+g generate");
+    session.press(KeyCode::Esc).await;
+
+    session.press(KeyCode::Char('3')).await;
+    session.assert_shows("h l parent, open  e edit  a new");
+}
+
+#[tokio::test]
+async fn test_continue_rebase_from_banner() {
+    let repo = TestRepo::new();
+    repo.commit(&[("a.txt", "base\n")], "base");
+    repo.git(&["checkout", "-q", "-b", "topic"]);
+    repo.commit(&[("a.txt", "topic\n")], "topic change");
+    repo.git(&["checkout", "-q", "main"]);
+    repo.commit(&[("a.txt", "main\n")], "main change");
+    repo.git(&["checkout", "-q", "topic"]);
+    assert!(!repo.try_git(&["rebase", "-q", "main"]).0);
+    repo.write("a.txt", "main\ntopic\n");
+    repo.git(&["add", "a.txt"]);
+
+    let mut session = Session::start(repo).await;
+    session.assert_shows("REBASE");
+
+    session.click_text("[Continue]").await;
+    assert!(!session.repo.path().join(".git/rebase-merge").exists());
+    assert_eq!(
+        session.repo.git(&["log", "--format=%s", "-3"]),
+        "topic change\nmain change\nbase\n"
+    );
+    assert_eq!(session.repo.read("a.txt"), "main\ntopic\n");
+}
+
+#[tokio::test]
+async fn test_keys_and_clicks_share_actions() {
+    let repo = TestRepo::new();
+    repo.git(&["tag", "v1"]);
+    repo.git(&["tag", "v2"]);
+
+    let mut session = Session::start(repo).await;
+    session.update(Msg::Command(CommandId::OpenTagPicker)).await;
+    session.assert_shows(" Tags (2) ");
+
+    // `x` and the Delete button both send TagDelete, which asks above the picker
+    session.press(KeyCode::Char('x')).await;
+    session.assert_shows(" Delete Tag ");
+    session.press(KeyCode::Char('n')).await;
+    session.update(Msg::Action(AppAction::TagDelete)).await;
+    session.assert_shows(" Delete Tag ");
+    session.press(KeyCode::Char('y')).await;
+    assert_eq!(session.repo.git(&["tag", "--list"]).lines().count(), 1);
+    session.assert_shows(" Tags (1) ");
+}
+
+#[tokio::test]
+async fn test_stale_lock_is_kept_while_a_job_runs() {
+    let repo = TestRepo::new();
+    repo.write(".git/index.lock", "");
+
+    let mut session = Session::start(repo).await;
+    session.assert_shows("index.lock exists");
+
+    // Any job of ours may be the git process holding the lock
+    let (hold, rx) = mpsc::channel();
+    let lost = JobResult::Ai {
+        result: Err("AI job disconnected".to_string()),
+    };
+    session.app.ai_job = Some(PendingJob { rx, lost });
+    session
+        .app
+        .update(Msg::Command(CommandId::RemoveStaleLocks));
+    assert!(session.repo.path().join(".git/index.lock").exists());
+    session.app.ai_job = None;
+    drop(hold);
+
+    session
+        .update(Msg::Command(CommandId::RemoveStaleLocks))
+        .await;
+    assert!(!session.repo.path().join(".git/index.lock").exists());
+    assert!(!session.screen().contains("index.lock exists"));
+}
+
+#[tokio::test]
+async fn test_lost_jobs_report_as_the_kind_that_ran() {
+    let mut session = Session::start(TestRepo::new()).await;
+    session.app.log_ui.searched_query = Some("fix".to_string());
+    let (tx, rx) = mpsc::channel();
+    let lost = JobResult::LogHistory {
+        limit: 999,
+        result: Err("Log job disconnected".to_string()),
+    };
+    session.app.log_job = Some(PendingJob { rx, lost });
+    drop(tx);
+    session.settle().await;
+    assert!(session.app.log_job.is_none());
+    assert_eq!(session.app.log_ui.history_limit, 999);
+    assert_eq!(session.app.log_ui.searched_query, None);
+    assert_eq!(
+        session.app.log_ui.status.as_deref(),
+        Some("Log job disconnected")
+    );
+
+    // A commit whose worker died must still release the commit form
+    session.app.commit.busy = true;
+    let (tx, rx) = mpsc::channel();
+    let lost = JobResult::Git {
+        cmd: "git commit".to_string(),
+        result: Err("Background job disconnected".to_string()),
+        refresh: false,
+        close_commit: true,
+        elapsed: Duration::ZERO,
+    };
+    session.app.pending_job = Some(PendingJob { rx, lost });
+    drop(tx);
+    session.settle().await;
+    assert!(session.app.pending_job.is_none());
+    assert!(!session.app.commit.busy);
+}
+
+#[tokio::test]
+async fn test_bookmark_quick_keys_through_update() {
+    let repo = TestRepo::new();
+    let mut session = Session::start(repo).await;
+    let app = &mut session.app;
+    app.bookmarks_path = Some(session.repo.path().join(".git/bookmarks.tsv"));
+    app.bookmarks = vec![
+        Bookmark::new("one", session.repo.path()),
+        Bookmark::new("two", session.repo.path().join(".git")),
+    ];
+
+    app.update(Msg::Action(AppAction::OpenBookmarks));
+    assert!(app.bookmarks_ui.open);
+    app.update(Msg::Action(AppAction::BookmarkAssignKey('3')));
+    assert_eq!(app.bookmarks[0].key, Some('3'));
+
+    // A digit moves to the bookmark that takes it, and pressing it again clears it
+    app.update(Msg::Action(AppAction::SelectBookmark(1)));
+    app.update(Msg::Event(Event::Key(KeyEvent::new(
+        KeyCode::Char('3'),
+        KeyModifiers::NONE,
+    ))));
+    assert_eq!(app.bookmarks[0].key, None);
+    assert_eq!(app.bookmarks[1].key, Some('3'));
+    app.update(Msg::Action(AppAction::BookmarkAssignKey('3')));
+    assert_eq!(app.bookmarks[1].key, None);
+    assert_eq!(
+        app.bookmarks_ui.status.as_deref(),
+        Some("Quick key cleared")
+    );
+}
+
+#[tokio::test]
+async fn test_lock_screen_keeps_input_from_the_app() {
+    let repo = TestRepo::new();
+    let mut session = Session::start(repo).await;
+    let app = &mut session.app;
+    app.update(Msg::Command(CommandId::LockScreen));
+    let locked_at = app.last_input;
+
+    app.update(Msg::Event(Event::Paste("c".to_string())));
+    app.update(Msg::Event(Event::Mouse(MouseEvent {
+        kind: MouseEventKind::Down(MouseButton::Left),
+        column: 1,
+        row: 1,
+        modifiers: KeyModifiers::NONE,
+    })));
+    assert!(app.lock_screen.is_some());
+    assert_eq!(app.last_input, locked_at);
+
+    app.update(Msg::Event(Event::Key(KeyEvent::new(
+        KeyCode::Char('c'),
+        KeyModifiers::NONE,
+    ))));
+    assert!(app.lock_screen.is_none());
+    assert!(!app.commit.open);
+}
+
+#[tokio::test]
+async fn test_render_snapshots_match_golden_files() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots");
+    let update = env::var_os("LZGIT_UPDATE_SNAPSHOTS").is_some();
+    for scenario in snapshot::Scenario::ALL {
+        let screen = snapshot::plain_text(
+            &snapshot::render_under(Path::new("/tmp"), scenario)
+                .await
+                .unwrap(),
+        );
+        let path = dir.join(format!("{}.txt", scenario.name()));
+        if update {
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(&path, &screen).unwrap();
+            continue;
+        }
+        let golden = fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            screen == golden,
+            "{} differs from {}; rerun with LZGIT_UPDATE_SNAPSHOTS=1 if the change is \
+             intended:\n{}",
+            scenario.name(),
+            path.display(),
+            screen
+        );
+    }
+}
@@ -0,0 +1,72 @@
+//! The single way into the app's state
+//!
+//! Terminal input, click zones, palette commands, control socket requests and
+//! background results all arrive as a [`Msg`] and go through [`App::update`], so the
+//! main loop, the `--render-snapshot` runner and the ui tests drive the app the same
+//! way. Anything that can be clicked is an [`AppAction`]. A key that does what a click,
+//! a palette entry or a bound key does sends that same action or [`CommandId`] back
+//! through here; the key handlers only type into fields, move selections and scroll
+//! on their own.
+
+use std::time::Instant;
+
+use crate::{control, git_diff_loader, preview_loader};
+use crossterm::event::{Event, KeyEventKind, KeyModifiers};
+
+use crate::app::{App, AppAction, CommandId, handle_event};
+
+pub enum Msg {
+    /// Key, mouse, paste or resize from the terminal
+    Event(Event),
+    /// What a click zone or a key does, as if clicked without modifiers
+    Action(AppAction),
+    /// A command palette entry
+    Command(CommandId),
+    /// A request from the control socket
+    Control(control::ControlRequest),
+    Preview(preview_loader::PreviewResult),
+    GitDiff(git_diff_loader::GitDiffResult),
+    /// Once per frame: poll background jobs, timers and the clock
+    Tick,
+}
+
+impl From<AppAction> for Msg {
+    fn from(action: AppAction) -> Self {
+        Msg::Action(action)
+    }
+}
+
+impl From<CommandId> for Msg {
+    fn from(id: CommandId) -> Self {
+        Msg::Command(id)
+    }
+}
+
+impl App {
+    pub fn update(&mut self, msg: Msg) {
+        match msg {
+            Msg::Event(Event::Key(key)) if self.lock_screen.is_some() => {
+                if key.kind == KeyEventKind::Press
+                    && self.lock_screen.as_mut().is_some_and(|lock| lock.key(&key))
+                {
+                    self.lock_screen = None;
+                    self.last_input = Instant::now();
+                }
+            }
+            // Clicks and pastes don't reach what the lock screen hides
+            Msg::Event(Event::Mouse(_) | Event::Paste(_)) if self.lock_screen.is_some() => {}
+            Msg::Event(event) => {
+                if matches!(event, Event::Key(_) | Event::Mouse(_) | Event::Paste(_)) {
+                    self.last_input = Instant::now();
+                }
+                handle_event(self, event)
+            }
+            Msg::Action(action) => self.dispatch(action, 0, 0, KeyModifiers::NONE),
+            Msg::Command(id) => self.run_command(id),
+            Msg::Control(req) => self.handle_control_request(req),
+            Msg::Preview(result) => self.handle_preview_result(result),
+            Msg::GitDiff(result) => self.handle_git_diff_result(result),
+            Msg::Tick => self.tick(),
+        }
+    }
+}
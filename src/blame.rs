@@ -0,0 +1,129 @@
+//! Full-screen blame of one file
+//!
+//! `b` in the Git tab (`B` in the Explorer) runs `git blame --porcelain` on the
+//! working-tree file and lists each line with the commit that last touched it.
+//! Enter shows that commit in History.
+
+use std::collections::HashMap;
+
+use ratatui::text::Line;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlameLine {
+    pub hash: String,
+    pub author: String,
+    /// Author date as a unix timestamp
+    pub timestamp: i64,
+    pub summary: String,
+    pub content: String,
+}
+
+impl BlameLine {
+    /// Lines changed in the working tree carry git's all-zero hash.
+    pub fn is_uncommitted(&self) -> bool {
+        self.hash.bytes().all(|b| b == b'0')
+    }
+
+    pub fn short(&self) -> &str {
+        &self.hash[..self.hash.len().min(8)]
+    }
+}
+
+#[derive(Default)]
+pub struct BlameUi {
+    pub open: bool,
+    /// Repository-relative path
+    pub path: String,
+    pub lines: Vec<BlameLine>,
+    /// Syntax-highlighted content, one per line; empty when highlighting is off
+    pub highlighted: Vec<Line<'static>>,
+    pub selected: usize,
+    pub scroll_y: usize,
+}
+
+impl BlameUi {
+    pub fn show(&mut self, path: String, lines: Vec<BlameLine>, highlighted: Vec<Line<'static>>) {
+        self.open = true;
+        self.path = path;
+        self.lines = lines;
+        self.highlighted = highlighted;
+        self.selected = 0;
+        self.scroll_y = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.lines.clear();
+        self.highlighted.clear();
+    }
+
+    pub fn move_selection(&mut self, delta: i32) {
+        let last = self.lines.len().saturating_sub(1) as i64;
+        self.selected = (self.selected as i64 + delta as i64).clamp(0, last) as usize;
+    }
+
+    pub fn selected_line(&self) -> Option<&BlameLine> {
+        self.lines.get(self.selected)
+    }
+}
+
+/// Parse `git blame --porcelain`. Commit details are only printed the first time a
+/// commit appears, so they are remembered by hash.
+pub fn parse_porcelain(text: &str) -> Vec<BlameLine> {
+    let mut commits: HashMap<String, BlameLine> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut current = BlameLine::default();
+
+    for raw in text.lines() {
+        if let Some(content) = raw.strip_prefix('\t') {
+            let mut line = commits
+                .get(&current.hash)
+                .cloned()
+                .unwrap_or_else(|| current.clone());
+            line.content = content.to_string();
+            commits
+                .entry(current.hash.clone())
+                .or_insert_with(|| current.clone());
+            lines.push(line);
+        } else if let Some(value) = raw.strip_prefix("author ") {
+            current.author = value.to_string();
+        } else if let Some(value) = raw.strip_prefix("author-time ") {
+            current.timestamp = value.trim().parse().unwrap_or(0);
+        } else if let Some(value) = raw.strip_prefix("summary ") {
+            current.summary = value.to_string();
+        } else {
+            let hash = raw.split(' ').next().unwrap_or_default();
+            if hash.len() == 40 && hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                current = commits.get(hash).cloned().unwrap_or_else(|| BlameLine {
+                    hash: hash.to_string(),
+                    ..BlameLine::default()
+                });
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_porcelain_reuses_commit_details() {
+        let a = "a".repeat(40);
+        let zero = "0".repeat(40);
+        let text = format!(
+            "{a} 1 1 2\nauthor Ann\nauthor-time 100\nsummary First\nfilename f.rs\n\tfn main() {{\n\
+             {a} 2 2\n\t}}\n\
+             {zero} 3 3 1\nauthor Not Committed Yet\nauthor-time 200\nsummary Version of f.rs\n\
+             filename f.rs\n\t// new\n"
+        );
+        let lines = parse_porcelain(&text);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].author, "Ann");
+        assert_eq!(lines[1].timestamp, 100);
+        assert_eq!(lines[1].content, "}");
+        assert_eq!(lines[0].short(), "aaaaaaaa");
+        assert!(lines[2].is_uncommitted() && !lines[0].is_uncommitted());
+    }
+}
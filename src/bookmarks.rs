@@ -0,0 +1,211 @@
+//! Persisted explorer bookmarks
+//!
+//! Stored as `bookmarks.tsv` next to the UI settings, one
+//! `name<TAB>path[<TAB>key[<TAB>icon]]` per line. Files starting with the [`HEADER`]
+//! line hold the complete ordered list; older files only hold user additions, which
+//! are appended to the defaults.
+//!
+//! Paths may use `~`, `$HOME`, `$VAR` or `${VAR}` so one file can be shared across
+//! machines; they are resolved at load and written back as they were.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+pub const HEADER: &str = "# lzgit bookmarks v2";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bookmark {
+    pub name: String,
+    pub path: PathBuf,
+    /// Path as written in the file, when it used `~` or variables
+    pub stored: Option<String>,
+    /// Quick-jump digit, used as Alt+1..9
+    pub key: Option<char>,
+    pub icon: Option<String>,
+}
+
+impl Bookmark {
+    pub fn new(name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            path: path.into(),
+            stored: None,
+            key: None,
+            icon: None,
+        }
+    }
+
+    pub fn icon(&self) -> &str {
+        self.icon.as_deref().unwrap_or("📁")
+    }
+}
+
+pub fn defaults(home: &Path) -> Vec<Bookmark> {
+    vec![
+        Bookmark::new("Root", "/"),
+        Bookmark::new("Home", home),
+        Bookmark::new("Tmp", "/tmp"),
+        Bookmark::new("Bin", "/usr/bin"),
+    ]
+}
+
+/// Parse a bookmarks file; `defaults` seed the list for files without the header.
+pub fn parse(data: &str, defaults: Vec<Bookmark>) -> Vec<Bookmark> {
+    let full_list = data.lines().next().map(str::trim) == Some(HEADER);
+    let mut out = if full_list { Vec::new() } else { defaults };
+
+    for line in data.lines() {
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        let mut parts = line.splitn(4, '\t');
+        let name = parts.next().unwrap_or("").trim();
+        let path_str = parts.next().unwrap_or("").trim();
+        if name.is_empty() || path_str.is_empty() {
+            continue;
+        }
+        let key = parts
+            .next()
+            .and_then(|k| k.trim().chars().next())
+            .filter(|c| ('1'..='9').contains(c));
+        let icon = parts
+            .next()
+            .map(str::trim)
+            .filter(|i| !i.is_empty())
+            .map(str::to_string);
+
+        let path = expand_path(path_str, |var| env::var(var).ok());
+        if out.iter().any(|b| b.path == path) {
+            continue;
+        }
+        let stored = (path.as_os_str() != path_str).then(|| path_str.to_string());
+        out.push(Bookmark {
+            name: name.to_string(),
+            path,
+            stored,
+            key,
+            icon,
+        });
+    }
+    out
+}
+
+pub fn format(bookmarks: &[Bookmark]) -> String {
+    let mut lines = vec![HEADER.to_string()];
+    for b in bookmarks {
+        let path = b
+            .stored
+            .clone()
+            .unwrap_or_else(|| b.path.to_string_lossy().to_string());
+        let mut line = format!("{}\t{}", b.name, path);
+        if b.key.is_some() || b.icon.is_some() {
+            line.push('\t');
+            line.extend(b.key);
+        }
+        if let Some(icon) = &b.icon {
+            line.push('\t');
+            line.push_str(icon);
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// `~/…` form of `path` when it is under `$HOME`, so new bookmarks stay portable.
+pub fn contract_home(path: &Path) -> Option<String> {
+    let home = env::var_os("HOME").map(PathBuf::from)?;
+    let rel = path.strip_prefix(&home).ok()?;
+    if rel.as_os_str().is_empty() {
+        return Some("~".to_string());
+    }
+    Some(format!("~/{}", rel.to_string_lossy()))
+}
+
+/// Expand a leading `~` and `$VAR` / `${VAR}` references; unknown variables are kept.
+pub fn expand_path(raw: &str, lookup: impl Fn(&str) -> Option<String>) -> PathBuf {
+    let mut out = String::new();
+    let mut rest = raw;
+
+    if (rest == "~" || rest.starts_with("~/"))
+        && let Some(home) = lookup("HOME")
+    {
+        out.push_str(&home);
+        rest = &rest[1..];
+    }
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        let (name, consumed) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+
+        match (!name.is_empty()).then(|| lookup(name)).flatten() {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[pos..pos + 1 + consumed]),
+        }
+        rest = &after[consumed..];
+    }
+    out.push_str(rest);
+    PathBuf::from(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_file_appends_to_defaults() {
+        let list = parse("Work\t/work\n", defaults(Path::new("/home/me")));
+        assert_eq!(list.len(), 5);
+        assert_eq!(list[4], Bookmark::new("Work", "/work"));
+    }
+
+    #[test]
+    fn test_roundtrip_keeps_order_and_keys() {
+        let mut work = Bookmark::new("Work", "/work");
+        work.key = Some('2');
+        let list = vec![work, Bookmark::new("Home", "/home/me")];
+        let parsed = parse(&format(&list), defaults(Path::new("/home/me")));
+        assert_eq!(parsed, list);
+    }
+
+    #[test]
+    fn test_expand_path_home_and_vars() {
+        let lookup = |var: &str| match var {
+            "HOME" => Some("/home/me".to_string()),
+            "PROJ" => Some("/src".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            expand_path("~/work", lookup),
+            PathBuf::from("/home/me/work")
+        );
+        assert_eq!(expand_path("$HOME/a", lookup), PathBuf::from("/home/me/a"));
+        assert_eq!(
+            expand_path("${PROJ}/lzgit", lookup),
+            PathBuf::from("/src/lzgit")
+        );
+        assert_eq!(
+            expand_path("/x/$NOPE/y", lookup),
+            PathBuf::from("/x/$NOPE/y")
+        );
+    }
+
+    #[test]
+    fn test_variable_paths_are_written_back_unexpanded() {
+        let data = format!("{}\nCode\t~/code\t\t🦀\n", HEADER);
+        let list = parse(&data, Vec::new());
+        assert_eq!(list[0].icon(), "🦀");
+        assert_eq!(list[0].stored.as_deref(), Some("~/code"));
+        assert_eq!(format(&list), format!("{}\nCode\t~/code\t\t🦀", HEADER));
+    }
+}
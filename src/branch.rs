@@ -7,6 +7,34 @@ pub struct BranchEntry {
     pub is_remote: bool,
     pub upstream: Option<String>,
     pub track: Option<String>,
+    /// `branch.<name>.description`, as set by `git branch --edit-description`
+    pub description: Option<String>,
+    /// Another worktree that has the branch checked out, which git won't let this
+    /// one check out or delete
+    pub worktree: Option<String>,
+}
+
+impl BranchEntry {
+    /// Commits ahead of and behind the upstream, parsed from `track` such as
+    /// `[ahead 1, behind 2]`.
+    pub fn ahead_behind(&self) -> (u32, u32) {
+        let Some(track) = self.track.as_deref() else {
+            return (0, 0);
+        };
+        let count = |word: &str| {
+            track
+                .trim_matches(|c| c == '[' || c == ']')
+                .split(", ")
+                .find_map(|part| part.strip_prefix(word)?.trim().parse().ok())
+                .unwrap_or(0)
+        };
+        (count("ahead"), count("behind"))
+    }
+
+    /// The upstream was deleted on the remote.
+    pub fn upstream_gone(&self) -> bool {
+        self.track.as_deref() == Some("[gone]")
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -22,7 +50,6 @@ pub struct BranchUi {
     pub branches: Vec<BranchEntry>,
     pub items: Vec<BranchListItem>,
     pub list_state: ListState,
-    pub confirm_checkout: Option<String>,
     pub status: Option<String>,
 }
 
@@ -70,7 +97,6 @@ impl BranchUi {
             branches: Vec::new(),
             items: Vec::new(),
             list_state: ListState::default(),
-            confirm_checkout: None,
             status: None,
         }
     }
@@ -213,3 +239,29 @@ impl BranchUi {
         self.list_state.select(Some(next as usize));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(track: Option<&str>) -> BranchEntry {
+        BranchEntry {
+            name: "main".to_string(),
+            is_current: false,
+            is_remote: false,
+            upstream: Some("origin/main".to_string()),
+            track: track.map(str::to_string),
+            description: None,
+            worktree: None,
+        }
+    }
+
+    #[test]
+    fn test_ahead_behind() {
+        assert_eq!(entry(Some("[ahead 1, behind 12]")).ahead_behind(), (1, 12));
+        assert_eq!(entry(Some("[behind 3]")).ahead_behind(), (0, 3));
+        assert_eq!(entry(None).ahead_behind(), (0, 0));
+        assert!(entry(Some("[gone]")).upstream_gone());
+        assert_eq!(entry(Some("[gone]")).ahead_behind(), (0, 0));
+    }
+}
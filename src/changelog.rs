@@ -0,0 +1,363 @@
+//! Release notes and version bumps from commit subjects
+//!
+//! Subjects that follow Conventional Commits (`feat(ui): add x`, `fix!: y`) are
+//! grouped by type, with breaking changes first; anything else lands under Other.
+//! The release helper suggests the next `vX.Y.Z` from the same commits: a breaking
+//! change bumps the major version, a feature the minor one, the rest the patch.
+//! The changelog popup renders the groups as markdown linking to the forge.
+
+use std::fmt;
+
+use crate::git_ops::CommitEntry;
+
+/// Commit types in the order their groups are listed, with their headings
+const GROUPS: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("refactor", "Refactoring"),
+    ("docs", "Documentation"),
+    ("test", "Tests"),
+    ("build", "Build"),
+    ("ci", "CI"),
+    ("style", "Style"),
+    ("chore", "Chores"),
+    ("revert", "Reverts"),
+];
+
+const BREAKING: &str = "Breaking Changes";
+const OTHER: &str = "Other";
+
+/// A subject split as `type(scope)!: description`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Conventional<'a> {
+    pub kind: &'a str,
+    pub scope: Option<&'a str>,
+    pub breaking: bool,
+    pub description: &'a str,
+}
+
+/// Split a Conventional Commits subject; `None` for anything else.
+pub fn parse_conventional(subject: &str) -> Option<Conventional<'_>> {
+    let (head, description) = subject.split_once(": ")?;
+    let (head, breaking) = match head.strip_suffix('!') {
+        Some(head) => (head, true),
+        None => (head, false),
+    };
+    let (kind, scope) = match head.split_once('(') {
+        Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?)),
+        None => (head, None),
+    };
+    let word = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    if !word(kind) || scope.is_some_and(|s| s.is_empty() || s.contains(' ')) {
+        return None;
+    }
+    let description = description.trim();
+    (!description.is_empty()).then_some(Conventional {
+        kind,
+        scope,
+        breaking,
+        description,
+    })
+}
+
+/// One line of release notes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub scope: Option<String>,
+    pub description: String,
+    pub short: String,
+    pub hash: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Group {
+    pub heading: &'static str,
+    pub entries: Vec<Entry>,
+}
+
+/// Group `commits` (newest first, as `git log` lists them) by type. Merge commits
+/// are left out; entries keep the order of the commits.
+pub fn group(commits: &[CommitEntry]) -> Vec<Group> {
+    let mut groups: Vec<Group> = Vec::new();
+    for commit in commits {
+        if commit.subject.starts_with("Merge ") {
+            continue;
+        }
+        let (heading, scope, description) = match parse_conventional(&commit.subject) {
+            Some(c) if c.breaking => (BREAKING, c.scope, c.description),
+            Some(c) => {
+                let heading = GROUPS
+                    .iter()
+                    .find(|(kind, _)| kind.eq_ignore_ascii_case(c.kind))
+                    .map_or(OTHER, |(_, heading)| heading);
+                (heading, c.scope, c.description)
+            }
+            None => (OTHER, None, commit.subject.as_str()),
+        };
+        let entry = Entry {
+            scope: scope.map(str::to_string),
+            description: description.to_string(),
+            short: commit.short.clone(),
+            hash: commit.hash.clone(),
+        };
+        match groups.iter_mut().find(|g| g.heading == heading) {
+            Some(group) => group.entries.push(entry),
+            None => groups.push(Group {
+                heading,
+                entries: vec![entry],
+            }),
+        }
+    }
+    let rank = |heading: &str| {
+        if heading == BREAKING {
+            0
+        } else {
+            GROUPS
+                .iter()
+                .position(|(_, h)| *h == heading)
+                .map_or(GROUPS.len() + 1, |i| i + 1)
+        }
+    };
+    groups.sort_by_key(|g| rank(g.heading));
+    groups
+}
+
+/// Release notes as plain text, for an annotated tag: git would drop `#` headings
+/// from the message.
+pub fn plain_text(groups: &[Group]) -> String {
+    let mut out = String::new();
+    for group in groups {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(group.heading);
+        out.push_str(":\n");
+        for entry in &group.entries {
+            match &entry.scope {
+                Some(scope) => out.push_str(&format!("- {}: {}", scope, entry.description)),
+                None => out.push_str(&format!("- {}", entry.description)),
+            }
+            out.push_str(&format!(" ({})\n", entry.short));
+        }
+    }
+    out
+}
+
+/// Release notes as markdown under a `## to` heading, entries sorted by scope
+/// within each group. With the forge's web URL, the heading links to the comparison
+/// of `from` and `to` and every entry to its commit.
+pub fn markdown(from: Option<&str>, to: &str, groups: &[Group], web: Option<&str>) -> String {
+    let mut out = match (web, from) {
+        (Some(web), Some(from)) => format!("## [{}]({}/compare/{}...{})\n", to, web, from, to),
+        _ => format!("## {}\n", to),
+    };
+    if groups.is_empty() {
+        out.push_str("\nNo changes.\n");
+    }
+    for group in groups {
+        out.push_str(&format!("\n### {}\n\n", group.heading));
+        let mut entries: Vec<&Entry> = group.entries.iter().collect();
+        entries.sort_by(|a, b| a.scope.cmp(&b.scope));
+        for entry in entries {
+            out.push_str("- ");
+            if let Some(scope) = &entry.scope {
+                out.push_str(&format!("**{}:** ", scope));
+            }
+            match web {
+                Some(web) => out.push_str(&format!(
+                    "{} ([{}]({}/commit/{}))\n",
+                    entry.description, entry.short, web, entry.hash
+                )),
+                None => out.push_str(&format!("{} ({})\n", entry.description, entry.short)),
+            }
+        }
+    }
+    out
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bump {
+    Major,
+    Minor,
+    Patch,
+}
+
+impl Bump {
+    pub const ALL: [Bump; 3] = [Bump::Patch, Bump::Minor, Bump::Major];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Bump::Major => "Major",
+            Bump::Minor => "Minor",
+            Bump::Patch => "Patch",
+        }
+    }
+}
+
+/// The bump the commits call for.
+pub fn suggested_bump(commits: &[CommitEntry]) -> Bump {
+    let mut bump = Bump::Patch;
+    for commit in commits {
+        match parse_conventional(&commit.subject) {
+            Some(c) if c.breaking => return Bump::Major,
+            Some(c) if c.kind.eq_ignore_ascii_case("feat") => bump = Bump::Minor,
+            _ => {}
+        }
+    }
+    bump
+}
+
+/// A `vX.Y.Z` tag; the prefix is whatever comes before the first digit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Version {
+    pub prefix: String,
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    pub fn parse(tag: &str) -> Option<Version> {
+        let start = tag.find(|c: char| c.is_ascii_digit())?;
+        let (prefix, rest) = tag.split_at(start);
+        // Pre-release and build suffixes are dropped
+        let core = rest.split(['-', '+']).next().unwrap_or(rest);
+        let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+        let major = parts.next()??;
+        let minor = parts.next().flatten().unwrap_or(0);
+        let patch = parts.next().flatten().unwrap_or(0);
+        Some(Version {
+            prefix: prefix.to_string(),
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    pub fn bump(&self, bump: Bump) -> Version {
+        let (major, minor, patch) = match bump {
+            Bump::Major => (self.major + 1, 0, 0),
+            Bump::Minor => (self.major, self.minor + 1, 0),
+            Bump::Patch => (self.major, self.minor, self.patch + 1),
+        };
+        Version {
+            prefix: self.prefix.clone(),
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl Default for Version {
+    /// What the first release is bumped from
+    fn default() -> Self {
+        Version {
+            prefix: "v".to_string(),
+            major: 0,
+            minor: 0,
+            patch: 0,
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}.{}.{}",
+            self.prefix, self.major, self.minor, self.patch
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(short: &str, subject: &str) -> CommitEntry {
+        CommitEntry {
+            hash: format!("{}000", short),
+            short: short.to_string(),
+            date: String::new(),
+            timestamp: 0,
+            author: String::new(),
+            subject: subject.to_string(),
+            decoration: String::new(),
+            signature: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_conventional() {
+        let c = parse_conventional("feat(ui): add a button").unwrap();
+        assert_eq!((c.kind, c.scope, c.breaking), ("feat", Some("ui"), false));
+        assert_eq!(c.description, "add a button");
+        assert!(
+            parse_conventional("fix!: drop the old flag")
+                .unwrap()
+                .breaking
+        );
+        assert_eq!(parse_conventional("Fix the build: again"), None);
+        assert_eq!(parse_conventional("Update README"), None);
+    }
+
+    #[test]
+    fn test_group_and_bump() {
+        let commits = [
+            commit("a1", "fix(git): keep the selection"),
+            commit("b2", "Merge branch 'topic'"),
+            commit("c3", "feat: tag picker"),
+            commit("d4", "Tidy up"),
+            commit("e5", "fix: crash on empty repo"),
+        ];
+        let groups = group(&commits);
+        let headings: Vec<&str> = groups.iter().map(|g| g.heading).collect();
+        assert_eq!(headings, ["Features", "Bug Fixes", "Other"]);
+        assert_eq!(groups[1].entries.len(), 2);
+        assert_eq!(plain_text(&groups[..1]), "Features:\n- tag picker (c3)\n");
+        assert_eq!(suggested_bump(&commits), Bump::Minor);
+        assert_eq!(suggested_bump(&commits[..1]), Bump::Patch);
+        assert_eq!(
+            suggested_bump(&[commit("f6", "refactor!: new config")]),
+            Bump::Major
+        );
+    }
+
+    #[test]
+    fn test_markdown() {
+        let groups = group(&[
+            commit("a1", "fix(ui): wrap long lines"),
+            commit("b2", "fix: crash on start"),
+            commit("c3", "fix(git): keep the selection"),
+        ]);
+        let web = "https://example.com/me/tool";
+        assert_eq!(
+            markdown(Some("v1.0.0"), "v1.1.0", &groups, Some(web)),
+            "## [v1.1.0](https://example.com/me/tool/compare/v1.0.0...v1.1.0)\n\n\
+             ### Bug Fixes\n\n\
+             - crash on start ([b2](https://example.com/me/tool/commit/b2000))\n\
+             - **git:** keep the selection ([c3](https://example.com/me/tool/commit/c3000))\n\
+             - **ui:** wrap long lines ([a1](https://example.com/me/tool/commit/a1000))\n"
+        );
+        assert_eq!(
+            markdown(None, "HEAD", &[], None),
+            "## HEAD\n\nNo changes.\n"
+        );
+    }
+
+    #[test]
+    fn test_version_bump() {
+        let v = Version::parse("v1.4.2-rc.1").unwrap();
+        assert_eq!(v.bump(Bump::Patch).to_string(), "v1.4.3");
+        assert_eq!(v.bump(Bump::Minor).to_string(), "v1.5.0");
+        assert_eq!(v.bump(Bump::Major).to_string(), "v2.0.0");
+        assert_eq!(
+            Version::parse("release-3").unwrap().to_string(),
+            "release-3.0.0"
+        );
+        assert_eq!(Version::parse("stable"), None);
+        assert_eq!(Version::default().bump(Bump::Minor).to_string(), "v0.1.0");
+    }
+}
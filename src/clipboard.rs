@@ -0,0 +1,214 @@
+//! Copying text to the clipboard
+//!
+//! Methods are tried in order until one works: an external `command` such as
+//! `wl-copy` or `xclip -selection clipboard`, the system clipboard, and OSC 52 (the
+//! terminal sets the clipboard, which also works over SSH). By default SSH sessions
+//! try OSC 52 first; `[clipboard] order` overrides this.
+
+use std::env;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use arboard::Clipboard;
+use base64::{Engine as _, engine::general_purpose};
+use crossterm::{execute, style::Print};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipboardMethod {
+    Command,
+    System,
+    Osc52,
+}
+
+impl ClipboardMethod {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "command" => Some(ClipboardMethod::Command),
+            "system" => Some(ClipboardMethod::System),
+            "osc52" => Some(ClipboardMethod::Osc52),
+            _ => None,
+        }
+    }
+}
+
+/// Methods to try, in order. `command` is only used when one is configured.
+pub fn chain(
+    order: Option<&[ClipboardMethod]>,
+    command: Option<&str>,
+    ssh: bool,
+) -> Vec<ClipboardMethod> {
+    let default: &[ClipboardMethod] = if ssh {
+        &[
+            ClipboardMethod::Command,
+            ClipboardMethod::Osc52,
+            ClipboardMethod::System,
+        ]
+    } else {
+        &[
+            ClipboardMethod::Command,
+            ClipboardMethod::System,
+            ClipboardMethod::Osc52,
+        ]
+    };
+    order
+        .unwrap_or(default)
+        .iter()
+        .copied()
+        .filter(|m| *m != ClipboardMethod::Command || command.is_some())
+        .collect()
+}
+
+/// Copy `text` with the first method that works and describe how it went, e.g.
+/// "Copied via wl-copy" or "Copied via OSC 52 (system: no display)".
+pub fn copy<W: Write>(
+    w: &mut W,
+    text: &str,
+    methods: &[ClipboardMethod],
+    command: Option<&str>,
+) -> Result<String, String> {
+    let mut failures = Vec::new();
+    for method in methods {
+        let (name, result) = match method {
+            ClipboardMethod::Command => {
+                let command = command.unwrap_or_default();
+                let name = command.split_whitespace().next().unwrap_or("command");
+                (name.to_string(), run_command(command, text))
+            }
+            ClipboardMethod::System => ("system clipboard".to_string(), set_system(text)),
+            ClipboardMethod::Osc52 => {
+                let name = if in_tmux() { "OSC 52/tmux" } else { "OSC 52" };
+                (
+                    name.to_string(),
+                    emit_osc52(w, text).map_err(|e| e.to_string()),
+                )
+            }
+        };
+        match result {
+            Ok(()) if failures.is_empty() => return Ok(format!("Copied via {}", name)),
+            Ok(()) => {
+                return Ok(format!("Copied via {} ({})", name, failures.join("; ")));
+            }
+            Err(e) => failures.push(format!("{}: {}", name, e)),
+        }
+    }
+    if failures.is_empty() {
+        Err("Copy failed: no clipboard method configured".to_string())
+    } else {
+        Err(format!("Copy failed: {}", failures.join("; ")))
+    }
+}
+
+/// `file://` URI for an absolute path, percent-encoding everything but unreserved
+/// characters and `/`, so it can be pasted into file managers and browsers.
+pub fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+fn run_command(command: &str, text: &str) -> Result<(), String> {
+    let mut parts = command.split_whitespace();
+    let program = parts.next().ok_or("empty command")?;
+    let mut child = Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+    let out = child.wait_with_output().map_err(|e| e.to_string())?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        let err = String::from_utf8_lossy(&out.stderr).trim().to_string();
+        Err(if err.is_empty() {
+            out.status.to_string()
+        } else {
+            err
+        })
+    }
+}
+
+fn set_system(text: &str) -> Result<(), String> {
+    let mut cb = Clipboard::new().map_err(|e| e.to_string())?;
+    cb.set_text(text.to_string()).map_err(|e| e.to_string())
+}
+
+fn osc52_sequence(text: &str) -> String {
+    let encoded = general_purpose::STANDARD.encode(text.as_bytes());
+    format!("\x1b]52;c;{}\x07", encoded)
+}
+
+fn in_tmux() -> bool {
+    env::var_os("TMUX").is_some()
+        || env::var_os("TERM").is_some_and(|t| t.to_string_lossy().starts_with("tmux"))
+}
+
+fn tmux_passthrough(seq: &str) -> String {
+    let escaped = seq.replace('\x1b', "\x1b\x1b");
+    format!("\x1bPtmux;{}\x1b\\", escaped)
+}
+
+fn emit_osc52<W: Write>(w: &mut W, text: &str) -> io::Result<()> {
+    let seq = osc52_sequence(text);
+    let out = if in_tmux() {
+        tmux_passthrough(&seq)
+    } else {
+        seq
+    };
+    execute!(w, Print(out))?;
+    w.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_prefers_osc52_over_ssh() {
+        use ClipboardMethod::*;
+        assert_eq!(chain(None, None, false), vec![System, Osc52]);
+        assert_eq!(chain(None, None, true), vec![Osc52, System]);
+        assert_eq!(
+            chain(None, Some("wl-copy"), true),
+            vec![Command, Osc52, System]
+        );
+        assert_eq!(chain(Some(&[Osc52, Command]), None, false), vec![Osc52]);
+    }
+
+    #[test]
+    fn test_file_uri_escapes() {
+        assert_eq!(
+            file_uri(Path::new("/home/me/My Notes/ä#1.md")),
+            "file:///home/me/My%20Notes/%C3%A4%231.md"
+        );
+    }
+
+    #[test]
+    fn test_copy_falls_back_and_reports() {
+        let mut out = Vec::new();
+        let msg = copy(
+            &mut out,
+            "hi",
+            &[ClipboardMethod::Command, ClipboardMethod::Osc52],
+            Some("lzgit-no-such-command"),
+        )
+        .unwrap();
+        assert!(msg.starts_with("Copied via OSC 52"), "{}", msg);
+        assert!(msg.contains("lzgit-no-such-command:"), "{}", msg);
+        assert!(String::from_utf8_lossy(&out).contains("aGk="));
+    }
+}
@@ -1,6 +1,130 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::git_ops::CommitOverrides;
+
+/// Messages remembered per repository unless `[behavior] commit_history` says otherwise
+pub const DEFAULT_MESSAGE_HISTORY: usize = 20;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CommitFocus {
     Message,
+    /// Type of the conventional-commit helper
+    Type,
+    /// Scope of the conventional-commit helper
+    Scope,
+    /// Date field of the advanced options
+    Date,
+}
+
+/// Types the conventional-commit helper offers
+pub const CONVENTIONAL_TYPES: [&str; 11] = [
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+/// Longest first line before the drawer warns; longer ones get cut off in logs
+pub const HEADER_LIMIT: usize = 72;
+
+/// Parts of a `type(scope)!: subject` header picked in the drawer's guided mode
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Conventional {
+    /// Index into `CONVENTIONAL_TYPES`
+    pub kind: usize,
+    pub scope: String,
+    pub breaking: bool,
+}
+
+impl Conventional {
+    /// What goes before the subject, e.g. `feat(ui)!: `
+    pub fn prefix(&self) -> String {
+        let kind = CONVENTIONAL_TYPES[self.kind.min(CONVENTIONAL_TYPES.len() - 1)];
+        let scope = self.scope.trim();
+        format!(
+            "{}{}{}: ",
+            kind,
+            if scope.is_empty() {
+                String::new()
+            } else {
+                format!("({})", scope)
+            },
+            if self.breaking { "!" } else { "" }
+        )
+    }
+
+    /// The parts of a header that starts with a known type, and the byte offset of
+    /// its subject.
+    pub fn parse(header: &str) -> Option<(Self, usize)> {
+        let colon = header.find(':')?;
+        let (head, breaking) = match header[..colon].strip_suffix('!') {
+            Some(head) => (head, true),
+            None => (&header[..colon], false),
+        };
+        let (kind, scope) = match head.split_once('(') {
+            Some((kind, rest)) => (kind, rest.strip_suffix(')')?),
+            None => (head, ""),
+        };
+        let kind = CONVENTIONAL_TYPES.iter().position(|t| *t == kind)?;
+        let subject = colon + 1 + usize::from(header[colon + 1..].starts_with(' '));
+        let parts = Self {
+            kind,
+            scope: scope.to_string(),
+            breaking,
+        };
+        Some((parts, subject))
+    }
+}
+
+const CO_AUTHOR_TRAILER: &str = "Co-authored-by:";
+
+/// Identities credited with `Co-authored-by:` trailers in `message`.
+pub fn co_authors(message: &str) -> Vec<String> {
+    message
+        .lines()
+        .filter_map(|line| strip_trailer(line, CO_AUTHOR_TRAILER))
+        .map(|author| author.trim().to_string())
+        .collect()
+}
+
+/// `message` with its `Co-authored-by:` trailers replaced by one per author, after
+/// a blank line at the end.
+pub fn set_co_authors(message: &str, authors: &[String]) -> String {
+    let kept: Vec<&str> = message
+        .lines()
+        .filter(|line| strip_trailer(line, CO_AUTHOR_TRAILER).is_none())
+        .collect();
+    let mut out = kept.join("\n").trim_end().to_string();
+    if authors.is_empty() {
+        return out;
+    }
+    // Other trailers such as Signed-off-by stay in the same block
+    let last_paragraph = out.rsplit("\n\n").next().unwrap_or_default();
+    let in_trailers = !last_paragraph.is_empty()
+        && out.contains("\n\n")
+        && last_paragraph.lines().all(|line| {
+            line.split_once(": ")
+                .is_some_and(|(key, _)| !key.is_empty() && !key.contains(' '))
+        });
+    out.push_str(if in_trailers { "\n" } else { "\n\n" });
+    let trailers: Vec<String> = authors
+        .iter()
+        .map(|author| format!("{} {}", CO_AUTHOR_TRAILER, author))
+        .collect();
+    out.push_str(&trailers.join("\n"));
+    out
+}
+
+/// The value of a `key` trailer line, matching the key in any case as git does.
+fn strip_trailer<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let head = line.get(..key.len())?;
+    head.eq_ignore_ascii_case(key).then(|| &line[key.len()..])
+}
+
+/// Why a message's first line needs another look, if it does.
+pub fn header_warning(message: &str) -> Option<String> {
+    let len = message.lines().next().unwrap_or_default().chars().count();
+    (len > HEADER_LIMIT).then(|| format!("first line is {} characters, over {}", len, HEADER_LIMIT))
 }
 
 #[derive(Clone, Debug)]
@@ -12,6 +136,20 @@ pub struct CommitState {
     pub scroll_y: u16,
     pub status: Option<String>,
     pub busy: bool,
+    /// Advanced options are expanded
+    pub advanced: bool,
+    /// `--author` override, `Name <email>`
+    pub author: Option<String>,
+    /// Date override as typed; empty keeps the current time
+    pub date: String,
+    /// Commit with `--amend`; the message starts as HEAD's
+    pub amend: bool,
+    /// Message typed before switching to amend, back when switching off
+    pub draft: Option<String>,
+    /// Guided mode: the first line starts with the header these parts make
+    pub conventional: Option<Conventional>,
+    /// How commits get signed here, when `commit.gpgsign` is on
+    pub signing: Option<&'static str>,
 }
 
 impl CommitState {
@@ -24,6 +162,143 @@ impl CommitState {
             scroll_y: 0,
             status: None,
             busy: false,
+            advanced: false,
+            author: None,
+            date: String::new(),
+            amend: false,
+            draft: None,
+            conventional: None,
+            signing: None,
+        }
+    }
+
+    /// Turn guided mode on, taking the parts from the header if it has them, or off.
+    pub fn toggle_conventional(&mut self) {
+        if self.conventional.take().is_some() {
+            if matches!(self.focus, CommitFocus::Type | CommitFocus::Scope) {
+                self.focus = CommitFocus::Message;
+            }
+            return;
+        }
+        let header = self.message.lines().next().unwrap_or_default();
+        match Conventional::parse(header) {
+            Some((parts, _)) => self.conventional = Some(parts),
+            None => {
+                self.conventional = Some(Conventional::default());
+                self.apply_conventional();
+            }
+        }
+        self.focus = CommitFocus::Type;
+    }
+
+    /// Rewrite the start of the first line from the guided parts, keeping the
+    /// subject and the cursor's place in it.
+    pub fn apply_conventional(&mut self) {
+        let Some(parts) = &self.conventional else {
+            return;
+        };
+        let prefix = parts.prefix();
+        let header_end = self.message.find('\n').unwrap_or(self.message.len());
+        let old = Conventional::parse(&self.message[..header_end]).map_or(0, |(_, at)| at);
+        let old_chars = self.message[..old].chars().count();
+        self.message.replace_range(..old, &prefix);
+        let new_chars = prefix.chars().count();
+        self.cursor = if self.cursor < old_chars {
+            new_chars
+        } else {
+            self.cursor - old_chars + new_chars
+        };
+    }
+
+    /// Step the guided type by `delta`, wrapping around.
+    pub fn cycle_conventional_type(&mut self, delta: i32) {
+        if let Some(parts) = self.conventional.as_mut() {
+            let len = CONVENTIONAL_TYPES.len() as i32;
+            parts.kind = (parts.kind as i32 + delta).rem_euclid(len) as usize;
+            self.apply_conventional();
+        }
+    }
+
+    /// Jump to the next guided type starting with `ch`.
+    pub fn pick_conventional_type(&mut self, ch: char) {
+        let Some(parts) = self.conventional.as_mut() else {
+            return;
+        };
+        let len = CONVENTIONAL_TYPES.len();
+        let next = (1..=len)
+            .map(|step| (parts.kind + step) % len)
+            .find(|&i| CONVENTIONAL_TYPES[i].starts_with(ch));
+        if let Some(kind) = next {
+            parts.kind = kind;
+            self.apply_conventional();
+        }
+    }
+
+    pub fn toggle_breaking(&mut self) {
+        if let Some(parts) = self.conventional.as_mut() {
+            parts.breaking = !parts.breaking;
+            self.apply_conventional();
+        }
+    }
+
+    /// Type into the guided scope; `None` deletes the last character.
+    pub fn edit_scope(&mut self, ch: Option<char>) {
+        let Some(parts) = self.conventional.as_mut() else {
+            return;
+        };
+        match ch {
+            Some(ch) if !matches!(ch, ' ' | '(' | ')' | ':') => parts.scope.push(ch),
+            Some(_) => return,
+            None => {
+                parts.scope.pop();
+            }
+        }
+        self.apply_conventional();
+    }
+
+    /// Next field for Tab: message, the guided type and scope, then the date.
+    pub fn next_focus(&mut self) {
+        let guided = self.conventional.is_some();
+        self.focus = match self.focus {
+            CommitFocus::Message if guided => CommitFocus::Type,
+            CommitFocus::Type => CommitFocus::Scope,
+            CommitFocus::Message | CommitFocus::Scope if self.advanced => CommitFocus::Date,
+            _ => CommitFocus::Message,
+        };
+    }
+
+    /// Overrides from the advanced options; they apply while collapsed too.
+    pub fn overrides(&self) -> CommitOverrides {
+        let date = self.date.trim();
+        CommitOverrides {
+            author: self.author.clone(),
+            date: (!date.is_empty()).then(|| date.to_string()),
+            amend: self.amend,
+        }
+    }
+
+    /// Amend with `head_message`, keeping what was typed so far for later.
+    pub fn start_amend(&mut self, head_message: &str) {
+        let draft = std::mem::replace(&mut self.message, head_message.to_string());
+        self.draft = Some(draft);
+        self.amend = true;
+        self.cursor = self.message.chars().count();
+        self.scroll_y = 0;
+    }
+
+    /// Back to a new commit with the message typed before amending.
+    pub fn stop_amend(&mut self) {
+        self.message = self.draft.take().unwrap_or_default();
+        self.amend = false;
+        self.cursor = self.message.chars().count();
+        self.scroll_y = 0;
+    }
+
+    pub fn clear_overrides(&mut self) {
+        self.author = None;
+        self.date.clear();
+        if self.focus == CommitFocus::Date {
+            self.focus = CommitFocus::Message;
         }
     }
 
@@ -106,6 +381,82 @@ impl CommitState {
     }
 }
 
+/// A commit template as it starts the message: without the `#` comment lines git
+/// would drop, or trailing blank lines.
+pub fn template_message(template: &str) -> String {
+    let lines: Vec<&str> = template
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .collect();
+    lines.join("\n").trim_end().to_string()
+}
+
+/// Past commit messages per repository, newest first, persisted as JSON in the
+/// state directory. A message is remembered when the commit starts, so it can be
+/// recalled after the commit failed too.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageHistory {
+    /// Keyed by repository root
+    #[serde(default)]
+    repos: BTreeMap<String, Vec<String>>,
+}
+
+impl MessageHistory {
+    /// Read the history; a missing file means none.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = match std::fs::read_to_string(path) {
+            Ok(d) => d,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.to_string()),
+        };
+        serde_json::from_str(&data).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let data = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, data).map_err(|e| e.to_string())
+    }
+
+    pub fn messages(&self, repo: &Path) -> &[String] {
+        self.repos
+            .get(&repo.to_string_lossy().into_owned())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Put `message` first for `repo`, dropping an older copy and anything past
+    /// `limit`.
+    pub fn remember(&mut self, repo: &Path, message: &str, limit: usize) {
+        let message = message.trim_end();
+        if message.trim().is_empty() {
+            return;
+        }
+        let key = repo.to_string_lossy().into_owned();
+        let list = self.repos.entry(key.clone()).or_default();
+        list.retain(|m| m != message);
+        list.insert(0, message.to_string());
+        list.truncate(limit);
+        if list.is_empty() {
+            self.repos.remove(&key);
+        }
+    }
+
+    pub fn forget(&mut self, repo: &Path, idx: usize) {
+        let key = repo.to_string_lossy().into_owned();
+        if let Some(list) = self.repos.get_mut(&key)
+            && idx < list.len()
+        {
+            list.remove(idx);
+            if list.is_empty() {
+                self.repos.remove(&key);
+            }
+        }
+    }
+}
+
 fn char_to_byte_index(s: &str, char_idx: usize) -> usize {
     if char_idx == 0 {
         return 0;
@@ -168,3 +519,90 @@ fn cursor_to_index_in_line(s: &str, target_line: usize, target_col: usize) -> us
 
     idx
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_history_keeps_newest_unique() {
+        let repo = Path::new("/src/app");
+        let mut history = MessageHistory::default();
+        for msg in ["Fix build\n", "Bump deps", "Fix build", "  ", "Add docs"] {
+            history.remember(repo, msg, 3);
+        }
+        assert_eq!(
+            history.messages(repo),
+            ["Add docs", "Fix build", "Bump deps"]
+        );
+        history.remember(repo, "Tidy", 3);
+        assert_eq!(history.messages(repo), ["Tidy", "Add docs", "Fix build"]);
+        assert!(history.messages(Path::new("/src/other")).is_empty());
+
+        history.forget(repo, 1);
+        assert_eq!(history.messages(repo), ["Tidy", "Fix build"]);
+    }
+
+    #[test]
+    fn test_co_author_trailers() {
+        let ada = "Ada <ada@example.com>".to_string();
+        let bob = "Bob <bob@example.com>".to_string();
+        let message = set_co_authors("Fix build\n", &[ada.clone(), bob.clone()]);
+        assert_eq!(
+            message,
+            "Fix build\n\nCo-authored-by: Ada <ada@example.com>\n\
+             Co-authored-by: Bob <bob@example.com>"
+        );
+        assert_eq!(co_authors(&message), [ada.clone(), bob]);
+
+        // Replaced, next to the other trailers
+        let signed = "Fix build\n\nWhy: it broke\n\nSigned-off-by: Me <me@example.com>\n\
+                      co-authored-by: Old <old@example.com>";
+        assert_eq!(
+            set_co_authors(signed, std::slice::from_ref(&ada)),
+            "Fix build\n\nWhy: it broke\n\nSigned-off-by: Me <me@example.com>\n\
+             Co-authored-by: Ada <ada@example.com>"
+        );
+        assert_eq!(set_co_authors(&message, &[]), "Fix build");
+    }
+
+    #[test]
+    fn test_conventional_header() {
+        let (parts, at) = Conventional::parse("fix(ui)!: Keep the cursor").unwrap();
+        assert_eq!(
+            (parts.kind, parts.scope.as_str(), parts.breaking, at),
+            (1, "ui", true, 10)
+        );
+        assert!(Conventional::parse("Fix: not a type").is_none());
+        assert!(Conventional::parse("Keep the cursor").is_none());
+
+        let mut commit = CommitState::new();
+        commit.message = "Keep the cursor\n\nBody".to_string();
+        commit.cursor = 4;
+        commit.toggle_conventional();
+        assert_eq!(commit.message, "feat: Keep the cursor\n\nBody");
+        assert_eq!(commit.cursor, 10);
+        commit.pick_conventional_type('f');
+        commit.edit_scope(Some('u'));
+        commit.edit_scope(Some('i'));
+        commit.toggle_breaking();
+        assert_eq!(commit.message, "fix(ui)!: Keep the cursor\n\nBody");
+        commit.cycle_conventional_type(-2);
+        commit.edit_scope(None);
+        commit.edit_scope(None);
+        commit.toggle_breaking();
+        assert_eq!(commit.message, "revert: Keep the cursor\n\nBody");
+        assert_eq!(commit.cursor, 12);
+
+        assert!(header_warning(&commit.message).is_none());
+        assert_eq!(co_authors(&commit.message), Vec::<String>::new());
+        assert!(header_warning(&"x".repeat(73)).is_some());
+    }
+
+    #[test]
+    fn test_template_message_drops_comments() {
+        let template = "feat: \n\n# Why:\nRefs: #\n\n# Lines starting with # are dropped\n";
+        assert_eq!(template_message(template), "feat: \n\nRefs: #");
+        assert_eq!(template_message("# only comments\n"), "");
+    }
+}
@@ -0,0 +1,1045 @@
+//! User configuration in `config.toml`
+//!
+//! The file lives in the settings directory (`~/.config/lzgit/config.toml`) and is
+//! only ever written by the user. It is read at startup, re-read when it changes on
+//! disk and on the "Reload config" palette command. Values here win over the state
+//! lzgit remembers in `ui.json`.
+//!
+//! ```toml
+//! [ui]
+//! theme = "tokyo-night-storm"   # mocha, tokyo-night-storm, gruvbox-dark-hard, nord, dracula, terminal
+//! wrap_diff = true
+//! syntax_highlight = true
+//! diff_line_numbers = false
+//! binary_hexdump = false        # hexdump diff for binaries up to 4 KiB
+//! show_hidden = false
+//!
+//! [syntax.nord]                 # syntax colors per theme: text, keyword, function,
+//! comment = "#7b88a1"           # string, number, type, variable, comment
+//!
+//! [behavior]
+//! auto_refresh = true           # reload the explorer when the directory changes
+//! review_before_commit = false  # Commit stays disabled until every staged hunk is reviewed
+//! commit_history = 20           # past messages per repo to recall (Ctrl+L), 0 = none
+//! count_prefix = false          # vim-style counts: 15j, 3}
+//!
+//! [timeouts]                    # seconds before a hung git command is killed, 0 = never
+//! local = 60                    # commands that only read; ones that write never time out
+//! network = 300                 # fetch, push, clone
+//!
+//! [confirm]                     # always, never or smart (ask when work could be lost)
+//! discard = "always"
+//! delete = "always"
+//! stash_pop = "always"
+//! stash_drop = "always"
+//! checkout = "smart"
+//! push = "never"
+//! hard_reset = "always"
+//! double_press = false          # discard/stash drop: press twice instead of a modal
+//!
+//! [clipboard]
+//! order = "command, system, osc52"  # tried in turn; default puts osc52 first over SSH
+//! command = "wl-copy"           # or "xclip -selection clipboard", "pbcopy"
+//!
+//! [keys.global]                 # also keys.explorer, keys.git, keys.log
+//! command_palette = "ctrl+k"    # see `keymap` for the actions
+//!
+//! [keys.git]
+//! push = "P"
+//!
+//! [ai]
+//! provider = "openrouter"
+//! model = "openai/gpt-5.2"
+//! api_key_env = "OPENROUTER_API_KEY"
+//!
+//! [forge]                     # CI badges in History for the current branch
+//! provider = "github"           # github, gitlab or gitea
+//! token_env = "GITHUB_TOKEN"
+//! api_url = "https://ghe.example.com/api/v3"  # when not derived from the remote URL
+//!
+//! [top_bar]
+//! clock = true
+//! branch = true
+//! ahead_behind = true
+//! operation = true
+//!
+//! [privacy]
+//! lock_after = 10               # minutes idle before the lock screen, 0 = never
+//! passphrase_env = "LZGIT_LOCK" # unlock with this variable's value, not any key
+//!
+//! [panes]
+//! tmux = "tmux split-window -h -c {cwd} {command}"
+//!
+//! [hooks]
+//! before_commit = "./scripts/check-message.sh"
+//!
+//! [features]
+//! network = true                # update check, AI, CI status and opening URLs
+//! terminal = true               # embedded terminal tab
+//! shell = true                  # anything run besides git: hooks, actions, editor
+//!
+//! [[actions]]
+//! context = "commit"            # commit, file or branch
+//! label = "Open on GitHub"
+//! template = "https://github.com/org/repo/commit/{sha}"
+//! ```
+//!
+//! Only this subset of TOML is understood: tables, arrays of tables, and strings,
+//! integers or booleans as values. Unknown tables and keys are
+//! reported with their line number instead of being ignored.
+//!
+//! The other files in the settings directory stay separate on purpose. `ui.json` and
+//! `bookmarks.tsv` are state lzgit writes back as it is used (the theme picked, the
+//! bookmarks added), which would clobber the comments and layout of a file the user
+//! edits by hand.
+//! There is no `toml` crate among the dependencies, so the parser below is our own
+//! and reads only the subset above.
+
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::Color;
+
+use crate::actions::{ActionContext, ExternalAction};
+use crate::clipboard::ClipboardMethod;
+use crate::confirm::{ConfirmKind, ConfirmMode, ConfirmSettings};
+use crate::forge::ForgeKind;
+use crate::hooks::{HookOp, Hooks};
+use crate::keymap::{self, KeyScope, Keymap};
+use crate::multiplexer::PaneTemplates;
+use crate::theme::{SyntaxRole, Theme};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Str(s) => write!(f, "\"{}\"", s),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Bool(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+struct Entry {
+    key: String,
+    value: Value,
+    line: usize,
+}
+
+struct Table {
+    name: String,
+    /// Declared with `[[name]]`
+    array: bool,
+    line: usize,
+    entries: Vec<Entry>,
+}
+
+/// A key from `[keys]`, e.g. `q`, `T` or `ctrl+p`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeySpec {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeySpec {
+    pub const fn char(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    pub const fn ctrl(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    fn parse(raw: &str) -> Option<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = raw.split('+').collect();
+        // A lone "+" is the plus key, not a separator.
+        let key = if raw.ends_with("++") || raw == "+" {
+            parts.retain(|p| !p.is_empty());
+            "+"
+        } else {
+            parts.pop()?
+        };
+        for m in parts {
+            modifiers |= match m.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+        let code = match key.to_ascii_lowercase().as_str() {
+            "esc" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "space" => KeyCode::Char(' '),
+            _ => {
+                let mut chars = key.chars();
+                let c = chars.next()?;
+                if chars.next().is_some() {
+                    return None;
+                }
+                KeyCode::Char(c)
+            }
+        };
+        Some(Self { code, modifiers })
+    }
+
+    /// Whether `key` triggers this binding; extra modifiers are allowed.
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        key.code == self.code && key.modifiers.contains(self.modifiers)
+    }
+}
+
+/// Written the way `[keys]` takes it, e.g. `ctrl+p`.
+impl fmt::Display for KeySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Esc => f.write_str("esc"),
+            KeyCode::Enter => f.write_str("enter"),
+            KeyCode::Tab => f.write_str("tab"),
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            _ => f.write_str("?"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UiConfig {
+    pub theme: Option<Theme>,
+    pub wrap_diff: Option<bool>,
+    pub syntax_highlight: Option<bool>,
+    pub diff_line_numbers: Option<bool>,
+    pub binary_hexdump: Option<bool>,
+    pub show_hidden: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BehaviorConfig {
+    pub auto_refresh: Option<bool>,
+    pub review_before_commit: bool,
+    pub commit_history: Option<usize>,
+    /// Digits before a movement repeat it
+    pub count_prefix: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimeoutsConfig {
+    pub local: Option<u64>,
+    pub network: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClipboardConfig {
+    pub order: Option<Vec<ClipboardMethod>>,
+    /// External command that reads the text on stdin
+    pub command: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TopBarConfig {
+    pub clock: Option<bool>,
+    pub branch: Option<bool>,
+    pub ahead_behind: Option<bool>,
+    pub operation: Option<bool>,
+}
+
+/// The lock screen; see [`crate::lock`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrivacyConfig {
+    /// Idle minutes before locking; unset or 0 locks only on request
+    pub lock_after: Option<u64>,
+    /// Environment variable holding the passphrase
+    pub passphrase_env: Option<String>,
+}
+
+/// Commit message generation; only OpenRouter is supported for now.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AiConfig {
+    pub model: Option<String>,
+    /// Environment variable holding the API key
+    pub api_key_env: Option<String>,
+}
+
+/// CI status from the forge; off until a provider is set.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ForgeConfig {
+    pub provider: Option<ForgeKind>,
+    pub api_url: Option<String>,
+    /// Environment variable holding the API token
+    pub token_env: Option<String>,
+}
+
+/// Switches for locked-down machines. Either `[features]` or the environment
+/// (`LZGIT_NO_NETWORK`, `LZGIT_NO_TERMINAL`, `LZGIT_NO_SHELL`, or `LZGIT_RESTRICTED`
+/// for all three) can turn a feature off; neither can turn it back on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Features {
+    pub network: bool,
+    pub terminal: bool,
+    /// Processes other than git
+    pub shell: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            network: true,
+            terminal: true,
+            shell: true,
+        }
+    }
+}
+
+impl Features {
+    pub fn from_env(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let set =
+            |var: &str| lookup(var).is_some_and(|v| !matches!(v.trim(), "" | "0" | "false" | "no"));
+        let all = set("LZGIT_RESTRICTED");
+        Self {
+            network: !(all || set("LZGIT_NO_NETWORK")),
+            terminal: !(all || set("LZGIT_NO_TERMINAL")),
+            shell: !(all || set("LZGIT_NO_SHELL")),
+        }
+    }
+
+    /// Features enabled in both; the embedded terminal is a shell too.
+    pub fn restrict(self, other: Features) -> Self {
+        let shell = self.shell && other.shell;
+        Self {
+            network: self.network && other.network,
+            terminal: self.terminal && other.terminal && shell,
+            shell,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub ui: UiConfig,
+    pub behavior: BehaviorConfig,
+    pub timeouts: TimeoutsConfig,
+    pub confirm: ConfirmSettings,
+    pub clipboard: ClipboardConfig,
+    /// `[keys.global]`, `[keys.git]` and the other per-tab tables
+    pub keymap: Keymap,
+    pub ai: AiConfig,
+    pub forge: ForgeConfig,
+    pub top_bar: TopBarConfig,
+    pub privacy: PrivacyConfig,
+    pub panes: PaneTemplates,
+    pub hooks: Hooks,
+    pub actions: Vec<ExternalAction>,
+    pub features: Features,
+    /// `[syntax.<theme>]` colors, applied over the theme's own
+    pub syntax: Vec<(Theme, SyntaxRole, Color)>,
+}
+
+/// Parse and validate a config file; every schema error is returned, not just the first.
+pub fn parse(src: &str) -> Result<Config, Vec<ConfigError>> {
+    let tables = parse_document(src).map_err(|e| vec![e])?;
+    let mut cfg = Config::default();
+    let mut errors = Vec::new();
+
+    for table in &tables {
+        if table.array {
+            if table.name == "actions" {
+                match parse_action(table) {
+                    Ok(action) => cfg.actions.push(action),
+                    Err(e) => errors.push(e),
+                }
+            } else {
+                errors.push(ConfigError::new(
+                    table.line,
+                    format!("unknown table [[{}]]", table.name),
+                ));
+            }
+            continue;
+        }
+
+        for entry in &table.entries {
+            if let Err(e) = apply_entry(&mut cfg, &table.name, entry) {
+                errors.push(e);
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(cfg)
+    } else {
+        Err(errors)
+    }
+}
+
+fn apply_entry(cfg: &mut Config, table: &str, entry: &Entry) -> Result<(), ConfigError> {
+    let key = entry.key.as_str();
+    match (table, key) {
+        ("ui", "theme") => {
+            let name = expect_str(entry)?;
+            let theme = serde_json::from_value(serde_json::Value::String(name.clone()))
+                .map_err(|_| ConfigError::new(entry.line, format!("unknown theme \"{}\"", name)))?;
+            cfg.ui.theme = Some(theme);
+        }
+        ("ui", "wrap_diff") => cfg.ui.wrap_diff = Some(expect_bool(entry)?),
+        ("ui", "syntax_highlight") => cfg.ui.syntax_highlight = Some(expect_bool(entry)?),
+        ("ui", "diff_line_numbers") => cfg.ui.diff_line_numbers = Some(expect_bool(entry)?),
+        ("ui", "binary_hexdump") => cfg.ui.binary_hexdump = Some(expect_bool(entry)?),
+        ("ui", "show_hidden") => cfg.ui.show_hidden = Some(expect_bool(entry)?),
+        ("behavior", "auto_refresh") => cfg.behavior.auto_refresh = Some(expect_bool(entry)?),
+        ("behavior", "review_before_commit") => {
+            cfg.behavior.review_before_commit = expect_bool(entry)?
+        }
+        ("behavior", "commit_history") => cfg.behavior.commit_history = Some(expect_count(entry)?),
+        ("behavior", "count_prefix") => cfg.behavior.count_prefix = expect_bool(entry)?,
+        ("timeouts", "local") => cfg.timeouts.local = Some(expect_secs(entry)?),
+        ("timeouts", "network") => cfg.timeouts.network = Some(expect_secs(entry)?),
+        ("clipboard", "order") => {
+            let raw = expect_str(entry)?;
+            let order = raw
+                .split(',')
+                .map(|m| {
+                    ClipboardMethod::parse(m.trim()).ok_or_else(|| {
+                        ConfigError::new(
+                            entry.line,
+                            format!(
+                                "unknown clipboard method \"{}\" (use command, system, osc52)",
+                                m.trim()
+                            ),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            cfg.clipboard.order = Some(order);
+        }
+        ("clipboard", "command") => cfg.clipboard.command = Some(expect_str(entry)?),
+        ("confirm", "double_press") => cfg.confirm.double_press = expect_bool(entry)?,
+        ("confirm", _) => {
+            let kind = ConfirmKind::ALL
+                .into_iter()
+                .find(|k| k.key() == key)
+                .ok_or_else(|| unknown_key(table, entry))?;
+            let raw = expect_str(entry)?;
+            let mode = ConfirmMode::parse(&raw).ok_or_else(|| {
+                ConfigError::new(
+                    entry.line,
+                    format!("`{}` must be always, never or smart", key),
+                )
+            })?;
+            cfg.confirm.set(kind, mode);
+        }
+        ("keys", _) => {
+            return Err(ConfigError::new(
+                entry.line,
+                format!("`{}` goes in a table such as [keys.global]", key),
+            ));
+        }
+        _ if let Some(scope) = KeyScope::ALL.into_iter().find(|s| s.table() == table) => {
+            let action = keymap::action_named(key).ok_or_else(|| unknown_key(table, entry))?;
+            cfg.keymap.bind(scope, action, expect_key(entry)?);
+        }
+        ("ai", "provider") => {
+            let provider = expect_str(entry)?;
+            if provider != "openrouter" {
+                return Err(ConfigError::new(
+                    entry.line,
+                    format!("unsupported AI provider \"{}\"", provider),
+                ));
+            }
+        }
+        ("ai", "model") => cfg.ai.model = Some(expect_str(entry)?),
+        ("ai", "api_key_env") => cfg.ai.api_key_env = Some(expect_str(entry)?),
+        ("forge", "provider") => {
+            let name = expect_str(entry)?;
+            let kind = ForgeKind::parse(&name).ok_or_else(|| {
+                ConfigError::new(
+                    entry.line,
+                    format!("unknown forge \"{}\" (use github, gitlab, gitea)", name),
+                )
+            })?;
+            cfg.forge.provider = Some(kind);
+        }
+        ("forge", "api_url") => cfg.forge.api_url = Some(expect_str(entry)?),
+        ("forge", "token_env") => cfg.forge.token_env = Some(expect_str(entry)?),
+        ("top_bar", "clock") => cfg.top_bar.clock = Some(expect_bool(entry)?),
+        ("top_bar", "branch") => cfg.top_bar.branch = Some(expect_bool(entry)?),
+        ("top_bar", "ahead_behind") => cfg.top_bar.ahead_behind = Some(expect_bool(entry)?),
+        ("top_bar", "operation") => cfg.top_bar.operation = Some(expect_bool(entry)?),
+        ("features", "network") => cfg.features.network = expect_bool(entry)?,
+        ("features", "terminal") => cfg.features.terminal = expect_bool(entry)?,
+        ("features", "shell") => cfg.features.shell = expect_bool(entry)?,
+        ("privacy", "lock_after") => cfg.privacy.lock_after = Some(expect_count(entry)? as u64),
+        ("privacy", "passphrase_env") => cfg.privacy.passphrase_env = Some(expect_str(entry)?),
+        ("panes", "tmux") => cfg.panes.tmux = Some(expect_str(entry)?),
+        ("panes", "zellij") => cfg.panes.zellij = Some(expect_str(entry)?),
+        _ if let Some(name) = table.strip_prefix("syntax.") => {
+            let theme = serde_json::from_value(serde_json::Value::String(name.to_string()))
+                .map_err(|_| ConfigError::new(entry.line, format!("unknown theme \"{}\"", name)))?;
+            let role = SyntaxRole::ALL
+                .into_iter()
+                .find(|r| r.key() == key)
+                .ok_or_else(|| unknown_key(table, entry))?;
+            cfg.syntax.push((theme, role, expect_color(entry)?));
+        }
+        ("hooks", _) => {
+            let (before, op) = match key.split_once('_') {
+                Some(("before", op)) => (true, op),
+                Some(("after", op)) => (false, op),
+                _ => return Err(unknown_key(table, entry)),
+            };
+            let op = match op {
+                "commit" => HookOp::Commit,
+                "push" => HookOp::Push,
+                "checkout" => HookOp::Checkout,
+                _ => return Err(unknown_key(table, entry)),
+            };
+            cfg.hooks.set(op, before, expect_str(entry)?);
+        }
+        ("", _) => {
+            return Err(ConfigError::new(
+                entry.line,
+                format!("`{}` must be inside a table such as [ui]", key),
+            ));
+        }
+        (
+            "ui" | "behavior" | "timeouts" | "ai" | "forge" | "top_bar" | "privacy" | "panes"
+            | "features",
+            _,
+        ) => {
+            return Err(unknown_key(table, entry));
+        }
+        _ => {
+            return Err(ConfigError::new(
+                entry.line,
+                format!("unknown table [{}]", table),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn parse_action(table: &Table) -> Result<ExternalAction, ConfigError> {
+    let (mut context, mut label, mut template) = (None, None, None);
+    for entry in &table.entries {
+        match entry.key.as_str() {
+            "context" => {
+                context = Some(match expect_str(entry)?.as_str() {
+                    "commit" => ActionContext::Commit,
+                    "file" => ActionContext::File,
+                    "branch" => ActionContext::Branch,
+                    other => {
+                        return Err(ConfigError::new(
+                            entry.line,
+                            format!("unknown action context \"{}\"", other),
+                        ));
+                    }
+                })
+            }
+            "label" => label = Some(expect_str(entry)?),
+            "template" => template = Some(expect_str(entry)?),
+            _ => return Err(unknown_key("actions", entry)),
+        }
+    }
+    match (context, label, template) {
+        (Some(context), Some(label), Some(template)) => Ok(ExternalAction {
+            context,
+            label,
+            template,
+        }),
+        _ => Err(ConfigError::new(
+            table.line,
+            "[[actions]] needs context, label and template",
+        )),
+    }
+}
+
+fn unknown_key(table: &str, entry: &Entry) -> ConfigError {
+    ConfigError::new(
+        entry.line,
+        format!("unknown key `{}` in [{}]", entry.key, table),
+    )
+}
+
+fn type_error(entry: &Entry, expected: &str) -> ConfigError {
+    ConfigError::new(
+        entry.line,
+        format!(
+            "`{}` must be {}, found {}",
+            entry.key, expected, entry.value
+        ),
+    )
+}
+
+fn expect_str(entry: &Entry) -> Result<String, ConfigError> {
+    match &entry.value {
+        Value::Str(s) => Ok(s.clone()),
+        _ => Err(type_error(entry, "a string")),
+    }
+}
+
+fn expect_bool(entry: &Entry) -> Result<bool, ConfigError> {
+    match entry.value {
+        Value::Bool(b) => Ok(b),
+        _ => Err(type_error(entry, "a boolean")),
+    }
+}
+
+fn expect_secs(entry: &Entry) -> Result<u64, ConfigError> {
+    match entry.value {
+        Value::Int(i) if i >= 0 => Ok(i as u64),
+        _ => Err(type_error(entry, "a number of seconds")),
+    }
+}
+
+fn expect_count(entry: &Entry) -> Result<usize, ConfigError> {
+    match entry.value {
+        Value::Int(i) if i >= 0 => Ok(i as usize),
+        _ => Err(type_error(entry, "a non-negative number")),
+    }
+}
+
+fn expect_key(entry: &Entry) -> Result<KeySpec, ConfigError> {
+    let raw = expect_str(entry)?;
+    KeySpec::parse(&raw)
+        .ok_or_else(|| ConfigError::new(entry.line, format!("invalid key \"{}\"", raw)))
+}
+
+/// A `"#rrggbb"` color.
+fn expect_color(entry: &Entry) -> Result<Color, ConfigError> {
+    let raw = expect_str(entry)?;
+    let hex = raw
+        .strip_prefix('#')
+        .filter(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()));
+    let Some(hex) = hex else {
+        return Err(ConfigError::new(
+            entry.line,
+            format!("invalid color \"{}\" (use \"#rrggbb\")", raw),
+        ));
+    };
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or_default();
+    Ok(Color::Rgb(channel(0), channel(2), channel(4)))
+}
+
+fn parse_document(src: &str) -> Result<Vec<Table>, ConfigError> {
+    let mut tables = vec![Table {
+        name: String::new(),
+        array: false,
+        line: 0,
+        entries: Vec::new(),
+    }];
+
+    for (idx, raw) in src.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = strip_comment(raw).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("[[") {
+            let name = rest
+                .strip_suffix("]]")
+                .ok_or_else(|| ConfigError::new(line_no, "expected `]]`"))?;
+            tables.push(Table {
+                name: parse_table_name(name, line_no)?,
+                array: true,
+                line: line_no,
+                entries: Vec::new(),
+            });
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('[') {
+            let name = rest
+                .strip_suffix(']')
+                .ok_or_else(|| ConfigError::new(line_no, "expected `]`"))?;
+            let name = parse_table_name(name, line_no)?;
+            if tables.iter().any(|t| !t.array && t.name == name) {
+                return Err(ConfigError::new(
+                    line_no,
+                    format!("table [{}] is defined twice", name),
+                ));
+            }
+            tables.push(Table {
+                name,
+                array: false,
+                line: line_no,
+                entries: Vec::new(),
+            });
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| ConfigError::new(line_no, "expected `key = value`"))?;
+        let key = parse_key(key.trim(), line_no)?;
+        let value = parse_value(value.trim(), line_no)?;
+
+        let table = tables.last_mut().expect("root table");
+        if table.entries.iter().any(|e| e.key == key) {
+            return Err(ConfigError::new(
+                line_no,
+                format!("duplicate key `{}`", key),
+            ));
+        }
+        table.entries.push(Entry {
+            key,
+            value,
+            line: line_no,
+        });
+    }
+    Ok(tables)
+}
+
+/// Drop a trailing `# comment` that is not inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => return &line[..i],
+            None => {}
+        }
+    }
+    line
+}
+
+fn is_bare_key(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+fn parse_table_name(raw: &str, line: usize) -> Result<String, ConfigError> {
+    let name = raw.trim();
+    if name.split('.').all(is_bare_key) {
+        Ok(name.to_string())
+    } else {
+        Err(ConfigError::new(
+            line,
+            format!("invalid table name `{}`", name),
+        ))
+    }
+}
+
+fn parse_key(raw: &str, line: usize) -> Result<String, ConfigError> {
+    if raw.starts_with('"') || raw.starts_with('\'') {
+        let (key, rest) = parse_string(raw, line)?;
+        if rest.trim().is_empty() {
+            return Ok(key);
+        }
+    } else if is_bare_key(raw) {
+        return Ok(raw.to_string());
+    }
+    Err(ConfigError::new(line, format!("invalid key `{}`", raw)))
+}
+
+fn parse_value(raw: &str, line: usize) -> Result<Value, ConfigError> {
+    if raw.starts_with('"') || raw.starts_with('\'') {
+        let (s, rest) = parse_string(raw, line)?;
+        if !rest.trim().is_empty() {
+            return Err(ConfigError::new(
+                line,
+                format!("unexpected `{}` after value", rest.trim()),
+            ));
+        }
+        return Ok(Value::Str(s));
+    }
+    match raw {
+        "true" => Ok(Value::Bool(true)),
+        "false" => Ok(Value::Bool(false)),
+        _ => raw
+            .replace('_', "")
+            .parse()
+            .map(Value::Int)
+            .map_err(|_| ConfigError::new(line, format!("invalid value `{}`", raw))),
+    }
+}
+
+/// Parse a basic (`"…"`) or literal (`'…'`) string at the start of `raw`.
+fn parse_string(raw: &str, line: usize) -> Result<(String, &str), ConfigError> {
+    let mut chars = raw.char_indices();
+    let quote = chars.next().map(|(_, c)| c).unwrap_or('"');
+    let mut out = String::new();
+    while let Some((i, c)) = chars.next() {
+        if c == quote {
+            return Ok((out, &raw[i + 1..]));
+        }
+        if c == '\\' && quote == '"' {
+            let escaped = match chars.next().map(|(_, c)| c) {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('"') => '"',
+                Some('\\') => '\\',
+                other => {
+                    return Err(ConfigError::new(
+                        line,
+                        format!("unsupported escape `\\{}`", other.unwrap_or(' ')),
+                    ));
+                }
+            };
+            out.push(escaped);
+        } else {
+            out.push(c);
+        }
+    }
+    Err(ConfigError::new(line, "unterminated string"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::AppAction;
+
+    #[test]
+    fn test_parse_sections_and_actions() {
+        let src = r#"
+# comment
+[ui]
+theme = "nord"   # trailing comment
+wrap_diff = true
+
+[behavior]
+review_before_commit = true
+commit_history = 5
+count_prefix = true
+
+[keys.global]
+command_palette = "ctrl+k"
+
+[[actions]]
+context = "file"
+label = "Open # in editor"
+template = 'code {abs_path}'
+"#;
+        let cfg = parse(src).unwrap();
+        assert_eq!(cfg.ui.theme, Some(Theme::Nord));
+        assert_eq!(cfg.ui.wrap_diff, Some(true));
+        assert_eq!(
+            cfg.keymap
+                .effective(KeyScope::Global, &AppAction::OpenCommandPalette),
+            vec![KeySpec::ctrl('k'), KeySpec::ctrl('p')]
+        );
+        assert_eq!(
+            cfg.keymap.effective(KeyScope::Global, &AppAction::Quit),
+            vec![KeySpec::char('q')]
+        );
+        assert!(cfg.behavior.count_prefix);
+        assert!(cfg.behavior.review_before_commit);
+        assert_eq!(cfg.behavior.commit_history, Some(5));
+        assert_eq!(cfg.actions.len(), 1);
+        assert_eq!(cfg.actions[0].label, "Open # in editor");
+        assert_eq!(cfg.actions[0].template, "code {abs_path}");
+    }
+
+    #[test]
+    fn test_schema_errors_report_every_line() {
+        let src = "[ui]\nwrap_diff = \"yes\"\ncolour = 1\n[nope]\nx = 1\n";
+        let errors = parse(src).unwrap_err();
+        let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![2, 3, 5]);
+        assert_eq!(
+            errors[0].to_string(),
+            "line 2: `wrap_diff` must be a boolean, found \"yes\""
+        );
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        assert_eq!(parse("[ui\n").unwrap_err()[0].line, 1);
+        assert_eq!(parse("[ui]\nx = \"open\n").unwrap_err()[0].line, 2);
+        assert!(parse("[ui]\nwrap_diff = true\nwrap_diff = false\n").is_err());
+        assert!(parse("[ui]\n[ui]\n").is_err());
+    }
+
+    #[test]
+    fn test_features_env_only_disables() {
+        let lookup = |var: &str| match var {
+            "LZGIT_NO_SHELL" => Some("1".to_string()),
+            "LZGIT_NO_NETWORK" => Some("0".to_string()),
+            _ => None,
+        };
+        let env = Features::from_env(lookup);
+        assert!(env.network && !env.shell);
+
+        let cfg = parse("[features]\nnetwork = false\n").unwrap();
+        let merged = cfg.features.restrict(env);
+        assert!(!merged.network && !merged.shell && !merged.terminal);
+        assert!(
+            Features::from_env(|_| None)
+                .restrict(Features::default())
+                .terminal
+        );
+    }
+
+    #[test]
+    fn test_confirm_modes() {
+        let cfg = parse("[confirm]\npush = \"smart\"\ndiscard = \"never\"\n").unwrap();
+        assert_eq!(cfg.confirm.mode(ConfirmKind::Push), ConfirmMode::Smart);
+        assert_eq!(cfg.confirm.mode(ConfirmKind::Discard), ConfirmMode::Never);
+        assert!(!cfg.confirm.double_press);
+        assert!(
+            parse("[confirm]\ndouble_press = true\n")
+                .unwrap()
+                .confirm
+                .double_press
+        );
+
+        let errors = parse("[confirm]\npush = \"maybe\"\nrebase = \"never\"\n").unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[1].message.contains("rebase"));
+    }
+
+    #[test]
+    fn test_clipboard_order() {
+        let cfg =
+            parse("[clipboard]\norder = \"osc52, command\"\ncommand = \"wl-copy\"\n").unwrap();
+        assert_eq!(
+            cfg.clipboard.order,
+            Some(vec![ClipboardMethod::Osc52, ClipboardMethod::Command])
+        );
+        assert_eq!(cfg.clipboard.command.as_deref(), Some("wl-copy"));
+
+        let errors = parse("[clipboard]\norder = \"system, xsel\"\n").unwrap_err();
+        assert!(errors[0].message.contains("xsel"));
+    }
+
+    #[test]
+    fn test_timeouts() {
+        let cfg = parse("[timeouts]\nlocal = 0\nnetwork = 90\n").unwrap();
+        assert_eq!(cfg.timeouts.local, Some(0));
+        assert_eq!(cfg.timeouts.network, Some(90));
+
+        let errors = parse("[timeouts]\nnetwork = -1\n").unwrap_err();
+        assert_eq!(errors[0].line, 2);
+    }
+
+    #[test]
+    fn test_privacy() {
+        let cfg = parse("[privacy]\nlock_after = 5\npassphrase_env = \"LOCK\"\n").unwrap();
+        assert_eq!(cfg.privacy.lock_after, Some(5));
+        assert_eq!(cfg.privacy.passphrase_env.as_deref(), Some("LOCK"));
+
+        let errors = parse("[privacy]\nlock_after = \"5m\"\nblur = true\n").unwrap_err();
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_forge() {
+        let cfg = parse("[forge]\nprovider = \"gitlab\"\ntoken_env = \"CI_TOKEN\"\n").unwrap();
+        assert_eq!(cfg.forge.provider, Some(ForgeKind::GitLab));
+        assert_eq!(cfg.forge.token_env.as_deref(), Some("CI_TOKEN"));
+
+        let errors = parse("[forge]\nprovider = \"bitbucket\"\n").unwrap_err();
+        assert!(errors[0].message.contains("bitbucket"));
+    }
+
+    #[test]
+    fn test_syntax_colors() {
+        let cfg = parse("[syntax.nord]\ncomment = \"#7B88a1\"\nkeyword = \"#81a1c1\"\n").unwrap();
+        assert_eq!(
+            cfg.syntax,
+            vec![
+                (
+                    Theme::Nord,
+                    SyntaxRole::Comment,
+                    Color::Rgb(0x7b, 0x88, 0xa1)
+                ),
+                (
+                    Theme::Nord,
+                    SyntaxRole::Keyword,
+                    Color::Rgb(0x81, 0xa1, 0xc1)
+                ),
+            ]
+        );
+
+        let src = "[syntax.nord]\ncomment = \"grey\"\nbogus = \"#000000\"\n\
+                   [syntax.solarized]\ntext = \"#ffffff\"\n";
+        let errors = parse(src).unwrap_err();
+        assert_eq!(
+            errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![2, 3, 5]
+        );
+    }
+
+    #[test]
+    fn test_key_spec_parse() {
+        assert_eq!(KeySpec::parse("Q"), Some(KeySpec::char('Q')));
+        assert_eq!(KeySpec::parse("Ctrl+p"), Some(KeySpec::ctrl('p')));
+        assert_eq!(
+            KeySpec::parse("ctrl++").map(|k| k.code),
+            Some(KeyCode::Char('+'))
+        );
+        assert_eq!(KeySpec::parse("hyper+x"), None);
+        assert_eq!(KeySpec::parse("ab"), None);
+        assert_eq!(KeySpec::ctrl('p').to_string(), "ctrl+p");
+        assert_eq!(KeySpec::char(' ').to_string(), "space");
+    }
+
+    #[test]
+    fn test_keymap_tables() {
+        let cfg = parse("[keys.git]\npush = \"P\"\n[keys.global]\nfetch = \"ctrl+f\"\n").unwrap();
+        assert_eq!(
+            cfg.keymap.effective(KeyScope::Git, &AppAction::GitPush),
+            vec![KeySpec::char('P')]
+        );
+        assert_eq!(
+            cfg.keymap.effective(KeyScope::Global, &AppAction::GitFetch),
+            vec![KeySpec::ctrl('f')]
+        );
+
+        let errors = parse(
+            "[keys]\nquit = \"x\"\n[keys.git]\nlaunch = \"x\"\n[keys.terminal]\nfetch = \"f\"\n",
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].message.contains("[keys.global]"));
+        assert!(errors[1].message.contains("launch"));
+        assert!(errors[2].message.contains("keys.terminal"));
+    }
+}
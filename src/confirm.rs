@@ -0,0 +1,200 @@
+//! Confirmation prompts and how eagerly to show them
+//!
+//! Each risky action has a [`ConfirmMode`] set under `[confirm]` in `config.toml`:
+//! `always` asks every time, `never` runs straight away, and `smart` asks only when
+//! this particular use can lose work:
+//!
+//! - `discard`: untracked files, or more than one file
+//! - `delete`: folders and non-empty files
+//! - `stash_pop`, `checkout`: when the working tree has changes
+//! - `stash_drop`: always, a dropped stash is hard to get back
+//! - `push`: pushing `main` or `master`
+//! - `hard_reset`: when the working tree has changes
+//!
+//! With `double_press = true`, discard, stash drop and hard reset skip the modal: the
+//! first press arms the action and a second press within [`ARM_WINDOW`] runs it.
+
+use std::time::{Duration, Instant};
+
+/// How long a first press of a dangerous key stays armed
+pub const ARM_WINDOW: Duration = Duration::from_millis(1500);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfirmMode {
+    Always,
+    Never,
+    Smart,
+}
+
+impl ConfirmMode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "always" => Some(ConfirmMode::Always),
+            "never" => Some(ConfirmMode::Never),
+            "smart" => Some(ConfirmMode::Smart),
+            _ => None,
+        }
+    }
+
+    /// Whether to prompt, given whether this use of the action can lose work.
+    pub fn should_ask(self, risky: bool) -> bool {
+        match self {
+            ConfirmMode::Always => true,
+            ConfirmMode::Never => false,
+            ConfirmMode::Smart => risky,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfirmKind {
+    Discard,
+    Delete,
+    StashPop,
+    StashDrop,
+    Checkout,
+    Push,
+    HardReset,
+}
+
+impl ConfirmKind {
+    pub const ALL: [ConfirmKind; 7] = [
+        ConfirmKind::Discard,
+        ConfirmKind::Delete,
+        ConfirmKind::StashPop,
+        ConfirmKind::StashDrop,
+        ConfirmKind::Checkout,
+        ConfirmKind::Push,
+        ConfirmKind::HardReset,
+    ];
+
+    /// Key under `[confirm]`
+    pub fn key(self) -> &'static str {
+        match self {
+            ConfirmKind::Discard => "discard",
+            ConfirmKind::Delete => "delete",
+            ConfirmKind::StashPop => "stash_pop",
+            ConfirmKind::StashDrop => "stash_drop",
+            ConfirmKind::Checkout => "checkout",
+            ConfirmKind::Push => "push",
+            ConfirmKind::HardReset => "hard_reset",
+        }
+    }
+
+    /// Actions bound to a single key, which `double_press` arms instead of asking
+    pub fn double_pressable(self) -> bool {
+        matches!(
+            self,
+            ConfirmKind::Discard | ConfirmKind::StashDrop | ConfirmKind::HardReset
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConfirmSettings {
+    modes: [ConfirmMode; 7],
+    /// Press dangerous keys twice instead of answering a modal
+    pub double_press: bool,
+}
+
+impl Default for ConfirmSettings {
+    fn default() -> Self {
+        let mut settings = Self {
+            modes: [ConfirmMode::Always; 7],
+            double_press: false,
+        };
+        settings.set(ConfirmKind::Checkout, ConfirmMode::Smart);
+        settings.set(ConfirmKind::Push, ConfirmMode::Never);
+        settings
+    }
+}
+
+impl ConfirmSettings {
+    pub fn mode(&self, kind: ConfirmKind) -> ConfirmMode {
+        self.modes[kind as usize]
+    }
+
+    pub fn set(&mut self, kind: ConfirmKind, mode: ConfirmMode) {
+        self.modes[kind as usize] = mode;
+    }
+}
+
+/// A question waiting for yes/no, and the action to run on yes.
+#[derive(Clone, Debug)]
+pub struct ConfirmDialog<A> {
+    pub title: String,
+    pub lines: Vec<String>,
+    /// Label of the confirm button, e.g. "Discard"
+    pub confirm_label: String,
+    pub action: A,
+}
+
+/// First press of a double-press action, waiting for the second
+#[derive(Clone, Debug)]
+pub struct Armed {
+    pub kind: ConfirmKind,
+    /// What the press targets, so pressing on another file arms again instead of running
+    pub target: String,
+    /// Shown in the footer while armed, e.g. "discard src/main.rs"
+    pub label: String,
+    pub at: Instant,
+}
+
+impl Armed {
+    pub fn new(kind: ConfirmKind, target: String, label: String) -> Self {
+        Self {
+            kind,
+            target,
+            label,
+            at: Instant::now(),
+        }
+    }
+
+    pub fn is_live(&self) -> bool {
+        self.at.elapsed() < ARM_WINDOW
+    }
+
+    /// Whether a press of `kind` on `target` is the second press.
+    pub fn fires(&self, kind: ConfirmKind, target: &str) -> bool {
+        self.is_live() && self.kind == kind && self.target == target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_match_previous_prompts() {
+        let settings = ConfirmSettings::default();
+        assert_eq!(settings.mode(ConfirmKind::Discard), ConfirmMode::Always);
+        assert!(!settings.mode(ConfirmKind::Checkout).should_ask(false));
+        assert!(settings.mode(ConfirmKind::Checkout).should_ask(true));
+        assert!(!settings.mode(ConfirmKind::Push).should_ask(true));
+        assert!(settings.mode(ConfirmKind::HardReset).should_ask(false));
+    }
+
+    #[test]
+    fn test_set_mode() {
+        let mut settings = ConfirmSettings::default();
+        settings.set(ConfirmKind::StashDrop, ConfirmMode::Never);
+        assert_eq!(settings.mode(ConfirmKind::StashDrop), ConfirmMode::Never);
+        assert_eq!(settings.mode(ConfirmKind::StashPop), ConfirmMode::Always);
+        assert_eq!(ConfirmMode::parse("smart"), Some(ConfirmMode::Smart));
+        assert_eq!(ConfirmMode::parse("sometimes"), None);
+    }
+
+    #[test]
+    fn test_armed_fires_on_same_target() {
+        let armed = Armed::new(ConfirmKind::Discard, "a.rs".into(), "discard a.rs".into());
+        assert!(armed.fires(ConfirmKind::Discard, "a.rs"));
+        assert!(!armed.fires(ConfirmKind::Discard, "b.rs"));
+        assert!(!armed.fires(ConfirmKind::StashDrop, "a.rs"));
+
+        let stale = Armed {
+            at: Instant::now() - ARM_WINDOW,
+            ..armed
+        };
+        assert!(!stale.fires(ConfirmKind::Discard, "a.rs"));
+    }
+}
@@ -78,7 +78,10 @@ pub fn apply_conflict_resolution(
         i += 1;
     }
 
-    let new_text = out.join("\n");
+    let mut new_text = out.join("\n");
+    if text.ends_with('\n') {
+        new_text.push('\n');
+    }
     fs::write(path, new_text).map_err(|e| e.to_string())?;
     Ok(())
 }
@@ -130,3 +133,27 @@ fn parse_blocks(lines: &[String]) -> Vec<ParsedBlock> {
 
     blocks
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolution_keeps_final_newline() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.txt");
+        fs::write(
+            &path,
+            "top\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> b\n",
+        )
+        .unwrap();
+
+        apply_conflict_resolution(&path, 0, ConflictResolution::Theirs).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "top\ntheirs\n");
+
+        fs::write(&path, "<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> b").unwrap();
+        apply_conflict_resolution(&path, 0, ConflictResolution::Ours).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "ours");
+    }
+}
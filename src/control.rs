@@ -0,0 +1,291 @@
+//! Local control socket for driving a running lzgit from editors and scripts.
+//!
+//! When started with `--socket <path>` (or `LZGIT_SOCKET`), lzgit listens on a unix
+//! socket for newline-delimited JSON-RPC 2.0 requests:
+//!
+//! - `focus_file` `{"path": "src/main.rs", "line": 42}` selects the file in the Git tab
+//! - `show_commit` `{"rev": "HEAD~2"}` selects the commit in the Log tab
+//! - `refresh` reloads git state
+//!
+//! `lzgit --select <path>[:line]` sends `focus_file` to a running instance when the
+//! socket is reachable, and otherwise starts with that file selected.
+//!
+//! Every request gets one response line once it has been queued for the UI.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+
+/// A request from the control socket, handled on the UI loop.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ControlRequest {
+    FocusFile { path: String, line: Option<u32> },
+    ShowCommit { rev: String },
+    Refresh,
+}
+
+/// Listening control socket; the socket file is removed on drop.
+pub struct ControlServer {
+    path: PathBuf,
+}
+
+impl ControlServer {
+    /// Bind the socket and spawn the accept loop. Requests arrive on `tx`.
+    #[cfg(unix)]
+    pub fn start(path: &Path, tx: mpsc::UnboundedSender<ControlRequest>) -> std::io::Result<Self> {
+        remove_stale_socket(path)?;
+        let listener = tokio::net::UnixListener::bind(path)?;
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_connection(stream, tx.clone()));
+            }
+        });
+
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn start(
+        _path: &Path,
+        _tx: mpsc::UnboundedSender<ControlRequest>,
+    ) -> std::io::Result<Self> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "control socket requires unix domain sockets",
+        ))
+    }
+}
+
+/// A socket left behind by a crashed instance would make bind fail, so it is
+/// removed; anything else at `path`, or a socket that still answers, is an error.
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> std::io::Result<()> {
+    use std::io::{Error, ErrorKind};
+    use std::os::unix::fs::FileTypeExt;
+
+    let meta = match std::fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !meta.file_type().is_socket() {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        ));
+    }
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(Error::new(
+            ErrorKind::AddrInUse,
+            format!("{} is in use by another instance", path.display()),
+        ));
+    }
+    std::fs::remove_file(path)
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(unix)]
+async fn serve_connection(
+    stream: tokio::net::UnixStream,
+    tx: mpsc::UnboundedSender<ControlRequest>,
+) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (read, mut write) = stream.into_split();
+    let mut lines = BufReader::new(read).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match parse_request(&line) {
+            Ok((id, req)) => {
+                if tx.send(req).is_err() {
+                    break;
+                }
+                json!({ "jsonrpc": "2.0", "id": id, "result": "ok" })
+            }
+            Err(err) => json!({
+                "jsonrpc": "2.0",
+                "id": err.id,
+                "error": { "code": err.code, "message": err.message },
+            }),
+        };
+
+        let mut out = response.to_string();
+        out.push('\n');
+        if write.write_all(out.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// A JSON-RPC error response for a rejected request.
+#[derive(Debug)]
+pub struct RpcError {
+    pub id: Value,
+    pub code: i64,
+    pub message: String,
+}
+
+/// Parse one JSON-RPC request line into its id and request.
+pub fn parse_request(line: &str) -> Result<(Value, ControlRequest), RpcError> {
+    let msg: Value = serde_json::from_str(line).map_err(|e| RpcError {
+        id: Value::Null,
+        code: -32700,
+        message: format!("Parse error: {}", e),
+    })?;
+    let id = msg.get("id").cloned().unwrap_or(Value::Null);
+    let err = |code: i64, message: String| RpcError {
+        id: id.clone(),
+        code,
+        message,
+    };
+
+    let Some(method) = msg.get("method").and_then(Value::as_str) else {
+        return Err(err(-32600, "Invalid request".to_string()));
+    };
+    let params = msg.get("params").cloned().unwrap_or(Value::Null);
+    let str_param = |name: &str| {
+        params
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| err(-32602, format!("Missing param: {}", name)))
+    };
+
+    let req = match method {
+        "focus_file" => ControlRequest::FocusFile {
+            path: str_param("path")?,
+            line: params
+                .get("line")
+                .and_then(Value::as_u64)
+                .and_then(|l| u32::try_from(l).ok()),
+        },
+        "show_commit" => ControlRequest::ShowCommit {
+            rev: str_param("rev")?,
+        },
+        "refresh" => ControlRequest::Refresh,
+        _ => return Err(err(-32601, format!("Method not found: {}", method))),
+    };
+    Ok((id, req))
+}
+
+/// Split a `--select` argument of the form `path[:line]`.
+pub fn parse_select(arg: &str) -> (String, Option<u32>) {
+    if let Some((path, line)) = arg.rsplit_once(':')
+        && !path.is_empty()
+        && let Ok(line) = line.parse::<u32>()
+    {
+        return (path.to_string(), Some(line));
+    }
+    (arg.to_string(), None)
+}
+
+/// Ask the instance listening on `socket` to focus `path`, waiting for its reply.
+#[cfg(unix)]
+pub fn send_focus_file(socket: &Path, path: &str, line: Option<u32>) -> std::io::Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+
+    let mut stream = std::os::unix::net::UnixStream::connect(socket)?;
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "focus_file",
+        "params": { "path": path, "line": line },
+    });
+    stream.write_all(format!("{}\n", request).as_bytes())?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    let reply: Value = serde_json::from_str(&reply).map_err(std::io::Error::other)?;
+    match reply.get("error") {
+        Some(err) => Err(std::io::Error::other(
+            err.get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("request failed")
+                .to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn send_focus_file(_socket: &Path, _path: &str, _line: Option<u32>) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "control socket requires unix domain sockets",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_focus_file() {
+        let (id, req) = parse_request(
+            r#"{"jsonrpc":"2.0","id":7,"method":"focus_file","params":{"path":"src/a.rs","line":12}}"#,
+        )
+        .unwrap();
+        assert_eq!(id, json!(7));
+        assert_eq!(
+            req,
+            ControlRequest::FocusFile {
+                path: "src/a.rs".to_string(),
+                line: Some(12)
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_method_and_missing_params() {
+        let err = parse_request(r#"{"id":1,"method":"explode"}"#).unwrap_err();
+        assert_eq!(err.code, -32601);
+        let err = parse_request(r#"{"id":2,"method":"show_commit","params":{}}"#).unwrap_err();
+        assert_eq!(err.code, -32602);
+        assert_eq!(err.id, json!(2));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_start_only_replaces_a_stale_socket() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("lzgit.sock");
+        let (tx, _rx) = mpsc::unbounded_channel();
+
+        std::fs::write(&path, "notes").unwrap();
+        assert!(ControlServer::start(&path, tx.clone()).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "notes");
+        std::fs::remove_file(&path).unwrap();
+
+        // Dropping a std listener leaves its socket file behind, like a crash
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        let server = ControlServer::start(&path, tx.clone()).unwrap();
+
+        let err = ControlServer::start(&path, tx).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+        assert!(std::os::unix::net::UnixStream::connect(&path).is_ok());
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_parse_select() {
+        assert_eq!(
+            parse_select("src/a.rs:42"),
+            ("src/a.rs".to_string(), Some(42))
+        );
+        assert_eq!(parse_select("src/a.rs"), ("src/a.rs".to_string(), None));
+        assert_eq!(parse_select("C:odd"), ("C:odd".to_string(), None));
+    }
+}
@@ -0,0 +1,138 @@
+//! Plain text and markdown rendering of the current view for "Export view"
+
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Plain,
+    Markdown,
+}
+
+impl ExportFormat {
+    /// Markdown for `.md`/`.markdown` files, plain text otherwise
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown") => {
+                ExportFormat::Markdown
+            }
+            _ => ExportFormat::Plain,
+        }
+    }
+}
+
+/// Export a diff or `git show` output. In markdown, lines before the first
+/// `diff --git` (the commit header) and the diff itself get separate fences.
+pub fn diff(title: &str, lines: &[String], format: ExportFormat) -> String {
+    if format == ExportFormat::Plain {
+        return lines.join("\n") + "\n";
+    }
+
+    let diff_start = lines
+        .iter()
+        .position(|l| l.starts_with("diff --git "))
+        .unwrap_or(lines.len());
+    let (header, body) = lines.split_at(diff_start);
+
+    let mut out = format!("## {}\n\n", title);
+    if header.iter().any(|l| !l.trim().is_empty()) {
+        out.push_str(&fence("text", header));
+    }
+    if !body.is_empty() {
+        if !header.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&fence("diff", body));
+    }
+    out
+}
+
+/// Export a list view as aligned columns, or as a markdown table.
+pub fn table(title: &str, headers: &[&str], rows: &[Vec<String>], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Plain => {
+            let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+            for row in rows {
+                for (i, cell) in row.iter().enumerate() {
+                    if let Some(w) = widths.get_mut(i) {
+                        *w = (*w).max(cell.chars().count());
+                    }
+                }
+            }
+            let line = |cells: Vec<&str>| -> String {
+                let last = cells.len().saturating_sub(1);
+                cells
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| {
+                        if i == last {
+                            c.to_string()
+                        } else {
+                            format!("{:<w$}", c, w = widths.get(i).copied().unwrap_or(0))
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("  ")
+            };
+            let mut out = line(headers.to_vec()) + "\n";
+            for row in rows {
+                out.push_str(&line(row.iter().map(String::as_str).collect()));
+                out.push('\n');
+            }
+            out
+        }
+        ExportFormat::Markdown => {
+            let cell = |s: &str| s.replace('|', "\\|");
+            let mut out = format!("## {}\n\n", title);
+            out.push_str(&format!("| {} |\n", headers.join(" | ")));
+            out.push_str(&format!("|{}\n", "---|".repeat(headers.len())));
+            for row in rows {
+                let cells: Vec<String> = row.iter().map(|c| cell(c)).collect();
+                out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            }
+            out
+        }
+    }
+}
+
+fn fence(lang: &str, lines: &[String]) -> String {
+    // Use a longer fence when the content itself contains one
+    let ticks = if lines.iter().any(|l| l.contains("```")) {
+        "````"
+    } else {
+        "```"
+    };
+    format!("{}{}\n{}\n{}\n", ticks, lang, lines.join("\n"), ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_diff_splits_header_and_body() {
+        let lines: Vec<String> = ["commit abc", "", "diff --git a/x b/x", "+y"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let out = diff("abc", &lines, ExportFormat::Markdown);
+        assert_eq!(
+            out,
+            "## abc\n\n```text\ncommit abc\n\n```\n\n```diff\ndiff --git a/x b/x\n+y\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_table_escapes_pipes() {
+        let rows = vec![vec!["abc".to_string(), "a | b".to_string()]];
+        let out = table(
+            "History",
+            &["Commit", "Subject"],
+            &rows,
+            ExportFormat::Markdown,
+        );
+        assert_eq!(
+            out,
+            "## History\n\n| Commit | Subject |\n|---|---|\n| abc | a \\| b |\n"
+        );
+    }
+}
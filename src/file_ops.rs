@@ -0,0 +1,403 @@
+//! Undoable Explorer file operations
+//!
+//! Creating, renaming/moving and deleting entries are recorded in a [`FileHistory`]
+//! for the session. Deletes rename the entry into a per-process trash directory so
+//! they can be restored; the trash is emptied on exit. The trash lives in the git
+//! dir of the repository holding the entry, or under the system temp dir outside a
+//! repository, and is never reached by copying: an entry that cannot be renamed
+//! into it fails with [`io::ErrorKind::CrossesDevices`] so the caller can offer a
+//! permanent delete instead.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Operations kept for undo
+const MAX_HISTORY: usize = 50;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileOp {
+    Create {
+        path: PathBuf,
+        is_dir: bool,
+    },
+    /// Rename, or move into another directory
+    Move {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    Trash {
+        path: PathBuf,
+        trashed: PathBuf,
+    },
+}
+
+impl FileOp {
+    pub fn describe(&self) -> String {
+        match self {
+            FileOp::Create { path, .. } => format!("create {}", display_name(path)),
+            FileOp::Move { from, to } if from.parent() == to.parent() => {
+                format!("rename {} → {}", display_name(from), display_name(to))
+            }
+            FileOp::Move { from, to } => {
+                format!("move {} → {}", display_name(from), to.display())
+            }
+            FileOp::Trash { path, .. } => format!("delete {}", display_name(path)),
+        }
+    }
+
+    /// Path worth selecting after the operation is applied.
+    pub fn target(&self) -> &Path {
+        match self {
+            FileOp::Create { path, .. } | FileOp::Trash { path, .. } => path,
+            FileOp::Move { to, .. } => to,
+        }
+    }
+
+    fn apply(&self) -> io::Result<()> {
+        match self {
+            FileOp::Create { path, is_dir } => create(path, *is_dir),
+            FileOp::Move { from, to } => move_path(from, to),
+            FileOp::Trash { path, trashed } => rename_new(path, trashed),
+        }
+    }
+
+    fn revert(&self) -> io::Result<()> {
+        match self {
+            FileOp::Create { path, is_dir } => remove_created(path, *is_dir),
+            FileOp::Move { from, to } => move_path(to, from),
+            FileOp::Trash { path, trashed } => rename_new(trashed, path),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct FileHistory {
+    undo: Vec<FileOp>,
+    redo: Vec<FileOp>,
+}
+
+impl FileHistory {
+    /// Remember an operation that has just been done; clears the redo stack.
+    pub fn record(&mut self, op: FileOp) {
+        self.redo.clear();
+        self.undo.push(op);
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Revert the most recent operation. `Ok(None)` when there is nothing to undo.
+    pub fn undo(&mut self) -> Result<Option<FileOp>, String> {
+        let Some(op) = self.undo.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = op.revert() {
+            let msg = format!("Undo {} failed: {}", op.describe(), e);
+            self.undo.push(op);
+            return Err(msg);
+        }
+        self.redo.push(op.clone());
+        Ok(Some(op))
+    }
+
+    /// Re-apply the most recently undone operation.
+    pub fn redo(&mut self) -> Result<Option<FileOp>, String> {
+        let Some(op) = self.redo.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = op.apply() {
+            let msg = format!("Redo {} failed: {}", op.describe(), e);
+            self.redo.push(op);
+            return Err(msg);
+        }
+        self.undo.push(op.clone());
+        Ok(Some(op))
+    }
+}
+
+/// Create an empty file or directory, failing if something is already there.
+pub fn create(path: &Path, is_dir: bool) -> io::Result<()> {
+    if is_dir {
+        fs::create_dir(path)
+    } else {
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .map(|_| ())
+    }
+}
+
+/// Rename `from` to `to`, copying across filesystems. Never overwrites.
+pub fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    ensure_free(to)?;
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_recursive(from, to)?;
+    remove_entry(from)
+}
+
+/// Rename without overwriting and without falling back to a copy.
+fn rename_new(from: &Path, to: &Path) -> io::Result<()> {
+    ensure_free(to)?;
+    fs::rename(from, to)
+}
+
+fn ensure_free(to: &Path) -> io::Result<()> {
+    if to.symlink_metadata().is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", to.display()),
+        ));
+    }
+    Ok(())
+}
+
+/// Copy without following symlinks, keeping permissions.
+fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    let meta = from.symlink_metadata()?;
+    if meta.file_type().is_symlink() {
+        copy_symlink(from, to)
+    } else if meta.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, meta.permissions())
+    } else {
+        // Copies the permission bits along with the contents
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(windows)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    let target = fs::read_link(from)?;
+    if fs::metadata(from).is_ok_and(|m| m.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, to)
+    } else {
+        std::os::windows::fs::symlink_file(target, to)
+    }
+}
+
+/// Remove a file, symlink or whole directory; symlinks are removed, not followed.
+pub fn remove_entry(path: &Path) -> io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Undoing a create only removes the entry while it is still empty.
+fn remove_created(path: &Path, is_dir: bool) -> io::Result<()> {
+    if is_dir {
+        // Fails on its own when the directory is no longer empty
+        fs::remove_dir(path)
+    } else if fs::metadata(path)?.len() > 0 {
+        Err(io::Error::other("file has content now"))
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Trash directories used this session, emptied by [`clear_trash`]
+static TRASH_DIRS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Session trash directory for `path`: in the git dir of the repository holding it,
+/// so the move is a rename on the same filesystem, or under the system temp dir.
+pub fn trash_dir(path: &Path) -> PathBuf {
+    let name = format!("lzgit-trash-{}", std::process::id());
+    match git_dir_of(path) {
+        Some(git_dir) => git_dir.join(name),
+        None => std::env::temp_dir().join(name),
+    }
+}
+
+/// Git dir of the work tree holding `path`, following `.git` files of worktrees
+/// and submodules. Entries inside a git dir have none.
+fn git_dir_of(path: &Path) -> Option<PathBuf> {
+    if path
+        .ancestors()
+        .any(|p| p.file_name().is_some_and(|n| n == ".git"))
+    {
+        return None;
+    }
+    for dir in path.ancestors().skip(1) {
+        let dot_git = dir.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        if let Ok(text) = fs::read_to_string(&dot_git) {
+            let target = text.strip_prefix("gitdir:")?.trim();
+            return Some(dir.join(target));
+        }
+    }
+    None
+}
+
+/// Move `path` into the session trash and return the operation to record.
+///
+/// Only renames: fails with [`io::ErrorKind::CrossesDevices`] when the trash is on
+/// another filesystem rather than copying the entry.
+pub fn trash(path: &Path) -> io::Result<FileOp> {
+    let dir = trash_dir(path);
+    fs::create_dir_all(&dir)?;
+    if let Ok(mut dirs) = TRASH_DIRS.lock()
+        && !dirs.contains(&dir)
+    {
+        dirs.push(dir.clone());
+    }
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut n = 0;
+    let trashed = loop {
+        let candidate = dir.join(format!("{}-{}", n, name));
+        if candidate.symlink_metadata().is_err() {
+            break candidate;
+        }
+        n += 1;
+    };
+    fs::rename(path, &trashed)?;
+    Ok(FileOp::Trash {
+        path: path.to_path_buf(),
+        trashed,
+    })
+}
+
+/// Empty the session trash; deletes become permanent.
+pub fn clear_trash() {
+    if let Ok(mut dirs) = TRASH_DIRS.lock() {
+        for dir in dirs.drain(..) {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_undo_redo_rename() {
+        let dir = TempDir::new().unwrap();
+        let from = dir.path().join("a.txt");
+        let to = dir.path().join("b.txt");
+        fs::write(&from, "hi").unwrap();
+
+        let mut history = FileHistory::default();
+        move_path(&from, &to).unwrap();
+        history.record(FileOp::Move {
+            from: from.clone(),
+            to: to.clone(),
+        });
+
+        assert!(history.undo().unwrap().is_some());
+        assert!(from.exists() && !to.exists());
+        assert!(history.redo().unwrap().is_some());
+        assert!(!from.exists() && to.exists());
+        assert_eq!(history.redo().unwrap(), None);
+    }
+
+    #[test]
+    fn test_undo_trash_restores() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("sub");
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("f"), "x").unwrap();
+
+        let mut history = FileHistory::default();
+        history.record(trash(&path).unwrap());
+        assert!(!path.exists());
+
+        history.undo().unwrap();
+        assert_eq!(fs::read_to_string(path.join("f")).unwrap(), "x");
+    }
+
+    #[test]
+    fn test_trash_stays_in_the_git_dir() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        let path = dir.path().join("src/main.rs");
+        fs::write(&path, "fn main() {}").unwrap();
+
+        let op = trash(&path).unwrap();
+        let FileOp::Trash { trashed, .. } = &op else {
+            panic!("{:?}", op);
+        };
+        assert!(trashed.starts_with(dir.path().join(".git")));
+        assert!(!path.exists());
+        op.revert().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fn main() {}");
+        assert_eq!(git_dir_of(&dir.path().join(".git/config")), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_keeps_symlinks_and_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let outside = dir.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+        fs::write(outside.join("big"), "not copied").unwrap();
+        let from = dir.path().join("from");
+        fs::create_dir(&from).unwrap();
+        fs::write(from.join("run.sh"), "#!/bin/sh").unwrap();
+        fs::set_permissions(from.join("run.sh"), fs::Permissions::from_mode(0o750)).unwrap();
+        std::os::unix::fs::symlink(&outside, from.join("link")).unwrap();
+        fs::set_permissions(&from, fs::Permissions::from_mode(0o700)).unwrap();
+
+        let to = dir.path().join("to");
+        copy_recursive(&from, &to).unwrap();
+        let link = to.join("link").symlink_metadata().unwrap();
+        assert!(link.file_type().is_symlink());
+        assert_eq!(fs::read_link(to.join("link")).unwrap(), outside);
+        let mode = |p: &Path| fs::metadata(p).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&to.join("run.sh")), 0o750);
+        assert_eq!(mode(&to), 0o700);
+
+        remove_entry(&from).unwrap();
+        assert!(outside.join("big").exists());
+    }
+
+    #[test]
+    fn test_undo_create_keeps_edited_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("new.txt");
+        create(&path, false).unwrap();
+        assert!(create(&path, false).is_err());
+
+        let mut history = FileHistory::default();
+        history.record(FileOp::Create {
+            path: path.clone(),
+            is_dir: false,
+        });
+        fs::write(&path, "edited").unwrap();
+
+        assert!(history.undo().is_err());
+        assert!(path.exists());
+        fs::write(&path, "").unwrap();
+        history.undo().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,375 @@
+//! CI status of commits from the forge's API
+//!
+//! With `[forge] provider` set, History shows whether each commit on the current
+//! branch passed CI. The project and API endpoint come from the remote's web URL
+//! (see [`crate::git_ops::web_url`]) unless `api_url` overrides the endpoint; the
+//! token is read from `token_env`, or `GITHUB_TOKEN` / `GITLAB_TOKEN` / `GITEA_TOKEN`.
+//! Public projects work without one, within the forge's rate limit.
+//!
+//! The remote decides where requests go, so the token is only sent when its host is
+//! the provider's public one (github.com, gitlab.com, codeberg.org) or the host of
+//! `api_url`. APIs that are not https are not asked at all, except on loopback.
+
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::config::ForgeConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForgeKind {
+    GitHub,
+    GitLab,
+    Gitea,
+}
+
+impl ForgeKind {
+    pub fn parse(name: &str) -> Option<ForgeKind> {
+        match name {
+            "github" => Some(ForgeKind::GitHub),
+            "gitlab" => Some(ForgeKind::GitLab),
+            "gitea" | "forgejo" => Some(ForgeKind::Gitea),
+            _ => None,
+        }
+    }
+
+    /// Host of the public instance, whose remotes get the token without `api_url`
+    fn public_host(self) -> &'static str {
+        match self {
+            ForgeKind::GitHub => "github.com",
+            ForgeKind::GitLab => "gitlab.com",
+            ForgeKind::Gitea => "codeberg.org",
+        }
+    }
+
+    fn token_env(self) -> &'static str {
+        match self {
+            ForgeKind::GitHub => "GITHUB_TOKEN",
+            ForgeKind::GitLab => "GITLAB_TOKEN",
+            ForgeKind::Gitea => "GITEA_TOKEN",
+        }
+    }
+}
+
+/// Outcome of a commit's checks, ordered so the worst one wins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CiStatus {
+    Success,
+    Pending,
+    Failure,
+}
+
+impl CiStatus {
+    pub fn icon(self) -> &'static str {
+        match self {
+            CiStatus::Success => "✓",
+            CiStatus::Pending => "●",
+            CiStatus::Failure => "✗",
+        }
+    }
+}
+
+/// One project on a forge, ready to be asked about commits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Forge {
+    pub kind: ForgeKind,
+    /// API root without a trailing slash
+    pub api: String,
+    /// `owner/repo`, or the full group path on GitLab
+    pub project: String,
+    pub token: Option<String>,
+}
+
+impl Forge {
+    /// The project behind `web` (`https://host/owner/repo`); `None` when no provider
+    /// is configured, the URL has no project path or the API is not https.
+    pub fn new(
+        web: &str,
+        cfg: &ForgeConfig,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Option<Forge> {
+        let kind = cfg.provider?;
+        let (scheme, rest) = web.split_once("://")?;
+        let (host, project) = rest.split_once('/')?;
+        let project = project.trim_end_matches('/');
+        if project.is_empty() {
+            return None;
+        }
+        let api = match &cfg.api_url {
+            Some(api) => api.trim_end_matches('/').to_string(),
+            None => match kind {
+                ForgeKind::GitHub if host == "github.com" => "https://api.github.com".to_string(),
+                ForgeKind::GitHub => format!("{}://{}/api/v3", scheme, host),
+                ForgeKind::GitLab => format!("{}://{}/api/v4", scheme, host),
+                ForgeKind::Gitea => format!("{}://{}/api/v1", scheme, host),
+            },
+        };
+        let (api_scheme, api_host) = url_host(&api)?;
+        if api_scheme != "https" && !is_loopback(api_host) {
+            return None;
+        }
+        let web_host = url_host(web)?.1;
+        let trusted = match &cfg.api_url {
+            Some(_) => web_host == api_host,
+            None => web_host == kind.public_host(),
+        };
+        let token_env = cfg.token_env.as_deref().unwrap_or(kind.token_env());
+        Some(Forge {
+            kind,
+            api,
+            project: project.to_string(),
+            token: lookup(token_env).filter(|t| trusted && !t.is_empty()),
+        })
+    }
+
+    /// CI status of `sha`; `None` when the forge ran nothing for it or does not
+    /// know the commit yet.
+    pub fn commit_status(&self, sha: &str) -> Result<Option<CiStatus>, String> {
+        match self.kind {
+            ForgeKind::GitHub => {
+                let base = format!("{}/repos/{}/commits/{}", self.api, self.project, sha);
+                let statuses = self.get(&format!("{}/status", base))?;
+                let checks = self.get(&format!("{}/check-runs", base))?;
+                Ok(worst([
+                    statuses.as_ref().and_then(combined_status),
+                    checks.as_ref().and_then(check_runs_status),
+                ]))
+            }
+            ForgeKind::GitLab => {
+                let url = format!(
+                    "{}/projects/{}/repository/commits/{}",
+                    self.api,
+                    self.project.replace('/', "%2F"),
+                    sha
+                );
+                Ok(self.get(&url)?.as_ref().and_then(pipeline_status))
+            }
+            ForgeKind::Gitea => {
+                let url = format!("{}/repos/{}/commits/{}/status", self.api, self.project, sha);
+                Ok(self.get(&url)?.as_ref().and_then(combined_status))
+            }
+        }
+    }
+
+    /// GET `url` as JSON; `None` when the forge does not know the commit.
+    fn get(&self, url: &str) -> Result<Option<Value>, String> {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(10))
+            .timeout_read(Duration::from_secs(20))
+            .build();
+        let mut request = agent.get(url).set("Accept", "application/json");
+        if let Some(token) = &self.token {
+            request = match self.kind {
+                ForgeKind::GitHub => request.set("Authorization", &format!("Bearer {}", token)),
+                ForgeKind::GitLab => request.set("PRIVATE-TOKEN", token),
+                ForgeKind::Gitea => request.set("Authorization", &format!("token {}", token)),
+            };
+        }
+        match request.call() {
+            Ok(res) => res.into_json().map(Some).map_err(|e| e.to_string()),
+            Err(ureq::Error::Status(404 | 422, _)) => Ok(None),
+            Err(ureq::Error::Status(code, _)) => Err(format!("forge API HTTP {}", code)),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// Scheme and host of `url`, without user info or port.
+fn url_host(url: &str) -> Option<(&str, &str)> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split('/').next()?;
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match authority.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => authority,
+    };
+    Some((scheme, host.trim_start_matches('[').trim_end_matches(']')))
+}
+
+fn is_loopback(host: &str) -> bool {
+    host == "localhost"
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+fn worst(statuses: impl IntoIterator<Item = Option<CiStatus>>) -> Option<CiStatus> {
+    statuses.into_iter().flatten().max()
+}
+
+/// Commit statuses (GitHub's combined status, Gitea's status endpoint)
+fn combined_status(v: &Value) -> Option<CiStatus> {
+    if v["total_count"].as_u64() == Some(0) {
+        return None;
+    }
+    match v["state"].as_str()? {
+        "success" | "warning" => Some(CiStatus::Success),
+        "failure" | "error" => Some(CiStatus::Failure),
+        "pending" => Some(CiStatus::Pending),
+        _ => None,
+    }
+}
+
+/// GitHub check runs, where Actions report
+fn check_runs_status(v: &Value) -> Option<CiStatus> {
+    worst(v["check_runs"].as_array()?.iter().map(|run| {
+        if run["status"].as_str() != Some("completed") {
+            return Some(CiStatus::Pending);
+        }
+        match run["conclusion"].as_str()? {
+            "success" | "neutral" | "skipped" => Some(CiStatus::Success),
+            "stale" => None,
+            _ => Some(CiStatus::Failure),
+        }
+    }))
+}
+
+/// The last GitLab pipeline of a commit
+fn pipeline_status(v: &Value) -> Option<CiStatus> {
+    match v["last_pipeline"]["status"].as_str()? {
+        "success" => Some(CiStatus::Success),
+        "failed" | "canceled" => Some(CiStatus::Failure),
+        "skipped" | "manual" => None,
+        _ => Some(CiStatus::Pending),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_forge_from_web_url() {
+        let cfg = ForgeConfig {
+            provider: Some(ForgeKind::GitHub),
+            ..ForgeConfig::default()
+        };
+        let token = |var: &str| (var == "GITHUB_TOKEN").then(|| "secret".to_string());
+        let forge = Forge::new("https://github.com/me/tool", &cfg, token).unwrap();
+        assert_eq!(forge.api, "https://api.github.com");
+        assert_eq!(forge.project, "me/tool");
+        assert_eq!(forge.token.as_deref(), Some("secret"));
+        let enterprise = Forge::new("https://git.corp/me/tool", &cfg, |_| None).unwrap();
+        assert_eq!(enterprise.api, "https://git.corp/api/v3");
+        assert_eq!(enterprise.token, None);
+
+        let cfg = ForgeConfig {
+            provider: Some(ForgeKind::GitLab),
+            api_url: Some("http://127.0.0.1:8080/api/v4/".to_string()),
+            token_env: Some("MY_TOKEN".to_string()),
+        };
+        let token = |var: &str| (var == "MY_TOKEN").then(|| "t".to_string());
+        let forge = Forge::new("https://127.0.0.1/group/sub/tool", &cfg, token).unwrap();
+        assert_eq!(forge.api, "http://127.0.0.1:8080/api/v4");
+        assert_eq!(forge.project, "group/sub/tool");
+        assert_eq!(forge.token.as_deref(), Some("t"));
+
+        assert_eq!(Forge::new("https://host", &cfg, |_| None), None);
+        assert_eq!(
+            Forge::new(
+                "https://github.com/me/tool",
+                &ForgeConfig::default(),
+                |_| None
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_token_only_goes_to_the_forge_host() {
+        let cfg = ForgeConfig {
+            provider: Some(ForgeKind::GitHub),
+            ..ForgeConfig::default()
+        };
+        let token = |_: &str| Some("secret".to_string());
+        // The remote picks the API host, so one elsewhere gets no token
+        let forge = Forge::new("https://evil.example/me/tool", &cfg, token).unwrap();
+        assert_eq!(forge.api, "https://evil.example/api/v3");
+        assert_eq!(forge.token, None);
+        assert_eq!(Forge::new("http://evil.example/me/tool", &cfg, token), None);
+
+        let cfg = ForgeConfig {
+            api_url: Some("https://git.corp/api/v3".to_string()),
+            ..cfg
+        };
+        let forge = Forge::new("https://git.corp/me/tool", &cfg, token).unwrap();
+        assert_eq!(forge.token.as_deref(), Some("secret"));
+        let forge = Forge::new("https://github.com/me/tool", &cfg, token).unwrap();
+        assert_eq!(forge.token, None);
+    }
+
+    /// Headers of the one request a forge sends to a local server.
+    fn request_headers(web: &str) -> String {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let cfg = ForgeConfig {
+            provider: Some(ForgeKind::Gitea),
+            api_url: Some(format!("http://{}", listener.local_addr().unwrap())),
+            token_env: None,
+        };
+        let forge = Forge::new(web, &cfg, |_| Some("secret".to_string())).unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut headers = String::new();
+            for line in BufReader::new(&stream).lines() {
+                let line = line.unwrap();
+                if line.is_empty() {
+                    break;
+                }
+                headers.push_str(&line);
+                headers.push('\n');
+            }
+            let body = r#"{"state":"success","total_count":1}"#;
+            let _ = write!(
+                &stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            headers.to_lowercase()
+        });
+        assert_eq!(forge.commit_status("abc"), Ok(Some(CiStatus::Success)));
+        server.join().unwrap()
+    }
+
+    #[test]
+    fn test_no_auth_header_for_a_foreign_host() {
+        let headers = request_headers("https://git.example.com/me/tool");
+        assert!(!headers.contains("authorization"), "{}", headers);
+        assert!(!headers.contains("secret"), "{}", headers);
+
+        let headers = request_headers("https://127.0.0.1/me/tool");
+        assert!(
+            headers.contains("authorization: token secret"),
+            "{}",
+            headers
+        );
+    }
+
+    #[test]
+    fn test_parse_statuses() {
+        let none = json!({ "state": "pending", "total_count": 0 });
+        assert_eq!(combined_status(&none), None);
+        let failed = json!({ "state": "error", "total_count": 2 });
+        assert_eq!(combined_status(&failed), Some(CiStatus::Failure));
+
+        let checks = json!({ "check_runs": [
+            { "status": "completed", "conclusion": "success" },
+            { "status": "in_progress", "conclusion": null },
+        ] });
+        assert_eq!(check_runs_status(&checks), Some(CiStatus::Pending));
+        assert_eq!(check_runs_status(&json!({ "check_runs": [] })), None);
+        assert_eq!(
+            worst([Some(CiStatus::Success), None, Some(CiStatus::Failure)]),
+            Some(CiStatus::Failure)
+        );
+
+        let pipeline = json!({ "last_pipeline": { "status": "running" } });
+        assert_eq!(pipeline_status(&pipeline), Some(CiStatus::Pending));
+        assert_eq!(pipeline_status(&json!({ "last_pipeline": null })), None);
+    }
+}
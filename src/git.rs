@@ -1,18 +1,36 @@
-use ratatui::widgets::ListState;
+use ratatui::{text::Line, widgets::ListState};
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    hash::{DefaultHasher, Hash, Hasher},
     io,
     path::{Path, PathBuf},
     process::Command,
 };
 use unicode_width::UnicodeWidthChar;
 
+use crate::git_ops::{self, CommitEntry, SubmoduleEntry};
+
+/// Commits listed under the Git tree
+pub const RECENT_COMMITS: usize = 4;
+
+/// Lines of context git shows around a hunk, and how many more `+` adds
+pub const DIFF_CONTEXT: usize = 3;
+pub const DIFF_CONTEXT_STEP: usize = 10;
+
+/// Letters and digits a block needs before it counts as moved, as in git's
+/// `--color-moved`, so stray braces and blank lines don't
+const MOVED_MIN_ALNUM: usize = 20;
+/// Deleted lines tried as the source of a block; bounds the work on diffs full of
+/// repeated lines
+const MOVED_MAX_CANDIDATES: usize = 64;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum GitSection {
     Staged,
     Working,
     Untracked,
     Conflicts,
+    Submodules,
 }
 
 /// Type of node in the flattened tree view
@@ -21,6 +39,8 @@ pub enum FlatNodeType {
     Section,
     Directory,
     File,
+    /// A submodule under the Submodules section; `path` is its path
+    Submodule,
 }
 
 /// Represents a single visible item in the tree view
@@ -35,6 +55,49 @@ pub struct FlatTreeItem {
     pub section: GitSection,
 }
 
+/// Changes under the directory selected in the tree, shown in place of a diff
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirSummary {
+    pub path: String,
+    pub section: GitSection,
+    pub files: Vec<DirSummaryFile>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirSummaryFile {
+    pub path: String,
+    /// Status letter in the directory's section
+    pub status: char,
+    /// `+added/-deleted`; `None` for untracked and binary files
+    pub stats: Option<(u32, u32)>,
+}
+
+impl DirSummary {
+    /// Added and deleted lines over all files
+    pub fn totals(&self) -> (u32, u32) {
+        self.files
+            .iter()
+            .filter_map(|f| f.stats)
+            .fold((0, 0), |(a, d), (fa, fd)| (a + fa, d + fd))
+    }
+}
+
+/// Widths of the `+` and `-` parts of a diffstat bar, scaled like `git diff --stat`
+/// so the largest change (`max` lines) fills `width`.
+pub fn diffstat_bar(added: u32, deleted: u32, max: u32, width: usize) -> (usize, usize) {
+    if max as usize <= width {
+        return (added as usize, deleted as usize);
+    }
+    let scale = |n: u32| {
+        if n == 0 {
+            0
+        } else {
+            (n as usize * width / max as usize).max(1)
+        }
+    };
+    (scale(added), scale(deleted))
+}
+
 /// Internal tree node for building the hierarchy
 #[derive(Clone, Debug)]
 enum TreeNode {
@@ -53,6 +116,9 @@ enum TreeNode {
         name: String,
         entry_idx: usize,
     },
+    Submodule {
+        path: String,
+    },
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -61,6 +127,24 @@ pub enum GitDiffMode {
     Unified,
 }
 
+/// Which version the full file view (`F`) shows; `s` cycles through them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FullFileSource {
+    WorkTree,
+    Head,
+    Both,
+}
+
+impl FullFileSource {
+    pub fn next(self) -> Self {
+        match self {
+            FullFileSource::WorkTree => FullFileSource::Head,
+            FullFileSource::Head => FullFileSource::Both,
+            FullFileSource::Both => FullFileSource::WorkTree,
+        }
+    }
+}
+
 /// A hunk in a diff, used for partial staging/reverting
 #[derive(Clone, Debug)]
 pub struct DiffHunk {
@@ -101,11 +185,25 @@ pub struct GitFileEntry {
 pub struct GitState {
     pub repo_root: Option<PathBuf>,
     pub branch: String,
+    /// Short hash of HEAD when it is detached; `branch` is empty then
+    pub detached: Option<String>,
+    /// Upstream of the current branch, `None` without one or when it is gone
+    pub upstream: Option<String>,
     pub ahead: u32,
     pub behind: u32,
 
     pub section: GitSection,
     pub entries: Vec<GitFileEntry>,
+    /// `+added/-deleted` per path, filled in by the background refresh
+    pub staged_stats: HashMap<String, (u32, u32)>,
+    pub worktree_stats: HashMap<String, (u32, u32)>,
+    /// Latest commits for the strip under the tree, newest first
+    pub recent_commits: Vec<CommitEntry>,
+    pub submodules: Vec<SubmoduleEntry>,
+    /// Strip row holding the selection instead of the tree
+    pub recent_selected: Option<usize>,
+    /// Strip rows that fit on screen at the last render; 0 when hidden
+    pub recent_shown: usize,
     pub filtered: Vec<usize>,
     pub list_state: ListState,
     pub selected_paths: BTreeSet<String>,
@@ -121,15 +219,31 @@ pub struct GitState {
     pub diff_mode: GitDiffMode,
     pub diff_lines: Vec<String>,
     pub diff_hunks: Vec<DiffHunk>,
+    /// Context lines per path, for files expanded beyond [`DIFF_CONTEXT`]
+    pub diff_context: HashMap<String, usize>,
     pub change_blocks: Vec<ChangeBlock>,
     pub diff_scroll_y: u16,
     pub diff_scroll_x: u16,
     pub diff_generation: u64,
     pub diff_request_id: u64,
+    /// Path and content hash of the diff on screen
+    diff_key: Option<(String, u64)>,
+    /// Scroll offsets of the diffs viewed this session, restored when the same
+    /// diff of the same file is shown again
+    diff_scroll_memory: HashMap<(String, u64), (u16, u16)>,
+
+    /// Row picked in the directory summary, see [`GitState::dir_summary`]
+    pub dir_summary_selected: usize,
 
     /// Show full file content instead of diff
     pub show_full_file: bool,
     pub full_file_content: Option<String>,
+    /// The file as committed in HEAD, for `full_file_source` Head and Both
+    pub full_file_head: Option<String>,
+    pub full_file_source: FullFileSource,
+    /// Highlighted working-tree and HEAD lines, filled on first render with
+    /// syntax highlighting on
+    pub full_file_highlighted: Option<(Vec<Line<'static>>, Vec<Line<'static>>)>,
     pub full_file_scroll_y: u16,
 }
 
@@ -140,14 +254,23 @@ impl GitState {
         section_expanded.insert(GitSection::Working, true);
         section_expanded.insert(GitSection::Untracked, true);
         section_expanded.insert(GitSection::Conflicts, true);
+        section_expanded.insert(GitSection::Submodules, true);
 
         Self {
             repo_root: None,
             branch: String::new(),
+            detached: None,
+            upstream: None,
             ahead: 0,
             behind: 0,
             section: GitSection::Working,
             entries: Vec::new(),
+            staged_stats: HashMap::new(),
+            worktree_stats: HashMap::new(),
+            recent_commits: Vec::new(),
+            submodules: Vec::new(),
+            recent_selected: None,
+            recent_shown: 0,
             filtered: Vec::new(),
             list_state: ListState::default(),
             selected_paths: BTreeSet::new(),
@@ -160,13 +283,20 @@ impl GitState {
             diff_mode: GitDiffMode::SideBySide,
             diff_lines: Vec::new(),
             diff_hunks: Vec::new(),
+            diff_context: HashMap::new(),
             change_blocks: Vec::new(),
             diff_scroll_y: 0,
             diff_scroll_x: 0,
             diff_generation: 0,
             diff_request_id: 0,
+            diff_key: None,
+            diff_scroll_memory: HashMap::new(),
+            dir_summary_selected: 0,
             show_full_file: false,
             full_file_content: None,
+            full_file_head: None,
+            full_file_source: FullFileSource::WorkTree,
+            full_file_highlighted: None,
             full_file_scroll_y: 0,
         }
     }
@@ -174,9 +304,12 @@ impl GitState {
     pub fn refresh(&mut self, current_path: &Path) {
         self.repo_root = None;
         self.branch.clear();
+        self.detached = None;
+        self.upstream = None;
         self.ahead = 0;
         self.behind = 0;
         self.entries.clear();
+        self.submodules.clear();
         self.filtered.clear();
         self.list_state.select(None);
         self.selected_paths.clear();
@@ -329,6 +462,12 @@ impl GitState {
             }
             i += 1;
         }
+        // `## HEAD (no branch)`: name the commit instead
+        if self.branch == "HEAD (no branch)" {
+            self.branch.clear();
+            self.detached = Some(git_ops::head_short(&root).unwrap_or_else(|_| "HEAD".into()));
+        }
+        self.submodules = git_ops::list_submodules(&root).unwrap_or_default();
 
         self.update_filtered();
         self.build_tree();
@@ -345,6 +484,34 @@ impl GitState {
         self.diff_scroll_x = 0;
     }
 
+    /// Show the loaded diff of `path` where it was last left, if it has not changed
+    /// since.
+    pub fn restore_diff_scroll(&mut self, path: &str) {
+        let mut hasher = DefaultHasher::new();
+        self.diff_lines.hash(&mut hasher);
+        let key = (path.to_string(), hasher.finish());
+        if let Some(&(y, x)) = self.diff_scroll_memory.get(&key) {
+            self.diff_scroll_y = y;
+            self.diff_scroll_x = x;
+        }
+        self.diff_key = Some(key);
+    }
+
+    /// Record the scroll offset of the diff on screen; called as it is drawn.
+    pub fn note_diff_scroll(&mut self) {
+        if let Some(key) = &self.diff_key {
+            let offset = (self.diff_scroll_y, self.diff_scroll_x);
+            if self.diff_scroll_memory.get(key) != Some(&offset) {
+                self.diff_scroll_memory.insert(key.clone(), offset);
+            }
+        }
+    }
+
+    /// Forget which diff is on screen while another one loads.
+    pub fn clear_diff_key(&mut self) {
+        self.diff_key = None;
+    }
+
     /// Set diff lines and parse hunks for revert functionality
     pub fn set_diff_lines(&mut self, lines: Vec<String>) {
         self.diff_lines = lines;
@@ -598,6 +765,37 @@ impl GitState {
         result
     }
 
+    /// Hunk under the top of the diff viewport for the current diff mode.
+    /// New-file line of the first change in hunk `idx`, which stays put when the
+    /// context around it grows.
+    pub fn hunk_first_change(&self, idx: usize) -> Option<u32> {
+        let hunk = self.diff_hunks.get(idx)?;
+        let (_, mut line) = parse_hunk_header(hunk.lines.first()?)?;
+        for l in &hunk.lines[1..] {
+            if !l.starts_with(' ') {
+                break;
+            }
+            line += 1;
+        }
+        Some(line)
+    }
+
+    pub fn hunk_at_scroll(&self) -> Option<usize> {
+        let row = self.diff_scroll_y as usize;
+        let found = match self.diff_mode {
+            GitDiffMode::Unified => self.hunk_at_display_row(row),
+            GitDiffMode::SideBySide => self
+                .diff_hunks
+                .iter()
+                .rposition(|h| h.sbs_display_row <= row),
+        };
+        found.or(if self.diff_hunks.is_empty() {
+            None
+        } else {
+            Some(0)
+        })
+    }
+
     pub fn selected_entry(&self) -> Option<&GitFileEntry> {
         let sel = self.list_state.selected()?;
         let abs = *self.filtered.get(sel)?;
@@ -615,6 +813,7 @@ impl GitState {
                 GitSection::Staged => staged && !e.is_conflict && !e.is_untracked,
                 GitSection::Untracked => e.is_untracked,
                 GitSection::Conflicts => e.is_conflict,
+                GitSection::Submodules => false,
             };
             if keep {
                 self.filtered.push(idx);
@@ -641,6 +840,7 @@ impl GitState {
         let rest = line.trim_start_matches("## ").trim();
         if rest.is_empty() {
             self.branch.clear();
+            self.upstream = None;
             self.ahead = 0;
             self.behind = 0;
             return;
@@ -652,8 +852,12 @@ impl GitState {
             (rest, None)
         };
 
-        let branch = head.split("...").next().unwrap_or(head).trim().to_string();
-        self.branch = branch;
+        let (branch, upstream) = match head.split_once("...") {
+            Some((branch, upstream)) => (branch, Some(upstream.trim().to_string())),
+            None => (head, None),
+        };
+        self.branch = branch.trim().to_string();
+        self.upstream = upstream.filter(|_| ab_part != Some("gone"));
         self.ahead = 0;
         self.behind = 0;
 
@@ -813,7 +1017,7 @@ impl GitState {
             result
         };
 
-        // Build sections in order: Staged, Changes, Untracked, Conflicts
+        // Build sections in order: Staged, Changes, Untracked, Conflicts, Submodules
         if !staged_entries.is_empty() {
             let children = build_section(
                 &staged_entries,
@@ -882,6 +1086,24 @@ impl GitState {
             });
         }
 
+        if !self.submodules.is_empty() {
+            let children = self
+                .submodules
+                .iter()
+                .map(|sub| TreeNode::Submodule {
+                    path: sub.path.clone(),
+                })
+                .collect();
+            self.tree.push(TreeNode::Section {
+                kind: GitSection::Submodules,
+                expanded: *self
+                    .section_expanded
+                    .get(&GitSection::Submodules)
+                    .unwrap_or(&true),
+                children,
+            });
+        }
+
         self.flatten_tree();
     }
 
@@ -911,6 +1133,7 @@ impl GitState {
                             GitSection::Working => "Changes".to_string(),
                             GitSection::Untracked => "Untracked".to_string(),
                             GitSection::Conflicts => "Conflicts".to_string(),
+                            GitSection::Submodules => "Submodules".to_string(),
                         },
                         path: String::new(),
                         section: *kind,
@@ -955,6 +1178,17 @@ impl GitState {
                         section,
                     });
                 }
+                TreeNode::Submodule { path } => {
+                    out.push(FlatTreeItem {
+                        depth,
+                        node_type: FlatNodeType::Submodule,
+                        expanded: false,
+                        entry_idx: None,
+                        name: path.clone(),
+                        path: path.clone(),
+                        section,
+                    });
+                }
             }
         }
 
@@ -1006,7 +1240,7 @@ impl GitState {
                 }
                 self.rebuild_tree_structure();
             }
-            FlatNodeType::File => {
+            FlatNodeType::File | FlatNodeType::Submodule => {
                 // No action for files
             }
         }
@@ -1037,7 +1271,7 @@ impl GitState {
                     self.rebuild_tree_structure();
                 }
             }
-            FlatNodeType::File => {}
+            FlatNodeType::File | FlatNodeType::Submodule => {}
         }
     }
 
@@ -1066,6 +1300,7 @@ impl GitState {
                     self.rebuild_tree_structure();
                 }
             }
+            FlatNodeType::Submodule => {}
             FlatNodeType::File => {
                 // For files, collapse parent directory or go to parent
                 if item.depth > 1 {
@@ -1118,7 +1353,7 @@ impl GitState {
                         update_section(child, section_expanded, dir_expanded);
                     }
                 }
-                TreeNode::File { .. } => {}
+                TreeNode::File { .. } | TreeNode::Submodule { .. } => {}
             }
         }
 
@@ -1149,6 +1384,45 @@ impl GitState {
         }
     }
 
+    /// Step between the bottom of the tree and the recent-commits strip. Returns
+    /// false when the tree should make the move instead.
+    pub fn recent_move(&mut self, down: bool) -> bool {
+        match self.recent_selected {
+            Some(0) if !down => self.recent_selected = None,
+            Some(i) if !down => self.recent_selected = Some(i - 1),
+            Some(i) => self.recent_selected = Some((i + 1).min(self.recent_shown.max(1) - 1)),
+            None => {
+                let at_end = self
+                    .tree_state
+                    .selected()
+                    .is_none_or(|s| s + 1 >= self.flat_tree.len());
+                if !down || !at_end || self.recent_shown == 0 {
+                    return false;
+                }
+                self.recent_selected = Some(0);
+            }
+        }
+        true
+    }
+
+    /// Select the next (or previous) file in the tree, skipping sections and directories
+    pub fn tree_move_to_file(&mut self, forward: bool) -> bool {
+        let current = self.tree_state.selected().unwrap_or(0);
+        let is_file = |i: &usize| self.flat_tree[*i].entry_idx.is_some();
+        let target = if forward {
+            (current + 1..self.flat_tree.len()).find(is_file)
+        } else {
+            (0..current).rev().find(is_file)
+        };
+        let Some(idx) = target else {
+            return false;
+        };
+        self.tree_state.select(Some(idx));
+        self.diff_scroll_y = 0;
+        self.diff_scroll_x = 0;
+        true
+    }
+
     /// Get the currently selected tree item
     pub fn selected_tree_item(&self) -> Option<&FlatTreeItem> {
         self.tree_state
@@ -1163,6 +1437,14 @@ impl GitState {
             .and_then(|idx| self.entries.get(idx))
     }
 
+    /// The submodule on the selected tree row, if that row is one
+    pub fn selected_submodule(&self) -> Option<&SubmoduleEntry> {
+        let item = self
+            .selected_tree_item()
+            .filter(|item| item.node_type == FlatNodeType::Submodule)?;
+        self.submodules.iter().find(|sub| sub.path == item.path)
+    }
+
     /// Get paths of all selected items in tree view
     /// Supports files, directories (all files under it), and sections (all files in section)
     pub fn selected_tree_paths(&self) -> Vec<String> {
@@ -1183,6 +1465,8 @@ impl GitState {
                 }
                 Vec::new()
             }
+            // A moved submodule is staged from Changes, like a file
+            FlatNodeType::Submodule => Vec::new(),
             FlatNodeType::Directory => {
                 // Collect all files under this directory in the same section
                 let dir_path = &item.path;
@@ -1233,6 +1517,7 @@ impl GitState {
     /// Select tree item at index
     pub fn select_tree(&mut self, idx: usize) {
         if idx < self.flat_tree.len() {
+            self.recent_selected = None;
             self.tree_state.select(Some(idx));
             self.diff_scroll_y = 0;
             self.diff_scroll_x = 0;
@@ -1277,6 +1562,70 @@ impl GitState {
         false
     }
 
+    /// Summary of the selected directory's changes in its section; `None` unless a
+    /// directory row is selected.
+    pub fn dir_summary(&self) -> Option<DirSummary> {
+        let item = self
+            .selected_tree_item()
+            .filter(|item| item.node_type == FlatNodeType::Directory)?;
+        let prefix = format!("{}/", item.path);
+        let section = item.section;
+        let files = self
+            .entries
+            .iter()
+            .filter(|e| e.path.starts_with(&prefix))
+            .filter_map(|e| {
+                let staged = e.x != ' ' && e.x != '?';
+                let unstaged = e.y != ' ' && e.y != '?';
+                let (status, stats) = match section {
+                    GitSection::Conflicts if e.is_conflict => ('U', &self.worktree_stats),
+                    GitSection::Untracked if e.is_untracked => ('?', &self.worktree_stats),
+                    GitSection::Staged if !e.is_conflict && !e.is_untracked && staged => {
+                        (e.x, &self.staged_stats)
+                    }
+                    GitSection::Working if !e.is_conflict && !e.is_untracked && unstaged => {
+                        (e.y, &self.worktree_stats)
+                    }
+                    _ => return None,
+                };
+                Some(DirSummaryFile {
+                    path: e.path.clone(),
+                    status,
+                    stats: stats.get(&e.path).copied().filter(|_| !e.is_untracked),
+                })
+            })
+            .collect();
+        Some(DirSummary {
+            path: item.path.clone(),
+            section,
+            files,
+        })
+    }
+
+    /// Expand the section and folders holding `path` and select it there.
+    pub fn reveal_file(&mut self, path: &str, section: GitSection) -> bool {
+        self.section_expanded.insert(section, true);
+        let mut dir = path;
+        while let Some((parent, _)) = dir.rsplit_once('/') {
+            self.dir_expanded
+                .remove(&format!("{:?}:{}", section, parent));
+            dir = parent;
+        }
+        self.rebuild_tree_structure();
+        let found = self.flat_tree.iter().position(|item| {
+            item.node_type == FlatNodeType::File
+                && item.section == section
+                && item
+                    .entry_idx
+                    .and_then(|idx| self.entries.get(idx))
+                    .is_some_and(|e| e.path == path)
+        });
+        if let Some(idx) = found {
+            self.select_tree(idx);
+        }
+        found.is_some()
+    }
+
     /// Remember current selection path for restoration after refresh
     pub fn selected_path(&self) -> Option<String> {
         self.selected_tree_entry().map(|e| e.path.clone())
@@ -1316,6 +1665,7 @@ impl GitState {
         self.section_expanded.insert(GitSection::Working, true);
         self.section_expanded.insert(GitSection::Untracked, true);
         self.section_expanded.insert(GitSection::Conflicts, true);
+        self.section_expanded.insert(GitSection::Submodules, true);
 
         // dir_expanded stores COLLAPSED paths, so clear it to expand all
         self.dir_expanded.clear();
@@ -1339,7 +1689,7 @@ impl GitState {
                         collect_dirs(child, section, out);
                     }
                 }
-                TreeNode::File { .. } => {}
+                TreeNode::File { .. } | TreeNode::Submodule { .. } => {}
             }
         }
 
@@ -1353,8 +1703,8 @@ impl GitState {
 }
 
 fn run_git(cwd: &Path, args: &[&str]) -> io::Result<std::process::Output> {
-    Command::new("git")
-        .arg("-C")
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
         .arg(cwd)
         .args(args)
         .env("GIT_TERMINAL_PROMPT", "0")
@@ -1364,8 +1714,8 @@ fn run_git(cwd: &Path, args: &[&str]) -> io::Result<std::process::Output> {
         .env("GIT_EDITOR", ":")
         .env("EDITOR", ":")
         .env("GIT_SEQUENCE_EDITOR", ":")
-        .env("GIT_MERGE_AUTOEDIT", "no")
-        .output()
+        .env("GIT_MERGE_AUTOEDIT", "no");
+    crate::git_ops::output_with_timeout(&mut cmd, args)
 }
 
 fn is_conflict_status(x: char, y: char) -> bool {
@@ -1408,6 +1758,8 @@ pub struct GitDiffCell {
     pub line_no: Option<u32>,
     pub text: String,
     pub kind: GitDiffCellKind,
+    /// Part of a block moved elsewhere in the diff, see [`moved_lines`]
+    pub moved: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -1492,6 +1844,36 @@ pub fn truncate_to_width(s: &str, width: usize) -> String {
     out
 }
 
+/// Shorten `s` to `width` columns by replacing the middle with `…`, keeping both ends.
+pub fn truncate_middle(s: &str, width: usize) -> String {
+    if display_width(s) <= width {
+        return s.to_string();
+    }
+    if width <= 1 {
+        return truncate_to_width("…", width);
+    }
+
+    let keep = width - 1;
+    let head_w = keep / 2;
+    let tail_w = keep - head_w;
+
+    let mut tail: Vec<char> = Vec::new();
+    let mut wsum = 0usize;
+    for ch in s.chars().rev() {
+        let w = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if wsum + w > tail_w {
+            break;
+        }
+        tail.push(ch);
+        wsum += w;
+    }
+
+    let mut out = truncate_to_width(s, head_w);
+    out.push('…');
+    out.extend(tail.into_iter().rev());
+    out
+}
+
 pub fn pad_to_width(mut s: String, width: usize) -> String {
     if width == 0 {
         return String::new();
@@ -1684,7 +2066,7 @@ pub fn render_side_by_side_cell_lines(
     out
 }
 
-fn parse_hunk_header(line: &str) -> Option<(u32, u32)> {
+pub fn parse_hunk_header(line: &str) -> Option<(u32, u32)> {
     let trimmed = line.trim();
     let Some(rest) = trimmed.strip_prefix("@@") else {
         return None;
@@ -1703,44 +2085,384 @@ fn parse_hunk_header(line: &str) -> Option<(u32, u32)> {
     Some((old_start, new_start))
 }
 
+/// Tracks old/new line numbers while walking unified diff lines in order
+#[derive(Default)]
+pub struct LineNumberTracker {
+    numbers: Option<(u32, u32)>,
+}
+
+impl LineNumberTracker {
+    /// Line numbers of `line` in the old and new file, advancing past it.
+    /// Headers and meta lines have neither. `--- ` and `+++ ` are only headers
+    /// before a file's first `@@`; in a hunk they are a removed `-- ` or an added
+    /// `++ ` line.
+    pub fn advance(&mut self, line: &str) -> (Option<u32>, Option<u32>) {
+        if line.starts_with("@@") {
+            self.numbers = parse_hunk_header(line);
+            return (None, None);
+        }
+        if line.starts_with("diff --git") {
+            self.numbers = None;
+            return (None, None);
+        }
+        let Some((old, new)) = self.numbers.as_mut() else {
+            return (None, None);
+        };
+        match line.chars().next() {
+            Some('+') => {
+                *new += 1;
+                (None, Some(*new - 1))
+            }
+            Some('-') => {
+                *old += 1;
+                (Some(*old - 1), None)
+            }
+            Some(' ') => {
+                *old += 1;
+                *new += 1;
+                (Some(*old - 1), Some(*new - 1))
+            }
+            _ => (None, None),
+        }
+    }
+}
+
+pub fn line_number_gutter(numbers: (Option<u32>, Option<u32>)) -> String {
+    let col = |n: Option<u32>| {
+        n.map(|n| format!("{:>4}", n))
+            .unwrap_or_else(|| "    ".to_string())
+    };
+    format!("{} {} ", col(numbers.0), col(numbers.1))
+}
+
+/// Which lines of a unified diff were moved: removed in one place and added in
+/// another of the same diff, like git's `--color-moved=blocks`. Indentation is
+/// ignored, so code moved into a new scope still counts, but a block re-added
+/// right where it was removed is a plain change.
+pub fn moved_lines(lines: &[String]) -> Vec<bool> {
+    struct Change<'a> {
+        idx: usize,
+        key: &'a str,
+        /// Changes without context lines in between share a run
+        run: usize,
+    }
+
+    let mut moved = vec![false; lines.len()];
+    let (mut dels, mut adds) = (Vec::new(), Vec::new());
+    let mut run = 0;
+    let mut in_hunk = false;
+    for (idx, line) in lines.iter().enumerate() {
+        if line.starts_with("diff --git ") || line.starts_with("@@") {
+            in_hunk = line.starts_with("@@");
+            run += 1;
+            continue;
+        }
+        let changes = match line.as_bytes().first() {
+            Some(b'-') if in_hunk => &mut dels,
+            Some(b'+') if in_hunk => &mut adds,
+            _ => {
+                run += 1;
+                continue;
+            }
+        };
+        changes.push(Change {
+            idx,
+            key: line[1..].trim(),
+            run,
+        });
+    }
+
+    let mut by_key: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, del) in dels.iter().enumerate() {
+        if del.key.chars().any(char::is_alphanumeric) {
+            by_key.entry(del.key).or_default().push(i);
+        }
+    }
+    let mut used = vec![false; dels.len()];
+    let mut a = 0;
+    while a < adds.len() {
+        let add = &adds[a];
+        // Longest run of deleted lines matching the added ones from `a` on
+        let (mut len, mut from) = (0, 0);
+        let candidates = by_key.get(add.key).map_or(&[][..], Vec::as_slice);
+        for &d in candidates
+            .iter()
+            .filter(|&&d| !used[d] && dels[d].run != add.run)
+            .take(MOVED_MAX_CANDIDATES)
+        {
+            let mut n = 1;
+            while a + n < adds.len()
+                && d + n < dels.len()
+                && adds[a + n].idx == add.idx + n
+                && dels[d + n].idx == dels[d].idx + n
+                && !used[d + n]
+                && adds[a + n].key == dels[d + n].key
+            {
+                n += 1;
+            }
+            if n > len {
+                (len, from) = (n, d);
+            }
+        }
+        let alnum: usize = adds[a..a + len]
+            .iter()
+            .map(|c| c.key.chars().filter(|ch| ch.is_alphanumeric()).count())
+            .sum();
+        if len == 0 || alnum < MOVED_MIN_ALNUM {
+            a += 1;
+            continue;
+        }
+        for i in 0..len {
+            moved[adds[a + i].idx] = true;
+            moved[dels[from + i].idx] = true;
+            used[from + i] = true;
+        }
+        a += len;
+    }
+    moved
+}
+
+/// A line of a diff that contains the searched term
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffMatch {
+    /// Index into the diff lines
+    pub line: usize,
+    pub path: String,
+    /// Line number in the new file, or in the old one for a removed line
+    pub number: u32,
+}
+
+/// Code lines of a unified diff that contain `term`, ignoring case unless the term
+/// has capitals. Headers and commit messages are not searched.
+pub fn find_in_diff(lines: &[String], term: &str) -> Vec<DiffMatch> {
+    if term.is_empty() {
+        return Vec::new();
+    }
+    let ignore_case = !term.chars().any(char::is_uppercase);
+    let term = if ignore_case {
+        term.to_lowercase()
+    } else {
+        term.to_string()
+    };
+    let mut numbers = LineNumberTracker::default();
+    let mut path = String::new();
+    let mut out = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            // `a/old b/new`: the new side, which a rename also shows
+            path = rest
+                .rsplit_once(" b/")
+                .map(|(_, new)| new)
+                .unwrap_or(rest)
+                .to_string();
+        }
+        let (old, new) = numbers.advance(line);
+        let Some(number) = new.or(old) else {
+            continue;
+        };
+        let code = &line[1..];
+        let hit = if ignore_case {
+            code.to_lowercase().contains(&term)
+        } else {
+            code.contains(&term)
+        };
+        if hit {
+            out.push(DiffMatch {
+                line: idx,
+                path: path.clone(),
+                number,
+            });
+        }
+    }
+    out
+}
+
+/// Line 1 of the new file whose diff starts at `lines[start]`, when a hunk shows it;
+/// a `#!` there tells how to highlight a file without an extension.
+pub fn diff_first_line(lines: &[String], start: usize) -> Option<&str> {
+    let mut numbers = LineNumberTracker::default();
+    for (idx, line) in lines.iter().enumerate().skip(start) {
+        if idx > start && line.starts_with("diff --git ") {
+            break;
+        }
+        if numbers.advance(line).1 == Some(1) {
+            return Some(&line[1..]);
+        }
+    }
+    None
+}
+
+/// Text of the new-side lines (context and additions) in `lines[start..=end]`,
+/// without diff markers
+pub fn selection_new_text(lines: &[String], start: usize, end: usize) -> String {
+    let mut numbers = LineNumberTracker::default();
+    let mut out: Vec<&str> = Vec::new();
+    for (idx, line) in lines.iter().enumerate().take(end + 1) {
+        let (_, new) = numbers.advance(line);
+        if idx >= start && new.is_some() {
+            out.push(&line[1..]);
+        }
+    }
+    out.join("\n")
+}
+
+/// Build an applyable patch from `lines[start..=end]`, as `git add -p` does when
+/// lines are edited out: every hunk the selection touches is kept with its context,
+/// unselected removals stay as context and unselected additions are dropped.
+pub fn selection_patch(lines: &[String], start: usize, end: usize) -> String {
+    struct Hunk {
+        old_start: u32,
+        body: Vec<String>,
+        counts: (u32, u32),
+        selected: bool,
+        /// The last line was an unselected addition, so its `\ No newline` goes too
+        dropped: bool,
+    }
+
+    fn flush(
+        out: &mut Vec<String>,
+        file_header: &[String],
+        header_written: &mut bool,
+        offset: &mut i64,
+        hunk: Option<Hunk>,
+    ) {
+        let Some(hunk) = hunk.filter(|h| h.selected) else {
+            return;
+        };
+        if !*header_written {
+            out.extend(file_header.iter().cloned());
+            *header_written = true;
+        }
+        let (old, new) = hunk.counts;
+        // An empty side points at the line before the change, as git does
+        let first_new = i64::from(hunk.old_start) + i64::from(old == 0) + *offset;
+        let new_start = if new == 0 { first_new - 1 } else { first_new };
+        out.push(format!(
+            "@@ -{},{} +{},{} @@",
+            hunk.old_start, old, new_start, new
+        ));
+        out.extend(hunk.body);
+        // Later hunks of the file move by what this one changed, not by the diff's
+        *offset += i64::from(new) - i64::from(old);
+    }
+
+    let mut out: Vec<String> = Vec::new();
+    let mut file_header: Vec<String> = Vec::new();
+    let mut header_written = false;
+    let mut offset = 0i64;
+    let mut hunk: Option<Hunk> = None;
+
+    for (idx, line) in lines.iter().enumerate() {
+        if line.starts_with("diff --git") || line.starts_with("@@") {
+            flush(
+                &mut out,
+                &file_header,
+                &mut header_written,
+                &mut offset,
+                hunk.take(),
+            );
+            if line.starts_with("diff --git") {
+                file_header = vec![line.clone()];
+                header_written = false;
+                offset = 0;
+            } else {
+                hunk = parse_hunk_header(line).map(|(old_start, _)| Hunk {
+                    old_start,
+                    body: Vec::new(),
+                    counts: (0, 0),
+                    selected: false,
+                    dropped: false,
+                });
+            }
+            continue;
+        }
+        let Some(hunk) = hunk.as_mut() else {
+            file_header.push(line.clone());
+            continue;
+        };
+        let selected = (start..=end).contains(&idx);
+        let kept = match line.chars().next() {
+            Some('+') if selected => {
+                hunk.counts.1 += 1;
+                line.clone()
+            }
+            Some('+') => {
+                hunk.dropped = true;
+                continue;
+            }
+            Some('-') if selected => {
+                hunk.counts.0 += 1;
+                line.clone()
+            }
+            Some('-') | Some(' ') => {
+                hunk.counts.0 += 1;
+                hunk.counts.1 += 1;
+                format!(" {}", &line[1..])
+            }
+            Some('\\') if !hunk.dropped => line.clone(),
+            _ => continue,
+        };
+        hunk.selected |= selected && !kept.starts_with([' ', '\\']);
+        hunk.dropped = false;
+        hunk.body.push(kept);
+    }
+    flush(
+        &mut out,
+        &file_header,
+        &mut header_written,
+        &mut offset,
+        hunk,
+    );
+
+    if out.is_empty() {
+        return String::new();
+    }
+    out.join("\n") + "\n"
+}
+
 pub fn build_side_by_side_rows(lines: &[String]) -> Vec<GitDiffRow> {
     let mut rows = Vec::new();
+    let moved = moved_lines(lines);
 
     let mut old_line: Option<u32> = None;
     let mut new_line: Option<u32> = None;
 
-    let mut pending_del: Vec<(u32, String)> = Vec::new();
-    let mut pending_add: Vec<(u32, String)> = Vec::new();
+    let mut pending_del: Vec<(u32, String, bool)> = Vec::new();
+    let mut pending_add: Vec<(u32, String, bool)> = Vec::new();
 
     let flush = |rows: &mut Vec<GitDiffRow>,
-                 pending_del: &mut Vec<(u32, String)>,
-                 pending_add: &mut Vec<(u32, String)>| {
+                 pending_del: &mut Vec<(u32, String, bool)>,
+                 pending_add: &mut Vec<(u32, String, bool)>| {
         let n = pending_del.len().max(pending_add.len());
         for i in 0..n {
-            let old = if let Some((ln, t)) = pending_del.get(i) {
+            let old = if let Some((ln, t, moved)) = pending_del.get(i) {
                 GitDiffCell {
                     line_no: Some(*ln),
                     text: t.clone(),
                     kind: GitDiffCellKind::Delete,
+                    moved: *moved,
                 }
             } else {
                 GitDiffCell {
                     line_no: None,
                     text: String::new(),
                     kind: GitDiffCellKind::Empty,
+                    moved: false,
                 }
             };
-            let new = if let Some((ln, t)) = pending_add.get(i) {
+            let new = if let Some((ln, t, moved)) = pending_add.get(i) {
                 GitDiffCell {
                     line_no: Some(*ln),
                     text: t.clone(),
                     kind: GitDiffCellKind::Add,
+                    moved: *moved,
                 }
             } else {
                 GitDiffCell {
                     line_no: None,
                     text: String::new(),
                     kind: GitDiffCellKind::Empty,
+                    moved: false,
                 }
             };
             rows.push(GitDiffRow::Split { old, new });
@@ -1749,7 +2471,7 @@ pub fn build_side_by_side_rows(lines: &[String]) -> Vec<GitDiffRow> {
         pending_add.clear();
     };
 
-    for line in lines {
+    for (idx, line) in lines.iter().enumerate() {
         if line.starts_with("diff --git ")
             || line.starts_with("index ")
             || line.starts_with("--- ")
@@ -1791,11 +2513,13 @@ pub fn build_side_by_side_rows(lines: &[String]) -> Vec<GitDiffRow> {
                         line_no: o,
                         text: text.clone(),
                         kind: GitDiffCellKind::Context,
+                        moved: false,
                     },
                     new: GitDiffCell {
                         line_no: n,
                         text,
                         kind: GitDiffCellKind::Context,
+                        moved: false,
                     },
                 });
                 if let Some(v) = old_line.as_mut() {
@@ -1809,18 +2533,18 @@ pub fn build_side_by_side_rows(lines: &[String]) -> Vec<GitDiffRow> {
                 if let Some(v) = old_line.as_mut() {
                     let ln = *v;
                     *v += 1;
-                    pending_del.push((ln, line.get(1..).unwrap_or("").to_string()));
+                    pending_del.push((ln, line.get(1..).unwrap_or("").to_string(), moved[idx]));
                 } else {
-                    pending_del.push((0, line.get(1..).unwrap_or("").to_string()));
+                    pending_del.push((0, line.get(1..).unwrap_or("").to_string(), moved[idx]));
                 }
             }
             '+' => {
                 if let Some(v) = new_line.as_mut() {
                     let ln = *v;
                     *v += 1;
-                    pending_add.push((ln, line.get(1..).unwrap_or("").to_string()));
+                    pending_add.push((ln, line.get(1..).unwrap_or("").to_string(), moved[idx]));
                 } else {
-                    pending_add.push((0, line.get(1..).unwrap_or("").to_string()));
+                    pending_add.push((0, line.get(1..).unwrap_or("").to_string(), moved[idx]));
                 }
             }
             _ => {
@@ -1833,3 +2557,223 @@ pub fn build_side_by_side_rows(lines: &[String]) -> Vec<GitDiffRow> {
     flush(&mut rows, &mut pending_del, &mut pending_add);
     rows
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diffstat_bar() {
+        assert_eq!(diffstat_bar(3, 1, 4, 40), (3, 1));
+        assert_eq!(diffstat_bar(100, 0, 200, 40), (20, 0));
+        assert_eq!(diffstat_bar(1, 199, 200, 40), (1, 39));
+    }
+
+    #[test]
+    fn test_find_in_diff() {
+        let diff: Vec<String> = [
+            "commit 1234567",
+            "    Rename the Parser",
+            "diff --git a/src/a.rs b/src/a.rs",
+            "--- a/src/a.rs",
+            "+++ b/src/a.rs",
+            "@@ -3,3 +3,3 @@",
+            " use parser;",
+            "-let p = Parser::new();",
+            "+let p = parser::build();",
+            "diff --git a/old.rs b/new.rs",
+            "@@ -10,1 +10,1 @@",
+            "-parse()",
+            "+parse_all()",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        let found: Vec<(usize, u32)> = find_in_diff(&diff, "parser")
+            .iter()
+            .map(|m| (m.line, m.number))
+            .collect();
+        assert_eq!(found, vec![(6, 3), (7, 4), (8, 4)]);
+
+        let found = find_in_diff(&diff, "Parser");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "src/a.rs");
+
+        let found = find_in_diff(&diff, "parse_all");
+        assert_eq!((found[0].path.as_str(), found[0].number), ("new.rs", 10));
+        assert!(find_in_diff(&diff, "").is_empty());
+    }
+
+    #[test]
+    fn test_diff_first_line() {
+        let diff: Vec<String> = [
+            "diff --git a/deploy b/deploy",
+            "new file mode 100755",
+            "--- /dev/null",
+            "+++ b/deploy",
+            "@@ -0,0 +1,2 @@",
+            "+#!/bin/sh",
+            "+make",
+            "diff --git a/run b/run",
+            "@@ -5,1 +5,1 @@",
+            "-a",
+            "+b",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        assert_eq!(diff_first_line(&diff, 0), Some("#!/bin/sh"));
+        assert_eq!(diff_first_line(&diff, 3), Some("#!/bin/sh"));
+        assert_eq!(diff_first_line(&diff, 7), None);
+    }
+
+    #[test]
+    fn test_line_numbers_in_hunks_with_dashes() {
+        let diff = [
+            "diff --git a/q.sql b/q.sql",
+            "--- a/q.sql",
+            "+++ b/q.sql",
+            "@@ -1,3 +1,3 @@",
+            " select 1;",
+            "--- old note",
+            "+++ new note",
+            " select 2;",
+        ];
+        let mut numbers = LineNumberTracker::default();
+        let got: Vec<_> = diff.iter().map(|l| numbers.advance(l)).collect();
+        assert_eq!(
+            got,
+            [
+                (None, None),
+                (None, None),
+                (None, None),
+                (None, None),
+                (Some(1), Some(1)),
+                (Some(2), None),
+                (None, Some(2)),
+                (Some(3), Some(3)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_selection_patch() {
+        let diff: Vec<String> = [
+            "diff --git a/q.sql b/q.sql",
+            "index 1111111..2222222 100644",
+            "--- a/q.sql",
+            "+++ b/q.sql",
+            "@@ -1,4 +1,4 @@",
+            " select 1;",
+            "--- old note",
+            "+++ new note",
+            " select 2;",
+            "-select 3;",
+            "+select 4;",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+
+        let header = [
+            "diff --git a/q.sql b/q.sql",
+            "index 1111111..2222222 100644",
+            "--- a/q.sql",
+            "+++ b/q.sql",
+        ];
+        let patch = |body: &[&str]| {
+            let lines: Vec<&str> = header.iter().chain(body).copied().collect();
+            lines.join("\n") + "\n"
+        };
+
+        // Just the removed comment: the other changes are left out of the hunk
+        assert_eq!(
+            selection_patch(&diff, 6, 6),
+            patch(&[
+                "@@ -1,4 +1,3 @@",
+                " select 1;",
+                "--- old note",
+                " select 2;",
+                " select 3;",
+            ])
+        );
+        assert_eq!(
+            selection_patch(&diff, 7, 10),
+            patch(&[
+                "@@ -1,4 +1,5 @@",
+                " select 1;",
+                " -- old note",
+                "+++ new note",
+                " select 2;",
+                "-select 3;",
+                "+select 4;",
+            ])
+        );
+
+        // A later hunk starts where the file is without the unselected earlier one
+        let two_hunks: Vec<String> = [
+            "diff --git a/q.sql b/q.sql",
+            "@@ -1,1 +1,2 @@",
+            " a",
+            "+b",
+            "@@ -9,2 +10,1 @@",
+            " y",
+            "-z",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        assert_eq!(
+            selection_patch(&two_hunks, 6, 6),
+            "diff --git a/q.sql b/q.sql\n@@ -9,2 +9,1 @@\n y\n-z\n"
+        );
+
+        assert_eq!(
+            selection_new_text(&diff, 5, 8),
+            "select 1;\n++ new note\nselect 2;"
+        );
+    }
+
+    #[test]
+    fn test_moved_lines() {
+        let diff: Vec<String> = [
+            "diff --git a/src/lib.rs b/src/lib.rs",
+            "--- a/src/lib.rs",
+            "+++ b/src/lib.rs",
+            "@@ -1,9 +1,9 @@",
+            "-fn helper(value: u32) -> u32 {",
+            "-    value * 2",
+            "-}",
+            " fn main() {",
+            "-    let x = 1;",
+            "+    let x = 2;",
+            "-    }",
+            "+    },",
+            " }",
+            "+",
+            "+    fn helper(value: u32) -> u32 {",
+            "+        value * 2",
+            "+    }",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        let moved: Vec<usize> = moved_lines(&diff)
+            .iter()
+            .enumerate()
+            .filter_map(|(i, m)| m.then_some(i))
+            .collect();
+        assert_eq!(moved, [4, 5, 6, 14, 15, 16]);
+
+        // Re-added where it was removed, only re-indented: a plain change
+        let reindent: Vec<String> = [
+            "@@ -1,2 +1,2 @@",
+            "-fn helper(value: u32) -> u32 {",
+            "+    fn helper(value: u32) -> u32 {",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        assert_eq!(moved_lines(&reindent), [false, false, false]);
+    }
+}
@@ -5,11 +5,22 @@
 //! for the actual git command execution since git_ops functions are blocking I/O.
 
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
 use crate::git_ops;
 
+/// How to present a loaded diff.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Context lines around hunks; git's default when `None`
+    pub context: Option<usize>,
+    /// Diff small binaries as hexdumps
+    pub hexdump: bool,
+}
+
 /// Request sent to the git diff loader task.
 pub enum GitDiffRequest {
     /// Load a diff for a file with cancellation support.
@@ -18,6 +29,7 @@ pub enum GitDiffRequest {
         path: String,
         is_untracked: bool,
         staged: bool,
+        options: DiffOptions,
         request_id: u64,
         cancel: CancellationToken,
     },
@@ -43,6 +55,8 @@ pub enum GitDiffResult {
 /// Handle for requesting git diffs.
 pub struct GitDiffLoader {
     tx: mpsc::Sender<GitDiffRequest>,
+    /// Diffs being loaded, cancelled ones included until git exits
+    running: Arc<AtomicUsize>,
 }
 
 impl GitDiffLoader {
@@ -54,9 +68,22 @@ impl GitDiffLoader {
         let (request_tx, request_rx) = mpsc::channel::<GitDiffRequest>(16);
         let (result_tx, result_rx) = mpsc::channel::<GitDiffResult>(16);
 
-        tokio::spawn(git_diff_loader_task(request_rx, result_tx));
+        let running = Arc::new(AtomicUsize::new(0));
+
+        tokio::spawn(git_diff_loader_task(request_rx, result_tx, running.clone()));
+
+        (
+            Self {
+                tx: request_tx,
+                running,
+            },
+            result_rx,
+        )
+    }
 
-        (Self { tx: request_tx }, result_rx)
+    /// Whether a git command is running for a diff.
+    pub fn busy(&self) -> bool {
+        self.running.load(Ordering::SeqCst) > 0
     }
 
     /// Request a diff synchronously (non-blocking send).
@@ -68,6 +95,7 @@ impl GitDiffLoader {
         path: String,
         is_untracked: bool,
         staged: bool,
+        options: DiffOptions,
         request_id: u64,
     ) -> CancellationToken {
         let cancel = CancellationToken::new();
@@ -76,6 +104,7 @@ impl GitDiffLoader {
             path,
             is_untracked,
             staged,
+            options,
             request_id,
             cancel: cancel.clone(),
         });
@@ -87,6 +116,7 @@ impl GitDiffLoader {
 async fn git_diff_loader_task(
     mut rx: mpsc::Receiver<GitDiffRequest>,
     tx: mpsc::Sender<GitDiffResult>,
+    running: Arc<AtomicUsize>,
 ) {
     let mut current_cancel: Option<CancellationToken> = None;
 
@@ -97,6 +127,7 @@ async fn git_diff_loader_task(
                 path,
                 is_untracked,
                 staged,
+                options,
                 request_id,
                 cancel,
             } => {
@@ -117,10 +148,12 @@ async fn git_diff_loader_task(
                 let path_clone = path.clone();
 
                 // Use spawn_blocking for the blocking git operation
+                running.fetch_add(1, Ordering::SeqCst);
                 let result = tokio::task::spawn_blocking(move || {
-                    load_diff(&repo_root_clone, &path_clone, is_untracked, staged)
+                    load_diff(&repo_root_clone, &path_clone, is_untracked, staged, options)
                 })
                 .await;
+                running.fetch_sub(1, Ordering::SeqCst);
 
                 // Check cancellation after the blocking work
                 if cancel.is_cancelled() {
@@ -153,6 +186,7 @@ fn load_diff(
     path: &str,
     is_untracked: bool,
     staged: bool,
+    options: DiffOptions,
 ) -> Result<Vec<String>, String> {
     if is_untracked {
         // For untracked files, read the content and format as a diff
@@ -193,11 +227,12 @@ fn load_diff(
             }
         }
     } else {
-        match git_ops::diff_path(repo_root, path, staged) {
+        match git_ops::diff_path(repo_root, path, staged, options.context) {
             Ok(text) => {
                 if text.trim().is_empty() {
                     Ok(vec!["No diff".to_string()])
                 } else {
+                    let text = git_ops::describe_binary_diffs(repo_root, &text, options.hexdump);
                     Ok(text.lines().map(|l| l.to_string()).collect())
                 }
             }
@@ -220,7 +255,13 @@ mod tests {
         writeln!(file, "line 1").unwrap();
         writeln!(file, "line 2").unwrap();
 
-        let result = load_diff(&temp_dir.path().to_path_buf(), "test.txt", true, false);
+        let result = load_diff(
+            &temp_dir.path().to_path_buf(),
+            "test.txt",
+            true,
+            false,
+            DiffOptions::default(),
+        );
 
         assert!(result.is_ok());
         let lines = result.unwrap();
@@ -236,7 +277,13 @@ mod tests {
         std::fs::create_dir(&sub_dir).unwrap();
         std::fs::write(sub_dir.join("file.txt"), "content").unwrap();
 
-        let result = load_diff(&temp_dir.path().to_path_buf(), "subdir", true, false);
+        let result = load_diff(
+            &temp_dir.path().to_path_buf(),
+            "subdir",
+            true,
+            false,
+            DiffOptions::default(),
+        );
 
         assert!(result.is_ok());
         let lines = result.unwrap();
@@ -0,0 +1,171 @@
+//! Bundled `.gitignore` templates
+//!
+//! A curated set offered when initializing a repository and by "Git: add .gitignore
+//! template…". Applying a template to an existing `.gitignore` only appends the
+//! patterns it lacks, so applying twice (or combining Rust with Node) is harmless.
+
+pub struct Template {
+    pub name: &'static str,
+    pub body: &'static str,
+}
+
+pub const TEMPLATES: &[Template] = &[
+    Template {
+        name: "Generic",
+        body: "\
+# OS files
+.DS_Store
+Thumbs.db
+desktop.ini
+
+# Editors
+.idea/
+.vscode/
+*.swp
+*~
+
+# Logs and local environment
+*.log
+.env
+.env.local
+",
+    },
+    Template {
+        name: "Rust",
+        body: "\
+# Build output
+/target/
+
+# Backup files from rustfmt
+**/*.rs.bk
+
+# Debug info on Windows
+*.pdb
+",
+    },
+    Template {
+        name: "Node",
+        body: "\
+# Dependencies
+node_modules/
+.pnp.*
+.yarn/*
+!.yarn/releases
+
+# Build output and caches
+dist/
+build/
+coverage/
+.cache/
+.next/
+
+# Logs
+npm-debug.log*
+yarn-debug.log*
+yarn-error.log*
+pnpm-debug.log*
+
+.env
+.env.local
+",
+    },
+    Template {
+        name: "Python",
+        body: "\
+# Bytecode
+__pycache__/
+*.py[cod]
+
+# Packaging
+build/
+dist/
+*.egg-info/
+.eggs/
+
+# Virtual environments
+.venv/
+venv/
+env/
+
+# Tools
+.pytest_cache/
+.mypy_cache/
+.ruff_cache/
+.coverage
+htmlcov/
+.ipynb_checkpoints/
+
+.env
+",
+    },
+    Template {
+        name: "Go",
+        body: "\
+# Binaries
+*.exe
+*.exe~
+*.dll
+*.so
+*.dylib
+/bin/
+
+# Test output
+*.test
+*.out
+coverage.txt
+
+# Workspace file
+go.work
+go.work.sum
+
+vendor/
+",
+    },
+];
+
+/// `existing` with the patterns of `template` it lacks appended under a
+/// `# <name> template` heading; unchanged when nothing is missing.
+pub fn apply(existing: &str, template: &Template) -> String {
+    let present: Vec<&str> = existing.lines().map(str::trim).collect();
+    let missing: Vec<&str> = template
+        .body
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#') && !present.contains(l))
+        .collect();
+    if missing.is_empty() {
+        return existing.to_string();
+    }
+    if existing.trim().is_empty() {
+        return template.body.to_string();
+    }
+
+    let mut out = existing.to_string();
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&format!("\n# {} template\n", template.name));
+    for line in missing {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_appends_missing_patterns() {
+        let rust = &TEMPLATES[1];
+        assert_eq!(apply("", rust), rust.body);
+
+        let merged = apply("/target/\n*.log", rust);
+        assert!(merged.starts_with("/target/\n*.log\n\n# Rust template\n"));
+        assert_eq!(merged.matches("/target/").count(), 1);
+        assert!(merged.ends_with("**/*.rs.bk\n*.pdb\n"));
+
+        assert_eq!(apply(&merged, rust), merged);
+    }
+}
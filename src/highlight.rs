@@ -2,7 +2,10 @@ use ratatui::{
     prelude::*,
     text::{Line, Span},
 };
-use std::{collections::HashMap, sync::OnceLock};
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock, RwLock},
+};
 use syntect::{
     easy::HighlightLines,
     highlighting::{
@@ -13,6 +16,8 @@ use syntect::{
     util::LinesWithEndings,
 };
 
+use crate::theme::{self, SyntaxColors};
+
 pub struct Highlighter {
     inner: HighlightLines<'static>,
 }
@@ -32,18 +37,32 @@ fn syntax_set() -> &'static SyntaxSet {
     SET.get_or_init(SyntaxSet::load_defaults_newlines)
 }
 
-/// Create a vibrant Dracula-like theme for syntax highlighting
-fn create_vibrant_theme() -> Theme {
-    // Dracula-inspired vibrant colors
-    let pink = SyntectColor { r: 255, g: 121, b: 198, a: 255 };      // #FF79C6 - keywords
-    let cyan = SyntectColor { r: 139, g: 233, b: 253, a: 255 };      // #8BE9FD - functions/types
-    let green = SyntectColor { r: 80, g: 250, b: 123, a: 255 };      // #50FA7B - strings
-    let yellow = SyntectColor { r: 241, g: 250, b: 140, a: 255 };    // #F1FA8C - classes
-    let orange = SyntectColor { r: 255, g: 184, b: 108, a: 255 };    // #FFB86C - numbers/constants
-    let purple = SyntectColor { r: 189, g: 147, b: 249, a: 255 };    // #BD93F9 - variables
-    let red = SyntectColor { r: 255, g: 85, b: 85, a: 255 };         // #FF5555 - errors
-    let comment = SyntectColor { r: 98, g: 114, b: 164, a: 255 };    // #6272A4 - comments
-    let fg = SyntectColor { r: 248, g: 248, b: 242, a: 255 };        // #F8F8F2 - foreground
+/// Load the syntax definitions on a background thread, so startup doesn't wait for
+/// them and the first highlighted view waits less.
+pub fn preload() {
+    std::thread::spawn(|| {
+        syntax_set();
+    });
+}
+
+/// Create the syntax highlighting theme for a palette's syntax colors
+fn create_theme(colors: &SyntaxColors) -> Theme {
+    fn rgb(color: Color) -> SyntectColor {
+        match color {
+            Color::Rgb(r, g, b) => SyntectColor { r, g, b, a: 255 },
+            _ => SyntectColor::WHITE,
+        }
+    }
+
+    // Named after the Dracula colors each role had before themes picked them
+    let pink = rgb(colors.keyword);      // keywords
+    let cyan = rgb(colors.function);     // functions/types
+    let green = rgb(colors.string);      // strings
+    let yellow = rgb(colors.type_name);  // classes
+    let orange = rgb(colors.number);     // numbers/constants
+    let purple = rgb(colors.variable);   // variables
+    let comment = rgb(colors.comment);   // comments
+    let fg = rgb(colors.text);           // foreground
 
     fn scope(s: &str) -> ScopeSelectors {
         s.parse().unwrap_or_default()
@@ -74,7 +93,7 @@ fn create_vibrant_theme() -> Theme {
     }
 
     Theme {
-        name: Some("Vibrant".to_string()),
+        name: Some("lzgit".to_string()),
         author: None,
         settings: ThemeSettings {
             foreground: Some(fg),
@@ -125,8 +144,8 @@ fn create_vibrant_theme() -> Theme {
             ThemeItem { scope: scope("markup.italic"), style: style_italic(yellow) },
             ThemeItem { scope: scope("markup.raw"), style: style(green) },
             ThemeItem { scope: scope("markup.underline.link"), style: style(cyan) },
-            // Invalid/Error - red
-            ThemeItem { scope: scope("invalid"), style: style(red) },
+            // Invalid/Error - bold keyword color
+            ThemeItem { scope: scope("invalid"), style: style_bold(pink) },
             // Rust specific
             ThemeItem { scope: scope("entity.name.lifetime"), style: style_italic(pink) },
             ThemeItem { scope: scope("entity.name.module"), style: style(cyan) },
@@ -135,9 +154,31 @@ fn create_vibrant_theme() -> Theme {
     }
 }
 
+/// Theme new highlighters use; `None` until the first `set_syntax_colors`
+static THEME: RwLock<Option<&'static Theme>> = RwLock::new(None);
+
+/// Highlight with these colors from now on, e.g. after the UI theme changed.
+/// Each distinct set is built once and kept, as highlighters borrow it.
+pub fn set_syntax_colors(colors: SyntaxColors) {
+    static BUILT: Mutex<Vec<(SyntaxColors, &'static Theme)>> = Mutex::new(Vec::new());
+    let mut built = BUILT.lock().unwrap_or_else(|e| e.into_inner());
+    let theme = match built.iter().find(|(c, _)| *c == colors) {
+        Some((_, theme)) => *theme,
+        None => {
+            let theme: &'static Theme = Box::leak(Box::new(create_theme(&colors)));
+            built.push((colors, theme));
+            theme
+        }
+    };
+    *THEME.write().unwrap_or_else(|e| e.into_inner()) = Some(theme);
+}
+
 fn theme() -> &'static Theme {
-    static THEME: OnceLock<Theme> = OnceLock::new();
-    THEME.get_or_init(create_vibrant_theme)
+    if let Some(theme) = *THEME.read().unwrap_or_else(|e| e.into_inner()) {
+        return theme;
+    }
+    set_syntax_colors(theme::palette(theme::Theme::Terminal).syntax);
+    theme()
 }
 
 pub fn is_supported_extension(ext: &str) -> bool {
@@ -163,6 +204,8 @@ pub fn is_supported_extension(ext: &str) -> bool {
         "java" | "kt" | "kts" |
         // Ruby
         "rb" | "erb" |
+        // Perl
+        "pl" | "pm" |
         // PHP
         "php" |
         // Lua
@@ -182,6 +225,75 @@ pub fn is_supported_extension(ext: &str) -> bool {
     )
 }
 
+/// Languages to pick from when the detected one is wrong: label and extension
+pub const LANGUAGES: &[(&str, &str)] = &[
+    ("C", "c"),
+    ("C++", "cpp"),
+    ("CSS", "css"),
+    ("Diff", "diff"),
+    ("Go", "go"),
+    ("HTML", "html"),
+    ("Java", "java"),
+    ("JavaScript", "js"),
+    ("JSON", "json"),
+    ("Lua", "lua"),
+    ("Makefile", "makefile"),
+    ("Markdown", "md"),
+    ("Perl", "pl"),
+    ("PHP", "php"),
+    ("Python", "py"),
+    ("Ruby", "rb"),
+    ("Rust", "rs"),
+    ("Shell", "sh"),
+    ("SQL", "sql"),
+    ("XML", "xml"),
+    ("YAML", "yaml"),
+];
+
+/// Extension to highlight `path` as: its own, else one its name implies
+/// (`Makefile`, `Dockerfile`, `.bashrc`), else the interpreter of a `#!` first line.
+pub fn detect_syntax<'a>(path: &'a str, first_line: Option<&str>) -> Option<&'a str> {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    if let Some(ext) = std::path::Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        && is_supported_extension(ext)
+    {
+        return Some(ext);
+    }
+    let lower = name.to_ascii_lowercase();
+    let by_name = match lower.as_str() {
+        "makefile" | "gnumakefile" => Some("makefile"),
+        // No Dockerfile syntax ships with syntect; its commands are shell
+        "dockerfile" | "containerfile" => Some("sh"),
+        ".bashrc" | ".bash_profile" | ".bash_aliases" | ".zshrc" | ".zprofile" | ".profile"
+        | "pkgbuild" | "apkbuild" => Some("sh"),
+        "gemfile" | "rakefile" | "vagrantfile" | "podfile" => Some("rb"),
+        _ if lower.starts_with("dockerfile.") => Some("sh"),
+        _ => None,
+    };
+    if by_name.is_some() {
+        return by_name;
+    }
+
+    let mut words = first_line?.strip_prefix("#!")?.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|w| !w.starts_with('-') && !w.contains('='))?;
+    }
+    match program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+        "sh" | "bash" | "zsh" | "dash" | "ksh" | "ash" | "fish" => Some("sh"),
+        "python" => Some("py"),
+        "node" | "nodejs" | "deno" | "bun" => Some("js"),
+        "ruby" => Some("rb"),
+        "perl" => Some("pl"),
+        "php" => Some("php"),
+        "lua" | "luajit" => Some("lua"),
+        "make" => Some("makefile"),
+        _ => None,
+    }
+}
+
 fn clamp01(v: f32) -> f32 {
     v.clamp(0.0, 1.0)
 }
@@ -448,3 +560,56 @@ impl HighlightCache {
         self.lines.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_uses_the_palette_syntax_colors() {
+        let palette = theme::palette(theme::Theme::Nord);
+        let syntax = syntax_set().find_syntax_by_extension("rs").unwrap();
+        let theme = Box::leak(Box::new(create_theme(&palette.syntax)));
+        let mut hl = Highlighter {
+            inner: HighlightLines::new(syntax, theme),
+        };
+        let line = hl.highlight_line("fn main() { return; }", palette.bg);
+        let keyword = line
+            .spans
+            .iter()
+            .find(|span| span.content == "return")
+            .unwrap();
+        assert_eq!(
+            keyword.style.fg,
+            Some(ensure_contrast(palette.syntax.keyword, palette.bg))
+        );
+    }
+
+    #[test]
+    fn test_detect_syntax() {
+        assert_eq!(detect_syntax("src/main.rs", Some("#!/bin/sh")), Some("rs"));
+        assert_eq!(detect_syntax("Makefile", None), Some("makefile"));
+        assert_eq!(detect_syntax("docker/Dockerfile", None), Some("sh"));
+        assert_eq!(detect_syntax("Dockerfile.dev", None), Some("sh"));
+        assert_eq!(detect_syntax("home/.bashrc", None), Some("sh"));
+        assert_eq!(
+            detect_syntax("bin/deploy", Some("#!/usr/bin/env bash")),
+            Some("sh")
+        );
+        assert_eq!(
+            detect_syntax("bin/tool", Some("#!/usr/bin/env -S python3.12 -u")),
+            Some("py")
+        );
+        assert_eq!(
+            detect_syntax("bin/serve", Some("#!/usr/local/bin/node")),
+            Some("js")
+        );
+        assert_eq!(detect_syntax("bin/run", Some("echo hi")), None);
+        assert_eq!(detect_syntax("notes.txt", Some("#!/bin/sh")), Some("sh"));
+        assert_eq!(detect_syntax("LICENSE", None), None);
+
+        for (label, ext) in LANGUAGES {
+            assert!(new_highlighter(ext).is_some(), "{}", label);
+        }
+    }
+}
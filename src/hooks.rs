@@ -0,0 +1,222 @@
+//! Before/after hook scripts around commit, push and checkout
+//!
+//! Hooks are set in the `[hooks]` table of `config.toml`:
+//!
+//! ```toml
+//! [hooks]
+//! before_commit = "./scripts/check-message.sh"
+//! after_push = "notify-send pushed"
+//! ```
+//!
+//! Each script runs with `sh -c` in the repository root. Context is passed in the
+//! environment: `LZGIT_OPERATION`, `LZGIT_REPO`, `LZGIT_BRANCH`, plus
+//! `LZGIT_COMMIT_MESSAGE` for commits and `LZGIT_TARGET` for checkouts. After hooks
+//! also get `LZGIT_RESULT` (`ok` or `error`). A failing before hook cancels the
+//! operation and its output is shown.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Prefix of job errors caused by a hook, so they are shown in a popup.
+pub const HOOK_FAILED: &str = "Hook failed";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookOp {
+    Commit,
+    Push,
+    Checkout,
+}
+
+impl HookOp {
+    fn name(self) -> &'static str {
+        match self {
+            HookOp::Commit => "commit",
+            HookOp::Push => "push",
+            HookOp::Checkout => "checkout",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Hooks {
+    before_commit: Option<String>,
+    after_commit: Option<String>,
+    before_push: Option<String>,
+    after_push: Option<String>,
+    before_checkout: Option<String>,
+    after_checkout: Option<String>,
+}
+
+impl Hooks {
+    pub fn set(&mut self, op: HookOp, before: bool, script: String) {
+        *self.slot(op, before) = Some(script);
+    }
+
+    fn slot(&mut self, op: HookOp, before: bool) -> &mut Option<String> {
+        match (op, before) {
+            (HookOp::Commit, true) => &mut self.before_commit,
+            (HookOp::Commit, false) => &mut self.after_commit,
+            (HookOp::Push, true) => &mut self.before_push,
+            (HookOp::Push, false) => &mut self.after_push,
+            (HookOp::Checkout, true) => &mut self.before_checkout,
+            (HookOp::Checkout, false) => &mut self.after_checkout,
+        }
+    }
+
+    fn script(&self, op: HookOp, before: bool) -> Option<&str> {
+        let script = match (op, before) {
+            (HookOp::Commit, true) => &self.before_commit,
+            (HookOp::Commit, false) => &self.after_commit,
+            (HookOp::Push, true) => &self.before_push,
+            (HookOp::Push, false) => &self.after_push,
+            (HookOp::Checkout, true) => &self.before_checkout,
+            (HookOp::Checkout, false) => &self.after_checkout,
+        };
+        script.as_deref().filter(|s| !s.trim().is_empty())
+    }
+
+    /// Run `f` between the before and after hooks configured for `op`.
+    pub fn run<F>(
+        &self,
+        op: HookOp,
+        cwd: &Path,
+        env: &[(String, String)],
+        f: F,
+    ) -> Result<(), String>
+    where
+        F: FnOnce() -> Result<(), String>,
+    {
+        if let Some(script) = self.script(op, true) {
+            run_script(script, op, cwd, env, None).map_err(|out| {
+                format!(
+                    "{}: before-{} (operation cancelled)\n{}",
+                    HOOK_FAILED,
+                    op.name(),
+                    out
+                )
+            })?;
+        }
+
+        let result = f();
+
+        if let Some(script) = self.script(op, false) {
+            let status = if result.is_ok() { "ok" } else { "error" };
+            let hook = run_script(script, op, cwd, env, Some(status));
+            if let (Ok(()), Err(out)) = (&result, hook) {
+                return Err(format!("{}: after-{}\n{}", HOOK_FAILED, op.name(), out));
+            }
+        }
+        result
+    }
+}
+
+fn run_script(
+    script: &str,
+    op: HookOp,
+    cwd: &Path,
+    env: &[(String, String)],
+    result: Option<&str>,
+) -> Result<(), String> {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(script)
+        .current_dir(cwd)
+        .env("LZGIT_OPERATION", op.name())
+        .env("LZGIT_REPO", cwd)
+        .envs(env.iter().map(|(k, v)| (k, v)));
+    if let Some(result) = result {
+        cmd.env("LZGIT_RESULT", result);
+    }
+
+    let out = cmd.output().map_err(|e| e.to_string())?;
+    if out.status.success() {
+        return Ok(());
+    }
+
+    let mut text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    if !stderr.trim().is_empty() {
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(stderr.trim());
+    }
+    if text.is_empty() {
+        text = format!("{} exited with {}", script, out.status);
+    }
+    Err(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use tempfile::TempDir;
+
+    fn hooks(op: HookOp, before: bool, script: &str) -> Hooks {
+        let mut hooks = Hooks::default();
+        hooks.set(op, before, script.to_string());
+        hooks
+    }
+
+    #[test]
+    fn test_failing_before_hook_cancels() {
+        let dir = TempDir::new().unwrap();
+        let hooks = hooks(HookOp::Commit, true, "echo \"no: $LZGIT_BRANCH\"; exit 1");
+        let env = [("LZGIT_BRANCH".to_string(), "main".to_string())];
+        let ran = Cell::new(false);
+        let err = hooks
+            .run(HookOp::Commit, dir.path(), &env, || {
+                ran.set(true);
+                Ok(())
+            })
+            .unwrap_err();
+        assert!(!ran.get());
+        assert_eq!(
+            err,
+            format!(
+                "{}: before-commit (operation cancelled)\nno: main",
+                HOOK_FAILED
+            )
+        );
+    }
+
+    #[test]
+    fn test_after_hook_gets_the_result() {
+        let dir = TempDir::new().unwrap();
+        let hooks = hooks(
+            HookOp::Push,
+            false,
+            "echo $LZGIT_OPERATION $LZGIT_RESULT >> out",
+        );
+        hooks.run(HookOp::Push, dir.path(), &[], || Ok(())).unwrap();
+        let err = hooks.run(
+            HookOp::Push,
+            dir.path(),
+            &[],
+            || Err("rejected".to_string()),
+        );
+        assert_eq!(err, Err("rejected".to_string()));
+        let out = std::fs::read_to_string(dir.path().join("out")).unwrap();
+        assert_eq!(out, "push ok\npush error\n");
+    }
+
+    #[test]
+    fn test_failing_after_hook_keeps_the_operation() {
+        let dir = TempDir::new().unwrap();
+        let hooks = hooks(HookOp::Checkout, false, "echo late >&2; exit 3");
+        let err = hooks
+            .run(HookOp::Checkout, dir.path(), &[], || {
+                std::fs::write(dir.path().join("done"), "").map_err(|e| e.to_string())
+            })
+            .unwrap_err();
+        assert_eq!(err, format!("{}: after-checkout\nlate", HOOK_FAILED));
+        assert!(dir.path().join("done").exists());
+
+        // The operation's own error wins over the hook's
+        let err = hooks.run(HookOp::Checkout, dir.path(), &[], || {
+            Err("dirty".to_string())
+        });
+        assert_eq!(err, Err("dirty".to_string()));
+    }
+}
@@ -0,0 +1,616 @@
+//! Key bindings from `[keys.<scope>]` in `config.toml`
+//!
+//! Each entry binds one of the [`BINDABLE`] actions by name to a key:
+//!
+//! ```toml
+//! [keys.global]                 # every tab but Terminal
+//! fetch = "ctrl+f"
+//! quit = "ctrl+q"               # quit, command_palette and theme_picker work there too
+//!
+//! [keys.git]                    # only in that tab; also explorer and log
+//! push = "P"
+//! stage_all = "ctrl+a"
+//! ```
+//!
+//! A bound key wins over whatever lzgit does with it in that tab, so it can also take
+//! a key away from its built-in use; the built-in key of the action keeps working
+//! unless something else is bound to it. Text inputs, pickers and dialogs still get
+//! their keys first.
+
+use crossterm::event::KeyEvent;
+
+use crate::app::{AppAction, Tab};
+use crate::config::KeySpec;
+
+/// Where a binding applies
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyScope {
+    Global,
+    Explorer,
+    Git,
+    Log,
+}
+
+impl KeyScope {
+    pub const ALL: [KeyScope; 4] = [
+        KeyScope::Global,
+        KeyScope::Explorer,
+        KeyScope::Git,
+        KeyScope::Log,
+    ];
+
+    /// Table name in `config.toml`
+    pub fn table(self) -> &'static str {
+        match self {
+            KeyScope::Global => "keys.global",
+            KeyScope::Explorer => "keys.explorer",
+            KeyScope::Git => "keys.git",
+            KeyScope::Log => "keys.log",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KeyScope::Global => "Everywhere",
+            KeyScope::Explorer => "Explorer",
+            KeyScope::Git => "Git",
+            KeyScope::Log => "Log",
+        }
+    }
+}
+
+/// An action keys can be bound to, by the name `[keys.<scope>]` uses for it
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Bindable {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub action: AppAction,
+}
+
+const fn bindable(name: &'static str, label: &'static str, action: AppAction) -> Bindable {
+    Bindable {
+        name,
+        label,
+        action,
+    }
+}
+
+/// Everything `[keys.<scope>]` can bind, in the order the cheatsheet lists it
+pub(crate) const BINDABLE: &[Bindable] = &[
+    bindable("quit", "Quit", AppAction::Quit),
+    bindable(
+        "command_palette",
+        "Command palette",
+        AppAction::OpenCommandPalette,
+    ),
+    bindable("theme_picker", "Themes", AppAction::OpenThemePicker),
+    bindable("help", "Show key bindings", AppAction::OpenKeyHelp),
+    bindable(
+        "explorer_tab",
+        "Explorer tab",
+        AppAction::SwitchTab(Tab::Explorer),
+    ),
+    bindable("git_tab", "Git tab", AppAction::SwitchTab(Tab::Git)),
+    bindable("log_tab", "Log tab", AppAction::SwitchTab(Tab::Log)),
+    bindable("refresh", "Refresh git status", AppAction::RefreshGit),
+    bindable("fetch", "Fetch", AppAction::GitFetch),
+    bindable("pull", "Pull…", AppAction::GitPull),
+    bindable("push", "Push…", AppAction::GitPush),
+    bindable("commit", "Commit drawer", AppAction::ToggleCommitDrawer),
+    bindable(
+        "stage",
+        "Stage or unstage the selection",
+        AppAction::ToggleGitStage,
+    ),
+    bindable(
+        "stage_all",
+        "Stage everything shown",
+        AppAction::GitStageAllVisible,
+    ),
+    bindable(
+        "unstage_all",
+        "Unstage everything shown",
+        AppAction::GitUnstageAllVisible,
+    ),
+    bindable("review", "Review staged hunks", AppAction::OpenReview),
+    bindable("branches", "Branches", AppAction::OpenBranchPicker),
+    bindable("stashes", "Stashes", AppAction::OpenStashPicker),
+    bindable("remotes", "Remotes", AppAction::OpenRemotes),
+    bindable("bookmarks", "Bookmarks", AppAction::OpenBookmarks),
+    bindable(
+        "toggle_hidden",
+        "Show hidden files",
+        AppAction::ToggleHidden,
+    ),
+    bindable("find_file", "Find a file", AppAction::OpenFileFinder),
+    bindable("lock_screen", "Lock the screen", AppAction::LockScreen),
+];
+
+/// The action `name` binds under `[keys.<scope>]`.
+pub(crate) fn action_named(name: &str) -> Option<AppAction> {
+    BINDABLE
+        .iter()
+        .find(|b| b.name == name)
+        .map(|b| b.action.clone())
+}
+
+/// The key lzgit gives `action` when nothing is bound, and where.
+pub(crate) fn builtin(action: &AppAction) -> Option<(KeyScope, KeySpec)> {
+    let (scope, c) = match action {
+        AppAction::Quit => (KeyScope::Global, 'q'),
+        AppAction::OpenCommandPalette => return Some((KeyScope::Global, KeySpec::ctrl('p'))),
+        AppAction::OpenThemePicker => (KeyScope::Global, 'T'),
+        AppAction::OpenKeyHelp => (KeyScope::Global, '?'),
+        AppAction::SwitchTab(Tab::Explorer) => (KeyScope::Global, '1'),
+        AppAction::SwitchTab(Tab::Git) => (KeyScope::Global, '2'),
+        AppAction::SwitchTab(Tab::Log) => (KeyScope::Global, '3'),
+        AppAction::RefreshGit => (KeyScope::Git, 'r'),
+        AppAction::ToggleCommitDrawer => (KeyScope::Git, 'c'),
+        AppAction::ToggleGitStage => (KeyScope::Git, ' '),
+        AppAction::GitStageAllVisible => (KeyScope::Git, 'A'),
+        AppAction::GitUnstageAllVisible => (KeyScope::Git, 'U'),
+        AppAction::OpenBranchPicker => (KeyScope::Git, 'B'),
+        AppAction::OpenBookmarks => (KeyScope::Explorer, 'b'),
+        AppAction::ToggleHidden => (KeyScope::Explorer, '.'),
+        AppAction::OpenFileFinder => return Some((KeyScope::Global, KeySpec::ctrl('o'))),
+        _ => return None,
+    };
+    Some((scope, KeySpec::char(c)))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Binding {
+    pub scope: KeyScope,
+    pub action: AppAction,
+    pub key: KeySpec,
+}
+
+/// The bindings read from `config.toml`, on top of the built-in keys
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Keymap {
+    pub(crate) fn bind(&mut self, scope: KeyScope, action: AppAction, key: KeySpec) {
+        self.bindings.push(Binding { scope, action, key });
+    }
+
+    /// Action bound to `key` in `scope`, before any bound everywhere.
+    pub(crate) fn lookup(&self, scope: KeyScope, key: &KeyEvent) -> Option<AppAction> {
+        [scope, KeyScope::Global].into_iter().find_map(|s| {
+            self.bindings
+                .iter()
+                .find(|b| b.scope == s && b.key.matches(key))
+                .map(|b| b.action.clone())
+        })
+    }
+
+    /// Whether `key` runs `action` everywhere, for keys that work over dialogs and in
+    /// the Terminal tab too.
+    pub(crate) fn pressed(&self, action: &AppAction, key: &KeyEvent) -> bool {
+        self.effective(KeyScope::Global, action)
+            .iter()
+            .any(|spec| spec.matches(key))
+    }
+
+    /// Keys that run `action` in `scope`: its bindings there, then its built-in key
+    /// unless a binding took that key over.
+    pub(crate) fn effective(&self, scope: KeyScope, action: &AppAction) -> Vec<KeySpec> {
+        let mut keys: Vec<KeySpec> = self
+            .bindings
+            .iter()
+            .filter(|b| b.scope == scope && b.action == *action)
+            .map(|b| b.key)
+            .collect();
+        if let Some((builtin_scope, key)) = builtin(action)
+            && builtin_scope == scope
+            && !self
+                .bindings
+                .iter()
+                .any(|b| b.key == key && (b.scope == scope || b.scope == KeyScope::Global))
+        {
+            keys.push(key);
+        }
+        keys
+    }
+
+    /// Whether a binding in `scope`, or everywhere, took over the built-in `key`.
+    pub fn takes_over(&self, scope: KeyScope, key: &str) -> bool {
+        self.bindings
+            .iter()
+            .any(|b| (b.scope == scope || b.scope == KeyScope::Global) && b.key.to_string() == key)
+    }
+
+    /// The hint bar for `context`, with the keys that work now: bound actions under
+    /// their key in `scope`, built-in keys unless a binding took one of them over.
+    /// Without a scope the tab bindings don't apply, so only built-in keys show.
+    pub fn bar(&self, context: &str, scope: Option<KeyScope>) -> Vec<(String, &'static str)> {
+        bar_hints(context)
+            .iter()
+            .filter_map(|hint| match hint {
+                BarHint::Action(action, what) => {
+                    let scope = scope?;
+                    let key = self
+                        .effective(scope, action)
+                        .into_iter()
+                        .chain(self.effective(KeyScope::Global, action))
+                        .next()?;
+                    Some((key.to_string(), *what))
+                }
+                BarHint::Key(keys, what) => {
+                    let taken = scope.is_some_and(|scope| {
+                        keys.split(' ').any(|key| self.takes_over(scope, key))
+                    });
+                    (!taken).then(|| (keys.to_string(), *what))
+                }
+            })
+            .take(BAR_LEN)
+            .collect()
+    }
+}
+
+/// How many keys the hint bar shows at most
+const BAR_LEN: usize = 6;
+
+/// An entry of the hint bar at the bottom of the screen
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum BarHint {
+    /// Shown under whatever key runs the action
+    Action(AppAction, &'static str),
+    /// A built-in key, gone once a binding takes it over
+    Key(&'static str, &'static str),
+}
+
+/// What the hint bar offers in a [`KeyHint::context`], most useful first
+pub(crate) fn bar_hints(context: &str) -> &'static [BarHint] {
+    use BarHint::{Action, Key};
+    const EXPLORER: &[BarHint] = &[
+        Key("h l", "parent, open"),
+        Key("e", "edit"),
+        Key("a", "new"),
+        Key("d", "delete"),
+        Action(AppAction::OpenBookmarks, "bookmarks"),
+        Action(AppAction::OpenFileFinder, "find"),
+    ];
+    const GIT: &[BarHint] = &[
+        Action(AppAction::ToggleGitStage, "stage"),
+        Action(AppAction::ToggleCommitDrawer, "commit"),
+        Key("v", "select lines"),
+        Key("{ }", "hunks"),
+        Action(AppAction::OpenBranchPicker, "branches"),
+        Action(AppAction::OpenKeyHelp, "all keys"),
+    ];
+    const GIT_CONFLICTS: &[BarHint] = &[
+        Key("n p", "conflicts"),
+        Key("o", "ours"),
+        Key("t", "theirs"),
+        Key("b", "both"),
+        Key("a", "resolved"),
+        Action(AppAction::OpenKeyHelp, "all keys"),
+    ];
+    const LOG: &[BarHint] = &[
+        Key("/", "filter"),
+        Key("space", "mark"),
+        Key("d f", "diff, files"),
+        Key("i", "inspect"),
+        Key("B", "branches"),
+        Action(AppAction::OpenKeyHelp, "all keys"),
+    ];
+    match context {
+        "Explorer" => EXPLORER,
+        "Git" => GIT,
+        "Git conflicts" => GIT_CONFLICTS,
+        "Commit drawer" => &[
+            Key("ctrl+enter", "commit"),
+            Key("ctrl+g", "generate"),
+            Key("ctrl+l", "templates"),
+            Key("ctrl+t", "guided"),
+            Key("ctrl+e", "amend"),
+            Key("esc", "close"),
+        ],
+        "Review" => &[
+            Key("space", "approve"),
+            Key("u", "unstage"),
+            Key("backspace", "back"),
+            Key("enter", "commit"),
+            Key("esc", "close"),
+        ],
+        "Diff selection" => &[
+            Key("y", "copy"),
+            Key("Y", "with line numbers"),
+            Key("p", "as a patch"),
+            Key("v", "cancel"),
+        ],
+        "Log" => LOG,
+        "Blame" => &[
+            Key("enter", "show the commit"),
+            Key("y", "copy the hash"),
+            Key("q", "close"),
+        ],
+        _ => &[],
+    }
+}
+
+/// A built-in key for the cheatsheet under `?`, outside what [`BINDABLE`] covers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyHint {
+    /// Tab or situation the key works in; a [`KeyScope::label`] for the tabs
+    pub context: &'static str,
+    pub keys: &'static str,
+    pub what: &'static str,
+}
+
+const fn hint(context: &'static str, keys: &'static str, what: &'static str) -> KeyHint {
+    KeyHint {
+        context,
+        keys,
+        what,
+    }
+}
+
+/// Keys lzgit handles itself, grouped by context in the order they are listed
+pub const BUILTIN_KEYS: &[KeyHint] = &[
+    hint("Everywhere", "alt+1..9", "Jump to a bookmark"),
+    hint("Everywhere", "esc", "Close the open dialog or picker"),
+    hint("Lists and diffs", "j k", "Move down, up"),
+    hint("Lists and diffs", "pgdn pgup", "Page down, up"),
+    hint("Lists and diffs", "ctrl+d ctrl+u", "Half a page down, up"),
+    hint("Lists and diffs", "home end", "First, last"),
+    hint("Lists and diffs", "gg G", "Top, bottom"),
+    hint("Lists and diffs", "tab", "Switch pane"),
+    hint(
+        "Lists and diffs",
+        "15j 3}",
+        "Repeat a movement (with count_prefix)",
+    ),
+    hint("Explorer", "h l", "Parent folder, open"),
+    hint("Explorer", "a", "New file or folder"),
+    hint("Explorer", "f2", "Rename"),
+    hint("Explorer", "d del", "Delete"),
+    hint("Explorer", "e", "Open in editor"),
+    hint("Explorer", "i", "Add to .gitignore"),
+    hint("Explorer", "u", "Undo the last file operation"),
+    hint("Explorer", "ctrl+r", "Redo"),
+    hint("Explorer", "r", "Reload the folder"),
+    hint("Explorer", "z", "Zoom the preview"),
+    hint("Explorer", "B", "Blame"),
+    hint("Explorer", "H", "Syntax highlight"),
+    hint("Explorer", "R", "Auto-refresh"),
+    hint("Explorer", "ctrl+up ctrl+down", "Scroll the preview"),
+    hint("Git", "v", "Select diff lines"),
+    hint("Git", ".", "Repeat the last stage, unstage or jump"),
+    hint("Git", "ctrl+a", "Select everything shown"),
+    hint("Git", "ctrl+z", "Undo a discard"),
+    hint("Git", "ctrl+y", "Redo a discard"),
+    hint("Git", "z", "Stash…"),
+    hint("Git", "S", "Stash the hunk at the top"),
+    hint("Git", "N", "New branch"),
+    hint("Git", "b", "Blame"),
+    hint("Git", "i", "Add to .gitignore"),
+    hint("Git", "I", "Init wizard"),
+    hint("Git", "s", "Side by side, or file source"),
+    hint("Git", "F", "Whole file"),
+    hint("Git", "+ -", "More, default context"),
+    hint("Git", "{ }", "Previous, next hunk"),
+    hint("Git", "[f ]f", "Previous, next file"),
+    hint("Git", "[ ]", "Narrow, widen the tree"),
+    hint("Git", "w", "Wrap diff"),
+    hint("Git", "H", "Syntax highlight"),
+    hint("Git", "#", "Line numbers"),
+    hint("Git", "u", "Update the submodule"),
+    hint("Git", "backspace", "Back to the superproject"),
+    hint("Git", "enter", "Expand, or show the commit"),
+    hint("Git conflicts", "n p", "Next, previous conflict"),
+    hint("Git conflicts", "o t b", "Take ours, theirs, both"),
+    hint("Git conflicts", "a", "Mark resolved"),
+    hint("Commit drawer", "ctrl+enter", "Commit"),
+    hint("Commit drawer", "ctrl+g", "Generate the message"),
+    hint("Commit drawer", "ctrl+l", "Templates and recent messages"),
+    hint("Commit drawer", "up down", "Same, in an empty message"),
+    hint("Commit drawer", "ctrl+e", "Amend"),
+    hint("Commit drawer", "ctrl+o", "Author"),
+    hint("Commit drawer", "ctrl+k", "Co-authors"),
+    hint("Commit drawer", "ctrl+a", "Advanced (date)"),
+    hint("Commit drawer", "ctrl+t", "Guided conventional commit"),
+    hint("Commit drawer", "tab", "Type, scope, then the message"),
+    hint(
+        "Commit drawer",
+        "left right !",
+        "Change the type, breaking change",
+    ),
+    hint("Commit drawer", "ctrl+r", "Review staged hunks"),
+    hint("Review", "space", "Approve the hunk"),
+    hint("Review", "u", "Unstage the hunk"),
+    hint("Review", "backspace", "Back"),
+    hint("Review", "enter", "Commit when done"),
+    hint("Diff selection", "y", "Copy the lines"),
+    hint("Diff selection", "Y", "Copy with line numbers"),
+    hint("Diff selection", "p", "Copy as a patch"),
+    hint("Diff selection", "v", "Cancel"),
+    hint("Log", "h r t c", "History, reflog, stashes, commands"),
+    hint("Log", "/", "Filter"),
+    hint("Log", "ctrl+u", "Clear the filter"),
+    hint("Log", "ctrl+f", "Find in this commit"),
+    hint("Log", ":", "Go to a commit"),
+    hint("Log", "A", "Filter by author"),
+    hint("Log", "B", "Branches"),
+    hint("Log", "T", "Tags"),
+    hint("Log", "b", "Refs sidebar"),
+    hint("Log", "space", "Mark for compare"),
+    hint("Log", "d f F", "Diff or files"),
+    hint("Log", "m", "Diff against…"),
+    hint("Log", "[ ]", "Older, newer revision of the file"),
+    hint("Log", "C", "Cherry-pick"),
+    hint("Log", "R", "Revert"),
+    hint("Log", "X", "Reset…"),
+    hint("Log", "O", "Check out"),
+    hint("Log", "S", "Search the full history"),
+    hint("Log", "L", "Load more"),
+    hint("Log", "i", "Inspect"),
+    hint("Log", "z", "Zoom"),
+    hint("Log", "v", "Select diff lines"),
+    hint("Log", "s", "Side by side"),
+    hint("Log", "{ }", "Previous, next hunk"),
+    hint("Log", "[f ]f", "Previous, next file"),
+    hint("Log", "o", "Reflog operation filter"),
+    hint("Log", "a p d", "Apply, pop, drop the stash"),
+    hint("Log", "x", "Clear the command log"),
+    hint("Blame", "enter", "Show the commit"),
+    hint("Blame", "y", "Copy the hash"),
+    hint("Blame", "q", "Close"),
+    hint("Pickers", "type", "Filter"),
+    hint("Pickers", "enter", "Pick"),
+    hint("Pickers", "ctrl+n", "New branch"),
+    hint("Pickers", "ctrl+r", "Rename the branch"),
+    hint("Pickers", "ctrl+t", "Set upstream"),
+    hint("Pickers", "ctrl+e", "Describe the branch"),
+    hint("Pickers", "del", "Delete the branch"),
+    hint("Pickers", "a p d", "Apply, pop, drop the stash"),
+    hint("Pickers", "ctrl+e", "Show the found file in the Explorer"),
+    hint("Pickers", "tab", "Tick a co-author"),
+    hint("Pickers", "J K", "Move the bookmark"),
+    hint("Pickers", "r x", "Rename, remove"),
+    hint(
+        "Terminal",
+        "any",
+        "Goes to the shell, except quit, palette and theme",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    #[test]
+    fn test_tab_bindings_win_and_take_over_builtin_keys() {
+        let mut keymap = Keymap::default();
+        keymap.bind(KeyScope::Global, AppAction::GitFetch, KeySpec::ctrl('f'));
+        keymap.bind(KeyScope::Git, AppAction::GitPush, KeySpec::char('A'));
+        keymap.bind(KeyScope::Git, AppAction::GitPull, KeySpec::ctrl('f'));
+
+        let ctrl_f = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        assert_eq!(
+            keymap.lookup(KeyScope::Git, &ctrl_f),
+            Some(AppAction::GitPull)
+        );
+        assert_eq!(
+            keymap.lookup(KeyScope::Log, &ctrl_f),
+            Some(AppAction::GitFetch)
+        );
+        let a = KeyEvent::new(KeyCode::Char('A'), KeyModifiers::SHIFT);
+        assert_eq!(keymap.lookup(KeyScope::Explorer, &a), None);
+
+        assert!(
+            keymap
+                .effective(KeyScope::Git, &AppAction::GitStageAllVisible)
+                .is_empty()
+        );
+        assert_eq!(
+            keymap.effective(KeyScope::Git, &AppAction::GitUnstageAllVisible),
+            vec![KeySpec::char('U')]
+        );
+        assert!(keymap.takes_over(KeyScope::Git, "A"));
+        assert!(keymap.takes_over(KeyScope::Explorer, "ctrl+f"));
+        assert!(!keymap.takes_over(KeyScope::Explorer, "A"));
+    }
+
+    #[test]
+    fn test_bar_shows_the_keys_that_work() {
+        let mut keymap = Keymap::default();
+        let keys = |bar: Vec<(String, &str)>| bar.into_iter().map(|(k, _)| k).collect::<Vec<_>>();
+        assert_eq!(
+            keys(keymap.bar("Git", Some(KeyScope::Git))),
+            ["space", "c", "v", "{ }", "B", "?"]
+        );
+
+        keymap.bind(KeyScope::Git, AppAction::ToggleGitStage, KeySpec::char('s'));
+        keymap.bind(
+            KeyScope::Git,
+            AppAction::ToggleCommitDrawer,
+            KeySpec::char('v'),
+        );
+        keymap.bind(KeyScope::Global, AppAction::OpenKeyHelp, KeySpec::ctrl('h'));
+        assert_eq!(
+            keys(keymap.bar("Git", Some(KeyScope::Git))),
+            ["s", "v", "{ }", "B", "ctrl+h"]
+        );
+
+        keymap.bind(KeyScope::Git, AppAction::GitFetch, KeySpec::char('o'));
+        let conflicts = keymap.bar("Git conflicts", Some(KeyScope::Git));
+        assert_eq!(keys(conflicts.clone()), ["n p", "t", "b", "a", "ctrl+h"]);
+        assert!(conflicts.contains(&("t".to_string(), "theirs")));
+        assert_eq!(
+            keys(keymap.bar("Diff selection", None)),
+            ["y", "Y", "p", "v"]
+        );
+    }
+
+    /// Every built-in key listed for a tab has an arm in that tab's part of the key
+    /// handler or in the function that turns its keys into actions, or is the built-in
+    /// key of a bindable action there.
+    #[test]
+    fn test_builtin_keys_reach_the_dispatcher() {
+        let source = include_str!("app/input.rs");
+        let tabs = source
+            .find("app.keymap_action(&key)")
+            .map(|at| &source[at..])
+            .unwrap();
+        let branch = |tab: &str| {
+            let from = tabs.find(&format!("Tab::{} => {{", tab)).unwrap();
+            let len = tabs[from + 1..]
+                .find("\n                        Tab::")
+                .unwrap();
+            let at = source
+                .find(&format!("fn {}_key(", tab.to_lowercase()))
+                .unwrap();
+            let end = source[at + 1..]
+                .find("\nfn ")
+                .map_or(source.len(), |n| at + 1 + n);
+            format!("{}{}", &tabs[from..from + 1 + len], &source[at..end])
+        };
+        for hint in BUILTIN_KEYS {
+            let (tab, scope) = match hint.context {
+                "Explorer" => ("Explorer", KeyScope::Explorer),
+                "Git" | "Git conflicts" => ("Git", KeyScope::Git),
+                "Log" => ("Log", KeyScope::Log),
+                _ => continue,
+            };
+            for key in hint.keys.split(' ') {
+                // "ctrl+up" to "up", a lone "+" stays
+                let name = key
+                    .rsplit_once('+')
+                    .filter(|(_, name)| !name.is_empty())
+                    .map_or(key, |(_, name)| name);
+                let code = match name {
+                    "enter" => KeyCode::Enter,
+                    "backspace" => KeyCode::Backspace,
+                    "del" => KeyCode::Delete,
+                    "space" => KeyCode::Char(' '),
+                    "tab" => KeyCode::Tab,
+                    "up" => KeyCode::Up,
+                    "down" => KeyCode::Down,
+                    "f2" => KeyCode::F(2),
+                    // Chords start with their bracket
+                    "[f" | "]f" => KeyCode::Char(name.chars().next().unwrap()),
+                    name if name.chars().count() == 1 => {
+                        KeyCode::Char(name.chars().next().unwrap())
+                    }
+                    name => panic!("no key code for `{}` in the {} hints", name, hint.context),
+                };
+                let bound = BINDABLE.iter().any(|b| {
+                    builtin(&b.action)
+                        .is_some_and(|(s, spec)| s == scope && spec.to_string() == key)
+                });
+                assert!(
+                    bound || branch(tab).contains(&format!("KeyCode::{:?}", code)),
+                    "`{}` ({}) is listed for {} but nothing there handles it",
+                    key,
+                    hint.what,
+                    hint.context
+                );
+            }
+        }
+    }
+}
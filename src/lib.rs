@@ -0,0 +1,71 @@
+//! lzgit as a library
+//!
+//! The `lzgit` binary only parses its arguments and sets up the terminal. [`app`]
+//! holds the `App` state, its background jobs, input handling and rendering; the
+//! other modules do not draw anything and can be used and tested on their own:
+//!
+//! - [`git_ops`] runs git and parses its output: status, diffs, history, branches,
+//!   stashes, blame and the commands behind every button.
+//! - [`git`] is the Git tab's state ([`git::GitState`]): the file list and tree,
+//!   sections, the loaded diff and its hunks.
+//! - [`git_diff_loader`] and [`preview_loader`] load diffs and file previews off the
+//!   UI thread with cancellation.
+//! - The remaining modules hold one feature's state or settings each, such as
+//!   [`branch`], [`blame`], [`review`] and [`config`].
+//!
+//! Functions in [`git_ops`] take the repository root and block until git exits, so
+//! tests can run them against a temporary repository:
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! let repo = Path::new("/path/to/repo");
+//! for branch in lzgit::git_ops::list_branches(repo)? {
+//!     let (ahead, behind) = branch.ahead_behind();
+//!     println!("{} ↑{} ↓{}", branch.name, ahead, behind);
+//! }
+//! # Ok::<(), String>(())
+//! ```
+
+use std::{env, path::PathBuf};
+
+pub mod actions;
+pub mod app;
+pub mod blame;
+pub mod bookmarks;
+pub mod branch;
+pub mod changelog;
+pub mod clipboard;
+pub mod commit;
+pub mod config;
+pub mod confirm;
+pub mod conflict;
+pub mod control;
+pub mod export;
+pub mod file_ops;
+pub mod forge;
+pub mod git;
+pub mod git_diff_loader;
+pub mod git_ops;
+pub mod gitignore;
+pub mod highlight;
+pub mod hooks;
+pub mod keymap;
+pub mod list_nav;
+pub mod lock_screen;
+pub mod multiplexer;
+pub mod openrouter;
+pub mod preview_cache;
+pub mod preview_loader;
+pub mod refs;
+pub mod review;
+pub mod theme;
+
+/// `$XDG_STATE_HOME/lzgit` (or `~/.local/state/lzgit`) for logs and history.
+pub fn state_dir() -> Option<PathBuf> {
+    let home = env::home_dir()?;
+    let base = env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".local").join("state"));
+    Some(base.join("lzgit"))
+}
@@ -0,0 +1,262 @@
+//! Shared list navigation keys
+//!
+//! Every list and scrollable pane moves with the same keys: `j`/`k` and the arrows,
+//! PageUp/PageDown, `Ctrl+d`/`Ctrl+u` for half a page, and Home/End or `gg`/`G` for
+//! the ends. [`NavKeys`] turns key presses into a [`ListNav`], which each pane
+//! applies to its selection or scroll offset. Diff panes jump between files with
+//! `[f`/`]f`; a `[` or `]` that no `f` follows does what the key does on its own.
+//!
+//! With `[behavior] count_prefix = true`, digits typed first repeat the movement, as in
+//! vim (`15j`, `3}`). Digits also switch tabs, so a lone `1`-`3` that nothing follows
+//! within [`COUNT_TIMEOUT`] is handed back as a tab switch.
+
+use std::time::{Duration, Instant};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Rows moved by PageUp/PageDown
+pub const PAGE: i32 = 10;
+
+/// How long a count prefix waits for its movement
+pub const COUNT_TIMEOUT: Duration = Duration::from_millis(700);
+
+/// Counts beyond this are clamped
+const MAX_COUNT: u32 = 9999;
+
+/// Reaches either end of any list without overflowing `cur + delta`
+const JUMP: i32 = i32::MAX / 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListNav {
+    /// Move by this many rows; negative is up
+    By(i32),
+    Top,
+    Bottom,
+}
+
+impl ListNav {
+    /// Delta for the `move_selection(delta)` helpers, which clamp to the list.
+    pub fn delta(self) -> i32 {
+        match self {
+            ListNav::By(n) => n,
+            ListNav::Top => -JUMP,
+            ListNav::Bottom => JUMP,
+        }
+    }
+
+    /// New scroll offset; `Bottom` overshoots and relies on the renderer clamping.
+    pub fn scroll(self, offset: usize) -> usize {
+        match self {
+            ListNav::By(n) => offset.saturating_add_signed(n as isize),
+            ListNav::Top => 0,
+            ListNav::Bottom => usize::MAX,
+        }
+    }
+
+    pub fn scroll_u16(self, offset: u16) -> u16 {
+        self.scroll(offset as usize).min(u16::MAX as usize) as u16
+    }
+}
+
+/// Maps key presses to movements, remembering a pending `g` for `gg`, a `[` or `]`
+/// for `[f`/`]f` and the count prefix typed so far.
+#[derive(Debug, Default)]
+pub struct NavKeys {
+    pending_g: bool,
+    bracket: Option<(char, Instant)>,
+    count: Option<u32>,
+    count_at: Option<Instant>,
+    /// Count that applied to the key being handled
+    repeat: u32,
+}
+
+impl NavKeys {
+    /// Add a digit to the count prefix; `0` only continues one.
+    pub fn push_digit(&mut self, digit: u32, now: Instant) {
+        let count = match self.count {
+            Some(count) => (count * 10 + digit).min(MAX_COUNT),
+            None if digit == 0 => return,
+            None => digit,
+        };
+        self.count = Some(count);
+        self.count_at = Some(now);
+    }
+
+    pub fn pending_count(&self) -> Option<u32> {
+        self.count
+    }
+
+    /// A count that no key followed within [`COUNT_TIMEOUT`].
+    pub fn take_stale_count(&mut self, now: Instant) -> Option<u32> {
+        let at = self.count_at?;
+        if now.duration_since(at) < COUNT_TIMEOUT {
+            return None;
+        }
+        self.count_at = None;
+        self.count.take()
+    }
+
+    /// Hold a `[` or `]` until the next key says whether it starts `[f`/`]f`.
+    pub fn push_bracket(&mut self, bracket: char, now: Instant) {
+        self.bracket = Some((bracket, now));
+    }
+
+    pub fn pending_bracket(&self) -> Option<char> {
+        self.bracket.map(|(bracket, _)| bracket)
+    }
+
+    pub fn take_bracket(&mut self) -> Option<char> {
+        self.bracket.take().map(|(bracket, _)| bracket)
+    }
+
+    /// A `[` or `]` that no key followed within [`COUNT_TIMEOUT`].
+    pub fn take_stale_bracket(&mut self, now: Instant) -> Option<char> {
+        let (_, at) = self.bracket?;
+        if now.duration_since(at) < COUNT_TIMEOUT {
+            return None;
+        }
+        self.take_bracket()
+    }
+
+    pub fn clear(&mut self) {
+        self.pending_g = false;
+        self.bracket = None;
+        self.count = None;
+        self.count_at = None;
+    }
+
+    /// Count prefix of the key last passed to [`NavKeys::key`], 1 without one; for
+    /// movements that are not list moves, such as hunk jumps.
+    pub fn repeat(&self) -> u32 {
+        self.repeat.max(1)
+    }
+
+    /// Movement for `key`, if it is one. `letters` enables `j`/`k`/`g`/`G`; lists
+    /// that filter as you type pass `false`. Arrows and paging keys with modifiers
+    /// are left to the pane (Ctrl+Up scrolls the preview, Shift+Up reorders).
+    pub fn key(&mut self, key: &KeyEvent, letters: bool) -> Option<ListNav> {
+        let pending_g = std::mem::take(&mut self.pending_g);
+        self.count_at = None;
+        self.repeat = self.count.take().unwrap_or(1);
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let plain = !key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let nav = match key.code {
+            KeyCode::Char('d') if ctrl => ListNav::By(PAGE / 2),
+            KeyCode::Char('u') if ctrl => ListNav::By(-PAGE / 2),
+            KeyCode::Down if plain => ListNav::By(1),
+            KeyCode::Up if plain => ListNav::By(-1),
+            KeyCode::PageDown if plain => ListNav::By(PAGE),
+            KeyCode::PageUp if plain => ListNav::By(-PAGE),
+            KeyCode::Home if plain => ListNav::Top,
+            KeyCode::End if plain => ListNav::Bottom,
+            _ if !letters || ctrl || key.modifiers.contains(KeyModifiers::ALT) => return None,
+            KeyCode::Char('j') => ListNav::By(1),
+            KeyCode::Char('k') => ListNav::By(-1),
+            KeyCode::Char('G') => ListNav::Bottom,
+            KeyCode::Char('g') if pending_g => ListNav::Top,
+            KeyCode::Char('g') => {
+                self.pending_g = true;
+                return None;
+            }
+            _ => return None,
+        };
+        Some(match nav {
+            ListNav::By(n) => ListNav::By(n.saturating_mul(self.repeat as i32)),
+            nav => nav,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(keys: &mut NavKeys, code: KeyCode, modifiers: KeyModifiers) -> Option<ListNav> {
+        keys.key(&KeyEvent::new(code, modifiers), true)
+    }
+
+    #[test]
+    fn test_gg_needs_two_presses() {
+        let mut keys = NavKeys::default();
+        let none = KeyModifiers::NONE;
+        assert_eq!(press(&mut keys, KeyCode::Char('g'), none), None);
+        assert_eq!(
+            press(&mut keys, KeyCode::Char('g'), none),
+            Some(ListNav::Top)
+        );
+        assert_eq!(press(&mut keys, KeyCode::Char('g'), none), None);
+        assert_eq!(press(&mut keys, KeyCode::Char('x'), none), None);
+        assert_eq!(press(&mut keys, KeyCode::Char('g'), none), None);
+        assert_eq!(
+            press(&mut keys, KeyCode::Char('G'), KeyModifiers::SHIFT),
+            Some(ListNav::Bottom)
+        );
+    }
+
+    #[test]
+    fn test_modified_arrows_are_not_navigation() {
+        let mut keys = NavKeys::default();
+        assert_eq!(press(&mut keys, KeyCode::Up, KeyModifiers::CONTROL), None);
+        assert_eq!(press(&mut keys, KeyCode::Down, KeyModifiers::SHIFT), None);
+        assert_eq!(
+            press(&mut keys, KeyCode::Char('d'), KeyModifiers::CONTROL),
+            Some(ListNav::By(PAGE / 2))
+        );
+        let filter = KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE);
+        assert_eq!(keys.key(&filter, false), None);
+    }
+
+    #[test]
+    fn test_count_prefix() {
+        let mut keys = NavKeys::default();
+        let now = Instant::now();
+        keys.push_digit(0, now);
+        assert_eq!(keys.pending_count(), None);
+        keys.push_digit(1, now);
+        keys.push_digit(5, now);
+        assert_eq!(
+            press(&mut keys, KeyCode::Char('j'), KeyModifiers::NONE),
+            Some(ListNav::By(15))
+        );
+        assert_eq!(
+            press(&mut keys, KeyCode::Char('j'), KeyModifiers::NONE),
+            Some(ListNav::By(1))
+        );
+
+        keys.push_digit(3, now);
+        assert_eq!(
+            press(&mut keys, KeyCode::Char('}'), KeyModifiers::NONE),
+            None
+        );
+        assert_eq!(keys.repeat(), 3);
+
+        keys.push_digit(2, now);
+        assert_eq!(keys.take_stale_count(now), None);
+        assert_eq!(keys.take_stale_count(now + COUNT_TIMEOUT), Some(2));
+        assert_eq!(keys.pending_count(), None);
+    }
+
+    #[test]
+    fn test_bracket_waits_for_its_f() {
+        let mut keys = NavKeys::default();
+        let now = Instant::now();
+        keys.push_bracket('[', now);
+        assert_eq!(keys.pending_bracket(), Some('['));
+        assert_eq!(keys.take_stale_bracket(now), None);
+        assert_eq!(keys.take_stale_bracket(now + COUNT_TIMEOUT), Some('['));
+        assert_eq!(keys.pending_bracket(), None);
+
+        keys.push_bracket(']', now);
+        keys.clear();
+        assert_eq!(keys.take_bracket(), None);
+    }
+
+    #[test]
+    fn test_scroll_clamps_at_top() {
+        assert_eq!(ListNav::By(-PAGE).scroll(3), 0);
+        assert_eq!(ListNav::Bottom.scroll_u16(3), u16::MAX);
+        assert_eq!(ListNav::Top.delta(), -JUMP);
+    }
+}
@@ -0,0 +1,86 @@
+//! The lock screen for shared terminals
+//!
+//! After `[privacy] lock_after` idle minutes, or on "Lock the screen", lzgit draws
+//! nothing but the lock screen until a key is pressed. When `passphrase_env` names a
+//! set environment variable, its value has to be typed instead.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Debug)]
+pub struct LockScreen {
+    passphrase: Option<String>,
+    /// Typed so far, shown masked
+    pub typed: String,
+    /// The last passphrase entered was wrong
+    pub wrong: bool,
+}
+
+impl LockScreen {
+    pub fn new(passphrase: Option<String>) -> Self {
+        Self {
+            passphrase: passphrase.filter(|p| !p.is_empty()),
+            typed: String::new(),
+            wrong: false,
+        }
+    }
+
+    pub fn needs_passphrase(&self) -> bool {
+        self.passphrase.is_some()
+    }
+
+    /// Take a key press; true once it unlocks.
+    pub fn key(&mut self, key: &KeyEvent) -> bool {
+        let Some(passphrase) = &self.passphrase else {
+            return true;
+        };
+        match key.code {
+            KeyCode::Enter => {
+                if self.typed == *passphrase {
+                    return true;
+                }
+                self.wrong = true;
+                self.typed.clear();
+            }
+            KeyCode::Backspace => {
+                self.typed.pop();
+            }
+            KeyCode::Esc => self.typed.clear(),
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.typed.push(c);
+            }
+            _ => {}
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(lock: &mut LockScreen, code: KeyCode) -> bool {
+        lock.key(&KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_unlock() {
+        let mut lock = LockScreen::new(None);
+        assert!(!lock.needs_passphrase());
+        assert!(press(&mut lock, KeyCode::Char('x')));
+        assert!(!LockScreen::new(Some(String::new())).needs_passphrase());
+
+        let mut lock = LockScreen::new(Some("ok".to_string()));
+        for c in "no".chars() {
+            assert!(!press(&mut lock, KeyCode::Char(c)));
+        }
+        assert!(!press(&mut lock, KeyCode::Enter));
+        assert!(lock.wrong && lock.typed.is_empty());
+
+        for c in "oks".chars() {
+            press(&mut lock, KeyCode::Char(c));
+        }
+        press(&mut lock, KeyCode::Backspace);
+        assert_eq!(lock.typed, "ok");
+        assert!(press(&mut lock, KeyCode::Enter));
+    }
+}
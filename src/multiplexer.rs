@@ -0,0 +1,151 @@
+//! Open files and diffs in a new tmux or zellij pane instead of suspending the TUI
+//!
+//! The multiplexer is detected from `$TMUX` / `$ZELLIJ`. The command that opens the
+//! pane is a template and can be overridden under `[panes]` in `config.toml`:
+//!
+//! ```toml
+//! [panes]
+//! tmux = "tmux split-window -h -c {cwd} {command}"
+//! zellij = "zellij run --cwd {cwd} -- sh -c {command}"
+//! ```
+//!
+//! `{cwd}` is the repository (or current) directory and `{command}` the editor or diff
+//! command to run in the pane; both are shell-quoted.
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+use crate::actions::shell_quote;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Multiplexer {
+    Tmux,
+    Zellij,
+}
+
+impl Multiplexer {
+    pub fn detect() -> Option<Self> {
+        if env::var_os("TMUX").is_some_and(|v| !v.is_empty()) {
+            Some(Multiplexer::Tmux)
+        } else if env::var_os("ZELLIJ").is_some() {
+            Some(Multiplexer::Zellij)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Multiplexer::Tmux => "tmux",
+            Multiplexer::Zellij => "zellij",
+        }
+    }
+
+    fn default_template(self) -> &'static str {
+        match self {
+            Multiplexer::Tmux => "tmux split-window -h -c {cwd} {command}",
+            Multiplexer::Zellij => "zellij run --cwd {cwd} -- sh -c {command}",
+        }
+    }
+}
+
+/// User overrides for the pane-opening command, per multiplexer.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PaneTemplates {
+    pub tmux: Option<String>,
+    pub zellij: Option<String>,
+}
+
+impl PaneTemplates {
+    fn template(&self, mux: Multiplexer) -> &str {
+        let custom = match mux {
+            Multiplexer::Tmux => self.tmux.as_deref(),
+            Multiplexer::Zellij => self.zellij.as_deref(),
+        };
+        custom
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or(mux.default_template())
+    }
+}
+
+fn expand(template: &str, cwd: &str, command: &str) -> String {
+    template
+        .trim()
+        .replace("{cwd}", &shell_quote(cwd))
+        .replace("{command}", &shell_quote(command))
+}
+
+/// Run `command` in a new pane of `mux`, starting in `cwd`.
+pub fn open_pane(
+    mux: Multiplexer,
+    templates: &PaneTemplates,
+    cwd: &Path,
+    command: &str,
+) -> Result<(), String> {
+    let expanded = expand(templates.template(mux), &cwd.to_string_lossy(), command);
+    let out = Command::new("sh")
+        .arg("-c")
+        .arg(&expanded)
+        .current_dir(cwd)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !out.status.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+        return Err(if stderr.is_empty() {
+            format!("{} exited with {}", mux.name(), out.status)
+        } else {
+            stderr
+        });
+    }
+    Ok(())
+}
+
+/// `$EDITOR` (or vim) on `path`.
+pub fn editor_command(path: &str) -> String {
+    let editor = env::var("EDITOR")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| "vim".to_string());
+    format!("{} {}", editor, shell_quote(path))
+}
+
+/// Colored `git` output piped through a pager that stays open.
+pub fn pager_command(git_args: &[&str]) -> String {
+    let args: Vec<String> = git_args.iter().map(|a| shell_quote(a)).collect();
+    format!("git -c color.ui=always {} | less -R", args.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_quotes_cwd_and_command() {
+        let templates = PaneTemplates::default();
+        assert_eq!(
+            expand(
+                templates.template(Multiplexer::Tmux),
+                "/repo dir",
+                "vim 'a.rs'"
+            ),
+            "tmux split-window -h -c '/repo dir' 'vim '\\''a.rs'\\'''"
+        );
+    }
+
+    #[test]
+    fn test_custom_template_overrides_default() {
+        let templates = PaneTemplates {
+            tmux: None,
+            zellij: Some("zellij run -f -- sh -c {command}".to_string()),
+        };
+        assert_eq!(
+            templates.template(Multiplexer::Zellij),
+            "zellij run -f -- sh -c {command}"
+        );
+        assert_eq!(
+            templates.template(Multiplexer::Tmux),
+            "tmux split-window -h -c {cwd} {command}"
+        );
+    }
+}
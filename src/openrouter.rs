@@ -9,11 +9,12 @@ pub struct OpenRouterConfig {
 }
 
 impl OpenRouterConfig {
-    pub fn from_env() -> Result<Self, String> {
-        let api_key = std::env::var("OPENROUTER_API_KEY")
-            .map_err(|_| "Missing OPENROUTER_API_KEY".to_string())?;
-        let model =
-            std::env::var("OPENROUTER_MODEL").unwrap_or_else(|_| "openai/gpt-5.2".to_string());
+    /// Read the key from `api_key_env` (default `OPENROUTER_API_KEY`); `model` from
+    /// the config wins over `OPENROUTER_MODEL`.
+    pub fn from_env(api_key_env: Option<&str>, model: Option<&str>) -> Result<Self, String> {
+        let key_var = api_key_env.unwrap_or("OPENROUTER_API_KEY");
+        let api_key = std::env::var(key_var).map_err(|_| format!("Missing {}", key_var))?;
+        let model = model_name(model);
         let referer = std::env::var("OPENROUTER_REFERER").ok();
         let title = std::env::var("OPENROUTER_TITLE").ok();
         Ok(Self {
@@ -25,6 +26,13 @@ impl OpenRouterConfig {
     }
 }
 
+pub fn model_name(configured: Option<&str>) -> String {
+    configured
+        .map(str::to_string)
+        .or_else(|| std::env::var("OPENROUTER_MODEL").ok())
+        .unwrap_or_else(|| "openai/gpt-5.2".to_string())
+}
+
 #[derive(Serialize)]
 struct ChatRequest {
     model: String,
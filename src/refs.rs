@@ -0,0 +1,235 @@
+//! Refs sidebar of the History tab
+//!
+//! Local branches, remote branches and tags are shown as a tree split on `/`, so
+//! `feature/login` sits under a `feature` folder and remotes group by remote name.
+//! Sections and folders collapse; tags start collapsed since repos can have many.
+
+use std::collections::BTreeSet;
+
+use ratatui::widgets::ListState;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RefKind {
+    Local,
+    Remote,
+    Tag,
+}
+
+impl RefKind {
+    pub const ALL: [RefKind; 3] = [RefKind::Local, RefKind::Remote, RefKind::Tag];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RefKind::Local => "Branches",
+            RefKind::Remote => "Remotes",
+            RefKind::Tag => "Tags",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RefEntry {
+    /// Short name, e.g. `main`, `origin/main` or `v1.0`
+    pub name: String,
+    pub kind: RefKind,
+    pub is_current: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RefItem {
+    Section(RefKind),
+    Folder {
+        key: String,
+        label: String,
+        depth: usize,
+    },
+    Ref {
+        idx: usize,
+        depth: usize,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct RefsSidebar {
+    pub open: bool,
+    pub refs: Vec<RefEntry>,
+    pub items: Vec<RefItem>,
+    /// Section labels and folder keys that are collapsed
+    pub collapsed: BTreeSet<String>,
+    pub list_state: ListState,
+    /// Ref under the last right-click, for the context menu
+    pub menu_target: Option<usize>,
+}
+
+impl Default for RefsSidebar {
+    fn default() -> Self {
+        Self {
+            open: false,
+            refs: Vec::new(),
+            items: Vec::new(),
+            collapsed: BTreeSet::from([RefKind::Tag.label().to_string()]),
+            list_state: ListState::default(),
+            menu_target: None,
+        }
+    }
+}
+
+impl RefsSidebar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_refs(&mut self, refs: Vec<RefEntry>) {
+        self.refs = refs;
+        self.rebuild();
+    }
+
+    fn rebuild(&mut self) {
+        self.items = build_items(&self.refs, &self.collapsed);
+        if let Some(sel) = self.list_state.selected()
+            && sel >= self.items.len()
+        {
+            self.list_state.select(self.items.len().checked_sub(1));
+        }
+    }
+
+    /// Collapse or expand the section or folder at `idx`.
+    pub fn toggle(&mut self, idx: usize) {
+        let key = match self.items.get(idx) {
+            Some(RefItem::Section(kind)) => kind.label().to_string(),
+            Some(RefItem::Folder { key, .. }) => key.clone(),
+            _ => return,
+        };
+        if !self.collapsed.remove(&key) {
+            self.collapsed.insert(key);
+        }
+        self.rebuild();
+    }
+
+    pub fn is_collapsed(&self, item: &RefItem) -> bool {
+        match item {
+            RefItem::Section(kind) => self.collapsed.contains(kind.label()),
+            RefItem::Folder { key, .. } => self.collapsed.contains(key),
+            RefItem::Ref { .. } => false,
+        }
+    }
+
+    pub fn entry_at(&self, idx: usize) -> Option<&RefEntry> {
+        match self.items.get(idx)? {
+            RefItem::Ref { idx, .. } => self.refs.get(*idx),
+            _ => None,
+        }
+    }
+}
+
+/// Flatten `refs` into visible rows, skipping everything below collapsed nodes.
+pub fn build_items(refs: &[RefEntry], collapsed: &BTreeSet<String>) -> Vec<RefItem> {
+    let mut items = Vec::new();
+    for kind in RefKind::ALL {
+        items.push(RefItem::Section(kind));
+        if collapsed.contains(kind.label()) {
+            continue;
+        }
+
+        let mut order: Vec<usize> = (0..refs.len()).filter(|i| refs[*i].kind == kind).collect();
+        order.sort_by(|a, b| refs[*a].name.cmp(&refs[*b].name));
+
+        // Folder segments currently open above the next ref
+        let mut stack: Vec<&str> = Vec::new();
+        for idx in order {
+            let segments: Vec<&str> = refs[idx].name.split('/').collect();
+            let folders = &segments[..segments.len() - 1];
+            let mut hidden = false;
+            for (depth, seg) in folders.iter().enumerate() {
+                let key = format!("{}:{}", kind.label(), folders[..=depth].join("/"));
+                if stack.get(depth) != Some(seg) {
+                    stack.truncate(depth);
+                    stack.push(seg);
+                    if !hidden {
+                        items.push(RefItem::Folder {
+                            key: key.clone(),
+                            label: seg.to_string(),
+                            depth: depth + 1,
+                        });
+                    }
+                }
+                hidden |= collapsed.contains(&key);
+            }
+            stack.truncate(folders.len());
+            if !hidden {
+                items.push(RefItem::Ref {
+                    idx,
+                    depth: folders.len() + 1,
+                });
+            }
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, kind: RefKind) -> RefEntry {
+        RefEntry {
+            name: name.to_string(),
+            kind,
+            is_current: false,
+        }
+    }
+
+    #[test]
+    fn test_build_items_groups_folders() {
+        let refs = vec![
+            entry("main", RefKind::Local),
+            entry("feature/b", RefKind::Local),
+            entry("feature/a", RefKind::Local),
+            entry("origin/main", RefKind::Remote),
+        ];
+        let items = build_items(&refs, &BTreeSet::new());
+        let folder = |key: &str, label: &str, depth| RefItem::Folder {
+            key: key.to_string(),
+            label: label.to_string(),
+            depth,
+        };
+        assert_eq!(
+            items,
+            vec![
+                RefItem::Section(RefKind::Local),
+                folder("Branches:feature", "feature", 1),
+                RefItem::Ref { idx: 2, depth: 2 },
+                RefItem::Ref { idx: 1, depth: 2 },
+                RefItem::Ref { idx: 0, depth: 1 },
+                RefItem::Section(RefKind::Remote),
+                folder("Remotes:origin", "origin", 1),
+                RefItem::Ref { idx: 3, depth: 2 },
+                RefItem::Section(RefKind::Tag),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collapsed_nodes_hide_children() {
+        let refs = vec![
+            entry("feature/a", RefKind::Local),
+            entry("main", RefKind::Local),
+            entry("v1", RefKind::Tag),
+        ];
+        let collapsed = BTreeSet::from(["Branches:feature".to_string(), "Tags".to_string()]);
+        let items = build_items(&refs, &collapsed);
+        assert_eq!(items.len(), 5);
+        assert!(matches!(items[1], RefItem::Folder { .. }));
+        assert_eq!(items[2], RefItem::Ref { idx: 1, depth: 1 });
+        assert_eq!(items[4], RefItem::Section(RefKind::Tag));
+    }
+
+    #[test]
+    fn test_toggle_section() {
+        let mut sidebar = RefsSidebar::new();
+        sidebar.set_refs(vec![entry("v1", RefKind::Tag)]);
+        assert_eq!(sidebar.items.len(), 3);
+        sidebar.toggle(2);
+        assert_eq!(sidebar.entry_at(3).map(|e| e.name.as_str()), Some("v1"));
+    }
+}
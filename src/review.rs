@@ -0,0 +1,231 @@
+//! Reviewing staged changes before committing
+//!
+//! The review steps through every hunk of `git diff --cached` one at a time: space
+//! approves a hunk and `u` unstages it. Approvals are remembered by file and hunk
+//! content rather than line numbers, so unstaging one hunk keeps the approvals of
+//! the hunks below it. With `[behavior] review_before_commit = true` the Commit
+//! button stays disabled until every staged hunk has been approved.
+
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReviewHunk {
+    pub path: String,
+    /// `diff --git`, `index`, `---` and `+++` lines of the file
+    header: Vec<String>,
+    /// The `@@` line and the hunk body
+    pub lines: Vec<String>,
+}
+
+impl ReviewHunk {
+    /// Single-hunk patch for `git apply`.
+    pub fn patch(&self) -> String {
+        let mut patch = self.header.join("\n");
+        patch.push('\n');
+        patch.push_str(&self.lines.join("\n"));
+        patch.push('\n');
+        patch
+    }
+
+    /// Identity that survives line-number shifts: the path, the text after the
+    /// `@@ ... @@` range and the body.
+    fn key(&self) -> String {
+        let mut key = self.path.clone();
+        for (i, line) in self.lines.iter().enumerate() {
+            key.push('\n');
+            if i == 0 {
+                key.push_str(line.splitn(3, "@@").nth(2).unwrap_or_default());
+            } else {
+                key.push_str(line);
+            }
+        }
+        key
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Approved,
+    Unstaged,
+}
+
+#[derive(Debug, Default)]
+pub struct ReviewState {
+    pub open: bool,
+    pub hunks: Vec<ReviewHunk>,
+    verdicts: Vec<Option<Verdict>>,
+    /// Hunk on screen; `hunks.len()` once the end is reached
+    pub index: usize,
+    pub scroll_y: u16,
+    approved: HashSet<String>,
+    /// Every staged hunk was approved when last checked
+    pub complete: bool,
+}
+
+impl ReviewState {
+    /// Start over on the hunks of `staged_diff`. Earlier approvals are kept.
+    pub fn start(&mut self, staged_diff: &str) {
+        self.hunks = parse_hunks(staged_diff);
+        self.verdicts = vec![None; self.hunks.len()];
+        self.index = 0;
+        self.scroll_y = 0;
+        self.open = true;
+    }
+
+    pub fn current(&self) -> Option<&ReviewHunk> {
+        self.hunks.get(self.index)
+    }
+
+    pub fn verdict(&self, idx: usize) -> Option<Verdict> {
+        self.verdicts.get(idx).copied().flatten()
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.index >= self.hunks.len()
+    }
+
+    /// Approved and unstaged hunks of this pass.
+    pub fn counts(&self) -> (usize, usize) {
+        let count = |v: Verdict| self.verdicts.iter().filter(|x| **x == Some(v)).count();
+        (count(Verdict::Approved), count(Verdict::Unstaged))
+    }
+
+    pub fn approve(&mut self) {
+        let Some(key) = self.current().map(ReviewHunk::key) else {
+            return;
+        };
+        self.approved.insert(key);
+        self.set_verdict(Verdict::Approved);
+    }
+
+    /// Mark the current hunk unstaged and move on; returns the patch to take out
+    /// of the index.
+    pub fn unstage(&mut self) -> Option<String> {
+        let hunk = self.current()?;
+        let (key, patch) = (hunk.key(), hunk.patch());
+        self.approved.remove(&key);
+        self.set_verdict(Verdict::Unstaged);
+        Some(patch)
+    }
+
+    pub fn back(&mut self) {
+        self.index = self.index.saturating_sub(1);
+        self.scroll_y = 0;
+    }
+
+    fn set_verdict(&mut self, verdict: Verdict) {
+        if let Some(slot) = self.verdicts.get_mut(self.index) {
+            *slot = Some(verdict);
+        }
+        self.index += 1;
+        self.scroll_y = 0;
+        if self.at_end() {
+            // Re-checked against the index on the next refresh
+            self.complete = true;
+        }
+    }
+
+    /// Hunks of `staged_diff` that have not been approved.
+    pub fn unreviewed(&self, staged_diff: &str) -> usize {
+        parse_hunks(staged_diff)
+            .iter()
+            .filter(|h| !self.approved.contains(&h.key()))
+            .count()
+    }
+
+    pub fn check(&mut self, staged_diff: &str) {
+        self.complete = self.unreviewed(staged_diff) == 0;
+    }
+}
+
+/// Split a unified diff into one entry per hunk. Files without hunks (binary,
+/// mode-only changes) are skipped.
+pub fn parse_hunks(diff: &str) -> Vec<ReviewHunk> {
+    let mut hunks: Vec<ReviewHunk> = Vec::new();
+    let mut header: Vec<String> = Vec::new();
+    let mut path = String::new();
+    let mut in_hunk = false;
+
+    for line in diff.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            header = vec![line.to_string()];
+            path = rest
+                .split_once(" b/")
+                .map(|(_, b)| b.to_string())
+                .unwrap_or_default();
+            in_hunk = false;
+        } else if line.starts_with("@@") {
+            hunks.push(ReviewHunk {
+                path: path.clone(),
+                header: header.clone(),
+                lines: vec![line.to_string()],
+            });
+            in_hunk = true;
+        } else if in_hunk {
+            if let Some(hunk) = hunks.last_mut() {
+                hunk.lines.push(line.to_string());
+            }
+        } else {
+            if let Some(new) = line.strip_prefix("+++ b/") {
+                path = new.to_string();
+            }
+            header.push(line.to_string());
+        }
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/src/a.rs b/src/a.rs
+index 111..222 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,2 +1,2 @@ fn main()
+-old
++new
+ same
+@@ -10,1 +10,2 @@
+ ctx
++added
+diff --git a/logo.png b/logo.png
+Binary files a/logo.png and b/logo.png differ
+";
+
+    #[test]
+    fn test_parse_hunks() {
+        let hunks = parse_hunks(DIFF);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[1].path, "src/a.rs");
+        assert_eq!(hunks[1].lines, vec!["@@ -10,1 +10,2 @@", " ctx", "+added"]);
+        assert!(
+            hunks[0]
+                .patch()
+                .starts_with("diff --git a/src/a.rs b/src/a.rs\nindex")
+        );
+        assert!(hunks[0].patch().ends_with(" same\n"));
+    }
+
+    #[test]
+    fn test_review_reaches_end() {
+        let mut review = ReviewState::default();
+        review.start(DIFF);
+        review.approve();
+        assert!(!review.complete);
+        assert!(review.unstage().unwrap().contains("+added"));
+        assert!(review.at_end() && review.complete);
+        assert_eq!(review.counts(), (1, 1));
+
+        // The unstaged hunk is gone; the approval survives a shifted header
+        let shifted = DIFF.replace("@@ -1,2 +1,2 @@", "@@ -3,2 +3,2 @@");
+        let remaining = shifted.split("@@ -10,1").next().unwrap();
+        review.check(remaining);
+        assert!(review.complete);
+        review.check(DIFF);
+        assert!(!review.complete);
+        assert_eq!(review.unreviewed(DIFF), 1);
+    }
+}
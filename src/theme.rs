@@ -0,0 +1,487 @@
+//! Color themes and the palette each one renders with
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    Mocha,
+    TokyoNightStorm,
+    GruvboxDarkHard,
+    Nord,
+    Dracula,
+    Terminal,
+}
+
+impl Theme {
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::Mocha => "Mocha",
+            Theme::TokyoNightStorm => "Tokyo Night",
+            Theme::GruvboxDarkHard => "Gruvbox",
+            Theme::Nord => "Nord",
+            Theme::Dracula => "Dracula",
+            Theme::Terminal => "Terminal",
+        }
+    }
+}
+
+/// What a syntax color is used for, as keys of `[syntax.<theme>]`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyntaxRole {
+    Text,
+    Keyword,
+    Function,
+    String,
+    Number,
+    Type,
+    Variable,
+    Comment,
+}
+
+impl SyntaxRole {
+    pub const ALL: [SyntaxRole; 8] = [
+        SyntaxRole::Text,
+        SyntaxRole::Keyword,
+        SyntaxRole::Function,
+        SyntaxRole::String,
+        SyntaxRole::Number,
+        SyntaxRole::Type,
+        SyntaxRole::Variable,
+        SyntaxRole::Comment,
+    ];
+
+    /// Key under `[syntax.<theme>]`
+    pub fn key(self) -> &'static str {
+        match self {
+            SyntaxRole::Text => "text",
+            SyntaxRole::Keyword => "keyword",
+            SyntaxRole::Function => "function",
+            SyntaxRole::String => "string",
+            SyntaxRole::Number => "number",
+            SyntaxRole::Type => "type",
+            SyntaxRole::Variable => "variable",
+            SyntaxRole::Comment => "comment",
+        }
+    }
+}
+
+/// Colors code is highlighted with, picked to sit on the theme's background
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SyntaxColors {
+    pub text: Color,
+    pub keyword: Color,
+    /// Functions, macros and modules
+    pub function: Color,
+    pub string: Color,
+    /// Numbers, constants and parameters
+    pub number: Color,
+    pub type_name: Color,
+    pub variable: Color,
+    pub comment: Color,
+}
+
+impl SyntaxColors {
+    pub fn set(&mut self, role: SyntaxRole, color: Color) {
+        let slot = match role {
+            SyntaxRole::Text => &mut self.text,
+            SyntaxRole::Keyword => &mut self.keyword,
+            SyntaxRole::Function => &mut self.function,
+            SyntaxRole::String => &mut self.string,
+            SyntaxRole::Number => &mut self.number,
+            SyntaxRole::Type => &mut self.type_name,
+            SyntaxRole::Variable => &mut self.variable,
+            SyntaxRole::Comment => &mut self.comment,
+        };
+        *slot = color;
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Palette {
+    pub bg: Color,
+    pub fg: Color,
+    pub accent_primary: Color,
+    pub accent_secondary: Color,
+    pub accent_tertiary: Color,
+    pub border_inactive: Color,
+    pub selection_bg: Color,
+    pub dir_color: Color,
+    pub exe_color: Color,
+    pub size_color: Color,
+    pub line_num_color: Color,
+    pub btn_bg: Color,
+    pub btn_fg: Color,
+    pub menu_bg: Color,
+    pub diff_add_bg: Color,
+    pub diff_del_bg: Color,
+    pub diff_hunk_bg: Color,
+    /// Lines of a block moved elsewhere in the diff, on either side
+    pub diff_moved_bg: Color,
+    pub diff_add_fg: Color,
+    pub diff_del_fg: Color,
+    pub diff_gutter_fg: Color,
+    pub syntax: SyntaxColors,
+}
+
+/// Stable color for an author, picked by hashing the name into the theme's accents
+/// and blends of them, so one person has the same color in every view.
+pub fn author_color(p: &Palette, author: &str) -> Color {
+    let choices = [
+        p.accent_primary,
+        p.accent_secondary,
+        p.accent_tertiary,
+        p.dir_color,
+        p.exe_color,
+        p.diff_add_fg,
+        tint(p.accent_primary, p.accent_secondary, 0.5),
+        tint(p.accent_secondary, p.accent_tertiary, 0.5),
+        tint(p.accent_tertiary, p.fg, 0.4),
+    ];
+    // FNV-1a, so colors don't change between runs or builds
+    let hash = author
+        .trim()
+        .to_lowercase()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x100_0000_01b3)
+        });
+    choices[(hash % choices.len() as u64) as usize]
+}
+
+fn tint(base: Color, overlay: Color, alpha: f32) -> Color {
+    let (br, bg, bb) = match base {
+        Color::Rgb(r, g, b) => (r, g, b),
+        _ => return base,
+    };
+    let (or, og, ob) = match overlay {
+        Color::Rgb(r, g, b) => (r, g, b),
+        _ => return base,
+    };
+
+    let mix = |b: u8, o: u8| -> u8 {
+        let b = b as f32;
+        let o = o as f32;
+        let v = b + (o - b) * alpha;
+        v.round().clamp(0.0, 255.0) as u8
+    };
+
+    Color::Rgb(mix(br, or), mix(bg, og), mix(bb, ob))
+}
+
+pub fn palette(theme: Theme) -> Palette {
+    let diff_alpha = 0.20;
+    let hunk_alpha = 0.12;
+
+    match theme {
+        Theme::Mocha => {
+            let bg = Color::Rgb(30, 30, 46);
+            let fg = Color::Rgb(248, 248, 255);
+            let accent_primary = Color::Rgb(203, 166, 247);
+            let accent_secondary = Color::Rgb(250, 179, 135);
+            let accent_tertiary = Color::Rgb(137, 180, 250);
+            let border_inactive = Color::Rgb(120, 124, 150);
+            let selection_bg = Color::Rgb(78, 82, 110);
+            let dir_color = Color::Rgb(137, 180, 250);
+            let exe_color = Color::Rgb(166, 227, 161);
+            let size_color = Color::Rgb(147, 153, 178);
+            let btn_bg = Color::Rgb(243, 139, 168);
+            let btn_fg = Color::Rgb(24, 24, 37);
+            let menu_bg = Color::Rgb(58, 60, 82);
+            // Soft teal for additions, warm coral for deletions
+            let diff_add_tint = Color::Rgb(148, 226, 213); // Catppuccin teal
+            let diff_del_tint = Color::Rgb(243, 139, 168); // Catppuccin red/pink
+
+            Palette {
+                bg,
+                fg,
+                accent_primary,
+                accent_secondary,
+                accent_tertiary,
+                border_inactive,
+                selection_bg,
+                dir_color,
+                exe_color,
+                size_color,
+                line_num_color: Color::Rgb(88, 91, 112), // Muted gray for line numbers
+                btn_bg,
+                btn_fg,
+                menu_bg,
+                diff_add_bg: tint(bg, diff_add_tint, diff_alpha),
+                diff_del_bg: tint(bg, diff_del_tint, diff_alpha),
+                diff_hunk_bg: tint(bg, accent_primary, hunk_alpha),
+                diff_moved_bg: tint(bg, accent_secondary, diff_alpha),
+                diff_add_fg: Color::Rgb(148, 226, 213), // Teal for + sign
+                diff_del_fg: Color::Rgb(243, 139, 168), // Red/pink for - sign
+                diff_gutter_fg: Color::Rgb(108, 112, 134), // Muted gray for line numbers
+                syntax: SyntaxColors {
+                    text: fg,
+                    keyword: Color::Rgb(203, 166, 247),
+                    function: Color::Rgb(137, 180, 250),
+                    string: Color::Rgb(166, 227, 161),
+                    number: Color::Rgb(250, 179, 135),
+                    type_name: Color::Rgb(249, 226, 175),
+                    variable: Color::Rgb(180, 190, 254),
+                    comment: Color::Rgb(147, 153, 178),
+                },
+            }
+        }
+        Theme::TokyoNightStorm => {
+            let bg = Color::Rgb(36, 40, 59);
+            let fg = Color::Rgb(192, 202, 245);
+            let accent_primary = Color::Rgb(122, 162, 247);
+            let accent_secondary = Color::Rgb(255, 158, 100);
+            let accent_tertiary = Color::Rgb(187, 154, 247);
+            let border_inactive = Color::Rgb(65, 72, 104);
+            let selection_bg = Color::Rgb(46, 60, 100);
+            let dir_color = Color::Rgb(122, 162, 247);
+            let exe_color = Color::Rgb(158, 206, 106);
+            let size_color = Color::Rgb(86, 95, 137);
+            let btn_bg = Color::Rgb(247, 118, 142);
+            let btn_fg = Color::Rgb(24, 24, 37);
+            let menu_bg = Color::Rgb(45, 49, 71);
+            let diff_add_tint = Color::Rgb(115, 218, 202); // Tokyo Night cyan/teal
+            let diff_del_tint = Color::Rgb(247, 118, 142); // Tokyo Night red
+
+            Palette {
+                bg,
+                fg,
+                accent_primary,
+                accent_secondary,
+                accent_tertiary,
+                border_inactive,
+                selection_bg,
+                dir_color,
+                exe_color,
+                size_color,
+                line_num_color: Color::Rgb(88, 91, 112), // Muted gray for line numbers
+                btn_bg,
+                btn_fg,
+                menu_bg,
+                diff_add_bg: tint(bg, diff_add_tint, diff_alpha),
+                diff_del_bg: tint(bg, diff_del_tint, diff_alpha),
+                diff_hunk_bg: tint(bg, accent_primary, hunk_alpha),
+                diff_moved_bg: tint(bg, accent_secondary, diff_alpha),
+                diff_add_fg: Color::Rgb(115, 218, 202), // Cyan/teal for + sign
+                diff_del_fg: Color::Rgb(247, 118, 142), // Red for - sign
+                diff_gutter_fg: Color::Rgb(86, 95, 137), // Muted gray
+                syntax: SyntaxColors {
+                    text: fg,
+                    keyword: Color::Rgb(187, 154, 247),
+                    function: Color::Rgb(122, 162, 247),
+                    string: Color::Rgb(158, 206, 106),
+                    number: Color::Rgb(255, 158, 100),
+                    type_name: Color::Rgb(42, 195, 222),
+                    variable: Color::Rgb(125, 207, 255),
+                    comment: Color::Rgb(86, 95, 137),
+                },
+            }
+        }
+        Theme::GruvboxDarkHard => {
+            let bg = Color::Rgb(29, 32, 33);
+            let fg = Color::Rgb(235, 219, 178);
+            let accent_primary = Color::Rgb(250, 189, 47);
+            let accent_secondary = Color::Rgb(214, 93, 14);
+            let accent_tertiary = Color::Rgb(131, 165, 152);
+            let border_inactive = Color::Rgb(80, 73, 69);
+            let selection_bg = Color::Rgb(60, 56, 54);
+            let dir_color = Color::Rgb(131, 165, 152);
+            let exe_color = Color::Rgb(184, 187, 38);
+            let size_color = Color::Rgb(146, 131, 116);
+            let btn_bg = Color::Rgb(251, 73, 52);
+            let btn_fg = Color::Rgb(29, 32, 33);
+            let menu_bg = Color::Rgb(50, 48, 47);
+            let diff_add_tint = Color::Rgb(142, 192, 124); // Gruvbox aqua/green
+            let diff_del_tint = Color::Rgb(251, 73, 52); // Gruvbox red
+
+            Palette {
+                bg,
+                fg,
+                accent_primary,
+                accent_secondary,
+                accent_tertiary,
+                border_inactive,
+                selection_bg,
+                dir_color,
+                exe_color,
+                size_color,
+                line_num_color: Color::Rgb(88, 91, 112), // Muted gray for line numbers
+                btn_bg,
+                btn_fg,
+                menu_bg,
+                diff_add_bg: tint(bg, diff_add_tint, diff_alpha),
+                diff_del_bg: tint(bg, diff_del_tint, diff_alpha),
+                diff_hunk_bg: tint(bg, accent_primary, hunk_alpha),
+                diff_moved_bg: tint(bg, accent_secondary, diff_alpha),
+                diff_add_fg: Color::Rgb(142, 192, 124), // Aqua for + sign
+                diff_del_fg: Color::Rgb(251, 73, 52),   // Red for - sign
+                diff_gutter_fg: Color::Rgb(146, 131, 116), // Muted gray
+                syntax: SyntaxColors {
+                    text: fg,
+                    keyword: Color::Rgb(251, 73, 52),
+                    function: Color::Rgb(142, 192, 124),
+                    string: Color::Rgb(184, 187, 38),
+                    number: Color::Rgb(211, 134, 155),
+                    type_name: Color::Rgb(250, 189, 47),
+                    variable: Color::Rgb(131, 165, 152),
+                    comment: Color::Rgb(146, 131, 116),
+                },
+            }
+        }
+        Theme::Nord => {
+            let bg = Color::Rgb(46, 52, 64);
+            let fg = Color::Rgb(216, 222, 233);
+            let accent_primary = Color::Rgb(136, 192, 208);
+            let accent_secondary = Color::Rgb(235, 203, 139);
+            let accent_tertiary = Color::Rgb(180, 142, 173);
+            let border_inactive = Color::Rgb(76, 86, 106);
+            let selection_bg = Color::Rgb(67, 76, 94);
+            let dir_color = Color::Rgb(129, 161, 193);
+            let exe_color = Color::Rgb(163, 190, 140);
+            let size_color = Color::Rgb(76, 86, 106);
+            let btn_bg = Color::Rgb(191, 97, 106);
+            let btn_fg = Color::Rgb(46, 52, 64);
+            let menu_bg = Color::Rgb(59, 66, 82);
+            let diff_add_tint = Color::Rgb(136, 192, 208); // Nord frost (cyan)
+            let diff_del_tint = Color::Rgb(191, 97, 106); // Nord aurora red
+
+            Palette {
+                bg,
+                fg,
+                accent_primary,
+                accent_secondary,
+                accent_tertiary,
+                border_inactive,
+                selection_bg,
+                dir_color,
+                exe_color,
+                size_color,
+                line_num_color: Color::Rgb(88, 91, 112), // Muted gray for line numbers
+                btn_bg,
+                btn_fg,
+                menu_bg,
+                diff_add_bg: tint(bg, diff_add_tint, diff_alpha),
+                diff_del_bg: tint(bg, diff_del_tint, diff_alpha),
+                diff_hunk_bg: tint(bg, accent_primary, hunk_alpha),
+                diff_moved_bg: tint(bg, accent_secondary, diff_alpha),
+                diff_add_fg: Color::Rgb(136, 192, 208), // Frost cyan for + sign
+                diff_del_fg: Color::Rgb(191, 97, 106),  // Aurora red for - sign
+                diff_gutter_fg: Color::Rgb(76, 86, 106), // Muted gray
+                syntax: SyntaxColors {
+                    text: fg,
+                    keyword: Color::Rgb(129, 161, 193),
+                    function: Color::Rgb(136, 192, 208),
+                    string: Color::Rgb(163, 190, 140),
+                    number: Color::Rgb(180, 142, 173),
+                    type_name: Color::Rgb(143, 188, 187),
+                    variable: Color::Rgb(216, 222, 233),
+                    comment: Color::Rgb(97, 110, 136),
+                },
+            }
+        }
+        Theme::Dracula => {
+            let bg = Color::Rgb(40, 42, 54);
+            let fg = Color::Rgb(248, 248, 242);
+            let accent_primary = Color::Rgb(189, 147, 249);
+            let accent_secondary = Color::Rgb(139, 233, 253);
+            let accent_tertiary = Color::Rgb(255, 121, 198);
+            let border_inactive = Color::Rgb(98, 114, 164);
+            let selection_bg = Color::Rgb(68, 71, 90);
+            let dir_color = Color::Rgb(139, 233, 253);
+            let exe_color = Color::Rgb(80, 250, 123);
+            let size_color = Color::Rgb(98, 114, 164);
+            let btn_bg = Color::Rgb(255, 85, 85);
+            let btn_fg = Color::Rgb(40, 42, 54);
+            let menu_bg = Color::Rgb(68, 71, 90);
+            let diff_add_tint = Color::Rgb(139, 233, 253); // Dracula cyan
+            let diff_del_tint = Color::Rgb(255, 121, 198); // Dracula pink
+
+            Palette {
+                bg,
+                fg,
+                accent_primary,
+                accent_secondary,
+                accent_tertiary,
+                border_inactive,
+                selection_bg,
+                dir_color,
+                exe_color,
+                size_color,
+                line_num_color: Color::Rgb(88, 91, 112), // Muted gray for line numbers
+                btn_bg,
+                btn_fg,
+                menu_bg,
+                diff_add_bg: tint(bg, diff_add_tint, diff_alpha),
+                diff_del_bg: tint(bg, diff_del_tint, diff_alpha),
+                diff_hunk_bg: tint(bg, accent_primary, hunk_alpha),
+                diff_moved_bg: tint(bg, accent_secondary, diff_alpha),
+                diff_add_fg: Color::Rgb(139, 233, 253), // Cyan for + sign
+                diff_del_fg: Color::Rgb(255, 121, 198), // Pink for - sign
+                diff_gutter_fg: Color::Rgb(98, 114, 164), // Muted gray
+                syntax: SyntaxColors {
+                    text: fg,
+                    keyword: Color::Rgb(255, 121, 198),
+                    function: Color::Rgb(139, 233, 253),
+                    string: Color::Rgb(80, 250, 123),
+                    number: Color::Rgb(255, 184, 108),
+                    type_name: Color::Rgb(241, 250, 140),
+                    variable: Color::Rgb(189, 147, 249),
+                    comment: Color::Rgb(98, 114, 164),
+                },
+            }
+        }
+        Theme::Terminal => {
+            // Clean dark theme inspired by OpenCode - pure grays, high contrast
+            let bg = Color::Rgb(22, 22, 22);
+            let fg = Color::Rgb(212, 212, 212);
+            let accent_primary = Color::Rgb(97, 175, 239); // Bright blue
+            let accent_secondary = Color::Rgb(229, 192, 123); // Orange/gold
+            let accent_tertiary = Color::Rgb(198, 120, 221); // Purple
+            let border_inactive = Color::Rgb(68, 68, 68);
+            let selection_bg = Color::Rgb(55, 55, 55);
+            let dir_color = Color::Rgb(97, 175, 239); // Blue for dirs
+            let exe_color = Color::Rgb(152, 195, 121); // Green for executables
+            let size_color = Color::Rgb(92, 99, 112); // Muted gray
+            let btn_bg = Color::Rgb(224, 108, 117); // Red
+            let btn_fg = Color::Rgb(22, 22, 22);
+            let menu_bg = Color::Rgb(38, 38, 38);
+            let diff_add_tint = Color::Rgb(86, 182, 194); // Cyan/teal
+            let diff_del_tint = Color::Rgb(224, 108, 117); // Warm red/coral
+
+            Palette {
+                bg,
+                fg,
+                accent_primary,
+                accent_secondary,
+                accent_tertiary,
+                border_inactive,
+                selection_bg,
+                dir_color,
+                exe_color,
+                size_color,
+                line_num_color: Color::Rgb(88, 91, 112), // Muted gray for line numbers
+                btn_bg,
+                btn_fg,
+                menu_bg,
+                diff_add_bg: tint(bg, diff_add_tint, diff_alpha),
+                diff_del_bg: tint(bg, diff_del_tint, diff_alpha),
+                diff_hunk_bg: tint(bg, accent_primary, hunk_alpha),
+                diff_moved_bg: tint(bg, accent_secondary, diff_alpha),
+                diff_add_fg: Color::Rgb(86, 182, 194), // Cyan for + sign
+                diff_del_fg: Color::Rgb(224, 108, 117), // Red for - sign
+                diff_gutter_fg: Color::Rgb(92, 99, 112), // Muted gray
+                syntax: SyntaxColors {
+                    text: fg,
+                    keyword: Color::Rgb(198, 120, 221),
+                    function: Color::Rgb(97, 175, 239),
+                    string: Color::Rgb(152, 195, 121),
+                    number: Color::Rgb(209, 154, 102),
+                    type_name: Color::Rgb(229, 192, 123),
+                    variable: Color::Rgb(224, 108, 117),
+                    comment: Color::Rgb(92, 99, 112),
+                },
+            }
+        }
+    }
+}
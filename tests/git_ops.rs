@@ -0,0 +1,1077 @@
+//! `git_ops` against throwaway repositories
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+use lzgit::{git, git_ops};
+use tempfile::TempDir;
+
+fn git(repo: &Path, args: &[&str]) {
+    let out = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .env("GIT_AUTHOR_NAME", "Test")
+        .env("GIT_AUTHOR_EMAIL", "test@example.com")
+        .env("GIT_COMMITTER_NAME", "Test")
+        .env("GIT_COMMITTER_EMAIL", "test@example.com")
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "git {:?}: {}",
+        args,
+        String::from_utf8_lossy(&out.stderr)
+    );
+}
+
+/// Run git and return what it printed.
+fn git_out(repo: &Path, args: &[&str]) -> String {
+    let out = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&out.stdout).into_owned()
+}
+
+/// Run git with `input` on stdin.
+fn git_stdin(repo: &Path, args: &[&str], input: &str) -> Output {
+    let mut child = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// A repository on `main` with one commit.
+fn repo() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    git(dir.path(), &["init", "-q", "-b", "main"]);
+    git(
+        dir.path(),
+        &["commit", "-q", "--allow-empty", "-m", "first"],
+    );
+    dir
+}
+
+#[test]
+fn test_branch_lifecycle() {
+    let dir = repo();
+    let root = dir.path();
+    git(root, &["checkout", "-q", "-b", "topic"]);
+    git(root, &["commit", "-q", "--allow-empty", "-m", "second"]);
+    git(root, &["checkout", "-q", "main"]);
+
+    git_ops::set_upstream(root, "topic", "main").unwrap();
+    let branches = git_ops::list_branches(root).unwrap();
+    let topic = branches.iter().find(|b| b.name == "topic").unwrap();
+    assert_eq!(topic.upstream.as_deref(), Some("main"));
+    assert_eq!(topic.ahead_behind(), (1, 0));
+
+    git_ops::rename_branch(root, "topic", "feature").unwrap();
+    let err = git_ops::delete_branch(root, "feature", false).unwrap_err();
+    assert!(err.contains("not fully merged"), "{}", err);
+    git_ops::delete_branch(root, "feature", true).unwrap();
+
+    let names: Vec<String> = git_ops::list_branches(root)
+        .unwrap()
+        .into_iter()
+        .map(|b| b.name)
+        .collect();
+    assert_eq!(names, vec!["main"]);
+}
+
+#[test]
+fn test_create_branch_at_does_not_switch() {
+    let dir = repo();
+    let root = dir.path();
+    git(root, &["commit", "-q", "--allow-empty", "-m", "second"]);
+
+    git_ops::create_branch_at(root, "old", "HEAD~1").unwrap();
+    let branches = git_ops::list_branches(root).unwrap();
+    let old = branches.iter().find(|b| b.name == "old").unwrap();
+    assert!(!old.is_current);
+    assert!(branches.iter().any(|b| b.name == "main" && b.is_current));
+}
+
+#[test]
+fn test_reset_modes() {
+    let dir = repo();
+    let root = dir.path();
+    std::fs::write(root.join("a.txt"), "a\n").unwrap();
+    git(root, &["add", "a.txt"]);
+    git(root, &["commit", "-q", "-m", "second"]);
+    let staged = || {
+        let out = Command::new("git")
+            .args(["diff", "--cached", "--name-only"])
+            .current_dir(root)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    };
+
+    git_ops::reset_to(root, "HEAD~1", git_ops::ResetMode::Soft).unwrap();
+    assert_eq!(staged(), "a.txt");
+    git(root, &["commit", "-q", "-m", "second"]);
+
+    git_ops::reset_to(root, "HEAD~1", git_ops::ResetMode::Mixed).unwrap();
+    assert_eq!(staged(), "");
+    assert!(root.join("a.txt").exists());
+    git(root, &["add", "a.txt"]);
+    git(root, &["commit", "-q", "-m", "second"]);
+
+    git_ops::reset_to(root, "HEAD~1", git_ops::ResetMode::Hard).unwrap();
+    assert!(!root.join("a.txt").exists());
+    assert!(!git_ops::is_dirty(root).unwrap());
+    assert!(git_ops::reset_to(root, "nope", git_ops::ResetMode::Hard).is_err());
+}
+
+#[test]
+fn test_binary_diff_sizes() {
+    let dir = repo();
+    let root = dir.path();
+    std::fs::write(root.join("logo.bin"), b"\x00\x01\x02").unwrap();
+    git(root, &["add", "logo.bin"]);
+    git(root, &["commit", "-q", "-m", "add logo"]);
+    std::fs::write(root.join("logo.bin"), b"\x00\x01\x02\x03\x04").unwrap();
+
+    // The new side is only in the working tree
+    let diff = git_ops::diff_path(root, "logo.bin", false, None).unwrap();
+    let text = git_ops::describe_binary_diffs(root, &diff, false);
+    assert!(
+        text.contains("Binary files differ: 3 → 5 bytes (+2)\n"),
+        "{}",
+        text
+    );
+    assert!(!text.contains("@@"));
+
+    git(root, &["commit", "-q", "-am", "grow logo"]);
+    let diff = git_ops::show_commit_file_diff(
+        root,
+        "HEAD",
+        "logo.bin",
+        None,
+        git_ops::DiffBase::Parent(0),
+    )
+    .unwrap();
+    let text = git_ops::describe_binary_diffs(root, &diff, true);
+    let hunk: Vec<&str> = text.lines().skip_while(|l| !l.starts_with("@@")).collect();
+    assert_eq!(
+        hunk,
+        vec![
+            "@@ -1,1 +1,1 @@",
+            "-00000000  00 01 02                                          |...|",
+            "+00000000  00 01 02 03 04                                    |.....|",
+        ]
+    );
+}
+
+#[test]
+fn test_checkout_detached() {
+    let dir = repo();
+    let root = dir.path();
+    git(root, &["commit", "-q", "--allow-empty", "-m", "second"]);
+    let first = git_ops::list_history(root, 2, None).unwrap()[1]
+        .hash
+        .clone();
+
+    git_ops::checkout_detached(root, &first).unwrap();
+    assert_eq!(git_ops::head_short(root).unwrap(), &first[..7]);
+    let mut state = git::GitState::new();
+    state.refresh(root);
+    assert_eq!(state.branch, "");
+    assert_eq!(state.detached.as_deref(), Some(&first[..7]));
+}
+
+#[test]
+fn test_tag_lifecycle() {
+    let dir = repo();
+    let root = dir.path();
+    // `git tag -a` runs without the helper's identity variables
+    git(root, &["config", "user.name", "Test"]);
+    git(root, &["config", "user.email", "test@example.com"]);
+    git(root, &["commit", "-q", "--allow-empty", "-m", "second"]);
+
+    assert_eq!(git_ops::latest_tag(root).unwrap(), None);
+    git_ops::create_tag(root, "v1.0", "HEAD~1", "First release").unwrap();
+    assert_eq!(git_ops::latest_tag(root).unwrap().as_deref(), Some("v1.0"));
+    git_ops::create_tag(root, "latest", "HEAD", "").unwrap();
+    let tags = git_ops::list_tags(root).unwrap();
+    let v1 = tags.iter().find(|t| t.name == "v1.0").unwrap();
+    assert!(v1.annotated);
+    assert_eq!(v1.subject, "First release");
+    let latest = tags.iter().find(|t| t.name == "latest").unwrap();
+    assert!(!latest.annotated);
+    assert_eq!(latest.subject, "second");
+    assert_ne!(v1.target, latest.target);
+
+    let remote = TempDir::new().unwrap();
+    git(remote.path(), &["init", "-q", "--bare"]);
+    git(
+        root,
+        &["remote", "add", "origin", remote.path().to_str().unwrap()],
+    );
+    let origin = git_ops::default_remote(root).unwrap();
+    git_ops::push_tags(root, &origin, &["v1.0".to_string()]).unwrap();
+    assert_eq!(git_ops::list_tags(remote.path()).unwrap().len(), 1);
+    git_ops::push_tags(root, &origin, &[]).unwrap();
+    assert_eq!(git_ops::list_tags(remote.path()).unwrap().len(), 2);
+
+    git_ops::delete_tag(root, "latest").unwrap();
+    let names: Vec<String> = git_ops::list_tags(root)
+        .unwrap()
+        .into_iter()
+        .map(|t| t.name)
+        .collect();
+    assert_eq!(names, vec!["v1.0"]);
+}
+
+#[test]
+fn test_remote_lifecycle() {
+    let dir = repo();
+    let root = dir.path();
+    let remote = TempDir::new().unwrap();
+    git(remote.path(), &["init", "-q", "--bare"]);
+    let url = remote.path().to_str().unwrap();
+
+    git_ops::add_remote(root, "upstream", url).unwrap();
+    git_ops::rename_remote(root, "upstream", "mirror").unwrap();
+    git_ops::set_remote_url(root, "mirror", "/nowhere.git", true).unwrap();
+    let remotes = git_ops::list_remotes(root).unwrap();
+    assert_eq!(remotes.len(), 1);
+    assert_eq!(remotes[0].name, "mirror");
+    assert_eq!(remotes[0].fetch_url, url);
+    assert_eq!(remotes[0].push_url, "/nowhere.git");
+
+    git_ops::set_remote_url(root, "mirror", url, true).unwrap();
+    git_ops::push(root, Some(("mirror", "main"))).unwrap();
+    git_ops::fetch_prune(root, Some("mirror")).unwrap();
+    git_ops::pull_rebase(root, Some(("mirror", "main"))).unwrap();
+
+    git_ops::remove_remote(root, "mirror").unwrap();
+    assert!(git_ops::list_remotes(root).unwrap().is_empty());
+}
+
+#[test]
+fn test_push_options() {
+    let dir = repo();
+    let root = dir.path();
+    let remote = TempDir::new().unwrap();
+    git(remote.path(), &["init", "-q", "--bare"]);
+    git_ops::add_remote(root, "origin", remote.path().to_str().unwrap()).unwrap();
+
+    let upstream = git_ops::PushOptions {
+        set_upstream: true,
+        target: Some(("origin".to_string(), "main".to_string())),
+        ..Default::default()
+    };
+    git_ops::push_with(root, &upstream).unwrap();
+    let main = git_ops::list_branches(root)
+        .unwrap()
+        .into_iter()
+        .find(|b| b.name == "main")
+        .unwrap();
+    assert_eq!(main.upstream.as_deref(), Some("origin/main"));
+
+    git(
+        root,
+        &["commit", "-q", "--amend", "--allow-empty", "-m", "reworded"],
+    );
+    assert!(git_ops::push(root, None).is_err());
+    let force = git_ops::PushOptions {
+        force_with_lease: true,
+        ..Default::default()
+    };
+    git_ops::push_with(root, &force).unwrap();
+    assert_eq!(git_ops::PushOptions::parse("--force-with-lease"), force);
+    assert_eq!(git_ops::PushOptions::parse("-u origin main"), upstream);
+}
+
+#[test]
+fn test_pull_strategies() {
+    let dir = repo();
+    let root = dir.path();
+    let remote = TempDir::new().unwrap();
+    git(remote.path(), &["init", "-q", "--bare"]);
+    let url = remote.path().to_str().unwrap();
+    git_ops::add_remote(root, "origin", url).unwrap();
+    git(root, &["push", "-q", "-u", "origin", "main"]);
+
+    let other = TempDir::new().unwrap();
+    git(other.path(), &["clone", "-q", "-b", "main", url, "."]);
+    std::fs::write(other.path().join("theirs.txt"), "theirs\n").unwrap();
+    git(other.path(), &["add", "."]);
+    git(other.path(), &["commit", "-q", "-m", "theirs"]);
+    git(other.path(), &["push", "-q"]);
+
+    // The merge commit is made without the helper's identity variables
+    git(root, &["config", "user.name", "Test"]);
+    git(root, &["config", "user.email", "test@example.com"]);
+    git(root, &["commit", "-q", "--allow-empty", "-m", "ours"]);
+    let ff_only = git_ops::PullOptions {
+        strategy: git_ops::PullStrategy::FfOnly,
+        autostash: false,
+    };
+    assert!(git_ops::pull(root, ff_only, None).is_err());
+
+    std::fs::write(root.join("ours.txt"), "dirty\n").unwrap();
+    git(root, &["add", "ours.txt"]);
+    let merge = git_ops::PullOptions {
+        strategy: git_ops::PullStrategy::Merge,
+        autostash: true,
+    };
+    git_ops::pull(root, merge, Some(("origin", "main"))).unwrap();
+    assert!(root.join("theirs.txt").exists());
+    assert_eq!(
+        std::fs::read_to_string(root.join("ours.txt")).unwrap(),
+        "dirty\n"
+    );
+    let log = git_ops::list_history(root, 1, None).unwrap();
+    assert!(log[0].subject.starts_with("Merge branch"));
+
+    assert_eq!(
+        git_ops::PullOptions::parse("--no-rebase --autostash origin main"),
+        (merge, Some(("origin".to_string(), "main".to_string())))
+    );
+}
+
+#[test]
+fn test_branch_description() {
+    let dir = repo();
+    let root = dir.path();
+    git(root, &["branch", "release/v1"]);
+
+    git_ops::set_branch_description(root, "release/v1", "Long-lived\nfor fixes").unwrap();
+    let branches = git_ops::list_branches(root).unwrap();
+    let release = branches.iter().find(|b| b.name == "release/v1").unwrap();
+    assert_eq!(
+        release.description.as_deref(),
+        Some("Long-lived\nfor fixes")
+    );
+    assert!(
+        branches
+            .iter()
+            .find(|b| b.name == "main")
+            .unwrap()
+            .description
+            .is_none()
+    );
+
+    git_ops::set_branch_description(root, "release/v1", "").unwrap();
+    // Removing a description that is not set is not an error
+    git_ops::set_branch_description(root, "release/v1", "").unwrap();
+    let branches = git_ops::list_branches(root).unwrap();
+    assert!(branches.iter().all(|b| b.description.is_none()));
+}
+
+#[test]
+fn test_branches_in_other_worktrees() {
+    let dir = repo();
+    let root = dir.path();
+    let other = TempDir::new().unwrap();
+    let wt = other.path().join("wt");
+    git(
+        root,
+        &["worktree", "add", "-q", "-b", "topic", wt.to_str().unwrap()],
+    );
+
+    let branches = git_ops::list_branches(root).unwrap();
+    let topic = branches.iter().find(|b| b.name == "topic").unwrap();
+    let path = topic.worktree.as_deref().unwrap();
+    assert!(path.ends_with("/wt"), "{}", path);
+    let main = branches.iter().find(|b| b.name == "main").unwrap();
+    assert!(main.is_current && main.worktree.is_none());
+
+    // From the other worktree it is the other way round
+    let branches = git_ops::list_branches(&wt).unwrap();
+    let main = branches.iter().find(|b| b.name == "main").unwrap();
+    assert!(main.worktree.is_some());
+    assert!(git_ops::delete_branch(root, "topic", true).is_err());
+}
+
+#[test]
+fn test_submodule_status_and_ops() {
+    let lib = repo();
+    let dir = repo();
+    let root = dir.path();
+    assert!(git_ops::list_submodules(root).unwrap().is_empty());
+
+    let url = lib.path().to_str().unwrap();
+    git(
+        root,
+        &[
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "add",
+            "-q",
+            url,
+            "libs/core",
+        ],
+    );
+    git(root, &["commit", "-q", "-m", "add core"]);
+    let subs = git_ops::list_submodules(root).unwrap();
+    assert_eq!(subs.len(), 1);
+    assert_eq!(subs[0].path, "libs/core");
+    assert_eq!(subs[0].state, git_ops::SubmoduleState::Current);
+    assert_eq!(subs[0].branch, "main");
+    assert!(!subs[0].dirty);
+
+    let sub = root.join("libs/core");
+    std::fs::write(sub.join("notes.txt"), "wip\n").unwrap();
+    git(&sub, &["commit", "-q", "--allow-empty", "-m", "local"]);
+    let subs = git_ops::list_submodules(root).unwrap();
+    assert_eq!(subs[0].state, git_ops::SubmoduleState::Moved);
+    assert!(subs[0].dirty);
+    assert_eq!(subs[0].ahead, 1);
+
+    std::fs::remove_file(sub.join("notes.txt")).unwrap();
+    git(root, &["submodule", "deinit", "-q", "-f", "libs/core"]);
+    let subs = git_ops::list_submodules(root).unwrap();
+    assert_eq!(subs[0].state, git_ops::SubmoduleState::Uninitialized);
+
+    // The clone under .git/modules is still there, so this needs no fetch
+    git_ops::submodule_op(root, git_ops::SubmoduleOp::Update, Some("libs/core")).unwrap();
+    git_ops::submodule_op(root, git_ops::SubmoduleOp::Sync, None).unwrap();
+    let subs = git_ops::list_submodules(root).unwrap();
+    assert_eq!(subs[0].state, git_ops::SubmoduleState::Current);
+}
+
+#[test]
+fn test_stash_push_options() {
+    let dir = repo();
+    let root = dir.path();
+    std::fs::write(root.join("a.txt"), "a\n").unwrap();
+    std::fs::write(root.join("b.txt"), "b\n").unwrap();
+    git(root, &["add", "a.txt", "b.txt"]);
+    git(root, &["commit", "-q", "-m", "files"]);
+    std::fs::write(root.join("a.txt"), "a2\n").unwrap();
+    std::fs::write(root.join("b.txt"), "b2\n").unwrap();
+    std::fs::write(root.join("new.txt"), "new\n").unwrap();
+
+    // Only the selected file, with a message
+    let paths = vec!["a.txt".to_string()];
+    git_ops::stash_push(root, "just a", git_ops::StashOptions::default(), &paths).unwrap();
+    let stashes = git_ops::list_stashes(root, 10).unwrap();
+    assert_eq!(stashes.len(), 1);
+    assert!(
+        stashes[0].subject.ends_with("just a"),
+        "{}",
+        stashes[0].subject
+    );
+    assert_eq!(std::fs::read_to_string(root.join("a.txt")).unwrap(), "a\n");
+    assert_eq!(std::fs::read_to_string(root.join("b.txt")).unwrap(), "b2\n");
+
+    let options = git_ops::StashOptions {
+        include_untracked: true,
+        ..Default::default()
+    };
+    git_ops::stash_push(root, "", options, &[]).unwrap();
+    assert!(!root.join("new.txt").exists());
+    assert_eq!(git_ops::list_stashes(root, 10).unwrap().len(), 2);
+
+    let err = git_ops::stash_push(root, "", git_ops::StashOptions::default(), &[]).unwrap_err();
+    assert!(err.contains("No local changes"), "{}", err);
+}
+
+#[test]
+fn test_mailmap_joins_author_identities() {
+    let dir = repo();
+    let root = dir.path();
+    std::fs::write(root.join("a.txt"), "a\n").unwrap();
+    git(root, &["add", "a.txt"]);
+    git(
+        root,
+        &[
+            "commit",
+            "-q",
+            "-m",
+            "old",
+            "--author=T. Est <old@example.com>",
+        ],
+    );
+
+    let raw = git_ops::list_author_idents(root, 100, false).unwrap();
+    assert_eq!(raw, ["T. Est <old@example.com>", "Test <test@example.com>"]);
+
+    let canonical = "Test <test@example.com>";
+    assert!(git_ops::add_mailmap_entry(root, canonical, &raw[0]).unwrap());
+    assert!(!git_ops::add_mailmap_entry(root, canonical, &raw[0]).unwrap());
+    assert_eq!(
+        git_ops::list_author_idents(root, 100, true).unwrap(),
+        [canonical]
+    );
+    let history = git_ops::list_history(root, 10, None).unwrap();
+    assert!(history.iter().all(|c| c.author == "Test"));
+    let blame = git_ops::blame_file(root, "a.txt").unwrap();
+    assert_eq!(blame[0].author, "Test");
+}
+
+#[test]
+fn test_list_files() {
+    let dir = repo();
+    let root = dir.path();
+    std::fs::create_dir(root.join("src")).unwrap();
+    std::fs::write(root.join("src/lib.rs"), "").unwrap();
+    std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+    git(root, &["add", "."]);
+    git(root, &["commit", "-q", "-m", "files"]);
+    std::fs::write(root.join("notes.md"), "").unwrap();
+    std::fs::write(root.join("debug.log"), "").unwrap();
+
+    assert_eq!(
+        git_ops::list_files(root).unwrap(),
+        vec![".gitignore", "notes.md", "src/lib.rs"]
+    );
+}
+
+#[test]
+fn test_commit_templates() {
+    let dir = repo();
+    let root = dir.path();
+    assert!(git_ops::commit_templates(root).is_empty());
+
+    std::fs::write(root.join(".gitmessage"), "feat: \n").unwrap();
+    std::fs::write(root.join("template.txt"), "fix: \n").unwrap();
+    git(root, &["config", "commit.template", "template.txt"]);
+    let templates = git_ops::commit_templates(root);
+    let names: Vec<_> = templates
+        .iter()
+        .map(|(path, text)| (path.file_name().unwrap().to_owned(), text.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("template.txt".into(), "fix: \n"),
+            (".gitmessage".into(), "feat: \n")
+        ]
+    );
+
+    // The same file is listed once
+    git(root, &["config", "commit.template", ".gitmessage"]);
+    assert_eq!(git_ops::commit_templates(root).len(), 1);
+}
+
+#[test]
+fn test_ssh_signed_commits() {
+    let dir = repo();
+    let root = dir.path();
+    let key = root.join(".git/signing_key");
+    let keygen = Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+        .arg(&key)
+        .output();
+    if !keygen.is_ok_and(|out| out.status.success()) {
+        return; // no ssh-keygen here
+    }
+    let public = std::fs::read_to_string(key.with_extension("pub")).unwrap();
+    let allowed = root.join(".git/allowed_signers");
+    std::fs::write(&allowed, format!("test@example.com {}", public)).unwrap();
+
+    git(root, &["config", "user.name", "Test"]);
+    git(root, &["config", "user.email", "test@example.com"]);
+    assert_eq!(git_ops::commit_signing(root), None);
+    git(root, &["config", "commit.gpgsign", "true"]);
+    git(root, &["config", "gpg.format", "ssh"]);
+    git(
+        root,
+        &[
+            "config",
+            "gpg.ssh.allowedSignersFile",
+            allowed.to_str().unwrap(),
+        ],
+    );
+    assert_eq!(git_ops::commit_signing(root), Some("SSH"));
+
+    std::fs::write(root.join("a.txt"), "a\n").unwrap();
+    git(root, &["add", "a.txt"]);
+    git(root, &["config", "user.signingkey", "/nonexistent/key"]);
+    let overrides = git_ops::CommitOverrides::default();
+    let err = git_ops::commit_message(root, "signed", &overrides).unwrap_err();
+    assert!(git_ops::signing_failed(&err), "{}", err);
+
+    git(root, &["config", "user.signingkey", key.to_str().unwrap()]);
+    git_ops::commit_message(root, "signed", &overrides).unwrap();
+    let history = git_ops::list_history(root, 10, None).unwrap();
+    assert_eq!(history[0].signature, git_ops::Signature::None);
+    let signature = |hash: &str| git_ops::commit_signature(root, hash);
+    assert_eq!(signature(&history[0].hash), git_ops::Signature::Good);
+    assert_eq!(signature(&history[1].hash), git_ops::Signature::None);
+
+    let header = git_ops::show_commit_header(root, &history[0].hash).unwrap();
+    assert!(header.contains("Signature:  good"), "{}", header);
+    assert!(
+        header.contains("Signer:     test@example.com"),
+        "{}",
+        header
+    );
+    assert_eq!(git_ops::signature_details(root, &history[1].hash), None);
+}
+
+#[test]
+fn test_wip_commit_and_undo() {
+    let dir = repo();
+    let root = dir.path();
+    // The WIP commit is made without the helper's identity variables
+    git(root, &["config", "user.name", "Test"]);
+    git(root, &["config", "user.email", "test@example.com"]);
+    assert!(git_ops::wip_commit(root).is_err());
+    assert!(git_ops::undo_wip(root).is_err());
+
+    std::fs::write(root.join("new.txt"), "new\n").unwrap();
+    git_ops::wip_commit(root).unwrap();
+    let subject = Command::new("git")
+        .args(["log", "-1", "--format=%s"])
+        .current_dir(root)
+        .output()
+        .unwrap();
+    let subject = String::from_utf8_lossy(&subject.stdout);
+    assert_eq!(subject.trim(), git_ops::WIP_MESSAGE);
+
+    git_ops::undo_wip(root).unwrap();
+    let status = Command::new("git")
+        .args(["status", "--porcelain"])
+        .current_dir(root)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&status.stdout), "A  new.txt\n");
+    assert!(git_ops::undo_wip(root).is_err());
+
+    git(root, &["commit", "-q", "-m", "WIPE the cache"]);
+    let err = git_ops::undo_wip(root).unwrap_err();
+    assert!(err.contains("not a WIP commit"), "{}", err);
+}
+
+#[test]
+fn test_autostash_around_checkout() {
+    let dir = repo();
+    let root = dir.path();
+    std::fs::write(root.join("a.txt"), "a\n").unwrap();
+    git(root, &["add", "a.txt"]);
+    git(root, &["commit", "-q", "-m", "add a"]);
+    git(root, &["branch", "other", "HEAD~1"]);
+    std::fs::write(root.join("a.txt"), "mine\n").unwrap();
+
+    let err = git_ops::checkout_branch(root, "other").unwrap_err();
+    assert!(git_ops::blocked_by_local_changes(&err));
+
+    // other has no a.txt, so the changes come back as a conflict
+    let err =
+        git_ops::with_autostash(root, || git_ops::checkout_branch(root, "other")).unwrap_err();
+    assert!(err.contains("stash@{0}"), "{}", err);
+    let list = Command::new("git")
+        .args(["stash", "list", "--format=%s"])
+        .current_dir(root)
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&list.stdout).contains(git_ops::AUTOSTASH_MESSAGE));
+}
+
+#[test]
+fn test_autostash_pops_its_own_stash() {
+    let dir = repo();
+    let root = dir.path();
+    git(root, &["config", "user.name", "Test"]);
+    git(root, &["config", "user.email", "test@example.com"]);
+    let stashes = || {
+        let out = Command::new("git")
+            .args(["stash", "list", "--format=%s"])
+            .current_dir(root)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    };
+    std::fs::write(root.join("a.txt"), "a\n").unwrap();
+    std::fs::write(root.join("b.txt"), "b\n").unwrap();
+    git(root, &["add", "a.txt", "b.txt"]);
+    git(root, &["commit", "-q", "-m", "add a and b"]);
+    std::fs::write(root.join("a.txt"), "mine\n").unwrap();
+
+    // The operation stashes something of its own on top
+    git_ops::with_autostash(root, || {
+        std::fs::write(root.join("b.txt"), "theirs\n").unwrap();
+        git(root, &["stash", "push", "-q", "-m", "theirs"]);
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(root.join("a.txt")).unwrap(),
+        "mine\n"
+    );
+    assert_eq!(std::fs::read_to_string(root.join("b.txt")).unwrap(), "b\n");
+    assert_eq!(stashes(), "On main: theirs");
+
+    // Nothing to stash just runs the operation
+    git(root, &["checkout", "-q", "--", "a.txt"]);
+    let mut ran = false;
+    git_ops::with_autostash(root, || {
+        ran = true;
+        Ok(())
+    })
+    .unwrap();
+    assert!(ran);
+    assert_eq!(stashes(), "On main: theirs");
+}
+
+#[test]
+fn test_stage_a_selection_with_dashed_lines() {
+    let dir = repo();
+    let root = dir.path();
+    std::fs::write(root.join("q.sql"), "select 1;\n-- old note\nselect 2;\n").unwrap();
+    git(root, &["add", "q.sql"]);
+    git(root, &["commit", "-q", "-m", "add q"]);
+    std::fs::write(root.join("q.sql"), "select 1;\n++ new note\nselect 3;\n").unwrap();
+
+    let diff: Vec<String> = git_ops::diff_path(root, "q.sql", false, None)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    let removed = diff.iter().position(|l| l == "--- old note").unwrap();
+    let patch = git::selection_patch(&diff, removed, removed);
+
+    let check = git_stdin(root, &["apply", "--check", "--cached", "-"], &patch);
+    assert!(
+        check.status.success(),
+        "{}\n{}",
+        patch,
+        String::from_utf8_lossy(&check.stderr)
+    );
+    assert!(
+        git_stdin(root, &["apply", "--cached", "-"], &patch)
+            .status
+            .success()
+    );
+    let staged = Command::new("git")
+        .args(["show", ":q.sql"])
+        .current_dir(root)
+        .output()
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&staged.stdout),
+        "select 1;\nselect 2;\n"
+    );
+}
+
+#[test]
+fn test_snapshot_keeps_untracked_files() {
+    let dir = repo();
+    let root = dir.path();
+    git(root, &["config", "user.name", "Test"]);
+    git(root, &["config", "user.email", "test@example.com"]);
+    std::fs::write(root.join("a.txt"), "a\n").unwrap();
+    git(root, &["add", "a.txt"]);
+    git(root, &["commit", "-q", "-m", "add a"]);
+    assert!(git_ops::create_snapshot(root, "clean").is_err());
+
+    std::fs::write(root.join("a.txt"), "changed\n").unwrap();
+    std::fs::write(root.join("new.txt"), "new\n").unwrap();
+    git_ops::create_snapshot(root, "both").unwrap();
+    std::fs::remove_file(root.join("new.txt")).unwrap();
+    git(root, &["checkout", "--", "a.txt"]);
+
+    git_ops::restore_snapshot(root, "both").unwrap();
+    let read = |name: &str| std::fs::read_to_string(root.join(name)).unwrap();
+    assert_eq!(read("a.txt"), "changed\n");
+    assert_eq!(read("new.txt"), "new\n");
+
+    // Untracked files alone are a change worth keeping
+    git(root, &["checkout", "--", "a.txt"]);
+    git_ops::create_snapshot(root, "untracked").unwrap();
+    std::fs::remove_file(root.join("new.txt")).unwrap();
+    git_ops::restore_snapshot(root, "untracked").unwrap();
+    assert_eq!(read("new.txt"), "new\n");
+    assert_eq!(read("a.txt"), "a\n");
+}
+
+#[test]
+fn test_repo_state_finds_locks_and_operations() {
+    let dir = repo();
+    let root = dir.path();
+    assert_eq!(git_ops::repo_state(root), git_ops::RepoState::default());
+
+    std::fs::write(root.join(".git/index.lock"), "").unwrap();
+    std::fs::write(root.join(".git/MERGE_HEAD"), "").unwrap();
+    let state = git_ops::repo_state(root);
+    assert_eq!(state.operation, Some(git_ops::GitOperation::Merge));
+    let names: Vec<_> = state.locks.iter().filter_map(|p| p.file_name()).collect();
+    assert_eq!(names, ["index.lock"]);
+
+    std::fs::create_dir(root.join(".git/rebase-merge")).unwrap();
+    let state = git_ops::repo_state(root);
+    assert_eq!(state.operation, Some(git_ops::GitOperation::Rebase));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_git_process_running_in_this_repo_only() {
+    let dir = repo();
+    let root = dir.path();
+    let other = TempDir::new().unwrap();
+    assert!(!git_ops::git_process_running(root));
+
+    // Waits for stdin until it is killed
+    let mut child = Command::new("git")
+        .args(["hash-object", "--stdin"])
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    let running = (0..50).any(|_| {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        git_ops::git_process_running(root)
+    });
+    let elsewhere = git_ops::git_process_running(other.path());
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert!(running);
+    assert!(!elsewhere);
+}
+
+#[test]
+fn test_stash_patch_takes_one_hunk() {
+    let dir = repo();
+    let root = dir.path();
+    let lines: Vec<String> = (1..=12).map(|n| format!("line {}\n", n)).collect();
+    std::fs::write(root.join("a.txt"), lines.concat()).unwrap();
+    git(root, &["add", "a.txt"]);
+    git(root, &["commit", "-q", "-m", "lines"]);
+    let changed = lines
+        .concat()
+        .replace("line 1\n", "one\n")
+        .replace("line 12\n", "twelve\n");
+    std::fs::write(root.join("a.txt"), &changed).unwrap();
+
+    // The file header and the first of the two hunks
+    let diff = git_out(root, &["diff", "-U1"]);
+    let second = diff.match_indices("\n@@").nth(1).unwrap().0;
+    git_ops::stash_patch(root, &diff[..=second], false, "first hunk").unwrap();
+    let stashes = git_ops::list_stashes(root, 10).unwrap();
+    assert_eq!(stashes.len(), 1);
+    assert!(
+        stashes[0].subject.ends_with("first hunk"),
+        "{}",
+        stashes[0].subject
+    );
+    let read = || std::fs::read_to_string(root.join("a.txt")).unwrap();
+    assert!(read().starts_with("line 1\n") && read().ends_with("twelve\n"));
+    let stashed = git_out(root, &["stash", "show", "-p", "stash@{0}"]);
+    assert!(
+        stashed.contains("+one") && !stashed.contains("+twelve"),
+        "{}",
+        stashed
+    );
+
+    // A staged hunk leaves the index too
+    git(root, &["add", "a.txt"]);
+    let diff = git_out(root, &["diff", "--cached", "-U1"]);
+    git_ops::stash_patch(root, &diff, true, "staged").unwrap();
+    assert_eq!(git_out(root, &["diff", "--cached"]), "");
+    assert_eq!(git_ops::list_stashes(root, 10).unwrap().len(), 2);
+}
+
+#[test]
+fn test_stash_patch_puts_the_index_back() {
+    let dir = repo();
+    let root = dir.path();
+    let big: String = (0..50_000).map(|n| format!("line {}\n", n)).collect();
+    std::fs::write(root.join("big.txt"), &big).unwrap();
+    std::fs::write(root.join("b.txt"), "b\n").unwrap();
+    git(root, &["add", "big.txt", "b.txt"]);
+    git(root, &["commit", "-q", "-m", "files"]);
+
+    std::fs::write(root.join("b.txt"), "staged\n").unwrap();
+    git(root, &["add", "b.txt"]);
+    let staged = git_out(root, &["diff", "--cached"]);
+
+    // A patch that does not apply fails without touching the staged change
+    let bad = "--- a/missing.txt\n+++ b/missing.txt\n@@ -1 +1 @@\n-x\n+y\n";
+    assert!(git_ops::stash_patch(root, bad, false, "bad").is_err());
+    assert_eq!(git_out(root, &["diff", "--cached"]), staged);
+    assert!(git_ops::list_stashes(root, 10).unwrap().is_empty());
+
+    // Megabytes of patch go through stdin while git's output is drained
+    std::fs::write(root.join("big.txt"), big.replace("line", "row")).unwrap();
+    let diff = git_out(root, &["diff", "--", "big.txt"]);
+    assert!(diff.len() > 1_000_000);
+    git_ops::stash_patch(root, &diff, false, "big").unwrap();
+    assert_eq!(git_out(root, &["diff", "--cached"]), staged);
+    assert_eq!(git_ops::list_stashes(root, 10).unwrap().len(), 1);
+}
+
+#[test]
+fn test_cherry_pick_and_abort() {
+    let dir = repo();
+    let root = dir.path();
+    // Cherry-pick commits without the helper's identity variables
+    git(root, &["config", "user.name", "Test"]);
+    git(root, &["config", "user.email", "test@example.com"]);
+    std::fs::write(root.join("a.txt"), "a\n").unwrap();
+    git(root, &["add", "a.txt"]);
+    git(root, &["commit", "-q", "-m", "a"]);
+    git(root, &["checkout", "-q", "-b", "topic"]);
+    std::fs::write(root.join("b.txt"), "b\n").unwrap();
+    git(root, &["add", "b.txt"]);
+    git(root, &["commit", "-q", "-m", "add b"]);
+    std::fs::write(root.join("a.txt"), "topic\n").unwrap();
+    git(root, &["commit", "-q", "-am", "a on topic"]);
+    let picks: Vec<String> = git_out(root, &["rev-list", "--reverse", "main..topic"])
+        .lines()
+        .map(String::from)
+        .collect();
+    git(root, &["checkout", "-q", "main"]);
+    std::fs::write(root.join("a.txt"), "main\n").unwrap();
+    git(root, &["commit", "-q", "-am", "a on main"]);
+    assert!(!git_ops::cherry_pick_in_progress(root).unwrap());
+
+    git_ops::cherry_pick(root, &picks[..1]).unwrap();
+    assert_eq!(git_out(root, &["log", "-1", "--format=%s"]), "add b\n");
+    assert!(root.join("b.txt").exists());
+
+    let err = git_ops::cherry_pick(root, &picks[1..]).unwrap_err();
+    assert!(!err.is_empty());
+    assert!(git_ops::cherry_pick_in_progress(root).unwrap());
+    git_ops::cherry_pick_step(root, "--abort").unwrap();
+    assert!(!git_ops::cherry_pick_in_progress(root).unwrap());
+    assert_eq!(
+        std::fs::read_to_string(root.join("a.txt")).unwrap(),
+        "main\n"
+    );
+}
+
+#[test]
+fn test_revert_commit_and_abort() {
+    let dir = repo();
+    let root = dir.path();
+    // Reverts commit without the helper's identity variables
+    git(root, &["config", "user.name", "Test"]);
+    git(root, &["config", "user.email", "test@example.com"]);
+    std::fs::write(root.join("a.txt"), "a\n").unwrap();
+    git(root, &["add", "a.txt"]);
+    git(root, &["commit", "-q", "-m", "add a"]);
+    let added = git_out(root, &["rev-parse", "HEAD"]).trim().to_string();
+    std::fs::write(root.join("b.txt"), "b\n").unwrap();
+    git(root, &["add", "b.txt"]);
+    git(root, &["commit", "-q", "-m", "add b"]);
+    let b = git_out(root, &["rev-parse", "HEAD"]).trim().to_string();
+
+    git_ops::revert_commit(root, &b, true).unwrap();
+    assert_eq!(git_out(root, &["status", "--porcelain"]), "D  b.txt\n");
+    assert_eq!(git_out(root, &["log", "-1", "--format=%s"]), "add b\n");
+    git(root, &["reset", "-q", "--hard"]);
+
+    git_ops::revert_commit(root, &b, false).unwrap();
+    assert_eq!(
+        git_out(root, &["log", "-1", "--format=%s"]),
+        "Revert \"add b\"\n"
+    );
+    assert!(!root.join("b.txt").exists());
+
+    // Reverting a line that changed since stops on the conflict
+    std::fs::write(root.join("a.txt"), "changed\n").unwrap();
+    git(root, &["commit", "-q", "-am", "change a"]);
+    assert!(!git_ops::revert_in_progress(root).unwrap());
+    assert!(git_ops::revert_commit(root, &added, false).is_err());
+    assert!(git_ops::revert_in_progress(root).unwrap());
+    git_ops::revert_step(root, "--abort").unwrap();
+    assert!(!git_ops::revert_in_progress(root).unwrap());
+    assert_eq!(
+        std::fs::read_to_string(root.join("a.txt")).unwrap(),
+        "changed\n"
+    );
+}
+
+#[test]
+fn test_merge_commit_against_each_parent() {
+    let dir = repo();
+    let root = dir.path();
+    std::fs::write(root.join("a.txt"), "a\n").unwrap();
+    git(root, &["add", "a.txt"]);
+    git(root, &["commit", "-q", "-m", "add a"]);
+    git(root, &["checkout", "-q", "-b", "topic"]);
+    std::fs::write(root.join("a.txt"), "topic\n").unwrap();
+    std::fs::write(root.join("t.txt"), "t\n").unwrap();
+    git(root, &["add", "."]);
+    git(root, &["commit", "-q", "-m", "topic work"]);
+    git(root, &["checkout", "-q", "main"]);
+    std::fs::write(root.join("a.txt"), "main\n").unwrap();
+    std::fs::write(root.join("m.txt"), "m\n").unwrap();
+    git(root, &["add", "."]);
+    git(root, &["commit", "-q", "-m", "main work"]);
+    // Resolve the conflict with a line from neither side
+    let merge = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(["merge", "-q", "topic"])
+        .current_dir(root)
+        .output()
+        .unwrap();
+    assert!(!merge.status.success());
+    std::fs::write(root.join("a.txt"), "merged\n").unwrap();
+    git(root, &["commit", "-q", "-am", "merge topic"]);
+    let merge = git_out(root, &["rev-parse", "HEAD"]).trim().to_string();
+
+    let parents = git_ops::commit_parents(root, &merge).unwrap();
+    assert_eq!(parents.len(), 2);
+    let paths = |base| -> Vec<String> {
+        let files = git_ops::list_commit_files(root, &merge, base).unwrap();
+        files.into_iter().map(|f| f.path).collect()
+    };
+    assert_eq!(paths(git_ops::DiffBase::Parent(0)), ["a.txt", "t.txt"]);
+    assert_eq!(paths(git_ops::DiffBase::Parent(1)), ["a.txt", "m.txt"]);
+
+    let first = git_ops::show_commit(root, &merge, git_ops::DiffBase::Parent(0)).unwrap();
+    assert!(
+        first.contains("merge topic") && first.contains("+t"),
+        "{}",
+        first
+    );
+    let combined = git_ops::show_commit(root, &merge, git_ops::DiffBase::Combined).unwrap();
+    assert!(combined.contains("diff --cc a.txt"), "{}", combined);
+
+    let diff = |base| git_ops::show_commit_file_diff(root, &merge, "a.txt", None, base).unwrap();
+    assert!(diff(git_ops::DiffBase::Parent(1)).contains("-topic"));
+    assert!(diff(git_ops::DiffBase::Combined).contains("++merged"));
+}
+
+#[test]
+fn test_history_listing_and_search() {
+    let dir = repo();
+    let root = dir.path();
+    git(
+        root,
+        &["commit", "-q", "--allow-empty", "-m", "Fix the parser"],
+    );
+    git(root, &["checkout", "-q", "-b", "topic"]);
+    git(
+        root,
+        &["commit", "-q", "--allow-empty", "-m", "fix the lexer"],
+    );
+    git(root, &["checkout", "-q", "main"]);
+
+    let subjects = |entries: Vec<git_ops::CommitEntry>| -> Vec<String> {
+        entries.into_iter().map(|e| e.subject).collect()
+    };
+    let history = git_ops::list_history(root, 10, None).unwrap();
+    assert_eq!(subjects(history), ["Fix the parser", "first"]);
+    let topic = git_ops::list_history(root, 1, Some("topic")).unwrap();
+    assert_eq!(subjects(topic), ["fix the lexer"]);
+
+    let terms = vec!["FIX".to_string()];
+    let found = git_ops::search_history(root, Some("topic"), &[], &terms, 10).unwrap();
+    assert_eq!(subjects(found), ["fix the lexer", "Fix the parser"]);
+    let authors = vec!["nobody".to_string()];
+    let found = git_ops::search_history(root, None, &authors, &terms, 10).unwrap();
+    assert!(found.is_empty());
+}